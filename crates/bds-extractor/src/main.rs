@@ -2,15 +2,19 @@
 //!
 //! Connects to a Bedrock Dedicated Server and extracts game data
 //! (items, blocks, creative content, etc.) for code generation.
+//!
+//! With `--recipes`, also writes the vanilla recipe dataset loaded by
+//! unastar's recipe registry.
 
 mod output;
+mod recipes;
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use jolyne::BedrockStream;
 use jolyne::stream::client::ClientHandshakeConfig;
+use jolyne::{BedrockStream, GameData};
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tracing::info;
 
@@ -26,6 +30,12 @@ struct Args {
     #[arg(short, long, default_value = "bds-data.json")]
     output: PathBuf,
 
+    /// Recipe dataset output path (e.g. `crates/unastar/src/data/recipes.json`).
+    ///
+    /// The item tag and fuel tables of an existing file at this path are kept.
+    #[arg(short, long)]
+    recipes: Option<PathBuf>,
+
     /// Connection timeout in seconds
     #[arg(short, long, default_value = "30")]
     timeout: u64,
@@ -63,7 +73,12 @@ async fn main() -> Result<()> {
     .await;
 
     match connect_result {
-        Ok(Ok(data)) => {
+        Ok(Ok(game_data)) => {
+            if let Some(path) = &args.recipes {
+                write_recipes(path, &game_data)?;
+            }
+            let data = output::ExtractedData::from_game_data(game_data);
+
             info!("Extraction complete!");
             info!("  Items: {}", data.items.registry.len());
             info!("  Blocks: {}", data.blocks.properties.len());
@@ -92,7 +107,7 @@ async fn main() -> Result<()> {
     }
 }
 
-async fn extract_data(addr: SocketAddr, player_name: &str) -> Result<output::ExtractedData> {
+async fn extract_data(addr: SocketAddr, player_name: &str) -> Result<GameData> {
     // Connect to BDS
     let handshake_stream = BedrockStream::connect(addr)
         .await
@@ -111,11 +126,42 @@ async fn extract_data(addr: SocketAddr, player_name: &str) -> Result<output::Ext
 
     info!("Handshake complete! Extracting data...");
 
-    // Convert to our output format
-    let extracted = output::ExtractedData::from_game_data(game_data);
-
     // Note: We drop the play_stream here which disconnects
     // In the future we could stay connected to receive more packets if needed
 
-    Ok(extracted)
+    Ok(game_data)
+}
+
+/// Write the recipe dataset from the captured `CraftingData` packet.
+fn write_recipes(path: &Path, game_data: &GameData) -> Result<()> {
+    let crafting = game_data
+        .crafting_data
+        .as_ref()
+        .context("Server sent no CraftingData packet")?;
+
+    // Tags and fuels aren't sent by the server; keep the ones already in the dataset
+    let kept = match std::fs::read_to_string(path) {
+        Ok(existing) => {
+            serde_json::from_str(&existing).context("Failed to parse existing recipes")?
+        }
+        Err(_) => recipes::HandMaintained::default(),
+    };
+
+    let data = recipes::RecipeData::from_crafting_data(
+        crafting,
+        &game_data.item_registry.itemstates,
+        kept,
+    );
+    info!(
+        "  Recipes: {} shaped, {} shapeless, {} furnace, {} stonecutter",
+        data.shaped.len(),
+        data.shapeless.len(),
+        data.furnace.len(),
+        data.stonecutter.len()
+    );
+
+    let json = serde_json::to_string_pretty(&data).context("Failed to serialize recipes")?;
+    std::fs::write(path, json).context("Failed to write recipes file")?;
+    info!("Recipes written to: {}", path.display());
+    Ok(())
 }
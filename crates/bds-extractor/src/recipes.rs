@@ -0,0 +1,281 @@
+//! Recipe dataset conversion.
+//!
+//! Converts the `CraftingData` packet sent by BDS into the recipe dataset
+//! format read by unastar's `RecipeRegistry` (`crates/unastar/src/data/recipes.json`):
+//! - Crafting table shaped/shapeless recipes
+//! - Stonecutter recipes (shapeless recipes in the `stonecutter` block)
+//! - Furnace recipes, grouped by input and output across furnace blocks
+//!
+//! Item tags and furnace fuels are not part of the packet; the `tags` and
+//! `fuel` tables of an existing dataset are carried over.
+
+use std::collections::{BTreeMap, HashMap};
+
+use jolyne::valentine::CraftingDataPacket;
+use jolyne::valentine::types::{
+    ItemLegacy, ItemstatesItem, RecipeIngredient, RecipeIngredientContent, RecipesItemRecipe,
+};
+use serde::{Deserialize, Serialize};
+
+/// Item metadata value matching any damage/data value.
+const ANY_METADATA: i16 = 32767;
+
+/// Pattern keys, assigned to distinct ingredients in order of appearance.
+const PATTERN_KEYS: &str = "ABCDEFGHI";
+
+/// Root of the recipe dataset.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecipeData {
+    pub tags: BTreeMap<String, Vec<String>>,
+    pub shaped: Vec<ShapedDef>,
+    pub shapeless: Vec<ShapelessDef>,
+    pub furnace: Vec<FurnaceDef>,
+    pub stonecutter: Vec<StonecutterDef>,
+    pub fuel: Vec<FuelDef>,
+}
+
+/// Tables of the dataset that are maintained by hand.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HandMaintained {
+    #[serde(default)]
+    pub tags: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub fuel: Vec<FuelDef>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShapedDef {
+    pub id: String,
+    pub block: String,
+    pub pattern: Vec<String>,
+    pub key: BTreeMap<char, String>,
+    pub output: OutputDef,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShapelessDef {
+    pub id: String,
+    pub block: String,
+    pub input: Vec<String>,
+    pub output: OutputDef,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FurnaceDef {
+    pub input: String,
+    pub output: OutputDef,
+    pub blocks: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StonecutterDef {
+    pub id: String,
+    pub input: String,
+    pub output: OutputDef,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuelDef {
+    pub input: String,
+    pub burn_time: i16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct OutputDef {
+    pub item: String,
+    pub count: u8,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub data: i16,
+}
+
+fn is_zero(data: &i16) -> bool {
+    *data == 0
+}
+
+impl RecipeData {
+    /// Build the dataset from the `CraftingData` packet.
+    ///
+    /// `items` is the item registry used to resolve network IDs. Recipes
+    /// with unresolvable or Molang ingredients, multiple outputs, or blocks
+    /// unastar has no window for are skipped.
+    pub fn from_crafting_data(
+        crafting: &CraftingDataPacket,
+        items: &[ItemstatesItem],
+        kept: HandMaintained,
+    ) -> Self {
+        let names: HashMap<i32, &str> = items
+            .iter()
+            .map(|item| (item.runtime_id as i32, item.name.as_str()))
+            .collect();
+        let item_name = |network_id: i32| names.get(&network_id).map(|name| name.to_string());
+        let output = |item: &ItemLegacy| -> Option<OutputDef> {
+            let content = item.content.as_ref();
+            Some(OutputDef {
+                item: item_name(item.network_id)?,
+                count: content.map_or(1, |c| c.count as u8),
+                data: content.map_or(0, |c| c.metadata as i16),
+            })
+        };
+        let single_output = |outputs: &[ItemLegacy]| match outputs {
+            [item] => output(item),
+            _ => None,
+        };
+
+        let mut data = Self {
+            tags: kept.tags,
+            shaped: Vec::new(),
+            shapeless: Vec::new(),
+            furnace: Vec::new(),
+            stonecutter: Vec::new(),
+            fuel: kept.fuel,
+        };
+        let mut furnace: BTreeMap<(String, OutputDef), Vec<String>> = BTreeMap::new();
+
+        for recipe in crafting.recipes.iter().filter_map(|r| r.recipe.as_ref()) {
+            match recipe {
+                RecipesItemRecipe::Shaped(shaped) if shaped.block == "crafting_table" => {
+                    let Some(output) = single_output(&shaped.output) else {
+                        continue;
+                    };
+                    let Some((pattern, key)) = pattern(&shaped.input, &item_name) else {
+                        continue;
+                    };
+                    data.shaped.push(ShapedDef {
+                        id: shaped.recipe_id.clone(),
+                        block: shaped.block.clone(),
+                        pattern,
+                        key,
+                        output,
+                    });
+                }
+                RecipesItemRecipe::Shapeless(shapeless)
+                    if matches!(shapeless.block.as_str(), "crafting_table" | "stonecutter") =>
+                {
+                    let Some(output) = single_output(&shapeless.output) else {
+                        continue;
+                    };
+                    let input: Option<Vec<String>> = shapeless
+                        .input
+                        .iter()
+                        .map(|i| Some(vec![ingredient(i, &item_name)?; i.count.max(1) as usize]))
+                        .collect::<Option<Vec<_>>>()
+                        .map(|input| input.into_iter().flatten().collect());
+                    let Some(input) = input else {
+                        continue;
+                    };
+
+                    if shapeless.block == "stonecutter" {
+                        if let [input] = input.as_slice() {
+                            data.stonecutter.push(StonecutterDef {
+                                id: shapeless.recipe_id.clone(),
+                                input: input.clone(),
+                                output,
+                            });
+                        }
+                    } else {
+                        data.shapeless.push(ShapelessDef {
+                            id: shapeless.recipe_id.clone(),
+                            block: shapeless.block.clone(),
+                            input,
+                            output,
+                        });
+                    }
+                }
+                RecipesItemRecipe::Furnace(recipe) => {
+                    if let (Some(input), Some(output)) =
+                        (item_name(recipe.input_id), output(&recipe.output))
+                    {
+                        furnace
+                            .entry((input, output))
+                            .or_default()
+                            .push(recipe.block.clone());
+                    }
+                }
+                RecipesItemRecipe::FurnaceWithMetadata(recipe)
+                    if recipe.input_meta == 0 || recipe.input_meta == ANY_METADATA as i32 =>
+                {
+                    if let (Some(input), Some(output)) =
+                        (item_name(recipe.input_id), output(&recipe.output))
+                    {
+                        furnace
+                            .entry((input, output))
+                            .or_default()
+                            .push(recipe.block.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        data.furnace = furnace
+            .into_iter()
+            .map(|((input, output), mut blocks)| {
+                blocks.sort();
+                blocks.dedup();
+                FurnaceDef {
+                    input,
+                    output,
+                    blocks,
+                }
+            })
+            .collect();
+        data
+    }
+}
+
+/// Dataset form of an ingredient: an item ID or `#tag`.
+///
+/// Returns `None` for empty or Molang ingredients.
+fn ingredient(
+    ingredient: &RecipeIngredient,
+    item_name: &impl Fn(i32) -> Option<String>,
+) -> Option<String> {
+    match ingredient.content.as_ref()? {
+        RecipeIngredientContent::IntIdMeta(id) if id.network_id != 0 => {
+            item_name(id.network_id as i32)
+        }
+        RecipeIngredientContent::ItemTag(tag) => Some(format!("#{}", tag.tag)),
+        RecipeIngredientContent::StringIdMeta(id) => Some(id.name.clone()),
+        RecipeIngredientContent::ComplexAlias(alias) => Some(alias.name.clone()),
+        _ => None,
+    }
+}
+
+/// Whether a shaped recipe cell is left empty.
+fn is_empty(cell: &RecipeIngredient) -> bool {
+    match &cell.content {
+        None => true,
+        Some(RecipeIngredientContent::IntIdMeta(id)) => id.network_id == 0,
+        Some(_) => false,
+    }
+}
+
+/// Pattern rows and key table of a shaped recipe's rows of ingredients.
+fn pattern(
+    rows: &[Vec<RecipeIngredient>],
+    item_name: &impl Fn(i32) -> Option<String>,
+) -> Option<(Vec<String>, BTreeMap<char, String>)> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut pattern = Vec::with_capacity(rows.len());
+    for row in rows {
+        let mut line = String::with_capacity(row.len());
+        for cell in row {
+            if is_empty(cell) {
+                line.push(' ');
+                continue;
+            }
+            let name = ingredient(cell, item_name)?;
+            let index = match keys.iter().position(|key| *key == name) {
+                Some(index) => index,
+                None => {
+                    keys.push(name);
+                    keys.len() - 1
+                }
+            };
+            line.push(PATTERN_KEYS.chars().nth(index)?);
+        }
+        pattern.push(line);
+    }
+    let key = PATTERN_KEYS.chars().zip(keys).collect();
+    Some((pattern, key))
+}
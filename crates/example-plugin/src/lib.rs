@@ -1,8 +1,6 @@
 //! Example native Rust plugin for Unastar.
 //!
 //! This demonstrates the new native plugin system with direct ECS access.
-use abi_stable::sabi_trait::TD_Opaque;
-use abi_stable::std_types::{RBox, RStr};
 use tracing::info;
 use unastar_api::{event_handler, native::*, native_plugin, Vec3};
 
@@ -28,6 +26,14 @@ impl Plugin for ExamplePlugin {
             "[Example Uncompiled Plugin] Loaded! World has {} entities",
             ctx.world.entity_count()
         );
+
+        ctx.register_command(
+            CommandSpec::new("greet", "Greet someone")
+                .alias("hi")
+                .overload(vec![
+                    CommandParam::new("name", CommandParamKind::String).optional()
+                ]),
+        );
     }
 
     fn tick(&mut self, ctx: &mut NativeGameContext) {
@@ -147,12 +153,12 @@ impl Plugin for ExamplePlugin {
     fn on_player_quit(&mut self, ctx: &mut NativeGameContext, entity: PluginEntity) {
         info!("[Example Plugin] Player quit! Entity: {:?}", entity);
     }
-}
 
-/// Export a function to create the plugin (called by server)
-#[no_mangle]
-pub extern "C" fn _create_plugin() -> RawPlugin_TO<RBox<()>> {
-    let plugin = ExamplePlugin::new();
-    let bridge = PluginBridge(plugin);
-    RawPlugin_TO::from_value(bridge, TD_Opaque)
+    fn on_command(&mut self, ctx: &mut NativeGameContext, call: &mut CommandCall) {
+        let name = call.arg(0).unwrap_or("world").to_string();
+        call.message(format!("Hello, {name}!"));
+    }
 }
+
+// Exports `_create_plugin` and `_plugin_abi_version` for the server
+unastar_api::export_plugin!(ExamplePlugin::new());
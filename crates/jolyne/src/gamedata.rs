@@ -4,8 +4,8 @@
 //! packets received from the server during the login/spawn sequence.
 
 use crate::valentine::{
-    AvailableEntityIdentifiersPacket, BiomeDefinitionListPacket, CreativeContentPacket,
-    ItemRegistryPacket, StartGamePacket,
+    AvailableEntityIdentifiersPacket, BiomeDefinitionListPacket, CraftingDataPacket,
+    CreativeContentPacket, ItemRegistryPacket, StartGamePacket,
 };
 
 /// Game data captured during the login sequence.
@@ -15,6 +15,7 @@ use crate::valentine::{
 /// - Block runtime ID mappings (`block_properties` in `start_game`)
 /// - Item registry definitions
 /// - Creative inventory content
+/// - Crafting, smelting and stonecutter recipes
 /// - Biome definitions
 /// - Entity identifiers
 #[derive(Debug, Clone)]
@@ -29,4 +30,6 @@ pub struct GameData {
     pub entity_identifiers: Option<AvailableEntityIdentifiersPacket>,
     /// Creative content for the creative inventory (if received).
     pub creative_content: Option<CreativeContentPacket>,
+    /// Recipes (if received).
+    pub crafting_data: Option<CraftingDataPacket>,
 }
@@ -21,10 +21,10 @@ use crate::stream::{
 };
 use crate::valentine::{
     AvailableEntityIdentifiersPacket, BiomeDefinitionListPacket, ClientToServerHandshakePacket,
-    CreativeContentPacket, ItemRegistryPacket, LoginPacket, PlayStatusPacketStatus,
-    RequestChunkRadiusPacket, RequestNetworkSettingsPacket, ResourcePackClientResponsePacket,
-    ResourcePackClientResponsePacketResponseStatus, ServerboundLoadingScreenPacket,
-    SetLocalPlayerAsInitializedPacket, StartGamePacket,
+    CraftingDataPacket, CreativeContentPacket, ItemRegistryPacket, LoginPacket,
+    PlayStatusPacketStatus, RequestChunkRadiusPacket, RequestNetworkSettingsPacket,
+    ResourcePackClientResponsePacket, ResourcePackClientResponsePacketResponseStatus,
+    ServerboundLoadingScreenPacket, SetLocalPlayerAsInitializedPacket, StartGamePacket,
 };
 use crate::valentine::{McpePacket, McpePacketData};
 
@@ -416,6 +416,7 @@ impl<T: Transport> BedrockStream<StartGame, Client, T> {
         let mut biome_definitions: Option<BiomeDefinitionListPacket> = None;
         let mut entity_identifiers: Option<AvailableEntityIdentifiersPacket> = None;
         let mut creative_content: Option<CreativeContentPacket> = None;
+        let mut crafting_data: Option<CraftingDataPacket> = None;
 
         tracing::debug!("Waiting for StartGame sequence...");
 
@@ -456,6 +457,10 @@ impl<T: Transport> BedrockStream<StartGame, Client, T> {
                     );
                     creative_content = Some(content);
                 }
+                McpePacketData::PacketCraftingData(crafting) => {
+                    tracing::debug!(recipes = %crafting.recipes.len(), "CraftingData received");
+                    crafting_data = Some(*crafting);
+                }
                 McpePacketData::PacketPlayStatus(status) => {
                     if status.status == PlayStatusPacketStatus::PlayerSpawn {
                         tracing::debug!("PlayerSpawn received");
@@ -500,6 +505,7 @@ impl<T: Transport> BedrockStream<StartGame, Client, T> {
             biome_definitions,
             entity_identifiers,
             creative_content,
+            crafting_data,
         };
 
         tracing::debug!("Game initialization complete, entering Play state");
@@ -276,6 +276,16 @@ pub fn native_plugin(_attr: TokenStream, item: TokenStream) -> TokenStream {
                                     self.#method_ident(ctx, entity);
                                 }
                             },
+                            "Command" => quote! {
+                                fn on_command(&mut self, ctx: &mut unastar_api::native::NativeGameContext, call: &mut unastar_api::native::CommandCall) {
+                                    self.#method_ident(ctx, call);
+                                }
+                            },
+                            "Damage" => quote! {
+                                fn on_entity_damage(&mut self, ctx: &mut unastar_api::native::NativeGameContext, entity: unastar_api::native::PluginEntity, damage: &mut unastar_api::native::EntityDamage) -> bool {
+                                    self.#method_ident(ctx, entity, damage)
+                                }
+                            },
                             _ => quote! {},
                         };
                         event_impls.push(impl_code);
@@ -337,9 +347,17 @@ fn classify_native_handler(sig: &syn::Signature) -> Option<&'static str> {
                     if is_matching_type(&pat3.ty, "str") {
                         return Some("Join");
                     }
+                    if is_matching_type(&pat3.ty, "EntityDamage") {
+                        return Some("Damage");
+                    }
                 }
             }
             if inputs.len() == 2 {
+                if let Some(FnArg::Typed(pat2)) = inputs.get(1) {
+                    if is_matching_type(&pat2.ty, "CommandCall") {
+                        return Some("Command");
+                    }
+                }
                 return Some("Quit");
             }
         }
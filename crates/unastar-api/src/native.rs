@@ -4,13 +4,15 @@ mod player;
 pub use player::Player;
 pub mod host;
 pub use host::*;
+mod command;
+pub use command::{CommandCall, CommandParam, CommandParamKind, CommandSpec};
 
 // Components module exists but doesn't export types (circular dependency issue)
 mod components;
 
 use abi_stable::{
     sabi_trait,
-    std_types::{RStr, RString},
+    std_types::{ROption, RStr, RString, RVec},
     StableAbi,
 };
 
@@ -18,6 +20,10 @@ use abi_stable::{
 pub use crate::PluginAction;
 pub use crate::Vec3;
 
+// Used by `export_plugin!`
+#[doc(hidden)]
+pub use abi_stable;
+
 // ... PluginAction, Vec3
 
 /// Resource queue for actions requested by native plugins.
@@ -41,6 +47,43 @@ impl BlockPos {
     }
 }
 
+/// Stage of the server's damage calculation.
+///
+/// Stages run in declaration order; each sees the amount left by the previous one.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, StableAbi)]
+pub enum DamageStage {
+    /// Incoming damage, after immunity frames.
+    Base,
+    /// After armour points and toughness.
+    Armour,
+    /// After the Resistance effect.
+    Resistance,
+    /// After Protection-family enchantments. This amount is taken from health.
+    Enchantments,
+}
+
+/// Damage being dealt to an entity, as seen by `on_entity_damage`.
+#[repr(C)]
+#[derive(Debug, Clone, StableAbi)]
+pub struct EntityDamage {
+    pub stage: DamageStage,
+    /// Kind of damage, e.g. `"attack"`, `"fall"` or `"fire"`.
+    pub cause: RString,
+    /// The entity responsible for the damage, if any.
+    pub attacker: ROption<PluginEntity>,
+    /// Damage at this stage. Plugins may change it.
+    pub amount: f32,
+}
+
+/// Version of the native plugin ABI.
+///
+/// Bump this whenever `RawPlugin`, the host vtable or a type passed across
+/// them changes. Plugins export it as `_plugin_abi_version` next to
+/// `_create_plugin`, and the server refuses to load a plugin built against
+/// a different version.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
 /// Core trait that all native Rust plugins must implement (ABI-stable version).
 /// internal use only.
 #[sabi_trait]
@@ -98,6 +141,17 @@ pub trait RawPlugin: Send + Sync + 'static {
     }
 
     fn on_player_quit(&mut self, ctx: &mut NativeGameContext, entity: PluginEntity) {}
+
+    fn on_entity_damage(
+        &mut self,
+        ctx: &mut NativeGameContext,
+        entity: PluginEntity,
+        damage: &mut EntityDamage,
+    ) -> bool {
+        true
+    }
+
+    fn on_command(&mut self, ctx: &mut NativeGameContext, call: &mut CommandCall) {}
 }
 
 /// User-facing Plugin trait with clean types.
@@ -153,11 +207,56 @@ pub trait Plugin: Send + Sync + 'static {
     }
 
     fn on_player_quit(&mut self, ctx: &mut NativeGameContext, entity: PluginEntity) {}
+
+    /// Called at every stage of the damage calculation.
+    ///
+    /// Change `damage.amount` to modify the hit, or return `false` to cancel it.
+    fn on_entity_damage(
+        &mut self,
+        ctx: &mut NativeGameContext,
+        entity: PluginEntity,
+        damage: &mut EntityDamage,
+    ) -> bool {
+        true
+    }
+
+    /// Called when a player runs a command this plugin registered in `on_load`.
+    fn on_command(&mut self, ctx: &mut NativeGameContext, call: &mut CommandCall) {}
 }
 
 /// Bridge struct that wraps a user Plugin and implements the ABI-stable RawPlugin trait.
 pub struct PluginBridge<P>(pub P);
 
+/// Export a plugin from a native plugin library.
+///
+/// Defines the two symbols the server looks up: `_create_plugin`, which
+/// wraps the plugin built by the given expression in a [`PluginBridge`], and
+/// `_plugin_abi_version`, which returns [`PLUGIN_ABI_VERSION`].
+///
+/// ```ignore
+/// unastar_api::export_plugin!(MyPlugin::new());
+/// ```
+#[macro_export]
+macro_rules! export_plugin {
+    ($plugin:expr) => {
+        /// Create the plugin (called by the server)
+        #[no_mangle]
+        pub extern "C" fn _create_plugin(
+        ) -> $crate::native::RawPlugin_TO<$crate::native::abi_stable::std_types::RBox<()>> {
+            $crate::native::RawPlugin_TO::from_value(
+                $crate::native::PluginBridge($plugin),
+                $crate::native::abi_stable::sabi_trait::TD_Opaque,
+            )
+        }
+
+        /// The plugin ABI version this plugin was built against
+        #[no_mangle]
+        pub extern "C" fn _plugin_abi_version() -> u32 {
+            $crate::native::PLUGIN_ABI_VERSION
+        }
+    };
+}
+
 impl<P: Plugin> RawPlugin for PluginBridge<P> {
     fn name(&self) -> RStr<'_> {
         self.0.name().into()
@@ -216,6 +315,19 @@ impl<P: Plugin> RawPlugin for PluginBridge<P> {
     fn on_player_quit(&mut self, ctx: &mut NativeGameContext, entity: PluginEntity) {
         self.0.on_player_quit(ctx, entity);
     }
+
+    fn on_entity_damage(
+        &mut self,
+        ctx: &mut NativeGameContext,
+        entity: PluginEntity,
+        damage: &mut EntityDamage,
+    ) -> bool {
+        self.0.on_entity_damage(ctx, entity, damage)
+    }
+
+    fn on_command(&mut self, ctx: &mut NativeGameContext, call: &mut CommandCall) {
+        self.0.on_command(ctx, call);
+    }
 }
 
 /// Context provided to plugins during on_load.
@@ -224,4 +336,24 @@ impl<P: Plugin> RawPlugin for PluginBridge<P> {
 pub struct PluginContext<'a> {
     /// The Game Context (wrapper around host VTable)
     pub world: NativeGameContext<'a>,
+    commands: RVec<CommandSpec>,
+}
+
+impl<'a> PluginContext<'a> {
+    pub fn new(world: NativeGameContext<'a>) -> Self {
+        Self {
+            world,
+            commands: RVec::new(),
+        }
+    }
+
+    /// Register a command. The plugin's `on_command` runs when it is used.
+    pub fn register_command(&mut self, command: CommandSpec) {
+        self.commands.push(command);
+    }
+
+    /// Take the commands registered during `on_load`.
+    pub fn take_commands(&mut self) -> Vec<CommandSpec> {
+        std::mem::take(&mut self.commands).into_vec()
+    }
 }
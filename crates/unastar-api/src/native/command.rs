@@ -0,0 +1,127 @@
+use super::PluginEntity;
+use abi_stable::{
+    std_types::{ROption, RString, RVec},
+    StableAbi,
+};
+
+/// Type of a command parameter.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, StableAbi)]
+pub enum CommandParamKind {
+    Int,
+    Float,
+    /// Three coordinates, each absolute, relative (`~`) or local (`^`).
+    Position,
+    /// A player name or target selector.
+    Target,
+    /// One of the parameter's `values`.
+    Enum,
+    /// A single word or quoted string.
+    String,
+    /// The rest of the command line.
+    Text,
+}
+
+/// A parameter of a plugin command overload.
+#[repr(C)]
+#[derive(Debug, Clone, StableAbi)]
+pub struct CommandParam {
+    pub name: RString,
+    pub kind: CommandParamKind,
+    pub optional: bool,
+    /// Accepted words for `CommandParamKind::Enum`.
+    pub values: RVec<RString>,
+}
+
+impl CommandParam {
+    pub fn new(name: &str, kind: CommandParamKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            optional: false,
+            values: RVec::new(),
+        }
+    }
+
+    /// An enum parameter accepting one of `values`.
+    pub fn enumeration(name: &str, values: &[&str]) -> Self {
+        Self {
+            values: values.iter().map(|&value| value.into()).collect(),
+            ..Self::new(name, CommandParamKind::Enum)
+        }
+    }
+
+    /// Mark the parameter as optional. Only trailing parameters may be optional.
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+}
+
+/// A command registered by a plugin in `on_load`.
+#[repr(C)]
+#[derive(Debug, Clone, StableAbi)]
+pub struct CommandSpec {
+    pub name: RString,
+    pub description: RString,
+    pub aliases: RVec<RString>,
+    /// Accepted parameter lists, tried in order.
+    pub overloads: RVec<RVec<CommandParam>>,
+}
+
+impl CommandSpec {
+    pub fn new(name: &str, description: &str) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            aliases: RVec::new(),
+            overloads: RVec::new(),
+        }
+    }
+
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    pub fn overload(mut self, params: Vec<CommandParam>) -> Self {
+        self.overloads.push(params.into());
+        self
+    }
+}
+
+/// A plugin command being executed, as seen by `on_command`.
+///
+/// The server has already matched the arguments against the command's
+/// overloads; `args` holds the text of each parameter of `overload` that
+/// was given.
+#[repr(C)]
+#[derive(Debug, Clone, StableAbi)]
+pub struct CommandCall {
+    /// Name the command was registered with.
+    pub command: RString,
+    /// The player running the command, if it was a player.
+    pub sender: ROption<PluginEntity>,
+    /// Index of the matched overload.
+    pub overload: u32,
+    pub args: RVec<RString>,
+    pub messages: RVec<RString>,
+    pub errors: RVec<RString>,
+}
+
+impl CommandCall {
+    /// Text of the argument at `index`, if it was given.
+    pub fn arg(&self, index: usize) -> Option<&str> {
+        self.args.get(index).map(|arg| arg.as_str())
+    }
+
+    /// Send a message to the sender.
+    pub fn message(&mut self, message: impl Into<String>) {
+        self.messages.push(RString::from(message.into()));
+    }
+
+    /// Report an error to the sender.
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.errors.push(RString::from(message.into()));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::Vec3;
 use abi_stable::{
     sabi_trait,
-    std_types::{ROption, RStr, RString},
+    std_types::{ROption, RResult, RStr, RString, RVec},
     StableAbi,
 };
 
@@ -55,6 +55,91 @@ pub trait RawPluginHost: Send + Sync {
 
     /// Give an item to a player.
     fn give_item(&mut self, player_uuid: RStr<'_>, item_id: RStr<'_>, count: u8);
+
+    /// Resolve a player name or target selector (`@a`, `@e[r=10]`, ...).
+    /// `executor` is what `@s` refers to and where distances are measured from.
+    fn select_entities(
+        &mut self,
+        target: RStr<'_>,
+        executor: ROption<PluginEntity>,
+    ) -> RResult<RVec<PluginEntity>, RString>;
+
+    /// Op level (0-4) of a player by name.
+    fn op_level(&self, player_name: RStr<'_>) -> u8;
+
+    /// Whether a player holds a permission node. Players without an explicit
+    /// grant or revoke hold it if their op level is at least `default_level`
+    /// (levels above 4 are treated as 4).
+    fn has_permission(&self, player_name: RStr<'_>, node: RStr<'_>, default_level: u8) -> bool;
+
+    /// Grant (`Some(true)`), revoke (`Some(false)`) or clear (`None`) a
+    /// permission node. Changes are saved to the permissions file.
+    fn set_permission(
+        &mut self,
+        player_name: RStr<'_>,
+        node: RStr<'_>,
+        value: ROption<bool>,
+    ) -> RResult<(), RString>;
+
+    /// Ticks since the default world was created; the time of day is this
+    /// modulo 24000.
+    fn world_time(&self) -> i64;
+
+    /// Set the default world's time and sync it to the players in it.
+    fn set_world_time(&mut self, time: i64);
+
+    /// Current weather in the default world: 0 clear, 1 rain, 2 thunder.
+    fn weather(&self) -> u8;
+
+    /// Change the default world's weather for `duration` ticks, or a random
+    /// duration if `duration` is not positive.
+    fn set_weather(&mut self, weather: u8, duration: i32) -> RResult<(), RString>;
+
+    /// Current difficulty: 0 peaceful, 1 easy, 2 normal, 3 hard.
+    fn difficulty(&self) -> u8;
+
+    /// Set the difficulty and sync it to every player.
+    fn set_difficulty(&mut self, difficulty: u8) -> RResult<(), RString>;
+
+    /// Value of a game rule such as `keepInventory`, or none if there is no
+    /// such rule.
+    fn game_rule(&self, name: RStr<'_>) -> ROption<bool>;
+
+    /// Set a game rule and sync it to every player.
+    fn set_game_rule(&mut self, name: RStr<'_>, value: bool) -> RResult<(), RString>;
+
+    /// Names of the loaded worlds, `default` first.
+    fn worlds(&self) -> RVec<RString>;
+
+    /// Create a world and load it. `generator` is 0 flat, 1 void platform,
+    /// 2 vanilla, 3 nether, 4 end (the last three using `seed`); `dimension`
+    /// is 0 overworld, 1 nether, 2 end.
+    fn create_world(
+        &mut self,
+        name: RStr<'_>,
+        generator: u8,
+        seed: i64,
+        dimension: i32,
+    ) -> RResult<(), RString>;
+
+    /// Load a configured world, or one created before a restart.
+    fn load_world(&mut self, name: RStr<'_>) -> RResult<(), RString>;
+
+    /// Save and unload a world after the current tick, moving its players to
+    /// the default world.
+    fn unload_world(&mut self, name: RStr<'_>) -> RResult<(), RString>;
+
+    /// Name of the world an entity is in.
+    fn entity_world(&self, entity: PluginEntity) -> ROption<RString>;
+
+    /// Move a player to a loaded world after the current tick, to `position`
+    /// or the world spawn.
+    fn change_world(
+        &mut self,
+        entity: PluginEntity,
+        world: RStr<'_>,
+        position: ROption<Vec3>,
+    ) -> RResult<(), RString>;
 }
 
 use abi_stable::std_types::RBox;
@@ -80,6 +165,170 @@ impl<'a> NativeGameContext<'a> {
     pub fn get_player_info(&self, entity: PluginEntity) -> Option<PlayerInfo> {
         self.host.get_player_info(entity).into_option()
     }
+
+    /// Resolve a player name or target selector, using the same rules as commands.
+    pub fn select_entities(
+        &mut self,
+        target: &str,
+        executor: Option<PluginEntity>,
+    ) -> Result<Vec<PluginEntity>, String> {
+        self.host
+            .select_entities(target.into(), executor.into())
+            .into_result()
+            .map(RVec::into_vec)
+            .map_err(RString::into_string)
+    }
+
+    /// Op level (0-4) of a player by name.
+    pub fn op_level(&self, player_name: &str) -> u8 {
+        self.host.op_level(player_name.into())
+    }
+
+    /// Whether a player holds a permission node, falling back to their op
+    /// level reaching `default_level`.
+    pub fn has_permission(&self, player_name: &str, node: &str, default_level: u8) -> bool {
+        self.host
+            .has_permission(player_name.into(), node.into(), default_level)
+    }
+
+    /// Grant a permission node to a player.
+    pub fn grant_permission(&mut self, player_name: &str, node: &str) -> Result<(), String> {
+        self.set_permission(player_name, node, Some(true))
+    }
+
+    /// Revoke a permission node from a player, even if their op level holds it.
+    pub fn revoke_permission(&mut self, player_name: &str, node: &str) -> Result<(), String> {
+        self.set_permission(player_name, node, Some(false))
+    }
+
+    /// Clear an explicit grant or revoke, going back to the op level default.
+    pub fn unset_permission(&mut self, player_name: &str, node: &str) -> Result<(), String> {
+        self.set_permission(player_name, node, None)
+    }
+
+    fn set_permission(
+        &mut self,
+        player_name: &str,
+        node: &str,
+        value: Option<bool>,
+    ) -> Result<(), String> {
+        self.host
+            .set_permission(player_name.into(), node.into(), value.into())
+            .into_result()
+            .map_err(RString::into_string)
+    }
+
+    /// Ticks since the default world was created.
+    pub fn world_time(&self) -> i64 {
+        self.host.world_time()
+    }
+
+    /// Set the default world's time.
+    pub fn set_world_time(&mut self, time: i64) {
+        self.host.set_world_time(time)
+    }
+
+    /// Current weather in the default world: 0 clear, 1 rain, 2 thunder.
+    pub fn weather(&self) -> u8 {
+        self.host.weather()
+    }
+
+    /// Change the default world's weather, for `duration` ticks or a random
+    /// vanilla duration.
+    pub fn set_weather(&mut self, weather: u8, duration: Option<i32>) -> Result<(), String> {
+        self.host
+            .set_weather(weather, duration.unwrap_or(0))
+            .into_result()
+            .map_err(RString::into_string)
+    }
+
+    /// Current difficulty: 0 peaceful, 1 easy, 2 normal, 3 hard.
+    pub fn difficulty(&self) -> u8 {
+        self.host.difficulty()
+    }
+
+    /// Set the difficulty for everyone.
+    pub fn set_difficulty(&mut self, difficulty: u8) -> Result<(), String> {
+        self.host
+            .set_difficulty(difficulty)
+            .into_result()
+            .map_err(RString::into_string)
+    }
+
+    /// Value of a game rule, or `None` if there is no such rule.
+    pub fn game_rule(&self, name: &str) -> Option<bool> {
+        self.host.game_rule(name.into()).into_option()
+    }
+
+    /// Set a game rule for everyone.
+    pub fn set_game_rule(&mut self, name: &str, value: bool) -> Result<(), String> {
+        self.host
+            .set_game_rule(name.into(), value)
+            .into_result()
+            .map_err(RString::into_string)
+    }
+
+    /// Names of the loaded worlds, `default` first.
+    pub fn worlds(&self) -> Vec<String> {
+        self.host
+            .worlds()
+            .into_iter()
+            .map(RString::into_string)
+            .collect()
+    }
+
+    /// Create and load a world: generator 0 flat, 1 void platform, 2 vanilla,
+    /// 3 nether, 4 end; dimension 0 overworld, 1 nether, 2 end.
+    pub fn create_world(
+        &mut self,
+        name: &str,
+        generator: u8,
+        seed: i64,
+        dimension: i32,
+    ) -> Result<(), String> {
+        self.host
+            .create_world(name.into(), generator, seed, dimension)
+            .into_result()
+            .map_err(RString::into_string)
+    }
+
+    /// Load a world by name.
+    pub fn load_world(&mut self, name: &str) -> Result<(), String> {
+        self.host
+            .load_world(name.into())
+            .into_result()
+            .map_err(RString::into_string)
+    }
+
+    /// Save and unload a world once the tick ends.
+    pub fn unload_world(&mut self, name: &str) -> Result<(), String> {
+        self.host
+            .unload_world(name.into())
+            .into_result()
+            .map_err(RString::into_string)
+    }
+
+    /// Name of the world an entity is in.
+    pub fn entity_world(&self, entity: PluginEntity) -> Option<String> {
+        self.host
+            .entity_world(entity)
+            .into_option()
+            .map(RString::into_string)
+    }
+
+    /// Move a player to another world once the tick ends, arriving at
+    /// `position` or the world spawn.
+    pub fn change_world(
+        &mut self,
+        entity: PluginEntity,
+        world: &str,
+        position: Option<Vec3>,
+    ) -> Result<(), String> {
+        self.host
+            .change_world(entity, world.into(), position.into())
+            .into_result()
+            .map_err(RString::into_string)
+    }
 }
 
 // Add user-friendly helpers for PlayerInfo since fields are RString
@@ -2,6 +2,7 @@
 //!
 //! Run with: cargo run --example test_chunk_gen
 
+use unastar::world::chunk::LAYER_BLOCK;
 use unastar::world::generator::VanillaGenerator;
 
 fn main() {
@@ -21,7 +22,7 @@ fn main() {
     for y in -64..320 {
         for x in 0..16 {
             for z in 0..16 {
-                let block = chunk.get_block(x, y, z);
+                let block = chunk.get_block(x, y, z, LAYER_BLOCK);
                 match block {
                     1 => stone_count += 1, // Stone
                     8 | 9 => water_count += 1, // Water (still or flowing)
@@ -43,7 +44,7 @@ fn main() {
         let mut count = 0;
         for x in 0..16 {
             for z in 0..16 {
-                let block = chunk.get_block(x, y, z);
+                let block = chunk.get_block(x, y, z, LAYER_BLOCK);
                 if block == 8 || block == 9 {
                     count += 1;
                 }
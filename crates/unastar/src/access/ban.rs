@@ -0,0 +1,259 @@
+//! Name, XUID and IP bans.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use super::ListFile;
+
+/// A single ban: why, by whom, and until when.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BanEntry {
+    /// Player the entry belongs to, for XUID and IP bans made from a name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub reason: String,
+    /// Who created the ban.
+    #[serde(default)]
+    pub source: String,
+    /// Unix time the ban was created, in seconds.
+    #[serde(default)]
+    pub created: u64,
+    /// Unix time the ban ends, in seconds. `None` is permanent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
+}
+
+impl BanEntry {
+    pub fn new(reason: impl Into<String>, source: impl Into<String>, created: u64) -> Self {
+        Self {
+            name: None,
+            reason: reason.into(),
+            source: source.into(),
+            created,
+            expires: None,
+        }
+    }
+
+    /// End the ban at `expires` (unix seconds).
+    pub fn until(mut self, expires: Option<u64>) -> Self {
+        self.expires = expires;
+        self
+    }
+
+    pub fn is_active(&self, now: u64) -> bool {
+        self.expires.is_none_or(|expires| now < expires)
+    }
+}
+
+/// An IP address or CIDR range, such as `203.0.113.7` or `10.0.0.0/8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpPattern {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpPattern {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+            v4 => v4,
+        };
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                prefix_eq(u32::from(net).into(), u32::from(ip).into(), 32, self.prefix)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                prefix_eq(u128::from(net), u128::from(ip), 128, self.prefix)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Whether the top `prefix` of `bits` bits of `a` and `b` match.
+fn prefix_eq(a: u128, b: u128, bits: u8, prefix: u8) -> bool {
+    let shift = u32::from(bits - prefix);
+    a.checked_shr(shift).unwrap_or(0) == b.checked_shr(shift).unwrap_or(0)
+}
+
+impl From<IpAddr> for IpPattern {
+    fn from(addr: IpAddr) -> Self {
+        let prefix = if addr.is_ipv4() { 32 } else { 128 };
+        Self { addr, prefix }
+    }
+}
+
+impl FromStr for IpPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr
+            .parse()
+            .map_err(|_| format!("\"{s}\" is not an IP address or range"))?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|&prefix| prefix <= max)
+                .ok_or_else(|| format!("\"{s}\" has an invalid prefix length"))?,
+            None => max,
+        };
+        Ok(Self { addr, prefix })
+    }
+}
+
+impl fmt::Display for IpPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let max = if self.addr.is_ipv4() { 32 } else { 128 };
+        if self.prefix == max {
+            write!(f, "{}", self.addr)
+        } else {
+            write!(f, "{}/{}", self.addr, self.prefix)
+        }
+    }
+}
+
+/// Contents of `bans.toml`.
+///
+/// Player bans are keyed by lowercase name, XUID bans by XUID and IP bans by
+/// address or CIDR range.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BanList {
+    players: BTreeMap<String, BanEntry>,
+    xuids: BTreeMap<String, BanEntry>,
+    ips: BTreeMap<String, BanEntry>,
+}
+
+impl ListFile for BanList {
+    fn validate(&self) -> Result<(), String> {
+        for pattern in self.ips.keys() {
+            pattern.parse::<IpPattern>()?;
+        }
+        Ok(())
+    }
+}
+
+impl BanList {
+    /// Ban a player by name and, when known, by XUID.
+    pub fn ban_player(&mut self, name: &str, xuid: Option<&str>, entry: BanEntry) {
+        if let Some(xuid) = xuid.filter(|xuid| !xuid.is_empty()) {
+            let mut entry = entry.clone();
+            entry.name = Some(name.to_string());
+            self.xuids.insert(xuid.to_string(), entry);
+        }
+        self.players.insert(name.to_ascii_lowercase(), entry);
+    }
+
+    /// Lift a player's name ban and any XUID bans recorded for that name.
+    pub fn pardon_player(&mut self, name: &str) -> bool {
+        let removed = self.players.remove(&name.to_ascii_lowercase()).is_some();
+        let before = self.xuids.len();
+        self.xuids.retain(|_, entry| {
+            !entry
+                .name
+                .as_deref()
+                .is_some_and(|banned| banned.eq_ignore_ascii_case(name))
+        });
+        removed || self.xuids.len() != before
+    }
+
+    pub fn ban_ip(&mut self, pattern: IpPattern, entry: BanEntry) {
+        self.ips.insert(pattern.to_string(), entry);
+    }
+
+    pub fn pardon_ip(&mut self, pattern: IpPattern) -> bool {
+        self.ips.remove(&pattern.to_string()).is_some()
+    }
+
+    /// The active ban matching a player's name or XUID.
+    pub fn player_ban(&self, name: &str, xuid: Option<&str>, now: u64) -> Option<&BanEntry> {
+        let by_name = self.players.get(&name.to_ascii_lowercase());
+        let by_xuid = xuid.and_then(|xuid| self.xuids.get(xuid));
+        by_name
+            .into_iter()
+            .chain(by_xuid)
+            .find(|entry| entry.is_active(now))
+    }
+
+    /// The active ban covering an IP address.
+    pub fn ip_ban(&self, ip: IpAddr, now: u64) -> Option<&BanEntry> {
+        self.ips.iter().find_map(|(pattern, entry)| {
+            let pattern: IpPattern = pattern.parse().ok()?;
+            (pattern.contains(ip) && entry.is_active(now)).then_some(entry)
+        })
+    }
+
+    /// Active player bans, sorted by name.
+    pub fn players(&self, now: u64) -> Vec<(&str, &BanEntry)> {
+        self.players
+            .iter()
+            .filter(|(_, entry)| entry.is_active(now))
+            .map(|(name, entry)| (name.as_str(), entry))
+            .collect()
+    }
+
+    /// Active IP bans, sorted by address.
+    pub fn ips(&self, now: u64) -> Vec<(&str, &BanEntry)> {
+        self.ips
+            .iter()
+            .filter(|(_, entry)| entry.is_active(now))
+            .map(|(pattern, entry)| (pattern.as_str(), entry))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ip_patterns() {
+        let range: IpPattern = "10.0.0.0/8".parse().unwrap();
+        assert!(range.contains("10.20.30.40".parse().unwrap()));
+        assert!(range.contains("::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!range.contains("11.0.0.1".parse().unwrap()));
+        assert_eq!(range.to_string(), "10.0.0.0/8");
+
+        let all: IpPattern = "::/0".parse().unwrap();
+        assert!(all.contains("2001:db8::1".parse().unwrap()));
+        let single: IpPattern = "203.0.113.7".parse().unwrap();
+        assert!(single.contains("203.0.113.7".parse().unwrap()));
+        assert!(!single.contains("203.0.113.8".parse().unwrap()));
+        assert!("10.0.0.0/33".parse::<IpPattern>().is_err());
+        assert!("steve".parse::<IpPattern>().is_err());
+    }
+
+    #[test]
+    fn test_bans() {
+        let mut bans = BanList::default();
+        bans.ban_player(
+            "Steve",
+            Some("2535"),
+            BanEntry::new("griefing", "Alex", 100).until(Some(200)),
+        );
+        assert!(bans.player_ban("steve", None, 150).is_some());
+        // A renamed account is still caught by its XUID
+        assert!(bans.player_ban("Steve2", Some("2535"), 150).is_some());
+        assert!(bans.player_ban("steve", Some("2535"), 200).is_none());
+
+        assert!(bans.pardon_player("STEVE"));
+        assert!(bans.player_ban("Steve2", Some("2535"), 150).is_none());
+        assert!(!bans.pardon_player("steve"));
+
+        bans.ban_ip("192.168.0.0/16".parse().unwrap(), BanEntry::new("", "", 0));
+        assert!(bans.ip_ban("192.168.4.4".parse().unwrap(), 0).is_some());
+        assert!(bans.validate().is_ok());
+        assert!(bans.pardon_ip("192.168.0.0/16".parse().unwrap()));
+        assert!(bans.ips(0).is_empty());
+    }
+}
@@ -0,0 +1,414 @@
+//! Login access control: bans and the whitelist.
+//!
+//! Both lists live next to `unastar.toml`, in `bans.toml` and
+//! `whitelist.toml`, and are checked during the join sequence right after
+//! authentication, before `StartGame` is sent. Bans carry a reason and an
+//! optional expiry time:
+//!
+//! ```toml
+//! [players.steve]
+//! reason = "Griefing"
+//! source = "Alex"
+//! created = 1767225600
+//! expires = 1767830400
+//!
+//! [ips."10.0.0.0/8"]
+//! reason = "VPN"
+//! ```
+//!
+//! Edits made to the files while the server runs are picked up without a
+//! restart: they are re-read whenever their modification time changes.
+
+mod ban;
+mod whitelist;
+
+pub use ban::{BanEntry, BanList, IpPattern};
+pub use whitelist::Whitelist;
+
+use bevy_ecs::prelude::*;
+use parking_lot::RwLock;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// File name of the ban list, placed next to the config file.
+pub const BANS_FILE: &str = "bans.toml";
+/// File name of the whitelist, placed next to the config file.
+pub const WHITELIST_FILE: &str = "whitelist.toml";
+
+/// Current unix time in seconds.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Parse a duration such as `30m`, `12h` or `1d12h` into seconds.
+pub fn parse_duration(text: &str) -> Option<u64> {
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for ch in text.chars() {
+        if ch.is_ascii_digit() {
+            digits.push(ch);
+            continue;
+        }
+        let unit = match ch.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+        let amount: u64 = std::mem::take(&mut digits).parse().ok()?;
+        total = total.checked_add(amount.checked_mul(unit)?)?;
+    }
+    (digits.is_empty() && total > 0).then_some(total)
+}
+
+/// Format seconds as the two largest units, e.g. `2d 3h`.
+pub fn format_duration(seconds: u64) -> String {
+    const UNITS: [(u64, &str); 5] = [
+        (7 * 24 * 60 * 60, "w"),
+        (24 * 60 * 60, "d"),
+        (60 * 60, "h"),
+        (60, "m"),
+        (1, "s"),
+    ];
+    let parts: Vec<String> = UNITS
+        .iter()
+        .scan(seconds, |left, &(size, unit)| {
+            let amount = *left / size;
+            *left %= size;
+            Some((amount, unit))
+        })
+        .filter(|(amount, _)| *amount > 0)
+        .take(2)
+        .map(|(amount, unit)| format!("{amount}{unit}"))
+        .collect();
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// A list stored in its own TOML file.
+trait ListFile: Serialize + DeserializeOwned + Default {
+    /// Check values serde can't, such as IP ranges.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// A list and the file it was loaded from.
+#[derive(Default)]
+struct Tracked<T> {
+    list: T,
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+impl<T: ListFile> Tracked<T> {
+    fn load(path: PathBuf) -> Result<Self, AccessError> {
+        let modified = modified(&path);
+        let list = match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                let list: T =
+                    toml_edit::de::from_str(&contents).map_err(|source| AccessError::Parse {
+                        path: path.clone(),
+                        source,
+                    })?;
+                list.validate().map_err(|message| AccessError::Invalid {
+                    path: path.clone(),
+                    message,
+                })?;
+                list
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => T::default(),
+            Err(source) => return Err(AccessError::Read { path, source }),
+        };
+        Ok(Self {
+            list,
+            path: Some(path),
+            modified,
+        })
+    }
+
+    /// Re-read the file, or only if it changed since it was last read or
+    /// written when `if_changed` is set.
+    fn reload(&mut self, if_changed: bool) -> Result<bool, AccessError> {
+        let Some(path) = &self.path else {
+            return Ok(false);
+        };
+        if if_changed && modified(path) == self.modified {
+            return Ok(false);
+        }
+        *self = Self::load(path.clone())?;
+        Ok(true)
+    }
+
+    fn save(&mut self) -> Result<(), AccessError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents =
+            toml_edit::ser::to_string_pretty(&self.list).map_err(AccessError::Serialize)?;
+        std::fs::write(path, contents).map_err(|source| AccessError::Write {
+            path: path.clone(),
+            source,
+        })?;
+        self.modified = modified(path);
+        Ok(())
+    }
+}
+
+/// Why a player may not join.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Denial {
+    Banned(BanEntry),
+    IpBanned(BanEntry),
+    NotWhitelisted,
+}
+
+impl Denial {
+    /// Message shown on the disconnect screen.
+    pub fn message(&self, now: u64) -> String {
+        let (mut message, entry) = match self {
+            Denial::Banned(entry) => ("You are banned from this server".to_string(), entry),
+            Denial::IpBanned(entry) => (
+                "Your IP address is banned from this server".to_string(),
+                entry,
+            ),
+            Denial::NotWhitelisted => {
+                return "You are not whitelisted on this server".to_string();
+            }
+        };
+        if !entry.reason.is_empty() {
+            message.push_str(&format!("\nReason: {}", entry.reason));
+        }
+        if let Some(expires) = entry.expires {
+            message.push_str(&format!(
+                "\nExpires in {}",
+                format_duration(expires.saturating_sub(now))
+            ));
+        }
+        message
+    }
+}
+
+/// Shared handle to the ban list and whitelist.
+///
+/// Inserted as a world resource; clones refer to the same lists, so the join
+/// sequence can check logins off the game thread. Changes made through
+/// [`update_bans`](Self::update_bans) and
+/// [`update_whitelist`](Self::update_whitelist) are written to disk
+/// immediately.
+#[derive(Resource, Clone, Default)]
+pub struct AccessControl {
+    bans: Arc<RwLock<Tracked<BanList>>>,
+    whitelist: Arc<RwLock<Tracked<Whitelist>>>,
+}
+
+impl std::fmt::Debug for AccessControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccessControl")
+            .field("bans", &self.bans.read().path)
+            .field("whitelist", &self.whitelist.read().path)
+            .finish()
+    }
+}
+
+impl AccessControl {
+    /// Load `bans.toml` and `whitelist.toml` from `dir`. Missing files are
+    /// created on the first change.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, AccessError> {
+        let dir = dir.as_ref();
+        Ok(Self {
+            bans: Arc::new(RwLock::new(Tracked::load(dir.join(BANS_FILE))?)),
+            whitelist: Arc::new(RwLock::new(Tracked::load(dir.join(WHITELIST_FILE))?)),
+        })
+    }
+
+    /// Re-read any file edited since it was last loaded.
+    ///
+    /// Returns whether anything was reloaded. On error the previous contents
+    /// are kept.
+    pub fn reload_if_changed(&self) -> Result<bool, AccessError> {
+        let bans = self.bans.write().reload(true)?;
+        let whitelist = self.whitelist.write().reload(true)?;
+        Ok(bans || whitelist)
+    }
+
+    /// Re-read both files.
+    pub fn reload(&self) -> Result<(), AccessError> {
+        self.bans.write().reload(false)?;
+        self.whitelist.write().reload(false)?;
+        Ok(())
+    }
+
+    /// Check whether a player may join. Operators bypass the whitelist but
+    /// not bans.
+    pub fn check(
+        &self,
+        name: &str,
+        xuid: Option<&str>,
+        ip: IpAddr,
+        is_op: bool,
+    ) -> Result<(), Denial> {
+        let now = unix_now();
+        {
+            let bans = self.bans.read();
+            if let Some(entry) = bans.list.player_ban(name, xuid, now) {
+                return Err(Denial::Banned(entry.clone()));
+            }
+            if let Some(entry) = bans.list.ip_ban(ip, now) {
+                return Err(Denial::IpBanned(entry.clone()));
+            }
+        }
+        if !is_op && !self.whitelist.read().list.allows(name, xuid) {
+            return Err(Denial::NotWhitelisted);
+        }
+        Ok(())
+    }
+
+    /// Move a whitelist entry added under a joining player's name to its
+    /// XUID. Called when the player joins.
+    pub fn claim(&self, name: &str, xuid: &str) -> Result<(), AccessError> {
+        let mut whitelist = self.whitelist.write();
+        if whitelist.list.claim(name, xuid) {
+            whitelist.save()?;
+        }
+        Ok(())
+    }
+
+    /// Snapshot of the ban list.
+    pub fn bans(&self) -> BanList {
+        self.bans.read().list.clone()
+    }
+
+    /// Snapshot of the whitelist.
+    pub fn whitelist(&self) -> Whitelist {
+        self.whitelist.read().list.clone()
+    }
+
+    /// Change the ban list and save it.
+    pub fn update_bans<R>(&self, f: impl FnOnce(&mut BanList) -> R) -> Result<R, AccessError> {
+        let mut bans = self.bans.write();
+        let result = f(&mut bans.list);
+        bans.save()?;
+        Ok(result)
+    }
+
+    /// Change the whitelist and save it.
+    pub fn update_whitelist<R>(
+        &self,
+        f: impl FnOnce(&mut Whitelist) -> R,
+    ) -> Result<R, AccessError> {
+        let mut whitelist = self.whitelist.write();
+        let result = f(&mut whitelist.list);
+        whitelist.save()?;
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AccessError {
+    #[error("failed to read `{path}`: {source}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to parse `{path}`: {source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml_edit::de::Error,
+    },
+    #[error("invalid entry in `{path}`: {message}")]
+    Invalid { path: PathBuf, message: String },
+    #[error("failed to serialize access list: {0}")]
+    Serialize(#[source] toml_edit::ser::Error),
+    #[error("failed to write `{path}`: {source}")]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_durations() {
+        assert_eq!(parse_duration("30m"), Some(30 * 60));
+        assert_eq!(parse_duration("1d12h"), Some(36 * 60 * 60));
+        assert_eq!(parse_duration("2W"), Some(14 * 24 * 60 * 60));
+        assert_eq!(parse_duration("12"), None);
+        assert_eq!(parse_duration("h"), None);
+        assert_eq!(parse_duration("0s"), None);
+        assert_eq!(format_duration(36 * 60 * 60 + 59), "1d 12h");
+        assert_eq!(format_duration(90), "1m 30s");
+        assert_eq!(format_duration(0), "0s");
+    }
+
+    #[test]
+    fn test_check_and_reload() {
+        let dir = std::env::temp_dir().join(format!("unastar-access-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let ip: IpAddr = "198.51.100.2".parse().unwrap();
+
+        let access = AccessControl::load(&dir).unwrap();
+        assert_eq!(access.check("Steve", None, ip, false), Ok(()));
+        access
+            .update_whitelist(|whitelist| {
+                whitelist.enabled = true;
+                whitelist.add("Alex", None)
+            })
+            .unwrap();
+        assert_eq!(
+            access.check("Steve", None, ip, false),
+            Err(Denial::NotWhitelisted)
+        );
+        assert_eq!(access.check("Steve", None, ip, true), Ok(()));
+        assert_eq!(access.check("alex", None, ip, false), Ok(()));
+
+        // Edits to the file are picked up
+        std::fs::write(
+            dir.join(BANS_FILE),
+            "[ips.\"198.51.100.0/24\"]\nreason = \"Spam\"\n",
+        )
+        .unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(dir.join(BANS_FILE))
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(access.reload_if_changed().unwrap());
+        let denial = access.check("alex", None, ip, true).unwrap_err();
+        assert_eq!(
+            denial.message(0),
+            "Your IP address is banned from this server\nReason: Spam"
+        );
+
+        std::fs::write(dir.join(BANS_FILE), "[ips.nope]\n").unwrap();
+        assert!(AccessControl::load(&dir).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,134 @@
+//! The whitelist.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::ListFile;
+
+/// Contents of `whitelist.toml`.
+///
+/// Players who signed in with Xbox Live are stored by XUID, with their last
+/// known name for display. Players added before they have joined, or who
+/// play without an XUID, are stored by lowercase name; the first player to
+/// join under such a name with an XUID takes the entry over.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Whitelist {
+    /// Only listed players (and operators) may join while enabled.
+    pub enabled: bool,
+    players: BTreeSet<String>,
+    xuids: BTreeMap<String, String>,
+}
+
+impl ListFile for Whitelist {}
+
+fn xuid_of(xuid: Option<&str>) -> Option<&str> {
+    xuid.filter(|xuid| !xuid.is_empty())
+}
+
+impl Whitelist {
+    /// Whether a player is listed: by XUID if it has one, otherwise by name.
+    pub fn contains(&self, name: &str, xuid: Option<&str>) -> bool {
+        match xuid_of(xuid) {
+            Some(xuid) => self.xuids.contains_key(xuid),
+            None => self.players.contains(&name.to_ascii_lowercase()),
+        }
+    }
+
+    /// Whether a player may join under the whitelist.
+    pub fn allows(&self, name: &str, xuid: Option<&str>) -> bool {
+        !self.enabled || self.contains(name, xuid)
+    }
+
+    /// Move the entry added under a joining player's name to its XUID and
+    /// refresh the name recorded for it. Returns whether anything changed.
+    pub fn claim(&mut self, name: &str, xuid: &str) -> bool {
+        let claimed = self.players.remove(&name.to_ascii_lowercase());
+        let listed = self.xuids.get_mut(xuid);
+        match listed {
+            Some(known) if known != name => {
+                *known = name.to_string();
+                true
+            }
+            Some(_) => claimed,
+            None if claimed => {
+                self.xuids.insert(xuid.to_string(), name.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Add a player by XUID when known, otherwise by name. Returns false if
+    /// they were already listed.
+    pub fn add(&mut self, name: &str, xuid: Option<&str>) -> bool {
+        match xuid_of(xuid) {
+            Some(xuid) => self
+                .xuids
+                .insert(xuid.to_string(), name.to_string())
+                .is_none(),
+            None if self.xuid_named(name).is_some() => false,
+            None => self.players.insert(name.to_ascii_lowercase()),
+        }
+    }
+
+    /// Remove a player's entries, returning false if they weren't listed.
+    ///
+    /// Without an XUID, the entry recorded under the name is removed too.
+    pub fn remove(&mut self, name: &str, xuid: Option<&str>) -> bool {
+        let xuid = xuid_of(xuid)
+            .map(str::to_string)
+            .or_else(|| self.xuid_named(name));
+        let by_xuid = xuid.is_some_and(|xuid| self.xuids.remove(&xuid).is_some());
+        let by_name = self.players.remove(&name.to_ascii_lowercase());
+        by_xuid || by_name
+    }
+
+    /// XUID last seen under a name.
+    fn xuid_named(&self, name: &str) -> Option<String> {
+        self.xuids
+            .iter()
+            .find(|(_, known)| known.eq_ignore_ascii_case(name))
+            .map(|(xuid, _)| xuid.clone())
+    }
+
+    /// Names of the listed players: XUID entries first, then names not yet
+    /// claimed.
+    pub fn players(&self) -> impl Iterator<Item = &str> {
+        self.xuids
+            .values()
+            .chain(self.players.iter())
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whitelist_by_xuid() {
+        let mut whitelist = Whitelist {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(whitelist.add("Steve", None));
+        assert!(!whitelist.add("steve", None));
+        assert!(whitelist.allows("Steve", None));
+        assert!(!whitelist.allows("Steve", Some("1001")));
+
+        // The first Steve to join with an XUID takes the entry
+        assert!(whitelist.claim("Steve", "1001"));
+        assert!(whitelist.allows("Steve", Some("1001")));
+        assert!(!whitelist.allows("Steve", Some("2002")));
+        assert!(!whitelist.allows("Steve", None));
+        assert!(!whitelist.claim("Steve", "2002"));
+
+        // Renames follow the XUID
+        assert!(whitelist.claim("Herobrine", "1001"));
+        assert_eq!(whitelist.players().collect::<Vec<_>>(), ["Herobrine"]);
+        assert!(!whitelist.add("herobrine", None));
+        assert!(whitelist.remove("Herobrine", None));
+        assert!(!whitelist.allows("Herobrine", Some("1001")));
+    }
+}
@@ -0,0 +1,558 @@
+//! Ban and whitelist commands.
+
+use bevy_ecs::prelude::*;
+use std::net::IpAddr;
+
+use super::builtin::target_players;
+use super::{Arguments, Command, CommandContext, CommandOutput, Overload, Param, ParamKind};
+use crate::access::{
+    AccessControl, BanEntry, IpPattern, format_duration, parse_duration, unix_now,
+};
+use crate::entity::components::{PlayerName, PlayerSession};
+use crate::permission::{self, OpLevel};
+use crate::server::game::kick_denied_players;
+
+fn access(ctx: &CommandContext, out: &mut CommandOutput) -> Option<AccessControl> {
+    let access = ctx.world.get_resource::<AccessControl>().cloned();
+    if access.is_none() {
+        out.error("Bans and the whitelist are not available");
+    }
+    access
+}
+
+pub(super) fn enum_param(name: &str, enum_name: &str, values: &[&str]) -> Param {
+    Param::new(
+        name,
+        ParamKind::Enum {
+            name: enum_name.to_string(),
+            values: values.iter().map(|value| value.to_string()).collect(),
+        },
+    )
+}
+
+/// Address of the online player called `name`.
+fn player_address(world: &mut World, name: &str) -> Option<IpAddr> {
+    world
+        .query::<(&PlayerName, &PlayerSession)>()
+        .iter(world)
+        .find(|(player, _)| player.0.eq_ignore_ascii_case(name))
+        .map(|(_, session)| session.address.ip())
+}
+
+/// Append ` for <duration>` and `: <reason>` to a ban message.
+fn describe(mut message: String, entry: &BanEntry) -> String {
+    if let Some(expires) = entry.expires {
+        message.push_str(&format!(
+            " for {}",
+            format_duration(expires.saturating_sub(entry.created))
+        ));
+    }
+    if !entry.reason.is_empty() {
+        message.push_str(&format!(": {}", entry.reason));
+    }
+    message
+}
+
+/// Ban the players named by the `player` argument.
+fn ban_players(ctx: &mut CommandContext, args: &Arguments, expires: Option<u64>) -> CommandOutput {
+    let mut out = CommandOutput::default();
+    let Some(access) = access(ctx, &mut out) else {
+        return out;
+    };
+    let Some(target) = args.target("player") else {
+        return out;
+    };
+    let players = match target_players(ctx, target) {
+        Ok(players) => players,
+        Err(e) => {
+            out.error(e);
+            return out;
+        }
+    };
+
+    let now = unix_now();
+    let reason = args.string("reason").unwrap_or_default();
+    let entry = BanEntry::new(reason, ctx.sender.name(ctx.world), now)
+        .until(expires.map(|duration| now + duration));
+    let players: Vec<(String, Option<String>)> = players
+        .into_iter()
+        .map(|(name, entity)| {
+            let xuid = entity
+                .and_then(|entity| ctx.world.get::<PlayerSession>(entity))
+                .and_then(|session| session.xuid.clone());
+            (name, xuid)
+        })
+        .collect();
+    let result = access.update_bans(|bans| {
+        for (name, xuid) in &players {
+            bans.ban_player(name, xuid.as_deref(), entry.clone());
+        }
+    });
+    if let Err(e) = result {
+        out.error(e.to_string());
+        return out;
+    }
+
+    kick_denied_players(ctx.world);
+    for (name, _) in players {
+        out.message(describe(format!("Banned {name}"), &entry));
+    }
+    out
+}
+
+/// `/ban`: ban players by name, and by XUID when they are online.
+pub struct BanCommand;
+
+impl Command for BanCommand {
+    fn name(&self) -> &str {
+        "ban"
+    }
+
+    fn description(&self) -> &str {
+        "Ban a player from the server"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Admin
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![
+            Overload::new()
+                .param(Param::target("player"))
+                .param(Param::text("reason").optional()),
+        ]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        ban_players(ctx, args, None)
+    }
+}
+
+/// `/tempban`: ban players for a limited time.
+pub struct TempBanCommand;
+
+impl Command for TempBanCommand {
+    fn name(&self) -> &str {
+        "tempban"
+    }
+
+    fn description(&self) -> &str {
+        "Ban a player for a time, such as 30m, 12h or 7d"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Admin
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![
+            Overload::new()
+                .param(Param::target("player"))
+                .param(Param::string("duration"))
+                .param(Param::text("reason").optional()),
+        ]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let duration = args.string("duration").unwrap_or_default();
+        let Some(duration) = parse_duration(duration) else {
+            let mut out = CommandOutput::default();
+            out.error(format!(
+                "Invalid duration \"{duration}\", expected e.g. 30m, 12h or 1d12h"
+            ));
+            return out;
+        };
+        ban_players(ctx, args, Some(duration))
+    }
+}
+
+/// `/ban-ip`: ban an address, a CIDR range, or an online player's address.
+pub struct BanIpCommand;
+
+impl Command for BanIpCommand {
+    fn name(&self) -> &str {
+        "ban-ip"
+    }
+
+    fn description(&self) -> &str {
+        "Ban an IP address, range or online player's address"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Admin
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![
+            Overload::new()
+                .param(Param::string("target"))
+                .param(Param::text("reason").optional()),
+        ]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let mut out = CommandOutput::default();
+        let Some(access) = access(ctx, &mut out) else {
+            return out;
+        };
+        let target = args.string("target").unwrap_or_default();
+        let pattern = match target.parse::<IpPattern>() {
+            Ok(pattern) => pattern,
+            Err(e) => match player_address(ctx.world, target) {
+                Some(ip) => IpPattern::from(ip),
+                None => {
+                    out.error(format!("{e}, and no player by that name is online"));
+                    return out;
+                }
+            },
+        };
+
+        let reason = args.string("reason").unwrap_or_default();
+        let entry = BanEntry::new(reason, ctx.sender.name(ctx.world), unix_now());
+        if let Err(e) = access.update_bans(|bans| bans.ban_ip(pattern, entry.clone())) {
+            out.error(e.to_string());
+            return out;
+        }
+
+        let kicked = kick_denied_players(ctx.world);
+        out.message(describe(format!("Banned IP {pattern}"), &entry));
+        if !kicked.is_empty() {
+            out.message(format!("Kicked {}", kicked.join(", ")));
+        }
+        out
+    }
+}
+
+/// `/pardon`: lift a player ban.
+pub struct PardonCommand;
+
+impl Command for PardonCommand {
+    fn name(&self) -> &str {
+        "pardon"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["unban"]
+    }
+
+    fn description(&self) -> &str {
+        "Lift a player's ban"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Admin
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![Overload::new().param(Param::string("player"))]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let mut out = CommandOutput::default();
+        let Some(access) = access(ctx, &mut out) else {
+            return out;
+        };
+        let name = args.string("player").unwrap_or_default();
+        match access.update_bans(|bans| bans.pardon_player(name)) {
+            Ok(true) => out.message(format!("Unbanned {name}")),
+            Ok(false) => out.error(format!("{name} is not banned")),
+            Err(e) => out.error(e.to_string()),
+        }
+        out
+    }
+}
+
+/// `/pardon-ip`: lift an IP ban.
+pub struct PardonIpCommand;
+
+impl Command for PardonIpCommand {
+    fn name(&self) -> &str {
+        "pardon-ip"
+    }
+
+    fn description(&self) -> &str {
+        "Lift the ban on an IP address or range"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Admin
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![Overload::new().param(Param::string("address"))]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let mut out = CommandOutput::default();
+        let Some(access) = access(ctx, &mut out) else {
+            return out;
+        };
+        let pattern = match args.string("address").unwrap_or_default().parse() {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                out.error(e);
+                return out;
+            }
+        };
+        match access.update_bans(|bans| bans.pardon_ip(pattern)) {
+            Ok(true) => out.message(format!("Unbanned IP {pattern}")),
+            Ok(false) => out.error(format!("{pattern} is not banned")),
+            Err(e) => out.error(e.to_string()),
+        }
+        out
+    }
+}
+
+/// `/banlist`: list active player or IP bans.
+pub struct BanListCommand;
+
+impl Command for BanListCommand {
+    fn name(&self) -> &str {
+        "banlist"
+    }
+
+    fn description(&self) -> &str {
+        "List banned players or IP addresses"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Admin
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![
+            Overload::new()
+                .param(enum_param("type", "BanListType", &["players", "ips"]).optional()),
+        ]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let mut out = CommandOutput::default();
+        let Some(access) = access(ctx, &mut out) else {
+            return out;
+        };
+        let now = unix_now();
+        let bans = access.bans();
+        let (kind, entries) = match args.string("type") {
+            Some("ips") => ("IP", bans.ips(now)),
+            _ => ("player", bans.players(now)),
+        };
+        if entries.is_empty() {
+            out.message(format!("There are no {kind} bans"));
+            return out;
+        }
+        out.message(format!("There are {} {kind} ban(s):", entries.len()));
+        for (key, entry) in entries {
+            let mut line = describe(key.to_string(), entry);
+            if let Some(expires) = entry.expires {
+                line.push_str(&format!(
+                    " ({} left)",
+                    format_duration(expires.saturating_sub(now))
+                ));
+            }
+            out.message(line);
+        }
+        out
+    }
+}
+
+/// `/whitelist`: manage and toggle the whitelist.
+pub struct WhitelistCommand;
+
+impl WhitelistCommand {
+    fn update(
+        access: &AccessControl,
+        out: &mut CommandOutput,
+        f: impl FnOnce(&mut crate::access::Whitelist) -> Result<String, String>,
+    ) -> bool {
+        match access.update_whitelist(f) {
+            Ok(Ok(message)) => {
+                out.message(message);
+                true
+            }
+            Ok(Err(message)) => {
+                out.error(message);
+                false
+            }
+            Err(e) => {
+                out.error(e.to_string());
+                false
+            }
+        }
+    }
+}
+
+impl Command for WhitelistCommand {
+    fn name(&self) -> &str {
+        "whitelist"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["allowlist"]
+    }
+
+    fn description(&self) -> &str {
+        "Manage the server whitelist"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Admin
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![
+            Overload::new().param(enum_param(
+                "action",
+                "WhitelistAction",
+                &["on", "off", "list", "reload"],
+            )),
+            Overload::new()
+                .param(enum_param("edit", "WhitelistEdit", &["add", "remove"]))
+                .param(Param::target("player")),
+        ]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let mut out = CommandOutput::default();
+        let Some(access) = access(ctx, &mut out) else {
+            return out;
+        };
+
+        let changed = match (args.string("action"), args.string("edit")) {
+            (Some("on"), _) => Self::update(&access, &mut out, |whitelist| {
+                whitelist.enabled = true;
+                Ok("Turned on the whitelist".to_string())
+            }),
+            (Some("off"), _) => Self::update(&access, &mut out, |whitelist| {
+                whitelist.enabled = false;
+                Ok("Turned off the whitelist".to_string())
+            }),
+            (Some("list"), _) => {
+                let whitelist = access.whitelist();
+                let players: Vec<&str> = whitelist.players().collect();
+                out.message(format!(
+                    "The whitelist is {} and has {} player(s): {}",
+                    if whitelist.enabled { "on" } else { "off" },
+                    players.len(),
+                    players.join(", ")
+                ));
+                false
+            }
+            (Some("reload"), _) => match access.reload() {
+                Ok(_) => {
+                    out.message("Reloaded the whitelist and ban list");
+                    true
+                }
+                Err(e) => {
+                    out.error(e.to_string());
+                    false
+                }
+            },
+            (_, Some(edit)) => {
+                let Some(target) = args.target("player") else {
+                    return out;
+                };
+                let players = match target_players(ctx, target) {
+                    Ok(players) => players,
+                    Err(e) => {
+                        out.error(e);
+                        return out;
+                    }
+                };
+                let add = edit == "add";
+                let mut changed = false;
+                for (name, entity) in players {
+                    let xuid = entity.and_then(|entity| permission::player_xuid(ctx.world, entity));
+                    changed |= Self::update(&access, &mut out, |whitelist| match add {
+                        true if whitelist.add(&name, xuid) => {
+                            Ok(format!("Added {name} to the whitelist"))
+                        }
+                        true => Err(format!("{name} is already whitelisted")),
+                        false if whitelist.remove(&name, xuid) => {
+                            Ok(format!("Removed {name} from the whitelist"))
+                        }
+                        false => Err(format!("{name} is not whitelisted")),
+                    });
+                }
+                changed
+            }
+            _ => false,
+        };
+        if changed {
+            kick_denied_players(ctx.world);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{CommandRegistry, CommandSender};
+    use crate::entity::components::Player;
+    use crate::permission::Permissions;
+    use jolyne::valentine::McpePacketData;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_ban_and_whitelist() {
+        let mut world = World::new();
+        let permissions = Permissions::default();
+        permissions
+            .set_level("Alex", Some("xuid-Alex"), OpLevel::Owner)
+            .unwrap();
+        world.insert_resource(permissions);
+        let access = AccessControl::default();
+        world.insert_resource(access.clone());
+
+        let mut spawn = |name: &str, ip: &str| {
+            let (tx, rx) = mpsc::channel(8);
+            let session = PlayerSession::new(
+                1,
+                name.to_string(),
+                Some(format!("xuid-{name}")),
+                None,
+                format!("{ip}:19132").parse().unwrap(),
+                tx,
+            );
+            let entity = world
+                .spawn((Player, PlayerName(name.to_string()), session))
+                .id();
+            (entity, rx)
+        };
+        let (alex, _alex_rx) = spawn("Alex", "198.51.100.1");
+        let (_, mut steve_rx) = spawn("Steve", "198.51.100.2");
+        let sender = CommandSender::Player {
+            entity: alex,
+            session: 1,
+        };
+        let registry = CommandRegistry::with_defaults();
+
+        let output = registry.execute(&mut world, sender, "/tempban Steve 1d griefing");
+        assert_eq!(output.messages, vec!["Banned Steve for 1d: griefing"]);
+        let ban = access.bans();
+        assert!(ban.player_ban("steve", None, unix_now()).is_some());
+        assert!(
+            ban.player_ban("renamed", Some("xuid-Steve"), unix_now())
+                .is_some()
+        );
+        let packet = steve_rx.try_recv().unwrap();
+        assert!(matches!(packet.data, McpePacketData::PacketDisconnect(_)));
+
+        let output = registry.execute(&mut world, sender, "/pardon steve");
+        assert_eq!(output.messages, vec!["Unbanned steve"]);
+        let output = registry.execute(&mut world, sender, "/ban-ip 198.51.100.0/24");
+        assert_eq!(output.messages[0], "Banned IP 198.51.100.0/24");
+
+        let output = registry.execute(&mut world, sender, "/whitelist add Steve");
+        assert_eq!(output.messages, vec!["Added Steve to the whitelist"]);
+        let output = registry.execute(&mut world, sender, "/whitelist add Steve");
+        assert_eq!(output.errors, vec!["Steve is already whitelisted"]);
+        registry.execute(&mut world, sender, "/whitelist on");
+        assert!(access.whitelist().enabled);
+        assert!(!access.whitelist().allows("Bob", None));
+        assert!(access.whitelist().allows("Steve", Some("xuid-Steve")));
+        assert!(!access.whitelist().allows("Steve", None));
+    }
+}
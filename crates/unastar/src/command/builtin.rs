@@ -0,0 +1,300 @@
+//! Commands the server registers by default.
+
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use jolyne::valentine::MovePlayerPacketTeleportCause;
+
+use super::selector::feet_position;
+use super::{Arguments, Command, CommandContext, CommandOutput, Overload, Param, TargetArg};
+use crate::entity::components::{PLAYER_EYE_HEIGHT, Player, PlayerName, Position};
+use crate::permission::{self, OpLevel, Permissions};
+use crate::server::game::{sync_permissions, teleport_player};
+
+pub(super) fn display_name(world: &World, entity: Entity) -> String {
+    world
+        .get::<PlayerName>(entity)
+        .map_or_else(|| format!("entity {entity}"), |name| name.0.clone())
+}
+
+pub struct PosCommand;
+
+impl Command for PosCommand {
+    fn name(&self) -> &str {
+        "pos"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["position", "coords"]
+    }
+
+    fn description(&self) -> &str {
+        "Show your current position"
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, _args: &Arguments) -> CommandOutput {
+        let mut out = CommandOutput::default();
+        let Some(position) = ctx
+            .sender
+            .entity()
+            .and_then(|entity| feet_position(ctx.world, entity))
+        else {
+            out.error("Only players have a position");
+            return out;
+        };
+        out.message(format!(
+            "Position: {:.1}, {:.1}, {:.1} (chunk {}, {})",
+            position.x,
+            position.y,
+            position.z,
+            (position.x.floor() as i32) >> 4,
+            (position.z.floor() as i32) >> 4,
+        ));
+        out
+    }
+}
+
+/// `/tp`: move entities to a position or to another entity.
+pub struct TeleportCommand;
+
+impl Command for TeleportCommand {
+    fn name(&self) -> &str {
+        "tp"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["teleport"]
+    }
+
+    fn description(&self) -> &str {
+        "Teleport entities to a position or another entity"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Gamemaster
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![
+            Overload::new().param(Param::position("destination")),
+            Overload::new().param(Param::target("destination")),
+            Overload::new()
+                .param(Param::target("victim"))
+                .param(Param::position("destination")),
+            Overload::new()
+                .param(Param::target("victim"))
+                .param(Param::target("destination")),
+        ]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let mut out = CommandOutput::default();
+
+        let victims = match args.target("victim") {
+            Some(victim) => victim.resolve(ctx),
+            None => ctx
+                .sender
+                .entity()
+                .map(|entity| vec![entity])
+                .ok_or_else(|| "A victim is required".to_string()),
+        };
+        let victims = match victims {
+            Ok(victims) => victims,
+            Err(e) => {
+                out.error(e);
+                return out;
+            }
+        };
+
+        let destination = if let Some(position) = args.position("destination") {
+            ctx.resolve_position(position)
+        } else {
+            let target = args
+                .target("destination")
+                .map(|target| target.resolve(ctx))
+                .unwrap_or_else(|| Err("A destination is required".to_string()));
+            let position = match target {
+                Ok(entities) if entities.len() == 1 => feet_position(ctx.world, entities[0]),
+                Ok(_) => {
+                    out.error("The destination must be a single entity");
+                    return out;
+                }
+                Err(e) => {
+                    out.error(e);
+                    return out;
+                }
+            };
+            let Some(position) = position else {
+                out.error("The destination has no position");
+                return out;
+            };
+            position
+        };
+
+        for &victim in &victims {
+            if ctx.world.get::<Player>(victim).is_some() {
+                teleport_player(
+                    ctx.world,
+                    victim,
+                    destination + DVec3::Y * PLAYER_EYE_HEIGHT,
+                    None,
+                    MovePlayerPacketTeleportCause::Command,
+                );
+            } else if let Some(mut position) = ctx.world.get_mut::<Position>(victim) {
+                position.0 = destination;
+            }
+        }
+
+        let name = match victims.as_slice() {
+            [victim] => display_name(ctx.world, *victim),
+            _ => format!("{} entities", victims.len()),
+        };
+        out.message(format!(
+            "Teleported {name} to {:.1}, {:.1}, {:.1}",
+            destination.x, destination.y, destination.z
+        ));
+        out
+    }
+}
+
+/// Players named by a target, with their entity when online.
+///
+/// A plain name that isn't online is still accepted, so offline players can
+/// be opped and deopped.
+pub(super) fn target_players(
+    ctx: &mut CommandContext,
+    target: &TargetArg,
+) -> Result<Vec<(String, Option<Entity>)>, String> {
+    match target.resolve(ctx) {
+        Ok(entities) => Ok(entities
+            .into_iter()
+            .filter_map(|entity| {
+                let name = ctx.world.get::<PlayerName>(entity)?;
+                Some((name.0.clone(), Some(entity)))
+            })
+            .collect()),
+        Err(_) if !target.is_selector() => Ok(vec![(target.raw().to_string(), None)]),
+        Err(e) => Err(e),
+    }
+}
+
+/// Set the op level of each player named by `target`.
+fn set_op_level(ctx: &mut CommandContext, target: &TargetArg, level: OpLevel) -> CommandOutput {
+    let mut out = CommandOutput::default();
+    let Some(permissions) = ctx.world.get_resource::<Permissions>().cloned() else {
+        out.error("Permissions are not available");
+        return out;
+    };
+    let sender_level = ctx.sender.op_level(ctx.world);
+    if level > sender_level {
+        out.error(format!(
+            "You can't grant an op level above your own ({})",
+            sender_level as u8
+        ));
+        return out;
+    }
+    let players = match target_players(ctx, target) {
+        Ok(players) => players,
+        Err(e) => {
+            out.error(e);
+            return out;
+        }
+    };
+
+    for (name, entity) in players {
+        let xuid = match entity {
+            Some(entity) => permission::player_xuid(ctx.world, entity).map(str::to_string),
+            None => permissions.known_xuid(&name),
+        };
+        if permissions.level(&name, xuid.as_deref()) > sender_level {
+            out.error(format!("{name} has a higher op level than you"));
+            continue;
+        }
+        if let Err(e) = permissions.set_level(&name, xuid.as_deref(), level) {
+            out.error(e.to_string());
+            continue;
+        }
+        if let Some(entity) = entity {
+            sync_permissions(ctx.world, ctx.commands, entity);
+        }
+        if level.is_op() {
+            out.message(format!(
+                "Made {name} a server operator (level {})",
+                level as u8
+            ));
+        } else {
+            out.message(format!("Made {name} no longer a server operator"));
+        }
+    }
+    out
+}
+
+/// `/op`: make players operators.
+pub struct OpCommand;
+
+impl Command for OpCommand {
+    fn name(&self) -> &str {
+        "op"
+    }
+
+    fn description(&self) -> &str {
+        "Grant operator status to a player"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Admin
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![
+            Overload::new()
+                .param(Param::target("player"))
+                .param(Param::int("level").optional()),
+        ]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let level = match args.int("level") {
+            Some(level) => match u8::try_from(level).ok().and_then(OpLevel::from_u8) {
+                Some(OpLevel::None) | None => {
+                    let mut out = CommandOutput::default();
+                    out.error("The op level must be between 1 and 4");
+                    return out;
+                }
+                Some(level) => level,
+            },
+            None => OpLevel::Owner,
+        };
+        let Some(target) = args.target("player") else {
+            return CommandOutput::default();
+        };
+        set_op_level(ctx, target, level)
+    }
+}
+
+/// `/deop`: remove operator status.
+pub struct DeopCommand;
+
+impl Command for DeopCommand {
+    fn name(&self) -> &str {
+        "deop"
+    }
+
+    fn description(&self) -> &str {
+        "Revoke operator status from a player"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Admin
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![Overload::new().param(Param::target("player"))]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let Some(target) = args.target("player") else {
+            return CommandOutput::default();
+        };
+        set_op_level(ctx, target, OpLevel::None)
+    }
+}
@@ -2,8 +2,41 @@
 //!
 //! This module provides a small, extensible command dispatcher inspired by Dragonfly,
 //! but designed around Rust's type system and ownership rules (no reflection).
-
+//!
+//! Commands declare their overloads up front. Arguments are matched against
+//! them before `execute` runs, and the same overloads are sent to the client
+//! in `AvailableCommands` for autocompletion.
+
+mod access;
+mod builtin;
+mod overload;
+mod packet;
+mod plugin;
+mod position;
+mod selector;
+mod server;
+mod target;
+mod world;
+
+pub use access::{
+    BanCommand, BanIpCommand, BanListCommand, PardonCommand, PardonIpCommand, TempBanCommand,
+    WhitelistCommand,
+};
+pub use builtin::{DeopCommand, OpCommand, PosCommand, TeleportCommand};
+pub use overload::{ArgError, ArgValue, Arguments, Overload, Param, ParamKind};
+pub use plugin::PluginCommand;
+pub use position::{Coordinate, PositionArg};
+pub use selector::{Selector, SelectorKind};
+pub use server::{ReloadCommand, SaveAllCommand, StopCommand};
+pub use target::TargetArg;
+pub use world::{DifficultyCommand, GameRuleCommand, TimeCommand, WeatherCommand, WorldCommand};
+
+use crate::entity::components::{PlayerName, Rotation};
 use crate::network::SessionId;
+use crate::permission::{self, OpLevel, Permissions};
+use crate::world::WorldId;
+use bevy_ecs::prelude::*;
+use glam::DVec3;
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -25,6 +58,14 @@ impl CommandOutput {
     pub fn is_empty(&self) -> bool {
         self.messages.is_empty() && self.errors.is_empty()
     }
+
+    /// Messages followed by errors, as shown to text-only senders.
+    pub fn lines(&self) -> impl Iterator<Item = String> + '_ {
+        self.messages
+            .iter()
+            .cloned()
+            .chain(self.errors.iter().map(|error| format!("Error: {error}")))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -51,38 +92,153 @@ impl CommandArgs {
     pub fn is_empty(&self) -> bool {
         self.index >= self.tokens.len()
     }
+
+    /// Index of the next token.
+    pub fn position(&self) -> usize {
+        self.index
+    }
+}
+
+/// Who is running a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandSender {
+    Player {
+        entity: Entity,
+        session: SessionId,
+    },
+    /// The server console, which holds every permission.
+    Console,
+    /// A remote administration client, with the same rights as the console.
+    Rcon,
+}
+
+impl CommandSender {
+    /// The sender's entity, if it has one.
+    pub fn entity(&self) -> Option<Entity> {
+        match self {
+            CommandSender::Player { entity, .. } => Some(*entity),
+            CommandSender::Console | CommandSender::Rcon => None,
+        }
+    }
+
+    /// Name of the sender, as shown in messages and ban entries.
+    pub fn name(&self, world: &World) -> String {
+        match self {
+            CommandSender::Player { entity, .. } => world
+                .get::<PlayerName>(*entity)
+                .map_or_else(|| "Unknown".to_string(), |name| name.0.clone()),
+            CommandSender::Console => "Server".to_string(),
+            CommandSender::Rcon => "Rcon".to_string(),
+        }
+    }
+
+    /// The sender's op level.
+    pub fn op_level(&self, world: &World) -> OpLevel {
+        match self {
+            CommandSender::Player { entity, .. } => permission::op_level(world, *entity),
+            CommandSender::Console | CommandSender::Rcon => OpLevel::Owner,
+        }
+    }
+
+    /// Whether the sender holds the command's permission node.
+    pub fn can_use(&self, world: &World, command: &dyn Command) -> bool {
+        match self {
+            CommandSender::Player { entity, .. } => {
+                match (
+                    world.get::<PlayerName>(*entity),
+                    world.get_resource::<Permissions>(),
+                ) {
+                    (Some(name), Some(permissions)) => permissions.has(
+                        &name.0,
+                        permission::player_xuid(world, *entity),
+                        &command.permission(),
+                        command.default_level(),
+                    ),
+                    _ => command.default_level() == OpLevel::None,
+                }
+            }
+            CommandSender::Console | CommandSender::Rcon => true,
+        }
+    }
 }
 
 /// Context for command execution.
 ///
-/// Note: Command execution in ECS is simplified - commands don't have
-/// direct server access. Use systems for complex game logic.
-pub struct CommandContext {
-    pub sender: SessionId,
+/// Commands get mutable access to the ECS world for the duration of `execute`.
+pub struct CommandContext<'w> {
+    pub sender: CommandSender,
+    pub world: &'w mut World,
+    /// The registry the command was dispatched from.
+    pub commands: &'w CommandRegistry,
 }
 
-impl CommandContext {
-    pub fn new(sender: SessionId) -> Self {
-        Self { sender }
+impl<'w> CommandContext<'w> {
+    pub fn new(sender: CommandSender, world: &'w mut World, commands: &'w CommandRegistry) -> Self {
+        Self {
+            sender,
+            world,
+            commands,
+        }
+    }
+
+    /// Feet position that relative coordinates are resolved against.
+    pub fn origin(&self) -> DVec3 {
+        self.sender
+            .entity()
+            .and_then(|entity| selector::feet_position(self.world, entity))
+            .unwrap_or(DVec3::ZERO)
+    }
+
+    /// World the sender is in; the default world for the console and RCON.
+    pub fn world_id(&self) -> WorldId {
+        self.sender
+            .entity()
+            .map_or(WorldId::DEFAULT, |entity| WorldId::of(self.world, entity))
+    }
+
+    /// View rotation that local coordinates are resolved against.
+    pub fn rotation(&self) -> Rotation {
+        self.sender
+            .entity()
+            .and_then(|entity| self.world.get::<Rotation>(entity))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Resolve a position argument against the sender.
+    pub fn resolve_position(&self, position: &PositionArg) -> DVec3 {
+        let rotation = self.rotation();
+        position.resolve(self.origin(), rotation.yaw, rotation.pitch)
     }
 }
 
 pub trait Command: Send + Sync + 'static {
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &str;
 
-    fn aliases(&self) -> &'static [&'static str] {
-        &[]
+    fn aliases(&self) -> Vec<&str> {
+        Vec::new()
     }
 
-    fn description(&self) -> &'static str {
+    fn description(&self) -> &str {
         ""
     }
 
-    fn usage(&self) -> &'static str {
-        ""
+    /// Permission node checked before the command runs.
+    fn permission(&self) -> String {
+        format!("unastar.command.{}", self.name().to_ascii_lowercase())
+    }
+
+    /// Op level that holds the permission node unless it is revoked.
+    fn default_level(&self) -> OpLevel {
+        OpLevel::None
     }
 
-    fn execute(&self, ctx: &CommandContext, args: &mut CommandArgs) -> CommandOutput;
+    /// Accepted parameter lists, tried in order. Defaults to no parameters.
+    fn overloads(&self) -> Vec<Overload> {
+        vec![Overload::new()]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput;
 }
 
 #[derive(Default)]
@@ -111,6 +267,24 @@ impl CommandRegistry {
     pub fn with_defaults() -> Self {
         let mut registry = Self::new();
         registry.register(PosCommand);
+        registry.register(TeleportCommand);
+        registry.register(OpCommand);
+        registry.register(DeopCommand);
+        registry.register(BanCommand);
+        registry.register(TempBanCommand);
+        registry.register(BanIpCommand);
+        registry.register(PardonCommand);
+        registry.register(PardonIpCommand);
+        registry.register(BanListCommand);
+        registry.register(WhitelistCommand);
+        registry.register(StopCommand);
+        registry.register(SaveAllCommand);
+        registry.register(ReloadCommand);
+        registry.register(TimeCommand);
+        registry.register(WeatherCommand);
+        registry.register(DifficultyCommand);
+        registry.register(GameRuleCommand);
+        registry.register(WorldCommand);
         registry
     }
 
@@ -122,7 +296,7 @@ impl CommandRegistry {
         let name = command.name().to_ascii_lowercase();
         self.commands.insert(name, command.clone());
 
-        for &alias in command.aliases() {
+        for alias in command.aliases() {
             self.commands
                 .insert(alias.to_ascii_lowercase(), command.clone());
         }
@@ -131,6 +305,53 @@ impl CommandRegistry {
     pub fn find(&self, name: &str) -> Option<Arc<dyn Command>> {
         self.commands.get(&name.to_ascii_lowercase()).cloned()
     }
+
+    /// Every registered command once, sorted by name.
+    pub fn commands(&self) -> Vec<Arc<dyn Command>> {
+        let mut commands: Vec<_> = self
+            .commands
+            .iter()
+            .filter(|(key, command)| command.name().eq_ignore_ascii_case(key))
+            .map(|(_, command)| command.clone())
+            .collect();
+        commands.sort_by_key(|command| command.name().to_ascii_lowercase());
+        commands
+    }
+
+    /// Parse and run a command line.
+    pub fn execute(&self, world: &mut World, sender: CommandSender, line: &str) -> CommandOutput {
+        let mut output = CommandOutput::default();
+        let invocation = match parse_command_line(line) {
+            Ok(invocation) => invocation,
+            Err(CommandParseError::Empty) => return output,
+            Err(e) => {
+                output.error(e.to_string());
+                return output;
+            }
+        };
+        let Some(command) = self.find(&invocation.name) else {
+            output.error(format!("Unknown command: {}", invocation.name));
+            return output;
+        };
+        if !sender.can_use(world, command.as_ref()) {
+            output.error("You do not have permission to use this command");
+            return output;
+        }
+
+        let overloads = command.overloads();
+        let args = match Arguments::parse(&overloads, &invocation.args) {
+            Ok(args) => args,
+            Err(e) => {
+                output.error(e.message);
+                for overload in &overloads {
+                    output.error(format!("Usage: {}", overload.usage(command.name())));
+                }
+                return output;
+            }
+        };
+        let mut ctx = CommandContext::new(sender, world, self);
+        command.execute(&mut ctx, &args)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -211,38 +432,6 @@ fn split_tokens(input: &str) -> Result<Vec<String>, CommandParseError> {
     Ok(tokens)
 }
 
-struct PosCommand;
-
-impl Command for PosCommand {
-    fn name(&self) -> &'static str {
-        "pos"
-    }
-
-    fn aliases(&self) -> &'static [&'static str] {
-        &["position", "coords"]
-    }
-
-    fn description(&self) -> &'static str {
-        "Show your current position"
-    }
-
-    fn usage(&self) -> &'static str {
-        "/pos"
-    }
-
-    fn execute(&self, _ctx: &CommandContext, args: &mut CommandArgs) -> CommandOutput {
-        let mut out = CommandOutput::default();
-        if !args.is_empty() {
-            out.error(format!("Usage: {}", self.usage()));
-            return out;
-        }
-
-        // Position will be fetched from ECS by the server when executing
-        out.message("Use a system to query position from ECS");
-        out
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -269,4 +458,54 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn registry_executes_typed_commands() {
+        use crate::entity::components::{
+            PLAYER_EYE_HEIGHT, Player, PlayerName, Position, RuntimeEntityId,
+        };
+
+        let mut world = World::new();
+        let permissions = Permissions::default();
+        world.insert_resource(permissions.clone());
+        let entity = world
+            .spawn((
+                Player,
+                PlayerName("Steve".to_string()),
+                RuntimeEntityId(1),
+                Position(DVec3::new(0.5, 64.0 + PLAYER_EYE_HEIGHT, 0.5)),
+            ))
+            .id();
+        let sender = CommandSender::Player { entity, session: 1 };
+        let registry = CommandRegistry::with_defaults();
+
+        let output = registry.execute(&mut world, sender, "/tp ~10 ~ -3");
+        assert_eq!(
+            output.errors,
+            vec!["You do not have permission to use this command"]
+        );
+        permissions
+            .set_level("Steve", None, OpLevel::Gamemaster)
+            .unwrap();
+
+        let output = registry.execute(&mut world, sender, "/tp ~10 ~ -3");
+        assert!(output.errors.is_empty(), "{:?}", output.errors);
+        let position = world.get::<Position>(entity).unwrap().0;
+        assert_eq!(position, DVec3::new(10.5, 64.0 + PLAYER_EYE_HEIGHT, -3.0));
+
+        let output = registry.execute(&mut world, sender, "/coords");
+        assert_eq!(
+            output.messages,
+            vec!["Position: 10.5, 64.0, -3.0 (chunk 0, -1)"]
+        );
+
+        let output = registry.execute(&mut world, sender, "/teleport Alex");
+        assert_eq!(output.errors, vec!["No player named \"Alex\" is online"]);
+
+        let output = registry.execute(&mut world, sender, "/tp 1 2");
+        assert!(output.errors.iter().any(|e| e.starts_with("Usage: /tp")));
+
+        let output = registry.execute(&mut world, sender, "/nope");
+        assert_eq!(output.errors, vec!["Unknown command: nope"]);
+    }
 }
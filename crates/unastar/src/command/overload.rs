@@ -0,0 +1,399 @@
+//! Command overloads and typed parameters.
+
+use super::CommandArgs;
+use super::position::PositionArg;
+use super::selector::Selector;
+use super::target::TargetArg;
+
+/// Type of a command parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamKind {
+    Int,
+    Float,
+    /// Three coordinates, each absolute, relative (`~`) or local (`^`).
+    Position,
+    /// A player name or target selector.
+    Target,
+    /// One of a fixed set of words. `name` identifies the enum to the client.
+    Enum {
+        name: String,
+        values: Vec<String>,
+    },
+    /// A single word or quoted string.
+    String,
+    /// The rest of the command line.
+    Text,
+}
+
+impl ParamKind {
+    /// Short name of the type shown in usage strings.
+    fn type_name(&self) -> String {
+        match self {
+            ParamKind::Int => "int".to_string(),
+            ParamKind::Float => "float".to_string(),
+            ParamKind::Position => "x y z".to_string(),
+            ParamKind::Target => "target".to_string(),
+            ParamKind::Enum { values, .. } => values.join("|"),
+            ParamKind::String => "string".to_string(),
+            ParamKind::Text => "text".to_string(),
+        }
+    }
+}
+
+/// A named parameter of an overload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub kind: ParamKind,
+    pub optional: bool,
+}
+
+impl Param {
+    pub fn new(name: impl Into<String>, kind: ParamKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            optional: false,
+        }
+    }
+
+    pub fn int(name: impl Into<String>) -> Self {
+        Self::new(name, ParamKind::Int)
+    }
+
+    pub fn float(name: impl Into<String>) -> Self {
+        Self::new(name, ParamKind::Float)
+    }
+
+    pub fn position(name: impl Into<String>) -> Self {
+        Self::new(name, ParamKind::Position)
+    }
+
+    pub fn target(name: impl Into<String>) -> Self {
+        Self::new(name, ParamKind::Target)
+    }
+
+    pub fn string(name: impl Into<String>) -> Self {
+        Self::new(name, ParamKind::String)
+    }
+
+    pub fn text(name: impl Into<String>) -> Self {
+        Self::new(name, ParamKind::Text)
+    }
+
+    /// An enum parameter. The parameter name doubles as the enum name.
+    pub fn enumeration(name: impl Into<String>, values: &[&str]) -> Self {
+        let name = name.into();
+        let kind = ParamKind::Enum {
+            name: name.clone(),
+            values: values.iter().map(|value| value.to_string()).collect(),
+        };
+        Self::new(name, kind)
+    }
+
+    /// Mark the parameter as optional. Only trailing parameters may be optional.
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    fn usage(&self) -> String {
+        let inner = format!("{}: {}", self.name, self.kind.type_name());
+        if self.optional {
+            format!("[{inner}]")
+        } else {
+            format!("<{inner}>")
+        }
+    }
+}
+
+/// One accepted parameter list of a command.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Overload {
+    pub params: Vec<Param>,
+}
+
+impl Overload {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn param(mut self, param: Param) -> Self {
+        self.params.push(param);
+        self
+    }
+
+    /// Usage line for this overload, e.g. `/tp <destination: x y z>`.
+    pub fn usage(&self, command: &str) -> String {
+        std::iter::once(format!("/{command}"))
+            .chain(self.params.iter().map(Param::usage))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Parse arguments against this overload.
+    pub fn parse(&self, args: &mut CommandArgs) -> Result<Vec<(String, ArgValue)>, ArgError> {
+        let mut values = Vec::with_capacity(self.params.len());
+        for param in &self.params {
+            if args.is_empty() {
+                if param.optional {
+                    break;
+                }
+                return Err(ArgError::new(
+                    args.position(),
+                    format!("Missing {}", param.usage()),
+                ));
+            }
+            let start = args.position();
+            let value =
+                parse_value(&param.kind, args).map_err(|message| ArgError::new(start, message))?;
+            values.push((param.name.clone(), value));
+        }
+        let position = args.position();
+        if let Some(extra) = args.next() {
+            return Err(ArgError::new(position, format!("Unexpected \"{extra}\"")));
+        }
+        Ok(values)
+    }
+}
+
+fn parse_value(kind: &ParamKind, args: &mut CommandArgs) -> Result<ArgValue, String> {
+    if matches!(kind, ParamKind::Position) {
+        return PositionArg::parse(args).map(ArgValue::Position);
+    }
+    if matches!(kind, ParamKind::Text) {
+        let text = args.rest().join(" ");
+        while args.next().is_some() {}
+        return Ok(ArgValue::Text(text));
+    }
+    if matches!(kind, ParamKind::Target) {
+        return parse_target(args).map(ArgValue::Target);
+    }
+
+    let token = args.next().unwrap_or_default();
+    match kind {
+        ParamKind::Int => token
+            .parse()
+            .map(ArgValue::Int)
+            .map_err(|_| format!("\"{token}\" is not a valid integer")),
+        ParamKind::Float => token
+            .parse()
+            .map(ArgValue::Float)
+            .map_err(|_| format!("\"{token}\" is not a valid number")),
+        ParamKind::Enum { values, .. } => values
+            .iter()
+            .find(|value| value.eq_ignore_ascii_case(token))
+            .map(|value| ArgValue::Enum(value.clone()))
+            .ok_or_else(|| format!("\"{token}\" is not one of {}", values.join(", "))),
+        ParamKind::String => Ok(ArgValue::String(token.to_string())),
+        ParamKind::Position | ParamKind::Target | ParamKind::Text => unreachable!(),
+    }
+}
+
+/// Parse a player name or selector.
+///
+/// A selector whose filters contain spaces spans several tokens and is
+/// joined back together.
+fn parse_target(args: &mut CommandArgs) -> Result<TargetArg, String> {
+    let mut text = args.next().unwrap_or_default().to_string();
+    if text.starts_with('@') {
+        while text.contains('[') && !text.ends_with(']') {
+            let Some(next) = args.next() else {
+                break;
+            };
+            text.push(' ');
+            text.push_str(next);
+        }
+        Selector::parse(&text)?;
+        return Ok(TargetArg::new(text));
+    }
+    // Gamertags start with a letter, which keeps coordinates from
+    // matching target overloads
+    if !text.starts_with(char::is_alphabetic) {
+        return Err(format!("\"{text}\" is not a player name or selector"));
+    }
+    Ok(TargetArg::new(text))
+}
+
+/// A parsed argument value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgValue {
+    Int(i32),
+    Float(f32),
+    Position(PositionArg),
+    Target(TargetArg),
+    Enum(String),
+    String(String),
+    Text(String),
+}
+
+/// Error from matching arguments against an overload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgError {
+    /// Index of the token the error is about.
+    pub position: usize,
+    pub message: String,
+}
+
+impl ArgError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+/// Arguments of a command invocation, matched against one of its overloads.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Arguments {
+    overload: usize,
+    values: Vec<(String, ArgValue)>,
+}
+
+impl Arguments {
+    /// Match arguments against the overloads in order and return the first that fits.
+    ///
+    /// If none fits, the error of the overload that got furthest is returned.
+    pub fn parse(overloads: &[Overload], args: &CommandArgs) -> Result<Self, ArgError> {
+        let mut best: Option<ArgError> = None;
+        for (index, overload) in overloads.iter().enumerate() {
+            match overload.parse(&mut args.clone()) {
+                Ok(values) => {
+                    return Ok(Self {
+                        overload: index,
+                        values,
+                    });
+                }
+                Err(error) => {
+                    if best
+                        .as_ref()
+                        .is_none_or(|best| error.position > best.position)
+                    {
+                        best = Some(error);
+                    }
+                }
+            }
+        }
+        Err(best.unwrap_or_else(|| ArgError::new(0, "Command takes no arguments")))
+    }
+
+    /// Index of the matched overload.
+    pub fn overload(&self) -> usize {
+        self.overload
+    }
+
+    /// Values in parameter order. Missing optional parameters are left out.
+    pub fn values(&self) -> &[(String, ArgValue)] {
+        &self.values
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ArgValue> {
+        self.values
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn int(&self, name: &str) -> Option<i32> {
+        match self.get(name)? {
+            ArgValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn float(&self, name: &str) -> Option<f32> {
+        match self.get(name)? {
+            ArgValue::Float(value) => Some(*value),
+            ArgValue::Int(value) => Some(*value as f32),
+            _ => None,
+        }
+    }
+
+    pub fn position(&self, name: &str) -> Option<&PositionArg> {
+        match self.get(name)? {
+            ArgValue::Position(position) => Some(position),
+            _ => None,
+        }
+    }
+
+    pub fn target(&self, name: &str) -> Option<&TargetArg> {
+        match self.get(name)? {
+            ArgValue::Target(target) => Some(target),
+            _ => None,
+        }
+    }
+
+    /// Text of an enum, string or text argument.
+    pub fn string(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            ArgValue::Enum(value) | ArgValue::String(value) | ArgValue::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> CommandArgs {
+        CommandArgs::new(line.split_whitespace().map(str::to_string).collect())
+    }
+
+    #[test]
+    fn test_overload_matching() {
+        let overloads = [
+            Overload::new().param(Param::position("destination")),
+            Overload::new()
+                .param(Param::target("victim"))
+                .param(Param::enumeration("mode", &["survival", "creative"]).optional()),
+        ];
+
+        let parsed = Arguments::parse(&overloads, &args("~ ~1 5")).unwrap();
+        assert_eq!(parsed.overload(), 0);
+        assert!(parsed.position("destination").is_some());
+
+        let parsed = Arguments::parse(&overloads, &args("Steve CREATIVE")).unwrap();
+        assert_eq!(parsed.overload(), 1);
+        assert_eq!(parsed.target("victim").unwrap().raw(), "Steve");
+        assert_eq!(parsed.string("mode"), Some("creative"));
+
+        let parsed = Arguments::parse(&overloads, &args("Steve")).unwrap();
+        assert!(!parsed.has("mode"));
+
+        let parsed = Arguments::parse(&overloads, &args("@e[type=pig, r=5] survival")).unwrap();
+        assert_eq!(parsed.target("victim").unwrap().raw(), "@e[type=pig, r=5]");
+        assert_eq!(parsed.string("mode"), Some("survival"));
+        assert!(Arguments::parse(&overloads, &args("@e[bogus=1]")).is_err());
+
+        let error = Arguments::parse(&overloads, &args("Steve adventure")).unwrap_err();
+        assert_eq!(error.position, 1);
+        assert!(Arguments::parse(&overloads, &args("")).is_err());
+    }
+
+    #[test]
+    fn test_typed_values() {
+        let overload = Overload::new()
+            .param(Param::int("count"))
+            .param(Param::float("scale"))
+            .param(Param::text("message"));
+        let values = overload.parse(&mut args("3 1.5 hello there")).unwrap();
+        assert_eq!(values[0].1, ArgValue::Int(3));
+        assert_eq!(values[1].1, ArgValue::Float(1.5));
+        assert_eq!(values[2].1, ArgValue::Text("hello there".to_string()));
+
+        let error = overload.parse(&mut args("three")).unwrap_err();
+        assert_eq!(error.position, 0);
+        let error = Overload::new().parse(&mut args("extra")).unwrap_err();
+        assert_eq!(error.message, "Unexpected \"extra\"");
+        assert_eq!(
+            overload.usage("demo"),
+            "/demo <count: int> <scale: float> <message: text>"
+        );
+    }
+}
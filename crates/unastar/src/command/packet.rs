@@ -0,0 +1,260 @@
+//! `AvailableCommands` packet generation.
+//!
+//! The client autocompletes commands from this packet, so it is built from
+//! the same overloads the server parses arguments with.
+
+use std::collections::HashMap;
+
+use jolyne::valentine::AvailableCommandsPacket;
+use jolyne::valentine::types::{
+    AvailableCommandsPacketCommandDataItem as CommandData,
+    AvailableCommandsPacketCommandDataItemOverloadsItem as OverloadData,
+    AvailableCommandsPacketCommandDataItemOverloadsItemParametersItem as ParamData,
+    AvailableCommandsPacketCommandDataItemOverloadsItemParametersItemEnumType as ParamFlags,
+    AvailableCommandsPacketCommandDataItemOverloadsItemParametersItemValueType as ParamType,
+    AvailableCommandsPacketEnumsItem as EnumData,
+};
+
+use super::{Command, CommandRegistry, ParamKind};
+
+/// Enum indices a parameter can refer to.
+///
+/// An enum parameter sends the index of its enum where other parameters send
+/// their value type, but the protocol crate only models the named value
+/// types. Parameter enums are therefore placed at the indices that coincide
+/// with one; alias enums and empty padding fill the slots in between.
+const PARAM_ENUM_SLOTS: [ParamType; 19] = [
+    ParamType::Int,
+    ParamType::Float,
+    ParamType::Value,
+    ParamType::WildcardInt,
+    ParamType::Operator,
+    ParamType::CommandOperator,
+    ParamType::Target,
+    ParamType::WildcardTarget,
+    ParamType::FilePath,
+    ParamType::IntegerRange,
+    ParamType::EquipmentSlots,
+    ParamType::String,
+    ParamType::BlockPosition,
+    ParamType::Position,
+    ParamType::Message,
+    ParamType::RawText,
+    ParamType::Json,
+    ParamType::BlockStates,
+    ParamType::Command,
+];
+
+/// Enum values and enums of the packet being built.
+#[derive(Default)]
+struct EnumTable {
+    values: Vec<String>,
+    value_indices: HashMap<String, u32>,
+    enums: Vec<Option<EnumData>>,
+    param_enums: HashMap<String, ParamType>,
+}
+
+impl EnumTable {
+    fn enum_data(&mut self, name: &str, values: &[String]) -> EnumData {
+        let values = values
+            .iter()
+            .map(|value| {
+                *self.value_indices.entry(value.clone()).or_insert_with(|| {
+                    self.values.push(value.clone());
+                    self.values.len() as u32 - 1
+                })
+            })
+            .collect();
+        EnumData {
+            name: name.to_string(),
+            values,
+        }
+    }
+
+    /// Add a parameter enum, returning the type its parameters are sent with.
+    ///
+    /// Returns `None` once every slot is taken.
+    fn param_enum(&mut self, name: &str, values: &[String]) -> Option<ParamType> {
+        if let Some(slot) = self.param_enums.get(name) {
+            return Some(*slot);
+        }
+        let slot = *PARAM_ENUM_SLOTS.get(self.param_enums.len())?;
+        let index = slot as usize;
+        if self.enums.len() <= index {
+            self.enums.resize(index + 1, None);
+        }
+        self.enums[index] = Some(self.enum_data(name, values));
+        self.param_enums.insert(name.to_string(), slot);
+        Some(slot)
+    }
+
+    /// Add a command's alias enum in the first free slot and return its index.
+    fn alias_enum(&mut self, name: &str, values: &[String]) -> i32 {
+        let data = Some(self.enum_data(name, values));
+        match self.enums.iter().position(Option::is_none) {
+            Some(index) => {
+                self.enums[index] = data;
+                index as i32
+            }
+            None => {
+                self.enums.push(data);
+                self.enums.len() as i32 - 1
+            }
+        }
+    }
+
+    fn finish(self) -> (Vec<String>, Vec<EnumData>) {
+        let enums = self
+            .enums
+            .into_iter()
+            .enumerate()
+            .map(|(index, data)| {
+                data.unwrap_or_else(|| EnumData {
+                    name: format!("unused{index}"),
+                    values: Vec::new(),
+                })
+            })
+            .collect();
+        (self.values, enums)
+    }
+}
+
+impl CommandRegistry {
+    /// Build the `AvailableCommands` packet for the registered commands
+    /// `allowed` accepts, usually those the receiving player may use.
+    pub fn to_packet(&self, allowed: impl Fn(&dyn Command) -> bool) -> AvailableCommandsPacket {
+        let mut commands = self.commands();
+        commands.retain(|command| allowed(command.as_ref()));
+        let mut table = EnumTable::default();
+
+        // Parameter enums first, so alias enums can't take their slots
+        let overloads: Vec<_> = commands.iter().map(|command| command.overloads()).collect();
+        for param in overloads
+            .iter()
+            .flatten()
+            .flat_map(|overload| &overload.params)
+        {
+            if let ParamKind::Enum { name, values } = &param.kind {
+                table.param_enum(name, values);
+            }
+        }
+
+        let mut command_data = Vec::with_capacity(commands.len());
+        for (command, overloads) in commands.iter().zip(&overloads) {
+            let name = command.name().to_ascii_lowercase();
+            let aliases: Vec<String> = command
+                .aliases()
+                .iter()
+                .map(|alias| alias.to_ascii_lowercase())
+                .collect();
+            let alias = if aliases.is_empty() {
+                -1
+            } else {
+                let values: Vec<String> = std::iter::once(name.clone()).chain(aliases).collect();
+                table.alias_enum(&format!("{name}Aliases"), &values)
+            };
+
+            let overloads = overloads
+                .iter()
+                .map(|overload| OverloadData {
+                    chaining: false,
+                    parameters: overload
+                        .params
+                        .iter()
+                        .map(|param| {
+                            let (value_type, enum_type) = match &param.kind {
+                                ParamKind::Enum { name, values } => {
+                                    match table.param_enum(name, values) {
+                                        Some(slot) => (slot, ParamFlags::Enum),
+                                        None => (ParamType::String, ParamFlags::Valid),
+                                    }
+                                }
+                                kind => (value_type(kind), ParamFlags::Valid),
+                            };
+                            ParamData {
+                                parameter_name: param.name.clone(),
+                                value_type,
+                                enum_type,
+                                optional: param.optional,
+                                options: 0,
+                            }
+                        })
+                        .collect(),
+                })
+                .collect();
+
+            command_data.push(CommandData {
+                name,
+                description: command.description().to_string(),
+                flags: 0,
+                permission_level: command.default_level().command_level_name().to_string(),
+                alias,
+                chained_subcommand_offsets: Vec::new(),
+                overloads,
+            });
+        }
+
+        let (enum_values, enums) = table.finish();
+        AvailableCommandsPacket {
+            values_len: enum_values.len() as i32,
+            enum_values,
+            chained_subcommand_values: Vec::new(),
+            suffixes: Vec::new(),
+            enums,
+            chained_subcommands: Vec::new(),
+            command_data,
+            dynamic_enums: Vec::new(),
+            enum_constraints: Vec::new(),
+        }
+    }
+}
+
+fn value_type(kind: &ParamKind) -> ParamType {
+    match kind {
+        ParamKind::Int => ParamType::Int,
+        ParamKind::Float => ParamType::Float,
+        ParamKind::Position => ParamType::Position,
+        ParamKind::Target => ParamType::Target,
+        ParamKind::String | ParamKind::Enum { .. } => ParamType::String,
+        ParamKind::Text => ParamType::RawText,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enum_slots() {
+        let registry = CommandRegistry::with_defaults();
+        let packet = registry.to_packet(|command| command.name() != "pos");
+        assert!(
+            packet
+                .command_data
+                .iter()
+                .all(|command| command.name != "pos")
+        );
+        let tp = packet
+            .command_data
+            .iter()
+            .find(|command| command.name == "tp")
+            .unwrap();
+        // The alias enum lists the name first
+        let aliases = &packet.enums[tp.alias as usize];
+        assert_eq!(packet.enum_values[aliases.values[0] as usize], "tp");
+        assert_eq!(tp.permission_level, "game_directors");
+
+        let mut table = EnumTable::default();
+        let modes = vec!["survival".to_string(), "creative".to_string()];
+        assert_eq!(table.param_enum("mode", &modes), Some(ParamType::Int));
+        assert_eq!(table.param_enum("mode", &modes), Some(ParamType::Int));
+        assert_eq!(table.param_enum("other", &modes), Some(ParamType::Float));
+        // Alias enums fill the gaps
+        assert_eq!(table.alias_enum("tpAliases", &modes), 0);
+        assert_eq!(table.alias_enum("posAliases", &modes), 2);
+        let (values, enums) = table.finish();
+        assert_eq!(values.len(), 2);
+        assert_eq!(enums.len(), 4);
+        assert_eq!(enums[3].name, "other");
+    }
+}
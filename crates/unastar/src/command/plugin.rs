@@ -0,0 +1,169 @@
+//! Commands registered by native plugins.
+
+use abi_stable::std_types::RVec;
+use bevy_ecs::prelude::*;
+use unastar_api::native::{CommandCall, CommandParamKind, CommandSpec, PluginEntity};
+
+use super::{
+    ArgValue, Arguments, Command, CommandContext, CommandOutput, Coordinate, Overload, Param,
+    ParamKind,
+};
+use crate::plugin::PluginRegistry;
+
+/// A plugin command, dispatched to the plugin's `on_command`.
+pub struct PluginCommand {
+    plugin: usize,
+    spec: CommandSpec,
+    overloads: Vec<Overload>,
+}
+
+impl PluginCommand {
+    /// Wrap a command registered by the plugin at index `plugin` of the registry.
+    pub fn new(plugin: usize, spec: CommandSpec) -> Self {
+        let mut overloads: Vec<Overload> = spec
+            .overloads
+            .iter()
+            .map(|params| Overload {
+                params: params
+                    .iter()
+                    .map(|param| {
+                        let kind = match param.kind {
+                            CommandParamKind::Int => ParamKind::Int,
+                            CommandParamKind::Float => ParamKind::Float,
+                            CommandParamKind::Position => ParamKind::Position,
+                            CommandParamKind::Target => ParamKind::Target,
+                            CommandParamKind::Enum => ParamKind::Enum {
+                                name: format!("{}{}", spec.name, param.name),
+                                values: param
+                                    .values
+                                    .iter()
+                                    .map(|value| value.to_string())
+                                    .collect(),
+                            },
+                            CommandParamKind::String => ParamKind::String,
+                            CommandParamKind::Text => ParamKind::Text,
+                        };
+                        Param {
+                            name: param.name.to_string(),
+                            kind,
+                            optional: param.optional,
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+        if overloads.is_empty() {
+            overloads.push(Overload::new());
+        }
+        Self {
+            plugin,
+            spec,
+            overloads,
+        }
+    }
+}
+
+impl Command for PluginCommand {
+    fn name(&self) -> &str {
+        &self.spec.name
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        self.spec
+            .aliases
+            .iter()
+            .map(|alias| alias.as_str())
+            .collect()
+    }
+
+    fn description(&self) -> &str {
+        &self.spec.description
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        self.overloads.clone()
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let mut call = CommandCall {
+            command: self.spec.name.clone(),
+            sender: ctx.sender.entity().map(PluginEntity::from).into(),
+            overload: args.overload() as u32,
+            args: args
+                .values()
+                .iter()
+                .map(|(_, value)| arg_text(value).into())
+                .collect(),
+            messages: RVec::new(),
+            errors: RVec::new(),
+        };
+
+        let mut out = CommandOutput::default();
+        if !ctx.world.contains_resource::<PluginRegistry>() {
+            out.error("Plugins are not loaded");
+            return out;
+        }
+        ctx.world
+            .resource_scope(|world, mut registry: Mut<PluginRegistry>| {
+                registry.on_command(world, self.plugin, &mut call);
+            });
+
+        out.messages.extend(
+            call.messages
+                .into_iter()
+                .map(|message| message.into_string()),
+        );
+        out.errors
+            .extend(call.errors.into_iter().map(|error| error.into_string()));
+        out
+    }
+}
+
+/// Text passed to the plugin for a parsed argument.
+fn arg_text(value: &ArgValue) -> String {
+    match value {
+        ArgValue::Int(value) => value.to_string(),
+        ArgValue::Float(value) => value.to_string(),
+        ArgValue::Position(position) => [position.x, position.y, position.z]
+            .iter()
+            .map(|coordinate| match *coordinate {
+                Coordinate::Absolute(value) => value.to_string(),
+                Coordinate::Relative(0.0) => "~".to_string(),
+                Coordinate::Relative(offset) => format!("~{offset}"),
+                Coordinate::Local(0.0) => "^".to_string(),
+                Coordinate::Local(offset) => format!("^{offset}"),
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+        ArgValue::Target(target) => target.raw().to_string(),
+        ArgValue::Enum(value) | ArgValue::String(value) | ArgValue::Text(value) => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::CommandArgs;
+    use unastar_api::native::CommandParam;
+
+    #[test]
+    fn test_plugin_overloads() {
+        let spec = CommandSpec::new("warp", "Warp somewhere")
+            .alias("w")
+            .overload(vec![
+                CommandParam::enumeration("place", &["spawn", "shop"]),
+                CommandParam::new("where", CommandParamKind::Position).optional(),
+            ]);
+        let command = PluginCommand::new(0, spec);
+        assert_eq!(command.aliases(), vec!["w"]);
+
+        let tokens = ["shop", "~", "~1", "5"].map(str::to_string).to_vec();
+        let args = Arguments::parse(&command.overloads(), &CommandArgs::new(tokens)).unwrap();
+        let texts: Vec<String> = args
+            .values()
+            .iter()
+            .map(|(_, value)| arg_text(value))
+            .collect();
+        assert_eq!(texts, vec!["shop", "~ ~1 5"]);
+    }
+}
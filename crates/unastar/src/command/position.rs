@@ -0,0 +1,172 @@
+//! Position arguments with relative (`~`) and local (`^`) coordinates.
+
+use glam::DVec3;
+
+use super::CommandArgs;
+
+/// One coordinate of a position argument.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coordinate {
+    /// A world coordinate.
+    Absolute(f64),
+    /// An offset from the sender's position (`~`).
+    Relative(f64),
+    /// An offset along the sender's view (`^`): left, up, forward.
+    Local(f64),
+}
+
+impl Coordinate {
+    fn parse(text: &str) -> Result<Self, String> {
+        let number = |rest: &str| -> Result<f64, String> {
+            if rest.is_empty() {
+                return Ok(0.0);
+            }
+            rest.parse()
+                .map_err(|_| format!("\"{text}\" is not a valid coordinate"))
+        };
+        if let Some(rest) = text.strip_prefix('~') {
+            number(rest).map(Coordinate::Relative)
+        } else if let Some(rest) = text.strip_prefix('^') {
+            number(rest).map(Coordinate::Local)
+        } else {
+            text.parse()
+                .map(Coordinate::Absolute)
+                .map_err(|_| format!("\"{text}\" is not a valid coordinate"))
+        }
+    }
+}
+
+/// A parsed `x y z` argument.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionArg {
+    pub x: Coordinate,
+    pub y: Coordinate,
+    pub z: Coordinate,
+}
+
+impl PositionArg {
+    /// Parse three coordinates from the arguments.
+    ///
+    /// Coordinates may be run together (`~~1~`) as the client allows. Local
+    /// coordinates can't be mixed with the other kinds.
+    pub fn parse(args: &mut CommandArgs) -> Result<Self, String> {
+        let mut coordinates = Vec::with_capacity(3);
+        while coordinates.len() < 3 {
+            let Some(token) = args.next() else {
+                return Err("Expected three coordinates".to_string());
+            };
+            for part in split_coordinates(token) {
+                if coordinates.len() == 3 {
+                    return Err(format!("Too many coordinates in \"{token}\""));
+                }
+                coordinates.push(Coordinate::parse(part)?);
+            }
+        }
+
+        let local = coordinates
+            .iter()
+            .filter(|c| matches!(c, Coordinate::Local(_)))
+            .count();
+        if local != 0 && local != 3 {
+            return Err("Cannot mix local (^) coordinates with other coordinates".to_string());
+        }
+        Ok(Self {
+            x: coordinates[0],
+            y: coordinates[1],
+            z: coordinates[2],
+        })
+    }
+
+    /// Resolve against the sender's feet position and view rotation in degrees.
+    pub fn resolve(&self, origin: DVec3, yaw: f32, pitch: f32) -> DVec3 {
+        if let (Coordinate::Local(left), Coordinate::Local(up), Coordinate::Local(forward)) =
+            (self.x, self.y, self.z)
+        {
+            return origin + local_offset(left, up, forward, yaw, pitch);
+        }
+        let axis = |coordinate: Coordinate, origin: f64| match coordinate {
+            Coordinate::Absolute(value) => value,
+            Coordinate::Relative(offset) | Coordinate::Local(offset) => origin + offset,
+        };
+        DVec3::new(
+            axis(self.x, origin.x),
+            axis(self.y, origin.y),
+            axis(self.z, origin.z),
+        )
+    }
+}
+
+/// Split a token like `~1~-2^` into its coordinates.
+fn split_coordinates(token: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (index, ch) in token.char_indices().skip(1) {
+        if ch == '~' || ch == '^' {
+            parts.push(&token[start..index]);
+            start = index;
+        }
+    }
+    parts.push(&token[start..]);
+    parts
+}
+
+/// World offset of a local (`^`) coordinate triple.
+fn local_offset(left: f64, up: f64, forward: f64, yaw: f32, pitch: f32) -> DVec3 {
+    let yaw = (yaw as f64 + 90.0).to_radians();
+    let pitch = (pitch as f64).to_radians();
+    let forward_axis = DVec3::new(
+        yaw.cos() * pitch.cos(),
+        -pitch.sin(),
+        yaw.sin() * pitch.cos(),
+    );
+    let up_axis = DVec3::new(
+        yaw.cos() * (-pitch + std::f64::consts::FRAC_PI_2).cos(),
+        (-pitch + std::f64::consts::FRAC_PI_2).sin(),
+        yaw.sin() * (-pitch + std::f64::consts::FRAC_PI_2).cos(),
+    );
+    let left_axis = -forward_axis.cross(up_axis);
+    forward_axis * forward + up_axis * up + left_axis * left
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Result<PositionArg, String> {
+        let mut args = CommandArgs::new(line.split_whitespace().map(str::to_string).collect());
+        PositionArg::parse(&mut args)
+    }
+
+    #[test]
+    fn test_relative_and_absolute() {
+        let origin = DVec3::new(10.0, 64.0, -5.0);
+        let position = parse("~ ~2 3.5").unwrap();
+        assert_eq!(
+            position.resolve(origin, 0.0, 0.0),
+            DVec3::new(10.0, 66.0, 3.5)
+        );
+
+        let packed = parse("~1~~-1").unwrap();
+        assert_eq!(
+            packed.resolve(origin, 0.0, 0.0),
+            DVec3::new(11.0, 64.0, -6.0)
+        );
+
+        assert!(parse("~ ~").is_err());
+        assert!(parse("^ ~ ^").is_err());
+        assert!(parse("1 2 x").is_err());
+    }
+
+    #[test]
+    fn test_local_coordinates() {
+        let origin = DVec3::new(0.0, 64.0, 0.0);
+        // Yaw 0 faces +Z
+        let forward = parse("^ ^ ^2").unwrap().resolve(origin, 0.0, 0.0);
+        assert!((forward - DVec3::new(0.0, 64.0, 2.0)).length() < 1e-9);
+        // Yaw 90 faces -X, so left is +Z
+        let left = parse("^1 ^ ^").unwrap().resolve(origin, 90.0, 0.0);
+        assert!((left - DVec3::new(0.0, 64.0, 1.0)).length() < 1e-9);
+        let up = parse("^ ^3 ^").unwrap().resolve(origin, 45.0, 0.0);
+        assert!((up - DVec3::new(0.0, 67.0, 0.0)).length() < 1e-9);
+    }
+}
@@ -0,0 +1,557 @@
+//! Target selectors: `@a`, `@p`, `@r`, `@e` and `@s` with `[key=value,...]` filters.
+//!
+//! Supported filters are `x`/`y`/`z` (origin, may be relative), `r`/`rm`
+//! (distance), `dx`/`dy`/`dz` (volume), `type`, `name`, `tag`, `m`, `c` and
+//! `l`/`lm`. `type`, `name`, `tag` and `m` accept `!` to negate and may be
+//! repeated; every filter must match.
+
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use rand::seq::SliceRandom;
+
+use super::Coordinate;
+use crate::entity::components::{
+    Dead, DroppedItem, Experience, GameMode, Mob, MobType, PLAYER_EYE_HEIGHT, Player, PlayerName,
+    Position, Tags,
+};
+use crate::server::broadcast::EntityGrid;
+use crate::world::ecs::WorldId;
+
+/// Feet position of an entity. Player positions are at eye level.
+pub(crate) fn feet_position(world: &World, entity: Entity) -> Option<DVec3> {
+    let position = world.get::<Position>(entity)?.0;
+    if world.get::<Player>(entity).is_some() {
+        Some(position - DVec3::Y * PLAYER_EYE_HEIGHT)
+    } else {
+        Some(position)
+    }
+}
+
+/// Namespaced type identifier of a selectable entity.
+fn entity_type(world: &World, entity: Entity) -> Option<String> {
+    if world.get::<Player>(entity).is_some() {
+        Some("minecraft:player".to_string())
+    } else if let Some(mob_type) = world.get::<MobType>(entity) {
+        Some(namespaced(&mob_type.identifier))
+    } else if world.get::<DroppedItem>(entity).is_some() {
+        Some("minecraft:item".to_string())
+    } else {
+        None
+    }
+}
+
+fn namespaced(identifier: &str) -> String {
+    if identifier.contains(':') {
+        identifier.to_ascii_lowercase()
+    } else {
+        format!("minecraft:{}", identifier.to_ascii_lowercase())
+    }
+}
+
+/// Which entities a selector starts from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectorKind {
+    /// `@a`: every player.
+    AllPlayers,
+    /// `@p`: the nearest player.
+    NearestPlayer,
+    /// `@r`: a random player, or a random entity of `type`.
+    RandomPlayer,
+    /// `@e`: every entity.
+    AllEntities,
+    /// `@s`: the entity running the command.
+    Executor,
+}
+
+/// A filter value, negated with a leading `!`.
+#[derive(Debug, Clone, PartialEq)]
+struct Match<T> {
+    value: T,
+    negated: bool,
+}
+
+impl<T> Match<T> {
+    fn test(&self, matches: bool) -> bool {
+        matches != self.negated
+    }
+}
+
+/// A parsed target selector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    kind: SelectorKind,
+    x: Option<Coordinate>,
+    y: Option<Coordinate>,
+    z: Option<Coordinate>,
+    radius: Option<f64>,
+    min_radius: Option<f64>,
+    dx: Option<f64>,
+    dy: Option<f64>,
+    dz: Option<f64>,
+    types: Vec<Match<String>>,
+    names: Vec<Match<String>>,
+    tags: Vec<Match<String>>,
+    game_modes: Vec<Match<GameMode>>,
+    count: Option<i32>,
+    level: Option<i32>,
+    min_level: Option<i32>,
+}
+
+impl Selector {
+    fn new(kind: SelectorKind) -> Self {
+        Self {
+            kind,
+            x: None,
+            y: None,
+            z: None,
+            radius: None,
+            min_radius: None,
+            dx: None,
+            dy: None,
+            dz: None,
+            types: Vec::new(),
+            names: Vec::new(),
+            tags: Vec::new(),
+            game_modes: Vec::new(),
+            count: None,
+            level: None,
+            min_level: None,
+        }
+    }
+
+    /// Parse a selector such as `@e[type=zombie,r=10,c=2]`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut chars = text.chars();
+        if chars.next() != Some('@') {
+            return Err(format!("\"{text}\" is not a selector"));
+        }
+        let kind = match chars.next() {
+            Some('a') => SelectorKind::AllPlayers,
+            Some('p') => SelectorKind::NearestPlayer,
+            Some('r') => SelectorKind::RandomPlayer,
+            Some('e') => SelectorKind::AllEntities,
+            Some('s') => SelectorKind::Executor,
+            _ => return Err(format!("Unknown selector \"{text}\"")),
+        };
+        let mut selector = Self::new(kind);
+
+        let rest = chars.as_str().trim();
+        if rest.is_empty() {
+            return Ok(selector);
+        }
+        let Some(filters) = rest
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        else {
+            return Err(format!("Malformed selector \"{text}\""));
+        };
+        for filter in filters.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            let Some((key, value)) = filter.split_once('=') else {
+                return Err(format!(
+                    "Expected key=value in selector, found \"{filter}\""
+                ));
+            };
+            selector.apply_filter(key.trim(), value.trim())?;
+        }
+        Ok(selector)
+    }
+
+    fn apply_filter(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = value.trim_matches('"');
+        let number = || -> Result<f64, String> {
+            value
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid value for {key}: \"{value}\""))
+        };
+        let integer = || -> Result<i32, String> {
+            value
+                .parse::<i32>()
+                .map_err(|_| format!("Invalid value for {key}: \"{value}\""))
+        };
+        let negatable = || match value.strip_prefix('!') {
+            Some(rest) => Match {
+                value: rest.to_string(),
+                negated: true,
+            },
+            None => Match {
+                value: value.to_string(),
+                negated: false,
+            },
+        };
+
+        match key {
+            "x" | "y" | "z" => {
+                let coordinate = match value.strip_prefix('~') {
+                    Some("") => Coordinate::Relative(0.0),
+                    Some(rest) => Coordinate::Relative(
+                        rest.parse()
+                            .map_err(|_| format!("Invalid value for {key}: \"{value}\""))?,
+                    ),
+                    None => Coordinate::Absolute(number()?),
+                };
+                match key {
+                    "x" => self.x = Some(coordinate),
+                    "y" => self.y = Some(coordinate),
+                    _ => self.z = Some(coordinate),
+                }
+            }
+            "r" => self.radius = Some(number()?),
+            "rm" => self.min_radius = Some(number()?),
+            "dx" => self.dx = Some(number()?),
+            "dy" => self.dy = Some(number()?),
+            "dz" => self.dz = Some(number()?),
+            "type" => {
+                let mut filter = negatable();
+                filter.value = namespaced(&filter.value);
+                self.types.push(filter);
+            }
+            "name" => self.names.push(negatable()),
+            "tag" => self.tags.push(negatable()),
+            "m" => {
+                let filter = negatable();
+                let mode = match filter.value.to_ascii_lowercase().as_str() {
+                    "0" | "s" | "survival" => GameMode::Survival,
+                    "1" | "c" | "creative" => GameMode::Creative,
+                    "2" | "a" | "adventure" => GameMode::Adventure,
+                    "6" | "spectator" => GameMode::Spectator,
+                    _ => return Err(format!("Unknown game mode \"{}\"", filter.value)),
+                };
+                self.game_modes.push(Match {
+                    value: mode,
+                    negated: filter.negated,
+                });
+            }
+            "c" => self.count = Some(integer()?),
+            "l" => self.level = Some(integer()?),
+            "lm" => self.min_level = Some(integer()?),
+            _ => return Err(format!("Unknown selector argument \"{key}\"")),
+        }
+        Ok(())
+    }
+
+    pub fn kind(&self) -> SelectorKind {
+        self.kind
+    }
+
+    /// Whether only players can match.
+    fn players_only(&self) -> bool {
+        match self.kind {
+            SelectorKind::AllPlayers | SelectorKind::NearestPlayer => true,
+            SelectorKind::RandomPlayer => self.types.is_empty(),
+            SelectorKind::AllEntities | SelectorKind::Executor => false,
+        }
+    }
+
+    /// Select entities.
+    ///
+    /// `executor` is what `@s` refers to and `origin` is where distances are
+    /// measured from, unless the selector sets `x`/`y`/`z`. Only entities in
+    /// the executor's world are selected; without an executor, as from the
+    /// console, that is the default world.
+    pub fn select(
+        &self,
+        world: &mut World,
+        executor: Option<Entity>,
+        origin: DVec3,
+    ) -> Vec<Entity> {
+        let axis = |coordinate: Option<Coordinate>, origin: f64| match coordinate {
+            Some(Coordinate::Absolute(value)) => value,
+            Some(Coordinate::Relative(offset) | Coordinate::Local(offset)) => origin + offset,
+            None => origin,
+        };
+        let origin = DVec3::new(
+            axis(self.x, origin.x),
+            axis(self.y, origin.y),
+            axis(self.z, origin.z),
+        );
+
+        let world_id = executor.map_or(WorldId::DEFAULT, |e| WorldId::of(world, e));
+        let candidates: Vec<Entity> = if self.kind == SelectorKind::Executor {
+            executor.into_iter().collect()
+        } else if let Some(radius) = self.radius
+            && let Some(grid) = world.get_resource::<EntityGrid>()
+        {
+            let chunk = (
+                (origin.x.floor() as i32) >> 4,
+                (origin.z.floor() as i32) >> 4,
+            );
+            grid.get_neighbors(world_id, chunk, (radius / 16.0).ceil() as i32)
+        } else {
+            world
+                .query_filtered::<Entity, Or<(With<Player>, With<Mob>, With<DroppedItem>)>>()
+                .iter(world)
+                .filter(|&entity| WorldId::of(world, entity) == world_id)
+                .collect()
+        };
+
+        let players_only = self.players_only();
+        let mut selected: Vec<(Entity, f64)> = candidates
+            .into_iter()
+            .filter(|&entity| !players_only || world.get::<Player>(entity).is_some())
+            // @a also finds players waiting to respawn
+            .filter(|&entity| {
+                self.kind == SelectorKind::AllPlayers || world.get::<Dead>(entity).is_none()
+            })
+            .filter_map(|entity| {
+                let position = feet_position(world, entity)?;
+                self.matches(world, entity, position, origin)
+                    .then(|| (entity, position.distance(origin)))
+            })
+            .collect();
+
+        let count = match (self.kind, self.count) {
+            (_, Some(count)) => Some(count),
+            (SelectorKind::NearestPlayer | SelectorKind::RandomPlayer, None) => Some(1),
+            _ => None,
+        };
+        if self.kind == SelectorKind::RandomPlayer {
+            selected.shuffle(&mut rand::thread_rng());
+        } else if let Some(count) = count {
+            // Negative counts take the furthest entities first
+            selected.sort_by(|a, b| a.1.total_cmp(&b.1));
+            if count < 0 {
+                selected.reverse();
+            }
+        }
+        if let Some(count) = count {
+            selected.truncate(count.unsigned_abs() as usize);
+        }
+        selected.into_iter().map(|(entity, _)| entity).collect()
+    }
+
+    fn matches(&self, world: &World, entity: Entity, position: DVec3, origin: DVec3) -> bool {
+        let distance = position.distance(origin);
+        if self.radius.is_some_and(|radius| distance > radius)
+            || self.min_radius.is_some_and(|min| distance < min)
+        {
+            return false;
+        }
+
+        // The volume covers whole blocks from the origin to origin + d
+        if self.dx.is_some() || self.dy.is_some() || self.dz.is_some() {
+            let corner = origin
+                + DVec3::new(
+                    self.dx.unwrap_or(0.0),
+                    self.dy.unwrap_or(0.0),
+                    self.dz.unwrap_or(0.0),
+                );
+            let min = origin.min(corner).floor();
+            let max = origin.max(corner).floor() + DVec3::ONE;
+            if position.cmplt(min).any() || position.cmpgt(max).any() {
+                return false;
+            }
+        }
+
+        if !self.types.is_empty() {
+            let Some(entity_type) = entity_type(world, entity) else {
+                return false;
+            };
+            if !self
+                .types
+                .iter()
+                .all(|filter| filter.test(filter.value == entity_type))
+            {
+                return false;
+            }
+        }
+
+        if !self.names.is_empty() {
+            let name = world.get::<PlayerName>(entity).map(|name| name.0.as_str());
+            if !self
+                .names
+                .iter()
+                .all(|filter| filter.test(name == Some(filter.value.as_str())))
+            {
+                return false;
+            }
+        }
+
+        if !self.tags.is_empty() {
+            let tags = world.get::<Tags>(entity);
+            let has_any = tags.is_some_and(|tags| !tags.is_empty());
+            let matched = self.tags.iter().all(|filter| {
+                if filter.value.is_empty() {
+                    // `tag=` matches untagged entities, `tag=!` tagged ones
+                    filter.negated == has_any
+                } else {
+                    filter.test(tags.is_some_and(|tags| tags.has(&filter.value)))
+                }
+            });
+            if !matched {
+                return false;
+            }
+        }
+
+        if !self.game_modes.is_empty() {
+            let Some(mode) = world.get::<GameMode>(entity) else {
+                return false;
+            };
+            if !self
+                .game_modes
+                .iter()
+                .all(|filter| filter.test(filter.value == *mode))
+            {
+                return false;
+            }
+        }
+
+        if self.level.is_some() || self.min_level.is_some() {
+            let Some(level) = world.get::<Experience>(entity).map(|xp| xp.level) else {
+                return false;
+            };
+            if self.level.is_some_and(|max| level > max)
+                || self.min_level.is_some_and(|min| level < min)
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::components::SpatialChunk;
+
+    fn spawn_player(world: &mut World, name: &str, feet: DVec3, mode: GameMode) -> Entity {
+        let position = Position(feet + DVec3::Y * PLAYER_EYE_HEIGHT);
+        world
+            .spawn((
+                Player,
+                PlayerName(name.to_string()),
+                SpatialChunk::from_position(&position),
+                position,
+                mode,
+                Experience {
+                    level: name.len() as i32,
+                    progress: 0.0,
+                },
+            ))
+            .id()
+    }
+
+    fn spawn_mob(world: &mut World, identifier: &str, feet: DVec3) -> Entity {
+        let position = Position(feet);
+        world
+            .spawn((
+                Mob,
+                MobType::new(identifier),
+                SpatialChunk::from_position(&position),
+                position,
+            ))
+            .id()
+    }
+
+    fn select(world: &mut World, text: &str, executor: Option<Entity>) -> Vec<Entity> {
+        Selector::parse(text)
+            .unwrap()
+            .select(world, executor, DVec3::ZERO)
+    }
+
+    #[test]
+    fn test_parse() {
+        let selector = Selector::parse("@e[type=!zombie, r=5,x=~2,m=c,tag=]").unwrap();
+        assert_eq!(selector.kind(), SelectorKind::AllEntities);
+        assert_eq!(selector.radius, Some(5.0));
+        assert_eq!(selector.x, Some(Coordinate::Relative(2.0)));
+        assert_eq!(selector.types[0].value, "minecraft:zombie");
+        assert!(selector.types[0].negated);
+        assert_eq!(selector.game_modes[0].value, GameMode::Creative);
+
+        assert!(Selector::parse("@x").is_err());
+        assert!(Selector::parse("@a[r=far]").is_err());
+        assert!(Selector::parse("@a[foo=1]").is_err());
+        assert!(Selector::parse("@a[r=1").is_err());
+    }
+
+    #[test]
+    fn test_select() {
+        let mut world = World::new();
+        world.insert_resource(EntityGrid::default());
+        let near = spawn_player(
+            &mut world,
+            "Near",
+            DVec3::new(2.0, 0.0, 0.0),
+            GameMode::Survival,
+        );
+        let far = spawn_player(
+            &mut world,
+            "Faraway",
+            DVec3::new(40.0, 0.0, 0.0),
+            GameMode::Creative,
+        );
+        let zombie = spawn_mob(&mut world, "minecraft:zombie", DVec3::new(3.0, 0.0, 0.0));
+        let pig = spawn_mob(&mut world, "pig", DVec3::new(-20.0, 0.0, 5.0));
+        world
+            .entity_mut(pig)
+            .insert(Tags(["pet".to_string()].into()));
+
+        assert_eq!(select(&mut world, "@p", None), vec![near]);
+        assert_eq!(select(&mut world, "@p[x=50]", None), vec![far]);
+        assert_eq!(select(&mut world, "@a[c=-1]", None), vec![far]);
+        assert_eq!(select(&mut world, "@a[m=!survival]", None), vec![far]);
+        assert_eq!(select(&mut world, "@a[lm=5]", None), vec![far]);
+        assert_eq!(select(&mut world, "@a[name=Near]", None), vec![near]);
+        assert_eq!(select(&mut world, "@s", Some(far)), vec![far]);
+        assert_eq!(
+            select(&mut world, "@s[r=10]", Some(far)),
+            Vec::<Entity>::new()
+        );
+
+        let mut nearby = select(&mut world, "@e[r=5]", None);
+        nearby.sort();
+        let mut expected = vec![near, zombie];
+        expected.sort();
+        assert_eq!(nearby, expected);
+
+        assert_eq!(select(&mut world, "@e[type=pig]", None), vec![pig]);
+        assert_eq!(select(&mut world, "@e[tag=pet]", None), vec![pig]);
+        assert_eq!(select(&mut world, "@e[rm=30]", None), vec![far]);
+        assert_eq!(
+            select(
+                &mut world,
+                "@e[x=1,y=0,z=-1,dx=2,dy=1,dz=2,type=!player]",
+                None
+            ),
+            vec![zombie]
+        );
+        assert_eq!(select(&mut world, "@r[type=zombie]", None), vec![zombie]);
+        assert_eq!(select(&mut world, "@r", None).len(), 1);
+    }
+
+    #[test]
+    fn test_select_in_executor_world() {
+        let mut world = World::new();
+        world.insert_resource(EntityGrid::default());
+        let home = spawn_player(&mut world, "Home", DVec3::ZERO, GameMode::Survival);
+        let away = spawn_player(
+            &mut world,
+            "Away",
+            DVec3::new(1.0, 0.0, 0.0),
+            GameMode::Creative,
+        );
+        let zombie = spawn_mob(&mut world, "zombie", DVec3::new(2.0, 0.0, 0.0));
+        world.entity_mut(away).insert(WorldId(1));
+        world.entity_mut(zombie).insert(WorldId(1));
+
+        // The console selects in the default world
+        assert_eq!(select(&mut world, "@a", None), vec![home]);
+        assert_eq!(select(&mut world, "@e", None), vec![home]);
+        assert_eq!(select(&mut world, "@p[x=2]", None), vec![home]);
+
+        assert_eq!(select(&mut world, "@p", Some(away)), vec![away]);
+        assert_eq!(
+            select(&mut world, "@a[m=!creative]", Some(away)),
+            Vec::<Entity>::new()
+        );
+        assert_eq!(select(&mut world, "@e[rm=1.5]", Some(away)), vec![zombie]);
+        assert_eq!(
+            select(&mut world, "@e[x=-1,y=0,z=0,dx=4,dy=1,dz=1]", Some(away)).len(),
+            2
+        );
+        assert_eq!(select(&mut world, "@e[c=-1]", Some(away)), vec![zombie]);
+        assert_eq!(select(&mut world, "@s", Some(away)), vec![away]);
+    }
+}
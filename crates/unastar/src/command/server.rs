@@ -0,0 +1,146 @@
+//! Server management commands.
+
+use bevy_ecs::prelude::*;
+
+use super::{Arguments, Command, CommandContext, CommandOutput};
+use crate::access::AccessControl;
+use crate::entity::components::Player;
+use crate::permission::{OpLevel, Permissions};
+use crate::server::game::{ServerRequest, ServerRequests, kick_denied_players, sync_permissions};
+
+/// Queue a request for the runtime to handle after the current tick.
+fn request(ctx: &mut CommandContext, request: ServerRequest, message: &str) -> CommandOutput {
+    let mut out = CommandOutput::default();
+    match ctx.world.get_resource_mut::<ServerRequests>() {
+        Some(mut requests) => {
+            requests.push(request);
+            out.message(message);
+        }
+        None => out.error("This server does not accept that request"),
+    }
+    out
+}
+
+/// `/stop`: save everything and shut the server down.
+pub struct StopCommand;
+
+impl Command for StopCommand {
+    fn name(&self) -> &str {
+        "stop"
+    }
+
+    fn description(&self) -> &str {
+        "Save the world and stop the server"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Owner
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, _args: &Arguments) -> CommandOutput {
+        request(ctx, ServerRequest::Stop, "Stopping the server")
+    }
+}
+
+/// `/save-all`: write players and modified chunks to disk.
+pub struct SaveAllCommand;
+
+impl Command for SaveAllCommand {
+    fn name(&self) -> &str {
+        "save-all"
+    }
+
+    fn description(&self) -> &str {
+        "Save players and modified chunks"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Owner
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, _args: &Arguments) -> CommandOutput {
+        request(ctx, ServerRequest::SaveAll, "Saving the game")
+    }
+}
+
+/// `/reload`: re-read permissions, bans and the whitelist from disk.
+pub struct ReloadCommand;
+
+impl Command for ReloadCommand {
+    fn name(&self) -> &str {
+        "reload"
+    }
+
+    fn description(&self) -> &str {
+        "Reload permissions, bans and the whitelist"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Owner
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, _args: &Arguments) -> CommandOutput {
+        let mut out = CommandOutput::default();
+        if let Some(permissions) = ctx.world.get_resource::<Permissions>()
+            && let Err(e) = permissions.reload()
+        {
+            out.error(e.to_string());
+            return out;
+        }
+        if let Some(access) = ctx.world.get_resource::<AccessControl>()
+            && let Err(e) = access.reload()
+        {
+            out.error(e.to_string());
+            return out;
+        }
+
+        let players: Vec<Entity> = ctx
+            .world
+            .query_filtered::<Entity, With<Player>>()
+            .iter(ctx.world)
+            .collect();
+        for entity in players {
+            sync_permissions(ctx.world, ctx.commands, entity);
+        }
+        let kicked = kick_denied_players(ctx.world);
+
+        out.message("Reloaded permissions, bans and the whitelist");
+        if !kicked.is_empty() {
+            out.message(format!("Kicked {}", kicked.join(", ")));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{CommandRegistry, CommandSender};
+    use crate::entity::components::PlayerName;
+
+    #[test]
+    fn test_console_requests() {
+        let mut world = World::new();
+        world.insert_resource(Permissions::default());
+        world.insert_resource(ServerRequests::default());
+        let player = world.spawn((Player, PlayerName("Steve".to_string()))).id();
+        let registry = CommandRegistry::with_defaults();
+
+        let steve = CommandSender::Player {
+            entity: player,
+            session: 1,
+        };
+        let output = registry.execute(&mut world, steve, "stop");
+        assert_eq!(output.errors.len(), 1);
+
+        let output = registry.execute(&mut world, CommandSender::Console, "save-all");
+        assert!(output.errors.is_empty());
+        registry.execute(&mut world, CommandSender::Console, "stop");
+        registry.execute(&mut world, CommandSender::Console, "stop");
+        let requests = world.resource_mut::<ServerRequests>().drain();
+        assert_eq!(requests, vec![ServerRequest::SaveAll, ServerRequest::Stop]);
+
+        let output = registry.execute(&mut world, CommandSender::Console, "reload");
+        assert!(output.errors.is_empty(), "{:?}", output.errors);
+    }
+}
@@ -0,0 +1,69 @@
+//! Target arguments: player names and selectors.
+
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+
+use super::CommandContext;
+use super::selector::{Selector, feet_position};
+use crate::entity::components::{Player, PlayerName};
+
+/// A target argument as typed, resolved against the world when the command runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetArg(String);
+
+impl TargetArg {
+    pub fn new(raw: impl Into<String>) -> Self {
+        Self(raw.into())
+    }
+
+    /// The argument as typed.
+    pub fn raw(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether the argument is a selector rather than a player name.
+    pub fn is_selector(&self) -> bool {
+        self.0.starts_with('@')
+    }
+
+    /// Resolve the target for a command.
+    ///
+    /// Unlike [`TargetArg::select`], matching nothing is an error.
+    pub fn resolve(&self, ctx: &mut CommandContext) -> Result<Vec<Entity>, String> {
+        let entities = self.select(ctx.world, ctx.sender.entity())?;
+        if entities.is_empty() {
+            return Err(match self.0.as_str() {
+                "@s" => "@s needs an entity to run the command".to_string(),
+                _ => "No targets matched selector".to_string(),
+            });
+        }
+        Ok(entities)
+    }
+
+    /// Select the entities the target refers to.
+    ///
+    /// Player names match online players case-insensitively. Selectors are
+    /// evaluated from `executor`, which is also what `@s` refers to.
+    pub fn select(
+        &self,
+        world: &mut World,
+        executor: Option<Entity>,
+    ) -> Result<Vec<Entity>, String> {
+        if self.is_selector() {
+            let selector = Selector::parse(&self.0)?;
+            let origin = executor
+                .and_then(|entity| feet_position(world, entity))
+                .unwrap_or(DVec3::ZERO);
+            return Ok(selector.select(world, executor, origin));
+        }
+
+        let player = world
+            .query_filtered::<(Entity, &PlayerName), With<Player>>()
+            .iter(world)
+            .find(|(_, name)| name.0.eq_ignore_ascii_case(&self.0))
+            .map(|(entity, _)| entity);
+        player
+            .map(|entity| vec![entity])
+            .ok_or_else(|| format!("No player named \"{}\" is online", self.0))
+    }
+}
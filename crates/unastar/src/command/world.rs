@@ -0,0 +1,545 @@
+//! World management, time, weather, difficulty and game rule commands.
+
+use super::access::enum_param;
+use super::builtin::display_name;
+use super::{Arguments, Command, CommandContext, CommandOutput, Overload, Param};
+use crate::entity::components::Player;
+use crate::permission::OpLevel;
+use crate::server::game::{
+    change_world, create_world, load_world, set_difficulty, set_game_rule, set_time, set_weather,
+    unload_world,
+};
+use crate::world::time::named_time;
+use crate::world::{
+    CarverSelection, Difficulty, GameRuleValue, GameRules, WeatherKind, WorldConfig,
+    WorldGenerator, WorldTime, Worlds,
+};
+
+const NAMED_TIMES: &[&str] = &["sunrise", "day", "noon", "sunset", "night", "midnight"];
+
+const GENERATORS: &[&str] = &["flat", "void", "vanilla", "nether", "end"];
+
+const DIMENSIONS: &[&str] = &["overworld", "nether", "the_end"];
+
+const CARVERS: &[&str] = &["all", "caves", "canyons", "none"];
+
+const DIFFICULTIES: &[&str] = &["peaceful", "easy", "normal", "hard", "p", "e", "n", "h"];
+
+/// `/world`: list, create, load and unload worlds, and move players between
+/// them.
+pub struct WorldCommand;
+
+impl WorldCommand {
+    fn list(ctx: &CommandContext, out: &mut CommandOutput) {
+        let Some(worlds) = ctx.world.get_resource::<Worlds>() else {
+            out.error("Worlds are not available");
+            return;
+        };
+        let loaded: Vec<&str> = worlds.iter().map(|(_, manager)| manager.name()).collect();
+        let unloaded: Vec<&str> = worlds
+            .known()
+            .map(|(name, _)| name)
+            .filter(|name| !loaded.contains(name))
+            .collect();
+        out.message(format!(
+            "There are {} loaded world(s): {}",
+            loaded.len(),
+            loaded.join(", ")
+        ));
+        if !unloaded.is_empty() {
+            out.message(format!("Not loaded: {}", unloaded.join(", ")));
+        }
+    }
+
+    fn create(ctx: &mut CommandContext, args: &Arguments, out: &mut CommandOutput) {
+        let name = args.string("name").unwrap_or_default();
+        let seed = args.int("seed").map_or_else(rand::random, i64::from);
+        let carvers = match args.string("carvers") {
+            Some("caves") => CarverSelection::Caves,
+            Some("canyons") => CarverSelection::Canyons,
+            Some("none") => CarverSelection::None,
+            _ => CarverSelection::All,
+        };
+        let generator = match args.string("generator") {
+            Some("flat") => WorldGenerator::SuperFlat,
+            Some("vanilla") => WorldGenerator::Vanilla { seed, carvers },
+            Some("nether") => WorldGenerator::Nether { seed, carvers },
+            Some("end") => WorldGenerator::End { seed },
+            _ => WorldGenerator::VoidSpawnPlatform {
+                platform_radius_chunks: 1,
+            },
+        };
+        // Nether and End terrain default to their own dimension
+        let dimension = match (args.string("dimension"), generator) {
+            (Some("nether"), _) | (None, WorldGenerator::Nether { .. }) => 1,
+            (Some("the_end"), _) | (None, WorldGenerator::End { .. }) => 2,
+            _ => 0,
+        };
+        let config = WorldConfig {
+            dimension,
+            generator,
+            ..WorldConfig::default()
+        };
+        match create_world(ctx.world, name, config) {
+            Ok(_) => out.message(format!("Created world {name}")),
+            Err(e) => out.error(e.to_string()),
+        }
+    }
+
+    fn teleport(ctx: &mut CommandContext, args: &Arguments, out: &mut CommandOutput) {
+        let name = args.string("name").unwrap_or_default();
+        let players = match args.target("player") {
+            Some(target) => match target.resolve(ctx) {
+                Ok(entities) => entities
+                    .into_iter()
+                    .filter(|&entity| ctx.world.get::<Player>(entity).is_some())
+                    .collect(),
+                Err(e) => {
+                    out.error(e);
+                    return;
+                }
+            },
+            None => match ctx.sender.entity() {
+                Some(entity) => vec![entity],
+                None => {
+                    out.error("A player is needed when run from the console");
+                    return;
+                }
+            },
+        };
+        for player in players {
+            if let Err(e) = change_world(ctx.world, player, name, None) {
+                out.error(e.to_string());
+                return;
+            }
+            out.message(format!(
+                "Sending {} to world {name}",
+                display_name(ctx.world, player)
+            ));
+        }
+    }
+}
+
+impl Command for WorldCommand {
+    fn name(&self) -> &str {
+        "world"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["worlds"]
+    }
+
+    fn description(&self) -> &str {
+        "Manage worlds and move players between them"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Admin
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![
+            Overload::new().param(enum_param("list", "WorldList", &["list"]).optional()),
+            Overload::new()
+                .param(enum_param("create", "WorldCreate", &["create"]))
+                .param(Param::string("name"))
+                .param(enum_param("generator", "WorldGenerator", GENERATORS))
+                .param(Param::int("seed").optional())
+                .param(enum_param("dimension", "WorldDimension", DIMENSIONS).optional())
+                .param(enum_param("carvers", "WorldCarvers", CARVERS).optional()),
+            Overload::new()
+                .param(enum_param("action", "WorldAction", &["load", "unload"]))
+                .param(Param::string("name")),
+            Overload::new()
+                .param(enum_param("tp", "WorldTeleport", &["tp"]))
+                .param(Param::string("name"))
+                .param(Param::target("player").optional()),
+        ]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let mut out = CommandOutput::default();
+        if args.has("create") {
+            Self::create(ctx, args, &mut out);
+        } else if args.has("tp") {
+            Self::teleport(ctx, args, &mut out);
+        } else if let Some(action) = args.string("action") {
+            let name = args.string("name").unwrap_or_default();
+            let result = match action {
+                "load" => load_world(ctx.world, name).map(|_| format!("Loaded world {name}")),
+                _ => unload_world(ctx.world, name).map(|_| format!("Unloading world {name}")),
+            };
+            match result {
+                Ok(message) => out.message(message),
+                Err(e) => out.error(e.to_string()),
+            }
+        } else {
+            Self::list(ctx, &mut out);
+        }
+        out
+    }
+}
+
+/// `/time`: query or change the time of day.
+pub struct TimeCommand;
+
+impl Command for TimeCommand {
+    fn name(&self) -> &str {
+        "time"
+    }
+
+    fn description(&self) -> &str {
+        "Change or query the world's game time"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Gamemaster
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![
+            Overload::new()
+                .param(enum_param("mode", "TimeModeSet", &["set"]))
+                .param(Param::int("time")),
+            Overload::new()
+                .param(enum_param("mode", "TimeModeSet", &["set"]))
+                .param(enum_param("name", "TimeSpec", NAMED_TIMES)),
+            Overload::new()
+                .param(enum_param("mode", "TimeModeAdd", &["add"]))
+                .param(Param::int("amount")),
+            Overload::new()
+                .param(enum_param("mode", "TimeModeQuery", &["query"]))
+                .param(enum_param(
+                    "query",
+                    "TimeQuery",
+                    &["daytime", "gametime", "day"],
+                )),
+        ]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let mut out = CommandOutput::default();
+        let id = ctx.world_id();
+        let Some(current) = ctx
+            .world
+            .get_resource::<Worlds>()
+            .and_then(|worlds| worlds.get(id))
+            .map(|manager| manager.time())
+        else {
+            out.error("Worlds are not available");
+            return out;
+        };
+
+        match args.string("mode") {
+            Some("set") => {
+                // Setting the time of day keeps the day count
+                let time_of_day = match args.string("name") {
+                    Some(name) => named_time(name).unwrap_or_default(),
+                    None => args.int("time").unwrap_or_default() as i64,
+                };
+                let time = current.time - current.day_time() + time_of_day;
+                set_time(ctx.world, id, time);
+                out.message(format!("Set the time to {time_of_day}"));
+            }
+            Some("add") => {
+                let amount = args.int("amount").unwrap_or_default() as i64;
+                let time = WorldTime {
+                    time: current.time + amount,
+                };
+                set_time(ctx.world, id, time.time);
+                out.message(format!("Set the time to {}", time.day_time()));
+            }
+            _ => {
+                let value = match args.string("query") {
+                    Some("gametime") => current.time,
+                    Some("day") => current.day(),
+                    _ => current.day_time(),
+                };
+                out.message(format!("The time is {value}"));
+            }
+        }
+        out
+    }
+}
+
+/// `/weather`: query or change the weather.
+pub struct WeatherCommand;
+
+impl Command for WeatherCommand {
+    fn name(&self) -> &str {
+        "weather"
+    }
+
+    fn description(&self) -> &str {
+        "Set the weather"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Gamemaster
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![
+            Overload::new()
+                .param(enum_param(
+                    "type",
+                    "WeatherType",
+                    &["clear", "rain", "thunder"],
+                ))
+                .param(Param::int("duration").optional()),
+            Overload::new().param(enum_param("query", "WeatherQuery", &["query"])),
+        ]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let mut out = CommandOutput::default();
+        let id = ctx.world_id();
+        let Some(manager) = ctx
+            .world
+            .get_resource::<Worlds>()
+            .and_then(|worlds| worlds.get(id))
+        else {
+            out.error("Worlds are not available");
+            return out;
+        };
+        if !manager.has_weather() {
+            out.error("There is no weather in this dimension");
+            return out;
+        }
+        let Some(kind) = args.string("type").and_then(WeatherKind::from_name) else {
+            let kind = manager.weather().kind();
+            out.message(format!("The weather is {}", kind.name()));
+            return out;
+        };
+
+        // Duration in ticks, like the vanilla Bedrock command
+        let duration = args.int("duration");
+        if duration.is_some_and(|ticks| ticks <= 0) {
+            out.error("The duration must be at least one tick");
+            return out;
+        }
+        set_weather(ctx.world, id, kind, duration);
+        out.message(match kind {
+            WeatherKind::Clear => "Changing to clear weather",
+            WeatherKind::Rain => "Changing to rainy weather",
+            WeatherKind::Thunder => "Changing to rain and thunder",
+        });
+        out
+    }
+}
+
+/// `/difficulty`: query or change the difficulty.
+pub struct DifficultyCommand;
+
+impl Command for DifficultyCommand {
+    fn name(&self) -> &str {
+        "difficulty"
+    }
+
+    fn description(&self) -> &str {
+        "Sets the difficulty level"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Gamemaster
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![
+            Overload::new().param(enum_param("difficulty", "Difficulty", DIFFICULTIES).optional()),
+            Overload::new().param(Param::int("id")),
+        ]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let mut out = CommandOutput::default();
+        let difficulty = match (args.string("difficulty"), args.int("id")) {
+            (Some(name), _) => Difficulty::from_name(name),
+            (None, Some(id)) => u8::try_from(id).ok().and_then(Difficulty::from_id),
+            (None, None) => {
+                let current = ctx
+                    .world
+                    .get_resource::<Difficulty>()
+                    .copied()
+                    .unwrap_or_default();
+                out.message(format!("The difficulty is {}", current.name()));
+                return out;
+            }
+        };
+        let Some(difficulty) = difficulty else {
+            out.error("Unknown difficulty");
+            return out;
+        };
+        set_difficulty(ctx.world, difficulty);
+        out.message(format!("Set the difficulty to {}", difficulty.name()));
+        out
+    }
+}
+
+/// `/gamerule`: list, query or change game rules.
+pub struct GameRuleCommand;
+
+impl Command for GameRuleCommand {
+    fn name(&self) -> &str {
+        "gamerule"
+    }
+
+    fn description(&self) -> &str {
+        "Sets or queries a game rule value"
+    }
+
+    fn default_level(&self) -> OpLevel {
+        OpLevel::Gamemaster
+    }
+
+    fn overloads(&self) -> Vec<Overload> {
+        vec![
+            Overload::new()
+                .param(enum_param("rule", "BoolGameRule", &GameRules::BOOL_NAMES).optional())
+                .param(enum_param("value", "Boolean", &["true", "false"]).optional()),
+            Overload::new()
+                .param(enum_param("rule", "IntGameRule", &GameRules::INT_NAMES))
+                .param(Param::int("value").optional()),
+        ]
+    }
+
+    fn execute(&self, ctx: &mut CommandContext, args: &Arguments) -> CommandOutput {
+        let mut out = CommandOutput::default();
+        let rules = ctx
+            .world
+            .get_resource::<GameRules>()
+            .copied()
+            .unwrap_or_default();
+        let Some(rule) = args.string("rule") else {
+            let list: Vec<String> = rules
+                .iter()
+                .map(|(name, value)| format!("{name} = {value}"))
+                .collect();
+            out.message(list.join(", "));
+            return out;
+        };
+
+        let value = match (args.int("value"), args.string("value")) {
+            (Some(value), _) => Some(GameRuleValue::Int(value)),
+            (None, Some(value)) => Some(GameRuleValue::Bool(value == "true")),
+            (None, None) => None,
+        };
+        match value {
+            Some(value) => {
+                if set_game_rule(ctx.world, rule, value) {
+                    out.message(format!("Game rule {rule} has been updated to {value}"));
+                } else {
+                    out.error(format!("Game rule {rule} cannot be set to {value}"));
+                }
+            }
+            None => match rules.get(rule) {
+                Some(value) => out.message(format!("{rule} = {value}")),
+                None => out.error(format!("Unknown game rule {rule}")),
+            },
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{CommandRegistry, CommandSender};
+    use bevy_ecs::prelude::*;
+
+    #[test]
+    fn test_time_and_weather() {
+        let mut world = World::new();
+        let mut worlds = Worlds::default();
+        *worlds.default_world_mut().time_mut() = WorldTime { time: 48000 + 500 };
+        world.insert_resource(worlds);
+        let registry = CommandRegistry::with_defaults();
+        let mut run = |line: &str| {
+            let output = registry.execute(&mut world, CommandSender::Console, line);
+            assert!(output.errors.is_empty(), "{line}: {:?}", output.errors);
+            output.messages
+        };
+
+        run("time set noon");
+        assert_eq!(run("time query gametime"), vec!["The time is 54000"]);
+        run("time add 24000");
+        assert_eq!(run("time query day"), vec!["The time is 3"]);
+        run("time set 100");
+        assert_eq!(run("time query daytime"), vec!["The time is 100"]);
+
+        run("weather thunder 200");
+        assert_eq!(run("weather query"), vec!["The weather is thunder"]);
+        let weather = world.resource::<Worlds>().default_world().weather();
+        assert_eq!((weather.rain_time, weather.thunder_time), (200, 200));
+        let output = registry.execute(&mut world, CommandSender::Console, "weather rain 0");
+        assert_eq!(output.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_difficulty_and_game_rules() {
+        let mut world = World::new();
+        world.insert_resource(Difficulty::default());
+        world.insert_resource(GameRules::default());
+        let registry = CommandRegistry::with_defaults();
+        let mut run = |line: &str| {
+            let output = registry.execute(&mut world, CommandSender::Console, line);
+            assert!(output.errors.is_empty(), "{line}: {:?}", output.errors);
+            output.messages
+        };
+
+        run("difficulty h");
+        assert_eq!(run("difficulty"), vec!["The difficulty is hard"]);
+        run("difficulty 0");
+        assert_eq!(run("difficulty"), vec!["The difficulty is peaceful"]);
+
+        run("gamerule keepinventory true");
+        assert_eq!(run("gamerule keepInventory"), vec!["keepInventory = true"]);
+        assert!(run("gamerule")[0].starts_with("keepInventory = true, "));
+        run("gamerule randomTickSpeed 3");
+        assert_eq!(run("gamerule randomtickspeed"), vec!["randomTickSpeed = 3"]);
+
+        let output = registry.execute(&mut world, CommandSender::Console, "difficulty 7");
+        assert_eq!(output.errors.len(), 1);
+        let output = registry.execute(
+            &mut world,
+            CommandSender::Console,
+            "gamerule spawnRadius true",
+        );
+        assert!(!output.errors.is_empty());
+        let rules = world.resource::<GameRules>();
+        assert!(rules.keep_inventory);
+        assert_eq!(rules.random_tick_speed, 3);
+    }
+
+    #[test]
+    fn test_world_command() {
+        let mut world = World::new();
+        world.insert_resource(Worlds::default());
+        let registry = CommandRegistry::with_defaults();
+        let mut run = |line: &str| registry.execute(&mut world, CommandSender::Console, line);
+
+        let output = run("world create skyblock vanilla 42 nether caves");
+        assert!(output.errors.is_empty(), "{:?}", output.errors);
+        assert_eq!(
+            run("world list").messages,
+            vec!["There are 2 loaded world(s): default, skyblock"]
+        );
+        assert_eq!(run("world create skyblock flat").errors.len(), 1);
+        assert_eq!(run("world load skyblock").errors.len(), 1);
+        assert_eq!(run("world unload default").errors.len(), 1);
+        assert_eq!(run("world tp skyblock").errors.len(), 1);
+        assert!(run("world unload skyblock").errors.is_empty());
+
+        let worlds = world.resource::<Worlds>();
+        let id = worlds.id("skyblock").unwrap();
+        let config = worlds.get(id).unwrap().world_config();
+        assert_eq!(config.dimension, 1);
+        assert_eq!(
+            config.generator,
+            WorldGenerator::Vanilla {
+                seed: 42,
+                carvers: CarverSelection::Caves
+            }
+        );
+    }
+}
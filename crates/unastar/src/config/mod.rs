@@ -2,11 +2,13 @@
 //!
 //! The server reads a TOML config file (default: `unastar.toml`) and applies:
 //! - Network/server settings
-//! - World generator/bounds settings
+//! - World generator/bounds settings, and extra named worlds
 //! - Spawn rules (including optional previous-position spawning)
 
 use crate::server::ServerConfig;
+use crate::world::DEFAULT_WORLD;
 use crate::world::WorldConfig;
+use crate::world::ecs::worlds::validate_name;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
@@ -46,6 +48,20 @@ allow_legacy_auth = true
 # Enable encryption handshake. Set to false for debugging.
 encryption_enabled = true
 
+# Directory of Bedrock `.mcstructure` exports of the vanilla structure
+# templates, laid out like `village/plains/houses/plains_small_house_1.mcstructure`.
+# Vanilla worlds only generate villages, pillager outposts and trial chambers
+# when it is set, and the server refuses to start if it doesn't exist.
+# structure_templates = "structures"
+
+  [server.rcon]
+  # Remote administration over the Source RCON protocol (TCP).
+  # Anyone with the password can run any command, so keep it private.
+  # Listens on this machine only; use "0.0.0.0:25575" to allow remote clients.
+  enabled = false
+  bind_address = "127.0.0.1:25575"
+  password = ""
+
 [world]
 # Dimension ID: 0 = Overworld, 1 = Nether, 2 = End
 dimension = 0
@@ -63,7 +79,11 @@ dimension = 0
   # max_z = 64
 
   [world.generator]
-  # kinds: "flat_stone" | "void_spawn_platform"
+  # kinds: "super_flat" | "void_spawn_platform" | "vanilla" | "nether" | "end"
+  # ("vanilla", "nether" and "end" take a `seed`; "vanilla" and "nether" also
+  # take `carvers` = "all" | "caves" | "canyons" | "none", default "all", or
+  # a list of configured carvers such as { only = ["cave", "canyon"] } out of
+  # "cave", "cave_extra_underground", "canyon" and "nether_cave")
   kind = "void_spawn_platform"
   # For void worlds, generate a stone platform at chunk coords [-r..r].
   platform_radius_chunks = 1
@@ -76,11 +96,27 @@ dimension = 0
   # Save modified chunks on shutdown.
   save_on_shutdown = true
 
+# Extra worlds, next to the `default` world above.
+#
+# Each has its own generator and storage under `worlds/<name>/db`, and takes
+# the same settings as `[world]`. Players move between worlds with
+# `/world tp <name>`; `load = false` worlds are loaded later with
+# `/world load <name>`.
+#
+# [[worlds]]
+# name = "survival_nether"
+# dimension = 1
+# load = true
+#   [worlds.generator]
+#   kind = "nether"
+#   seed = 1234
+
 [players]
 # Where Unastar stores per-player files (e.g. last known position).
 data_dir = \"playerdata\"
 
-# If true, Unastar writes a player's last known position on disconnect (legacy TOML format).
+# If true and LevelDB is disabled, Unastar writes a player's last known position on
+# disconnect (legacy TOML format). Existing files are migrated into LevelDB on join.
 save_previous_position = true
 
 # Enable LevelDB-based player persistence (recommended).
@@ -120,9 +156,15 @@ location = { x = 0.5, y = 17.0, z = 0.5, yaw = 0.0, pitch = 0.0 }
 pub struct UnastarConfig {
     pub server: ServerConfigFile,
     pub world: WorldConfig,
+    /// Named worlds besides the default one.
+    pub worlds: Vec<NamedWorldConfig>,
     pub players: PlayerStorageConfig,
     #[serde(alias = "spawn")]
     pub spawn_rules: Vec<SpawnRule>,
+    /// Directory the config was loaded from; sibling files such as
+    /// `permissions.toml` live here.
+    #[serde(skip)]
+    pub config_dir: PathBuf,
 }
 
 impl Default for UnastarConfig {
@@ -130,8 +172,10 @@ impl Default for UnastarConfig {
         Self {
             server: ServerConfigFile::default(),
             world: WorldConfig::default(),
+            worlds: Vec::new(),
             players: PlayerStorageConfig::default(),
             spawn_rules: vec![SpawnRule::default()],
+            config_dir: PathBuf::new(),
         }
     }
 }
@@ -154,16 +198,22 @@ impl UnastarConfig {
             Err(source) => return Err(ConfigError::Read { path, source }),
         };
 
-        let cfg: Self =
+        let mut cfg: Self =
             toml_edit::de::from_str(&contents).map_err(|source| ConfigError::Parse {
                 path: path.clone(),
                 source,
             })?;
 
         cfg.validate().map_err(ConfigError::Validation)?;
+        cfg.config_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
         Ok(cfg)
     }
 
+    /// Path of the ops and permissions file.
+    pub fn permissions_path(&self) -> PathBuf {
+        self.config_dir.join(crate::permission::PERMISSIONS_FILE)
+    }
+
     pub fn server_config(&self) -> ServerConfig {
         let defaults = ServerConfig::default();
 
@@ -186,6 +236,9 @@ impl UnastarConfig {
             simulation_distance: defaults.simulation_distance,
             chunk_unload_ticks: defaults.chunk_unload_ticks,
             world: self.world,
+            worlds: self.worlds.clone(),
+            spawn_rules: self.spawn_rules.clone(),
+            structure_templates: self.server.structure_templates.clone(),
         }
     }
 
@@ -204,10 +257,47 @@ impl UnastarConfig {
                 "`server.default_chunk_radius` cannot exceed `server.max_chunk_radius`".into(),
             );
         }
+        if self.server.rcon.enabled && self.server.rcon.password.is_empty() {
+            return Err("`server.rcon.password` must be set when RCON is enabled".into());
+        }
+        if let Some(dir) = &self.server.structure_templates
+            && !dir.is_dir()
+        {
+            return Err(format!(
+                "`server.structure_templates` directory {} does not exist",
+                dir.display()
+            ));
+        }
+        let mut names = std::collections::HashSet::from([DEFAULT_WORLD]);
+        for entry in &self.worlds {
+            validate_name(&entry.name).map_err(|e| format!("`[[worlds]]`: {e}"))?;
+            if !names.insert(entry.name.as_str()) {
+                return Err(format!(
+                    "`[[worlds]]`: world {} is defined twice",
+                    entry.name
+                ));
+            }
+        }
         Ok(())
     }
 }
 
+/// A `[[worlds]]` entry: a world besides the default one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedWorldConfig {
+    /// World name, also its storage directory.
+    pub name: String,
+    #[serde(flatten)]
+    pub config: WorldConfig,
+    /// Load the world at startup.
+    #[serde(default = "default_load")]
+    pub load: bool,
+}
+
+fn default_load() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ServerConfigFile {
@@ -225,6 +315,10 @@ pub struct ServerConfigFile {
     pub allow_legacy_auth: bool,
     /// Enable encryption handshake.
     pub encryption_enabled: bool,
+    /// Remote administration listener.
+    pub rcon: RconConfig,
+    /// Directory of `.mcstructure` templates for jigsaw structures.
+    pub structure_templates: Option<PathBuf>,
 }
 
 impl Default for ServerConfigFile {
@@ -239,6 +333,28 @@ impl Default for ServerConfigFile {
             online_mode: true,
             allow_legacy_auth: true,
             encryption_enabled: true,
+            rcon: RconConfig::default(),
+            structure_templates: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RconConfig {
+    pub enabled: bool,
+    /// TCP address for the RCON listener.
+    pub bind_address: String,
+    /// Password clients must authenticate with. Required when enabled.
+    pub password: String,
+}
+
+impl Default for RconConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: "127.0.0.1:25575".into(),
+            password: String::new(),
         }
     }
 }
@@ -248,7 +364,8 @@ impl Default for ServerConfigFile {
 pub struct PlayerStorageConfig {
     /// Directory for player data files.
     pub data_dir: PathBuf,
-    /// Save player position on disconnect (legacy TOML files).
+    /// Save player position on disconnect to legacy TOML files when LevelDB
+    /// persistence is disabled. Existing files are migrated on join.
     pub save_previous_position: bool,
     /// Enable LevelDB-based player persistence.
     pub leveldb_enabled: bool,
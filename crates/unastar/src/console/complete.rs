@@ -0,0 +1,146 @@
+//! Tab completion for console command lines.
+
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+use crate::command::{CommandRegistry, Overload, ParamKind};
+
+/// Selectors offered where a target is expected.
+const SELECTORS: [&str; 5] = ["@a", "@e", "@p", "@r", "@s"];
+
+struct CommandEntry {
+    names: Vec<String>,
+    overloads: Vec<Overload>,
+}
+
+/// Completes command names and arguments from the registered overloads.
+///
+/// Clones share the list of online players.
+#[derive(Clone)]
+pub struct Completer {
+    commands: Arc<Vec<CommandEntry>>,
+    players: Arc<RwLock<Vec<String>>>,
+}
+
+impl Completer {
+    pub fn new(registry: &CommandRegistry) -> Self {
+        let commands = registry
+            .commands()
+            .iter()
+            .map(|command| CommandEntry {
+                names: std::iter::once(command.name())
+                    .chain(command.aliases())
+                    .map(str::to_ascii_lowercase)
+                    .collect(),
+                overloads: command.overloads(),
+            })
+            .collect();
+        Self {
+            commands: Arc::new(commands),
+            players: Arc::default(),
+        }
+    }
+
+    /// Replace the player names offered for target parameters.
+    pub fn set_players(&self, players: Vec<String>) {
+        *self.players.write() = players;
+    }
+
+    /// Candidates for the word being typed at the end of `line`, and the byte
+    /// offset that word starts at.
+    pub fn complete(&self, line: &str) -> (usize, Vec<String>) {
+        let start = line.rfind(char::is_whitespace).map_or(0, |index| index + 1);
+        let word = &line[start..];
+        let before: Vec<&str> = line[..start].split_whitespace().collect();
+
+        let mut candidates: Vec<String> = match before.split_first() {
+            None => {
+                let slash = if word.starts_with('/') { "/" } else { "" };
+                self.commands
+                    .iter()
+                    .flat_map(|command| &command.names)
+                    .map(|name| format!("{slash}{name}"))
+                    .collect()
+            }
+            Some((name, args)) => {
+                let name = name.trim_start_matches('/').to_ascii_lowercase();
+                let Some(command) = self
+                    .commands
+                    .iter()
+                    .find(|command| command.names.contains(&name))
+                else {
+                    return (start, Vec::new());
+                };
+                command
+                    .overloads
+                    .iter()
+                    .filter_map(|overload| param_at(overload, args.len()))
+                    .flat_map(|kind| self.values(kind))
+                    .collect()
+            }
+        };
+
+        let word = word.to_ascii_lowercase();
+        candidates.retain(|candidate| candidate.to_ascii_lowercase().starts_with(&word));
+        candidates.sort();
+        candidates.dedup();
+        (start, candidates)
+    }
+
+    fn values(&self, kind: &ParamKind) -> Vec<String> {
+        match kind {
+            ParamKind::Enum { values, .. } => values.clone(),
+            ParamKind::Target => SELECTORS
+                .iter()
+                .map(|selector| selector.to_string())
+                .chain(self.players.read().iter().cloned())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Kind of the parameter that the argument word at `index` belongs to.
+fn param_at(overload: &Overload, index: usize) -> Option<&ParamKind> {
+    let mut words = 0;
+    for param in &overload.params {
+        let width = match param.kind {
+            ParamKind::Position => 3,
+            ParamKind::Text => return Some(&param.kind),
+            _ => 1,
+        };
+        if index < words + width {
+            return Some(&param.kind);
+        }
+        words += width;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complete() {
+        let completer = Completer::new(&CommandRegistry::with_defaults());
+        completer.set_players(vec!["Steve".to_string(), "Alex".to_string()]);
+
+        let (start, candidates) = completer.complete("tel");
+        assert_eq!((start, candidates), (0, vec!["teleport".to_string()]));
+        let (_, candidates) = completer.complete("/de");
+        assert_eq!(candidates, vec!["/deop"]);
+
+        let (start, candidates) = completer.complete("tp St");
+        assert_eq!((start, candidates), (3, vec!["Steve".to_string()]));
+        let (_, candidates) = completer.complete("whitelist ");
+        assert!(candidates.contains(&"add".to_string()));
+        assert!(candidates.contains(&"reload".to_string()));
+        let (_, candidates) = completer.complete("whitelist add @");
+        assert_eq!(candidates, SELECTORS);
+
+        // The destination of `/tp <victim> <x y z>` is not completed
+        assert!(completer.complete("tp Alex 1 2 ").1.is_empty());
+        assert!(completer.complete("nope ").1.is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+//! Interactive server console.
+//!
+//! Reads command lines from stdin with history and tab completion, and keeps
+//! the line being typed at the bottom of the terminal while log output
+//! scrolls above it. Install [`ConsoleWriter`] as the tracing writer so log
+//! lines and the prompt don't interleave.
+
+mod complete;
+mod terminal;
+
+pub use complete::Completer;
+
+use parking_lot::Mutex;
+use std::io::Write;
+use tokio::sync::mpsc;
+
+const PROMPT: &str = "> ";
+
+/// The line shown at the bottom of the terminal.
+struct Prompt {
+    /// Whether a prompt is drawn at all; false until an interactive console
+    /// starts.
+    active: bool,
+    line: String,
+}
+
+static PROMPT_STATE: Mutex<Prompt> = Mutex::new(Prompt {
+    active: false,
+    line: String::new(),
+});
+
+/// Write `bytes` above the prompt, then draw the prompt again.
+fn emit(bytes: &[u8]) {
+    let prompt = PROMPT_STATE.lock();
+    let mut stdout = std::io::stdout().lock();
+    if prompt.active {
+        let _ = stdout.write_all(b"\r\x1b[2K");
+    }
+    let _ = stdout.write_all(bytes);
+    if prompt.active {
+        let _ = write!(stdout, "{PROMPT}{}", prompt.line);
+    }
+    let _ = stdout.flush();
+}
+
+/// The line being typed.
+fn current_line() -> String {
+    PROMPT_STATE.lock().line.clone()
+}
+
+/// Change the line being typed and redraw it.
+fn edit(f: impl FnOnce(&mut String)) {
+    let mut prompt = PROMPT_STATE.lock();
+    f(&mut prompt.line);
+    if prompt.active {
+        let mut stdout = std::io::stdout().lock();
+        let _ = write!(stdout, "\r\x1b[2K{PROMPT}{}", prompt.line);
+        let _ = stdout.flush();
+    }
+}
+
+fn set_active(active: bool) {
+    let mut prompt = PROMPT_STATE.lock();
+    if prompt.active && !active {
+        let mut stdout = std::io::stdout().lock();
+        let _ = stdout.write_all(b"\r\x1b[2K");
+        let _ = stdout.flush();
+    }
+    prompt.active = active;
+    prompt.line.clear();
+}
+
+/// Print a line of console output, e.g. command feedback.
+pub fn print(line: &str) {
+    emit(format!("{line}\n").as_bytes());
+}
+
+/// [`MakeWriter`](tracing_subscriber::fmt::MakeWriter) for stdout that
+/// keeps the console prompt intact.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleWriter;
+
+impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for ConsoleWriter {
+    type Writer = LogLine;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogLine(Vec::new())
+    }
+}
+
+/// One buffered log event, written out when dropped.
+pub struct LogLine(Vec<u8>);
+
+impl Write for LogLine {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogLine {
+    fn drop(&mut self) {
+        if !self.0.is_empty() {
+            emit(&self.0);
+        }
+    }
+}
+
+/// A running console. The terminal is restored when this is dropped.
+pub struct Console {
+    lines: mpsc::UnboundedReceiver<String>,
+    completer: Completer,
+    _terminal: Option<terminal::RawMode>,
+}
+
+impl Console {
+    /// Start reading commands from stdin.
+    ///
+    /// An interactive terminal gets line editing and completion; otherwise
+    /// stdin is read line by line.
+    pub fn start(completer: Completer) -> Self {
+        let (tx, lines) = mpsc::unbounded_channel();
+        let raw_mode = terminal::RawMode::enable();
+        let interactive = raw_mode.is_some();
+        let reader = completer.clone();
+        let spawned = std::thread::Builder::new()
+            .name("console".to_string())
+            .spawn(move || {
+                if interactive {
+                    terminal::read_interactive(tx, reader);
+                } else {
+                    terminal::read_lines(tx);
+                }
+            });
+        if let Err(e) = spawned {
+            tracing::warn!(error = %e, "Failed to start console reader");
+        }
+        set_active(interactive);
+        Self {
+            lines,
+            completer,
+            _terminal: raw_mode,
+        }
+    }
+
+    /// Wait for the next command line. Returns `None` once stdin is closed.
+    pub async fn next_line(&mut self) -> Option<String> {
+        self.lines.recv().await
+    }
+
+    /// Replace the player names offered when completing targets.
+    pub fn set_players(&self, players: Vec<String>) {
+        self.completer.set_players(players);
+    }
+}
+
+impl Drop for Console {
+    fn drop(&mut self) {
+        set_active(false);
+    }
+}
@@ -0,0 +1,237 @@
+//! Terminal input: raw mode and the line editor.
+
+use std::io::{BufRead, Read};
+use tokio::sync::mpsc;
+
+use super::{Completer, current_line, edit, print};
+
+/// Most history entries kept for the up and down arrows.
+const HISTORY_LIMIT: usize = 100;
+
+/// Stdin switched out of canonical mode, restored on drop.
+///
+/// Echo and line buffering are turned off so the editor can draw the line
+/// itself; signals stay enabled so Ctrl-C still shuts the server down.
+#[cfg(unix)]
+pub struct RawMode {
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl RawMode {
+    /// Enter raw mode, or `None` if stdin isn't a terminal.
+    pub fn enable() -> Option<Self> {
+        // SAFETY: termios is plain data, and fd 0 stays open for the process.
+        unsafe {
+            if libc::isatty(libc::STDIN_FILENO) != 1 {
+                return None;
+            }
+            let mut original: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                return None;
+            }
+            let mut raw = original;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+                return None;
+            }
+            Some(Self { original })
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        // SAFETY: restores the attributes read in `enable`.
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+/// Raw mode isn't supported here; the console falls back to reading lines.
+#[cfg(not(unix))]
+pub struct RawMode;
+
+#[cfg(not(unix))]
+impl RawMode {
+    pub fn enable() -> Option<Self> {
+        None
+    }
+}
+
+/// Forward lines from a non-interactive stdin until it closes.
+pub fn read_lines(tx: mpsc::UnboundedSender<String>) {
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if tx.send(line).is_err() {
+            break;
+        }
+    }
+}
+
+/// Progress through an ANSI escape sequence.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    Started,
+    Csi,
+}
+
+struct Editor {
+    completer: Completer,
+    history: Vec<String>,
+    /// Entry shown while walking the history; `history.len()` is the new line.
+    history_index: usize,
+    escape: Escape,
+    /// Bytes of an incomplete UTF-8 character.
+    pending: Vec<u8>,
+}
+
+/// Read keys from a terminal in raw mode until stdin closes.
+pub fn read_interactive(tx: mpsc::UnboundedSender<String>, completer: Completer) {
+    let mut editor = Editor {
+        completer,
+        history: Vec::new(),
+        history_index: 0,
+        escape: Escape::None,
+        pending: Vec::new(),
+    };
+    let mut stdin = std::io::stdin().lock();
+    let mut buf = [0u8; 64];
+    loop {
+        let read = match stdin.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        for &byte in &buf[..read] {
+            if let Some(line) = editor.key(byte)
+                && tx.send(line).is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+impl Editor {
+    /// Handle one input byte, returning a submitted line.
+    fn key(&mut self, byte: u8) -> Option<String> {
+        match self.escape {
+            Escape::Started => {
+                self.escape = if byte == b'[' {
+                    Escape::Csi
+                } else {
+                    Escape::None
+                };
+                return None;
+            }
+            Escape::Csi => {
+                // Parameter bytes continue the sequence; anything else ends it
+                if !(0x30..=0x3f).contains(&byte) {
+                    self.escape = Escape::None;
+                    match byte {
+                        b'A' => self.walk_history(-1),
+                        b'B' => self.walk_history(1),
+                        _ => {}
+                    }
+                }
+                return None;
+            }
+            Escape::None => {}
+        }
+
+        match byte {
+            b'\r' | b'\n' => return self.submit(),
+            0x7f | 0x08 => edit(|line| {
+                line.pop();
+            }),
+            b'\t' => self.complete(),
+            // Ctrl-U
+            0x15 => edit(String::clear),
+            0x1b => self.escape = Escape::Started,
+            byte if byte < 0x20 => {}
+            byte => {
+                self.pending.push(byte);
+                match std::str::from_utf8(&self.pending) {
+                    Ok(text) => {
+                        let text = text.to_string();
+                        self.pending.clear();
+                        edit(|line| line.push_str(&text));
+                    }
+                    Err(e) if e.error_len().is_some() => self.pending.clear(),
+                    Err(_) => {}
+                }
+            }
+        }
+        None
+    }
+
+    fn submit(&mut self) -> Option<String> {
+        let mut submitted = String::new();
+        edit(|line| submitted = std::mem::take(line));
+        let submitted = submitted.trim().to_string();
+        self.history_index = self.history.len();
+        if submitted.is_empty() {
+            return None;
+        }
+        print(&format!("{}{submitted}", super::PROMPT));
+        if self.history.last() != Some(&submitted) {
+            if self.history.len() == HISTORY_LIMIT {
+                self.history.remove(0);
+            }
+            self.history.push(submitted.clone());
+        }
+        self.history_index = self.history.len();
+        Some(submitted)
+    }
+
+    fn walk_history(&mut self, step: isize) {
+        let Some(index) = self.history_index.checked_add_signed(step) else {
+            return;
+        };
+        if index > self.history.len() {
+            return;
+        }
+        self.history_index = index;
+        let entry = self.history.get(index).cloned().unwrap_or_default();
+        edit(|line| *line = entry);
+    }
+
+    fn complete(&self) {
+        let current = current_line();
+        let (start, candidates) = self.completer.complete(&current);
+        match candidates.as_slice() {
+            [] => {}
+            [only] => edit(|line| {
+                line.truncate(start);
+                line.push_str(only);
+                line.push(' ');
+            }),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.as_str(), |common, candidate| {
+                    let len = common
+                        .char_indices()
+                        .zip(candidate.chars())
+                        .take_while(|((_, a), b)| a.eq_ignore_ascii_case(b))
+                        .last()
+                        .map_or(0, |((index, a), _)| index + a.len_utf8());
+                    &common[..len]
+                });
+                if common.len() > current.len() - start {
+                    edit(|line| {
+                        line.truncate(start);
+                        line.push_str(common);
+                    });
+                } else {
+                    print(&candidates.join("  "));
+                }
+            }
+        }
+    }
+}
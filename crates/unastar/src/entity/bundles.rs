@@ -3,7 +3,7 @@
 use bevy_ecs::prelude::*;
 
 use super::components::*;
-use crate::world::ecs::{ChunkLoader, LastPublisherState};
+use crate::world::ecs::{ChunkLoader, LastPublisherState, WorldId};
 
 /// Bundle for spawning a player entity.
 /// Contains all components needed to spawn a player in the ECS.
@@ -16,9 +16,11 @@ use crate::world::ecs::{ChunkLoader, LastPublisherState};
 /// - Core identity: Player, PlayerName, PlayerUuid, PlayerSession, RuntimeEntityId
 /// - Transform: Position, Rotation
 /// - Game state: GameMode, PlayerState, PlayerInput, BreakingState
-/// - Chunk streaming: ChunkRadius, ChunkLoader, LastPublisherState, SpatialChunk
+/// - Chunk streaming: WorldId, ChunkRadius, ChunkLoader, LastPublisherState, SpatialChunk
 /// - Network: LastBroadcastPosition
 /// - Inventory: MainInventory, ArmourInventory, OffhandSlot, HeldSlot, CursorItem, etc.
+/// - Vitals: Health, Hunger, Experience, AirSupply, Effects, SpawnPoint
+/// - Damage: DamageImmunity, FallDistance
 ///
 /// Note: Player spawning emits a `PlayerSpawnedEvent` (not a marker component)
 /// to trigger broadcast to other players without archetype changes.
@@ -36,6 +38,7 @@ pub struct PlayerBundle {
     pub input: PlayerInput,
     pub chunk_radius: ChunkRadius,
     pub breaking_state: BreakingState,
+    pub world: WorldId,
     pub spatial_chunk: SpatialChunk,
     pub last_broadcast: LastBroadcastPosition,
     // Chunk streaming components (Phase 7: included at spawn to avoid archetype changes)
@@ -47,8 +50,18 @@ pub struct PlayerBundle {
     pub offhand: OffhandSlot,
     pub held_slot: HeldSlot,
     pub cursor: CursorItem,
+    pub ui_inventory: UiInventory,
     pub inventory_opened: InventoryOpened,
     pub item_stack_state: ItemStackRequestState,
+    // Vitals (restored from saved player data)
+    pub health: Health,
+    pub hunger: Hunger,
+    pub experience: Experience,
+    pub air_supply: AirSupply,
+    pub effects: Effects,
+    pub spawn_point: SpawnPoint,
+    pub damage_immunity: DamageImmunity,
+    pub fall_distance: FallDistance,
 }
 
 /// Bundle for spawning a basic living entity (mob).
@@ -59,9 +72,12 @@ pub struct LivingBundle {
     pub velocity: Velocity,
     pub rotation: Rotation,
     pub on_ground: OnGround,
+    pub hitbox: Hitbox,
     pub runtime_id: RuntimeId,
     pub health: Health,
     pub effects: Effects,
+    pub damage_immunity: DamageImmunity,
+    pub fall_distance: FallDistance,
     pub speed: Speed,
     pub age: Age,
 }
@@ -76,13 +92,21 @@ pub struct MobBundle {
     pub velocity: Velocity,
     pub rotation: Rotation,
     pub on_ground: OnGround,
+    pub hitbox: Hitbox,
     pub runtime_id: RuntimeId,
     pub health: Health,
     pub effects: Effects,
+    pub damage_immunity: DamageImmunity,
+    pub fall_distance: FallDistance,
     pub speed: Speed,
     pub ai_state: AiState,
+    pub goals: Goals,
     pub hostile: Hostile,
+    pub mob_age: MobAge,
     pub age: Age,
+    pub world: WorldId,
+    pub spatial_chunk: SpatialChunk,
+    pub last_broadcast: LastBroadcastPosition,
 }
 
 /// Bundle for spawning a dropped item entity.
@@ -94,11 +118,14 @@ pub struct ItemBundle {
     pub velocity: Velocity,
     pub rotation: Rotation,
     pub on_ground: OnGround,
+    pub hitbox: Hitbox,
     pub runtime_id: RuntimeId,
     pub pickup_delay: PickupDelay,
     pub item_owner: ItemOwner,
     pub despawn_timer: DespawnTimer,
     pub age: Age,
+    pub world: WorldId,
+    pub spatial_chunk: SpatialChunk,
 }
 
 /// Bundle for spawning a projectile entity.
@@ -109,6 +136,7 @@ pub struct ProjectileBundle {
     pub position: Position,
     pub velocity: Velocity,
     pub rotation: Rotation,
+    pub hitbox: Hitbox,
     pub runtime_id: RuntimeId,
     pub hit_state: ProjectileHit,
     pub pickup_mode: PickupMode,
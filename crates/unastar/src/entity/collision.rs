@@ -0,0 +1,501 @@
+//! Axis-aligned bounding boxes and block collision resolution.
+//!
+//! Movement is resolved one axis at a time (Y, then X, then Z) against the
+//! collision boxes of every block the swept entity box could touch. Resolving
+//! axes separately is what gives wall sliding: a blocked X component does not
+//! stop the Z component. When a horizontal collision happens on the ground,
+//! the move is retried lifted by the entity's step height so that slabs and
+//! single blocks can be walked up.
+
+use glam::DVec3;
+
+use crate::registry::block::collision_shape;
+
+/// Small epsilon used to keep entities from sinking into surfaces.
+const EPSILON: f64 = 1.0e-7;
+
+/// Axis-aligned bounding box in world space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: DVec3,
+    pub max: DVec3,
+}
+
+impl Aabb {
+    /// Create a box from two corners.
+    pub const fn new(min: DVec3, max: DVec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Create a box from raw coordinates.
+    pub const fn from_coords(
+        min_x: f64,
+        min_y: f64,
+        min_z: f64,
+        max_x: f64,
+        max_y: f64,
+        max_z: f64,
+    ) -> Self {
+        Self {
+            min: DVec3::new(min_x, min_y, min_z),
+            max: DVec3::new(max_x, max_y, max_z),
+        }
+    }
+
+    /// Create an entity box centred horizontally on `feet`.
+    pub fn from_feet(feet: DVec3, width: f64, height: f64) -> Self {
+        let half = width / 2.0;
+        Self {
+            min: DVec3::new(feet.x - half, feet.y, feet.z - half),
+            max: DVec3::new(feet.x + half, feet.y + height, feet.z + half),
+        }
+    }
+
+    /// Translate the box.
+    #[inline]
+    pub fn offset(&self, by: DVec3) -> Self {
+        Self {
+            min: self.min + by,
+            max: self.max + by,
+        }
+    }
+
+    /// Grow the box in the direction of `movement` only.
+    pub fn expand_towards(&self, movement: DVec3) -> Self {
+        Self {
+            min: self.min + movement.min(DVec3::ZERO),
+            max: self.max + movement.max(DVec3::ZERO),
+        }
+    }
+
+    /// Grow the box on every side.
+    pub fn inflate(&self, amount: f64) -> Self {
+        Self {
+            min: self.min - DVec3::splat(amount),
+            max: self.max + DVec3::splat(amount),
+        }
+    }
+
+    /// Check whether two boxes overlap (touching faces do not count).
+    #[inline]
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x < other.max.x
+            && self.max.x > other.min.x
+            && self.min.y < other.max.y
+            && self.max.y > other.min.y
+            && self.min.z < other.max.z
+            && self.max.z > other.min.z
+    }
+
+    /// Position of the bottom centre of the box.
+    #[inline]
+    pub fn feet(&self) -> DVec3 {
+        DVec3::new(
+            (self.min.x + self.max.x) / 2.0,
+            self.min.y,
+            (self.min.z + self.max.z) / 2.0,
+        )
+    }
+
+    /// Point in the box closest to `point`.
+    #[inline]
+    pub fn closest_point(&self, point: DVec3) -> DVec3 {
+        point.clamp(self.min, self.max)
+    }
+
+    /// Check whether the segment from `from` to `to` passes through the box.
+    pub fn intersects_segment(&self, from: DVec3, to: DVec3) -> bool {
+        let delta = to - from;
+        let (mut enter, mut exit) = (0.0_f64, 1.0_f64);
+        for axis in 0..3 {
+            let (start, step) = (from[axis], delta[axis]);
+            let (min, max) = (self.min[axis], self.max[axis]);
+            if step.abs() < EPSILON {
+                if start <= min || start >= max {
+                    return false;
+                }
+                continue;
+            }
+            let (t0, t1) = ((min - start) / step, (max - start) / step);
+            enter = enter.max(t0.min(t1));
+            exit = exit.min(t0.max(t1));
+            if enter >= exit {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Clip movement along X so that `self` does not enter `other`.
+    pub fn clip_x(&self, other: &Aabb, mut dx: f64) -> f64 {
+        if other.max.y <= self.min.y
+            || other.min.y >= self.max.y
+            || other.max.z <= self.min.z
+            || other.min.z >= self.max.z
+        {
+            return dx;
+        }
+        if dx > 0.0 && other.min.x >= self.max.x - EPSILON {
+            dx = dx.min(other.min.x - self.max.x);
+        } else if dx < 0.0 && other.max.x <= self.min.x + EPSILON {
+            dx = dx.max(other.max.x - self.min.x);
+        }
+        dx
+    }
+
+    /// Clip movement along Y so that `self` does not enter `other`.
+    pub fn clip_y(&self, other: &Aabb, mut dy: f64) -> f64 {
+        if other.max.x <= self.min.x
+            || other.min.x >= self.max.x
+            || other.max.z <= self.min.z
+            || other.min.z >= self.max.z
+        {
+            return dy;
+        }
+        if dy > 0.0 && other.min.y >= self.max.y - EPSILON {
+            dy = dy.min(other.min.y - self.max.y);
+        } else if dy < 0.0 && other.max.y <= self.min.y + EPSILON {
+            dy = dy.max(other.max.y - self.min.y);
+        }
+        dy
+    }
+
+    /// Clip movement along Z so that `self` does not enter `other`.
+    pub fn clip_z(&self, other: &Aabb, mut dz: f64) -> f64 {
+        if other.max.x <= self.min.x
+            || other.min.x >= self.max.x
+            || other.max.y <= self.min.y
+            || other.min.y >= self.max.y
+        {
+            return dz;
+        }
+        if dz > 0.0 && other.min.z >= self.max.z - EPSILON {
+            dz = dz.min(other.min.z - self.max.z);
+        } else if dz < 0.0 && other.max.z <= self.min.z + EPSILON {
+            dz = dz.max(other.max.z - self.min.z);
+        }
+        dz
+    }
+}
+
+/// Source of block data for collision queries.
+///
+/// Implemented over the ECS chunk storage by the physics systems and over
+/// closures in tests.
+pub trait BlockSource {
+    /// Runtime ID of the block at world coordinates, or `None` if the chunk
+    /// containing it is not loaded.
+    fn block_at(&self, x: i32, y: i32, z: i32) -> Option<u32>;
+}
+
+impl<F> BlockSource for F
+where
+    F: Fn(i32, i32, i32) -> Option<u32>,
+{
+    fn block_at(&self, x: i32, y: i32, z: i32) -> Option<u32> {
+        self(x, y, z)
+    }
+}
+
+/// Collect the world-space collision boxes of all blocks overlapping `region`.
+///
+/// Unloaded blocks are treated as solid so entities never fall into chunks
+/// that have not been streamed in yet.
+pub fn collect_block_boxes(blocks: &impl BlockSource, region: &Aabb, out: &mut Vec<Aabb>) {
+    let min_x = region.min.x.floor() as i32;
+    let min_z = region.min.z.floor() as i32;
+    // Fences and walls extend half a block above their own cell.
+    let min_y = region.min.y.floor() as i32 - 1;
+    let max_x = region.max.x.ceil() as i32;
+    let max_y = region.max.y.ceil() as i32;
+    let max_z = region.max.z.ceil() as i32;
+
+    for x in min_x..max_x {
+        for z in min_z..max_z {
+            for y in min_y..max_y {
+                let origin = DVec3::new(x as f64, y as f64, z as f64);
+                let Some(runtime_id) = blocks.block_at(x, y, z) else {
+                    let cell = Aabb::new(origin, origin + DVec3::ONE);
+                    if cell.intersects(region) {
+                        out.push(cell);
+                    }
+                    continue;
+                };
+                for shape_box in collision_shape(runtime_id).boxes() {
+                    let world_box = shape_box.offset(origin);
+                    if world_box.intersects(region) {
+                        out.push(world_box);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Check that no block collision box lies on the segment between two points.
+///
+/// Used for line-of-sight checks; unloaded blocks block the view.
+pub fn line_of_sight(blocks: &impl BlockSource, from: DVec3, to: DVec3) -> bool {
+    let mut boxes = Vec::new();
+    let region = Aabb::new(from.min(to), from.max(to)).inflate(EPSILON);
+    collect_block_boxes(blocks, &region, &mut boxes);
+    !boxes.iter().any(|b| b.intersects_segment(from, to))
+}
+
+/// Result of a collision-resolved move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveResult {
+    /// The movement actually applied after collisions.
+    pub movement: DVec3,
+    /// Whether the entity landed on something this move.
+    pub on_ground: bool,
+    /// Whether the X component was blocked.
+    pub collided_x: bool,
+    /// Whether the Y component was blocked.
+    pub collided_y: bool,
+    /// Whether the Z component was blocked.
+    pub collided_z: bool,
+}
+
+impl MoveResult {
+    /// Whether either horizontal component was blocked.
+    #[inline]
+    pub fn collided_horizontally(&self) -> bool {
+        self.collided_x || self.collided_z
+    }
+}
+
+/// Resolve `movement` for an entity box against the block world.
+///
+/// `was_on_ground` allows step-up even when the entity is not moving down
+/// this tick (e.g. walking into a slab). `step_height` is the maximum height
+/// the entity can climb without jumping; pass `0.0` to disable stepping.
+pub fn move_with_collision(
+    blocks: &impl BlockSource,
+    aabb: &Aabb,
+    movement: DVec3,
+    was_on_ground: bool,
+    step_height: f64,
+) -> MoveResult {
+    let mut boxes = Vec::new();
+    collect_block_boxes(
+        blocks,
+        &aabb.expand_towards(movement).inflate(EPSILON),
+        &mut boxes,
+    );
+
+    let resolved = sweep(aabb, movement, &boxes);
+    let mut result = MoveResult {
+        movement: resolved,
+        on_ground: movement.y < 0.0 && resolved.y != movement.y,
+        collided_x: resolved.x != movement.x,
+        collided_y: resolved.y != movement.y,
+        collided_z: resolved.z != movement.z,
+    };
+
+    let can_step =
+        step_height > 0.0 && (was_on_ground || result.on_ground) && result.collided_horizontally();
+    if !can_step {
+        return result;
+    }
+
+    // Retry the move lifted by the step height, then settle back down.
+    let step_movement = DVec3::new(movement.x, step_height, movement.z);
+    boxes.clear();
+    collect_block_boxes(
+        blocks,
+        &aabb.expand_towards(step_movement).inflate(EPSILON),
+        &mut boxes,
+    );
+
+    let mut stepped = aabb.offset(DVec3::new(0.0, clip_axis_y(aabb, step_height, &boxes), 0.0));
+    let lifted = stepped.min.y - aabb.min.y;
+    let dx = clip_axis_x(&stepped, movement.x, &boxes);
+    stepped = stepped.offset(DVec3::new(dx, 0.0, 0.0));
+    let dz = clip_axis_z(&stepped, movement.z, &boxes);
+    stepped = stepped.offset(DVec3::new(0.0, 0.0, dz));
+    let settle = clip_axis_y(&stepped, -lifted + movement.y.min(0.0), &boxes);
+    stepped = stepped.offset(DVec3::new(0.0, settle, 0.0));
+
+    let step_delta = stepped.min - aabb.min;
+    let step_horizontal = step_delta.x * step_delta.x + step_delta.z * step_delta.z;
+    let flat_horizontal = resolved.x * resolved.x + resolved.z * resolved.z;
+    if step_horizontal > flat_horizontal {
+        result = MoveResult {
+            movement: step_delta,
+            on_ground: true,
+            collided_x: dx != movement.x,
+            collided_y: true,
+            collided_z: dz != movement.z,
+        };
+    }
+
+    result
+}
+
+/// Sweep a box through `movement`, clipping each axis in Y, X, Z order.
+fn sweep(aabb: &Aabb, movement: DVec3, boxes: &[Aabb]) -> DVec3 {
+    let dy = clip_axis_y(aabb, movement.y, boxes);
+    let moved = aabb.offset(DVec3::new(0.0, dy, 0.0));
+    let dx = clip_axis_x(&moved, movement.x, boxes);
+    let moved = moved.offset(DVec3::new(dx, 0.0, 0.0));
+    let dz = clip_axis_z(&moved, movement.z, boxes);
+    DVec3::new(dx, dy, dz)
+}
+
+fn clip_axis_x(aabb: &Aabb, delta: f64, boxes: &[Aabb]) -> f64 {
+    boxes.iter().fold(delta, |d, b| aabb.clip_x(b, d))
+}
+
+fn clip_axis_y(aabb: &Aabb, delta: f64, boxes: &[Aabb]) -> f64 {
+    boxes.iter().fold(delta, |d, b| aabb.clip_y(b, d))
+}
+
+fn clip_axis_z(aabb: &Aabb, delta: f64, boxes: &[Aabb]) -> f64 {
+    boxes.iter().fold(delta, |d, b| aabb.clip_z(b, d))
+}
+
+/// Compute the velocity after being knocked back from `source` towards `target`.
+///
+/// Mirrors vanilla: current horizontal velocity is halved and replaced by a
+/// push of `strength` away from the source, with a vertical lift capped at
+/// `strength` while on the ground.
+pub fn knockback_velocity(
+    current: DVec3,
+    source: DVec3,
+    target: DVec3,
+    strength: f64,
+    on_ground: bool,
+) -> DVec3 {
+    let mut dx = target.x - source.x;
+    let mut dz = target.z - source.z;
+    let distance = (dx * dx + dz * dz).sqrt();
+    if distance < 1.0e-4 {
+        dx = 0.0;
+        dz = 0.0;
+    } else {
+        dx /= distance;
+        dz /= distance;
+    }
+
+    let mut velocity = DVec3::new(
+        current.x / 2.0 + dx * strength,
+        current.y,
+        current.z / 2.0 + dz * strength,
+    );
+    if on_ground {
+        velocity.y = (current.y / 2.0 + strength).min(strength);
+    }
+    velocity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::chunk::blocks;
+
+    /// Flat stone floor with its top face at y = 64.
+    fn floor(_x: i32, y: i32, _z: i32) -> Option<u32> {
+        Some(if y < 64 { *blocks::STONE } else { *blocks::AIR })
+    }
+
+    fn player_box(feet: DVec3) -> Aabb {
+        Aabb::from_feet(feet, 0.6, 1.8)
+    }
+
+    #[test]
+    fn test_lands_on_floor() {
+        let aabb = player_box(DVec3::new(0.5, 64.5, 0.5));
+        let result = move_with_collision(&floor, &aabb, DVec3::new(0.0, -1.0, 0.0), false, 0.0);
+        assert!(result.on_ground);
+        assert!((result.movement.y + 0.5).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_wall_slides() {
+        // Floor plus a wall filling x >= 2 up to y = 70.
+        let walled = |x: i32, y: i32, _z: i32| -> Option<u32> {
+            Some(if y < 64 || (x >= 2 && y < 70) {
+                *blocks::STONE
+            } else {
+                *blocks::AIR
+            })
+        };
+        let aabb = player_box(DVec3::new(1.5, 64.0, 0.5));
+        let result = move_with_collision(&walled, &aabb, DVec3::new(0.5, 0.0, 0.5), true, 0.6);
+        assert!(result.collided_x);
+        assert!((result.movement.x - 0.2).abs() < 1.0e-6);
+        assert!((result.movement.z - 0.5).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_steps_up_slab() {
+        let slab = blocks::get_block_id("minecraft:smooth_stone_slab");
+        let stepped = move |x: i32, y: i32, _z: i32| -> Option<u32> {
+            Some(if y < 64 {
+                *blocks::STONE
+            } else if x == 1 && y == 64 {
+                slab
+            } else {
+                *blocks::AIR
+            })
+        };
+        let aabb = player_box(DVec3::new(0.5, 64.0, 0.5));
+        let result = move_with_collision(&stepped, &aabb, DVec3::new(0.5, 0.0, 0.0), true, 0.6);
+        assert!((result.movement.x - 0.5).abs() < 1.0e-6);
+        assert!((result.movement.y - 0.5).abs() < 1.0e-6);
+
+        // A full block is too tall to step onto.
+        let wall = |x: i32, y: i32, _z: i32| -> Option<u32> {
+            Some(if y < 64 || (x == 1 && y == 64) {
+                *blocks::STONE
+            } else {
+                *blocks::AIR
+            })
+        };
+        let result = move_with_collision(&wall, &aabb, DVec3::new(0.5, 0.0, 0.0), true, 0.6);
+        assert!(result.collided_x);
+        assert_eq!(result.movement.y, 0.0);
+    }
+
+    #[test]
+    fn test_unloaded_is_solid() {
+        let aabb = player_box(DVec3::new(0.5, 80.0, 0.5));
+        let unloaded = |_x: i32, _y: i32, _z: i32| -> Option<u32> { None };
+        let result = move_with_collision(&unloaded, &aabb, DVec3::new(0.0, -0.5, 0.0), false, 0.0);
+        assert_eq!(result.movement.y, 0.0);
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        // Floor plus a one block high wall at x = 2
+        let walled = |x: i32, y: i32, _z: i32| -> Option<u32> {
+            Some(if y < 64 || (x == 2 && y == 64) {
+                *blocks::STONE
+            } else {
+                *blocks::AIR
+            })
+        };
+        let eye = DVec3::new(0.5, 65.62, 0.5);
+        assert!(line_of_sight(&walled, eye, DVec3::new(4.5, 65.5, 0.5)));
+        assert!(!line_of_sight(&walled, eye, DVec3::new(4.5, 64.5, 0.5)));
+        assert!(line_of_sight(&walled, eye, DVec3::new(1.5, 64.5, 0.5)));
+
+        let target = Aabb::from_feet(DVec3::new(4.5, 64.0, 0.5), 0.6, 1.8);
+        assert_eq!(target.closest_point(eye), DVec3::new(4.2, 65.62, 0.5));
+    }
+
+    #[test]
+    fn test_knockback_direction() {
+        let velocity = knockback_velocity(
+            DVec3::ZERO,
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(2.0, 0.0, 0.0),
+            0.4,
+            true,
+        );
+        assert!((velocity.x - 0.4).abs() < 1.0e-9);
+        assert_eq!(velocity.z, 0.0);
+        assert!((velocity.y - 0.4).abs() < 1.0e-9);
+    }
+}
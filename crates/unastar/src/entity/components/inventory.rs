@@ -7,7 +7,7 @@
 
 use bevy_ecs::prelude::*;
 
-use crate::item::ItemStack;
+use crate::item::{ArmourValues, ItemStack};
 
 /// Error type for inventory operations.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -368,6 +368,13 @@ impl ArmourInventory {
         self.inner.items()
     }
 
+    /// Total armour points and toughness of the worn pieces.
+    pub fn defence(&self) -> ArmourValues {
+        self.items()
+            .map(|(_, item)| ArmourValues::of(&item.item_id))
+            .sum()
+    }
+
     /// Clear all armor slots.
     pub fn clear(&mut self) -> Vec<ItemStack> {
         self.inner.clear()
@@ -461,6 +468,49 @@ impl HeldSlot {
 #[derive(Component, Clone, Debug, Default)]
 pub struct CursorItem(pub ItemStack);
 
+/// UI container slots (crafting grids, stonecutter input, ...).
+///
+/// Mirrors the client's UI window (window ID 124). The cursor is tracked
+/// separately in `CursorItem`.
+#[derive(Component, Clone, Debug)]
+pub struct UiInventory(pub Inventory);
+
+impl UiInventory {
+    /// Number of slots in the UI window.
+    pub const SIZE: usize = 51;
+    /// Stonecutter input slot.
+    pub const STONECUTTER_INPUT: usize = 3;
+    /// First slot of the 2x2 inventory crafting grid.
+    pub const CRAFTING_SMALL_START: usize = 28;
+    /// First slot of the 3x3 crafting table grid.
+    pub const CRAFTING_LARGE_START: usize = 32;
+
+    /// The active crafting grid as a row-major square (2x2 or 3x3).
+    ///
+    /// The 3x3 grid is used whenever any of its slots hold an item.
+    pub fn crafting_grid(&self) -> Vec<ItemStack> {
+        let slots = self.0.slots();
+        let large = &slots[Self::CRAFTING_LARGE_START..Self::CRAFTING_LARGE_START + 9];
+        if large.iter().any(|item| !item.is_empty()) {
+            large.to_vec()
+        } else {
+            slots[Self::CRAFTING_SMALL_START..Self::CRAFTING_SMALL_START + 4].to_vec()
+        }
+    }
+
+    /// Check if a slot is a crafting grid or stonecutter input slot.
+    pub fn is_crafting_input(slot: usize) -> bool {
+        slot == Self::STONECUTTER_INPUT
+            || (Self::CRAFTING_SMALL_START..Self::CRAFTING_LARGE_START + 9).contains(&slot)
+    }
+}
+
+impl Default for UiInventory {
+    fn default() -> Self {
+        Self(Inventory::new(Self::SIZE))
+    }
+}
+
 /// Marker component indicating the player's inventory UI is currently open.
 ///
 /// This prevents duplicate ContainerOpen packets which would crash the client.
@@ -518,6 +568,7 @@ pub struct PlayerInventoryBundle {
     pub offhand: OffhandSlot,
     pub held_slot: HeldSlot,
     pub cursor: CursorItem,
+    pub ui_inventory: UiInventory,
     pub inventory_opened: InventoryOpened,
     pub item_stack_state: ItemStackRequestState,
 }
@@ -682,6 +733,11 @@ mod tests {
         // Invalid item for slot
         let boots = ItemStack::new("minecraft:diamond_boots", 1);
         assert!(armour.set_helmet(boots).is_err());
+
+        let _ = armour.set_boots(ItemStack::new("minecraft:netherite_boots", 1));
+        let defence = armour.defence();
+        assert_eq!(defence.points, 6.0);
+        assert_eq!(defence.toughness, 5.0);
     }
 
     #[test]
@@ -704,4 +760,28 @@ mod tests {
         held.set(100); // Should clamp to 8
         assert_eq!(held.0, 8);
     }
+
+    #[test]
+    fn test_ui_crafting_grid() {
+        let mut ui = UiInventory::default();
+        assert_eq!(ui.crafting_grid().len(), 4);
+
+        let plank = ItemStack::new("minecraft:oak_planks", 1);
+        ui.0.set_item(UiInventory::CRAFTING_SMALL_START + 1, plank.clone())
+            .unwrap();
+        assert_eq!(ui.crafting_grid()[1], plank);
+
+        // Any item in the 3x3 grid switches to the crafting table layout
+        ui.0.set_item(UiInventory::CRAFTING_LARGE_START + 4, plank.clone())
+            .unwrap();
+        let grid = ui.crafting_grid();
+        assert_eq!(grid.len(), 9);
+        assert_eq!(grid[4], plank);
+
+        assert!(UiInventory::is_crafting_input(
+            UiInventory::STONECUTTER_INPUT
+        ));
+        assert!(!UiInventory::is_crafting_input(0));
+        assert!(!UiInventory::is_crafting_input(50));
+    }
 }
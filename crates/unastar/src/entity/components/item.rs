@@ -2,36 +2,19 @@
 
 use bevy_ecs::prelude::*;
 
+use crate::item::ItemStack;
+
 /// Marker for dropped item entities.
 #[derive(Component, Debug)]
 pub struct DroppedItem;
 
-/// Item stack data for dropped items.
+/// Item stack carried by a dropped item entity.
 #[derive(Component, Debug, Clone)]
-pub struct ItemStackData {
-    pub item_id: String,
-    pub count: u8,
-    pub damage: i16,
-    pub nbt: Option<Vec<u8>>,
-}
+pub struct ItemStackData(pub ItemStack);
 
 impl ItemStackData {
-    pub fn new(item_id: impl Into<String>, count: u8) -> Self {
-        Self {
-            item_id: item_id.into(),
-            count,
-            damage: 0,
-            nbt: None,
-        }
-    }
-
-    pub fn with_damage(mut self, damage: i16) -> Self {
-        self.damage = damage;
-        self
-    }
-
     pub fn is_empty(&self) -> bool {
-        self.count == 0 || self.item_id.is_empty()
+        self.0.is_empty()
     }
 }
 
@@ -3,6 +3,8 @@
 use bevy_ecs::prelude::*;
 use std::collections::HashMap;
 
+use crate::entity::damage::DamageSource;
+
 /// Marker component for living entities.
 #[derive(Component, Debug, Default)]
 pub struct Living;
@@ -53,6 +55,25 @@ impl Health {
     }
 }
 
+/// The most recent damage an entity took.
+///
+/// Read when the entity dies to pick the death message and credit the killer.
+#[derive(Component, Debug, Clone)]
+pub struct LastDamage {
+    pub source: DamageSource,
+    pub amount: f32,
+}
+
+/// Marks an entity whose health has reached zero.
+///
+/// Mobs are despawned once their death animation has played. Players keep
+/// the marker until they respawn.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Dead {
+    /// Ticks since the entity died.
+    pub ticks: u32,
+}
+
 /// Status effect type ID.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct EffectType(pub u8);
@@ -81,6 +102,37 @@ impl EffectType {
     pub const HEALTH_BOOST: Self = Self(21);
     pub const ABSORPTION: Self = Self(22);
     pub const SATURATION: Self = Self(23);
+
+    /// Look an effect up by its identifier, such as `fire_resistance`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        Some(match name {
+            "speed" => Self::SPEED,
+            "slowness" => Self::SLOWNESS,
+            "haste" => Self::HASTE,
+            "mining_fatigue" => Self::MINING_FATIGUE,
+            "strength" => Self::STRENGTH,
+            "instant_health" => Self::INSTANT_HEALTH,
+            "instant_damage" => Self::INSTANT_DAMAGE,
+            "jump_boost" => Self::JUMP_BOOST,
+            "nausea" => Self::NAUSEA,
+            "regeneration" => Self::REGENERATION,
+            "resistance" => Self::RESISTANCE,
+            "fire_resistance" => Self::FIRE_RESISTANCE,
+            "water_breathing" => Self::WATER_BREATHING,
+            "invisibility" => Self::INVISIBILITY,
+            "blindness" => Self::BLINDNESS,
+            "night_vision" => Self::NIGHT_VISION,
+            "hunger" => Self::HUNGER,
+            "weakness" => Self::WEAKNESS,
+            "poison" => Self::POISON,
+            "wither" => Self::WITHER,
+            "health_boost" => Self::HEALTH_BOOST,
+            "absorption" => Self::ABSORPTION,
+            "saturation" => Self::SATURATION,
+            _ => return None,
+        })
+    }
 }
 
 /// A single active effect.
@@ -235,15 +287,20 @@ impl Default for Speed {
 }
 
 /// Damage immunity timer (ticks until can be hurt again).
+///
+/// While immune, only the part of a hit that exceeds `last_damage` gets through.
 #[derive(Component, Debug, Clone, Copy, Default)]
 pub struct DamageImmunity {
     pub ticks_remaining: u32,
+    /// Incoming damage of the hit that started the current immunity window.
+    pub last_damage: f32,
 }
 
 impl DamageImmunity {
     pub fn new(ticks: u32) -> Self {
         Self {
             ticks_remaining: ticks,
+            last_damage: 0.0,
         }
     }
 
@@ -255,3 +312,26 @@ impl DamageImmunity {
         self.ticks_remaining > 0
     }
 }
+
+/// Distance fallen since the entity last stood on the ground.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct FallDistance(pub f32);
+
+impl FallDistance {
+    /// Track one tick of vertical movement.
+    ///
+    /// Returns the total distance fallen when the entity lands.
+    pub fn update(&mut self, dy: f64, on_ground: bool) -> Option<f32> {
+        if dy < 0.0 {
+            self.0 -= dy as f32;
+        }
+        if on_ground && self.0 > 0.0 {
+            return Some(std::mem::take(&mut self.0));
+        }
+        None
+    }
+
+    pub fn reset(&mut self) {
+        self.0 = 0.0;
+    }
+}
@@ -35,6 +35,125 @@ pub struct AiState {
     pub target: Option<Entity>,
     pub path: Vec<glam::IVec3>,
     pub current_goal: Option<String>,
+    /// Ticks the current goal has been running.
+    pub goal_ticks: u32,
+    /// Ticks until the path to a moving target is found again.
+    pub repath_ticks: u32,
+    /// Ticks until the mob can attack again.
+    pub attack_cooldown: u32,
+    /// Point the mob is looking at this tick.
+    pub look_at: Option<glam::DVec3>,
+}
+
+impl AiState {
+    /// Stop whatever the mob was doing.
+    pub fn reset(&mut self) {
+        self.target = None;
+        self.path.clear();
+        self.goal_ticks = 0;
+        self.repath_ticks = 0;
+        self.look_at = None;
+    }
+}
+
+/// A behaviour a mob can run.
+///
+/// Speeds multiply the mob's `Speed`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Goal {
+    /// Run away for a few seconds after being hurt.
+    Flee { speed: f32 },
+    /// Chase the nearest player within `range` blocks and hit it.
+    MeleeAttack { speed: f32, damage: f32, range: f64 },
+    /// Follow players holding one of `items`.
+    Tempt { items: Vec<String>, speed: f32 },
+    /// As a baby, stay close to an adult of the same type.
+    FollowParent { speed: f32 },
+    /// Walk somewhere nearby, on average once every `interval` ticks.
+    Wander { speed: f32, interval: u32 },
+    /// Now and then, watch a player within `range` blocks.
+    LookAtPlayer { range: f64 },
+}
+
+impl Goal {
+    /// Name of the goal, as shown in `AiState::current_goal`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Goal::Flee { .. } => "flee",
+            Goal::MeleeAttack { .. } => "melee_attack",
+            Goal::Tempt { .. } => "tempt",
+            Goal::FollowParent { .. } => "follow_parent",
+            Goal::Wander { .. } => "wander",
+            Goal::LookAtPlayer { .. } => "look_at_player",
+        }
+    }
+
+    /// Multiplier of the mob's speed while the goal runs.
+    pub fn speed(&self) -> f32 {
+        match self {
+            Goal::Flee { speed }
+            | Goal::MeleeAttack { speed, .. }
+            | Goal::Tempt { speed, .. }
+            | Goal::FollowParent { speed }
+            | Goal::Wander { speed, .. } => *speed,
+            Goal::LookAtPlayer { .. } => 0.0,
+        }
+    }
+}
+
+/// A mob's goals and which one is running.
+///
+/// Every tick the first goal by priority (lowest number first) that is
+/// already running and can go on, or that can start, runs. A goal that
+/// starts stops any goal after it.
+#[derive(Component, Debug, Clone, Default)]
+pub struct Goals {
+    entries: Vec<(u8, Goal)>,
+    active: Option<usize>,
+}
+
+impl Goals {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a goal. Goals of the same priority keep the order they were added in.
+    pub fn with(mut self, priority: u8, goal: Goal) -> Self {
+        self.add(priority, goal);
+        self
+    }
+
+    /// Add a goal. Goals of the same priority keep the order they were added in.
+    pub fn add(&mut self, priority: u8, goal: Goal) {
+        let index = self.entries.partition_point(|(p, _)| *p <= priority);
+        self.entries.insert(index, (priority, goal));
+        if let Some(active) = self.active.as_mut()
+            && *active >= index
+        {
+            *active += 1;
+        }
+    }
+
+    /// Goals in the order they are tried.
+    pub fn iter(&self) -> impl Iterator<Item = &(u8, Goal)> {
+        self.entries.iter()
+    }
+
+    /// Index of the running goal.
+    pub fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+    pub fn set_active(&mut self, index: Option<usize>) {
+        self.active = index;
+    }
+
+    /// The running goal.
+    pub fn active_goal(&self) -> Option<&Goal> {
+        self.active
+            .and_then(|index| self.entries.get(index))
+            .map(|(_, goal)| goal)
+    }
 }
 
 /// Whether the mob is hostile.
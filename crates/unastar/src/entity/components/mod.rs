@@ -6,6 +6,7 @@ pub mod living;
 pub mod mob;
 pub mod player;
 pub mod projectile;
+pub mod tags;
 pub mod transform;
 
 pub use inventory::*;
@@ -14,4 +15,5 @@ pub use living::*;
 pub use mob::*;
 pub use player::*;
 pub use projectile::*;
+pub use tags::*;
 pub use transform::*;
@@ -1,20 +1,30 @@
 //! Player-specific components.
 
+use std::net::SocketAddr;
 use std::sync::atomic::{AtomicU32, Ordering};
 
 use bevy_ecs::lifecycle::HookContext;
 use bevy_ecs::prelude::*;
 use bevy_ecs::world::DeferredWorld;
+use glam::IVec3;
 use jolyne::valentine::McpePacket;
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
-use super::transform::Position;
+use super::living::Health;
+use super::transform::{Position, RuntimeIdComponent, runtime_id_on_insert, runtime_id_on_replace};
+use crate::world::Difficulty;
 
 /// Marker for player entities.
 #[derive(Component, Debug)]
 pub struct Player;
 
+/// Height of a player's eyes above their feet.
+///
+/// Bedrock clients report player positions at eye level, so `Position` of a
+/// player is this far above the bottom of its hitbox.
+pub const PLAYER_EYE_HEIGHT: f64 = 1.62;
+
 /// Player display name for network sync.
 #[derive(Component, Debug, Clone)]
 pub struct PlayerName(pub String);
@@ -30,6 +40,8 @@ pub struct PlayerSession {
     pub display_name: String,
     pub xuid: Option<String>,
     pub uuid: Option<String>,
+    /// Address the client connected from.
+    pub address: SocketAddr,
     /// Bounded outbound channel to prevent memory explosion on slow connections.
     pub outbound_tx: mpsc::Sender<McpePacket>,
     /// Count of dropped packets due to channel being full.
@@ -44,6 +56,7 @@ impl std::fmt::Debug for PlayerSession {
             .field("display_name", &self.display_name)
             .field("xuid", &self.xuid)
             .field("uuid", &self.uuid)
+            .field("address", &self.address)
             .field(
                 "packets_dropped",
                 &self.packets_dropped.load(Ordering::Relaxed),
@@ -59,6 +72,7 @@ impl PlayerSession {
         display_name: String,
         xuid: Option<String>,
         uuid: Option<String>,
+        address: SocketAddr,
         outbound_tx: mpsc::Sender<McpePacket>,
     ) -> Self {
         Self {
@@ -66,6 +80,7 @@ impl PlayerSession {
             display_name,
             xuid,
             uuid,
+            address,
             outbound_tx,
             packets_dropped: AtomicU32::new(0),
         }
@@ -136,6 +151,27 @@ impl GameMode {
     pub fn instant_break(&self) -> bool {
         matches!(self, GameMode::Creative)
     }
+
+    /// Bedrock game type ID, as used in saved player data.
+    pub fn id(self) -> u8 {
+        match self {
+            GameMode::Survival => 0,
+            GameMode::Creative => 1,
+            GameMode::Adventure => 2,
+            GameMode::Spectator => 6,
+        }
+    }
+
+    /// Game mode for a Bedrock game type ID.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(GameMode::Survival),
+            1 => Some(GameMode::Creative),
+            2 => Some(GameMode::Adventure),
+            6 => Some(GameMode::Spectator),
+            _ => None,
+        }
+    }
 }
 
 /// Player movement state flags.
@@ -178,44 +214,76 @@ impl Default for ChunkRadius {
 }
 
 /// Runtime entity ID for network synchronization.
+///
+/// Kept in the [`RuntimeIdIndex`](super::RuntimeIdIndex) by component hooks.
 #[derive(Component, Debug, Clone, Copy)]
+#[component(
+    on_insert = runtime_id_on_insert::<RuntimeEntityId>,
+    on_replace = runtime_id_on_replace::<RuntimeEntityId>
+)]
 pub struct RuntimeEntityId(pub i64);
 
+impl RuntimeIdComponent for RuntimeEntityId {
+    fn runtime_id(&self) -> i64 {
+        self.0
+    }
+}
+
 impl Default for RuntimeEntityId {
     fn default() -> Self {
         Self(1)
     }
 }
 
+/// Highest food level.
+pub const MAX_FOOD_LEVEL: i32 = 20;
+
+/// Exhaustion that costs one saturation or food point.
+const EXHAUSTION_PER_POINT: f32 = 4.0;
+/// Exhaustion stops building up past this.
+const MAX_EXHAUSTION: f32 = 40.0;
+/// Exhaustion of healing one health point.
+const REGEN_EXHAUSTION: f32 = 6.0;
+/// Ticks between heals, and between starvation damage.
+const SLOW_TICKS: u32 = 80;
+/// Ticks between heals while saturated with a full hunger bar.
+const FAST_TICKS: u32 = 10;
+
 /// Player food/hunger state.
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, PartialEq)]
 pub struct Hunger {
     pub food_level: i32,
     pub saturation: f32,
     pub exhaustion: f32,
+    /// Ticks towards the next heal or starvation damage.
+    pub tick_timer: u32,
 }
 
 impl Default for Hunger {
     fn default() -> Self {
         Self {
-            food_level: 20,
+            food_level: MAX_FOOD_LEVEL,
             saturation: 5.0,
             exhaustion: 0.0,
+            tick_timer: 0,
         }
     }
 }
 
+/// What the food bar does to health on a tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HungerOutcome {
+    /// Heal this much health.
+    Heal(f32),
+    /// Take one point of starvation damage.
+    Starve,
+}
+
 impl Hunger {
+    /// Add exhaustion from an activity. It is turned into lost saturation and
+    /// food on the next tick.
     pub fn exhaust(&mut self, amount: f32) {
-        self.exhaustion += amount;
-        while self.exhaustion >= 4.0 {
-            self.exhaustion -= 4.0;
-            if self.saturation > 0.0 {
-                self.saturation = (self.saturation - 1.0).max(0.0);
-            } else if self.food_level > 0 {
-                self.food_level -= 1;
-            }
-        }
+        self.exhaustion = (self.exhaustion + amount).min(MAX_EXHAUSTION);
     }
 
     pub fn can_sprint(&self) -> bool {
@@ -225,6 +293,98 @@ impl Hunger {
     pub fn can_regenerate(&self) -> bool {
         self.food_level >= 18
     }
+
+    /// Whether food can be eaten now.
+    pub fn can_eat(&self, always_edible: bool) -> bool {
+        always_edible || self.food_level < MAX_FOOD_LEVEL
+    }
+
+    /// Restore food and saturation. Saturation never exceeds the food level.
+    pub fn eat(&mut self, nutrition: i32, saturation: f32) {
+        self.food_level = (self.food_level + nutrition).min(MAX_FOOD_LEVEL);
+        self.saturation = (self.saturation + saturation).min(self.food_level as f32);
+    }
+
+    /// Advance the food bar one tick, as vanilla does.
+    ///
+    /// Exhaustion drains saturation and then food. A full bar heals quickly
+    /// while saturated and slowly down to 18 food; an empty bar starves the
+    /// player, down to 10 health on easy and 1 on normal. On peaceful food
+    /// never drains and both food and health come back on their own.
+    pub fn tick(
+        &mut self,
+        tick: u64,
+        difficulty: Difficulty,
+        health: &Health,
+        natural_regeneration: bool,
+    ) -> Option<HungerOutcome> {
+        let peaceful = difficulty == Difficulty::Peaceful;
+        if self.exhaustion >= EXHAUSTION_PER_POINT {
+            self.exhaustion -= EXHAUSTION_PER_POINT;
+            if self.saturation > 0.0 {
+                self.saturation = (self.saturation - 1.0).max(0.0);
+            } else if !peaceful {
+                self.food_level = (self.food_level - 1).max(0);
+            }
+        }
+
+        let hurt = health.current < health.max;
+        if peaceful && natural_regeneration {
+            if tick.is_multiple_of(10) && self.food_level < MAX_FOOD_LEVEL {
+                self.food_level += 1;
+            }
+            if tick.is_multiple_of(20) && hurt {
+                return Some(HungerOutcome::Heal(1.0));
+            }
+        }
+
+        if natural_regeneration
+            && hurt
+            && self.saturation > 0.0
+            && self.food_level >= MAX_FOOD_LEVEL
+        {
+            self.tick_timer += 1;
+            if self.tick_timer >= FAST_TICKS {
+                let spent = self.saturation.min(REGEN_EXHAUSTION);
+                self.exhaust(spent);
+                self.tick_timer = 0;
+                return Some(HungerOutcome::Heal(spent / REGEN_EXHAUSTION));
+            }
+        } else if natural_regeneration && hurt && self.can_regenerate() {
+            self.tick_timer += 1;
+            if self.tick_timer >= SLOW_TICKS {
+                self.exhaust(REGEN_EXHAUSTION);
+                self.tick_timer = 0;
+                return Some(HungerOutcome::Heal(1.0));
+            }
+        } else if self.food_level <= 0 {
+            self.tick_timer += 1;
+            if self.tick_timer >= SLOW_TICKS {
+                self.tick_timer = 0;
+                let starve = match difficulty {
+                    Difficulty::Hard => true,
+                    Difficulty::Normal => health.current > 1.0,
+                    Difficulty::Easy => health.current > 10.0,
+                    Difficulty::Peaceful => false,
+                };
+                if starve {
+                    return Some(HungerOutcome::Starve);
+                }
+            }
+        } else {
+            self.tick_timer = 0;
+        }
+        None
+    }
+}
+
+/// An item being used over time, such as food being eaten.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsingItem {
+    /// Hotbar slot of the item.
+    pub slot: u8,
+    /// Server tick the use started on.
+    pub started: u64,
 }
 
 /// Player experience.
@@ -234,6 +394,15 @@ pub struct Experience {
     pub progress: f32, // 0.0 to 1.0
 }
 
+/// Player respawn point set by a bed or respawn anchor.
+///
+/// `position` is `None` until one is set, in which case the world spawn is used.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct SpawnPoint {
+    pub position: Option<IVec3>,
+    pub dimension: i32,
+}
+
 /// Block breaking state for survival mode.
 /// Tracks ongoing block breaking for crack animation and anti-cheat validation.
 #[derive(Component, Debug, Default)]
@@ -350,14 +519,18 @@ impl SpatialChunk {
 }
 
 /// Hook called when SpatialChunk is inserted.
-/// Adds the entity to EntityGrid at its chunk position.
+/// Adds the entity to EntityGrid at its world and chunk position.
 fn spatial_chunk_on_insert(mut world: DeferredWorld<'_>, context: HookContext) {
     let entity = context.entity;
     let Some(chunk) = world.get::<SpatialChunk>(entity).copied() else {
         return;
     };
+    let world_id = world
+        .get::<crate::world::WorldId>(entity)
+        .copied()
+        .unwrap_or_default();
     if let Some(mut grid) = world.get_resource_mut::<crate::server::broadcast::EntityGrid>() {
-        grid.insert(chunk.as_tuple(), entity);
+        grid.insert(world_id, chunk.as_tuple(), entity);
     }
 }
 
@@ -368,8 +541,12 @@ fn spatial_chunk_on_remove(mut world: DeferredWorld<'_>, context: HookContext) {
     let Some(chunk) = world.get::<SpatialChunk>(entity).copied() else {
         return;
     };
+    let world_id = world
+        .get::<crate::world::WorldId>(entity)
+        .copied()
+        .unwrap_or_default();
     if let Some(mut grid) = world.get_resource_mut::<crate::server::broadcast::EntityGrid>() {
-        grid.remove(chunk.as_tuple(), entity);
+        grid.remove(world_id, chunk.as_tuple(), entity);
     }
 }
 
@@ -410,4 +587,107 @@ mod tests {
         let chunk = SpatialChunk::from_position(&pos);
         assert_eq!(chunk.x, -2);
     }
+
+    fn run(hunger: &mut Hunger, health: &mut Health, difficulty: Difficulty, ticks: u64) -> u32 {
+        let mut starved = 0;
+        for tick in 1..=ticks {
+            match hunger.tick(tick, difficulty, health, true) {
+                Some(HungerOutcome::Heal(amount)) => health.heal(amount),
+                Some(HungerOutcome::Starve) => {
+                    health.damage(1.0);
+                    starved += 1;
+                }
+                None => {}
+            }
+        }
+        starved
+    }
+
+    #[test]
+    fn test_hunger_exhaustion() {
+        let mut hunger = Hunger::default();
+        hunger.exhaust(100.0);
+        assert_eq!(hunger.exhaustion, MAX_EXHAUSTION);
+
+        let mut hunger = Hunger {
+            saturation: 1.0,
+            ..Hunger::default()
+        };
+        let health = Health::default();
+        for _ in 0..3 {
+            hunger.exhaust(4.0);
+            hunger.tick(1, Difficulty::Normal, &health, true);
+        }
+        // Saturation goes first, then food
+        assert_eq!((hunger.saturation, hunger.food_level), (0.0, 18));
+
+        hunger.exhaust(4.0);
+        hunger.tick(1, Difficulty::Peaceful, &health, true);
+        assert_eq!(hunger.food_level, 18);
+
+        hunger.eat(8, 12.8);
+        assert_eq!(hunger.food_level, 20);
+        assert_eq!(hunger.saturation, 12.8);
+        assert!(!hunger.can_eat(false) && hunger.can_eat(true));
+    }
+
+    #[test]
+    fn test_hunger_regeneration_and_starvation() {
+        // Saturated with a full bar: half a heart every 10 ticks
+        let mut hunger = Hunger {
+            saturation: 20.0,
+            ..Hunger::default()
+        };
+        let mut health = Health {
+            current: 10.0,
+            max: 20.0,
+        };
+        run(&mut hunger, &mut health, Difficulty::Normal, 50);
+        assert_eq!(health.current, 15.0);
+        assert!(hunger.exhaustion > 0.0);
+
+        // No regeneration below 18 food
+        let mut hunger = Hunger {
+            food_level: 17,
+            saturation: 0.0,
+            ..Hunger::default()
+        };
+        run(&mut hunger, &mut health, Difficulty::Normal, 400);
+        assert_eq!(health.current, 15.0);
+
+        // Starvation stops at 10 health on easy and 1 on normal
+        let mut hunger = Hunger {
+            food_level: 0,
+            saturation: 0.0,
+            ..Hunger::default()
+        };
+        let mut health = Health::default();
+        assert_eq!(run(&mut hunger, &mut health, Difficulty::Easy, 80 * 15), 10);
+        assert_eq!(health.current, 10.0);
+        run(&mut hunger, &mut health, Difficulty::Normal, 80 * 15);
+        assert_eq!(health.current, 1.0);
+        run(&mut hunger, &mut health, Difficulty::Hard, 80);
+        assert_eq!(health.current, 0.0);
+    }
+
+    #[test]
+    fn test_runtime_id_index() {
+        use super::super::transform::{RuntimeId, RuntimeIdIndex};
+
+        let mut world = World::new();
+        world.init_resource::<RuntimeIdIndex>();
+        let player = world.spawn(RuntimeEntityId(1)).id();
+        let mob = world.spawn(RuntimeId(2)).id();
+
+        let index = world.resource::<RuntimeIdIndex>();
+        assert_eq!(index.get(1), Some(player));
+        assert_eq!(index.get(2), Some(mob));
+
+        world.entity_mut(mob).insert(RuntimeId(3));
+        world.despawn(player);
+        let index = world.resource::<RuntimeIdIndex>();
+        assert_eq!(index.get(1), None);
+        assert_eq!(index.get(2), None);
+        assert_eq!(index.get(3), Some(mob));
+    }
 }
@@ -0,0 +1,30 @@
+//! Scoreboard-style entity tags.
+
+use bevy_ecs::prelude::*;
+use std::collections::BTreeSet;
+
+/// Tags on an entity, matched by the `tag=` selector filter.
+///
+/// Entities without the component have no tags.
+#[derive(Component, Debug, Clone, Default)]
+pub struct Tags(pub BTreeSet<String>);
+
+impl Tags {
+    pub fn has(&self, tag: &str) -> bool {
+        self.0.contains(tag)
+    }
+
+    /// Add a tag. Returns `false` if it was already set.
+    pub fn add(&mut self, tag: impl Into<String>) -> bool {
+        self.0.insert(tag.into())
+    }
+
+    /// Remove a tag. Returns `false` if it wasn't set.
+    pub fn remove(&mut self, tag: &str) -> bool {
+        self.0.remove(tag)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
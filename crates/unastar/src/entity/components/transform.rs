@@ -1,6 +1,10 @@
 //! Spatial transform components.
 
+use std::collections::HashMap;
+
+use bevy_ecs::lifecycle::HookContext;
 use bevy_ecs::prelude::*;
+use bevy_ecs::world::DeferredWorld;
 use glam::DVec3;
 
 /// World position in double precision (blocks).
@@ -71,8 +75,51 @@ pub struct HeadYaw(pub f32);
 #[derive(Component, Debug, Clone, Copy, Default)]
 pub struct OnGround(pub bool);
 
+/// Collision box dimensions in blocks.
+///
+/// The box is centred horizontally on the entity's [`Position`], which is
+/// taken to be at its feet.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Hitbox {
+    pub width: f64,
+    pub height: f64,
+    /// Maximum height the entity can walk up without jumping.
+    pub step_height: f64,
+}
+
+impl Hitbox {
+    /// Player-sized box (0.6 x 1.8, steps 0.6).
+    pub const PLAYER: Self = Self::new(0.6, 1.8, 0.6);
+    /// Dropped item box (0.25 x 0.25, no stepping).
+    pub const ITEM: Self = Self::new(0.25, 0.25, 0.0);
+    /// Arrow-sized projectile box (0.5 x 0.5, no stepping).
+    pub const PROJECTILE: Self = Self::new(0.5, 0.5, 0.0);
+
+    pub const fn new(width: f64, height: f64, step_height: f64) -> Self {
+        Self {
+            width,
+            height,
+            step_height,
+        }
+    }
+
+    /// World-space bounding box at the given feet position.
+    pub fn aabb_at(&self, position: glam::DVec3) -> crate::entity::collision::Aabb {
+        crate::entity::collision::Aabb::from_feet(position, self.width, self.height)
+    }
+}
+
+impl Default for Hitbox {
+    fn default() -> Self {
+        Self::PLAYER
+    }
+}
+
 /// Unique runtime ID for network protocol.
+///
+/// Kept in the [`RuntimeIdIndex`] by component hooks.
 #[derive(Component, Debug, Clone, Copy)]
+#[component(on_insert = runtime_id_on_insert::<RuntimeId>, on_replace = runtime_id_on_replace::<RuntimeId>)]
 pub struct RuntimeId(pub i64);
 
 impl RuntimeId {
@@ -81,6 +128,65 @@ impl RuntimeId {
     }
 }
 
+/// Maps network runtime IDs to entities.
+///
+/// Covers players (`RuntimeEntityId`) and other entities (`RuntimeId`), so
+/// packets naming an entity can be resolved without scanning every entity.
+#[derive(Resource, Debug, Default)]
+pub struct RuntimeIdIndex {
+    entities: HashMap<i64, Entity>,
+}
+
+impl RuntimeIdIndex {
+    /// The entity with a runtime ID.
+    pub fn get(&self, runtime_id: i64) -> Option<Entity> {
+        self.entities.get(&runtime_id).copied()
+    }
+}
+
+/// A component holding a network runtime ID.
+pub(super) trait RuntimeIdComponent: Component {
+    fn runtime_id(&self) -> i64;
+}
+
+impl RuntimeIdComponent for RuntimeId {
+    fn runtime_id(&self) -> i64 {
+        self.0
+    }
+}
+
+/// Hook called when a runtime ID is inserted.
+/// Adds the entity to the `RuntimeIdIndex`.
+pub(super) fn runtime_id_on_insert<C: RuntimeIdComponent>(
+    mut world: DeferredWorld<'_>,
+    context: HookContext,
+) {
+    let entity = context.entity;
+    let Some(runtime_id) = world.get::<C>(entity).map(C::runtime_id) else {
+        return;
+    };
+    if let Some(mut index) = world.get_resource_mut::<RuntimeIdIndex>() {
+        index.entities.insert(runtime_id, entity);
+    }
+}
+
+/// Hook called when a runtime ID is replaced or removed.
+/// Removes the entity from the `RuntimeIdIndex`.
+pub(super) fn runtime_id_on_replace<C: RuntimeIdComponent>(
+    mut world: DeferredWorld<'_>,
+    context: HookContext,
+) {
+    let entity = context.entity;
+    let Some(runtime_id) = world.get::<C>(entity).map(C::runtime_id) else {
+        return;
+    };
+    if let Some(mut index) = world.get_resource_mut::<RuntimeIdIndex>()
+        && index.get(runtime_id) == Some(entity)
+    {
+        index.entities.remove(&runtime_id);
+    }
+}
+
 /// Entity age in ticks.
 #[derive(Component, Debug, Clone, Copy, Default)]
 pub struct Age(pub u64);
@@ -1,7 +1,20 @@
-//! Damage and healing sources.
+//! Damage and healing sources, and the vanilla damage reduction formulas.
 
 use bevy_ecs::prelude::*;
 
+use crate::item::EnchantmentType;
+
+pub use unastar_api::native::DamageStage;
+
+/// Ticks an entity ignores weaker hits for after being damaged.
+pub const IMMUNITY_TICKS: u32 = 10;
+
+/// Fall distance absorbed without damage.
+pub const SAFE_FALL_DISTANCE: f32 = 3.0;
+
+/// Damage multiplier of a critical hit.
+pub const CRITICAL_MULTIPLIER: f32 = 1.5;
+
 /// Source of damage dealt to an entity.
 #[derive(Debug, Clone)]
 pub enum DamageSource {
@@ -59,6 +72,32 @@ impl DamageSource {
         !matches!(self, DamageSource::Void | DamageSource::Starvation)
     }
 
+    /// Whether Protection-family enchantments reduce this damage.
+    pub fn reduced_by_enchantments(&self) -> bool {
+        !matches!(self, DamageSource::Void | DamageSource::Starvation)
+    }
+
+    /// Enchantment protection factor one armour enchantment gives against this damage.
+    pub fn protection_factor(&self, kind: EnchantmentType, level: i16) -> i32 {
+        let level = i32::from(level.max(0));
+        match kind {
+            EnchantmentType::PROTECTION => level,
+            EnchantmentType::FIRE_PROTECTION if self.is_fire() => level * 2,
+            EnchantmentType::BLAST_PROTECTION if matches!(self, DamageSource::Explosion { .. }) => {
+                level * 2
+            }
+            EnchantmentType::PROJECTILE_PROTECTION
+                if matches!(self, DamageSource::Projectile { .. }) =>
+            {
+                level * 2
+            }
+            EnchantmentType::FEATHER_FALLING if matches!(self, DamageSource::Fall { .. }) => {
+                level * 3
+            }
+            _ => 0,
+        }
+    }
+
     /// Whether this is fire-based damage (blocked by fire resistance).
     pub fn is_fire(&self) -> bool {
         matches!(self, DamageSource::Fire { .. })
@@ -87,6 +126,21 @@ impl DamageSource {
         false
     }
 
+    /// Hunger exhaustion a player gains from taking this damage.
+    pub fn exhaustion(&self) -> f32 {
+        match self {
+            DamageSource::Attack { .. }
+            | DamageSource::Projectile { .. }
+            | DamageSource::Fire { is_lava: true }
+            | DamageSource::Explosion { .. }
+            | DamageSource::Lightning
+            | DamageSource::Thorns { .. }
+            | DamageSource::Cactus
+            | DamageSource::SweetBerryBush => 0.1,
+            _ => 0.0,
+        }
+    }
+
     /// Get the attacker entity if any.
     pub fn attacker(&self) -> Option<Entity> {
         match self {
@@ -98,6 +152,128 @@ impl DamageSource {
             _ => None,
         }
     }
+
+    /// Short name of the damage kind, as reported to plugins.
+    pub fn cause_name(&self) -> &'static str {
+        match self {
+            DamageSource::Attack { .. } => "attack",
+            DamageSource::Projectile { .. } => "projectile",
+            DamageSource::Fall { .. } => "fall",
+            DamageSource::Drowning => "drowning",
+            DamageSource::Suffocation => "suffocation",
+            DamageSource::Void => "void",
+            DamageSource::Fire { is_lava: true } => "lava",
+            DamageSource::Fire { is_lava: false } => "fire",
+            DamageSource::Explosion { .. } => "explosion",
+            DamageSource::Lightning => "lightning",
+            DamageSource::Starvation => "starvation",
+            DamageSource::Magic { .. } => "magic",
+            DamageSource::Thorns { .. } => "thorns",
+            DamageSource::Cactus => "cactus",
+            DamageSource::SweetBerryBush => "sweet_berry_bush",
+            DamageSource::Generic => "generic",
+        }
+    }
+
+    /// Translation key for the death message of an entity killed by this damage.
+    ///
+    /// The message takes the victim's name as its first parameter and, when
+    /// there is a `killer`, the killer's name as its second.
+    pub fn death_message_key(&self, killer: Option<Killer>) -> &'static str {
+        let has_killer = killer.is_some();
+        match self {
+            DamageSource::Attack { .. } if killer == Some(Killer::Player) => "death.attack.player",
+            DamageSource::Attack { .. } if has_killer => "death.attack.mob",
+            DamageSource::Projectile { .. } if has_killer => "death.attack.arrow",
+            DamageSource::Thorns { .. } if has_killer => "death.attack.thorns",
+            DamageSource::Explosion { .. } if has_killer => "death.attack.explosion.player",
+            DamageSource::Explosion { .. } => "death.attack.explosion",
+            DamageSource::Magic { .. } if has_killer => "death.attack.indirectMagic",
+            DamageSource::Magic { .. } => "death.attack.magic",
+            DamageSource::Fall { .. } => "death.attack.fall",
+            DamageSource::Drowning => "death.attack.drown",
+            DamageSource::Suffocation => "death.attack.inWall",
+            DamageSource::Void => "death.attack.outOfWorld",
+            DamageSource::Fire { is_lava: true } => "death.attack.lava",
+            DamageSource::Fire { is_lava: false } => "death.attack.onFire",
+            DamageSource::Lightning => "death.attack.lightningBolt",
+            DamageSource::Starvation => "death.attack.starve",
+            DamageSource::Cactus => "death.attack.cactus",
+            DamageSource::SweetBerryBush => "death.attack.sweetBerry",
+            _ => "death.attack.generic",
+        }
+    }
+}
+
+/// What kind of entity killed another, for its death message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Killer {
+    Player,
+    Mob,
+}
+
+/// Damage left after armour.
+///
+/// Toughness makes armour lose less of its effect against big hits; the
+/// effective armour never drops below a fifth of its points.
+pub fn armour_reduction(damage: f32, points: f32, toughness: f32) -> f32 {
+    let toughness_factor = 2.0 + toughness / 4.0;
+    let effective = (points - damage / toughness_factor).clamp(points * 0.2, 20.0);
+    damage * (1.0 - effective / 25.0)
+}
+
+/// Damage left after the Resistance effect: 20% less per level.
+pub fn resistance_reduction(damage: f32, amplifier: u8) -> f32 {
+    let levels = (u32::from(amplifier) + 1).min(5) as f32;
+    damage * (1.0 - levels * 0.2)
+}
+
+/// Damage left after armour enchantments with the given total protection factor.
+///
+/// The factor is capped at 20, an 80% reduction.
+pub fn enchantment_reduction(damage: f32, protection_factor: i32) -> f32 {
+    let factor = protection_factor.clamp(0, 20) as f32;
+    damage * (1.0 - factor / 25.0)
+}
+
+/// Damage taken from landing after a fall.
+///
+/// Jump Boost raises the safe distance by one block per level.
+pub fn fall_damage(distance: f32, jump_boost: Option<u8>) -> f32 {
+    let safe = SAFE_FALL_DISTANCE + jump_boost.map_or(0.0, |amplifier| amplifier as f32 + 1.0);
+    (distance - safe).ceil().max(0.0)
+}
+
+/// Damage of a melee hit with a weapon dealing `base` damage.
+///
+/// Strength adds 3 and Weakness takes 4 per level before the critical
+/// multiplier; Sharpness adds 1.25 per level after it.
+pub fn melee_damage(
+    base: f32,
+    sharpness: i16,
+    strength: Option<u8>,
+    weakness: Option<u8>,
+    critical: bool,
+) -> f32 {
+    let levels = |amplifier: Option<u8>| amplifier.map_or(0.0, |a| a as f32 + 1.0);
+    let mut damage = (base + 3.0 * levels(strength) - 4.0 * levels(weakness)).max(0.0);
+    if critical {
+        damage *= CRITICAL_MULTIPLIER;
+    }
+    damage + 1.25 * sharpness.max(0) as f32
+}
+
+/// One stage of the damage calculation, triggered before the stage's amount is used.
+///
+/// Observers may change `amount` or set `cancelled`. A cancelled hit deals
+/// no damage and skips the remaining stages.
+#[derive(Event, Debug)]
+pub struct DamageCalculation {
+    pub entity: Entity,
+    pub source: DamageSource,
+    pub stage: DamageStage,
+    pub amount: f32,
+    pub cancelled: bool,
 }
 
 /// Source of healing applied to an entity.
@@ -120,6 +296,8 @@ pub enum HealingSource {
 }
 
 /// Event for when an entity takes damage.
+///
+/// `amount` is the incoming damage, `final_amount` what was taken from health.
 #[derive(Event, Debug)]
 pub struct DamageEvent {
     pub entity: Entity,
@@ -142,3 +320,82 @@ pub struct DeathEvent {
     pub entity: Entity,
     pub source: DamageSource,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_death_message_key() {
+        let attacker = Entity::from_raw_u32(1).unwrap();
+        let attack = DamageSource::Attack { attacker };
+        assert_eq!(
+            attack.death_message_key(Some(Killer::Mob)),
+            "death.attack.mob"
+        );
+        assert_eq!(
+            attack.death_message_key(Some(Killer::Player)),
+            "death.attack.player"
+        );
+        // The attacker may have despawned before its name could be resolved
+        assert_eq!(attack.death_message_key(None), "death.attack.generic");
+
+        let lava = DamageSource::Fire { is_lava: true };
+        assert_eq!(lava.death_message_key(None), "death.attack.lava");
+        let explosion = DamageSource::Explosion { source: None };
+        assert_eq!(explosion.death_message_key(None), "death.attack.explosion");
+        assert_eq!(
+            explosion.death_message_key(Some(Killer::Mob)),
+            "death.attack.explosion.player"
+        );
+    }
+
+    #[test]
+    fn test_armour_reduction() {
+        // Full diamond: 20 points, 8 toughness
+        assert!((armour_reduction(10.0, 20.0, 8.0) - 3.0).abs() < 1e-4);
+        // Big hits wear armour down to a fifth of its points
+        assert!((armour_reduction(100.0, 20.0, 0.0) - 84.0).abs() < 1e-4);
+        assert_eq!(armour_reduction(5.0, 0.0, 0.0), 5.0);
+    }
+
+    #[test]
+    fn test_effect_and_enchantment_reduction() {
+        assert!((resistance_reduction(10.0, 0) - 8.0).abs() < 1e-4);
+        assert_eq!(resistance_reduction(10.0, 4), 0.0);
+        assert_eq!(resistance_reduction(10.0, 255), 0.0);
+
+        assert!((enchantment_reduction(10.0, 4) - 8.4).abs() < 1e-4);
+        assert!((enchantment_reduction(10.0, 32) - 2.0).abs() < 1e-4);
+
+        let fall = DamageSource::Fall { distance: 10.0 };
+        assert_eq!(
+            fall.protection_factor(EnchantmentType::FEATHER_FALLING, 4),
+            12
+        );
+        assert_eq!(
+            fall.protection_factor(EnchantmentType::BLAST_PROTECTION, 4),
+            0
+        );
+        assert_eq!(fall.protection_factor(EnchantmentType::PROTECTION, 4), 4);
+    }
+
+    #[test]
+    fn test_melee_damage() {
+        assert_eq!(melee_damage(8.0, 0, None, None, false), 8.0);
+        assert_eq!(melee_damage(8.0, 0, None, None, true), 12.0);
+        // Sharpness V on a critical hit is added after the multiplier
+        assert!((melee_damage(8.0, 5, None, None, true) - 18.25).abs() < 1e-4);
+        assert_eq!(melee_damage(1.0, 0, Some(1), None, false), 7.0);
+        assert_eq!(melee_damage(1.0, 0, None, Some(0), false), 0.0);
+    }
+
+    #[test]
+    fn test_fall_damage() {
+        assert_eq!(fall_damage(3.0, None), 0.0);
+        assert_eq!(fall_damage(3.5, None), 1.0);
+        assert_eq!(fall_damage(10.0, None), 7.0);
+        // Jump Boost II adds two safe blocks
+        assert_eq!(fall_damage(10.0, Some(1)), 5.0);
+    }
+}
@@ -0,0 +1,242 @@
+//! Built-in mob types: their stats and AI goals.
+
+use rand::Rng;
+
+use crate::entity::components::{Goal, Goals, Hitbox};
+use crate::item::ItemStack;
+
+/// Stats of a mob type.
+#[derive(Debug, Clone, Copy)]
+pub struct MobDefinition {
+    pub identifier: &'static str,
+    pub health: f32,
+    pub hitbox: Hitbox,
+    /// Movement speed, as the `minecraft:movement` attribute.
+    pub speed: f32,
+    /// Damage of a melee hit on Normal difficulty; zero for mobs that don't attack.
+    pub attack_damage: f32,
+    /// Items that tempt the mob.
+    pub temptations: &'static [&'static str],
+    /// Items dropped when the mob dies.
+    pub loot: &'static [MobDrop],
+}
+
+/// An item a mob drops on death.
+#[derive(Debug, Clone, Copy)]
+pub struct MobDrop {
+    pub item: &'static str,
+    /// Item dropped instead when the mob dies on fire.
+    pub cooked: Option<&'static str>,
+    pub min: u8,
+    pub max: u8,
+}
+
+impl MobDrop {
+    const fn new(item: &'static str, min: u8, max: u8) -> Self {
+        Self {
+            item,
+            cooked: None,
+            min,
+            max,
+        }
+    }
+
+    const fn cookable(item: &'static str, cooked: &'static str, min: u8, max: u8) -> Self {
+        Self {
+            item,
+            cooked: Some(cooked),
+            min,
+            max,
+        }
+    }
+}
+
+const SEEDS: &[&str] = &[
+    "minecraft:wheat_seeds",
+    "minecraft:pumpkin_seeds",
+    "minecraft:melon_seeds",
+    "minecraft:beetroot_seeds",
+];
+
+/// Every mob type that can be spawned.
+pub const MOBS: &[MobDefinition] = &[
+    MobDefinition {
+        identifier: "minecraft:zombie",
+        health: 20.0,
+        hitbox: Hitbox::new(0.6, 1.9, 0.6),
+        speed: 0.23,
+        attack_damage: 3.0,
+        temptations: &[],
+        loot: &[MobDrop::new("minecraft:rotten_flesh", 0, 2)],
+    },
+    MobDefinition {
+        identifier: "minecraft:husk",
+        health: 20.0,
+        hitbox: Hitbox::new(0.6, 1.9, 0.6),
+        speed: 0.23,
+        attack_damage: 3.0,
+        temptations: &[],
+        loot: &[MobDrop::new("minecraft:rotten_flesh", 0, 2)],
+    },
+    MobDefinition {
+        identifier: "minecraft:cow",
+        health: 10.0,
+        hitbox: Hitbox::new(0.9, 1.3, 0.6),
+        speed: 0.25,
+        attack_damage: 0.0,
+        temptations: &["minecraft:wheat"],
+        loot: &[
+            MobDrop::new("minecraft:leather", 0, 2),
+            MobDrop::cookable("minecraft:beef", "minecraft:cooked_beef", 1, 3),
+        ],
+    },
+    MobDefinition {
+        identifier: "minecraft:pig",
+        health: 10.0,
+        hitbox: Hitbox::new(0.9, 0.9, 0.6),
+        speed: 0.25,
+        attack_damage: 0.0,
+        temptations: &["minecraft:carrot", "minecraft:potato", "minecraft:beetroot"],
+        loot: &[MobDrop::cookable(
+            "minecraft:porkchop",
+            "minecraft:cooked_porkchop",
+            1,
+            3,
+        )],
+    },
+    MobDefinition {
+        identifier: "minecraft:sheep",
+        health: 8.0,
+        hitbox: Hitbox::new(0.9, 1.3, 0.6),
+        speed: 0.25,
+        attack_damage: 0.0,
+        temptations: &["minecraft:wheat"],
+        loot: &[
+            MobDrop::new("minecraft:white_wool", 1, 1),
+            MobDrop::cookable("minecraft:mutton", "minecraft:cooked_mutton", 1, 2),
+        ],
+    },
+    MobDefinition {
+        identifier: "minecraft:chicken",
+        health: 4.0,
+        hitbox: Hitbox::new(0.6, 0.8, 0.6),
+        speed: 0.25,
+        attack_damage: 0.0,
+        temptations: SEEDS,
+        loot: &[
+            MobDrop::new("minecraft:feather", 0, 2),
+            MobDrop::cookable("minecraft:chicken", "minecraft:cooked_chicken", 1, 1),
+        ],
+    },
+];
+
+impl MobDefinition {
+    /// Look up a mob type by identifier, with or without the `minecraft:` prefix.
+    pub fn get(identifier: &str) -> Option<&'static Self> {
+        let identifier = identifier.strip_prefix("minecraft:").unwrap_or(identifier);
+        MOBS.iter()
+            .find(|mob| mob.identifier.strip_prefix("minecraft:") == Some(identifier))
+    }
+
+    /// Roll the items the mob drops on death.
+    pub fn roll_loot(&self, on_fire: bool) -> Vec<ItemStack> {
+        let mut rng = rand::thread_rng();
+        self.loot
+            .iter()
+            .filter_map(|drop| {
+                let count = rng.gen_range(drop.min..=drop.max);
+                let item = match drop.cooked {
+                    Some(cooked) if on_fire => cooked,
+                    _ => drop.item,
+                };
+                (count > 0).then(|| ItemStack::new(item, count))
+            })
+            .collect()
+    }
+
+    /// Whether the mob attacks players.
+    pub fn is_hostile(&self) -> bool {
+        self.attack_damage > 0.0
+    }
+
+    /// The mob's default goals.
+    ///
+    /// Hostile mobs hunt players and otherwise wander. Animals flee when
+    /// hurt, follow players holding their food, and babies keep close to
+    /// their parents.
+    pub fn goals(&self) -> Goals {
+        let mut goals = Goals::new();
+        if self.is_hostile() {
+            goals.add(
+                2,
+                Goal::MeleeAttack {
+                    speed: 1.0,
+                    damage: self.attack_damage,
+                    range: 16.0,
+                },
+            );
+        } else {
+            goals.add(1, Goal::Flee { speed: 1.25 });
+        }
+        if !self.temptations.is_empty() {
+            goals.add(
+                3,
+                Goal::Tempt {
+                    items: self.temptations.iter().map(|id| id.to_string()).collect(),
+                    speed: 1.2,
+                },
+            );
+            goals.add(4, Goal::FollowParent { speed: 1.1 });
+        }
+        goals
+            .with(
+                5,
+                Goal::Wander {
+                    speed: 0.8,
+                    interval: 120,
+                },
+            )
+            .with(6, Goal::LookAtPlayer { range: 6.0 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mob_goals() {
+        let zombie = MobDefinition::get("zombie").unwrap();
+        assert_eq!(zombie.identifier, "minecraft:zombie");
+        let names: Vec<_> = zombie.goals().iter().map(|(_, goal)| goal.name()).collect();
+        assert_eq!(names, ["melee_attack", "wander", "look_at_player"]);
+
+        let cow = MobDefinition::get("minecraft:cow").unwrap();
+        let names: Vec<_> = cow.goals().iter().map(|(_, goal)| goal.name()).collect();
+        assert_eq!(
+            names,
+            ["flee", "tempt", "follow_parent", "wander", "look_at_player"]
+        );
+        assert!(MobDefinition::get("minecraft:ghast").is_none());
+    }
+
+    #[test]
+    fn test_mob_loot() {
+        let pig = MobDefinition::get("pig").unwrap();
+        for _ in 0..20 {
+            let loot = pig.roll_loot(false);
+            assert_eq!(loot.len(), 1);
+            assert_eq!(loot[0].item_id, "minecraft:porkchop");
+            assert!((1..=3).contains(&loot[0].count));
+        }
+        assert_eq!(pig.roll_loot(true)[0].item_id, "minecraft:cooked_porkchop");
+
+        let zombie = MobDefinition::get("zombie").unwrap();
+        for loot in (0..20).map(|_| zombie.roll_loot(false)) {
+            assert!(
+                loot.iter()
+                    .all(|item| item.item_id == "minecraft:rotten_flesh")
+            );
+        }
+    }
+}
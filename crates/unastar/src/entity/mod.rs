@@ -4,9 +4,12 @@
 //! This includes players, mobs, items, projectiles, and other game entities.
 
 pub mod bundles;
+pub mod collision;
 pub mod components;
 pub mod damage;
 pub mod metadata;
+pub mod mobs;
+pub mod pathfinding;
 pub mod systems;
 
 pub use bundles::*;
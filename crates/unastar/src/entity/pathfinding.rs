@@ -0,0 +1,401 @@
+//! A* pathfinding over the block grid.
+//!
+//! A path is the list of block cells a mob's feet pass through. A cell is a
+//! node if the mob fits in it (no collision from its feet up to its height)
+//! and it can stand there: on a block no taller than a full block, or
+//! swimming in water. From a node a mob can walk to any of its eight
+//! neighbours, jump up one block, drop down a few blocks, or swim up and
+//! down in water. Lava and unloaded chunks are never entered.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use glam::IVec3;
+
+use crate::entity::collision::BlockSource;
+use crate::registry::block::collision_shape;
+use crate::world::chunk::blocks;
+
+/// Cost of a straight step; the other costs are relative to it.
+const STEP_COST: u32 = 10;
+/// Cost of a diagonal step.
+const DIAGONAL_COST: u32 = 14;
+/// Extra cost of jumping up a block.
+const JUMP_COST: u32 = 10;
+/// Extra cost per block dropped.
+const DROP_COST: u32 = 5;
+/// Cost multiplier for moving into water.
+const WATER_FACTOR: u32 = 2;
+
+/// The mob a path is for and how hard to search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathOptions {
+    /// Height of the mob in whole blocks.
+    pub height: i32,
+    /// Furthest the mob drops down in one step.
+    pub max_drop: i32,
+    /// Nodes to expand before settling for the closest one found.
+    pub max_nodes: usize,
+    /// Whether paths may go through water.
+    pub can_swim: bool,
+}
+
+impl Default for PathOptions {
+    fn default() -> Self {
+        Self {
+            height: 2,
+            max_drop: 3,
+            max_nodes: 1024,
+            can_swim: true,
+        }
+    }
+}
+
+impl PathOptions {
+    /// Options for a mob `height` blocks tall.
+    pub fn for_height(height: f64) -> Self {
+        Self {
+            height: (height.ceil() as i32).max(1),
+            ..Self::default()
+        }
+    }
+}
+
+/// What a cell holds, as far as walking is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Open,
+    Water,
+    Blocked,
+}
+
+struct Grid<'a, B> {
+    blocks: &'a B,
+    options: &'a PathOptions,
+}
+
+impl<B: BlockSource> Grid<'_, B> {
+    fn cell(&self, pos: IVec3) -> Cell {
+        match self.blocks.block_at(pos.x, pos.y, pos.z) {
+            None => Cell::Blocked,
+            Some(id) if blocks::is_water(id) => {
+                if self.options.can_swim {
+                    Cell::Water
+                } else {
+                    Cell::Blocked
+                }
+            }
+            Some(id) if blocks::is_lava(id) => Cell::Blocked,
+            Some(id) if collision_shape(id).is_empty() => Cell::Open,
+            Some(_) => Cell::Blocked,
+        }
+    }
+
+    /// Whether the mob fits with its feet in `pos`.
+    fn fits(&self, pos: IVec3) -> bool {
+        (0..self.options.height).all(|dy| self.cell(pos + IVec3::Y * dy) != Cell::Blocked)
+    }
+
+    /// Whether the block at `pos` can be stood on.
+    fn supports(&self, pos: IVec3) -> bool {
+        let Some(id) = self.blocks.block_at(pos.x, pos.y, pos.z) else {
+            return false;
+        };
+        if blocks::is_lava(id) {
+            return false;
+        }
+        let top = collision_shape(id)
+            .boxes()
+            .iter()
+            .map(|b| b.max.y)
+            .fold(0.0, f64::max);
+        top > 0.0 && top <= 1.0
+    }
+
+    /// Whether `pos` is a node: the mob fits there and is standing or swimming.
+    fn is_node(&self, pos: IVec3) -> bool {
+        self.fits(pos) && (self.cell(pos) == Cell::Water || self.supports(pos - IVec3::Y))
+    }
+
+    /// Cost of entering `pos` with a move of base cost `cost`.
+    fn enter(&self, pos: IVec3, cost: u32) -> u32 {
+        if self.cell(pos) == Cell::Water {
+            cost * WATER_FACTOR
+        } else {
+            cost
+        }
+    }
+
+    /// Nodes reachable in one move from `from`, with their costs.
+    fn neighbours(&self, from: IVec3, out: &mut Vec<(IVec3, u32)>) {
+        out.clear();
+        let headroom = self.fits(from + IVec3::Y);
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                if dx == 0 && dz == 0 {
+                    continue;
+                }
+                let diagonal = dx != 0 && dz != 0;
+                if diagonal
+                    && !(self.fits(from + IVec3::new(dx, 0, 0))
+                        && self.fits(from + IVec3::new(0, 0, dz)))
+                {
+                    continue;
+                }
+                let cost = if diagonal { DIAGONAL_COST } else { STEP_COST };
+                let side = from + IVec3::new(dx, 0, dz);
+
+                if self.is_node(side) {
+                    out.push((side, self.enter(side, cost)));
+                } else if self.fits(side) {
+                    for drop in 1..=self.options.max_drop {
+                        let below = side - IVec3::Y * drop;
+                        if self.cell(below) == Cell::Blocked {
+                            break;
+                        }
+                        if self.is_node(below) {
+                            let cost = cost + DROP_COST * drop as u32;
+                            out.push((below, self.enter(below, cost)));
+                            break;
+                        }
+                    }
+                } else if !diagonal && headroom && self.is_node(side + IVec3::Y) {
+                    let up = side + IVec3::Y;
+                    out.push((up, self.enter(up, cost + JUMP_COST)));
+                }
+            }
+        }
+
+        if self.cell(from) == Cell::Water {
+            let up = from + IVec3::Y;
+            if self.is_node(up) {
+                out.push((up, self.enter(up, STEP_COST)));
+            }
+            let down = from - IVec3::Y;
+            if self.cell(down) == Cell::Water && self.is_node(down) {
+                out.push((down, self.enter(down, STEP_COST)));
+            }
+        }
+    }
+}
+
+/// Estimated cost between two cells: octile distance plus vertical distance.
+fn heuristic(a: IVec3, b: IVec3) -> u32 {
+    let d = (a - b).abs();
+    let (long, short) = (d.x.max(d.z) as u32, d.x.min(d.z) as u32);
+    STEP_COST * (long - short) + DIAGONAL_COST * short + STEP_COST * d.y as u32
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Open {
+    estimate: u32,
+    cost: u32,
+    pos: IVec3,
+}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so the heap pops the lowest estimate first
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Whether a mob with these options can stand at `pos`.
+pub fn is_walkable(blocks: &impl BlockSource, pos: IVec3, options: &PathOptions) -> bool {
+    Grid { blocks, options }.is_node(pos)
+}
+
+/// Find a path from `start` to `goal`, both the cells of the mob's feet.
+///
+/// Returns the cells to walk through after `start`, ending at `goal`. If the
+/// goal can't be reached, or is too far to find within the node limit, the
+/// path leads to the closest cell found instead. Returns `None` if no move
+/// gets any closer.
+pub fn find_path(
+    blocks: &impl BlockSource,
+    start: IVec3,
+    goal: IVec3,
+    options: &PathOptions,
+) -> Option<Vec<IVec3>> {
+    let grid = Grid { blocks, options };
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<IVec3, IVec3> = HashMap::new();
+    let mut costs: HashMap<IVec3, u32> = HashMap::new();
+    let mut neighbours = Vec::with_capacity(10);
+
+    let mut best = (heuristic(start, goal), start);
+    costs.insert(start, 0);
+    open.push(Open {
+        estimate: best.0,
+        cost: 0,
+        pos: start,
+    });
+
+    let mut expanded = 0;
+    while let Some(Open { cost, pos, .. }) = open.pop() {
+        if pos == goal {
+            best = (0, goal);
+            break;
+        }
+        if costs.get(&pos).is_some_and(|&known| known < cost) {
+            continue;
+        }
+        expanded += 1;
+        if expanded > options.max_nodes {
+            break;
+        }
+
+        grid.neighbours(pos, &mut neighbours);
+        for &(next, step) in &neighbours {
+            let cost = cost + step;
+            if costs.get(&next).is_some_and(|&known| known <= cost) {
+                continue;
+            }
+            costs.insert(next, cost);
+            came_from.insert(next, pos);
+            let remaining = heuristic(next, goal);
+            if remaining < best.0 {
+                best = (remaining, next);
+            }
+            open.push(Open {
+                estimate: cost + remaining,
+                cost,
+                pos: next,
+            });
+        }
+    }
+
+    let (_, end) = best;
+    if end == start {
+        return None;
+    }
+    let mut path = vec![end];
+    let mut current = end;
+    while let Some(&previous) = came_from.get(&current) {
+        if previous == start {
+            break;
+        }
+        path.push(previous);
+        current = previous;
+    }
+    path.reverse();
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::chunk::blocks::{AIR, STONE, WATER};
+
+    /// A stone floor at y = 63 with extra blocks on top.
+    fn world(extra: &[(IVec3, u32)]) -> impl Fn(i32, i32, i32) -> Option<u32> + '_ {
+        move |x, y, z| {
+            if let Some(&(_, id)) = extra.iter().find(|(pos, _)| *pos == IVec3::new(x, y, z)) {
+                return Some(id);
+            }
+            Some(if y <= 63 { *STONE } else { *AIR })
+        }
+    }
+
+    #[test]
+    fn test_straight_path() {
+        let blocks = world(&[]);
+        let start = IVec3::new(0, 64, 0);
+        let goal = IVec3::new(5, 64, 0);
+        let path = find_path(&blocks, start, goal, &PathOptions::default()).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!(path.last(), Some(&goal));
+    }
+
+    #[test]
+    fn test_jumps_one_block_but_not_two() {
+        let step = [(IVec3::new(1, 64, 0), *STONE)];
+        let blocks = world(&step);
+        let options = PathOptions::default();
+        let start = IVec3::new(0, 64, 0);
+        let path = find_path(&blocks, start, IVec3::new(1, 65, 0), &options).unwrap();
+        assert_eq!(path, vec![IVec3::new(1, 65, 0)]);
+
+        // A wall two blocks high around the goal can't be climbed, and no
+        // step along it gets any closer
+        let mut wall = Vec::new();
+        for x in 1..=3 {
+            for z in -1..=1 {
+                if (x, z) != (2, 0) {
+                    wall.push((IVec3::new(x, 64, z), *STONE));
+                    wall.push((IVec3::new(x, 65, z), *STONE));
+                }
+            }
+        }
+        let blocks = world(&wall);
+        let goal = IVec3::new(2, 64, 0);
+        assert_eq!(find_path(&blocks, start, goal, &options), None);
+    }
+
+    #[test]
+    fn test_drops_down() {
+        // A ledge at y = 67 next to the floor
+        let ledge: Vec<_> = (64..=66).map(|y| (IVec3::new(0, y, 0), *STONE)).collect();
+        let blocks = world(&ledge);
+        let start = IVec3::new(0, 67, 0);
+        let goal = IVec3::new(2, 64, 0);
+        let path = find_path(&blocks, start, goal, &PathOptions::default()).unwrap();
+        assert_eq!(path.first(), Some(&IVec3::new(1, 64, 0)));
+        assert_eq!(path.last(), Some(&goal));
+
+        let timid = PathOptions {
+            max_drop: 2,
+            ..PathOptions::default()
+        };
+        assert_eq!(find_path(&blocks, start, goal, &timid), None);
+    }
+
+    #[test]
+    fn test_swims_through_water() {
+        // A trench of water across the way, with walls to the sides
+        let mut terrain = Vec::new();
+        for z in -3..=3 {
+            terrain.push((IVec3::new(2, 63, z), *WATER));
+            terrain.push((IVec3::new(2, 62, z), *WATER));
+        }
+        let blocks = world(&terrain);
+        let start = IVec3::new(0, 64, 0);
+        let goal = IVec3::new(4, 64, 0);
+        let path = find_path(&blocks, start, goal, &PathOptions::default()).unwrap();
+        assert_eq!(path.last(), Some(&goal));
+
+        let dry = PathOptions {
+            can_swim: false,
+            ..PathOptions::default()
+        };
+        let swimming = IVec3::new(2, 63, 0);
+        assert!(is_walkable(&blocks, swimming, &PathOptions::default()));
+        assert!(!is_walkable(&blocks, swimming, &dry));
+    }
+
+    #[test]
+    fn test_detours_around_wall() {
+        let wall: Vec<_> = (-2..=2)
+            .flat_map(|z| {
+                [
+                    (IVec3::new(2, 64, z), *STONE),
+                    (IVec3::new(2, 65, z), *STONE),
+                ]
+            })
+            .collect();
+        let blocks = world(&wall);
+        let goal = IVec3::new(4, 64, 0);
+        let path = find_path(&blocks, IVec3::new(0, 64, 0), goal, &PathOptions::default()).unwrap();
+        assert_eq!(path.last(), Some(&goal));
+        assert!(path.iter().all(|pos| pos.x != 2 || pos.z.abs() > 2));
+    }
+}
@@ -0,0 +1,757 @@
+//! Mob AI: choosing goals, running them and walking paths.
+//!
+//! Each tick a mob's [`Goals`] are tried in priority order (see [`Goals`]).
+//! Goals that go somewhere set a path with the A* pathfinder; the mob then
+//! steers towards the next cell of its path, jumping up blocks and
+//! swimming to the surface of water. Steering only changes the velocity;
+//! the physics systems do the moving.
+
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use glam::{DVec3, IVec3};
+use rand::Rng;
+
+use crate::entity::collision::BlockSource;
+use crate::entity::components::*;
+use crate::entity::damage::DamageSource;
+use crate::entity::pathfinding::{PathOptions, find_path, is_walkable};
+use crate::entity::systems::damage::DealDamage;
+use crate::entity::systems::physics::loaded_block_at;
+use crate::world::Difficulty;
+use crate::world::chunk::blocks;
+use crate::world::ecs::{ChunkData, WorldId, Worlds};
+
+/// Blocks per tick walked at a speed of 1.0.
+const MOVEMENT_FACTOR: f64 = 0.5;
+/// Upward velocity of a jump.
+const JUMP_VELOCITY: f64 = 0.42;
+/// Upward velocity of a mob swimming to the surface.
+const SWIM_VELOCITY: f64 = 0.1;
+/// Horizontal distance from the centre of a path cell at which it counts as reached.
+const NODE_REACHED: f64 = 0.35;
+
+/// Ticks between finding a new path to a moving target.
+const REPATH_TICKS: u32 = 10;
+/// Ticks between melee hits.
+const ATTACK_COOLDOWN: u32 = 20;
+/// Ticks a mob keeps fleeing after being hurt.
+const FLEE_TICKS: u32 = 60;
+/// How far a fleeing mob runs.
+const FLEE_DISTANCE: f64 = 8.0;
+/// How far from straight away a fleeing mob may end up.
+const FLEE_SPREAD: i32 = 3;
+/// Ticks a wandering mob walks before giving up on reaching its spot.
+const WANDER_TICKS: u32 = 200;
+/// How far a wandering mob goes, horizontally and vertically.
+const WANDER_RANGE: (i32, i32) = (10, 3);
+/// Range at which held food tempts a mob.
+const TEMPT_RANGE: f64 = 10.0;
+/// How close a tempted mob comes.
+const TEMPT_STOP: f64 = 2.5;
+/// Range at which a baby looks for an adult.
+const PARENT_RANGE: f64 = 8.0;
+/// Babies stop following at this distance, or when the adult gets this far away.
+const PARENT_DISTANCE: (f64, f64) = (3.0, 16.0);
+/// Chance per tick to start looking at a nearby player.
+const LOOK_CHANCE: f32 = 0.02;
+/// Ticks a mob keeps looking at a player.
+const LOOK_TICKS: u32 = 60;
+/// Random spots tried when looking for somewhere to go.
+const DESTINATION_ATTEMPTS: usize = 10;
+
+/// A player as the AI sees it.
+struct Target {
+    entity: Entity,
+    feet: DVec3,
+    /// Survival or adventure: the player can be attacked.
+    attackable: bool,
+    held_item: Option<String>,
+}
+
+/// What a mob can sense this tick.
+struct Surroundings<'a, B> {
+    blocks: &'a B,
+    players: &'a [Target],
+    /// Adult mobs, for babies to follow.
+    adults: &'a [(Entity, String, DVec3)],
+    peaceful: bool,
+}
+
+impl<B> Surroundings<'_, B> {
+    fn player(&self, entity: Entity) -> Option<&Target> {
+        self.players.iter().find(|player| player.entity == entity)
+    }
+
+    fn nearest_player(
+        &self,
+        from: DVec3,
+        range: f64,
+        filter: impl Fn(&Target) -> bool,
+    ) -> Option<&Target> {
+        self.players
+            .iter()
+            .filter(|player| filter(player) && player.feet.distance(from) <= range)
+            .min_by(|a, b| a.feet.distance(from).total_cmp(&b.feet.distance(from)))
+    }
+}
+
+/// The mob being thought for.
+struct MobView<'a> {
+    entity: Entity,
+    feet: DVec3,
+    hitbox: Hitbox,
+    identifier: &'a str,
+    baby: bool,
+    /// Set while the mob was just hurt, to its attacker's position if known.
+    hurt: Option<Option<DVec3>>,
+}
+
+impl MobView<'_> {
+    fn cell(&self) -> IVec3 {
+        cell_at(self.feet)
+    }
+
+    fn path_options(&self) -> PathOptions {
+        PathOptions::for_height(self.hitbox.height)
+    }
+
+    fn path_to<B: BlockSource>(&self, blocks: &B, destination: DVec3) -> Vec<IVec3> {
+        find_path(
+            blocks,
+            self.cell(),
+            cell_at(destination),
+            &self.path_options(),
+        )
+        .unwrap_or_default()
+    }
+
+    /// A path to a random walkable spot up to `spread` blocks from `around`,
+    /// if one is found.
+    fn path_near<B: BlockSource>(
+        &self,
+        blocks: &B,
+        around: IVec3,
+        spread: i32,
+        rng: &mut impl Rng,
+    ) -> Option<Vec<IVec3>> {
+        let options = self.path_options();
+        let vertical = WANDER_RANGE.1;
+        (0..DESTINATION_ATTEMPTS)
+            .find_map(|_| {
+                let column = around
+                    + IVec3::new(
+                        rng.gen_range(-spread..=spread),
+                        0,
+                        rng.gen_range(-spread..=spread),
+                    );
+                (-vertical..=vertical)
+                    .rev()
+                    .map(|dy| column + IVec3::Y * dy)
+                    .find(|&spot| is_walkable(blocks, spot, &options))
+            })
+            .and_then(|spot| find_path(blocks, self.cell(), spot, &options))
+    }
+}
+
+/// The block cell of a feet position, rounding up from half a block so a
+/// mob standing on a slab is in the cell above it.
+fn cell_at(feet: DVec3) -> IVec3 {
+    IVec3::new(
+        feet.x.floor() as i32,
+        (feet.y + 0.5).floor() as i32,
+        feet.z.floor() as i32,
+    )
+}
+
+/// A melee hit a mob lands this tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MeleeHit {
+    target: Entity,
+    damage: f32,
+}
+
+/// How a goal starts: what it goes after and the path there.
+struct Start {
+    target: Option<Entity>,
+    path: Vec<IVec3>,
+}
+
+fn try_start<B: BlockSource>(
+    goal: &Goal,
+    mob: &MobView,
+    around: &Surroundings<B>,
+    rng: &mut impl Rng,
+) -> Option<Start> {
+    match goal {
+        Goal::Flee { .. } => {
+            let attacker = mob.hurt?.filter(|pos| pos.distance(mob.feet) > 0.01);
+            let direction = match attacker {
+                Some(attacker) => (mob.feet - attacker).with_y(0.0).normalize_or_zero(),
+                None => {
+                    let angle = rng.gen_range(0.0..std::f64::consts::TAU);
+                    DVec3::new(angle.cos(), 0.0, angle.sin())
+                }
+            };
+            let away = cell_at(mob.feet + direction * FLEE_DISTANCE);
+            let path = mob.path_near(around.blocks, away, FLEE_SPREAD, rng)?;
+            Some(Start { target: None, path })
+        }
+        Goal::MeleeAttack { range, .. } => {
+            if around.peaceful {
+                return None;
+            }
+            let player = around.nearest_player(mob.feet, *range, |p| p.attackable)?;
+            Some(Start {
+                target: Some(player.entity),
+                path: mob.path_to(around.blocks, player.feet),
+            })
+        }
+        Goal::Tempt { items, .. } => {
+            let player = around.nearest_player(mob.feet, TEMPT_RANGE, |p| holds(p, items))?;
+            Some(Start {
+                target: Some(player.entity),
+                path: Vec::new(),
+            })
+        }
+        Goal::FollowParent { .. } => {
+            if !mob.baby {
+                return None;
+            }
+            let (parent, _, position) = around
+                .adults
+                .iter()
+                .filter(|(_, identifier, position)| {
+                    identifier == mob.identifier && position.distance(mob.feet) <= PARENT_RANGE
+                })
+                .min_by(|a, b| a.2.distance(mob.feet).total_cmp(&b.2.distance(mob.feet)))?;
+            if position.distance(mob.feet) < PARENT_DISTANCE.0 {
+                return None;
+            }
+            Some(Start {
+                target: Some(*parent),
+                path: mob.path_to(around.blocks, *position),
+            })
+        }
+        Goal::Wander { interval, .. } => {
+            if rng.gen_range(0..(*interval).max(1)) != 0 {
+                return None;
+            }
+            let path = mob.path_near(around.blocks, mob.cell(), WANDER_RANGE.0, rng)?;
+            Some(Start { target: None, path })
+        }
+        Goal::LookAtPlayer { range } => {
+            if rng.r#gen::<f32>() >= LOOK_CHANCE {
+                return None;
+            }
+            let player = around.nearest_player(mob.feet, *range, |_| true)?;
+            Some(Start {
+                target: Some(player.entity),
+                path: Vec::new(),
+            })
+        }
+    }
+}
+
+fn can_continue<B>(goal: &Goal, mob: &MobView, ai: &AiState, around: &Surroundings<B>) -> bool {
+    let player = ai.target.and_then(|target| around.player(target));
+    let distance = |position: DVec3| position.distance(mob.feet);
+    match goal {
+        Goal::Flee { .. } => ai.goal_ticks < FLEE_TICKS && !ai.path.is_empty(),
+        Goal::MeleeAttack { range, .. } => {
+            !around.peaceful
+                && player.is_some_and(|player| player.attackable && distance(player.feet) <= *range)
+        }
+        Goal::Tempt { items, .. } => player
+            .is_some_and(|player| holds(player, items) && distance(player.feet) <= TEMPT_RANGE),
+        Goal::FollowParent { .. } => around
+            .adults
+            .iter()
+            .find(|(entity, _, _)| Some(*entity) == ai.target)
+            .is_some_and(|(_, _, position)| {
+                (PARENT_DISTANCE.0..=PARENT_DISTANCE.1).contains(&distance(*position))
+            }),
+        Goal::Wander { .. } => ai.goal_ticks < WANDER_TICKS && !ai.path.is_empty(),
+        Goal::LookAtPlayer { range } => {
+            ai.goal_ticks < LOOK_TICKS
+                && player.is_some_and(|player| distance(player.feet) <= *range)
+        }
+    }
+}
+
+/// Run one tick of a goal. Returns the hit if the mob attacks.
+fn tick_goal<B: BlockSource>(
+    goal: &Goal,
+    mob: &MobView,
+    ai: &mut AiState,
+    around: &Surroundings<B>,
+) -> Option<MeleeHit> {
+    ai.goal_ticks += 1;
+    let repath = |ai: &mut AiState, destination: DVec3| {
+        if ai.repath_ticks == 0 {
+            ai.path = mob.path_to(around.blocks, destination);
+            ai.repath_ticks = REPATH_TICKS;
+        } else {
+            ai.repath_ticks -= 1;
+        }
+    };
+    let player = ai.target.and_then(|target| around.player(target));
+    match goal {
+        Goal::MeleeAttack { damage, .. } => {
+            let player = player?;
+            ai.look_at = Some(player.feet + DVec3::Y * PLAYER_EYE_HEIGHT);
+            repath(ai, player.feet);
+            let reach = mob.hitbox.width * 2.0;
+            let in_reach =
+                mob.feet.distance_squared(player.feet) <= reach * reach + Hitbox::PLAYER.width;
+            if in_reach && ai.attack_cooldown == 0 {
+                ai.attack_cooldown = ATTACK_COOLDOWN;
+                return Some(MeleeHit {
+                    target: player.entity,
+                    damage: *damage,
+                });
+            }
+        }
+        Goal::Tempt { .. } => {
+            let player = player?;
+            ai.look_at = Some(player.feet + DVec3::Y * PLAYER_EYE_HEIGHT);
+            if player.feet.distance(mob.feet) < TEMPT_STOP {
+                ai.path.clear();
+            } else {
+                repath(ai, player.feet);
+            }
+        }
+        Goal::FollowParent { .. } => {
+            let (_, _, position) = around
+                .adults
+                .iter()
+                .find(|(entity, _, _)| Some(*entity) == ai.target)?;
+            repath(ai, *position);
+        }
+        Goal::LookAtPlayer { .. } => {
+            ai.look_at = player.map(|player| player.feet + DVec3::Y * PLAYER_EYE_HEIGHT);
+        }
+        Goal::Flee { .. } | Goal::Wander { .. } => {}
+    }
+    None
+}
+
+fn holds(player: &Target, items: &[String]) -> bool {
+    player
+        .held_item
+        .as_ref()
+        .is_some_and(|held| items.contains(held))
+}
+
+/// Pick the goal to run this tick and run it.
+fn think<B: BlockSource>(
+    mob: &MobView,
+    ai: &mut AiState,
+    goals: &mut Goals,
+    around: &Surroundings<B>,
+    rng: &mut impl Rng,
+) -> Option<MeleeHit> {
+    ai.attack_cooldown = ai.attack_cooldown.saturating_sub(1);
+    ai.look_at = None;
+
+    let mut chosen = None;
+    for (index, (_, goal)) in goals.iter().enumerate() {
+        if goals.active() == Some(index) {
+            if can_continue(goal, mob, ai, around) {
+                chosen = Some((index, None));
+                break;
+            }
+        } else if let Some(start) = try_start(goal, mob, around, rng) {
+            chosen = Some((index, Some(start)));
+            break;
+        }
+    }
+
+    let index = match chosen {
+        Some((index, Some(start))) => {
+            ai.reset();
+            ai.target = start.target;
+            ai.path = start.path;
+            Some(index)
+        }
+        Some((index, None)) => Some(index),
+        None => {
+            if goals.active().is_some() {
+                ai.reset();
+            }
+            None
+        }
+    };
+    goals.set_active(index);
+    ai.current_goal = goals.active_goal().map(|goal| goal.name().to_string());
+
+    let goal = goals.active_goal()?.clone();
+    tick_goal(&goal, mob, ai, around)
+}
+
+/// Steer along the path at `speed` blocks per tick and face the way the mob goes.
+fn steer<B: BlockSource>(
+    blocks: &B,
+    mob: &MobView,
+    ai: &mut AiState,
+    on_ground: bool,
+    speed: f64,
+    velocity: &mut DVec3,
+    rotation: &mut Rotation,
+) {
+    while let Some(node) = ai.path.first() {
+        let center = node.as_dvec3() + DVec3::new(0.5, 0.0, 0.5);
+        let horizontal = (center - mob.feet).with_y(0.0).length();
+        if horizontal < NODE_REACHED && (node.y as f64 - mob.feet.y).abs() < 1.0 {
+            ai.path.remove(0);
+        } else {
+            break;
+        }
+    }
+
+    let middle = mob.feet + DVec3::Y * (mob.hitbox.height * 0.5);
+    let in_water = blocks
+        .block_at(
+            middle.x.floor() as i32,
+            middle.y.floor() as i32,
+            middle.z.floor() as i32,
+        )
+        .is_some_and(blocks::is_water);
+
+    if let Some(node) = ai.path.first()
+        && speed > 0.0
+        && (on_ground || in_water)
+    {
+        let center = node.as_dvec3() + DVec3::new(0.5, 0.0, 0.5);
+        let direction = (center - mob.feet).with_y(0.0).normalize_or_zero();
+        velocity.x = direction.x * speed;
+        velocity.z = direction.z * speed;
+        if direction != DVec3::ZERO {
+            rotation.yaw = yaw_towards(direction);
+        }
+        if on_ground && node.y as f64 > mob.feet.y + 0.5 {
+            velocity.y = JUMP_VELOCITY;
+        }
+    }
+    if in_water && velocity.y < SWIM_VELOCITY {
+        velocity.y = SWIM_VELOCITY;
+    }
+
+    match ai.look_at {
+        Some(point) => {
+            let eyes = mob.feet + DVec3::Y * (mob.hitbox.height * 0.85);
+            let delta = point - eyes;
+            rotation.head_yaw = yaw_towards(delta);
+            rotation.pitch = (-delta.y.atan2(delta.with_y(0.0).length())).to_degrees() as f32;
+            if ai.path.is_empty() {
+                rotation.yaw = rotation.head_yaw;
+            }
+        }
+        None => {
+            rotation.head_yaw = rotation.yaw;
+            rotation.pitch = 0.0;
+        }
+    }
+}
+
+/// Yaw in degrees facing along `direction`.
+fn yaw_towards(direction: DVec3) -> f32 {
+    (-direction.x).atan2(direction.z).to_degrees() as f32
+}
+
+/// Components a mob thinks and steers with.
+type ThinkingMob = (
+    Entity,
+    &'static Position,
+    &'static mut Velocity,
+    &'static mut Rotation,
+    &'static OnGround,
+    &'static Hitbox,
+    &'static Speed,
+    &'static MobType,
+    &'static mut AiState,
+    &'static mut Goals,
+    Option<&'static MobAge>,
+    Option<&'static DamageImmunity>,
+    Option<&'static LastDamage>,
+    Option<&'static Hostile>,
+    &'static WorldId,
+);
+
+/// Components a player is seen by mobs with.
+type SeenPlayer = (
+    Entity,
+    &'static WorldId,
+    &'static Position,
+    &'static GameMode,
+    Option<&'static HeldSlot>,
+    Option<&'static MainInventory>,
+);
+
+/// System: Run mob goals and steer mobs along their paths.
+///
+/// Mobs only see players, adults and blocks in their own world. Melee hits
+/// go through the damage pipeline, scaled by difficulty. On Peaceful hostile
+/// mobs are removed.
+pub fn tick_mob_ai(
+    mut commands: Commands,
+    mut mobs: Query<ThinkingMob, (With<Mob>, Without<Dead>)>,
+    players: Query<SeenPlayer, (With<Player>, Without<Dead>)>,
+    worlds: Res<Worlds>,
+    chunks: Query<&ChunkData>,
+    difficulty: Option<Res<Difficulty>>,
+) {
+    let difficulty = difficulty.map_or_else(Difficulty::default, |d| *d);
+
+    let mut players_by_world: HashMap<WorldId, Vec<Target>> = HashMap::new();
+    for (entity, world, position, mode, slot, inventory) in players.iter() {
+        players_by_world.entry(*world).or_default().push(Target {
+            entity,
+            feet: position.0 - DVec3::Y * PLAYER_EYE_HEIGHT,
+            attackable: mode.allows_damage(),
+            held_item: slot
+                .zip(inventory)
+                .and_then(|(slot, inventory)| inventory.hotbar(slot.0))
+                .filter(|item| !item.is_empty())
+                .map(|item| item.item_id.clone()),
+        });
+    }
+    let mut adults_by_world: HashMap<WorldId, Vec<(Entity, String, DVec3)>> = HashMap::new();
+    for mob in mobs
+        .iter()
+        .filter(|mob| !mob.10.is_some_and(MobAge::is_baby))
+    {
+        adults_by_world.entry(*mob.14).or_default().push((
+            mob.0,
+            mob.7.identifier.clone(),
+            mob.1.0,
+        ));
+    }
+    let mut rng = rand::thread_rng();
+
+    for (world_id, manager) in worlds.iter() {
+        let blocks = |x, y, z| loaded_block_at(manager, &chunks, x, y, z);
+        let around = Surroundings {
+            blocks: &blocks,
+            players: players_by_world.get(&world_id).map_or(&[], Vec::as_slice),
+            adults: adults_by_world.get(&world_id).map_or(&[], Vec::as_slice),
+            peaceful: difficulty == Difficulty::Peaceful,
+        };
+        tick_world_mobs(
+            &mut commands,
+            &mut mobs,
+            world_id,
+            &around,
+            difficulty,
+            &mut rng,
+        );
+    }
+}
+
+/// Think and steer for every mob in one world.
+fn tick_world_mobs<B: BlockSource>(
+    commands: &mut Commands,
+    mobs: &mut Query<ThinkingMob, (With<Mob>, Without<Dead>)>,
+    world_id: WorldId,
+    around: &Surroundings<'_, B>,
+    difficulty: Difficulty,
+    rng: &mut impl Rng,
+) {
+    for (
+        entity,
+        position,
+        mut velocity,
+        mut rotation,
+        on_ground,
+        hitbox,
+        speed,
+        mob_type,
+        mut ai,
+        mut goals,
+        age,
+        immunity,
+        last_damage,
+        hostile,
+        world,
+    ) in mobs.iter_mut()
+    {
+        if *world != world_id {
+            continue;
+        }
+        if around.peaceful && hostile.is_some_and(|hostile| hostile.0) {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let hurt = immunity
+            .filter(|immunity| immunity.is_immune())
+            .and(last_damage)
+            .map(|damage| {
+                damage
+                    .source
+                    .attacker()
+                    .and_then(|attacker| around.player(attacker))
+                    .map(|player| player.feet)
+            });
+        let mob = MobView {
+            entity,
+            feet: position.0,
+            hitbox: *hitbox,
+            identifier: &mob_type.identifier,
+            baby: age.is_some_and(MobAge::is_baby),
+            hurt,
+        };
+
+        if let Some(hit) = think(&mob, &mut ai, &mut goals, around, rng) {
+            commands.queue(DealDamage {
+                entity: hit.target,
+                source: DamageSource::Attack {
+                    attacker: mob.entity,
+                },
+                amount: difficulty.scale_mob_damage(hit.damage),
+            });
+        }
+        let modifier = goals.active_goal().map_or(0.0, Goal::speed);
+        let speed = f64::from(speed.0 * modifier) * MOVEMENT_FACTOR;
+        steer(
+            around.blocks,
+            &mob,
+            &mut ai,
+            on_ground.0,
+            speed,
+            &mut velocity.0,
+            &mut rotation,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::mobs::MobDefinition;
+    use crate::world::chunk::blocks::{AIR, STONE};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn flat(_x: i32, y: i32, _z: i32) -> Option<u32> {
+        Some(if y < 64 { *STONE } else { *AIR })
+    }
+
+    fn mob_at(world: &mut World, feet: DVec3) -> MobView<'static> {
+        MobView {
+            entity: world.spawn_empty().id(),
+            feet,
+            hitbox: Hitbox::new(0.6, 1.9, 0.6),
+            identifier: "minecraft:zombie",
+            baby: false,
+            hurt: None,
+        }
+    }
+
+    #[test]
+    fn test_zombie_chases_and_hits() {
+        let mut world = World::new();
+        let mut rng = StdRng::seed_from_u64(1);
+        let player = Target {
+            entity: world.spawn_empty().id(),
+            feet: DVec3::new(8.5, 64.0, 0.5),
+            attackable: true,
+            held_item: None,
+        };
+        let players = [player];
+        let around = Surroundings {
+            blocks: &flat,
+            players: &players,
+            adults: &[],
+            peaceful: false,
+        };
+        let mut goals = MobDefinition::get("zombie").unwrap().goals();
+        let mut ai = AiState::default();
+
+        let far = mob_at(&mut world, DVec3::new(0.5, 64.0, 0.5));
+        assert_eq!(think(&far, &mut ai, &mut goals, &around, &mut rng), None);
+        assert_eq!(ai.current_goal.as_deref(), Some("melee_attack"));
+        assert_eq!(ai.path.last(), Some(&IVec3::new(8, 64, 0)));
+
+        let mut velocity = DVec3::ZERO;
+        let mut rotation = Rotation::default();
+        steer(
+            &flat,
+            &far,
+            &mut ai,
+            true,
+            0.1,
+            &mut velocity,
+            &mut rotation,
+        );
+        assert!(velocity.x > 0.09);
+        assert!((rotation.yaw + 90.0).abs() < 1.0);
+
+        let near = MobView {
+            feet: DVec3::new(7.5, 64.0, 0.5),
+            ..far
+        };
+        let hit = think(&near, &mut ai, &mut goals, &around, &mut rng).unwrap();
+        assert_eq!(hit.target, players[0].entity);
+        // Only once per cooldown
+        assert_eq!(think(&near, &mut ai, &mut goals, &around, &mut rng), None);
+
+        let peaceful = Surroundings {
+            peaceful: true,
+            ..around
+        };
+        think(&near, &mut ai, &mut goals, &peaceful, &mut rng);
+        assert_ne!(ai.current_goal.as_deref(), Some("melee_attack"));
+    }
+
+    #[test]
+    fn test_flees_when_hurt() {
+        let mut world = World::new();
+        let mut rng = StdRng::seed_from_u64(2);
+        let around = Surroundings {
+            blocks: &flat,
+            players: &[],
+            adults: &[],
+            peaceful: false,
+        };
+        let mut goals = MobDefinition::get("cow").unwrap().goals();
+        let mut ai = AiState::default();
+        let attacker = DVec3::new(-1.5, 64.0, 0.5);
+        let cow = MobView {
+            hurt: Some(Some(attacker)),
+            ..mob_at(&mut world, DVec3::new(0.5, 64.0, 0.5))
+        };
+        think(&cow, &mut ai, &mut goals, &around, &mut rng);
+        assert_eq!(ai.current_goal.as_deref(), Some("flee"));
+        let end = ai.path.last().unwrap();
+        assert!(end.x > 2);
+    }
+
+    #[test]
+    fn test_jumps_up_path() {
+        let step = |x: i32, y: i32, _z: i32| {
+            Some(if y < 64 || (x == 1 && y == 64) {
+                *STONE
+            } else {
+                *AIR
+            })
+        };
+        let mut world = World::new();
+        let mob = mob_at(&mut world, DVec3::new(0.5, 64.0, 0.5));
+        let mut ai = AiState {
+            path: vec![IVec3::new(1, 65, 0)],
+            ..AiState::default()
+        };
+        let mut velocity = DVec3::ZERO;
+        let mut rotation = Rotation::default();
+        steer(
+            &step,
+            &mob,
+            &mut ai,
+            true,
+            0.1,
+            &mut velocity,
+            &mut rotation,
+        );
+        assert_eq!(velocity.y, JUMP_VELOCITY);
+    }
+}
@@ -0,0 +1,347 @@
+//! The damage pipeline.
+//!
+//! All damage goes through [`apply_damage`]. Entities that can't be hurt
+//! (dead, creative or spectator players, fire-resistant entities in fire,
+//! players protected by a game rule) are skipped, and an entity in its
+//! immunity window only takes the part of a hit that exceeds the one that
+//! started the window. The rest runs through the [`DamageStage`]s in order:
+//! base, armour, Resistance, enchantments.
+//!
+//! Each stage triggers a [`DamageCalculation`] and then calls the native
+//! plugins' `on_entity_damage`; both may change the amount or cancel the hit.
+//! Whatever is left is taken from `Health` and reported with a `DamageEvent`.
+
+use abi_stable::std_types::RString;
+use bevy_ecs::prelude::*;
+use unastar_api::native::{EntityDamage, PluginEntity};
+
+use crate::entity::components::*;
+use crate::entity::damage::{
+    DamageCalculation, DamageEvent, DamageSource, DamageStage, IMMUNITY_TICKS, armour_reduction,
+    enchantment_reduction, fall_damage, resistance_reduction,
+};
+use crate::plugin::PluginRegistry;
+use crate::world::GameRules;
+
+/// Command: Deal damage to an entity through the damage pipeline.
+///
+/// Lets systems damage entities with `commands.queue(DealDamage { .. })`.
+#[derive(Debug)]
+pub struct DealDamage {
+    pub entity: Entity,
+    pub source: DamageSource,
+    pub amount: f32,
+}
+
+impl Command for DealDamage {
+    fn apply(self, world: &mut World) {
+        apply_damage(world, self.entity, self.source, self.amount);
+    }
+}
+
+/// Whether a game rule turns off this kind of damage to players.
+fn disabled_by_game_rules(world: &World, source: &DamageSource) -> bool {
+    let Some(rules) = world.get_resource::<GameRules>() else {
+        return false;
+    };
+    let is_player = |entity: Entity| world.get::<Player>(entity).is_some();
+    match *source {
+        DamageSource::Fall { .. } => !rules.fall_damage,
+        DamageSource::Fire { .. } => !rules.fire_damage,
+        DamageSource::Drowning => !rules.drowning_damage,
+        DamageSource::Attack { attacker } | DamageSource::Thorns { attacker } => {
+            !rules.pvp && is_player(attacker)
+        }
+        DamageSource::Projectile {
+            owner: Some(owner), ..
+        } => !rules.pvp && is_player(owner),
+        _ => false,
+    }
+}
+
+/// Run damage through the pipeline and take it from the entity's health.
+///
+/// Returns the damage taken, or `None` if the entity was immune or the hit
+/// was cancelled.
+pub fn apply_damage(
+    world: &mut World,
+    entity: Entity,
+    source: DamageSource,
+    amount: f32,
+) -> Option<f32> {
+    let target = world.get_entity(entity).ok()?;
+    if target.contains::<Dead>() || !target.contains::<Health>() {
+        return None;
+    }
+    let vulnerable = target
+        .get::<GameMode>()
+        .is_none_or(|mode| mode.allows_damage());
+    if !vulnerable && !matches!(source, DamageSource::Void) {
+        return None;
+    }
+    if target.contains::<Player>() && disabled_by_game_rules(world, &source) {
+        return None;
+    }
+    let effects = target.get::<Effects>();
+    if source.is_fire() && effects.is_some_and(|e| e.has(EffectType::FIRE_RESISTANCE)) {
+        return None;
+    }
+    let resistance = effects
+        .and_then(|e| e.get(EffectType::RESISTANCE))
+        .map(|effect| effect.level);
+
+    // Within the immunity window only a stronger hit gets through, for the difference
+    let (mut remaining, new_window) = match target.get::<DamageImmunity>() {
+        Some(immunity) if immunity.is_immune() => {
+            if amount <= immunity.last_damage {
+                return None;
+            }
+            (amount - immunity.last_damage, false)
+        }
+        _ => (amount, true),
+    };
+
+    let (defence, protection_factor) = target
+        .get::<ArmourInventory>()
+        .map(|armour| (armour.defence(), protection_factor(armour, &source)))
+        .unwrap_or_default();
+
+    for stage in [
+        DamageStage::Base,
+        DamageStage::Armour,
+        DamageStage::Resistance,
+        DamageStage::Enchantments,
+    ] {
+        remaining = match stage {
+            DamageStage::Armour if source.reduced_by_armor() => {
+                armour_reduction(remaining, defence.points, defence.toughness)
+            }
+            DamageStage::Resistance if source.reduced_by_resistance() => {
+                resistance.map_or(remaining, |level| resistance_reduction(remaining, level))
+            }
+            DamageStage::Enchantments if source.reduced_by_enchantments() => {
+                enchantment_reduction(remaining, protection_factor)
+            }
+            _ => remaining,
+        };
+        remaining = run_stage(world, entity, &source, stage, remaining)?;
+    }
+    let final_amount = remaining.max(0.0);
+
+    let mut target = world.entity_mut(entity);
+    if let Some(mut health) = target.get_mut::<Health>() {
+        health.damage(final_amount);
+    }
+    if let Some(mut immunity) = target.get_mut::<DamageImmunity>() {
+        immunity.last_damage = amount;
+        if new_window {
+            immunity.ticks_remaining = IMMUNITY_TICKS;
+        }
+    }
+
+    world.trigger(DamageEvent {
+        entity,
+        source,
+        amount,
+        final_amount,
+    });
+    Some(final_amount)
+}
+
+/// Let observers and plugins adjust one stage. Returns `None` if the hit was cancelled.
+fn run_stage(
+    world: &mut World,
+    entity: Entity,
+    source: &DamageSource,
+    stage: DamageStage,
+    amount: f32,
+) -> Option<f32> {
+    let mut calculation = DamageCalculation {
+        entity,
+        source: source.clone(),
+        stage,
+        amount,
+        cancelled: false,
+    };
+    world.trigger_ref(&mut calculation);
+    if calculation.cancelled {
+        return None;
+    }
+
+    if !world.contains_resource::<PluginRegistry>() {
+        return Some(calculation.amount);
+    }
+    let mut damage = EntityDamage {
+        stage,
+        cause: RString::from(source.cause_name()),
+        attacker: source.attacker().map(PluginEntity::from).into(),
+        amount: calculation.amount,
+    };
+    let allow = world.resource_scope(|world, mut registry: Mut<PluginRegistry>| {
+        registry.on_entity_damage(world, entity, &mut damage)
+    });
+    allow.then_some(damage.amount)
+}
+
+/// Total enchantment protection factor of worn armour against a damage source.
+fn protection_factor(armour: &ArmourInventory, source: &DamageSource) -> i32 {
+    armour
+        .items()
+        .flat_map(|(_, item)| item.enchantments())
+        .map(|enchantment| source.protection_factor(enchantment.kind, enchantment.level))
+        .sum()
+}
+
+/// Deal fall damage to an entity that just landed after falling `distance` blocks.
+pub fn apply_fall_damage(world: &mut World, entity: Entity, distance: f32) -> Option<f32> {
+    let jump_boost = world
+        .get::<Effects>(entity)
+        .and_then(|e| e.get(EffectType::JUMP_BOOST))
+        .map(|effect| effect.level);
+    let amount = fall_damage(distance, jump_boost);
+    if amount <= 0.0 {
+        return None;
+    }
+    apply_damage(world, entity, DamageSource::Fall { distance }, amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::ItemStack;
+
+    fn spawn_target(world: &mut World) -> Entity {
+        world
+            .spawn((
+                Living,
+                Health::new(20.0),
+                Effects::default(),
+                DamageImmunity::default(),
+                ArmourInventory::new(),
+            ))
+            .id()
+    }
+
+    fn health(world: &World, entity: Entity) -> f32 {
+        world.get::<Health>(entity).unwrap().current
+    }
+
+    #[test]
+    fn test_armour_and_resistance() {
+        let mut world = World::new();
+        let target = spawn_target(&mut world);
+        {
+            let mut armour = world.get_mut::<ArmourInventory>(target).unwrap();
+            let _ = armour.set_chestplate(ItemStack::new("minecraft:diamond_chestplate", 1));
+        }
+        world
+            .get_mut::<Effects>(target)
+            .unwrap()
+            .add(EffectType::RESISTANCE, 0, 100);
+
+        let attack = DamageSource::Attack { attacker: target };
+        let taken = apply_damage(&mut world, target, attack, 10.0).unwrap();
+        // 8 points, 2 toughness: 10 * (1 - 4 / 25) * 0.8
+        assert!((taken - 6.72).abs() < 1e-4);
+        assert!((health(&world, target) - 13.28).abs() < 1e-4);
+
+        // Void ignores armour and resistance, and immunity only lets the excess through
+        let taken = apply_damage(&mut world, target, DamageSource::Void, 12.0).unwrap();
+        assert!((taken - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_immunity_window() {
+        let mut world = World::new();
+        let target = spawn_target(&mut world);
+
+        assert_eq!(
+            apply_damage(&mut world, target, DamageSource::Generic, 4.0),
+            Some(4.0)
+        );
+        assert_eq!(
+            apply_damage(&mut world, target, DamageSource::Generic, 3.0),
+            None
+        );
+        assert_eq!(health(&world, target), 16.0);
+
+        world
+            .get_mut::<DamageImmunity>(target)
+            .unwrap()
+            .ticks_remaining = 0;
+        assert_eq!(
+            apply_damage(&mut world, target, DamageSource::Generic, 3.0),
+            Some(3.0)
+        );
+    }
+
+    #[test]
+    fn test_stage_observers() {
+        let mut world = World::new();
+        let target = spawn_target(&mut world);
+        world.add_observer(|mut trigger: On<DamageCalculation>| {
+            let calculation = trigger.event_mut();
+            match (&calculation.source, calculation.stage) {
+                (DamageSource::Cactus, DamageStage::Base) => calculation.cancelled = true,
+                (_, DamageStage::Enchantments) => calculation.amount *= 2.0,
+                _ => {}
+            }
+        });
+
+        assert_eq!(
+            apply_damage(&mut world, target, DamageSource::Cactus, 5.0),
+            None
+        );
+        assert_eq!(
+            apply_damage(&mut world, target, DamageSource::Generic, 5.0),
+            Some(10.0)
+        );
+        assert_eq!(health(&world, target), 10.0);
+    }
+
+    #[test]
+    fn test_creative_and_fire_resistance() {
+        let mut world = World::new();
+        let target = spawn_target(&mut world);
+        world.entity_mut(target).insert(GameMode::Creative);
+        assert_eq!(
+            apply_damage(&mut world, target, DamageSource::Generic, 5.0),
+            None
+        );
+        assert!(apply_damage(&mut world, target, DamageSource::Void, 5.0).is_some());
+
+        let target = spawn_target(&mut world);
+        world
+            .get_mut::<Effects>(target)
+            .unwrap()
+            .add(EffectType::FIRE_RESISTANCE, 0, 100);
+        let fire = DamageSource::Fire { is_lava: true };
+        assert_eq!(apply_damage(&mut world, target, fire, 5.0), None);
+    }
+
+    #[test]
+    fn test_game_rules() {
+        let mut world = World::new();
+        world.insert_resource(GameRules {
+            fall_damage: false,
+            pvp: false,
+            ..GameRules::default()
+        });
+        let player = spawn_target(&mut world);
+        world.entity_mut(player).insert(Player);
+        let attacker = spawn_target(&mut world);
+        world.entity_mut(attacker).insert(Player);
+        let mob = spawn_target(&mut world);
+
+        let fall = DamageSource::Fall { distance: 8.0 };
+        assert_eq!(apply_damage(&mut world, player, fall.clone(), 5.0), None);
+        assert!(apply_damage(&mut world, mob, fall, 5.0).is_some());
+
+        let attack = |attacker| DamageSource::Attack { attacker };
+        assert_eq!(
+            apply_damage(&mut world, player, attack(attacker), 5.0),
+            None
+        );
+        assert!(apply_damage(&mut world, player, attack(mob), 5.0).is_some());
+    }
+}
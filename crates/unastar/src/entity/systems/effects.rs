@@ -3,6 +3,8 @@
 use bevy_ecs::prelude::*;
 
 use crate::entity::components::*;
+use crate::entity::damage::DamageSource;
+use crate::entity::systems::damage::DealDamage;
 
 /// System: Tick all status effects.
 pub fn tick_effects(mut query: Query<&mut Effects>) {
@@ -26,27 +28,37 @@ pub fn apply_regeneration(mut query: Query<(&mut Health, &Effects), With<Living>
 }
 
 /// System: Apply poison effect damage.
-pub fn apply_poison(mut query: Query<(&mut Health, &Effects), With<Living>>) {
-    for (mut health, effects) in query.iter_mut() {
+pub fn apply_poison(
+    mut commands: Commands,
+    query: Query<(Entity, &Health, &Effects), With<Living>>,
+) {
+    for (entity, health, effects) in query.iter() {
         if let Some(poison) = effects.get(EffectType::POISON) {
             // Poison ticks every 25 / (level + 1) ticks, but doesn't kill
             let interval = 25 / (poison.level as u32 + 1);
             if poison.duration_ticks % interval == 0 && health.current > 1.0 {
-                let new_health = (health.current - 1.0).max(1.0);
-                health.current = new_health;
+                commands.queue(DealDamage {
+                    entity,
+                    source: DamageSource::Magic { source: None },
+                    amount: 1.0f32.min(health.current - 1.0),
+                });
             }
         }
     }
 }
 
 /// System: Apply wither effect damage.
-pub fn apply_wither(mut query: Query<(&mut Health, &Effects), With<Living>>) {
-    for (mut health, effects) in query.iter_mut() {
+pub fn apply_wither(mut commands: Commands, query: Query<(Entity, &Effects), With<Living>>) {
+    for (entity, effects) in query.iter() {
         if let Some(wither) = effects.get(EffectType::WITHER) {
             // Wither ticks every 40 / (level + 1) ticks and can kill
             let interval = 40 / (wither.level as u32 + 1);
             if wither.duration_ticks % interval == 0 {
-                health.damage(1.0);
+                commands.queue(DealDamage {
+                    entity,
+                    source: DamageSource::Magic { source: None },
+                    amount: 1.0,
+                });
             }
         }
     }
@@ -66,13 +78,17 @@ pub fn tick_air_supply(mut query: Query<(&Position, &mut AirSupply, &mut Health)
 }
 
 /// System: Tick fire duration.
-pub fn tick_fire(mut query: Query<(&mut OnFire, &mut Health), With<Living>>) {
-    for (mut on_fire, mut health) in query.iter_mut() {
+pub fn tick_fire(mut commands: Commands, mut query: Query<(Entity, &mut OnFire), With<Living>>) {
+    for (entity, mut on_fire) in query.iter_mut() {
         if on_fire.is_on_fire() {
             on_fire.tick();
             // Fire damage every 20 ticks
             if on_fire.ticks_remaining % 20 == 0 {
-                health.damage(1.0);
+                commands.queue(DealDamage {
+                    entity,
+                    source: DamageSource::Fire { is_lava: false },
+                    amount: 1.0,
+                });
             }
         }
     }
@@ -0,0 +1,72 @@
+//! Hunger systems.
+
+use bevy_ecs::prelude::*;
+
+use crate::ecs::resources::TickCounter;
+use crate::entity::components::*;
+use crate::entity::damage::{DamageEvent, DamageSource};
+use crate::entity::systems::damage::DealDamage;
+use crate::world::{Difficulty, GameRules};
+
+/// Exhaustion per tick for each level of the Hunger effect.
+const HUNGER_EFFECT_EXHAUSTION: f32 = 0.005;
+
+type HungryPlayer<'a> = (
+    Entity,
+    &'a mut Hunger,
+    &'a mut Health,
+    Option<&'a GameMode>,
+    Option<&'a Effects>,
+);
+
+/// System: Drain food, heal from a full bar and starve from an empty one.
+///
+/// Only survival and adventure players get hungry. Timer-only updates skip
+/// change detection so the HUD is only re-synced when the bar changes.
+pub fn tick_hunger(
+    mut commands: Commands,
+    tick: Res<TickCounter>,
+    difficulty: Option<Res<Difficulty>>,
+    rules: Option<Res<GameRules>>,
+    mut query: Query<HungryPlayer, (With<Player>, Without<Dead>)>,
+) {
+    let difficulty = difficulty.map_or(Difficulty::default(), |d| *d);
+    let regenerate = rules.is_none_or(|rules| rules.natural_regeneration);
+    for (entity, mut hunger, mut health, game_mode, effects) in query.iter_mut() {
+        if game_mode.is_some_and(|mode| !mode.allows_damage()) {
+            continue;
+        }
+        let before = (hunger.food_level, hunger.saturation, hunger.exhaustion);
+        let hunger_ref = hunger.bypass_change_detection();
+        if let Some(effect) = effects.and_then(|e| e.get(EffectType::HUNGER)) {
+            hunger_ref.exhaust(HUNGER_EFFECT_EXHAUSTION * (effect.level as f32 + 1.0));
+        }
+        let outcome = hunger_ref.tick(tick.current, difficulty, &health, regenerate);
+        if before != (hunger.food_level, hunger.saturation, hunger.exhaustion) {
+            hunger.set_changed();
+        }
+
+        match outcome {
+            Some(HungerOutcome::Heal(amount)) => health.heal(amount),
+            Some(HungerOutcome::Starve) => commands.queue(DealDamage {
+                entity,
+                source: DamageSource::Starvation,
+                amount: 1.0,
+            }),
+            None => {}
+        }
+    }
+}
+
+/// Observer: Taking damage makes players hungrier.
+///
+/// Register with: `world.add_observer(exhaust_on_damage)`
+pub fn exhaust_on_damage(trigger: On<DamageEvent>, mut query: Query<&mut Hunger>) {
+    let event = trigger.event();
+    let exhaustion = event.source.exhaustion();
+    if exhaustion > 0.0
+        && let Ok(mut hunger) = query.get_mut(event.entity)
+    {
+        hunger.exhaust(exhaustion);
+    }
+}
@@ -3,6 +3,7 @@
 use bevy_ecs::prelude::*;
 
 use crate::entity::components::*;
+use crate::entity::damage::{DamageEvent, DamageSource, DeathEvent};
 
 /// System: Tick entity age.
 pub fn tick_age(mut query: Query<&mut Age>) {
@@ -11,11 +12,48 @@ pub fn tick_age(mut query: Query<&mut Age>) {
     }
 }
 
-/// System: Despawn dead entities.
-pub fn despawn_dead(mut commands: Commands, query: Query<(Entity, &Health), With<Living>>) {
-    for (entity, health) in query.iter() {
+/// Ticks a dead mob lies on the ground before it is removed.
+pub const DEATH_ANIMATION_TICKS: u32 = 20;
+
+/// Observer: Remember the latest damage taken, for death messages.
+///
+/// Register with: `world.add_observer(record_last_damage)`
+pub fn record_last_damage(trigger: On<DamageEvent>, mut commands: Commands) {
+    let event = trigger.event();
+    if let Ok(mut entity) = commands.get_entity(event.entity) {
+        entity.try_insert(LastDamage {
+            source: event.source.clone(),
+            amount: event.final_amount,
+        });
+    }
+}
+
+/// System: Mark entities whose health reached zero as dead.
+///
+/// Triggers a `DeathEvent` once per death, carrying the last damage source
+/// (or `Generic` if the entity was never damaged through a `DamageEvent`).
+pub fn detect_deaths(
+    mut commands: Commands,
+    query: Query<(Entity, &Health, Option<&LastDamage>), Without<Dead>>,
+) {
+    for (entity, health, last_damage) in query.iter() {
         if health.is_dead() {
-            // TODO: Send death event, play death animation, drop items
+            commands.entity(entity).insert(Dead::default());
+            commands.trigger(DeathEvent {
+                entity,
+                source: last_damage.map_or(DamageSource::Generic, |last| last.source.clone()),
+            });
+        }
+    }
+}
+
+/// System: Despawn dead mobs once their death animation has played.
+///
+/// Players are not `Living` and are left alone; they stay dead until they respawn.
+pub fn despawn_dead(mut commands: Commands, mut query: Query<(Entity, &mut Dead), With<Living>>) {
+    for (entity, mut dead) in query.iter_mut() {
+        dead.ticks += 1;
+        if dead.ticks >= DEATH_ANIMATION_TICKS {
             commands.entity(entity).despawn();
         }
     }
@@ -63,3 +101,47 @@ pub fn tick_mob_age(mut query: Query<&mut MobAge, With<Mob>>) {
         mob_age.tick();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Deaths(Vec<(Entity, DamageSource)>);
+
+    #[test]
+    fn test_death_lifecycle() {
+        let mut world = World::new();
+        world.init_resource::<Deaths>();
+        world.add_observer(record_last_damage);
+        world.add_observer(|trigger: On<DeathEvent>, mut deaths: ResMut<Deaths>| {
+            let event = trigger.event();
+            deaths.0.push((event.entity, event.source.clone()));
+        });
+        let mut schedule = Schedule::default();
+        schedule.add_systems((detect_deaths, despawn_dead).chain());
+
+        let mob = world.spawn((Living, Health::new(10.0))).id();
+        world.trigger(DamageEvent {
+            entity: mob,
+            source: DamageSource::Cactus,
+            amount: 10.0,
+            final_amount: 10.0,
+        });
+        world.flush();
+        world.get_mut::<Health>(mob).unwrap().damage(10.0);
+
+        schedule.run(&mut world);
+        let deaths = &world.resource::<Deaths>().0;
+        assert_eq!(deaths.len(), 1);
+        assert_eq!(deaths[0].0, mob);
+        assert!(matches!(deaths[0].1, DamageSource::Cactus));
+
+        // The death is only reported once while the animation plays
+        for _ in 1..DEATH_ANIMATION_TICKS {
+            schedule.run(&mut world);
+        }
+        assert_eq!(world.resource::<Deaths>().0.len(), 1);
+        assert!(world.get_entity(mob).is_err());
+    }
+}
@@ -1,9 +1,15 @@
 //! ECS systems for entity logic.
 
+pub mod ai;
+pub mod damage;
 pub mod effects;
+pub mod hunger;
 pub mod lifecycle;
 pub mod physics;
 
+pub use ai::*;
+pub use damage::*;
 pub use effects::*;
+pub use hunger::*;
 pub use lifecycle::*;
 pub use physics::*;
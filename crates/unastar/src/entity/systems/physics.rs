@@ -3,30 +3,134 @@
 use bevy_ecs::prelude::*;
 use glam::DVec3;
 
+use crate::entity::collision::{knockback_velocity, move_with_collision};
 use crate::entity::components::*;
+use crate::entity::damage::{DamageEvent, DamageSource};
+use crate::entity::systems::damage::apply_fall_damage;
+use crate::world::ecs::{
+    ChunkData, ChunkManager, WorldId, Worlds, world_to_chunk_coords, world_to_local_coords,
+};
 
-/// System: Apply gravity to entities with velocity.
-pub fn apply_gravity(mut query: Query<&mut Velocity, (With<Living>, Without<OnGround>)>) {
+/// Horizontal velocity retained per tick while standing on a block.
+const GROUND_FRICTION: f64 = 0.6;
+
+/// Base knockback strength applied by melee and projectile hits.
+pub const BASE_KNOCKBACK: f64 = 0.4;
+
+/// Look up a block in the loaded ECS chunks.
+///
+/// Returns `None` if the containing chunk is not loaded.
+pub fn loaded_block_at(
+    manager: &ChunkManager,
+    chunks: &Query<&ChunkData>,
+    x: i32,
+    y: i32,
+    z: i32,
+) -> Option<u32> {
+    let (cx, cz) = world_to_chunk_coords(x, z);
+    let entity = manager.get_by_coords(cx, cz)?;
+    let chunk = chunks.get(entity).ok()?;
+    let (lx, ly, lz) = world_to_local_coords(x, y, z);
+    Some(chunk.inner.get_block(lx, ly, lz, 0))
+}
+
+/// System: Apply gravity to living entities and dropped items.
+///
+/// Gravity is applied even on the ground; the collision pass cancels it and
+/// uses the blocked downward movement to detect whether the entity is still
+/// standing on something.
+pub fn apply_gravity(
+    mut living: Query<&mut Velocity, With<Living>>,
+    mut items: Query<&mut Velocity, (With<DroppedItem>, Without<Living>)>,
+) {
     const GRAVITY: f64 = 0.08;
+    const ITEM_GRAVITY: f64 = 0.04;
 
-    for mut velocity in query.iter_mut() {
+    for mut velocity in living.iter_mut() {
         velocity.0.y -= GRAVITY;
     }
+    for mut velocity in items.iter_mut() {
+        velocity.0.y -= ITEM_GRAVITY;
+    }
 }
 
-/// System: Apply velocity to position.
-pub fn apply_velocity(mut query: Query<(&mut Position, &Velocity)>) {
-    for (mut position, velocity) in query.iter_mut() {
-        position.0 += velocity.0;
+/// Components `apply_velocity` moves an entity with.
+type MovingEntity = (
+    Entity,
+    &'static mut Position,
+    &'static mut Velocity,
+    Option<&'static mut OnGround>,
+    Option<&'static Hitbox>,
+    Option<&'static mut FallDistance>,
+    Option<&'static WorldId>,
+);
+
+/// System: Move entities by their velocity, resolving block collisions.
+///
+/// Players are excluded: their movement is client-authoritative and arrives
+/// through `PlayerAuthInput`. Entities tracking a `FallDistance` take fall
+/// damage when they land. Entities without a `WorldId` are in the default
+/// world.
+pub fn apply_velocity(
+    mut commands: Commands,
+    mut query: Query<MovingEntity, Without<Player>>,
+    worlds: Res<Worlds>,
+    chunks: Query<&ChunkData>,
+) {
+    for (entity, mut position, mut velocity, on_ground, hitbox, fall_distance, world) in
+        query.iter_mut()
+    {
+        if velocity.0 == DVec3::ZERO {
+            continue;
+        }
+        let Some(manager) = worlds.get(world.copied().unwrap_or_default()) else {
+            continue;
+        };
+        let blocks = |x, y, z| loaded_block_at(manager, &chunks, x, y, z);
+
+        let hitbox = hitbox.copied().unwrap_or(Hitbox::PROJECTILE);
+        let was_on_ground = on_ground.as_ref().is_some_and(|g| g.0);
+        let result = move_with_collision(
+            &blocks,
+            &hitbox.aabb_at(position.0),
+            velocity.0,
+            was_on_ground,
+            hitbox.step_height,
+        );
+
+        position.0 += result.movement;
+        if result.collided_x {
+            velocity.0.x = 0.0;
+        }
+        if result.collided_y {
+            velocity.0.y = 0.0;
+        }
+        if result.collided_z {
+            velocity.0.z = 0.0;
+        }
+        if let Some(mut on_ground) = on_ground {
+            on_ground.0 = result.on_ground;
+        }
+        if let Some(mut fall_distance) = fall_distance
+            && let Some(distance) = fall_distance.update(result.movement.y, result.on_ground)
+        {
+            commands.queue(move |world: &mut World| {
+                apply_fall_damage(world, entity, distance);
+            });
+        }
     }
 }
 
-/// System: Apply drag to velocity.
-pub fn apply_drag(mut query: Query<&mut Velocity>) {
+/// System: Apply drag to velocity, with extra friction on the ground.
+pub fn apply_drag(mut query: Query<(&mut Velocity, Option<&OnGround>)>) {
     const DRAG: f64 = 0.02;
 
-    for mut velocity in query.iter_mut() {
+    for (mut velocity, on_ground) in query.iter_mut() {
         velocity.0 *= 1.0 - DRAG;
+        if on_ground.is_some_and(|g| g.0) {
+            velocity.0.x *= GROUND_FRICTION;
+            velocity.0.z *= GROUND_FRICTION;
+        }
     }
 }
 
@@ -41,24 +145,38 @@ pub fn clamp_velocity(mut query: Query<&mut Velocity>) {
     }
 }
 
-/// System: Check ground collision (placeholder).
-pub fn check_ground_collision(mut query: Query<(&Position, &mut OnGround, &mut Velocity)>) {
-    // Simple Y=0 ground check for now
-    // TODO: Real block collision detection
-    for (position, mut on_ground, mut velocity) in query.iter_mut() {
-        if position.0.y <= 0.0 && velocity.0.y <= 0.0 {
-            on_ground.0 = true;
-            velocity.0.y = 0.0;
-        } else if position.0.y > 0.0 {
-            on_ground.0 = false;
-        }
-    }
-}
-
-/// System: Apply knockback from damage.
+/// Observer: Apply knockback from damage events.
+///
+/// The push direction is from the attacking entity (or projectile) towards
+/// the damaged entity. Sources without a position (fall, fire...) do not
+/// knock back.
+///
+/// Register with: `world.add_observer(apply_knockback)`
 pub fn apply_knockback(
-    _query: Query<(&mut Velocity, &Position)>,
-    // TODO: Read knockback events
+    trigger: On<DamageEvent>,
+    positions: Query<&Position>,
+    mut targets: Query<(&Position, &mut Velocity, Option<&OnGround>)>,
 ) {
-    // Placeholder - would read knockback events and apply to velocity
+    let event = trigger.event();
+    let source_entity = match &event.source {
+        DamageSource::Attack { attacker } => *attacker,
+        DamageSource::Projectile { projectile, .. } => *projectile,
+        _ => return,
+    };
+
+    let Ok(source) = positions.get(source_entity) else {
+        return;
+    };
+    let source = source.0;
+    let Ok((target, mut velocity, on_ground)) = targets.get_mut(event.entity) else {
+        return;
+    };
+
+    velocity.0 = knockback_velocity(
+        velocity.0,
+        source,
+        target.0,
+        BASE_KNOCKBACK,
+        on_ground.is_none_or(|g| g.0),
+    );
 }
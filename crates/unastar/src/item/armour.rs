@@ -0,0 +1,84 @@
+//! Armour protection values.
+
+/// Protection granted by a worn armour piece.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ArmourValues {
+    /// Armour points (half a chestplate icon each).
+    pub points: f32,
+    /// Armour toughness, which weakens the reduction loss from big hits.
+    pub toughness: f32,
+}
+
+impl ArmourValues {
+    /// Protection of an armour item, or zero for anything that isn't armour.
+    pub fn of(item_id: &str) -> Self {
+        let name = item_id.strip_prefix("minecraft:").unwrap_or(item_id);
+        if name == "turtle_helmet" {
+            return Self {
+                points: 2.0,
+                toughness: 0.0,
+            };
+        }
+        let Some((material, piece)) = name.rsplit_once('_') else {
+            return Self::default();
+        };
+
+        // Points per piece: helmet, chestplate, leggings, boots
+        let (points, toughness) = match material {
+            "leather" => ([1.0, 3.0, 2.0, 1.0], 0.0),
+            "chainmail" => ([2.0, 5.0, 4.0, 1.0], 0.0),
+            "iron" => ([2.0, 6.0, 5.0, 2.0], 0.0),
+            "golden" => ([2.0, 5.0, 3.0, 1.0], 0.0),
+            "diamond" => ([3.0, 8.0, 6.0, 3.0], 2.0),
+            "netherite" => ([3.0, 8.0, 6.0, 3.0], 3.0),
+            _ => return Self::default(),
+        };
+        let index = match piece {
+            "helmet" => 0,
+            "chestplate" => 1,
+            "leggings" => 2,
+            "boots" => 3,
+            _ => return Self::default(),
+        };
+        Self {
+            points: points[index],
+            toughness,
+        }
+    }
+}
+
+impl std::ops::Add for ArmourValues {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            points: self.points + other.points,
+            toughness: self.toughness + other.toughness,
+        }
+    }
+}
+
+impl std::iter::Sum for ArmourValues {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |total, piece| total + piece)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_armour_values() {
+        let chest = ArmourValues::of("minecraft:diamond_chestplate");
+        assert_eq!(chest.points, 8.0);
+        assert_eq!(chest.toughness, 2.0);
+        assert_eq!(ArmourValues::of("minecraft:iron_boots").points, 2.0);
+        assert_eq!(ArmourValues::of("minecraft:turtle_helmet").points, 2.0);
+        assert_eq!(
+            ArmourValues::of("minecraft:diamond_sword"),
+            ArmourValues::default()
+        );
+        assert_eq!(ArmourValues::of("minecraft:stone"), ArmourValues::default());
+    }
+}
@@ -0,0 +1,56 @@
+//! Enchantment types.
+//!
+//! Enchantments are stored on items in the Bedrock `ench` tag as a list of
+//! `{id: short, lvl: short}` compounds.
+
+/// Enchantment type ID (Bedrock numeric ID).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EnchantmentType(pub i16);
+
+impl EnchantmentType {
+    pub const PROTECTION: Self = Self(0);
+    pub const FIRE_PROTECTION: Self = Self(1);
+    pub const FEATHER_FALLING: Self = Self(2);
+    pub const BLAST_PROTECTION: Self = Self(3);
+    pub const PROJECTILE_PROTECTION: Self = Self(4);
+    pub const THORNS: Self = Self(5);
+    pub const RESPIRATION: Self = Self(6);
+    pub const DEPTH_STRIDER: Self = Self(7);
+    pub const AQUA_AFFINITY: Self = Self(8);
+    pub const SHARPNESS: Self = Self(9);
+    pub const SMITE: Self = Self(10);
+    pub const BANE_OF_ARTHROPODS: Self = Self(11);
+    pub const KNOCKBACK: Self = Self(12);
+    pub const FIRE_ASPECT: Self = Self(13);
+    pub const LOOTING: Self = Self(14);
+    pub const EFFICIENCY: Self = Self(15);
+    pub const SILK_TOUCH: Self = Self(16);
+    pub const UNBREAKING: Self = Self(17);
+    pub const FORTUNE: Self = Self(18);
+    pub const POWER: Self = Self(19);
+    pub const PUNCH: Self = Self(20);
+    pub const FLAME: Self = Self(21);
+    pub const INFINITY: Self = Self(22);
+    pub const LUCK_OF_THE_SEA: Self = Self(23);
+    pub const LURE: Self = Self(24);
+    pub const FROST_WALKER: Self = Self(25);
+    pub const MENDING: Self = Self(26);
+    pub const BINDING: Self = Self(27);
+    pub const VANISHING: Self = Self(28);
+    pub const IMPALING: Self = Self(29);
+    pub const RIPTIDE: Self = Self(30);
+    pub const LOYALTY: Self = Self(31);
+    pub const CHANNELING: Self = Self(32);
+    pub const MULTISHOT: Self = Self(33);
+    pub const PIERCING: Self = Self(34);
+    pub const QUICK_CHARGE: Self = Self(35);
+    pub const SOUL_SPEED: Self = Self(36);
+    pub const SWIFT_SNEAK: Self = Self(37);
+}
+
+/// An enchantment applied to an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Enchantment {
+    pub kind: EnchantmentType,
+    pub level: i16,
+}
@@ -0,0 +1,55 @@
+//! Food values of edible items.
+//!
+//! The values are data, loaded into the item registry from
+//! `data/foods.json`; see [`ItemRegistry::load_foods`].
+//!
+//! [`ItemRegistry::load_foods`]: crate::registry::ItemRegistry::load_foods
+
+use serde::{Deserialize, Deserializer};
+
+use crate::entity::components::EffectType;
+
+/// Ticks it takes to eat most foods.
+pub const DEFAULT_EAT_TICKS: u32 = 32;
+
+/// A status effect given by eating an item.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct FoodEffect {
+    #[serde(deserialize_with = "effect_by_name")]
+    pub effect: EffectType,
+    /// Effect amplifier (0 is level I).
+    pub level: u8,
+    pub duration_ticks: u32,
+    /// Chance of the effect being applied, from 0 to 1.
+    pub chance: f32,
+}
+
+/// What eating an item does (Bedrock values).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Food {
+    /// Hunger points restored.
+    pub nutrition: i32,
+    /// Saturation points restored.
+    pub saturation: f32,
+    /// Whether the item can be eaten with a full hunger bar.
+    #[serde(default)]
+    pub always_edible: bool,
+    /// Ticks of use before the item is eaten.
+    #[serde(default = "default_eat_ticks")]
+    pub eat_ticks: u32,
+    /// Item left behind once eaten, such as the bowl of a stew.
+    #[serde(default)]
+    pub remainder: Option<String>,
+    #[serde(default)]
+    pub effects: Vec<FoodEffect>,
+}
+
+fn default_eat_ticks() -> u32 {
+    DEFAULT_EAT_TICKS
+}
+
+fn effect_by_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<EffectType, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    EffectType::from_name(&name)
+        .ok_or_else(|| serde::de::Error::custom(format!("unknown effect {name:?}")))
+}
@@ -2,6 +2,14 @@
 //!
 //! This module provides the core item representation used throughout the server.
 
+mod armour;
+mod enchantment;
+mod food;
 mod stack;
+mod weapon;
 
-pub use stack::ItemStack;
+pub use armour::ArmourValues;
+pub use enchantment::{Enchantment, EnchantmentType};
+pub use food::{DEFAULT_EAT_TICKS, Food, FoodEffect};
+pub use stack::{ItemStack, slots_from_nbt, slots_to_nbt};
+pub use weapon::{FIST_DAMAGE, attack_damage};
@@ -3,6 +3,12 @@
 //! An `ItemStack` represents a stack of items with a count, damage value,
 //! and optional NBT data. Operations are immutable—they return new stacks.
 
+use zuri_nbt::encoding::LittleEndian;
+use zuri_nbt::view::View;
+use zuri_nbt::{NBTTag, tag};
+
+use super::enchantment::{Enchantment, EnchantmentType};
+
 /// A stack of items.
 ///
 /// This is the core item representation, similar to Dragonfly's `item.Stack`.
@@ -99,6 +105,38 @@ impl ItemStack {
         self.nbt.as_ref().is_some_and(|b| !b.is_empty())
     }
 
+    /// Enchantments stored in the item's `ench` tag.
+    ///
+    /// Malformed entries are skipped.
+    pub fn enchantments(&self) -> Vec<Enchantment> {
+        let Some(NBTTag::Compound(extra)) = self
+            .nbt
+            .as_deref()
+            .and_then(|bytes| NBTTag::read(bytes, LittleEndian).ok())
+        else {
+            return Vec::new();
+        };
+        let nbt = NBTTag::Compound(extra);
+        nbt.view()
+            .at("ench")
+            .iter_list()
+            .filter_map(|entry| {
+                Some(Enchantment {
+                    kind: EnchantmentType(entry.at("id").short().ok()?),
+                    level: entry.at("lvl").short().ok()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Level of an enchantment on this item, or 0 if it isn't enchanted with it.
+    pub fn enchantment_level(&self, kind: EnchantmentType) -> i16 {
+        self.enchantments()
+            .iter()
+            .find(|enchantment| enchantment.kind == kind)
+            .map_or(0, |enchantment| enchantment.level)
+    }
+
     /// Get the maximum stack size for this item.
     ///
     /// TODO: Look up from item registry for tools (max 1), eggs (max 16), etc.
@@ -252,6 +290,86 @@ impl ItemStack {
             None => (self.clone(), other.clone()),
         }
     }
+
+    /// Serialize to the Bedrock saved-item compound (`Name`, `Count`, `Damage`, `tag`).
+    ///
+    /// The `nbt` bytes are little-endian NBT as stored on disk; they are embedded
+    /// as the `tag` compound. Containers add their own `Slot` key.
+    pub fn to_nbt(&self) -> tag::Compound {
+        let mut builder = tag::Compound::builder()
+            .with_string("Name", self.item_id.as_str())
+            .with_byte("Count", self.count)
+            .with_short("Damage", self.damage)
+            .with_byte("WasPickedUp", 0u8);
+        if let Some(NBTTag::Compound(extra)) = self
+            .nbt
+            .as_deref()
+            .and_then(|bytes| NBTTag::read(bytes, LittleEndian).ok())
+        {
+            builder = builder.with_compound("tag", extra);
+        }
+        builder.build()
+    }
+
+    /// Deserialize from a Bedrock saved-item compound.
+    ///
+    /// Missing or malformed fields fall back to an empty stack.
+    pub fn from_nbt(compound: &tag::Compound) -> Self {
+        let nbt = NBTTag::Compound(compound.clone());
+        let view = nbt.view();
+        let Ok(name) = view.at("Name").string() else {
+            return Self::empty();
+        };
+
+        let mut stack = Self::new(name, view.at("Count").byte().unwrap_or(0))
+            .with_damage(view.at("Damage").short().unwrap_or(0));
+        if let Ok(extra) = view.at("tag").compound() {
+            let mut bytes = Vec::new();
+            if NBTTag::Compound(extra.clone())
+                .write(&mut bytes, LittleEndian)
+                .is_ok()
+            {
+                stack.nbt = Some(bytes);
+            }
+        }
+        stack
+    }
+}
+
+/// Encode inventory slots as a list of saved-item compounds.
+///
+/// Empty slots are skipped; each entry records its index as `Slot`.
+pub fn slots_to_nbt(items: &[ItemStack]) -> NBTTag {
+    let list: Vec<NBTTag> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| !item.is_empty())
+        .map(|(slot, item)| {
+            let mut compound = item.to_nbt();
+            compound
+                .0
+                .insert("Slot".into(), tag::Byte(slot as u8).into());
+            compound.into()
+        })
+        .collect();
+    tag::List(list).into()
+}
+
+/// Decode a slot list written by `slots_to_nbt` into `size` slots.
+///
+/// Entries without a valid `Slot` are dropped.
+pub fn slots_from_nbt(list: &View<'_>, size: usize) -> Vec<ItemStack> {
+    let mut items = vec![ItemStack::empty(); size];
+    for entry in list.iter_list() {
+        let Ok(compound) = entry.compound() else {
+            continue;
+        };
+        let slot = entry.at("Slot").byte().unwrap_or(u8::MAX) as usize;
+        if let Some(item) = items.get_mut(slot) {
+            *item = ItemStack::from_nbt(compound);
+        }
+    }
+    items
 }
 
 // ============================================================================
@@ -319,6 +437,40 @@ mod tests {
         assert!(rest.is_empty());
     }
 
+    #[test]
+    fn test_nbt_roundtrip() {
+        let stack = ItemStack::new("minecraft:diamond_sword", 1).with_damage(12);
+        let decoded = ItemStack::from_nbt(&stack.to_nbt());
+        assert_eq!(decoded, stack);
+
+        // Missing name reads as an empty stack
+        assert!(ItemStack::from_nbt(&Default::default()).is_empty());
+    }
+
+    #[test]
+    fn test_enchantments() {
+        let ench = tag::List(vec![
+            tag::Compound::builder()
+                .with_short("id", 0i16)
+                .with_short("lvl", 4i16)
+                .build()
+                .into(),
+        ]);
+        let mut bytes = Vec::new();
+        NBTTag::Compound(tag::Compound::builder().with_list("ench", ench).build())
+            .write(&mut bytes, LittleEndian)
+            .unwrap();
+        let stack = ItemStack::new("minecraft:diamond_chestplate", 1).with_nbt(bytes);
+
+        assert_eq!(stack.enchantment_level(EnchantmentType::PROTECTION), 4);
+        assert_eq!(stack.enchantment_level(EnchantmentType::THORNS), 0);
+        assert!(
+            ItemStack::new("minecraft:stick", 1)
+                .enchantments()
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_comparable() {
         let a = ItemStack::new("minecraft:diamond", 32);
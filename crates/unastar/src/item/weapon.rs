@@ -0,0 +1,68 @@
+//! Melee attack damage.
+
+/// Damage dealt by an empty hand or an item that isn't a weapon or tool.
+pub const FIST_DAMAGE: f32 = 1.0;
+
+/// Melee damage of an item (Bedrock values, including the base hand damage).
+///
+/// Copper tools hit as hard as stone ones.
+pub fn attack_damage(item_id: &str) -> f32 {
+    match item_id {
+        "minecraft:wooden_sword" => 5.0,
+        "minecraft:golden_sword" => 5.0,
+        "minecraft:stone_sword" => 6.0,
+        "minecraft:copper_sword" => 6.0,
+        "minecraft:iron_sword" => 7.0,
+        "minecraft:diamond_sword" => 8.0,
+        "minecraft:netherite_sword" => 9.0,
+        "minecraft:wooden_axe" => 4.0,
+        "minecraft:golden_axe" => 4.0,
+        "minecraft:stone_axe" => 5.0,
+        "minecraft:copper_axe" => 5.0,
+        "minecraft:iron_axe" => 6.0,
+        "minecraft:diamond_axe" => 7.0,
+        "minecraft:netherite_axe" => 8.0,
+        "minecraft:wooden_pickaxe" => 3.0,
+        "minecraft:golden_pickaxe" => 3.0,
+        "minecraft:stone_pickaxe" => 4.0,
+        "minecraft:copper_pickaxe" => 4.0,
+        "minecraft:iron_pickaxe" => 5.0,
+        "minecraft:diamond_pickaxe" => 6.0,
+        "minecraft:netherite_pickaxe" => 7.0,
+        "minecraft:wooden_shovel" => 2.0,
+        "minecraft:golden_shovel" => 2.0,
+        "minecraft:stone_shovel" => 3.0,
+        "minecraft:copper_shovel" => 3.0,
+        "minecraft:iron_shovel" => 4.0,
+        "minecraft:diamond_shovel" => 5.0,
+        "minecraft:netherite_shovel" => 6.0,
+        "minecraft:wooden_hoe" => 2.0,
+        "minecraft:golden_hoe" => 2.0,
+        "minecraft:stone_hoe" => 3.0,
+        "minecraft:copper_hoe" => 3.0,
+        "minecraft:iron_hoe" => 4.0,
+        "minecraft:diamond_hoe" => 5.0,
+        "minecraft:netherite_hoe" => 6.0,
+        "minecraft:trident" => 9.0,
+        "minecraft:mace" => 6.0,
+        _ => FIST_DAMAGE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attack_damage() {
+        assert_eq!(attack_damage("minecraft:diamond_sword"), 8.0);
+        assert_eq!(attack_damage("minecraft:golden_sword"), 5.0);
+        assert_eq!(attack_damage("minecraft:netherite_axe"), 8.0);
+        assert_eq!(attack_damage("minecraft:stone_shovel"), 3.0);
+        assert_eq!(attack_damage("minecraft:copper_sword"), 6.0);
+        assert_eq!(attack_damage("minecraft:copper_axe"), 5.0);
+        assert_eq!(attack_damage("minecraft:trident"), 9.0);
+        assert_eq!(attack_damage("minecraft:diamond_chestplate"), FIST_DAMAGE);
+        assert_eq!(attack_damage("minecraft:air"), FIST_DAMAGE);
+    }
+}
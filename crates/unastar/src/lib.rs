@@ -5,23 +5,31 @@
 
 #![feature(portable_simd)]
 
+pub mod access;
 pub mod command;
 pub mod config;
+pub mod console;
 pub mod ecs;
 pub mod entity;
 pub mod item;
 pub mod network;
+pub mod permission;
 pub mod plugin;
 pub mod registry;
 pub mod server;
 pub mod storage;
 pub mod world;
 
-pub use command::{Command, CommandArgs, CommandContext, CommandOutput, CommandRegistry};
+pub use access::AccessControl;
+pub use command::{
+    Arguments, Command, CommandArgs, CommandContext, CommandOutput, CommandRegistry, CommandSender,
+    Overload, Param,
+};
 pub use config::{ConfigError, UnastarConfig};
 pub use ecs::UnastarEcs;
 pub use entity::{DamageSource, HealingSource};
 pub use network::{NetworkEvent, SessionId};
+pub use permission::{OpLevel, Permissions};
 pub use registry::{
     biome::{BiomeEntry, BiomeRegistry},
     block::{BlockEntry, BlockRegistry},
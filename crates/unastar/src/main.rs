@@ -7,6 +7,7 @@ use std::sync::Arc;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 use unastar::config::UnastarConfig;
+use unastar::console::ConsoleWriter;
 use unastar::server::UnastarServer;
 
 /*
@@ -26,7 +27,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Initialize tracing
     let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-    tracing_subscriber::fmt().with_env_filter(env_filter).init();
+    tracing_subscriber::fmt()
+        .with_env_filter(env_filter)
+        .with_writer(ConsoleWriter)
+        .init();
 
     info!("Unastar Server starting...");
 
@@ -2,8 +2,11 @@
 
 use glam::DVec3;
 use jolyne::valentine::McpePacket;
+use std::net::SocketAddr;
 use tokio::sync::mpsc;
 
+use crate::storage::PlayerData;
+
 /// Unique session identifier.
 pub type SessionId = u64;
 
@@ -19,11 +22,15 @@ pub enum NetworkEvent {
         display_name: String,
         xuid: Option<String>,
         uuid: Option<String>,
+        /// Address the client connected from.
+        address: SocketAddr,
         runtime_id: i64,
         initial_position: DVec3,
         /// Bounded channel to send packets to this player.
         /// Uses bounded capacity to prevent memory explosion on slow connections.
         outbound_tx: mpsc::Sender<McpePacket>,
+        /// Saved player data loaded during the join sequence.
+        player_data: Option<Box<PlayerData>>,
     },
 
     /// Player sent a packet.
@@ -4,7 +4,7 @@ pub mod events;
 
 use jolyne::BedrockStream;
 use jolyne::stream::{Play, Server as ServerRole};
-use jolyne::valentine::McpePacket;
+use jolyne::valentine::{McpePacket, McpePacketData};
 use tokio::sync::{broadcast, mpsc};
 use tracing::{debug, info, trace, warn};
 
@@ -40,6 +40,8 @@ pub fn spawn_network_task(
 /// Uses manual flushing for efficient batching:
 /// - `send_packet()` queues packets without sending
 /// - `flush()` sends all queued packets as a single batch on tick
+///
+/// A queued `Disconnect` packet is flushed and ends the session.
 async fn run_network_loop(
     mut stream: BedrockStream<Play, ServerRole, jolyne::stream::transport::RakNetTransport>,
     session_id: SessionId,
@@ -47,7 +49,7 @@ async fn run_network_loop(
     mut outbound_rx: mpsc::Receiver<McpePacket>,
     mut tick_rx: broadcast::Receiver<()>,
 ) {
-    loop {
+    'session: loop {
         tokio::select! {
             biased;
 
@@ -57,10 +59,14 @@ async fn run_network_loop(
                     Ok(()) => {
                         // Drain any remaining packets and queue them
                         while let Ok(packet) = outbound_rx.try_recv() {
+                            let disconnect = is_disconnect(&packet);
                             if let Err(e) = stream.send_packet(packet).await {
                                 tracing::error!(session_id, "Send failed (tick flush): {:?}", e);
                                 return;
                             }
+                            if disconnect {
+                                break 'session;
+                            }
                         }
                         // Flush all queued packets as a single batch
                         if let Err(e) = stream.flush().await {
@@ -107,16 +113,24 @@ async fn run_network_loop(
 
             // Priority 3: Queue outbound packets (batched flush on tick signal)
             Some(packet) = outbound_rx.recv() => {
+                let disconnect = is_disconnect(&packet);
                 if let Err(e) = stream.send_packet(packet).await {
                     tracing::error!(session_id, "Send failed (immediate): {:?}", e);
                     break;
                 }
+                if disconnect {
+                    break;
+                }
                 // Drain any other pending packets into buffer
                 while let Ok(p) = outbound_rx.try_recv() {
+                    let disconnect = is_disconnect(&p);
                     if let Err(e) = stream.send_packet(p).await {
                         warn!(session_id, "Send failed: {:?}", e);
                         return;
                     }
+                    if disconnect {
+                        break 'session;
+                    }
                 }
                 // NO flush here - packets accumulate until tick signal for efficient batching
                 // This reduces compression operations from N per tick to 1 per tick
@@ -127,3 +141,8 @@ async fn run_network_loop(
     // Final flush on disconnect
     let _ = stream.flush().await;
 }
+
+/// Whether the server is closing the connection with this packet.
+fn is_disconnect(packet: &McpePacket) -> bool {
+    matches!(packet.data, McpePacketData::PacketDisconnect(_))
+}
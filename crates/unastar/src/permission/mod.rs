@@ -0,0 +1,557 @@
+//! Operator levels and permission nodes.
+//!
+//! Every player has an [`OpLevel`] (none unless opped) and may have nodes
+//! granted or revoked explicitly. Commands check a node such as
+//! `unastar.command.tp`; a player holds it if it is granted, or if their op
+//! level reaches the command's default level and it isn't revoked.
+//!
+//! Both are persisted to `permissions.toml` next to `unastar.toml`. Players
+//! signed in with Xbox Live are keyed by XUID, so a name change or another
+//! player taking the name doesn't move their permissions; the last known name
+//! is kept in `names` for display. Players without an XUID are keyed by
+//! lowercase name:
+//!
+//! ```toml
+//! [ops]
+//! 2535416409876543 = 4
+//! alex = 2
+//!
+//! [players.2535416409876543]
+//! grant = ["unastar.command.tp"]
+//! revoke = ["unastar.command.*"]
+//!
+//! [names]
+//! 2535416409876543 = "Steve"
+//! ```
+//!
+//! Entries added by name for a player who hasn't joined yet (`alex` above)
+//! move to the XUID of the first player to join under that name.
+//!
+//! Nodes may end in `.*` to match everything below them, and `*` matches
+//! every node. When several entries match, the most specific wins and a
+//! revoke beats a grant of the same node.
+
+use bevy_ecs::prelude::*;
+use jolyne::valentine::types::{CommandPermissionLevel, PermissionLevel};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::entity::components::{PlayerName, PlayerSession};
+
+/// File name of the permissions file, placed next to the config file.
+pub const PERMISSIONS_FILE: &str = "permissions.toml";
+
+/// Operator level, as in `/op`.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum OpLevel {
+    /// Not an operator.
+    #[default]
+    None = 0,
+    /// May bypass spawn protection.
+    Moderator = 1,
+    /// Game-changing commands such as `/tp` and `/gamemode`.
+    Gamemaster = 2,
+    /// Player management such as `/op`, `/kick` and `/ban`.
+    Admin = 3,
+    /// Server management such as `/stop`.
+    Owner = 4,
+}
+
+impl OpLevel {
+    pub fn from_u8(level: u8) -> Option<Self> {
+        match level {
+            0 => Some(OpLevel::None),
+            1 => Some(OpLevel::Moderator),
+            2 => Some(OpLevel::Gamemaster),
+            3 => Some(OpLevel::Admin),
+            4 => Some(OpLevel::Owner),
+            _ => None,
+        }
+    }
+
+    pub fn is_op(self) -> bool {
+        self > OpLevel::None
+    }
+
+    /// Player permission sent in `StartGame`, `AddPlayer` and `UpdateAbilities`.
+    pub fn permission_level(self) -> PermissionLevel {
+        if self.is_op() {
+            PermissionLevel::Operator
+        } else {
+            PermissionLevel::Member
+        }
+    }
+
+    /// Command permission sent in `AddPlayer` and `UpdateAbilities`.
+    pub fn command_permission(self) -> CommandPermissionLevel {
+        match self {
+            OpLevel::None => CommandPermissionLevel::Normal,
+            OpLevel::Moderator | OpLevel::Gamemaster => CommandPermissionLevel::Operator,
+            OpLevel::Admin => CommandPermissionLevel::Host,
+            OpLevel::Owner => CommandPermissionLevel::Owner,
+        }
+    }
+
+    /// Permission level name used for commands in `AvailableCommands`.
+    pub fn command_level_name(self) -> &'static str {
+        match self {
+            OpLevel::None => "any",
+            OpLevel::Moderator | OpLevel::Gamemaster => "game_directors",
+            OpLevel::Admin => "admin",
+            OpLevel::Owner => "owner",
+        }
+    }
+}
+
+impl TryFrom<u8> for OpLevel {
+    type Error = String;
+
+    fn try_from(level: u8) -> Result<Self, Self::Error> {
+        OpLevel::from_u8(level).ok_or_else(|| format!("op level {level} is not between 0 and 4"))
+    }
+}
+
+impl From<OpLevel> for u8 {
+    fn from(level: OpLevel) -> Self {
+        level as u8
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct PlayerNodes {
+    grant: BTreeSet<String>,
+    revoke: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct PermissionsFile {
+    ops: BTreeMap<String, OpLevel>,
+    players: BTreeMap<String, PlayerNodes>,
+    /// Last known name of each XUID with an entry.
+    names: BTreeMap<String, String>,
+}
+
+impl PermissionsFile {
+    /// Record the name of an XUID entry, or forget it once the entry is gone.
+    fn update_name(&mut self, key: &str, name: &str, xuid: Option<&str>) {
+        let Some(xuid) = xuid.filter(|xuid| !xuid.is_empty()) else {
+            return;
+        };
+        if self.ops.contains_key(key) || self.players.contains_key(key) {
+            self.names.insert(xuid.to_string(), name.to_string());
+        } else {
+            self.names.remove(xuid);
+        }
+    }
+}
+
+/// How specifically `pattern` matches `node`, if it does.
+fn specificity(pattern: &str, node: &str) -> Option<usize> {
+    if pattern == node {
+        return Some(usize::MAX);
+    }
+    if pattern == "*" {
+        return Some(0);
+    }
+    let prefix = pattern.strip_suffix(".*")?;
+    node.strip_prefix(prefix)
+        .is_some_and(|rest| rest.starts_with('.'))
+        .then_some(prefix.len())
+}
+
+/// Key of a player's entries: its XUID, or its lowercase name without one.
+fn key(name: &str, xuid: Option<&str>) -> String {
+    match xuid.filter(|xuid| !xuid.is_empty()) {
+        Some(xuid) => xuid.to_string(),
+        None => name.to_ascii_lowercase(),
+    }
+}
+
+fn read_file(path: &Path) -> Result<PermissionsFile, PermissionsError> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => {
+            toml_edit::de::from_str(&contents).map_err(|source| PermissionsError::Parse {
+                path: path.to_path_buf(),
+                source,
+            })
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(PermissionsFile::default()),
+        Err(source) => Err(PermissionsError::Read {
+            path: path.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+/// Shared handle to the server's ops and permission nodes.
+///
+/// Inserted as a world resource; clones refer to the same data, so the join
+/// sequence can read levels before the player enters the ECS. Changes are
+/// written to disk immediately when the handle was loaded from a file.
+#[derive(Resource, Clone, Default)]
+pub struct Permissions {
+    file: Arc<RwLock<PermissionsFile>>,
+    path: Option<Arc<PathBuf>>,
+}
+
+impl std::fmt::Debug for Permissions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Permissions")
+            .field("path", &self.path)
+            .field("ops", &self.file.read().ops.len())
+            .finish()
+    }
+}
+
+impl Permissions {
+    /// Load from `path`. A missing file is created on the first change.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PermissionsError> {
+        let path = path.as_ref().to_path_buf();
+        Ok(Self {
+            file: Arc::new(RwLock::new(read_file(&path)?)),
+            path: Some(Arc::new(path)),
+        })
+    }
+
+    /// Re-read the file, picking up edits made while the server is running.
+    /// Handles without a file are left unchanged.
+    pub fn reload(&self) -> Result<(), PermissionsError> {
+        if let Some(path) = &self.path {
+            *self.file.write() = read_file(path)?;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<(), PermissionsError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = toml_edit::ser::to_string_pretty(&*self.file.read())
+            .map_err(PermissionsError::Serialize)?;
+        std::fs::write(path.as_ref(), contents).map_err(|source| PermissionsError::Write {
+            path: path.as_ref().clone(),
+            source,
+        })
+    }
+
+    /// Move entries added under a player's name to its XUID and refresh the
+    /// name recorded for it. Called when the player joins.
+    pub fn claim(&self, name: &str, xuid: &str) -> Result<(), PermissionsError> {
+        if xuid.is_empty() {
+            return Ok(());
+        }
+        {
+            let mut file = self.file.write();
+            let name_key = key(name, None);
+            let level = file.ops.remove(&name_key);
+            let nodes = file.players.remove(&name_key);
+            let changed = level.is_some()
+                || nodes.is_some()
+                || file.names.get(xuid).is_some_and(|known| known != name);
+            if !changed {
+                return Ok(());
+            }
+            if let Some(level) = level {
+                file.ops.entry(xuid.to_string()).or_insert(level);
+            }
+            if let Some(nodes) = nodes {
+                let entry = file.players.entry(xuid.to_string()).or_default();
+                entry.grant.extend(nodes.grant);
+                entry.revoke.extend(nodes.revoke);
+            }
+            // Another XUID may have held the name before
+            file.names
+                .retain(|other, known| other == xuid || !known.eq_ignore_ascii_case(name));
+            file.update_name(xuid, name, Some(xuid));
+        }
+        self.save()
+    }
+
+    /// XUID last seen under a name, for players with an entry.
+    ///
+    /// Used to find the entries of offline players named in commands.
+    pub fn known_xuid(&self, name: &str) -> Option<String> {
+        let file = self.file.read();
+        file.names
+            .iter()
+            .find(|(_, known)| known.eq_ignore_ascii_case(name))
+            .map(|(xuid, _)| xuid.clone())
+    }
+
+    /// Op level of a player.
+    pub fn level(&self, name: &str, xuid: Option<&str>) -> OpLevel {
+        self.file
+            .read()
+            .ops
+            .get(&key(name, xuid))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Set a player's op level. `OpLevel::None` removes them from the ops list.
+    pub fn set_level(
+        &self,
+        name: &str,
+        xuid: Option<&str>,
+        level: OpLevel,
+    ) -> Result<(), PermissionsError> {
+        {
+            let mut file = self.file.write();
+            let key = key(name, xuid);
+            if level.is_op() {
+                file.ops.insert(key.clone(), level);
+            } else {
+                file.ops.remove(&key);
+            }
+            file.update_name(&key, name, xuid);
+        }
+        self.save()
+    }
+
+    /// Operators and their levels, sorted by key. Players with an XUID are
+    /// listed under their last known name.
+    pub fn ops(&self) -> Vec<(String, OpLevel)> {
+        let file = self.file.read();
+        file.ops
+            .iter()
+            .map(|(key, level)| (file.names.get(key).unwrap_or(key).clone(), *level))
+            .collect()
+    }
+
+    /// Whether a player holds `node`, falling back to `default_level` when
+    /// no explicit entry matches.
+    pub fn has(&self, name: &str, xuid: Option<&str>, node: &str, default_level: OpLevel) -> bool {
+        let file = self.file.read();
+        let key = key(name, xuid);
+        if let Some(nodes) = file.players.get(&key) {
+            let best = |set: &BTreeSet<String>| {
+                set.iter()
+                    .filter_map(|pattern| specificity(pattern, node))
+                    .max()
+            };
+            match (best(&nodes.grant), best(&nodes.revoke)) {
+                (Some(grant), Some(revoke)) => return grant > revoke,
+                (Some(_), None) => return true,
+                (None, Some(_)) => return false,
+                (None, None) => {}
+            }
+        }
+        file.ops.get(&key).copied().unwrap_or_default() >= default_level
+    }
+
+    /// Grant a node to a player, replacing any revoke of the same node.
+    pub fn grant(
+        &self,
+        name: &str,
+        xuid: Option<&str>,
+        node: &str,
+    ) -> Result<(), PermissionsError> {
+        {
+            let mut file = self.file.write();
+            let key = key(name, xuid);
+            let nodes = file.players.entry(key.clone()).or_default();
+            nodes.revoke.remove(node);
+            nodes.grant.insert(node.to_string());
+            file.update_name(&key, name, xuid);
+        }
+        self.save()
+    }
+
+    /// Revoke a node from a player, replacing any grant of the same node.
+    pub fn revoke(
+        &self,
+        name: &str,
+        xuid: Option<&str>,
+        node: &str,
+    ) -> Result<(), PermissionsError> {
+        {
+            let mut file = self.file.write();
+            let key = key(name, xuid);
+            let nodes = file.players.entry(key.clone()).or_default();
+            nodes.grant.remove(node);
+            nodes.revoke.insert(node.to_string());
+            file.update_name(&key, name, xuid);
+        }
+        self.save()
+    }
+
+    /// Remove an explicit grant or revoke, going back to the op level default.
+    pub fn unset(
+        &self,
+        name: &str,
+        xuid: Option<&str>,
+        node: &str,
+    ) -> Result<(), PermissionsError> {
+        {
+            let mut file = self.file.write();
+            let key = key(name, xuid);
+            if let Some(nodes) = file.players.get_mut(&key) {
+                nodes.grant.remove(node);
+                nodes.revoke.remove(node);
+                if nodes.grant.is_empty() && nodes.revoke.is_empty() {
+                    file.players.remove(&key);
+                }
+            }
+            file.update_name(&key, name, xuid);
+        }
+        self.save()
+    }
+}
+
+/// XUID of an online player, if it signed in with Xbox Live.
+pub fn player_xuid(world: &World, entity: Entity) -> Option<&str> {
+    world
+        .get::<PlayerSession>(entity)?
+        .xuid
+        .as_deref()
+        .filter(|xuid| !xuid.is_empty())
+}
+
+/// Op level of a player entity. Without a [`Permissions`] resource nobody is op.
+pub fn op_level(world: &World, entity: Entity) -> OpLevel {
+    match (
+        world.get::<PlayerName>(entity),
+        world.get_resource::<Permissions>(),
+    ) {
+        (Some(name), Some(permissions)) => permissions.level(&name.0, player_xuid(world, entity)),
+        _ => OpLevel::None,
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PermissionsError {
+    #[error("failed to read `{path}`: {source}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to parse `{path}`: {source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml_edit::de::Error,
+    },
+    #[error("failed to serialize permissions: {0}")]
+    Serialize(#[source] toml_edit::ser::Error),
+    #[error("failed to write `{path}`: {source}")]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_resolution() {
+        let permissions = Permissions::default();
+        assert!(!permissions.has("Steve", None, "unastar.command.tp", OpLevel::Gamemaster));
+        assert!(permissions.has("Steve", None, "unastar.command.pos", OpLevel::None));
+
+        permissions
+            .set_level("Steve", None, OpLevel::Admin)
+            .unwrap();
+        assert_eq!(permissions.level("steve", None), OpLevel::Admin);
+        assert!(permissions.has("Steve", None, "unastar.command.tp", OpLevel::Gamemaster));
+        assert!(!permissions.has("Steve", None, "unastar.command.stop", OpLevel::Owner));
+
+        permissions
+            .revoke("Steve", None, "unastar.command.*")
+            .unwrap();
+        permissions
+            .grant("Steve", None, "unastar.command.stop")
+            .unwrap();
+        assert!(!permissions.has("Steve", None, "unastar.command.tp", OpLevel::Gamemaster));
+        assert!(permissions.has("STEVE", None, "unastar.command.stop", OpLevel::Owner));
+        assert_eq!(specificity("unastar.command.*", "unastar.commandx"), None);
+
+        permissions
+            .unset("Steve", None, "unastar.command.*")
+            .unwrap();
+        assert!(permissions.has("Steve", None, "unastar.command.tp", OpLevel::Gamemaster));
+        permissions.set_level("Steve", None, OpLevel::None).unwrap();
+        assert!(permissions.ops().is_empty());
+    }
+
+    #[test]
+    fn test_keyed_by_xuid() {
+        let permissions = Permissions::default();
+        permissions
+            .set_level("Steve", None, OpLevel::Owner)
+            .unwrap();
+        permissions
+            .grant("Steve", None, "unastar.command.tp")
+            .unwrap();
+
+        // The first Steve to join with an XUID takes the entries over
+        permissions.claim("Steve", "1001").unwrap();
+        assert_eq!(permissions.level("Steve", Some("1001")), OpLevel::Owner);
+        assert!(permissions.has("Steve", Some("1001"), "unastar.command.tp", OpLevel::Owner));
+        assert_eq!(
+            permissions.ops(),
+            vec![("Steve".to_string(), OpLevel::Owner)]
+        );
+        assert_eq!(permissions.known_xuid("steve").as_deref(), Some("1001"));
+
+        // Another account using the name gets nothing, with or without an XUID
+        permissions.claim("Steve", "2002").unwrap();
+        assert_eq!(permissions.level("Steve", Some("2002")), OpLevel::None);
+        assert_eq!(permissions.level("Steve", None), OpLevel::None);
+
+        // A renamed player keeps its level
+        permissions.claim("Herobrine", "1001").unwrap();
+        assert_eq!(permissions.level("Herobrine", Some("1001")), OpLevel::Owner);
+        assert_eq!(permissions.known_xuid("Steve"), None);
+
+        permissions
+            .set_level("Herobrine", Some("1001"), OpLevel::None)
+            .unwrap();
+        permissions
+            .unset("Herobrine", Some("1001"), "unastar.command.tp")
+            .unwrap();
+        assert_eq!(permissions.known_xuid("Herobrine"), None);
+    }
+
+    #[test]
+    fn test_persistence() {
+        let path =
+            std::env::temp_dir().join(format!("unastar-permissions-{}.toml", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let permissions = Permissions::load(&path).unwrap();
+        permissions
+            .set_level("Alex", Some("1001"), OpLevel::Owner)
+            .unwrap();
+        permissions
+            .grant("Steve", None, "unastar.command.tp")
+            .unwrap();
+
+        let reloaded = Permissions::load(&path).unwrap();
+        assert_eq!(reloaded.level("alex", Some("1001")), OpLevel::Owner);
+        assert_eq!(reloaded.known_xuid("Alex").as_deref(), Some("1001"));
+        assert!(reloaded.has("steve", None, "unastar.command.tp", OpLevel::Owner));
+
+        std::fs::write(&path, "[ops]\nsteve = 2\n").unwrap();
+        permissions.reload().unwrap();
+        assert_eq!(permissions.level("alex", Some("1001")), OpLevel::None);
+        assert_eq!(permissions.level("steve", None), OpLevel::Gamemaster);
+
+        std::fs::write(&path, "[ops]\nalex = 9\n").unwrap();
+        assert!(Permissions::load(&path).is_err());
+        assert!(permissions.reload().is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+}
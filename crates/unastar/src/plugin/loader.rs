@@ -3,7 +3,7 @@ use libloading::{Library, Symbol};
 use std::fs;
 use std::path::Path;
 use tracing::{error, info, warn};
-use unastar_api::native::{RawPlugin, RawPlugin_TO};
+use unastar_api::native::{PLUGIN_ABI_VERSION, RawPlugin, RawPlugin_TO};
 
 pub struct PluginLoader;
 
@@ -60,6 +60,30 @@ impl PluginLoader {
             }
         };
 
+        // Refuse plugins built against another ABI: calling into a vtable of
+        // a different layout is undefined behaviour
+        type AbiVersionFn = extern "C" fn() -> u32;
+
+        let abi_version = unsafe {
+            match lib.get::<AbiVersionFn>(b"_plugin_abi_version") {
+                Ok(version_fn) => version_fn(),
+                Err(_) => {
+                    warn!(
+                        "Plugin {:?} does not export _plugin_abi_version; rebuild it against this server",
+                        path
+                    );
+                    return None;
+                }
+            }
+        };
+        if abi_version != PLUGIN_ABI_VERSION {
+            warn!(
+                "Plugin {:?} was built for plugin ABI {}, but the server uses {}",
+                path, abi_version, PLUGIN_ABI_VERSION
+            );
+            return None;
+        }
+
         // Find the creation function
         // Returns RawPlugin_TO by value (it's a struct wrapping a pointer)
         type CreateFn = extern "C" fn() -> RawPlugin_TO<RBox<()>>;
@@ -252,7 +252,9 @@ impl PluginManager {
             "world_get_block",
             |caller: Caller<'_, HostContext>, x: i32, y: i32, z: i32| -> u32 {
                 let world = unsafe { &*caller.data().world_ptr };
-                if let Some(chunk_manager) = world.get_resource::<crate::world::ecs::ChunkManager>()
+                if let Some(chunk_manager) = world
+                    .get_resource::<crate::world::ecs::Worlds>()
+                    .map(crate::world::ecs::Worlds::default_world)
                 {
                     use crate::world::ecs::{world_to_chunk_coords, world_to_local_coords};
                     let (cx, cz) = world_to_chunk_coords(x, z);
@@ -262,7 +264,7 @@ impl PluginManager {
                         if let Some(chunk_data) =
                             world.get::<crate::world::ecs::ChunkData>(chunk_entity)
                         {
-                            return chunk_data.inner.get_block(lx, ly, lz);
+                            return chunk_data.inner.get_block(lx, ly, lz, 0);
                         }
                     }
                 }
@@ -433,6 +435,21 @@ impl PluginManager {
             .unwrap()
             .current;
 
+        // Default world time of day, weather (0 clear, 1 rain, 2 thunder)
+        // and difficulty
+        let default_world = ecs_world
+            .get_resource::<crate::world::Worlds>()
+            .map(|worlds| worlds.default_world());
+        let level = (
+            default_world.map_or(0, |world| world.time().time.max(0) as u64),
+            default_world.map_or(0, |world| world.weather().kind().id()),
+            ecs_world
+                .get_resource::<crate::world::Difficulty>()
+                .copied()
+                .unwrap_or_default()
+                .id(),
+        );
+
         let world_ptr = ecs_world as *mut bevy_ecs::world::World;
 
         // Get player count for SharedState
@@ -669,6 +686,7 @@ impl PluginManager {
                 &events_bytes,
                 (spawn_x, spawn_y, spawn_z),
                 tick_id,
+                level,
                 player_count,
                 active_player_data,
             )
@@ -768,12 +786,14 @@ impl PluginManager {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn tick_plugin(
         id: &PluginId,
         plugin: &mut LoadedPlugin,
         events_bytes: &[u8],
         spawn: (f64, f64, f64),
         tick_id: u64,
+        level: (u64, u8, u8),
         player_count: u32,
         active_player: Option<(u32, f64, f64, f64, f32, f32, f32)>,
     ) -> Result<Vec<PluginAction>, anyhow::Error> {
@@ -786,14 +806,14 @@ impl PluginManager {
         // Construct SharedState
         let mut shared_state = unastar_api::SharedState {
             tick_id,
-            world_time: tick_id, // For now use tick_id
-            current_tps: 20.0,   // Placeholder
+            world_time: level.0,
+            current_tps: 20.0, // Placeholder
             player_count,
             spawn_x: spawn.0,
             spawn_y: spawn.1,
             spawn_z: spawn.2,
-            weather: 0,
-            difficulty: 2, // Normal
+            weather: level.1,
+            difficulty: level.2,
             active_player_id: 0,
             active_player_x: 0.0,
             active_player_y: 0.0,
@@ -8,13 +8,17 @@ use std::sync::Arc;
 use tracing::{info, warn};
 
 use abi_stable::std_types::{RBox, RStr};
-use unastar_api::native::{BlockPos, Player, PluginEntity, RawPlugin_TO, Vec3};
+use unastar_api::native::{
+    BlockPos, CommandCall, CommandSpec, EntityDamage, Player, PluginEntity, RawPlugin_TO, Vec3,
+};
 
 /// Resource that holds all loaded plugins.
 #[derive(Resource)]
 pub struct PluginRegistry {
     /// Loaded plugins
     plugins: Vec<RawPlugin_TO<RBox<()>>>,
+    /// Commands registered by plugins, with the index of the owning plugin
+    commands: Vec<(usize, CommandSpec)>,
 }
 
 impl PluginRegistry {
@@ -22,6 +26,7 @@ impl PluginRegistry {
     pub fn new() -> Self {
         Self {
             plugins: Vec::new(),
+            commands: Vec::new(),
         }
     }
 
@@ -40,8 +45,11 @@ impl PluginRegistry {
                 ),
             );
 
-            let mut ctx = unastar_api::native::PluginContext { world: native_ctx };
+            let mut ctx = unastar_api::native::PluginContext::new(native_ctx);
             plugin.on_load(&mut ctx);
+            let index = self.plugins.len();
+            self.commands
+                .extend(ctx.take_commands().into_iter().map(|spec| (index, spec)));
         }
 
         info!("Plugin loaded: {}", name);
@@ -93,6 +101,49 @@ impl PluginRegistry {
         }
     }
 
+    /// Let plugins modify or cancel one stage of a hit. Returns false if any plugin cancelled it.
+    pub fn on_entity_damage(
+        &mut self,
+        world: &mut World,
+        entity: Entity,
+        damage: &mut EntityDamage,
+    ) -> bool {
+        let mut allow = true;
+        for plugin in &mut self.plugins {
+            let host = crate::server::game::host::ServerHost { world: &mut *world };
+            let mut native_ctx = unastar_api::native::NativeGameContext::new(
+                unastar_api::native::RawPluginHost_TO::from_value(
+                    host,
+                    abi_stable::sabi_trait::TD_Opaque,
+                ),
+            );
+            if !plugin.on_entity_damage(&mut native_ctx, PluginEntity::from(entity), damage) {
+                allow = false;
+            }
+        }
+        allow
+    }
+
+    /// Commands registered by plugins, with the index of the owning plugin.
+    pub fn commands(&self) -> &[(usize, CommandSpec)] {
+        &self.commands
+    }
+
+    /// Run a plugin command on the plugin that registered it.
+    pub fn on_command(&mut self, world: &mut World, plugin: usize, call: &mut CommandCall) {
+        let Some(plugin) = self.plugins.get_mut(plugin) else {
+            return;
+        };
+        let host = crate::server::game::host::ServerHost { world };
+        let mut native_ctx = unastar_api::native::NativeGameContext::new(
+            unastar_api::native::RawPluginHost_TO::from_value(
+                host,
+                abi_stable::sabi_trait::TD_Opaque,
+            ),
+        );
+        plugin.on_command(&mut native_ctx, call);
+    }
+
     // Add other event methods as needed...
 }
 
@@ -3,7 +3,319 @@
 //! Blocks are more complex than items/entities because of block states.
 //! Each block type has multiple runtime IDs (one per state combination).
 
+use std::sync::LazyLock;
+
 use super::{Registry, RegistryEntry};
+use crate::entity::collision::Aabb;
+
+/// Collision geometry of a block, in block-local coordinates (0..1).
+///
+/// Valentine's block data carries no shape information, so shapes are
+/// derived from the block identifier. Only the default state is modelled;
+/// state-dependent blocks (top slabs, open doors) use their most common shape.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CollisionShape {
+    /// Entities pass straight through (air, fluids, plants, torches...).
+    Empty,
+    /// Full 1x1x1 cube.
+    Full,
+    /// One or more boxes, possibly taller than a block (fences, walls).
+    Boxes(Vec<Aabb>),
+}
+
+impl CollisionShape {
+    const FULL_BOX: [Aabb; 1] = [Aabb::from_coords(0.0, 0.0, 0.0, 1.0, 1.0, 1.0)];
+
+    /// A single box spanning the full footprint up to `height`.
+    fn slab(height: f64) -> Self {
+        Self::Boxes(vec![Aabb::from_coords(0.0, 0.0, 0.0, 1.0, height, 1.0)])
+    }
+
+    /// A single box inset horizontally by `inset` up to `height`.
+    fn inset(inset: f64, height: f64) -> Self {
+        Self::Boxes(vec![Aabb::from_coords(
+            inset,
+            0.0,
+            inset,
+            1.0 - inset,
+            height,
+            1.0 - inset,
+        )])
+    }
+
+    /// Collision boxes of this shape.
+    pub fn boxes(&self) -> &[Aabb] {
+        match self {
+            Self::Empty => &[],
+            Self::Full => &Self::FULL_BOX,
+            Self::Boxes(boxes) => boxes,
+        }
+    }
+
+    /// Whether this shape has any collision.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Empty)
+    }
+
+    /// Derive the collision shape for a block from its string identifier.
+    pub fn for_block(string_id: &str) -> Self {
+        let name = string_id.strip_prefix("minecraft:").unwrap_or(string_id);
+
+        const PASSABLE: &[&str] = &[
+            "air",
+            "cave_air",
+            "void_air",
+            "structure_void",
+            "light_block",
+            "water",
+            "flowing_water",
+            "lava",
+            "flowing_lava",
+            "bubble_column",
+            "fire",
+            "soul_fire",
+            "short_grass",
+            "tall_grass",
+            "fern",
+            "large_fern",
+            "deadbush",
+            "dead_bush",
+            "seagrass",
+            "kelp",
+            "reeds",
+            "sugar_cane",
+            "vine",
+            "web",
+            "cobweb",
+            "redstone_wire",
+            "tripwire",
+            "tripwire_hook",
+            "lever",
+            "rail",
+            "golden_rail",
+            "detector_rail",
+            "activator_rail",
+            "wheat",
+            "carrots",
+            "potatoes",
+            "beetroot",
+            "nether_wart",
+            "sweet_berry_bush",
+            "dandelion",
+            "poppy",
+            "blue_orchid",
+            "allium",
+            "azure_bluet",
+            "oxeye_daisy",
+            "cornflower",
+            "lily_of_the_valley",
+            "wither_rose",
+            "sunflower",
+            "lilac",
+            "rose_bush",
+            "peony",
+            "torchflower",
+            "pink_petals",
+            "brown_mushroom",
+            "red_mushroom",
+            "crimson_fungus",
+            "warped_fungus",
+            "crimson_roots",
+            "warped_roots",
+            "nether_sprouts",
+            "hanging_roots",
+            "glow_lichen",
+            "sculk_vein",
+            "spore_blossom",
+            "small_dripleaf_block",
+            "cave_vines",
+            "weeping_vines",
+            "twisting_vines",
+            "powder_snow",
+        ];
+        if PASSABLE.contains(&name)
+            || name.ends_with("_sapling")
+            || name.ends_with("torch")
+            || name.ends_with("_tulip")
+            || name.ends_with("_button")
+            || name.ends_with("_pressure_plate")
+            || name.ends_with("_sign")
+            || name.ends_with("_banner")
+            || name.ends_with("_coral")
+            || name.ends_with("_coral_fan")
+            || name.ends_with("_coral_wall_fan")
+            || name.ends_with("_vines_body")
+            || name.ends_with("_vines_head")
+        {
+            return Self::Empty;
+        }
+
+        if name.ends_with("_slab") && !name.contains("double") {
+            return Self::slab(0.5);
+        }
+        if name.ends_with("carpet") {
+            return Self::slab(0.0625);
+        }
+        if name.ends_with("_trapdoor") {
+            return Self::slab(0.1875);
+        }
+        if name.ends_with("_bed") || name == "bed" {
+            return Self::slab(0.5625);
+        }
+        if name.ends_with("_fence")
+            || name.ends_with("_fence_gate")
+            || name == "fence_gate"
+            || name.ends_with("_wall")
+        {
+            return Self::slab(1.5);
+        }
+
+        match name {
+            "snow_layer" => Self::slab(0.125),
+            "daylight_detector" | "daylight_detector_inverted" => Self::slab(0.375),
+            "enchanting_table" => Self::slab(0.75),
+            "stonecutter_block" | "stonecutter" => Self::slab(0.5625),
+            "soul_sand" => Self::slab(0.875),
+            "farmland" | "grass_path" | "dirt_path" => Self::slab(0.9375),
+            "chest" | "trapped_chest" | "ender_chest" => Self::inset(0.0625, 0.875),
+            "cactus" => Self::inset(0.0625, 1.0),
+            "lantern" | "soul_lantern" => Self::inset(0.3125, 0.5625),
+            "flower_pot" => Self::inset(0.3125, 0.375),
+            _ => Self::Full,
+        }
+    }
+}
+
+/// Whether a block can hold a liquid in its second storage layer.
+///
+/// Derived from the identifier like [`CollisionShape::for_block`].
+pub fn is_waterloggable(string_id: &str) -> bool {
+    let name = string_id.strip_prefix("minecraft:").unwrap_or(string_id);
+    const SUFFIXES: &[&str] = &[
+        "_slab",
+        "_stairs",
+        "_fence",
+        "_fence_gate",
+        "_wall",
+        "_trapdoor",
+        "_door",
+        "_sign",
+        "_hanging_sign",
+        "_pane",
+        "_coral",
+        "_coral_fan",
+        "_coral_wall_fan",
+        "lantern",
+        "chain",
+        "candle",
+    ];
+    const NAMES: &[&str] = &[
+        "glass_pane",
+        "iron_bars",
+        "ladder",
+        "chest",
+        "trapped_chest",
+        "ender_chest",
+        "scaffolding",
+        "conduit",
+        "sea_pickle",
+        "lightning_rod",
+        "pointed_dripstone",
+        "amethyst_cluster",
+        "hopper",
+        "barrier",
+        "mangrove_roots",
+        "big_dripleaf",
+        "small_dripleaf_block",
+        "heavy_core",
+    ];
+    !name.contains("double_slab")
+        && (NAMES.contains(&name) || SUFFIXES.iter().any(|suffix| name.ends_with(suffix)))
+}
+
+/// Waterloggable flags indexed by block runtime (state) ID.
+static WATERLOGGABLE: LazyLock<Vec<bool>> = LazyLock::new(|| {
+    use jolyne::valentine::blocks::BLOCKS;
+
+    let max_state = BLOCKS
+        .iter()
+        .map(|block| block.max_state_id())
+        .max()
+        .unwrap_or(0);
+    let mut table = vec![false; max_state as usize + 1];
+    for block in BLOCKS.iter() {
+        if is_waterloggable(block.string_id()) {
+            for state in block.min_state_id()..=block.max_state_id() {
+                table[state as usize] = true;
+            }
+        }
+    }
+    table
+});
+
+/// Check whether a block runtime ID can be waterlogged.
+#[inline]
+pub fn waterloggable(runtime_id: u32) -> bool {
+    WATERLOGGABLE
+        .get(runtime_id as usize)
+        .copied()
+        .unwrap_or(false)
+}
+
+/// Block string IDs indexed by block runtime (state) ID.
+static STRING_IDS: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
+    use jolyne::valentine::blocks::BLOCKS;
+
+    let max_state = BLOCKS
+        .iter()
+        .map(|block| block.max_state_id())
+        .max()
+        .unwrap_or(0);
+    let mut table = vec![""; max_state as usize + 1];
+    for block in BLOCKS.iter() {
+        for state in block.min_state_id()..=block.max_state_id() {
+            table[state as usize] = block.string_id();
+        }
+    }
+    table
+});
+
+/// Get the string identifier (e.g. `"minecraft:chest"`) of a block runtime ID.
+#[inline]
+pub fn string_id(runtime_id: u32) -> Option<&'static str> {
+    STRING_IDS
+        .get(runtime_id as usize)
+        .copied()
+        .filter(|id| !id.is_empty())
+}
+
+/// Collision shapes indexed by block runtime (state) ID.
+static COLLISION_SHAPES: LazyLock<Vec<CollisionShape>> = LazyLock::new(|| {
+    use jolyne::valentine::blocks::BLOCKS;
+
+    let max_state = BLOCKS
+        .iter()
+        .map(|block| block.max_state_id())
+        .max()
+        .unwrap_or(0);
+    let mut shapes = vec![CollisionShape::Empty; max_state as usize + 1];
+    for block in BLOCKS.iter() {
+        let shape = CollisionShape::for_block(block.string_id());
+        for state in block.min_state_id()..=block.max_state_id() {
+            shapes[state as usize] = shape.clone();
+        }
+    }
+    shapes
+});
+
+/// Get the collision shape for a block runtime ID.
+///
+/// Unknown runtime IDs are treated as full cubes.
+#[inline]
+pub fn collision_shape(runtime_id: u32) -> &'static CollisionShape {
+    static FULL: CollisionShape = CollisionShape::Full;
+    COLLISION_SHAPES.get(runtime_id as usize).unwrap_or(&FULL)
+}
 
 /// Runtime block entry in the registry.
 #[derive(Debug, Clone)]
@@ -22,6 +334,10 @@ pub struct BlockEntry {
     pub max_state_id: u32,
     /// Default state ID for this block.
     pub default_state_id: u32,
+    /// Collision geometry used by entity physics.
+    pub collision: CollisionShape,
+    /// Whether the block can be waterlogged.
+    pub waterloggable: bool,
 }
 
 impl RegistryEntry for BlockEntry {
@@ -53,6 +369,8 @@ impl BlockRegistry {
                 min_state_id: block.min_state_id(),
                 max_state_id: block.max_state_id(),
                 default_state_id: block.default_state_id(),
+                collision: CollisionShape::for_block(block.string_id()),
+                waterloggable: is_waterloggable(block.string_id()),
             };
             let _ = self.register(entry);
         }
@@ -93,3 +411,38 @@ impl BlockRegistry {
         properties
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collision_shapes() {
+        assert!(CollisionShape::for_block("minecraft:air").is_empty());
+        assert!(CollisionShape::for_block("minecraft:water").is_empty());
+        assert!(CollisionShape::for_block("minecraft:oak_sapling").is_empty());
+        assert_eq!(
+            CollisionShape::for_block("minecraft:stone"),
+            CollisionShape::Full
+        );
+        let height = |name| CollisionShape::for_block(name).boxes()[0].max.y;
+        assert_eq!(height("minecraft:oak_slab"), 0.5);
+        assert_eq!(height("minecraft:oak_fence"), 1.5);
+    }
+
+    #[test]
+    fn test_waterloggable() {
+        assert!(is_waterloggable("minecraft:oak_slab"));
+        assert!(is_waterloggable("minecraft:chest"));
+        assert!(!is_waterloggable("minecraft:stone"));
+        assert!(!is_waterloggable("minecraft:oak_double_slab"));
+    }
+
+    #[test]
+    fn test_collision_shape_by_runtime_id() {
+        use crate::world::chunk::blocks;
+
+        assert!(collision_shape(*blocks::AIR).is_empty());
+        assert_eq!(*collision_shape(*blocks::STONE), CollisionShape::Full);
+    }
+}
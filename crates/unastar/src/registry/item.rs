@@ -1,6 +1,9 @@
 //! Item registry for runtime item management.
 
+use std::collections::HashMap;
+
 use super::{Registry, RegistryEntry};
+use crate::item::Food;
 
 /// Runtime item entry in the registry.
 #[derive(Debug, Clone)]
@@ -13,6 +16,10 @@ pub struct ItemEntry {
     pub name: String,
     /// Maximum stack size.
     pub stack_size: u8,
+    /// Melee damage dealt when attacking with this item.
+    pub attack_damage: f32,
+    /// Nutrition and effects if the item can be eaten.
+    pub food: Option<Food>,
 }
 
 impl RegistryEntry for ItemEntry {
@@ -29,8 +36,11 @@ impl RegistryEntry for ItemEntry {
 pub type ItemRegistry = Registry<ItemEntry>;
 
 impl ItemRegistry {
-    /// Load vanilla items from valentine's generated data.
+    /// Load vanilla items from valentine's generated data, with the bundled
+    /// food values.
     pub fn load_vanilla(&mut self) {
+        const FOODS_JSON: &str = include_str!("../data/foods.json");
+
         use jolyne::valentine::items::ITEMS;
 
         for item in ITEMS.iter() {
@@ -39,10 +49,30 @@ impl ItemRegistry {
                 string_id: item.string_id().to_string(),
                 name: item.name().to_string(),
                 stack_size: item.stack_size(),
+                attack_damage: crate::item::attack_damage(item.string_id()),
+                food: None,
             };
             // Ignore conflicts for vanilla loading
             let _ = self.register(entry);
         }
+        if let Err(e) = self.load_foods(FOODS_JSON) {
+            tracing::warn!("Failed to load food values: {}. Eating is disabled.", e);
+        }
+    }
+
+    /// Load food values from a JSON map of item id to food, replacing those
+    /// of the items it names. Unknown items are skipped.
+    pub fn load_foods(&mut self, json: &str) -> Result<(), serde_json::Error> {
+        let foods: HashMap<String, Food> = serde_json::from_str(json)?;
+        for (name, food) in foods {
+            let Some(id) = self.get_by_name(&name).map(|entry| entry.id) else {
+                continue;
+            };
+            if let Some(entry) = self.get_mut(id) {
+                entry.food = Some(food);
+            }
+        }
+        Ok(())
     }
 
     /// Convert registry to protocol packet.
@@ -64,3 +94,40 @@ impl ItemRegistry {
         jolyne::valentine::ItemRegistryPacket { itemstates }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_food() {
+        let mut items = ItemRegistry::new();
+        items.load_vanilla();
+        fn food_of(items: &ItemRegistry, name: &str) -> Option<Food> {
+            items.get_by_name(name)?.food.clone()
+        }
+        let food = |name: &str| food_of(&items, name);
+
+        let steak = food("minecraft:cooked_beef").unwrap();
+        assert_eq!((steak.nutrition, steak.saturation), (8, 12.8));
+        assert!(!steak.always_edible && steak.effects.is_empty());
+
+        let stew = food("minecraft:rabbit_stew").unwrap();
+        assert_eq!(stew.remainder.as_deref(), Some("minecraft:bowl"));
+        assert_eq!(food("minecraft:dried_kelp").unwrap().eat_ticks, 16);
+        assert!(food("minecraft:golden_apple").unwrap().always_edible);
+        assert_eq!(food("minecraft:pufferfish").unwrap().effects.len(), 3);
+        assert_eq!(food("minecraft:diamond"), None);
+
+        items
+            .load_foods(r#"{"minecraft:diamond": {"nutrition": 1, "saturation": 0.5}}"#)
+            .unwrap();
+        let diamond = food_of(&items, "minecraft:diamond").unwrap();
+        assert_eq!(diamond.eat_ticks, crate::item::DEFAULT_EAT_TICKS);
+        assert!(
+            items
+                .load_foods(r#"{"minecraft:apple": {"nutrition": 1}}"#)
+                .is_err()
+        );
+    }
+}
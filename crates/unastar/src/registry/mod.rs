@@ -1,18 +1,20 @@
 //! Registry system for runtime-mutable game data.
 //!
-//! Provides extensible registries for items, blocks, entities, and biomes.
+//! Provides extensible registries for items, blocks, entities, biomes and recipes.
 
 pub mod biome;
 pub mod block;
 pub mod creative;
 pub mod entity;
 pub mod item;
+pub mod recipe;
 
 pub use biome::BiomeRegistry;
 pub use block::BlockRegistry;
 pub use creative::CreativeInventoryData;
 pub use entity::EntityRegistry;
 pub use item::ItemRegistry;
+pub use recipe::RecipeRegistry;
 
 use std::fmt::Debug;
 
@@ -0,0 +1,920 @@
+//! Recipe registry for crafting, smelting and stonecutting.
+//!
+//! Loads recipes from the bundled `data/recipes.json` dataset:
+//! - `shaped`: pattern recipes for the 2x2 and 3x3 crafting grids
+//! - `shapeless`: any-arrangement crafting recipes
+//! - `furnace`: smelting recipes, listed per furnace block
+//! - `stonecutter`: single-input stonecutter recipes
+//! - `fuel`: furnace fuels and their burn time in ticks
+//!
+//! Regenerate the recipe tables from a vanilla server's `CraftingData` with
+//! `bds-extractor --recipes src/data/recipes.json`. Tags and fuels are not
+//! sent by the server; the extractor keeps the ones already in the file.
+//!
+//! Ingredients are item IDs (`minecraft:stick`) or item tags prefixed with
+//! `#` (`#minecraft:planks`). Tags are defined in the dataset's `tags` table.
+//!
+//! Crafting and stonecutter recipes are assigned network IDs in load order;
+//! clients reference these in `CraftRecipe` item stack actions.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::{BlockRegistry, ItemRegistry, Registry, RegistryEntry};
+use crate::item::ItemStack;
+
+/// Recipe block for crafting table and inventory grid recipes.
+pub const CRAFTING_TABLE: &str = "crafting_table";
+/// Recipe block for stonecutter recipes.
+pub const STONECUTTER: &str = "stonecutter";
+
+/// Item metadata value matching any damage/data value.
+const ANY_METADATA: i16 = 32767;
+
+/// A single recipe input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ingredient {
+    /// A specific item, regardless of damage.
+    Item(String),
+    /// Any item carrying the tag.
+    Tag(String),
+}
+
+impl Ingredient {
+    /// Parse `minecraft:item` or `#minecraft:tag`.
+    pub fn parse(s: &str) -> Self {
+        match s.strip_prefix('#') {
+            Some(tag) => Self::Tag(tag.to_string()),
+            None => Self::Item(s.to_string()),
+        }
+    }
+}
+
+/// A recipe result.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RecipeOutput {
+    pub item: String,
+    #[serde(default = "default_count")]
+    pub count: u8,
+    #[serde(default)]
+    pub data: i16,
+}
+
+fn default_count() -> u8 {
+    1
+}
+
+impl RecipeOutput {
+    /// The result for `times` crafts.
+    pub fn to_stack(&self, times: u8) -> ItemStack {
+        ItemStack::new(self.item.clone(), self.count.saturating_mul(times.max(1)))
+            .with_damage(self.data)
+    }
+}
+
+/// Recipe shape and inputs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecipeKind {
+    /// Pattern recipe. `input` is row-major, `width * height` cells.
+    Shaped {
+        width: usize,
+        height: usize,
+        input: Vec<Option<Ingredient>>,
+    },
+    /// Each ingredient must be present once, in any slot.
+    Shapeless { input: Vec<Ingredient> },
+    /// Single input cut into the output.
+    Stonecutter { input: Ingredient },
+}
+
+/// A crafting or stonecutter recipe, keyed by network ID.
+#[derive(Debug, Clone)]
+pub struct RecipeEntry {
+    /// Network ID sent in `CraftingData` (1-based).
+    pub network_id: u32,
+    /// Recipe identifier (e.g., "minecraft:chest").
+    pub recipe_id: String,
+    /// Block the recipe is crafted in ("crafting_table", "stonecutter").
+    pub block: String,
+    pub kind: RecipeKind,
+    pub output: RecipeOutput,
+}
+
+impl RegistryEntry for RecipeEntry {
+    fn id(&self) -> u32 {
+        self.network_id
+    }
+
+    fn string_id(&self) -> &str {
+        &self.recipe_id
+    }
+}
+
+impl RecipeEntry {
+    /// Number of input items consumed per craft.
+    pub fn input_count(&self) -> usize {
+        match &self.kind {
+            RecipeKind::Shaped { input, .. } => input.iter().flatten().count(),
+            RecipeKind::Shapeless { input } => input.len(),
+            RecipeKind::Stonecutter { .. } => 1,
+        }
+    }
+
+    /// Every ingredient consumed per craft, one entry per item.
+    pub fn ingredients(&self) -> Vec<&Ingredient> {
+        match &self.kind {
+            RecipeKind::Shaped { input, .. } => input.iter().flatten().collect(),
+            RecipeKind::Shapeless { input } => input.iter().collect(),
+            RecipeKind::Stonecutter { input } => vec![input],
+        }
+    }
+
+    /// Whether the recipe needs a 3x3 grid (a crafting table).
+    pub fn needs_crafting_table(&self) -> bool {
+        match &self.kind {
+            RecipeKind::Shaped { width, height, .. } => *width > 2 || *height > 2,
+            RecipeKind::Shapeless { input } => input.len() > 4,
+            RecipeKind::Stonecutter { .. } => false,
+        }
+    }
+}
+
+/// A smelting recipe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FurnaceRecipe {
+    pub input: String,
+    pub output: RecipeOutput,
+    /// Furnace block ("furnace", "blast_furnace", "smoker").
+    pub block: String,
+}
+
+/// Registry of all recipes.
+#[derive(Debug, Clone, Default)]
+pub struct RecipeRegistry {
+    recipes: Registry<RecipeEntry>,
+    furnace: Vec<FurnaceRecipe>,
+    /// Burn time in ticks by fuel item ID.
+    fuel: HashMap<String, i16>,
+    tags: HashMap<String, Vec<String>>,
+}
+
+// JSON dataset layout.
+
+#[derive(Deserialize)]
+struct RecipeData {
+    #[serde(default)]
+    tags: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    shaped: Vec<ShapedDef>,
+    #[serde(default)]
+    shapeless: Vec<ShapelessDef>,
+    #[serde(default)]
+    furnace: Vec<FurnaceDef>,
+    #[serde(default)]
+    stonecutter: Vec<StonecutterDef>,
+    #[serde(default)]
+    fuel: Vec<FuelDef>,
+}
+
+#[derive(Deserialize)]
+struct ShapedDef {
+    id: String,
+    block: String,
+    pattern: Vec<String>,
+    key: HashMap<char, String>,
+    output: RecipeOutput,
+}
+
+#[derive(Deserialize)]
+struct ShapelessDef {
+    id: String,
+    block: String,
+    input: Vec<String>,
+    output: RecipeOutput,
+}
+
+#[derive(Deserialize)]
+struct FurnaceDef {
+    input: String,
+    output: RecipeOutput,
+    blocks: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct StonecutterDef {
+    id: String,
+    input: String,
+    output: RecipeOutput,
+}
+
+#[derive(Deserialize)]
+struct FuelDef {
+    input: String,
+    burn_time: i16,
+}
+
+impl RecipeRegistry {
+    /// Create an empty recipe registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the bundled vanilla recipe dataset.
+    pub fn load_vanilla(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        const RECIPES_JSON: &str = include_str!("../data/recipes.json");
+        self.load_json(RECIPES_JSON)
+    }
+
+    /// Load recipes from a JSON dataset, appending to existing recipes.
+    pub fn load_json(&mut self, json: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let data: RecipeData = serde_json::from_str(json)?;
+        self.tags.extend(data.tags);
+
+        for def in data.shaped {
+            let height = def.pattern.len();
+            let width = def
+                .pattern
+                .iter()
+                .map(|row| row.chars().count())
+                .max()
+                .unwrap_or(0);
+            let mut input = Vec::with_capacity(width * height);
+            for row in &def.pattern {
+                let mut chars = row.chars();
+                for _ in 0..width {
+                    input.push(match chars.next() {
+                        Some(' ') | None => None,
+                        Some(c) => {
+                            let key = def
+                                .key
+                                .get(&c)
+                                .ok_or_else(|| format!("recipe {}: unknown key '{}'", def.id, c))?;
+                            Some(Ingredient::parse(key))
+                        }
+                    });
+                }
+            }
+            self.register_recipe(
+                def.id,
+                def.block,
+                RecipeKind::Shaped {
+                    width,
+                    height,
+                    input,
+                },
+                def.output,
+            )?;
+        }
+
+        for def in data.shapeless {
+            let input = def.input.iter().map(|s| Ingredient::parse(s)).collect();
+            self.register_recipe(
+                def.id,
+                def.block,
+                RecipeKind::Shapeless { input },
+                def.output,
+            )?;
+        }
+
+        for def in data.stonecutter {
+            let input = Ingredient::parse(&def.input);
+            self.register_recipe(
+                def.id,
+                STONECUTTER.to_string(),
+                RecipeKind::Stonecutter { input },
+                def.output,
+            )?;
+        }
+
+        for def in data.furnace {
+            // Tag inputs expand to one recipe per item; the protocol takes item IDs
+            for input in self.expand(&def.input) {
+                for block in &def.blocks {
+                    self.furnace.push(FurnaceRecipe {
+                        input: input.clone(),
+                        output: def.output.clone(),
+                        block: block.clone(),
+                    });
+                }
+            }
+        }
+
+        for def in data.fuel {
+            for input in self.expand(&def.input) {
+                self.fuel.insert(input, def.burn_time);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Item IDs matching an item ID or `#tag` string.
+    fn expand(&self, input: &str) -> Vec<String> {
+        match Ingredient::parse(input) {
+            Ingredient::Item(item) => vec![item],
+            Ingredient::Tag(tag) => self.tags.get(&tag).cloned().unwrap_or_default(),
+        }
+    }
+
+    /// Register a crafting or stonecutter recipe under the next network ID.
+    pub fn register_recipe(
+        &mut self,
+        recipe_id: String,
+        block: String,
+        kind: RecipeKind,
+        output: RecipeOutput,
+    ) -> Result<u32, super::RegistryError> {
+        let network_id = self.recipes.len() as u32 + 1;
+        self.recipes.register(RecipeEntry {
+            network_id,
+            recipe_id,
+            block,
+            kind,
+            output,
+        })?;
+        Ok(network_id)
+    }
+
+    /// Get a recipe by network ID.
+    pub fn get(&self, network_id: u32) -> Option<&RecipeEntry> {
+        self.recipes.get(network_id)
+    }
+
+    /// Iterate crafting and stonecutter recipes.
+    pub fn iter(&self) -> impl Iterator<Item = &RecipeEntry> {
+        self.recipes.iter()
+    }
+
+    /// Iterate smelting recipes.
+    pub fn furnace_recipes(&self) -> &[FurnaceRecipe] {
+        &self.furnace
+    }
+
+    /// Number of crafting and stonecutter recipes.
+    pub fn len(&self) -> usize {
+        self.recipes.len()
+    }
+
+    /// Check if no recipes are registered.
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty() && self.furnace.is_empty()
+    }
+
+    /// Smelting result for an item in the given furnace block.
+    pub fn furnace_output(&self, block: &str, input: &ItemStack) -> Option<&RecipeOutput> {
+        self.furnace
+            .iter()
+            .find(|r| r.block == block && r.input == input.item_id)
+            .map(|r| &r.output)
+    }
+
+    /// Burn time in ticks of a furnace fuel, or `None` if the item isn't fuel.
+    pub fn fuel_duration(&self, fuel: &ItemStack) -> Option<i16> {
+        if fuel.is_empty() {
+            return None;
+        }
+        self.fuel.get(&fuel.item_id).copied()
+    }
+
+    /// Check whether an item satisfies an ingredient.
+    pub fn ingredient_matches(&self, ingredient: &Ingredient, item: &ItemStack) -> bool {
+        if item.is_empty() {
+            return false;
+        }
+        match ingredient {
+            Ingredient::Item(id) => item.item_id == *id,
+            Ingredient::Tag(tag) => self
+                .tags
+                .get(tag)
+                .is_some_and(|items| items.contains(&item.item_id)),
+        }
+    }
+
+    /// Check whether a crafting grid holds a recipe's inputs.
+    ///
+    /// `grid` is a row-major square grid (2x2 or 3x3). Shaped recipes may sit
+    /// anywhere in the grid and may be mirrored horizontally.
+    pub fn matches_grid(&self, recipe: &RecipeEntry, grid: &[ItemStack]) -> bool {
+        let size = grid.len().isqrt();
+        if size * size != grid.len() {
+            return false;
+        }
+
+        match &recipe.kind {
+            RecipeKind::Shaped {
+                width,
+                height,
+                input,
+            } => {
+                let Some((min_x, min_y, max_x, max_y)) = occupied_bounds(grid, size) else {
+                    return false;
+                };
+                if max_x - min_x + 1 != *width || max_y - min_y + 1 != *height {
+                    return false;
+                }
+                let cell = |x: usize, y: usize| &grid[(min_y + y) * size + min_x + x];
+                let matches = |mirrored: bool| {
+                    (0..*height).all(|y| {
+                        (0..*width).all(|x| {
+                            let ix = if mirrored { width - 1 - x } else { x };
+                            match &input[y * width + ix] {
+                                Some(ingredient) => self.ingredient_matches(ingredient, cell(x, y)),
+                                None => cell(x, y).is_empty(),
+                            }
+                        })
+                    })
+                };
+                matches(false) || matches(true)
+            }
+            RecipeKind::Shapeless { input } => {
+                let items: Vec<&ItemStack> = grid.iter().filter(|i| !i.is_empty()).collect();
+                items.len() == input.len()
+                    && self.assign_shapeless(input, &items, &mut vec![false; items.len()])
+            }
+            RecipeKind::Stonecutter { input } => {
+                let items: Vec<&ItemStack> = grid.iter().filter(|i| !i.is_empty()).collect();
+                items.len() == 1 && self.ingredient_matches(input, items[0])
+            }
+        }
+    }
+
+    /// Check that consumed items pay exactly for `times` crafts of a recipe.
+    ///
+    /// Each ingredient must be covered by `times` matching items and nothing
+    /// else may be consumed.
+    pub fn matches_consumed(
+        &self,
+        recipe: &RecipeEntry,
+        consumed: &[ItemStack],
+        times: u8,
+    ) -> bool {
+        let mut remaining: Vec<(&ItemStack, usize)> = consumed
+            .iter()
+            .filter(|item| !item.is_empty())
+            .map(|item| (item, item.count as usize))
+            .collect();
+
+        // Exact items first so a tag can't claim items an exact ingredient needs.
+        let mut ingredients = recipe.ingredients();
+        ingredients.sort_by_key(|ingredient| matches!(ingredient, Ingredient::Tag(_)));
+
+        for ingredient in ingredients {
+            let mut needed = times as usize;
+            for (item, left) in remaining.iter_mut() {
+                if needed == 0 {
+                    break;
+                }
+                if *left > 0 && self.ingredient_matches(ingredient, item) {
+                    let take = needed.min(*left);
+                    *left -= take;
+                    needed -= take;
+                }
+            }
+            if needed > 0 {
+                return false;
+            }
+        }
+        remaining.iter().all(|(_, left)| *left == 0)
+    }
+
+    /// Assign each ingredient a distinct grid item (backtracking; grids are tiny).
+    fn assign_shapeless(
+        &self,
+        input: &[Ingredient],
+        items: &[&ItemStack],
+        used: &mut [bool],
+    ) -> bool {
+        let Some((ingredient, rest)) = input.split_first() else {
+            return true;
+        };
+        for (i, item) in items.iter().enumerate() {
+            if !used[i] && self.ingredient_matches(ingredient, item) {
+                used[i] = true;
+                if self.assign_shapeless(rest, items, used) {
+                    return true;
+                }
+                used[i] = false;
+            }
+        }
+        false
+    }
+
+    /// Build the `CraftingData` packet sent to joining players.
+    pub fn to_packet(
+        &self,
+        items: &ItemRegistry,
+        blocks: &BlockRegistry,
+    ) -> jolyne::valentine::CraftingDataPacket {
+        use jolyne::valentine::types::{
+            ItemLegacy, ItemLegacyContent, ItemLegacyContentExtra, RecipeIngredient,
+            RecipeIngredientContent, RecipeIngredientContentIntIdMeta,
+            RecipeIngredientContentItemTag, RecipeIngredientType, RecipeUnlockingRequirement,
+            RecipeUnlockingRequirementContext, RecipesItem, RecipesItemRecipe,
+            RecipesItemRecipeFurnace, RecipesItemRecipeShaped, RecipesItemRecipeShapeless,
+            RecipesItemType,
+        };
+
+        let item_legacy = |output: &RecipeOutput| -> Option<ItemLegacy> {
+            let entry = items.get_by_name(&output.item)?;
+            Some(ItemLegacy {
+                network_id: entry.id as i32,
+                content: Some(Box::new(ItemLegacyContent {
+                    count: output.count as u16,
+                    metadata: output.data as i32,
+                    block_runtime_id: blocks
+                        .get_by_name(&output.item)
+                        .map_or(0, |b| b.default_state_id as i32),
+                    extra: ItemLegacyContentExtra::default(),
+                })),
+            })
+        };
+        let ingredient = |ingredient: Option<&Ingredient>| -> RecipeIngredient {
+            match ingredient {
+                Some(Ingredient::Item(id)) => match items.get_by_name(id) {
+                    Some(entry) => RecipeIngredient {
+                        type_: RecipeIngredientType::IntIdMeta,
+                        content: Some(RecipeIngredientContent::IntIdMeta(Box::new(
+                            RecipeIngredientContentIntIdMeta {
+                                network_id: entry.id as i16,
+                                metadata: Some(ANY_METADATA),
+                            },
+                        ))),
+                        count: 1,
+                    },
+                    None => RecipeIngredient::default(),
+                },
+                Some(Ingredient::Tag(tag)) => RecipeIngredient {
+                    type_: RecipeIngredientType::ItemTag,
+                    content: Some(RecipeIngredientContent::ItemTag(
+                        RecipeIngredientContentItemTag { tag: tag.clone() },
+                    )),
+                    count: 1,
+                },
+                None => RecipeIngredient {
+                    type_: RecipeIngredientType::Invalid,
+                    content: None,
+                    count: 0,
+                },
+            }
+        };
+        let unlocking_requirement = || RecipeUnlockingRequirement {
+            context: RecipeUnlockingRequirementContext::AlwaysUnlocked,
+            ingredients: None,
+        };
+
+        let mut recipes = Vec::with_capacity(self.recipes.len() + self.furnace.len());
+        for recipe in self.recipes.iter() {
+            let Some(output) = item_legacy(&recipe.output) else {
+                continue;
+            };
+            let uuid = recipe_uuid(&recipe.recipe_id);
+            let network_id = recipe.network_id as i32;
+
+            let (type_, recipe) = match &recipe.kind {
+                RecipeKind::Shaped {
+                    width,
+                    height,
+                    input,
+                } => (
+                    RecipesItemType::Shaped,
+                    RecipesItemRecipe::Shaped(Box::new(RecipesItemRecipeShaped {
+                        recipe_id: recipe.recipe_id.clone(),
+                        width: *width as i32,
+                        height: *height as i32,
+                        input: input
+                            .chunks(*width)
+                            .map(|row| row.iter().map(|i| ingredient(i.as_ref())).collect())
+                            .collect(),
+                        output: vec![output],
+                        uuid,
+                        block: recipe.block.clone(),
+                        priority: 0,
+                        assume_symmetry: true,
+                        unlocking_requirement: unlocking_requirement(),
+                        network_id,
+                    })),
+                ),
+                RecipeKind::Shapeless { input } => (
+                    RecipesItemType::Shapeless,
+                    RecipesItemRecipe::Shapeless(Box::new(RecipesItemRecipeShapeless {
+                        recipe_id: recipe.recipe_id.clone(),
+                        input: input.iter().map(|i| ingredient(Some(i))).collect(),
+                        output: vec![output],
+                        uuid,
+                        block: recipe.block.clone(),
+                        priority: 0,
+                        unlocking_requirement: unlocking_requirement(),
+                        network_id,
+                    })),
+                ),
+                RecipeKind::Stonecutter { input } => (
+                    RecipesItemType::Shapeless,
+                    RecipesItemRecipe::Shapeless(Box::new(RecipesItemRecipeShapeless {
+                        recipe_id: recipe.recipe_id.clone(),
+                        input: vec![ingredient(Some(input))],
+                        output: vec![output],
+                        uuid,
+                        block: recipe.block.clone(),
+                        priority: 0,
+                        unlocking_requirement: unlocking_requirement(),
+                        network_id,
+                    })),
+                ),
+            };
+            recipes.push(RecipesItem {
+                type_,
+                recipe: Some(recipe),
+            });
+        }
+
+        for recipe in &self.furnace {
+            let (Some(input), Some(output)) = (
+                items.get_by_name(&recipe.input),
+                item_legacy(&recipe.output),
+            ) else {
+                continue;
+            };
+            recipes.push(RecipesItem {
+                type_: RecipesItemType::Furnace,
+                recipe: Some(RecipesItemRecipe::Furnace(Box::new(
+                    RecipesItemRecipeFurnace {
+                        input_id: input.id as i32,
+                        output,
+                        block: recipe.block.clone(),
+                    },
+                ))),
+            });
+        }
+
+        jolyne::valentine::CraftingDataPacket {
+            recipes,
+            potion_type_recipes: Vec::new(),
+            potion_container_recipes: Vec::new(),
+            material_reducers: Vec::new(),
+            clear_recipes: true,
+        }
+    }
+}
+
+/// Bounding box of non-empty cells in a square grid: (min_x, min_y, max_x, max_y).
+fn occupied_bounds(grid: &[ItemStack], size: usize) -> Option<(usize, usize, usize, usize)> {
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    for (i, item) in grid.iter().enumerate() {
+        if item.is_empty() {
+            continue;
+        }
+        let (x, y) = (i % size, i / size);
+        bounds = Some(match bounds {
+            None => (x, y, x, y),
+            Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+        });
+    }
+    bounds
+}
+
+/// Stable recipe UUID derived from the recipe ID (FNV-1a).
+fn recipe_uuid(recipe_id: &str) -> uuid::Uuid {
+    let mut hash: u128 = 0x6c62272e07bb014262b821756295c58d;
+    for byte in recipe_id.bytes() {
+        hash ^= byte as u128;
+        hash = hash.wrapping_mul(0x0000000001000000000000000000013B);
+    }
+    uuid::Uuid::from_u128(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vanilla() -> RecipeRegistry {
+        let mut registry = RecipeRegistry::new();
+        registry.load_vanilla().expect("Failed to load recipes");
+        registry
+    }
+
+    fn find<'a>(registry: &'a RecipeRegistry, id: &str) -> &'a RecipeEntry {
+        registry.iter().find(|r| r.recipe_id == id).unwrap()
+    }
+
+    fn grid(cells: &[&str]) -> Vec<ItemStack> {
+        cells
+            .iter()
+            .map(|id| {
+                if id.is_empty() {
+                    ItemStack::empty()
+                } else {
+                    ItemStack::new(*id, 1)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_load_vanilla_recipes() {
+        let registry = vanilla();
+        assert!(!registry.is_empty());
+
+        // Every item referenced by the dataset exists in the item registry
+        let mut items = ItemRegistry::new();
+        items.load_vanilla();
+        for recipe in registry.iter() {
+            assert!(
+                items.get_by_name(&recipe.output.item).is_some(),
+                "unknown output {}",
+                recipe.output.item
+            );
+            for ingredient in recipe.ingredients() {
+                if let Ingredient::Item(item) = ingredient {
+                    assert!(items.get_by_name(item).is_some(), "unknown input {item}");
+                }
+            }
+        }
+        for recipe in registry.furnace_recipes() {
+            assert!(
+                items.get_by_name(&recipe.input).is_some(),
+                "{}",
+                recipe.input
+            );
+        }
+
+        // Network IDs are dense and 1-based
+        for (i, recipe) in registry.iter().enumerate() {
+            assert_eq!(recipe.network_id as usize, i + 1);
+        }
+    }
+
+    #[test]
+    fn test_shaped_matching() {
+        let registry = vanilla();
+        let table = find(&registry, "minecraft:crafting_table");
+
+        // 2x2 in the inventory grid, with mixed plank types via the tag
+        let planks = grid(&[
+            "minecraft:oak_planks",
+            "minecraft:birch_planks",
+            "minecraft:oak_planks",
+            "minecraft:oak_planks",
+        ]);
+        assert!(registry.matches_grid(table, &planks));
+
+        // Offset inside a 3x3 grid
+        let offset = grid(&[
+            "",
+            "",
+            "",
+            "",
+            "minecraft:oak_planks",
+            "minecraft:oak_planks",
+            "",
+            "minecraft:oak_planks",
+            "minecraft:oak_planks",
+        ]);
+        assert!(registry.matches_grid(table, &offset));
+
+        // Missing a plank
+        let missing = grid(&[
+            "minecraft:oak_planks",
+            "",
+            "minecraft:oak_planks",
+            "minecraft:oak_planks",
+        ]);
+        assert!(!registry.matches_grid(table, &missing));
+
+        // Mirrored axe
+        let axe = find(&registry, "minecraft:iron_axe");
+        let mirrored = grid(&[
+            "minecraft:iron_ingot",
+            "minecraft:iron_ingot",
+            "",
+            "minecraft:stick",
+            "minecraft:iron_ingot",
+            "",
+            "minecraft:stick",
+            "",
+            "",
+        ]);
+        assert!(registry.matches_grid(axe, &mirrored));
+        assert!(axe.needs_crafting_table());
+        assert!(!table.needs_crafting_table());
+    }
+
+    #[test]
+    fn test_shapeless_matching() {
+        let registry = vanilla();
+        let planks = find(&registry, "minecraft:oak_planks_from_log");
+        assert!(registry.matches_grid(planks, &grid(&["", "", "minecraft:oak_log", ""])));
+        assert!(!registry.matches_grid(planks, &grid(&["", "", "minecraft:birch_log", ""])));
+        assert!(!registry.matches_grid(
+            planks,
+            &grid(&["minecraft:oak_log", "", "minecraft:oak_log", ""])
+        ));
+        assert_eq!(planks.output.to_stack(2).count, 8);
+    }
+
+    #[test]
+    fn test_common_recipes() {
+        let registry = vanilla();
+
+        // Beds take the colour of their wool as data value
+        let bed = find(&registry, "minecraft:bed_red");
+        let wool = "minecraft:red_wool";
+        let planks = "minecraft:spruce_planks";
+        assert!(registry.matches_grid(
+            bed,
+            &grid(&[wool, wool, wool, planks, planks, planks, "", "", ""])
+        ));
+        assert_eq!(bed.output.item, "minecraft:bed");
+        assert_eq!(bed.output.to_stack(1).damage, 14);
+
+        let bucket = find(&registry, "minecraft:bucket");
+        let iron = "minecraft:iron_ingot";
+        assert!(registry.matches_grid(bucket, &grid(&[iron, "", iron, "", iron, "", "", "", ""])));
+
+        let boat = find(&registry, "minecraft:birch_boat");
+        let birch = "minecraft:birch_planks";
+        assert!(registry.matches_grid(
+            boat,
+            &grid(&["", "", "", birch, "", birch, birch, birch, birch])
+        ));
+        assert!(!registry.matches_grid(
+            boat,
+            &grid(&["", "", "", planks, "", planks, planks, planks, planks])
+        ));
+
+        let sand = ItemStack::new("minecraft:sand", 1);
+        assert_eq!(
+            registry.furnace_output("furnace", &sand).unwrap().item,
+            "minecraft:glass"
+        );
+        let pane = find(&registry, "minecraft:glass_pane");
+        assert_eq!(pane.output.count, 16);
+    }
+
+    #[test]
+    fn test_consumed_matching() {
+        let registry = vanilla();
+        let table = find(&registry, "minecraft:crafting_table");
+        let consumed = [
+            ItemStack::new("minecraft:oak_planks", 6),
+            ItemStack::new("minecraft:birch_planks", 2),
+        ];
+        assert!(registry.matches_consumed(table, &consumed, 2));
+        // Paying for two crafts but only crafting one
+        assert!(!registry.matches_consumed(table, &consumed, 1));
+        // Short one plank
+        assert!(!registry.matches_consumed(table, &consumed[..1], 2));
+        // Wrong item
+        let dirt = [ItemStack::new("minecraft:dirt", 4)];
+        assert!(!registry.matches_consumed(table, &dirt, 1));
+    }
+
+    #[test]
+    fn test_furnace_output() {
+        let registry = vanilla();
+        let raw_iron = ItemStack::new("minecraft:raw_iron", 1);
+        assert_eq!(
+            registry.furnace_output("furnace", &raw_iron).unwrap().item,
+            "minecraft:iron_ingot"
+        );
+        assert!(
+            registry
+                .furnace_output("blast_furnace", &raw_iron)
+                .is_some()
+        );
+        assert!(registry.furnace_output("smoker", &raw_iron).is_none());
+
+        // Tag inputs expand to every tagged item
+        let log = ItemStack::new("minecraft:stripped_cherry_log", 1);
+        assert_eq!(
+            registry.furnace_output("furnace", &log).unwrap().item,
+            "minecraft:charcoal"
+        );
+    }
+
+    #[test]
+    fn test_fuel_duration() {
+        let registry = vanilla();
+        let fuel = |item: &str| registry.fuel_duration(&ItemStack::new(item, 1));
+        assert_eq!(fuel("minecraft:coal"), Some(1600));
+        // Tag fuels expand to every tagged item
+        assert_eq!(fuel("minecraft:cherry_planks"), Some(300));
+        assert_eq!(fuel("minecraft:dirt"), None);
+        assert_eq!(registry.fuel_duration(&ItemStack::empty()), None);
+    }
+
+    #[test]
+    fn test_crafting_data_packet() {
+        let registry = vanilla();
+        let mut items = ItemRegistry::new();
+        items.load_vanilla();
+        let mut blocks = BlockRegistry::new();
+        blocks.load_vanilla();
+
+        let packet = registry.to_packet(&items, &blocks);
+        assert_eq!(
+            packet.recipes.len(),
+            registry.len() + registry.furnace_recipes().len()
+        );
+        assert!(packet.clear_recipes);
+    }
+}
@@ -7,9 +7,8 @@
 
 use bevy_ecs::prelude::*;
 use jolyne::valentine::types::{
-    AbilityLayers, AbilityLayersType, AbilitySet, CommandPermissionLevel, DeviceOs,
-    EntityProperties, GameMode as ProtocolGameMode, Item, Links, MetadataDictionary,
-    PermissionLevel, Vec3F,
+    AbilityLayers, AbilityLayersType, AbilitySet, DeviceOs, EntityProperties,
+    GameMode as ProtocolGameMode, Item, Links, MetadataDictionary, Vec3F,
 };
 use jolyne::valentine::{AddPlayerPacket, MovePlayerPacketMode, RemoveEntityPacket};
 use jolyne::valentine::{McpePacket, MovePlayerPacket};
@@ -20,37 +19,45 @@ use crate::entity::components::{
     GameMode, LastBroadcastPosition, Player, PlayerName, PlayerSession, PlayerUuid, Position,
     Rotation, RuntimeEntityId, SpatialChunk,
 };
-use crate::world::ecs::{PlayerDespawnedEvent, PlayerSpawnedEvent};
+use crate::permission::{OpLevel, Permissions};
+use crate::world::ecs::{PlayerDespawnedEvent, PlayerSpawnedEvent, WorldId};
 
 /// Spatial hash grid for efficient neighbor lookups.
-/// Maps chunk coordinates to lists of entities in that chunk.
+/// Maps world and chunk coordinates to lists of entities in that chunk.
+///
+/// Holds every entity with a [`SpatialChunk`]: players, mobs and dropped items.
+/// Entities only see neighbours in their own world.
 #[derive(Resource, Default)]
 pub struct EntityGrid {
-    buckets: HashMap<(i32, i32), Vec<Entity>>,
+    buckets: HashMap<(WorldId, i32, i32), Vec<Entity>>,
 }
 
 impl EntityGrid {
     /// Insert an entity into a chunk bucket.
-    pub fn insert(&mut self, chunk: (i32, i32), entity: Entity) {
-        self.buckets.entry(chunk).or_default().push(entity);
+    pub fn insert(&mut self, world: WorldId, chunk: (i32, i32), entity: Entity) {
+        self.buckets
+            .entry((world, chunk.0, chunk.1))
+            .or_default()
+            .push(entity);
     }
 
     /// Remove an entity from a chunk bucket.
-    pub fn remove(&mut self, chunk: (i32, i32), entity: Entity) {
-        if let Some(bucket) = self.buckets.get_mut(&chunk) {
+    pub fn remove(&mut self, world: WorldId, chunk: (i32, i32), entity: Entity) {
+        let key = (world, chunk.0, chunk.1);
+        if let Some(bucket) = self.buckets.get_mut(&key) {
             bucket.retain(|&e| e != entity);
             if bucket.is_empty() {
-                self.buckets.remove(&chunk);
+                self.buckets.remove(&key);
             }
         }
     }
 
     /// Get all entities in neighboring chunks (square grid around center).
-    pub fn get_neighbors(&self, center: (i32, i32), radius: i32) -> Vec<Entity> {
+    pub fn get_neighbors(&self, world: WorldId, center: (i32, i32), radius: i32) -> Vec<Entity> {
         let mut result = Vec::new();
         for dx in -radius..=radius {
             for dz in -radius..=radius {
-                let chunk = (center.0 + dx, center.1 + dz);
+                let chunk = (world, center.0 + dx, center.1 + dz);
                 if let Some(bucket) = self.buckets.get(&chunk) {
                     result.extend(bucket.iter().copied());
                 }
@@ -61,13 +68,14 @@ impl EntityGrid {
 }
 
 /// Builds an AddPlayer packet for broadcasting a player to others.
-fn build_add_player_packet(
+pub(crate) fn build_add_player_packet(
     runtime_id: i64,
     uuid: Uuid,
     name: &str,
     position: &Position,
     rotation: &Rotation,
     game_mode: GameMode,
+    op_level: OpLevel,
 ) -> AddPlayerPacket {
     let protocol_gamemode = match game_mode {
         GameMode::Survival => ProtocolGameMode::Survival,
@@ -95,8 +103,8 @@ fn build_add_player_packet(
         metadata: MetadataDictionary::default(),
         properties: EntityProperties::default(),
         unique_id: runtime_id,
-        permission_level: PermissionLevel::Member,
-        command_permission: CommandPermissionLevel::Normal,
+        permission_level: op_level.permission_level(),
+        command_permission: op_level.command_permission(),
         abilities: vec![AbilityLayers {
             type_: AbilityLayersType::Base,
             allowed: AbilitySet::all(),
@@ -144,7 +152,7 @@ fn build_remove_entity_packet(runtime_id: i64) -> RemoveEntityPacket {
 }
 
 /// Calculate squared distance for movement threshold.
-fn position_distance_sq(last: &LastBroadcastPosition, current: &Position) -> f64 {
+pub(crate) fn position_distance_sq(last: &LastBroadcastPosition, current: &Position) -> f64 {
     let dx = last.x - current.0.x;
     let dy = last.y - current.0.y;
     let dz = last.z - current.0.z;
@@ -152,13 +160,31 @@ fn position_distance_sq(last: &LastBroadcastPosition, current: &Position) -> f64
 }
 
 /// Movement threshold squared (0.1 blocks)
-const MOVEMENT_THRESHOLD_SQ: f64 = 0.01;
+pub(crate) const MOVEMENT_THRESHOLD_SQ: f64 = 0.01;
 
 /// Rotation threshold (degrees)
-const ROTATION_THRESHOLD: f32 = 1.0;
-
-/// System: Broadcast newly spawned players to all existing players,
-/// and send existing players to the new player.
+pub(crate) const ROTATION_THRESHOLD: f32 = 1.0;
+
+/// Players as seen by the other players in their world.
+type ShownPlayers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static RuntimeEntityId,
+        &'static PlayerUuid,
+        &'static PlayerName,
+        &'static Position,
+        &'static Rotation,
+        &'static GameMode,
+        &'static PlayerSession,
+        &'static WorldId,
+    ),
+    With<Player>,
+>;
+
+/// System: Broadcast newly spawned players to the existing players in their
+/// world, and send those players to the new player.
 ///
 /// Reads `PlayerSpawnedEvent` events instead of querying for marker components,
 /// eliminating archetype changes when players spawn.
@@ -168,39 +194,31 @@ const ROTATION_THRESHOLD: f32 = 1.0;
 pub fn broadcast_spawn_system(
     mut events: MessageReader<PlayerSpawnedEvent>,
     // Query for new players by entity from the event
-    new_players: Query<
-        (
-            &RuntimeEntityId,
-            &PlayerUuid,
-            &PlayerName,
-            &Position,
-            &Rotation,
-            &GameMode,
-            &PlayerSession,
-        ),
-        With<Player>,
-    >,
+    new_players: ShownPlayers,
     // Query for all existing players (to send to new player and to broadcast new player to them)
-    existing_players: Query<
-        (
-            Entity,
-            &RuntimeEntityId,
-            &PlayerUuid,
-            &PlayerName,
-            &Position,
-            &Rotation,
-            &GameMode,
-            &PlayerSession,
-        ),
-        With<Player>,
-    >,
+    existing_players: ShownPlayers,
+    permissions: Option<Res<Permissions>>,
 ) {
+    let op_level = |name: &PlayerName, session: &PlayerSession| {
+        permissions.as_ref().map_or(OpLevel::None, |permissions| {
+            permissions.level(&name.0, session.xuid.as_deref())
+        })
+    };
     for event in events.read() {
         let new_entity = event.entity;
 
         // Get the new player's data
-        let Ok((new_rid, new_uuid, new_name, new_pos, new_rot, new_mode, new_session)) =
-            new_players.get(new_entity)
+        let Ok((
+            _,
+            new_rid,
+            new_uuid,
+            new_name,
+            new_pos,
+            new_rot,
+            new_mode,
+            new_session,
+            new_world,
+        )) = new_players.get(new_entity)
         else {
             // Entity may have been despawned between event emission and processing
             tracing::warn!(entity = ?new_entity, "PlayerSpawnedEvent for non-existent entity");
@@ -216,11 +234,13 @@ pub fn broadcast_spawn_system(
             new_pos,
             new_rot,
             *new_mode,
+            op_level(new_name, new_session),
         );
 
-        // Send new player to all existing players (except themselves)
-        for (other_entity, _, _, _, _, _, _, other_session) in existing_players.iter() {
-            if other_entity == new_entity {
+        // Send new player to all existing players in the same world (except themselves)
+        for (other_entity, _, _, _, _, _, _, other_session, other_world) in existing_players.iter()
+        {
+            if other_entity == new_entity || other_world != new_world {
                 continue;
             }
             let _ = other_session.send(McpePacket::from(new_packet.clone()));
@@ -235,10 +255,11 @@ pub fn broadcast_spawn_system(
             other_pos,
             other_rot,
             other_mode,
-            _,
+            other_session,
+            other_world,
         ) in existing_players.iter()
         {
-            if other_entity == new_entity {
+            if other_entity == new_entity || other_world != new_world {
                 continue;
             }
             let other_packet = build_add_player_packet(
@@ -248,6 +269,7 @@ pub fn broadcast_spawn_system(
                 other_pos,
                 other_rot,
                 *other_mode,
+                op_level(other_name, other_session),
             );
             let _ = new_session.send(McpePacket::from(other_packet));
         }
@@ -257,9 +279,9 @@ pub fn broadcast_spawn_system(
     }
 }
 
-/// System: Updates SpatialChunk when player crosses chunk boundaries.
+/// System: Updates SpatialChunk when an entity crosses chunk boundaries.
 ///
-/// Only runs on players with Changed<Position>, avoiding polling all players.
+/// Only runs on entities with Changed<Position>, avoiding polling all entities.
 /// When SpatialChunk is mutated, the component's on_insert hook does NOT fire
 /// (hooks only fire on insert/remove, not mutation), so we manually update
 /// the EntityGrid here.
@@ -267,17 +289,17 @@ pub fn broadcast_spawn_system(
 /// Runs before broadcast systems to ensure spatial data is current.
 pub fn sync_spatial_chunks(
     mut grid: ResMut<EntityGrid>,
-    mut players: Query<(Entity, &Position, &mut SpatialChunk), (With<Player>, Changed<Position>)>,
+    mut entities: Query<(Entity, &Position, &WorldId, &mut SpatialChunk), Changed<Position>>,
 ) {
-    for (entity, pos, mut spatial) in players.iter_mut() {
+    for (entity, pos, &world, mut spatial) in entities.iter_mut() {
         let new_x = (pos.0.x.floor() as i32) >> 4;
         let new_z = (pos.0.z.floor() as i32) >> 4;
 
         if spatial.x != new_x || spatial.z != new_z {
             // Remove from old bucket
-            grid.remove((spatial.x, spatial.z), entity);
+            grid.remove(world, (spatial.x, spatial.z), entity);
             // Insert into new bucket
-            grid.insert((new_x, new_z), entity);
+            grid.insert(world, (new_x, new_z), entity);
             // Update the component
             spatial.x = new_x;
             spatial.z = new_z;
@@ -285,30 +307,45 @@ pub fn sync_spatial_chunks(
     }
 }
 
+/// Players whose movement is broadcast, with where it was last broadcast.
+type MovingPlayers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static RuntimeEntityId,
+        &'static Position,
+        &'static Rotation,
+        &'static WorldId,
+        &'static mut LastBroadcastPosition,
+    ),
+    With<Player>,
+>;
+
+/// A player movement to broadcast.
+struct MovementUpdate {
+    entity: Entity,
+    runtime_id: i64,
+    world: WorldId,
+    position: Position,
+    rotation: Rotation,
+}
+
 /// System: Broadcast movement updates for players who have moved significantly.
 ///
 /// Uses EntityGrid for O(N) spatial lookups instead of O(N²).
 /// Only broadcasts if position changed by more than MOVEMENT_THRESHOLD or rotation changed.
 pub fn broadcast_movement_system(
     grid: Res<EntityGrid>,
-    mut players: Query<
-        (
-            Entity,
-            &RuntimeEntityId,
-            &Position,
-            &Rotation,
-            &mut LastBroadcastPosition,
-        ),
-        With<Player>,
-    >,
+    mut players: MovingPlayers,
     sessions: Query<(Entity, &PlayerSession)>,
 ) {
     // Collect movement updates to avoid borrow conflicts
-    let mut updates: Vec<(Entity, i64, Position, Rotation, f64, f64, f64, f32, f32)> = Vec::new();
+    let mut updates: Vec<MovementUpdate> = Vec::new();
 
     let player_count = players.iter().count();
-    for (entity, rid, pos, rot, last_pos) in players.iter() {
-        let dist_sq = position_distance_sq(&last_pos, pos);
+    for (entity, rid, pos, rot, &world, last_pos) in players.iter() {
+        let dist_sq = position_distance_sq(last_pos, pos);
         let yaw_diff = (last_pos.yaw - rot.yaw).abs();
         let pitch_diff = (last_pos.pitch - rot.pitch).abs();
 
@@ -316,17 +353,13 @@ pub fn broadcast_movement_system(
             || yaw_diff > ROTATION_THRESHOLD
             || pitch_diff > ROTATION_THRESHOLD
         {
-            updates.push((
+            updates.push(MovementUpdate {
                 entity,
-                rid.0,
-                pos.clone(),
-                rot.clone(),
-                pos.0.x,
-                pos.0.y,
-                pos.0.z,
-                rot.yaw,
-                rot.pitch,
-            ));
+                runtime_id: rid.0,
+                world,
+                position: *pos,
+                rotation: *rot,
+            });
         }
     }
 
@@ -334,12 +367,19 @@ pub fn broadcast_movement_system(
     let mut packets_sent = 0usize;
 
     // Apply updates using spatial grid for O(N) lookups
-    for (moving_entity, runtime_id, pos, rot, x, y, z, yaw, pitch) in updates {
+    for MovementUpdate {
+        entity: moving_entity,
+        runtime_id,
+        world,
+        position: pos,
+        rotation: rot,
+    } in updates
+    {
         let move_packet = build_move_player_packet(runtime_id, &pos, &rot, true);
         let mover_chunk = ((pos.0.x.floor() as i32) >> 4, (pos.0.z.floor() as i32) >> 4);
 
         // O(1) neighbor lookup: get entities in 3x3 chunk area
-        let nearby = grid.get_neighbors(mover_chunk, 1);
+        let nearby = grid.get_neighbors(world, mover_chunk, 1);
 
         for observer_entity in nearby {
             if observer_entity == moving_entity {
@@ -352,12 +392,12 @@ pub fn broadcast_movement_system(
         }
 
         // Update last broadcast position
-        if let Ok((_, _, _, _, mut last_pos)) = players.get_mut(moving_entity) {
-            last_pos.x = x;
-            last_pos.y = y;
-            last_pos.z = z;
-            last_pos.yaw = yaw;
-            last_pos.pitch = pitch;
+        if let Ok((_, _, _, _, _, mut last_pos)) = players.get_mut(moving_entity) {
+            last_pos.x = pos.0.x;
+            last_pos.y = pos.0.y;
+            last_pos.z = pos.0.z;
+            last_pos.yaw = rot.yaw;
+            last_pos.pitch = rot.pitch;
         }
     }
 
@@ -424,8 +464,7 @@ pub fn cleanup_despawned_entities(
 // =============================================================================
 
 use crate::entity::components::BreakingState;
-use crate::world::ChunkManager;
-use crate::world::ecs::{ChunkData, ChunkViewers};
+use crate::world::ecs::{ChunkData, ChunkViewers, Worlds};
 use jolyne::valentine::types::SoundType;
 use jolyne::valentine::{LevelEventPacket, LevelEventPacketEvent, LevelSoundEventPacket};
 
@@ -437,12 +476,12 @@ use jolyne::valentine::{LevelEventPacket, LevelEventPacketEvent, LevelSoundEvent
 ///
 /// This implements server-side block cracking animations like dragonfly does.
 pub fn tick_block_breaking(
-    mut players: Query<(Entity, &mut BreakingState, &PlayerSession), With<Player>>,
-    chunk_manager: bevy_ecs::prelude::Res<ChunkManager>,
+    mut players: Query<(Entity, &mut BreakingState, &WorldId, &PlayerSession), With<Player>>,
+    worlds: Res<Worlds>,
     chunks: Query<(&ChunkViewers, &ChunkData)>,
     all_sessions: Query<&PlayerSession>,
 ) {
-    for (_player_entity, mut breaking, _player_session) in players.iter_mut() {
+    for (_player_entity, mut breaking, world_id, _player_session) in players.iter_mut() {
         // Skip if not breaking
         if !breaking.is_breaking() {
             continue;
@@ -461,7 +500,10 @@ pub fn tick_block_breaking(
             let cz = z >> 4;
 
             // Get chunk entity to find viewers
-            let Some(chunk_entity) = chunk_manager.get_by_coords(cx, cz) else {
+            let Some(chunk_entity) = worlds
+                .get(*world_id)
+                .and_then(|chunk_manager| chunk_manager.get_by_coords(cx, cz))
+            else {
                 continue;
             };
 
@@ -490,7 +532,7 @@ pub fn tick_block_breaking(
                     let local_x = (x & 15) as u8;
                     let local_y = y as i16;
                     let local_z = (z & 15) as u8;
-                    chunk_data.inner.get_block(local_x, local_y, local_z)
+                    chunk_data.inner.get_block(local_x, local_y, local_z, 0)
                 })
                 .unwrap_or(0);
 
@@ -544,21 +586,24 @@ pub fn tick_block_breaking(
 // Batched Block Update Broadcasting
 // =============================================================================
 
-use crate::world::ecs::BlockBroadcastEvent;
+use crate::world::BlockEntity;
+use crate::world::ecs::{BlockBroadcastEvent, ChunkBlockEntities, block_entity_packet};
 use jolyne::valentine::UpdateBlockPacket;
 use jolyne::valentine::types::UpdateBlockFlags;
 
 /// System: Batch block updates and broadcast to chunk viewers.
 ///
 /// Reads all `BlockBroadcastEvent` events from the current tick, groups them
-/// by chunk entity, and sends batched `UpdateBlockPacket`s to viewers.
+/// by chunk entity, and sends batched `UpdateBlockPacket`s to viewers, followed
+/// by `BlockActorData` for any block entity at an updated position.
 /// This reduces network overhead when multiple blocks change in the same tick.
 ///
 /// Note: This system focuses on `UpdateBlockPacket` only. Particles and sounds
 /// are still sent directly by `break_block`/`place_block` for now.
 pub fn broadcast_block_updates(
     mut events: MessageReader<BlockBroadcastEvent>,
-    chunks: Query<&ChunkViewers>,
+    chunks: Query<(&ChunkViewers, Option<&ChunkBlockEntities>)>,
+    block_entities: Query<&BlockEntity>,
     sessions: Query<&PlayerSession>,
 ) {
     // Group events by chunk for efficient packet bundling
@@ -580,30 +625,44 @@ pub fn broadcast_block_updates(
 
     // Send batched updates to viewers
     for (chunk_entity, updates) in updates_by_chunk {
-        let Ok(viewers) = chunks.get(chunk_entity) else {
+        let Ok((viewers, tracked)) = chunks.get(chunk_entity) else {
             continue;
         };
 
         // Build packets for all updates in this chunk
-        let packets: Vec<UpdateBlockPacket> = updates
+        let mut packets: Vec<McpePacket> = updates
             .iter()
-            .map(|update| UpdateBlockPacket {
-                position: jolyne::valentine::types::BlockCoordinates {
-                    x: update.block_pos.x,
-                    y: update.block_pos.y,
-                    z: update.block_pos.z,
-                },
-                block_runtime_id: update.new_block as i32,
-                flags: UpdateBlockFlags::NEIGHBORS | UpdateBlockFlags::NETWORK,
-                layer: 0,
+            .map(|update| {
+                McpePacket::from(UpdateBlockPacket {
+                    position: jolyne::valentine::types::BlockCoordinates {
+                        x: update.block_pos.x,
+                        y: update.block_pos.y,
+                        z: update.block_pos.z,
+                    },
+                    block_runtime_id: update.new_block as i32,
+                    flags: UpdateBlockFlags::NEIGHBORS | UpdateBlockFlags::NETWORK,
+                    layer: update.layer as _,
+                })
             })
             .collect();
 
+        // Block entities of newly placed blocks follow their block update
+        if let Some(tracked) = tracked {
+            packets.extend(
+                updates
+                    .iter()
+                    .filter(|update| update.layer == 0)
+                    .filter_map(|update| tracked.get(update.block_pos))
+                    .filter_map(|entity| block_entities.get(entity).ok())
+                    .map(block_entity_packet),
+            );
+        }
+
         // Send all packets to each viewer
         for viewer in viewers.iter() {
             if let Ok(session) = sessions.get(viewer) {
                 for packet in &packets {
-                    let _ = session.send(McpePacket::from(packet.clone()));
+                    let _ = session.send(packet.clone());
                     total_packets_sent += 1;
                 }
             }
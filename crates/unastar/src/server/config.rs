@@ -1,5 +1,8 @@
 //! Server configuration.
 
+use std::path::PathBuf;
+
+use crate::config::{NamedWorldConfig, SpawnRule};
 use crate::entity::components::GameMode;
 use crate::world::WorldConfig;
 
@@ -29,6 +32,13 @@ pub struct ServerConfig {
     pub chunk_unload_ticks: u32,
     /// World configuration (generator, bounds, dimension).
     pub world: WorldConfig,
+    /// Worlds besides the default one, loadable by name.
+    pub worlds: Vec<NamedWorldConfig>,
+    /// Spawn rules, used to place players that respawn without a spawn point.
+    pub spawn_rules: Vec<SpawnRule>,
+    /// Directory of `.mcstructure` templates for jigsaw structures. Without
+    /// one, villages, outposts and trial chambers don't generate.
+    pub structure_templates: Option<PathBuf>,
 }
 
 impl Default for ServerConfig {
@@ -43,6 +53,9 @@ impl Default for ServerConfig {
             simulation_distance: 6,  // 2 more than default view
             chunk_unload_ticks: 100, // 5 second grace period
             world: WorldConfig::default(),
+            worlds: Vec::new(),
+            spawn_rules: vec![SpawnRule::default()],
+            structure_templates: None,
         }
     }
 }
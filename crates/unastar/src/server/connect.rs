@@ -5,36 +5,91 @@
 use glam::DVec3;
 use jolyne::auth::ValidatedIdentity;
 use jolyne::stream::server::ServerHandshakeConfig;
-use jolyne::valentine::BlockCoordinates;
 use jolyne::valentine::types::Vec3F;
+use jolyne::valentine::{BlockCoordinates, DisconnectFailReason, McpePacket};
 use jolyne::{JolyneError, ServerLogin, ServerPlay, WorldTemplate};
 use p384::SecretKey;
+use thiserror::Error;
 
-use crate::config::{PlayerDataStore, SpawnLocation, UnastarConfig};
+use crate::access::{AccessControl, Denial, unix_now};
+use crate::config::{PlayerDataStore, SpawnLocation, SpawnRule, UnastarConfig};
+use crate::permission::Permissions;
+use crate::server::game::types::disconnect_packet;
+use crate::storage::{PlayerData, PlayerProvider};
+use crate::world::WorldConfig;
+
+/// Why a join sequence ended before the player could spawn.
+#[derive(Debug, Error)]
+pub enum JoinError {
+    #[error(transparent)]
+    Protocol(#[from] JolyneError),
+    /// Refused by a ban or the whitelist.
+    #[error("{name} was refused: {reason}")]
+    Denied { name: String, reason: String },
+}
 
 /// Performs the complete join sequence for a connecting player.
 ///
 /// This includes:
 /// 1. Network settings negotiation
-/// 2. Authentication
+/// 2. Authentication, then ban and whitelist checks
 /// 3. Encryption handshake
 /// 4. Resource pack negotiation
-/// 5. Spawn location resolution
+/// 5. Saved player data and spawn location resolution
 /// 6. Start game packet
+#[allow(clippy::too_many_arguments)]
 pub async fn accept_join_sequence(
     template: &WorldTemplate,
     server_key: &SecretKey,
     config: &UnastarConfig,
     player_data_store: &PlayerDataStore,
+    player_provider: Option<&dyn PlayerProvider>,
+    permissions: &Permissions,
+    access: &AccessControl,
     session_id: u64,
     handshake_stream: ServerLogin,
-) -> Result<(ServerPlay, ValidatedIdentity, Vec3F), JolyneError> {
+) -> Result<(ServerPlay, ValidatedIdentity, Vec3F, Option<PlayerData>), JoinError> {
+    let address = handshake_stream.peer_addr();
+
     // 1. Network Settings
     let login = handshake_stream.accept_network_settings().await?;
 
     // 2. Auth
     let (secure, identity) = login.authenticate().await?;
 
+    let name = identity.display_name.as_deref().unwrap_or_default();
+    if let Err(e) = access.reload_if_changed() {
+        tracing::warn!(error = %e, "Failed to reload access lists, keeping the previous ones");
+    }
+    if let Some(xuid) = identity.xuid.as_deref() {
+        if let Err(e) = permissions.claim(name, xuid) {
+            tracing::warn!(error = %e, "Failed to save permissions");
+        }
+        if let Err(e) = access.claim(name, xuid) {
+            tracing::warn!(error = %e, "Failed to save the whitelist");
+        }
+    }
+    if let Err(denial) = access.check(
+        name,
+        identity.xuid.as_deref(),
+        address.ip(),
+        permissions.level(name, identity.xuid.as_deref()).is_op(),
+    ) {
+        let reason = match &denial {
+            Denial::NotWhitelisted => DisconnectFailReason::NotAllowed,
+            Denial::Banned(_) | Denial::IpBanned(_) => DisconnectFailReason::Kicked,
+        };
+        let message = denial.message(unix_now());
+        let mut transport = secure.into_transport();
+        let _ = transport
+            .send_batch(&[McpePacket::from(disconnect_packet(reason, &message))])
+            .await;
+        return Err(JoinError::Denied {
+            name: name.to_string(),
+            reason: message,
+        });
+    }
+
     // 3. Encryption/handshake
     let packs = secure
         .finish_handshake(
@@ -48,8 +103,20 @@ pub async fn accept_join_sequence(
     // 4. Resource packs (none/default)
     let start_game_state = packs.negotiate_packs(false).await?;
 
-    // 5. Resolve spawn before StartGame.
-    let spawn = resolve_spawn_location(config, &identity, template, player_data_store).await;
+    // 5. Load saved data and resolve spawn before StartGame.
+    let player_data = load_player_data(
+        config,
+        identity.uuid.as_deref(),
+        player_provider,
+        player_data_store,
+    )
+    .await;
+    let spawn = resolve_spawn_location(
+        &config.spawn_rules,
+        &config.world,
+        template,
+        player_data.as_ref(),
+    );
     let initial_position = Vec3F {
         x: spawn.x,
         y: spawn.y,
@@ -68,10 +135,61 @@ pub async fn accept_join_sequence(
         x: spawn.pitch,
         z: spawn.yaw,
     };
+    if let Some(name) = identity.display_name.as_deref() {
+        join_params.start_game.permission_level = permissions
+            .level(name, identity.xuid.as_deref())
+            .permission_level();
+    }
 
     // 7. Join.
     let play = start_game_state.start_game(join_params).await?;
-    Ok((play, identity, initial_position))
+    Ok((play, identity, initial_position, player_data))
+}
+
+/// Load a player's saved data.
+///
+/// Reads from the `PlayerProvider` first. Players without a record there
+/// are migrated from the legacy last-position file written by
+/// `PlayerDataStore`; the full record is written on their next save.
+pub async fn load_player_data(
+    config: &UnastarConfig,
+    uuid: Option<&str>,
+    player_provider: Option<&dyn PlayerProvider>,
+    player_data_store: &PlayerDataStore,
+) -> Option<PlayerData> {
+    let uuid = uuid?;
+
+    if let Some(provider) = player_provider
+        && let Ok(parsed) = uuid::Uuid::parse_str(uuid)
+    {
+        match provider.load(parsed).await {
+            Ok(Some(data)) => return Some(data),
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!(%uuid, error = %e, "Failed to load player data");
+                return None;
+            }
+        }
+    }
+
+    let last = match player_data_store.load_last_position(uuid).await {
+        Ok(last) => last?,
+        Err(e) => {
+            tracing::warn!(%uuid, error = %e, "Failed to read legacy player file");
+            return None;
+        }
+    };
+    tracing::info!(%uuid, "Migrating legacy player position file");
+    let location = last.location;
+    Some(PlayerData {
+        version: 1,
+        uuid: uuid.to_string(),
+        position: [location.x as f64, location.y as f64, location.z as f64],
+        rotation: [location.yaw, location.pitch],
+        dimension: last.dimension,
+        game_mode: config.server_config().default_gamemode.id(),
+        ..PlayerData::default()
+    })
 }
 
 /// Resolves the spawn location for a player based on config rules.
@@ -80,15 +198,16 @@ pub async fn accept_join_sequence(
 /// For vanilla worlds, users should configure an appropriate spawn point in the config.
 /// The expensive `find_safe_spawn()` is only called as a last resort when no location
 /// is configured at all.
-pub async fn resolve_spawn_location(
-    config: &UnastarConfig,
-    identity: &ValidatedIdentity,
+///
+/// Pass `saved: None` to ignore previous positions, as when a player respawns.
+pub fn resolve_spawn_location(
+    rules: &[SpawnRule],
+    world: &WorldConfig,
     template: &WorldTemplate,
-    player_data_store: &PlayerDataStore,
+    saved: Option<&PlayerData>,
 ) -> SpawnLocation {
     use jolyne::valentine::StartGamePacketDimension;
 
-    let uuid = identity.uuid.as_deref();
     let world_dimension = match template.start_game_template.dimension {
         StartGamePacketDimension::Overworld => 0,
         StartGamePacketDimension::Nether => 1,
@@ -96,16 +215,22 @@ pub async fn resolve_spawn_location(
     };
 
     // Check spawn rules in order
-    for rule in &config.spawn_rules {
+    for rule in rules {
         // Check for previous position first if enabled
-        if rule.previous_position {
-            if let Some(uuid) = uuid {
-                if let Ok(Some(last)) = player_data_store.load_last_position(uuid).await {
-                    if last.dimension == world_dimension {
-                        return last.location;
-                    }
-                }
-            }
+        if rule.previous_position
+            && let Some(saved) = saved
+            && saved.dimension == world_dimension
+            && saved.world == crate::world::DEFAULT_WORLD
+        {
+            let [x, y, z] = saved.position;
+            let [yaw, pitch] = saved.rotation;
+            return SpawnLocation {
+                x: x as f32,
+                y: y as f32,
+                z: z as f32,
+                yaw,
+                pitch,
+            };
         }
         // Use configured location directly (trust the user's config)
         if rule.always_at_location {
@@ -116,19 +241,18 @@ pub async fn resolve_spawn_location(
     }
 
     // Fallback: if any rule has a location, use it directly
-    if let Some(location) = config.spawn_rules.iter().find_map(|r| r.location) {
+    if let Some(location) = rules.iter().find_map(|r| r.location) {
         return location;
     }
 
     // Final fallback: use template spawn (for non-vanilla) or search for safe spawn (vanilla)
     // NOTE: find_safe_spawn() is expensive and should be avoided by configuring spawn in config.
     // This is only called when no spawn location is configured at all.
-    if let crate::world::WorldGenerator::Vanilla { seed } = config.world.generator {
+    if let Some(generator) = world.generator.terrain() {
         tracing::warn!(
             "No spawn location configured for vanilla world - searching for safe spawn. \
              This is slow! Configure [[spawn_rules]] with a location in your config."
         );
-        let generator = crate::world::generator::VanillaGenerator::new(seed);
         let (x, y, z) = generator.find_safe_spawn();
         return SpawnLocation {
             x: x as f32 + 0.5,
@@ -0,0 +1,86 @@
+//! Ban and whitelist enforcement for players already online.
+//!
+//! Logins are checked in the join sequence; this covers players who are
+//! banned, or drop off the whitelist, while connected.
+
+use bevy_ecs::prelude::*;
+use jolyne::valentine::{DisconnectFailReason, McpePacket};
+use tracing::{info, warn};
+
+use super::GameServer;
+use super::types::disconnect_packet;
+use crate::access::{AccessControl, unix_now};
+use crate::entity::components::{PlayerName, PlayerSession};
+use crate::permission::op_level;
+
+/// How often the access list files are checked for edits, in ticks.
+const ACCESS_RELOAD_INTERVAL: u64 = 100;
+
+/// Disconnect a player, showing them `message`.
+pub fn kick_player(world: &World, entity: Entity, message: &str) -> bool {
+    let Some(session) = world.get::<PlayerSession>(entity) else {
+        return false;
+    };
+    session.send(McpePacket::from(disconnect_packet(
+        DisconnectFailReason::Kicked,
+        message,
+    )))
+}
+
+/// Kick every online player the access lists no longer allow.
+///
+/// Returns the names of the kicked players.
+pub fn kick_denied_players(world: &mut World) -> Vec<String> {
+    let Some(access) = world.get_resource::<AccessControl>().cloned() else {
+        return Vec::new();
+    };
+    let players: Vec<(Entity, String, Option<String>, std::net::IpAddr)> = world
+        .query::<(Entity, &PlayerName, &PlayerSession)>()
+        .iter(world)
+        .map(|(entity, name, session)| {
+            (
+                entity,
+                name.0.clone(),
+                session.xuid.clone(),
+                session.address.ip(),
+            )
+        })
+        .collect();
+
+    let now = unix_now();
+    let mut kicked = Vec::new();
+    for (entity, name, xuid, ip) in players {
+        let is_op = op_level(world, entity).is_op();
+        if let Err(denial) = access.check(&name, xuid.as_deref(), ip, is_op) {
+            kick_player(world, entity, &denial.message(now));
+            info!(name = %name, "Kicked player no longer allowed on the server");
+            kicked.push(name);
+        }
+    }
+    kicked
+}
+
+impl GameServer {
+    /// Use `access` for bans and the whitelist, e.g. one loaded from disk.
+    pub fn set_access(&mut self, access: AccessControl) {
+        self.ecs.world_mut().insert_resource(access);
+    }
+
+    /// Periodically pick up edits to the access list files.
+    pub(super) fn tick_access(&mut self) {
+        if !self.current_tick.is_multiple_of(ACCESS_RELOAD_INTERVAL) {
+            return;
+        }
+        let Some(access) = self.ecs.world().get_resource::<AccessControl>() else {
+            return;
+        };
+        match access.reload_if_changed() {
+            Ok(true) => {
+                info!("Reloaded ban list and whitelist");
+                kick_denied_players(self.ecs.world_mut());
+            }
+            Ok(false) => {}
+            Err(e) => warn!(error = %e, "Failed to reload access lists"),
+        }
+    }
+}
@@ -13,14 +13,16 @@
 //! support via `BlockBroadcastEvent`.
 
 use bevy_ecs::entity::Entity;
-use glam::IVec3;
+use glam::{DVec3, IVec3};
 use tracing::{debug, info, trace};
 
 use super::GameServer;
 use crate::ecs::events::EventBuffer;
 use crate::entity::components::{BreakingState, PlayerName, PlayerSession, PlayerUuid};
-use crate::world::chunk::blocks;
-use crate::world::ecs::{BlockBroadcastEvent, BlockChanged, ChunkManager, ChunkViewers};
+use crate::item::ItemStack;
+use crate::registry::block::waterloggable;
+use crate::world::chunk::{LAYER_BLOCK, LAYER_LIQUID, blocks};
+use crate::world::ecs::{BlockBroadcastEvent, BlockChanged, ChunkViewers, WorldId, Worlds};
 use crate::world::ecs::{world_to_chunk_coords, world_to_local_coords};
 use jolyne::valentine::blocks::BLOCKS;
 use jolyne::valentine::types::{Action, BlockCoordinates, Vec3F};
@@ -41,6 +43,7 @@ impl GameServer {
         };
         // Get current tick for timing
         let current_tick = self.current_tick;
+        let world_id = WorldId::of(self.ecs.world(), player_entity);
 
         // Cap block actions per packet (DoS protection)
         for action_item in block_actions.iter().take(MAX_BLOCK_ACTIONS) {
@@ -93,7 +96,7 @@ impl GameServer {
                         let break_time_ticks = if is_creative {
                             0 // Instant break in creative
                         } else {
-                            self.get_block_break_time(x, y, z)
+                            self.get_block_break_time(world_id, x, y, z)
                         };
 
                         info!(pos = ?(x, y, z), is_creative, break_time_ticks, "StartBreak - setting break time");
@@ -126,7 +129,7 @@ impl GameServer {
 
                         // Broadcast crack animation to chunk viewers (except breaker)
                         if !is_creative {
-                            self.broadcast_block_crack_start(x, y, z, break_time_ticks);
+                            self.broadcast_block_crack_start(world_id, x, y, z, break_time_ticks);
                         }
                     } else {
                         info!("StartBreak: no position in content (will use CrackBreak position)");
@@ -157,7 +160,7 @@ impl GameServer {
                             let break_time_ticks = if is_creative {
                                 0
                             } else {
-                                self.get_block_break_time(x, y, z)
+                                self.get_block_break_time(world_id, x, y, z)
                             };
 
                             info!(pos = ?(x, y, z), is_creative, break_time_ticks, "CrackBreak: starting break (StartBreak had no position)");
@@ -211,7 +214,7 @@ impl GameServer {
                         }
 
                         // Broadcast stop crack to all viewers
-                        self.broadcast_block_crack_stop(x, y, z);
+                        self.broadcast_block_crack_stop(world_id, x, y, z);
                     }
                 }
                 // AbortBreak: player stopped manually
@@ -226,7 +229,7 @@ impl GameServer {
                         }
 
                         // Broadcast stop crack to all viewers
-                        self.broadcast_block_crack_stop(x, y, z);
+                        self.broadcast_block_crack_stop(world_id, x, y, z);
                     }
                 }
                 _ => {}
@@ -238,6 +241,7 @@ impl GameServer {
     /// Includes ALL viewers including the breaking player.
     pub(super) fn broadcast_block_crack_start(
         &self,
+        world_id: WorldId,
         x: i32,
         y: i32,
         z: i32,
@@ -246,7 +250,7 @@ impl GameServer {
         let (cx, cz) = world_to_chunk_coords(x, z);
 
         let world = self.ecs.world();
-        let Some(chunk_manager) = world.get_resource::<ChunkManager>() else {
+        let Some(chunk_manager) = world.resource::<Worlds>().get(world_id) else {
             return;
         };
         let Some(chunk_entity) = chunk_manager.get_by_coords(cx, cz) else {
@@ -282,11 +286,11 @@ impl GameServer {
 
     /// Broadcast block crack stop animation to chunk viewers.
     /// Includes ALL viewers including the breaking player.
-    pub(super) fn broadcast_block_crack_stop(&self, x: i32, y: i32, z: i32) {
+    pub(super) fn broadcast_block_crack_stop(&self, world_id: WorldId, x: i32, y: i32, z: i32) {
         let (cx, cz) = world_to_chunk_coords(x, z);
 
         let world = self.ecs.world();
-        let Some(chunk_manager) = world.get_resource::<ChunkManager>() else {
+        let Some(chunk_manager) = world.resource::<Worlds>().get(world_id) else {
             return;
         };
         let Some(chunk_entity) = chunk_manager.get_by_coords(cx, cz) else {
@@ -321,10 +325,11 @@ impl GameServer {
         let (local_x, local_y, local_z) = world_to_local_coords(x, y, z);
 
         // Get chunk entity - must exist if player is viewing this chunk
+        let world_id = WorldId::of(self.ecs.world(), breaking_player);
         let chunk_entity = {
             let world = self.ecs.world();
-            let Some(chunk_manager) = world.get_resource::<ChunkManager>() else {
-                debug!("break_block: no ChunkManager resource");
+            let Some(chunk_manager) = world.resource::<Worlds>().get(world_id) else {
+                debug!("break_block: world not loaded");
                 return;
             };
             chunk_manager.get_by_coords(cx, cz)
@@ -339,7 +344,7 @@ impl GameServer {
         let original_block_id = {
             let world = self.ecs.world();
             if let Some(chunk_data) = world.get::<crate::world::ecs::ChunkData>(chunk_entity) {
-                chunk_data.inner.get_block(local_x, local_y, local_z)
+                chunk_data.inner.get_block(local_x, local_y, local_z, 0)
             } else {
                 0
             }
@@ -363,15 +368,32 @@ impl GameServer {
             });
         }
 
-        // Update block to air in chunk data (ECS component is source of truth)
+        // Update block in chunk data (ECS component is source of truth).
+        // A waterlogged block leaves its liquid behind in the block layer.
+        let mut replacement = *blocks::AIR;
+        let mut was_waterlogged = false;
         {
             let world = self.ecs.world_mut();
             if let Some(mut chunk_data) =
                 world.get_mut::<crate::world::ecs::ChunkData>(chunk_entity)
             {
+                let liquid = chunk_data
+                    .inner
+                    .get_block(local_x, local_y, local_z, LAYER_LIQUID);
+                if liquid != *blocks::AIR {
+                    replacement = liquid;
+                    was_waterlogged = true;
+                    chunk_data.inner.set_block(
+                        local_x,
+                        local_y,
+                        local_z,
+                        LAYER_LIQUID,
+                        *blocks::AIR,
+                    );
+                }
                 chunk_data
                     .inner
-                    .set_block(local_x, local_y, local_z, *blocks::AIR);
+                    .set_block(local_x, local_y, local_z, LAYER_BLOCK, replacement);
                 // Log block ID with comparisons to known blocks
                 let is_dirt = original_block_id == *blocks::DIRT;
                 let is_grass = original_block_id == *blocks::GRASS_BLOCK;
@@ -400,6 +422,9 @@ impl GameServer {
             }
         }
 
+        // Close any container windows before the block entity goes away
+        self.close_containers_at(world_id, IVec3::new(x, y, z));
+
         // Trigger BlockChanged observer for immediate game logic (physics, lighting, etc.)
         // This fires synchronously within this tick, and the observer marks the chunk dirty.
         {
@@ -408,7 +433,7 @@ impl GameServer {
                 chunk_entity,
                 block_pos: IVec3::new(x, y, z),
                 old_block: original_block_id,
-                new_block: *blocks::AIR,
+                new_block: replacement,
             });
         }
 
@@ -420,8 +445,17 @@ impl GameServer {
             world.write_message(BlockBroadcastEvent {
                 chunk_entity,
                 block_pos: IVec3::new(x, y, z),
-                new_block: *blocks::AIR,
+                new_block: replacement,
+                layer: LAYER_BLOCK,
             });
+            if was_waterlogged {
+                world.write_message(BlockBroadcastEvent {
+                    chunk_entity,
+                    block_pos: IVec3::new(x, y, z),
+                    new_block: *blocks::AIR,
+                    layer: LAYER_LIQUID,
+                });
+            }
         }
 
         // Spawn item drop if breaking player is in survival mode
@@ -436,77 +470,24 @@ impl GameServer {
             };
 
             if is_survival {
-                // Get item network ID for this block
                 // Most blocks drop themselves - look up the item by block name
-                let item_network_id = {
-                    // Look up block name from runtime ID using jolyne BLOCKS slice
-                    let block_name = BLOCKS.get(original_block_id as usize).map(|b| b.name());
-
-                    use crate::registry::RegistryEntry;
-                    block_name
-                        .and_then(|name| self.items.get_by_name(name).map(|item| item.id()))
-                        .unwrap_or(0)
-                };
-
-                if item_network_id > 0 {
-                    // Allocate item entity ID
-                    let item_entity_id = self.next_item_entity_id;
-                    self.next_item_entity_id += 1;
-
-                    // Create AddItemEntity packet
-                    use jolyne::valentine::AddItemEntityPacket;
-                    use jolyne::valentine::types::{
-                        Item, ItemContent, ItemContentExtra, ItemExtraDataWithoutBlockingTick,
-                    };
-
-                    let item_packet = AddItemEntityPacket {
-                        entity_id_self: item_entity_id,
-                        runtime_entity_id: item_entity_id,
-                        item: Item {
-                            network_id: item_network_id as i32,
-                            content: Some(Box::new(ItemContent {
-                                count: 1,
-                                metadata: 0,
-                                has_stack_id: 0,
-                                stack_id: None,
-                                block_runtime_id: original_block_id as i32,
-                                extra: ItemContentExtra::Default(
-                                    ItemExtraDataWithoutBlockingTick::default(),
-                                ),
-                            })),
-                        },
-                        position: Vec3F {
-                            x: x as f32 + 0.5,
-                            y: y as f32 + 0.25, // Spawn slightly above block center
-                            z: z as f32 + 0.5,
-                        },
-                        velocity: Vec3F {
-                            x: 0.0,
-                            y: 0.1, // Small upward velocity
-                            z: 0.0,
-                        },
-                        metadata: vec![], // Empty metadata
-                        is_from_fishing: false,
-                    };
-
-                    // Send to breaking player
-                    let world = self.ecs.world();
-                    if let Some(session) = world.get::<PlayerSession>(breaking_player) {
-                        let _ = session.send(McpePacket::from(item_packet.clone()));
-                        info!(pos = ?(x, y, z), item_network_id, entity_id = item_entity_id, "Spawned item drop");
-                    }
-
-                    // Also send to chunk viewers
-                    if let Some(chunk_viewers) = world.get::<ChunkViewers>(chunk_entity) {
-                        for viewer in chunk_viewers.iter() {
-                            if viewer != breaking_player {
-                                if let Some(viewer_session) = world.get::<PlayerSession>(viewer) {
-                                    let _ =
-                                        viewer_session.send(McpePacket::from(item_packet.clone()));
-                                }
-                            }
-                        }
-                    }
+                let drop = BLOCKS
+                    .get(original_block_id as usize)
+                    .map(|b| b.name())
+                    .filter(|name| self.items.get_by_name(name).is_some())
+                    .map(|name| ItemStack::new(name, 1));
+
+                if let Some(drop) = drop {
+                    // Spawn slightly above block center with a small upward velocity
+                    let position = DVec3::new(x as f64 + 0.5, y as f64 + 0.25, z as f64 + 0.5);
+                    self.spawn_dropped_item(
+                        world_id,
+                        position,
+                        drop,
+                        DVec3::new(0.0, 0.1, 0.0),
+                        None,
+                    );
+                    info!(pos = ?(x, y, z), block = original_block_id, "Spawned item drop");
                 }
             }
         }
@@ -524,12 +505,17 @@ impl GameServer {
                 LevelEventPacket, LevelEventPacketEvent, LevelSoundEventPacket, UpdateBlockPacket,
             };
 
-            let update_packet = UpdateBlockPacket {
-                position: BlockCoordinates { x, y, z },
-                block_runtime_id: *blocks::AIR as i32,
-                flags: UpdateBlockFlags::NEIGHBORS | UpdateBlockFlags::NETWORK,
-                layer: 0,
-            };
+            // A waterlogged block also clears the client's liquid layer
+            let update_packets: Vec<UpdateBlockPacket> = [(replacement, LAYER_BLOCK)]
+                .into_iter()
+                .chain(was_waterlogged.then_some((*blocks::AIR, LAYER_LIQUID)))
+                .map(|(block, layer)| UpdateBlockPacket {
+                    position: BlockCoordinates { x, y, z },
+                    block_runtime_id: block as i32,
+                    flags: UpdateBlockFlags::NEIGHBORS | UpdateBlockFlags::NETWORK,
+                    layer: layer as i32,
+                })
+                .collect();
 
             // Destroy particles
             let particle_packet = LevelEventPacket {
@@ -562,7 +548,9 @@ impl GameServer {
 
             for viewer_entity in chunk_viewers.iter() {
                 if let Some(session) = world.get::<PlayerSession>(viewer_entity) {
-                    let _ = session.send(McpePacket::from(update_packet.clone()));
+                    for update_packet in &update_packets {
+                        let _ = session.send(McpePacket::from(update_packet.clone()));
+                    }
                     let _ = session.send(McpePacket::from(particle_packet.clone()));
                     let _ = session.send(McpePacket::from(sound_packet.clone()));
                     sent_to.insert(viewer_entity);
@@ -573,7 +561,9 @@ impl GameServer {
             if !sent_to.contains(&breaking_player) {
                 if let Some(session) = world.get::<PlayerSession>(breaking_player) {
                     info!("Sending break effects directly to breaking player (not in viewers)");
-                    let _ = session.send(McpePacket::from(update_packet.clone()));
+                    for update_packet in &update_packets {
+                        let _ = session.send(McpePacket::from(update_packet.clone()));
+                    }
                     let _ = session.send(McpePacket::from(particle_packet.clone()));
                     let _ = session.send(McpePacket::from(sound_packet.clone()));
                 }
@@ -586,12 +576,17 @@ impl GameServer {
                 LevelEventPacket, LevelEventPacketEvent, LevelSoundEventPacket, UpdateBlockPacket,
             };
 
-            let update_packet = UpdateBlockPacket {
-                position: BlockCoordinates { x, y, z },
-                block_runtime_id: *blocks::AIR as i32,
-                flags: UpdateBlockFlags::NEIGHBORS | UpdateBlockFlags::NETWORK,
-                layer: 0,
-            };
+            // A waterlogged block also clears the client's liquid layer
+            let update_packets: Vec<UpdateBlockPacket> = [(replacement, LAYER_BLOCK)]
+                .into_iter()
+                .chain(was_waterlogged.then_some((*blocks::AIR, LAYER_LIQUID)))
+                .map(|(block, layer)| UpdateBlockPacket {
+                    position: BlockCoordinates { x, y, z },
+                    block_runtime_id: block as i32,
+                    flags: UpdateBlockFlags::NEIGHBORS | UpdateBlockFlags::NETWORK,
+                    layer: layer as i32,
+                })
+                .collect();
 
             let particle_packet = LevelEventPacket {
                 event: LevelEventPacketEvent::ParticleDestroy,
@@ -618,7 +613,9 @@ impl GameServer {
             };
 
             if let Some(session) = world.get::<PlayerSession>(breaking_player) {
-                let _ = session.send(McpePacket::from(update_packet));
+                for update_packet in update_packets {
+                    let _ = session.send(McpePacket::from(update_packet));
+                }
                 let _ = session.send(McpePacket::from(particle_packet));
                 let _ = session.send(McpePacket::from(sound_packet));
             }
@@ -628,14 +625,14 @@ impl GameServer {
     /// Get the break time in ticks for the block at the given world coordinates.
     /// Uses block hardness from BlockDefDyn. Formula: hardness * 1.5 * 20 ticks for bare hand.
     /// Returns minimum 1 tick, or 20 ticks if block not found.
-    pub(super) fn get_block_break_time(&self, x: i32, y: i32, z: i32) -> u32 {
+    pub(super) fn get_block_break_time(&self, world_id: WorldId, x: i32, y: i32, z: i32) -> u32 {
         let (cx, cz) = world_to_chunk_coords(x, z);
         let (local_x, local_y, local_z) = world_to_local_coords(x, y, z);
 
         // Get block runtime ID from chunk
         let block_runtime_id = {
             let world = self.ecs.world();
-            let Some(chunk_manager) = world.get_resource::<ChunkManager>() else {
+            let Some(chunk_manager) = world.resource::<Worlds>().get(world_id) else {
                 return 20;
             };
             let Some(chunk_entity) = chunk_manager.get_by_coords(cx, cz) else {
@@ -644,7 +641,7 @@ impl GameServer {
             let Some(chunk_data) = world.get::<crate::world::ecs::ChunkData>(chunk_entity) else {
                 return 20;
             };
-            chunk_data.inner.get_block(local_x, local_y, local_z)
+            chunk_data.inner.get_block(local_x, local_y, local_z, 0)
         };
 
         // Find BlockDefDyn that contains this runtime ID
@@ -694,6 +691,16 @@ impl GameServer {
             }
         }
 
+        // Using a container opens it instead of placing against it
+        let clicked = IVec3::new(
+            data.block_position.x,
+            data.block_position.y,
+            data.block_position.z,
+        );
+        if self.try_open_container(entity, clicked) {
+            return;
+        }
+
         // 1. Get held item and map to block
         let network_id = data.held_item.network_id;
         if network_id == 0 {
@@ -763,17 +770,25 @@ impl GameServer {
             });
         }
 
-        self.place_block(x, y, z, block_runtime_id);
+        let world_id = WorldId::of(self.ecs.world(), entity);
+        self.place_block(world_id, x, y, z, block_runtime_id);
     }
 
     /// Place a block at world coordinates: update chunk and broadcast
-    pub(super) fn place_block(&mut self, x: i32, y: i32, z: i32, block_runtime_id: u32) {
+    pub(super) fn place_block(
+        &mut self,
+        world_id: WorldId,
+        x: i32,
+        y: i32,
+        z: i32,
+        block_runtime_id: u32,
+    ) {
         let (cx, cz) = world_to_chunk_coords(x, z);
         let (local_x, local_y, local_z) = world_to_local_coords(x, y, z);
 
         // Get chunk entity
         let world = self.ecs.world();
-        let chunk_entity = if let Some(chunk_manager) = world.get_resource::<ChunkManager>() {
+        let chunk_entity = if let Some(chunk_manager) = world.resource::<Worlds>().get(world_id) {
             chunk_manager.get_by_coords(cx, cz)
         } else {
             None
@@ -788,21 +803,39 @@ impl GameServer {
         let old_block_id = {
             let world = self.ecs.world();
             if let Some(chunk_data) = world.get::<crate::world::ecs::ChunkData>(chunk_entity) {
-                chunk_data.inner.get_block(local_x, local_y, local_z)
+                chunk_data
+                    .inner
+                    .get_block(local_x, local_y, local_z, LAYER_BLOCK)
             } else {
                 0
             }
         };
 
+        // Placing a waterloggable block into water keeps a water source in the liquid layer
+        let waterlogged = blocks::is_water_source(old_block_id) && waterloggable(block_runtime_id);
+
         // Update chunk data
         {
             let world = self.ecs.world_mut();
             if let Some(mut chunk_data) =
                 world.get_mut::<crate::world::ecs::ChunkData>(chunk_entity)
             {
-                chunk_data
-                    .inner
-                    .set_block(local_x, local_y, local_z, block_runtime_id);
+                chunk_data.inner.set_block(
+                    local_x,
+                    local_y,
+                    local_z,
+                    LAYER_BLOCK,
+                    block_runtime_id,
+                );
+                if waterlogged {
+                    chunk_data.inner.set_block(
+                        local_x,
+                        local_y,
+                        local_z,
+                        LAYER_LIQUID,
+                        *blocks::WATER,
+                    );
+                }
             } else {
                 return;
             }
@@ -834,7 +867,16 @@ impl GameServer {
                 chunk_entity,
                 block_pos: IVec3::new(x, y, z),
                 new_block: block_runtime_id,
+                layer: LAYER_BLOCK,
             });
+            if waterlogged {
+                world.write_message(BlockBroadcastEvent {
+                    chunk_entity,
+                    block_pos: IVec3::new(x, y, z),
+                    new_block: *blocks::WATER,
+                    layer: LAYER_LIQUID,
+                });
+            }
         }
 
         // Broadcast to viewers
@@ -843,12 +885,17 @@ impl GameServer {
             use jolyne::valentine::types::{SoundType, UpdateBlockFlags};
             use jolyne::valentine::{LevelSoundEventPacket, UpdateBlockPacket};
 
-            let update_packet = UpdateBlockPacket {
-                position: BlockCoordinates { x, y, z },
-                block_runtime_id: block_runtime_id as i32,
-                flags: UpdateBlockFlags::NEIGHBORS | UpdateBlockFlags::NETWORK,
-                layer: 0,
-            };
+            // A waterlogged block also sends the water in its liquid layer
+            let update_packets: Vec<UpdateBlockPacket> = [(block_runtime_id, LAYER_BLOCK)]
+                .into_iter()
+                .chain(waterlogged.then_some((*blocks::WATER, LAYER_LIQUID)))
+                .map(|(block, layer)| UpdateBlockPacket {
+                    position: BlockCoordinates { x, y, z },
+                    block_runtime_id: block as i32,
+                    flags: UpdateBlockFlags::NEIGHBORS | UpdateBlockFlags::NETWORK,
+                    layer: layer as i32,
+                })
+                .collect();
 
             // Place sound
             let sound_packet = LevelSoundEventPacket {
@@ -867,10 +914,53 @@ impl GameServer {
 
             for viewer_entity in chunk_viewers.iter() {
                 if let Some(session) = world.get::<PlayerSession>(viewer_entity) {
-                    let _ = session.send(McpePacket::from(update_packet.clone()));
+                    for update_packet in &update_packets {
+                        let _ = session.send(McpePacket::from(update_packet.clone()));
+                    }
                     let _ = session.send(McpePacket::from(sound_packet.clone()));
                 }
             }
         }
     }
+
+    /// Swap the block at world coordinates without placement effects.
+    ///
+    /// Used for state changes such as a furnace lighting up: the chunk is
+    /// updated, `BlockChanged` observers run and viewers receive the update
+    /// with the batched block broadcast, but no sound is played.
+    pub(super) fn set_block(&mut self, world_id: WorldId, pos: IVec3, block_runtime_id: u32) {
+        let (cx, cz) = world_to_chunk_coords(pos.x, pos.z);
+        let (local_x, local_y, local_z) = world_to_local_coords(pos.x, pos.y, pos.z);
+        let world = self.ecs.world_mut();
+        let Some(chunk_entity) = world
+            .resource::<Worlds>()
+            .get(world_id)
+            .and_then(|chunks| chunks.get_by_coords(cx, cz))
+        else {
+            return;
+        };
+        let Some(mut chunk_data) = world.get_mut::<crate::world::ecs::ChunkData>(chunk_entity)
+        else {
+            return;
+        };
+        let old_block = chunk_data
+            .inner
+            .get_block(local_x, local_y, local_z, LAYER_BLOCK);
+        chunk_data
+            .inner
+            .set_block(local_x, local_y, local_z, LAYER_BLOCK, block_runtime_id);
+
+        world.trigger(BlockChanged {
+            chunk_entity,
+            block_pos: pos,
+            old_block,
+            new_block: block_runtime_id,
+        });
+        world.write_message(BlockBroadcastEvent {
+            chunk_entity,
+            block_pos: pos,
+            new_block: block_runtime_id,
+            layer: LAYER_BLOCK,
+        });
+    }
 }
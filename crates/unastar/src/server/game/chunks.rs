@@ -7,9 +7,9 @@ use tracing::{debug, trace};
 
 use super::GameServer;
 use crate::entity::components::{ChunkRadius, PlayerSession};
-use crate::world::ChunkPos;
 use crate::world::chunk::HeightMapType;
-use crate::world::ecs::{ChunkManager, ChunkViewers};
+use crate::world::ecs::{ChunkBlockEntities, ChunkViewers, WorldId, Worlds, block_entity_packet};
+use crate::world::{BlockEntity, ChunkPos};
 use jolyne::valentine::types::{
     HeightMapDataType, SubChunkEntryWithoutCachingItem, SubChunkEntryWithoutCachingItemResult,
     Vec3I,
@@ -60,6 +60,28 @@ impl GameServer {
                 .map(|s| s.session_id)
                 .unwrap_or(0)
         };
+        let world_id = self
+            .ecs
+            .world()
+            .get::<WorldId>(entity)
+            .copied()
+            .unwrap_or_default();
+        // Requests still in flight from before a dimension change ask for the
+        // old dimension's chunks
+        let dimension = self
+            .ecs
+            .world()
+            .resource::<Worlds>()
+            .get(world_id)
+            .map(|chunk_manager| chunk_manager.dimension());
+        if dimension != Some(req.dimension) {
+            trace!(
+                session_id,
+                dimension = req.dimension,
+                "SubChunkRequest for another dimension"
+            );
+            return;
+        }
 
         let origin = &req.origin;
         let chunk_x = origin.x;
@@ -78,6 +100,8 @@ impl GameServer {
         let mut served_chunks: std::collections::HashSet<(i32, i32)> =
             std::collections::HashSet::new();
         let mut entries = Vec::with_capacity(req.requests.len().min(MAX_SUBCHUNK_REQUESTS));
+        // Non-empty subchunks served, whose block entities follow the response
+        let mut served_subchunks: Vec<(Entity, i32)> = Vec::new();
 
         for offset in req.requests.iter().take(MAX_SUBCHUNK_REQUESTS) {
             let sub_y = origin.y + offset.dy as i32;
@@ -108,10 +132,10 @@ impl GameServer {
             // Get chunk data from ECS ChunkData component (source of truth)
             let chunk_entity = {
                 let world = self.ecs.world();
-                let chunk_manager = world
-                    .get_resource::<ChunkManager>()
-                    .expect("ChunkManager resource must exist");
-                chunk_manager.get_by_coords(chunk_pos.x, chunk_pos.z)
+                world
+                    .resource::<Worlds>()
+                    .get(world_id)
+                    .and_then(|chunk_manager| chunk_manager.get_by_coords(chunk_pos.x, chunk_pos.z))
             };
 
             let Some(chunk_entity) = chunk_entity else {
@@ -132,9 +156,9 @@ impl GameServer {
 
             let (is_empty, subchunk_data, hm_type, hm_data) = {
                 let world = self.ecs.world();
-                let chunk_manager = world
-                    .get_resource::<ChunkManager>()
-                    .expect("ChunkManager must exist");
+                let Some(chunk_manager) = world.resource::<Worlds>().get(world_id) else {
+                    continue;
+                };
 
                 if let Some(chunk_data) = world.get::<crate::world::ecs::ChunkData>(chunk_entity) {
                     let is_empty = chunk_data.inner.is_subchunk_empty(sub_y);
@@ -189,6 +213,7 @@ impl GameServer {
             let result = if is_empty {
                 SubChunkEntryWithoutCachingItemResult::SuccessAllAir
             } else {
+                served_subchunks.push((chunk_entity, sub_y));
                 SubChunkEntryWithoutCachingItemResult::Success
             };
 
@@ -210,7 +235,7 @@ impl GameServer {
         // First collect existing chunk entities, then add viewers
         let chunk_entities: Vec<_> = {
             let world = self.ecs.world();
-            if let Some(chunk_manager) = world.get_resource::<crate::world::ecs::ChunkManager>() {
+            if let Some(chunk_manager) = world.resource::<Worlds>().get(world_id) {
                 served_chunks
                     .iter()
                     .filter_map(|(cx, cz)| chunk_manager.get_by_coords(*cx, *cz))
@@ -245,6 +270,22 @@ impl GameServer {
                     session_id,
                     "Failed to send SubChunk response (channel full or closed)"
                 );
+                return;
+            }
+
+            // Block entities must arrive after the blocks they belong to
+            for (chunk_entity, sub_y) in served_subchunks {
+                let Some(tracked) = world.get::<ChunkBlockEntities>(chunk_entity) else {
+                    continue;
+                };
+                for (pos, block_entity) in tracked.iter() {
+                    if pos.y >> 4 != sub_y {
+                        continue;
+                    }
+                    if let Some(block_entity) = world.get::<BlockEntity>(block_entity) {
+                        let _ = session.send(block_entity_packet(block_entity));
+                    }
+                }
             }
         }
     }
@@ -0,0 +1,373 @@
+//! Melee combat.
+//!
+//! Players attack with an `ItemUseOnEntity` inventory transaction. The
+//! server checks that the target is in reach and in sight, works out the
+//! damage of the held item and sends the hit through the damage pipeline.
+//! Knockback and the hurt animation follow only if the target took damage.
+
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use jolyne::valentine::types::{AnimatePacketActionId, Vec3F};
+use jolyne::valentine::{
+    AnimatePacket, EntityEventPacket, EntityEventPacketEventId, McpePacket, SetEntityMotionPacket,
+};
+use tracing::debug;
+
+use super::GameServer;
+use super::hunger;
+use super::types::ItemRegistryResource;
+use crate::ecs::resources::TickCounter;
+use crate::entity::collision::{Aabb, BlockSource, knockback_velocity, line_of_sight};
+use crate::entity::components::*;
+use crate::entity::damage::{DamageSource, melee_damage};
+use crate::entity::systems::{BASE_KNOCKBACK, apply_damage};
+use crate::item::{EnchantmentType, FIST_DAMAGE};
+use crate::server::broadcast::EntityGrid;
+use crate::world::ecs::{ChunkData, WorldId, Worlds, world_to_chunk_coords, world_to_local_coords};
+
+/// Attack reach of survival and adventure players, from the eyes.
+const SURVIVAL_REACH: f64 = 3.0;
+/// Attack reach of creative players, from the eyes.
+const CREATIVE_REACH: f64 = 6.0;
+/// Extra reach allowed for the target moving while the hit was in flight.
+const REACH_TOLERANCE: f64 = 0.5;
+
+/// Horizontal push added per level of sprint and Knockback.
+const EXTRA_KNOCKBACK: f64 = 0.5;
+/// Vertical lift of the extra push.
+const EXTRA_KNOCKBACK_LIFT: f64 = 0.1;
+
+/// A melee hit that passed validation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Attack {
+    damage: f32,
+    critical: bool,
+    /// Sprint plus the weapon's Knockback level.
+    knockback: i16,
+}
+
+impl GameServer {
+    /// Handle a player attacking the entity with the given runtime ID.
+    pub(super) fn handle_entity_attack(&mut self, attacker: Entity, target_runtime_id: i64) {
+        let world = self.ecs.world_mut();
+        let Some(target) = find_runtime_entity(world, target_runtime_id) else {
+            debug!(runtime_id = target_runtime_id, "Attack on unknown entity");
+            return;
+        };
+        let world_id = world.get::<WorldId>(attacker).copied().unwrap_or_default();
+        if target == attacker || world.get::<WorldId>(target) != Some(&world_id) {
+            return;
+        }
+
+        let blocks = |x, y, z| loaded_block_at(world, world_id, x, y, z);
+        let Some(attack) = check_attack(world, &blocks, attacker, target) else {
+            debug!(attacker = ?attacker, target = ?target, "Rejected attack");
+            return;
+        };
+
+        let source = DamageSource::Attack { attacker };
+        if apply_damage(world, target, source, attack.damage).is_none() {
+            return;
+        }
+        hunger::exhaust(world, attacker, hunger::ATTACK_EXHAUSTION);
+        self.knock_back(attacker, target, attack.knockback);
+        self.broadcast_hurt(target, attack.critical);
+    }
+
+    /// Push the target away from the attacker.
+    ///
+    /// Mobs already got the base knockback from the damage event and only
+    /// need the extra push. Players move themselves, so they are sent the
+    /// whole velocity.
+    pub(super) fn knock_back(&mut self, attacker: Entity, target: Entity, level: i16) {
+        let world = self.ecs.world_mut();
+        let Some(source) = world.get::<Position>(attacker).map(|pos| pos.0) else {
+            return;
+        };
+        let yaw = world
+            .get::<Rotation>(attacker)
+            .map_or(0.0, |rot| (rot.yaw as f64).to_radians());
+        let extra = if level > 0 {
+            let strength = EXTRA_KNOCKBACK * level as f64;
+            DVec3::new(
+                -yaw.sin() * strength,
+                EXTRA_KNOCKBACK_LIFT,
+                yaw.cos() * strength,
+            )
+        } else {
+            DVec3::ZERO
+        };
+
+        if let Some(mut velocity) = world.get_mut::<Velocity>(target) {
+            velocity.0 += extra;
+            return;
+        }
+
+        let tick = world
+            .get_resource::<TickCounter>()
+            .map_or(0, |tick| tick.current as i64);
+        let Some(target_position) = world.get::<Position>(target).map(|pos| pos.0) else {
+            return;
+        };
+        let Some(runtime_id) = world.get::<RuntimeEntityId>(target).map(|rid| rid.0) else {
+            return;
+        };
+        let Some(session) = world.get::<PlayerSession>(target) else {
+            return;
+        };
+        let on_ground = world
+            .get::<FallDistance>(target)
+            .is_none_or(|fall| fall.0 <= 0.0);
+        let velocity = knockback_velocity(
+            DVec3::ZERO,
+            source,
+            target_position,
+            BASE_KNOCKBACK,
+            on_ground,
+        ) + extra;
+        let _ = session.send(McpePacket::from(SetEntityMotionPacket {
+            runtime_entity_id: runtime_id,
+            velocity: Vec3F {
+                x: velocity.x as f32,
+                y: velocity.y as f32,
+                z: velocity.z as f32,
+            },
+            tick,
+        }));
+    }
+
+    /// Show the hurt animation, and critical hit particles, to players near the target.
+    pub(super) fn broadcast_hurt(&self, target: Entity, critical: bool) {
+        let world = self.ecs.world();
+        let Some(runtime_id) = world
+            .get::<RuntimeEntityId>(target)
+            .map(|rid| rid.0)
+            .or_else(|| world.get::<RuntimeId>(target).map(|rid| rid.0))
+        else {
+            return;
+        };
+        let (Some(position), Some(grid)) = (
+            world.get::<Position>(target),
+            world.get_resource::<EntityGrid>(),
+        ) else {
+            return;
+        };
+        let world_id = world.get::<WorldId>(target).copied().unwrap_or_default();
+
+        let mut packets = vec![McpePacket::from(EntityEventPacket {
+            runtime_entity_id: runtime_id,
+            event_id: EntityEventPacketEventId::HurtAnimation,
+            data: 0,
+        })];
+        if critical {
+            packets.push(McpePacket::from(AnimatePacket {
+                action_id: AnimatePacketActionId::CriticalHit,
+                runtime_entity_id: runtime_id,
+                data: 0.0,
+                swing_source: None,
+            }));
+        }
+
+        let chunk = (
+            position.0.x.floor() as i32 >> 4,
+            position.0.z.floor() as i32 >> 4,
+        );
+        for viewer in grid.get_neighbors(world_id, chunk, 1) {
+            if let Some(session) = world.get::<PlayerSession>(viewer) {
+                for packet in &packets {
+                    let _ = session.send(packet.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Find the entity with a network runtime ID, player or not.
+fn find_runtime_entity(world: &World, runtime_id: i64) -> Option<Entity> {
+    world.get_resource::<RuntimeIdIndex>()?.get(runtime_id)
+}
+
+/// Look up a block in the loaded ECS chunks of a world.
+pub(super) fn loaded_block_at(
+    world: &World,
+    world_id: WorldId,
+    x: i32,
+    y: i32,
+    z: i32,
+) -> Option<u32> {
+    let (cx, cz) = world_to_chunk_coords(x, z);
+    let chunk = world
+        .get_resource::<Worlds>()?
+        .get(world_id)?
+        .get_by_coords(cx, cz)?;
+    let data = world.get::<ChunkData>(chunk)?;
+    let (lx, ly, lz) = world_to_local_coords(x, y, z);
+    Some(data.inner.get_block(lx, ly, lz, 0))
+}
+
+/// World-space box of an entity. Player positions are at eye level.
+fn entity_box(world: &World, entity: Entity, position: DVec3) -> Aabb {
+    if world.get::<Player>(entity).is_some() {
+        return Hitbox::PLAYER.aabb_at(position - DVec3::Y * PLAYER_EYE_HEIGHT);
+    }
+    world
+        .get::<Hitbox>(entity)
+        .copied()
+        .unwrap_or_default()
+        .aabb_at(position)
+}
+
+/// Validate a player's attack and work out its damage.
+///
+/// Returns `None` if either side is dead, the attacker is a spectator, or
+/// the target is out of reach or behind blocks.
+fn check_attack(
+    world: &World,
+    blocks: &impl BlockSource,
+    attacker: Entity,
+    target: Entity,
+) -> Option<Attack> {
+    let player = world.get_entity(attacker).ok()?;
+    let victim = world.get_entity(target).ok()?;
+    if player.contains::<Dead>() || victim.contains::<Dead>() || !victim.contains::<Health>() {
+        return None;
+    }
+    let mode = player.get::<GameMode>().copied().unwrap_or_default();
+    if mode == GameMode::Spectator {
+        return None;
+    }
+
+    let eye = player.get::<Position>()?.0;
+    let target_box = entity_box(world, target, victim.get::<Position>()?.0);
+    let closest = target_box.closest_point(eye);
+    let reach = match mode {
+        GameMode::Creative => CREATIVE_REACH,
+        _ => SURVIVAL_REACH,
+    };
+    if eye.distance(closest) > reach + REACH_TOLERANCE {
+        return None;
+    }
+    let centre = (target_box.min + target_box.max) / 2.0;
+    if !line_of_sight(blocks, eye, closest) && !line_of_sight(blocks, eye, centre) {
+        return None;
+    }
+
+    let held = player
+        .get::<HeldSlot>()
+        .zip(player.get::<MainInventory>())
+        .and_then(|(slot, inventory)| inventory.0.item(slot.0 as usize))
+        .filter(|item| !item.is_empty());
+    let base = held
+        .and_then(|item| {
+            let items = world.get_resource::<ItemRegistryResource>()?;
+            items
+                .0
+                .get_by_name(&item.item_id)
+                .map(|entry| entry.attack_damage)
+        })
+        .unwrap_or(FIST_DAMAGE);
+    let enchantment = |kind| held.map_or(0, |item| item.enchantment_level(kind));
+
+    let idle = PlayerState::default();
+    let state = player.get::<PlayerState>().unwrap_or(&idle);
+    let effects = player.get::<Effects>();
+    let amplifier = |kind| effects.and_then(|e| e.get(kind)).map(|effect| effect.level);
+    let falling = player
+        .get::<FallDistance>()
+        .is_some_and(|fall| fall.0 > 0.0);
+    let critical = falling
+        && !state.flying
+        && !state.gliding
+        && !state.swimming
+        && amplifier(EffectType::BLINDNESS).is_none();
+
+    Some(Attack {
+        damage: melee_damage(
+            base,
+            enchantment(EnchantmentType::SHARPNESS),
+            amplifier(EffectType::STRENGTH),
+            amplifier(EffectType::WEAKNESS),
+            critical,
+        ),
+        critical,
+        knockback: i16::from(state.sprinting) + enchantment(EnchantmentType::KNOCKBACK),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::ItemStack;
+    use crate::world::chunk::blocks;
+
+    /// Flat stone floor with its top face at y = 64, and a wall at x = 5.
+    fn walled(x: i32, y: i32, _z: i32) -> Option<u32> {
+        Some(if y < 64 || (x == 5 && y < 70) {
+            *blocks::STONE
+        } else {
+            *blocks::AIR
+        })
+    }
+
+    fn spawn_player(world: &mut World, feet: DVec3) -> Entity {
+        world
+            .spawn((
+                Player,
+                Position(feet + DVec3::Y * PLAYER_EYE_HEIGHT),
+                Health::new(20.0),
+                GameMode::Survival,
+                PlayerState::default(),
+                FallDistance::default(),
+                HeldSlot(0),
+                MainInventory::default(),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn test_reach_and_line_of_sight() {
+        let mut world = World::new();
+        let attacker = spawn_player(&mut world, DVec3::new(0.5, 64.0, 0.5));
+        let near = spawn_player(&mut world, DVec3::new(3.0, 64.0, 0.5));
+        let far = spawn_player(&mut world, DVec3::new(4.5, 64.0, 0.5));
+        let hidden = spawn_player(&mut world, DVec3::new(6.5, 64.0, 0.5));
+
+        let attack = check_attack(&world, &walled, attacker, near).unwrap();
+        assert_eq!(attack.damage, FIST_DAMAGE);
+        assert!(check_attack(&world, &walled, attacker, far).is_none());
+
+        world.entity_mut(attacker).insert(GameMode::Creative);
+        assert!(check_attack(&world, &walled, attacker, far).is_some());
+        assert!(check_attack(&world, &walled, attacker, hidden).is_none());
+
+        world.entity_mut(attacker).insert(GameMode::Spectator);
+        assert!(check_attack(&world, &walled, attacker, near).is_none());
+    }
+
+    #[test]
+    fn test_critical_sprint_hit() {
+        let mut world = World::new();
+        let attacker = spawn_player(&mut world, DVec3::new(0.5, 64.5, 0.5));
+        let target = spawn_player(&mut world, DVec3::new(2.0, 64.0, 0.5));
+        {
+            let mut inventory = world.get_mut::<MainInventory>(attacker).unwrap();
+            let _ = inventory
+                .0
+                .set_item(0, ItemStack::new("minecraft:diamond_sword", 1));
+        }
+        world.get_mut::<FallDistance>(attacker).unwrap().0 = 0.5;
+        world.get_mut::<PlayerState>(attacker).unwrap().sprinting = true;
+
+        // Without an item registry the sword hits like a fist
+        let attack = check_attack(&world, &walled, attacker, target).unwrap();
+        assert!(attack.critical);
+        assert_eq!(attack.damage, FIST_DAMAGE * 1.5);
+        assert_eq!(attack.knockback, 1);
+
+        let mut items = crate::registry::ItemRegistry::new();
+        items.load_vanilla();
+        world.insert_resource(ItemRegistryResource(std::sync::Arc::new(items)));
+        let attack = check_attack(&world, &walled, attacker, target).unwrap();
+        assert_eq!(attack.damage, 12.0);
+    }
+}
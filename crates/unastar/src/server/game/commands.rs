@@ -2,21 +2,16 @@
 //!
 //! Contains command request processing and output sending.
 
-use glam::DVec3;
 use tracing::trace;
 
 use super::GameServer;
+use super::join::abilities_packet;
 use super::types::{SessionEntityMap, system_text};
-use crate::command::{CommandOutput, CommandParseError, parse_command_line};
-use crate::entity::components::transform::Position;
-use crate::entity::components::{PlayerSession, RuntimeEntityId};
+use crate::command::{CommandOutput, CommandRegistry, CommandSender};
+use crate::entity::components::PlayerSession;
 use crate::network::SessionId;
-use crate::world::ecs::ChunkLoader;
-use jolyne::valentine::types::{LegacyEntityType, Vec3F};
-use jolyne::valentine::{
-    CommandRequestPacket, McpePacket, MovePlayerPacket, MovePlayerPacketMode,
-    MovePlayerPacketTeleport, MovePlayerPacketTeleportCause,
-};
+use bevy_ecs::prelude::*;
+use jolyne::valentine::{CommandRequestPacket, McpePacket};
 
 impl GameServer {
     /// Handle a command request from a player.
@@ -35,174 +30,27 @@ impl GameServer {
             return;
         }
 
-        let invocation = match parse_command_line(command_line) {
-            Ok(inv) => inv,
-            Err(CommandParseError::Empty) => return,
-            Err(e) => {
-                self.send_command_output(
-                    session_id,
-                    CommandOutput {
-                        messages: vec![],
-                        errors: vec![e.to_string()],
-                    },
-                );
-                return;
-            }
-        };
-
-        // Handle teleport command directly (needs server access)
-        let name_lower = invocation.name.to_ascii_lowercase();
-        if name_lower == "tp" || name_lower == "teleport" {
-            self.handle_teleport_command(session_id, invocation.args.rest());
-            return;
-        }
-
-        let Some(_command) = self.commands.find(&invocation.name) else {
-            self.send_command_output(
-                session_id,
-                CommandOutput {
-                    messages: vec![],
-                    errors: vec![format!("Unknown command: {}", invocation.name)],
-                },
-            );
+        let Some(entity) = self
+            .ecs
+            .world()
+            .get_resource::<SessionEntityMap>()
+            .and_then(|map| map.get(session_id))
+        else {
             return;
         };
-
-        // Execute command - need to pass self, which is complex
-        // For now, just acknowledge
-        let output = CommandOutput {
-            messages: vec![format!("Command '{}' executed", invocation.name)],
-            errors: vec![],
+        let sender = CommandSender::Player {
+            entity,
+            session: session_id,
         };
+        let output = self
+            .commands
+            .execute(self.ecs.world_mut(), sender, command_line);
 
         if !output.is_empty() {
             self.send_command_output(session_id, output);
         }
     }
 
-    /// Handle teleport command: /tp <x> <y> <z>
-    fn handle_teleport_command(&mut self, session_id: SessionId, args: &[String]) {
-        // Parse coordinates
-        if args.len() < 3 {
-            self.send_command_output(
-                session_id,
-                CommandOutput {
-                    messages: vec![],
-                    errors: vec!["Usage: /tp <x> <y> <z>".to_string()],
-                },
-            );
-            return;
-        }
-
-        let x: f64 = match args[0].parse() {
-            Ok(v) => v,
-            Err(_) => {
-                self.send_command_output(
-                    session_id,
-                    CommandOutput {
-                        messages: vec![],
-                        errors: vec![format!("Invalid x coordinate: {}", args[0])],
-                    },
-                );
-                return;
-            }
-        };
-
-        let y: f64 = match args[1].parse() {
-            Ok(v) => v,
-            Err(_) => {
-                self.send_command_output(
-                    session_id,
-                    CommandOutput {
-                        messages: vec![],
-                        errors: vec![format!("Invalid y coordinate: {}", args[1])],
-                    },
-                );
-                return;
-            }
-        };
-
-        let z: f64 = match args[2].parse() {
-            Ok(v) => v,
-            Err(_) => {
-                self.send_command_output(
-                    session_id,
-                    CommandOutput {
-                        messages: vec![],
-                        errors: vec![format!("Invalid z coordinate: {}", args[2])],
-                    },
-                );
-                return;
-            }
-        };
-
-        // Get player entity and runtime ID
-        let (entity, runtime_id) = {
-            let session_map = match self.ecs.world().get_resource::<SessionEntityMap>() {
-                Some(map) => map,
-                None => return,
-            };
-            let entity = match session_map.get(session_id) {
-                Some(e) => e,
-                None => return,
-            };
-            let runtime_id = match self.ecs.world().get::<RuntimeEntityId>(entity) {
-                Some(id) => id.0,
-                None => return,
-            };
-            (entity, runtime_id)
-        };
-
-        // Update position in ECS
-        {
-            let world = self.ecs.world_mut();
-            if let Some(mut position) = world.get_mut::<Position>(entity) {
-                position.0 = DVec3::new(x, y, z);
-            }
-
-            // Update chunk loader to trigger chunk loading at new position
-            if let Some(mut chunk_loader) = world.get_mut::<ChunkLoader>(entity) {
-                let new_chunk_x = (x / 16.0).floor() as i32;
-                let new_chunk_z = (z / 16.0).floor() as i32;
-                chunk_loader.move_to(new_chunk_x, new_chunk_z);
-            }
-        }
-
-        // Build teleport packet
-        let packet = MovePlayerPacket {
-            runtime_id: runtime_id as i32,
-            position: Vec3F {
-                x: x as f32,
-                y: y as f32,
-                z: z as f32,
-            },
-            pitch: 0.0,
-            yaw: 0.0,
-            head_yaw: 0.0,
-            mode: MovePlayerPacketMode::Teleport,
-            on_ground: false,
-            ridden_runtime_id: 0,
-            teleport: Some(MovePlayerPacketTeleport {
-                cause: MovePlayerPacketTeleportCause::Command,
-                source_entity_type: LegacyEntityType::Player,
-            }),
-            tick: self.current_tick as i64,
-        };
-
-        // Send teleport packet to client (get session after mutable borrow is done)
-        if let Some(session) = self.ecs.world().get::<PlayerSession>(entity) {
-            let _ = session.send(McpePacket::from(packet));
-        }
-
-        self.send_command_output(
-            session_id,
-            CommandOutput {
-                messages: vec![format!("Teleported to {:.1}, {:.1}, {:.1}", x, y, z)],
-                errors: vec![],
-            },
-        );
-    }
-
     /// Send command output messages to a player.
     pub(super) fn send_command_output(&self, session_id: SessionId, output: CommandOutput) {
         let session_map = match self.ecs.world().get_resource::<SessionEntityMap>() {
@@ -226,3 +74,19 @@ impl GameServer {
         }
     }
 }
+
+/// Resend a player's abilities and command list after their permissions
+/// change, so the client shows the right commands and operator controls.
+pub fn sync_permissions(world: &World, commands: &CommandRegistry, entity: Entity) {
+    let Some(session) = world.get::<PlayerSession>(entity) else {
+        return;
+    };
+    let sender = CommandSender::Player {
+        entity,
+        session: session.session_id,
+    };
+    let _ = session.send(McpePacket::from(abilities_packet(world, entity)));
+    let _ = session.send(McpePacket::from(
+        commands.to_packet(|command| sender.can_use(world, command)),
+    ));
+}
@@ -0,0 +1,741 @@
+//! World containers backed by block entities.
+//!
+//! Handles the container window lifecycle for chests, furnaces and hoppers
+//! (crafting tables and stonecutters open a window with no block entity):
+//! - `ContainerOpen` + `InventoryContent` when a player uses the block
+//! - `OpenContainer` on the player tracks which container they are viewing
+//! - `InventorySlot` updates to every other viewer when a slot changes
+//! - `ContainerClose` when the player closes the window or the block is broken
+//!
+//! Container windows always use `WindowId::First`; a player has at most one
+//! block container open at a time.
+
+use bevy_ecs::entity::Entity;
+use bevy_ecs::world::World;
+use glam::IVec3;
+use jolyne::valentine::types::{
+    BlockCoordinates, BlockEventPacketType, ContainerSlotType, FullContainerName, Item,
+    ItemContent, ItemContentExtra, WindowId, WindowType,
+};
+use jolyne::valentine::{
+    BlockEventPacket, ContainerClosePacket, ContainerOpenPacket, InventoryContentPacket,
+    InventorySlotPacket, McpePacket, WindowIdVarint,
+};
+use tracing::debug;
+
+use super::GameServer;
+use crate::entity::components::{
+    ContainerType, ItemStackRequestState, MainInventory, OpenContainer, PlayerSession, PlayerState,
+    UiInventory,
+};
+use crate::item::ItemStack;
+use crate::registry::block::string_id;
+use crate::world::ecs::{
+    BlockEntityWorldExt, ChunkData, ChunkStateFlags, ChunkViewers, WorldId, world_to_local_coords,
+};
+use crate::world::{BlockEntity, BlockEntityData};
+
+/// Window ID used for all block containers.
+const CONTAINER_WINDOW_ID: WindowId = WindowId::First;
+
+impl GameServer {
+    /// Open the container at `pos` for a player.
+    ///
+    /// Returns `false` if there is no container block entity at `pos`, or the
+    /// player is sneaking (sneak-click places against the container instead).
+    pub(super) fn try_open_container(&mut self, entity: Entity, pos: IVec3) -> bool {
+        let world = self.ecs.world();
+        if world.get::<PlayerState>(entity).is_some_and(|s| s.sneaking) {
+            return false;
+        }
+
+        let world_id = WorldId::of(world, entity);
+        let block_entities = container_block_entities(world, world_id, pos);
+        let Some(&lead) = block_entities.first() else {
+            return self.try_open_workstation(entity, pos);
+        };
+        let Some(lead) = world.get::<BlockEntity>(lead) else {
+            return false;
+        };
+        let Some(container_type) = container_type(&lead.data, block_entities.len() > 1) else {
+            return false;
+        };
+        let lead_pos = lead.position;
+
+        if self.ecs.world().get::<OpenContainer>(entity).is_some() {
+            self.close_container(entity, true);
+        }
+
+        let world = self.ecs.world_mut();
+        world.entity_mut(entity).insert(OpenContainer {
+            position: Some((lead_pos.x, lead_pos.y, lead_pos.z)),
+            window_id: CONTAINER_WINDOW_ID as u8,
+            container_type,
+        });
+
+        if let Some(session) = world.get::<PlayerSession>(entity) {
+            let _ = session.send(McpePacket::from(ContainerOpenPacket {
+                window_id: CONTAINER_WINDOW_ID,
+                window_type: window_type(container_type),
+                coordinates: BlockCoordinates {
+                    x: pos.x,
+                    y: pos.y,
+                    z: pos.z,
+                },
+                runtime_entity_id: -1,
+            }));
+        }
+        self.send_container_contents(entity, lead_pos);
+        self.update_container_lid(world_id, lead_pos);
+
+        debug!(entity = ?entity, pos = ?lead_pos, ?container_type, "Opened container");
+        true
+    }
+
+    /// Open a crafting table or stonecutter window at `pos`.
+    ///
+    /// These have no block entity; their slots live in the player's
+    /// `UiInventory` and are crafted from via item stack requests.
+    fn try_open_workstation(&mut self, entity: Entity, pos: IVec3) -> bool {
+        let world = self.ecs.world();
+        let Some(container_type) = workstation_type(world, WorldId::of(world, entity), pos) else {
+            return false;
+        };
+
+        if self.ecs.world().get::<OpenContainer>(entity).is_some() {
+            self.close_container(entity, true);
+        }
+
+        let world = self.ecs.world_mut();
+        world.entity_mut(entity).insert(OpenContainer {
+            position: Some((pos.x, pos.y, pos.z)),
+            window_id: CONTAINER_WINDOW_ID as u8,
+            container_type,
+        });
+
+        if let Some(session) = world.get::<PlayerSession>(entity) {
+            let _ = session.send(McpePacket::from(ContainerOpenPacket {
+                window_id: CONTAINER_WINDOW_ID,
+                window_type: window_type(container_type),
+                coordinates: BlockCoordinates {
+                    x: pos.x,
+                    y: pos.y,
+                    z: pos.z,
+                },
+                runtime_entity_id: -1,
+            }));
+        }
+
+        debug!(entity = ?entity, pos = ?pos, ?container_type, "Opened workstation");
+        true
+    }
+
+    /// Move items left in the crafting grid back into the player's inventory.
+    ///
+    /// The client clears its grid when the window closes, so anything still
+    /// in it would otherwise vanish. Items that don't fit are discarded.
+    pub(super) fn return_crafting_items(&mut self, entity: Entity) {
+        let world = self.ecs.world_mut();
+        let Some(mut ui) = world.get_mut::<UiInventory>(entity) else {
+            return;
+        };
+        let leftovers: Vec<ItemStack> = (0..UiInventory::SIZE)
+            .filter(|&slot| UiInventory::is_crafting_input(slot))
+            .filter_map(|slot| ui.0.set_item(slot, ItemStack::empty()).ok())
+            .filter(|item| !item.is_empty())
+            .collect();
+        if leftovers.is_empty() {
+            return;
+        }
+
+        let Some(mut inventory) = world.get_mut::<MainInventory>(entity) else {
+            return;
+        };
+        let before = inventory.0.slots().to_vec();
+        for item in leftovers {
+            let (_, lost) = inventory.0.add_item(item);
+            if !lost.is_empty() {
+                debug!(entity = ?entity, item = %lost.item_id, count = lost.count, "Crafting grid item did not fit");
+            }
+        }
+        self.send_main_inventory_changes(entity, &before);
+    }
+
+    /// Send the main inventory slots that differ from `before` to the player.
+    pub(super) fn send_main_inventory_changes(&mut self, entity: Entity, before: &[ItemStack]) {
+        let Some(inventory) = self.ecs.world().get::<MainInventory>(entity) else {
+            return;
+        };
+        let changed: Vec<(usize, ItemStack)> = inventory
+            .0
+            .slots()
+            .iter()
+            .enumerate()
+            .filter(|(slot, item)| before.get(*slot) != Some(*item))
+            .map(|(slot, item)| (slot, item.clone()))
+            .collect();
+
+        for (slot, item) in changed {
+            let network_item = self.network_item_for(entity, &item);
+            if let Some(session) = self.ecs.world().get::<PlayerSession>(entity) {
+                let _ = session.send(McpePacket::from(InventorySlotPacket {
+                    window_id: WindowIdVarint::Inventory,
+                    slot: slot as i32,
+                    container: FullContainerName {
+                        container_id: ContainerSlotType::HotbarAndInventory,
+                        dynamic_container_id: None,
+                    },
+                    storage_item: Item::default(),
+                    item: network_item,
+                }));
+            }
+        }
+    }
+
+    /// Close a player's open block container, if any.
+    ///
+    /// `server` is `true` when the server forces the window shut (block broken,
+    /// another container opened) rather than acknowledging a client close.
+    pub(super) fn close_container(&mut self, entity: Entity, server: bool) {
+        let world = self.ecs.world_mut();
+        let Some(open) = world.entity_mut(entity).take::<OpenContainer>() else {
+            return;
+        };
+
+        if let Some(session) = world.get::<PlayerSession>(entity) {
+            let _ = session.send(McpePacket::from(ContainerClosePacket {
+                window_id: CONTAINER_WINDOW_ID,
+                window_type: window_type(open.container_type),
+                server,
+            }));
+        }
+
+        if matches!(
+            open.container_type,
+            ContainerType::CraftingTable | ContainerType::Stonecutter
+        ) {
+            self.return_crafting_items(entity);
+        } else if let Some((x, y, z)) = open.position {
+            let world_id = WorldId::of(self.ecs.world(), entity);
+            self.update_container_lid(world_id, IVec3::new(x, y, z));
+        }
+        debug!(entity = ?entity, pos = ?open.position, "Closed container");
+    }
+
+    /// Force-close every window viewing the container at `pos` in a world.
+    ///
+    /// Called before the container block is removed.
+    pub(super) fn close_containers_at(&mut self, world_id: WorldId, pos: IVec3) {
+        let world = self.ecs.world();
+        let lead_pos = container_block_entities(world, world_id, pos)
+            .first()
+            .and_then(|&e| world.get::<BlockEntity>(e))
+            .map_or(pos, |b| b.position);
+
+        for viewer in container_viewers(self.ecs.world_mut(), world_id, lead_pos) {
+            self.close_container(viewer, true);
+        }
+    }
+
+    /// Send the full contents of the container at `pos` to a viewer.
+    ///
+    /// Containers whose slots share one slot type are sent in a single
+    /// `InventoryContent`; furnaces, whose slots each have their own type,
+    /// are sent slot by slot.
+    fn send_container_contents(&mut self, viewer: Entity, pos: IVec3) {
+        let world = self.ecs.world();
+        let block_entities = container_block_entities(world, WorldId::of(world, viewer), pos);
+        let Some(container_type) = world.get::<OpenContainer>(viewer).map(|o| o.container_type)
+        else {
+            return;
+        };
+        let items: Vec<ItemStack> = block_entities
+            .iter()
+            .filter_map(|&e| world.get::<BlockEntity>(e)?.data.items())
+            .flatten()
+            .cloned()
+            .collect();
+
+        let names: Vec<FullContainerName> = (0..items.len())
+            .map(|slot| container_name(container_type, slot))
+            .collect();
+        if names.windows(2).any(|pair| pair[0] != pair[1]) {
+            for (slot, item) in items.iter().enumerate() {
+                let network_item = self.network_item_for(viewer, item);
+                if let Some(session) = self.ecs.world().get::<PlayerSession>(viewer) {
+                    let _ = session.send(McpePacket::from(InventorySlotPacket {
+                        window_id: WindowIdVarint::First,
+                        slot: slot as i32,
+                        container: names[slot].clone(),
+                        storage_item: Item::default(),
+                        item: network_item,
+                    }));
+                }
+            }
+            return;
+        }
+
+        let input = items
+            .iter()
+            .map(|item| self.network_item_for(viewer, item))
+            .collect();
+
+        if let Some(session) = self.ecs.world().get::<PlayerSession>(viewer) {
+            let _ = session.send(McpePacket::from(InventoryContentPacket {
+                window_id: WindowIdVarint::First,
+                input,
+                container: container_name(container_type, 0),
+                storage_item: Item::default(),
+            }));
+        }
+    }
+
+    /// Send changed container slots to every viewer except `source`.
+    ///
+    /// A player source already predicted the change and received it in the
+    /// `ItemStackResponse`; server-side changes (smelting) have no source.
+    /// Marks the container's chunk dirty.
+    pub(super) fn sync_container_slots(
+        &mut self,
+        world_id: WorldId,
+        pos: IVec3,
+        slots: &[usize],
+        source: Option<Entity>,
+    ) {
+        if slots.is_empty() {
+            return;
+        }
+
+        let world = self.ecs.world_mut();
+        let block_entities = container_block_entities(world, world_id, pos);
+        for &block_entity in &block_entities {
+            let Some(block_pos) = world.get::<BlockEntity>(block_entity).map(|b| b.position) else {
+                continue;
+            };
+            if let Some(chunk_entity) = world.chunk_entity_at(world_id, block_pos)
+                && let Some(mut flags) = world.get_mut::<ChunkStateFlags>(chunk_entity)
+            {
+                flags.mark_dirty();
+            }
+        }
+
+        let items: Vec<(usize, ItemStack)> = slots
+            .iter()
+            .filter_map(|&slot| Some((slot, container_item(world, &block_entities, slot)?)))
+            .collect();
+
+        for viewer in container_viewers(world, world_id, pos) {
+            if Some(viewer) == source {
+                continue;
+            }
+            let Some(container_type) = self
+                .ecs
+                .world()
+                .get::<OpenContainer>(viewer)
+                .map(|o| o.container_type)
+            else {
+                continue;
+            };
+            for (slot, item) in &items {
+                let network_item = self.network_item_for(viewer, item);
+                if let Some(session) = self.ecs.world().get::<PlayerSession>(viewer) {
+                    let _ = session.send(McpePacket::from(InventorySlotPacket {
+                        window_id: WindowIdVarint::First,
+                        slot: *slot as i32,
+                        container: container_name(container_type, *slot),
+                        storage_item: Item::default(),
+                        item: network_item,
+                    }));
+                }
+            }
+        }
+    }
+
+    /// Animate the chest lid at `pos` for everyone nearby.
+    ///
+    /// The lid stays open while at least one player is viewing the chest.
+    fn update_container_lid(&mut self, world_id: WorldId, pos: IVec3) {
+        let world = self.ecs.world_mut();
+        let block_entities = container_block_entities(world, world_id, pos);
+        let is_chest = block_entities.first().is_some_and(|&e| {
+            matches!(
+                world.get::<BlockEntity>(e).map(|b| &b.data),
+                Some(BlockEntityData::Chest(_))
+            )
+        });
+        if !is_chest {
+            return;
+        }
+
+        let viewers = container_viewers(world, world_id, pos).len() as i32;
+        for &block_entity in &block_entities {
+            let Some(block_pos) = world.get::<BlockEntity>(block_entity).map(|b| b.position) else {
+                continue;
+            };
+            let packet = McpePacket::from(BlockEventPacket {
+                position: BlockCoordinates {
+                    x: block_pos.x,
+                    y: block_pos.y,
+                    z: block_pos.z,
+                },
+                type_: BlockEventPacketType::ChangeState,
+                data: viewers,
+            });
+            let Some(chunk_entity) = world.chunk_entity_at(world_id, block_pos) else {
+                continue;
+            };
+            let Some(chunk_viewers) = world.get::<ChunkViewers>(chunk_entity) else {
+                continue;
+            };
+            for player in chunk_viewers.iter() {
+                if let Some(session) = world.get::<PlayerSession>(player) {
+                    let _ = session.send(packet.clone());
+                }
+            }
+        }
+    }
+
+    /// Convert an item stack to its network form, allocating a stack network
+    /// ID from the viewer's `ItemStackRequestState`.
+    pub(super) fn network_item_for(&mut self, viewer: Entity, item: &ItemStack) -> Item {
+        if item.is_empty() {
+            return Item::default();
+        }
+        let stack_id = self
+            .ecs
+            .world_mut()
+            .get_mut::<ItemStackRequestState>(viewer)
+            .map_or(0, |mut state| state.next_id());
+        self.network_item(item, stack_id)
+    }
+
+    /// Convert an item stack to its network form.
+    pub(super) fn network_item(&self, item: &ItemStack, stack_id: i32) -> Item {
+        if item.is_empty() {
+            return Item::default();
+        }
+        let Some(entry) = self.items.get_by_name(&item.item_id) else {
+            return Item::default();
+        };
+        let block_runtime_id = if item.runtime_id != 0 {
+            item.runtime_id
+        } else {
+            self.blocks
+                .get_by_name(&item.item_id)
+                .map_or(0, |block| block.default_state_id as i32)
+        };
+
+        Item {
+            network_id: entry.id as i32,
+            content: Some(Box::new(ItemContent {
+                count: item.count as u16,
+                metadata: item.damage as i32,
+                has_stack_id: u8::from(stack_id != 0),
+                stack_id: (stack_id != 0).then_some(stack_id),
+                block_runtime_id,
+                extra: ItemContentExtra::Default(Default::default()),
+            })),
+        }
+    }
+}
+
+/// The block entities backing the container at `pos`, in slot order.
+///
+/// A paired chest yields the lead half first, so its slots come before the
+/// partner's. Returns an empty list if there is no container at `pos`.
+pub(super) fn container_block_entities(
+    world: &World,
+    world_id: WorldId,
+    pos: IVec3,
+) -> Vec<Entity> {
+    let Some(entity) = world.block_entity_at(world_id, pos) else {
+        return Vec::new();
+    };
+    let Some(block_entity) = world.get::<BlockEntity>(entity) else {
+        return Vec::new();
+    };
+    if block_entity.data.items().is_none() {
+        return Vec::new();
+    }
+
+    if let BlockEntityData::Chest(chest) = &block_entity.data
+        && let Some((px, pz)) = chest.pair
+        && let Some(partner) = world.block_entity_at(world_id, IVec3::new(px, pos.y, pz))
+        && matches!(
+            world.get::<BlockEntity>(partner).map(|b| &b.data),
+            Some(BlockEntityData::Chest(_))
+        )
+    {
+        return if chest.pair_lead {
+            vec![entity, partner]
+        } else {
+            vec![partner, entity]
+        };
+    }
+    vec![entity]
+}
+
+/// Read a container slot, spanning both halves of a double chest.
+pub(super) fn container_item(
+    world: &World,
+    block_entities: &[Entity],
+    slot: usize,
+) -> Option<ItemStack> {
+    let mut slot = slot;
+    for &entity in block_entities {
+        let items = world.get::<BlockEntity>(entity)?.data.items()?;
+        if slot < items.len() {
+            return Some(items[slot].clone());
+        }
+        slot -= items.len();
+    }
+    None
+}
+
+/// Write a container slot, spanning both halves of a double chest.
+///
+/// Returns `false` if the slot is out of range.
+pub(super) fn set_container_item(
+    world: &mut World,
+    block_entities: &[Entity],
+    slot: usize,
+    item: ItemStack,
+) -> bool {
+    let mut slot = slot;
+    for &entity in block_entities {
+        let Some(mut block_entity) = world.get_mut::<BlockEntity>(entity) else {
+            return false;
+        };
+        let Some(items) = block_entity.data.items_mut() else {
+            return false;
+        };
+        if slot < items.len() {
+            items[slot] = item;
+            return true;
+        }
+        slot -= items.len();
+    }
+    false
+}
+
+/// All players in a world with the container at `pos` open.
+///
+/// `pos` is the lead position stored in `OpenContainer`.
+pub(super) fn container_viewers(world: &mut World, world_id: WorldId, pos: IVec3) -> Vec<Entity> {
+    let target = Some((pos.x, pos.y, pos.z));
+    let mut query = world.query::<(Entity, &OpenContainer, Option<&WorldId>)>();
+    query
+        .iter(world)
+        .filter(|(_, open, world)| {
+            open.position == target && world.copied().unwrap_or_default() == world_id
+        })
+        .map(|(entity, _, _)| entity)
+        .collect()
+}
+
+/// Container type for a block entity, if it can be opened.
+pub fn container_type(data: &BlockEntityData, paired: bool) -> Option<ContainerType> {
+    match data {
+        BlockEntityData::Chest(_) if paired => Some(ContainerType::DoubleChest),
+        BlockEntityData::Chest(_) => Some(ContainerType::Chest),
+        BlockEntityData::Furnace(_) => Some(ContainerType::Furnace),
+        BlockEntityData::Hopper(_) => Some(ContainerType::Hopper),
+        _ => None,
+    }
+}
+
+/// Window type of a block-entity-less workstation at `pos`.
+fn workstation_type(world: &World, world_id: WorldId, pos: IVec3) -> Option<ContainerType> {
+    let chunk_entity = world.chunk_entity_at(world_id, pos)?;
+    let chunk = world.get::<ChunkData>(chunk_entity)?;
+    let (x, y, z) = world_to_local_coords(pos.x, pos.y, pos.z);
+    workstation_for_block(string_id(chunk.inner.get_block(x, y, z, 0))?)
+}
+
+/// Container type opened by using a workstation block.
+pub fn workstation_for_block(block: &str) -> Option<ContainerType> {
+    match block {
+        "minecraft:crafting_table" => Some(ContainerType::CraftingTable),
+        "minecraft:stonecutter_block" => Some(ContainerType::Stonecutter),
+        _ => None,
+    }
+}
+
+/// Protocol window type for a container type.
+pub fn window_type(container_type: ContainerType) -> WindowType {
+    match container_type {
+        ContainerType::Chest
+        | ContainerType::DoubleChest
+        | ContainerType::ShulkerBox
+        | ContainerType::Barrel => WindowType::Container,
+        ContainerType::CraftingTable => WindowType::Workbench,
+        ContainerType::Furnace => WindowType::Furnace,
+        ContainerType::BlastFurnace => WindowType::BlastFurnace,
+        ContainerType::Smoker => WindowType::Smoker,
+        ContainerType::BrewingStand => WindowType::BrewingStand,
+        ContainerType::EnchantingTable => WindowType::Enchantment,
+        ContainerType::Anvil => WindowType::Anvil,
+        ContainerType::Grindstone => WindowType::Grindstone,
+        ContainerType::Stonecutter => WindowType::Stonecutter,
+        ContainerType::Loom => WindowType::Loom,
+        ContainerType::SmithingTable => WindowType::SmithingTable,
+        ContainerType::Hopper => WindowType::Hopper,
+        ContainerType::Dispenser => WindowType::Dispenser,
+        ContainerType::Dropper => WindowType::Dropper,
+    }
+}
+
+/// Container name of a slot in a block container window.
+///
+/// Furnace slots each have their own type; the protocol has no
+/// hopper-specific type, so hoppers use the level-entity container like
+/// chests do.
+fn container_name(container_type: ContainerType, slot: usize) -> FullContainerName {
+    let container_id = match (container_type, slot) {
+        (ContainerType::Furnace, 0) => ContainerSlotType::FurnaceIngredient,
+        (ContainerType::BlastFurnace, 0) => ContainerSlotType::BlastFurnaceIngredient,
+        (ContainerType::Smoker, 0) => ContainerSlotType::SmokerIngredient,
+        (ContainerType::Furnace | ContainerType::BlastFurnace | ContainerType::Smoker, 1) => {
+            ContainerSlotType::FurnaceFuel
+        }
+        (ContainerType::Furnace | ContainerType::BlastFurnace | ContainerType::Smoker, _) => {
+            ContainerSlotType::FurnaceOutput
+        }
+        (ContainerType::Barrel, _) => ContainerSlotType::Barrel,
+        (ContainerType::ShulkerBox, _) => ContainerSlotType::Shulker,
+        // Chests, hoppers, dispensers and droppers share the level-entity container
+        _ => ContainerSlotType::Container,
+    };
+    FullContainerName {
+        container_id,
+        dynamic_container_id: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::block_entity::Chest;
+    use crate::world::ecs::ChunkBlockEntities;
+
+    fn spawn_chest(world: &mut World, chunk: Entity, pos: IVec3, chest: Chest) -> Entity {
+        let entity = world
+            .spawn(BlockEntity::new(pos, BlockEntityData::Chest(chest)))
+            .id();
+        world
+            .get_mut::<ChunkBlockEntities>(chunk)
+            .unwrap()
+            .insert(pos, entity);
+        entity
+    }
+
+    #[test]
+    fn test_double_chest_slots() {
+        let mut world = World::new();
+        let chunk = world.spawn(ChunkBlockEntities::default()).id();
+        let mut worlds = crate::world::ecs::Worlds::default();
+        worlds
+            .default_world_mut()
+            .insert(crate::world::ecs::ChunkPosition::new(0, 0), chunk);
+        world.insert_resource(worlds);
+
+        let lead_pos = IVec3::new(1, 64, 1);
+        let partner_pos = IVec3::new(2, 64, 1);
+        let lead = spawn_chest(
+            &mut world,
+            chunk,
+            lead_pos,
+            Chest {
+                pair: Some((2, 1)),
+                pair_lead: true,
+                ..Default::default()
+            },
+        );
+        let partner = spawn_chest(
+            &mut world,
+            chunk,
+            partner_pos,
+            Chest {
+                pair: Some((1, 1)),
+                ..Default::default()
+            },
+        );
+
+        // Either half resolves to the same slot order
+        assert_eq!(
+            container_block_entities(&world, WorldId::DEFAULT, lead_pos),
+            vec![lead, partner]
+        );
+        assert_eq!(
+            container_block_entities(&world, WorldId::DEFAULT, partner_pos),
+            vec![lead, partner]
+        );
+
+        let halves = [lead, partner];
+        let diamond = ItemStack::new("minecraft:diamond", 3);
+        assert!(set_container_item(&mut world, &halves, 30, diamond.clone()));
+        assert!(!set_container_item(
+            &mut world,
+            &halves,
+            54,
+            diamond.clone()
+        ));
+
+        let partner_items = world
+            .get::<BlockEntity>(partner)
+            .unwrap()
+            .data
+            .items()
+            .unwrap();
+        assert_eq!(partner_items[3], diamond);
+        assert_eq!(container_item(&world, &halves, 30), Some(diamond));
+        assert!(container_item(&world, &halves, 54).is_none());
+    }
+
+    #[test]
+    fn test_container_viewers() {
+        let mut world = World::new();
+        let pos = IVec3::new(4, 70, -2);
+        let open = OpenContainer {
+            position: Some((pos.x, pos.y, pos.z)),
+            window_id: CONTAINER_WINDOW_ID as u8,
+            container_type: ContainerType::Chest,
+        };
+        let a = world.spawn(open.clone()).id();
+        let b = world.spawn(open).id();
+        world.spawn(OpenContainer {
+            position: Some((0, 0, 0)),
+            window_id: CONTAINER_WINDOW_ID as u8,
+            container_type: ContainerType::Chest,
+        });
+
+        let viewers = container_viewers(&mut world, WorldId::DEFAULT, pos);
+        assert_eq!(viewers.len(), 2);
+        assert!(viewers.contains(&a) && viewers.contains(&b));
+    }
+
+    #[test]
+    fn test_container_slot_names() {
+        let slot_type = |container_type, slot| container_name(container_type, slot).container_id;
+        assert_eq!(
+            slot_type(ContainerType::Furnace, 0),
+            ContainerSlotType::FurnaceIngredient
+        );
+        assert_eq!(
+            slot_type(ContainerType::Furnace, 1),
+            ContainerSlotType::FurnaceFuel
+        );
+        assert_eq!(
+            slot_type(ContainerType::Furnace, 2),
+            ContainerSlotType::FurnaceOutput
+        );
+        assert_eq!(
+            slot_type(ContainerType::Hopper, 4),
+            ContainerSlotType::Container
+        );
+        assert_eq!(
+            slot_type(ContainerType::DoubleChest, 40),
+            ContainerSlotType::Container
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! Damage feedback for clients.
+//!
+//! Damage is calculated in the ECS by the damage pipeline
+//! (`entity::systems::damage`); this module tells players about the result.
+
+use bevy_ecs::prelude::*;
+use jolyne::valentine::{McpePacket, UpdateAttributesPacket};
+
+use super::join::attribute;
+use crate::ecs::resources::TickCounter;
+use crate::entity::components::{Health, PlayerSession, RuntimeEntityId};
+
+/// System: Send players their health whenever it changes.
+pub(super) fn sync_player_health(
+    players: Query<(&PlayerSession, &RuntimeEntityId, &Health), Changed<Health>>,
+    tick: Res<TickCounter>,
+) {
+    for (session, runtime_id, health) in players.iter() {
+        let _ = session.send(McpePacket::from(UpdateAttributesPacket {
+            runtime_entity_id: runtime_id.0,
+            attributes: vec![attribute(
+                "minecraft:health",
+                health.current,
+                health.max,
+                20.0,
+                20.0,
+            )],
+            tick: tick.current as i64,
+        }));
+    }
+}
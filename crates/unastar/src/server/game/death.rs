@@ -0,0 +1,445 @@
+//! Death and respawn.
+//!
+//! `detect_deaths` marks entities whose health reached zero and triggers a
+//! `DeathEvent`. The server queues those events and handles them after the
+//! tick: every client plays the death animation, and a dead player spills
+//! its inventory, gets a death message in chat and is sent to the respawn
+//! screen. The client then drives the respawn:
+//!
+//! 1. Server sends `Respawn` (searching) with the respawn position on death.
+//! 2. Client sends `Respawn` (client ready) when the button is pressed.
+//! 3. Server answers with `Respawn` (ready to spawn).
+//! 4. Client sends the `Respawn` player action and the player is restored.
+
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use jolyne::valentine::types::Vec3F;
+use jolyne::valentine::{
+    EntityEventPacket, EntityEventPacketEventId, McpePacket, MovePlayerPacketTeleportCause,
+    RemoveEntityPacket, RespawnPacket, UpdateAttributesPacket,
+};
+use tracing::{debug, info};
+
+use super::GameServer;
+use super::hunger::hunger_attributes;
+use super::join::attribute;
+use super::types::{SessionEntityMap, translated_text};
+use crate::config::SpawnLocation;
+use crate::entity::components::{
+    AirSupply, ArmourInventory, CursorItem, Dead, Effects, GameMode, Health, Hunger, LastDamage,
+    MainInventory, MobAge, MobType, OffhandSlot, OnFire, Player, PlayerName, PlayerSession,
+    PlayerUuid, Position, Rotation, RuntimeEntityId, RuntimeId, SpawnPoint, UiInventory, UsingItem,
+};
+use crate::entity::damage::{DamageSource, DeathEvent, Killer};
+use crate::entity::mobs::MobDefinition;
+use crate::permission::op_level;
+use crate::server::broadcast::build_add_player_packet;
+use crate::server::resolve_spawn_location;
+use crate::world::ecs::{ChunkManager, WorldId, Worlds};
+
+/// `RespawnPacket` state: the server is still finding a spawn position.
+const RESPAWN_SEARCHING: u8 = 0;
+/// `RespawnPacket` state: the server is ready for the client to respawn.
+const RESPAWN_READY: u8 = 1;
+/// `RespawnPacket` state: the client pressed the respawn button.
+const RESPAWN_CLIENT_READY: u8 = 2;
+
+/// How far below eye level a dead player's items are dropped.
+const DROP_HEIGHT_OFFSET: f64 = 0.3;
+
+/// Deaths triggered during the tick, waiting to be handled by the server.
+#[derive(Resource, Default)]
+pub(super) struct PendingDeaths(Vec<(Entity, DamageSource)>);
+
+/// Observer: Queue deaths for `GameServer::process_deaths`.
+pub(super) fn queue_death(trigger: On<DeathEvent>, mut pending: ResMut<PendingDeaths>) {
+    let event = trigger.event();
+    pending.0.push((event.entity, event.source.clone()));
+}
+
+impl GameServer {
+    /// Handle every death queued during the last tick.
+    pub(super) fn process_deaths(&mut self) {
+        let deaths = self
+            .ecs
+            .world_mut()
+            .get_resource_mut::<PendingDeaths>()
+            .map(|mut pending| std::mem::take(&mut pending.0))
+            .unwrap_or_default();
+        for (entity, source) in deaths {
+            self.handle_death(entity, &source);
+        }
+    }
+
+    fn handle_death(&mut self, entity: Entity, source: &DamageSource) {
+        let world = self.ecs.world();
+        let Some(runtime_id) = world
+            .get::<RuntimeEntityId>(entity)
+            .map(|rid| rid.0)
+            .or_else(|| world.get::<RuntimeId>(entity).map(|rid| rid.0))
+        else {
+            return;
+        };
+        let is_player = world.get::<Player>(entity).is_some();
+
+        // The dying player's own client plays the animation from its health
+        self.broadcast_in_world(
+            WorldId::of(world, entity),
+            McpePacket::from(EntityEventPacket {
+                runtime_entity_id: runtime_id,
+                event_id: EntityEventPacketEventId::DeathAnimation,
+                data: 0,
+            }),
+            Some(entity),
+        );
+        if !is_player {
+            self.drop_mob_loot(entity);
+            return;
+        }
+
+        let rules = self.game_rules();
+        self.close_container(entity, true);
+        if !rules.keep_inventory {
+            self.spill_inventory(entity);
+        }
+        self.send_vitals(entity);
+
+        let spawn = self.respawn_location(entity);
+        if let Some(session) = self.ecs.world().get::<PlayerSession>(entity) {
+            let _ = session.send(McpePacket::from(RespawnPacket {
+                position: spawn_position(&spawn),
+                state: RESPAWN_SEARCHING,
+                runtime_entity_id: runtime_id,
+            }));
+        }
+        self.broadcast_death_message(entity, source, rules.show_death_messages);
+    }
+
+    /// Drop everything a player carries at its feet and clear its inventory.
+    fn spill_inventory(&mut self, entity: Entity) {
+        let world = self.ecs.world_mut();
+        let Some(position) = world.get::<Position>(entity).map(|pos| pos.0) else {
+            return;
+        };
+
+        let mut items = Vec::new();
+        if let Some(mut inventory) = world.get_mut::<MainInventory>(entity) {
+            items.extend(inventory.0.clear());
+        }
+        if let Some(mut armour) = world.get_mut::<ArmourInventory>(entity) {
+            items.extend(armour.clear());
+        }
+        if let Some(mut offhand) = world.get_mut::<OffhandSlot>(entity) {
+            items.push(std::mem::take(&mut offhand.0));
+        }
+        if let Some(mut cursor) = world.get_mut::<CursorItem>(entity) {
+            items.push(std::mem::take(&mut cursor.0));
+        }
+        if let Some(mut ui) = world.get_mut::<UiInventory>(entity) {
+            items.extend(ui.0.clear());
+        }
+
+        let origin = position - DVec3::Y * DROP_HEIGHT_OFFSET;
+        let world_id = WorldId::of(self.ecs.world(), entity);
+        let mut dropped = 0;
+        for item in items {
+            if self
+                .spawn_dropped_item(world_id, origin, item, scatter_velocity(), None)
+                .is_some()
+            {
+                dropped += 1;
+            }
+        }
+
+        let contents = self.inventory_contents(entity);
+        if let Some(session) = self.ecs.world().get::<PlayerSession>(entity) {
+            self.send_inventory_contents(session, contents);
+        }
+        debug!(entity = ?entity, dropped, "Spilled inventory on death");
+    }
+
+    /// Drop the loot of a dying mob where it stands.
+    ///
+    /// Babies drop nothing, and meat comes out cooked if the mob was burning.
+    fn drop_mob_loot(&mut self, entity: Entity) {
+        let world = self.ecs.world();
+        let (Some(mob), Some(position)) = (
+            world
+                .get::<MobType>(entity)
+                .and_then(|mob| MobDefinition::get(&mob.identifier)),
+            world.get::<Position>(entity).map(|pos| pos.0),
+        ) else {
+            return;
+        };
+        if world.get::<MobAge>(entity).is_some_and(MobAge::is_baby) {
+            return;
+        }
+        let on_fire = world.get::<OnFire>(entity).is_some_and(OnFire::is_on_fire);
+        let world_id = WorldId::of(world, entity);
+        for item in mob.roll_loot(on_fire) {
+            self.spawn_dropped_item(world_id, position, item, scatter_velocity(), None);
+        }
+    }
+
+    /// Announce a player's death to everyone, or only log it when death
+    /// messages are turned off.
+    fn broadcast_death_message(&self, entity: Entity, source: &DamageSource, announce: bool) {
+        let Some(victim) = self.ecs.world().get::<PlayerName>(entity) else {
+            return;
+        };
+        let killer = source
+            .attacker()
+            .and_then(|attacker| self.killer_name(attacker));
+        let key = source.death_message_key(killer.as_ref().map(|(kind, _)| *kind));
+        info!(player = %victim.0, cause = key, "Player died");
+        if !announce {
+            return;
+        }
+
+        let mut parameters = vec![victim.0.clone()];
+        parameters.extend(killer.map(|(_, name)| name));
+        self.broadcast_packet(McpePacket::from(translated_text(key, parameters)), None);
+    }
+
+    /// Kind of an entity and the name used for it in chat messages.
+    ///
+    /// Mobs use a translation key so each client shows its own language.
+    fn killer_name(&self, entity: Entity) -> Option<(Killer, String)> {
+        let world = self.ecs.world();
+        if let Some(name) = world.get::<PlayerName>(entity) {
+            return Some((Killer::Player, name.0.clone()));
+        }
+        let mob = world.get::<MobType>(entity)?;
+        let id = mob.identifier.trim_start_matches("minecraft:");
+        Some((Killer::Mob, format!("%entity.{id}.name")))
+    }
+
+    /// Where a player respawns, in the world it died in.
+    ///
+    /// A spawn point set in the current dimension wins. Otherwise players
+    /// in the default world use the configured `spawn_rules`, ignoring
+    /// previous positions, and players elsewhere the world spawn.
+    pub(super) fn respawn_location(&self, entity: Entity) -> SpawnLocation {
+        let world = self.ecs.world();
+        let world_id = WorldId::of(world, entity);
+        let manager = world.resource::<Worlds>().get(world_id);
+        let dimension = manager.map_or(self.config.world.dimension, ChunkManager::dimension);
+        if let Some(spawn) = world.get::<SpawnPoint>(entity)
+            && let Some(pos) = spawn.position
+            && spawn.dimension == dimension
+        {
+            return SpawnLocation {
+                x: pos.x as f32 + 0.5,
+                y: pos.y as f32,
+                z: pos.z as f32 + 0.5,
+                yaw: 0.0,
+                pitch: 0.0,
+            };
+        }
+        match manager {
+            Some(manager) if !world_id.is_default() => {
+                let spawn = manager.spawn_position();
+                SpawnLocation {
+                    x: spawn.x as f32,
+                    y: spawn.y as f32,
+                    z: spawn.z as f32,
+                    yaw: 0.0,
+                    pitch: 0.0,
+                }
+            }
+            _ => self.default_spawn(),
+        }
+    }
+
+    /// Where players arrive in the default world: the configured
+    /// `spawn_rules`, ignoring previous positions.
+    pub(super) fn default_spawn(&self) -> SpawnLocation {
+        resolve_spawn_location(
+            &self.config.spawn_rules,
+            &self.config.world,
+            &self.world_template,
+            None,
+        )
+    }
+
+    /// Handle a Respawn packet from the client.
+    ///
+    /// The client sends this when the respawn button is pressed; the server
+    /// confirms with the position the player will respawn at.
+    pub(super) fn handle_respawn(&mut self, entity: Entity, pk: &RespawnPacket) {
+        let world = self.ecs.world();
+        if pk.state != RESPAWN_CLIENT_READY || world.get::<Dead>(entity).is_none() {
+            return;
+        }
+        let Some(runtime_id) = world.get::<RuntimeEntityId>(entity).map(|rid| rid.0) else {
+            return;
+        };
+
+        let spawn = self.respawn_location(entity);
+        if let Some(session) = self.ecs.world().get::<PlayerSession>(entity) {
+            let _ = session.send(McpePacket::from(RespawnPacket {
+                position: spawn_position(&spawn),
+                state: RESPAWN_READY,
+                runtime_entity_id: runtime_id,
+            }));
+        }
+    }
+
+    /// Bring a dead player back at its respawn location.
+    ///
+    /// Restores health, hunger and air, and clears effects and fire.
+    pub(super) fn respawn_player(&mut self, entity: Entity) {
+        if self.ecs.world().get::<Dead>(entity).is_none() {
+            return;
+        }
+        let spawn = self.respawn_location(entity);
+
+        let world = self.ecs.world_mut();
+        let mut player = world.entity_mut(entity);
+        player.remove::<(Dead, LastDamage, UsingItem)>();
+        if let Some(mut health) = player.get_mut::<Health>() {
+            health.current = health.max;
+        }
+        if let Some(mut hunger) = player.get_mut::<Hunger>() {
+            *hunger = Hunger::default();
+        }
+        if let Some(mut air) = player.get_mut::<AirSupply>() {
+            air.current_ticks = air.max_ticks;
+        }
+        if let Some(mut effects) = player.get_mut::<Effects>() {
+            effects.active.clear();
+        }
+        if let Some(mut fire) = player.get_mut::<OnFire>() {
+            fire.extinguish();
+        }
+
+        let position = DVec3::new(spawn.x as f64, spawn.y as f64, spawn.z as f64);
+        self.teleport_player(
+            entity,
+            position,
+            Some(Rotation::new(spawn.yaw, spawn.pitch)),
+            MovePlayerPacketTeleportCause::Unknown,
+        );
+        self.send_vitals(entity);
+        self.show_respawned_player(entity);
+        info!(entity = ?entity, pos = ?(spawn.x, spawn.y, spawn.z), "Player respawned");
+    }
+
+    /// Replace the corpse other players see with the living player.
+    fn show_respawned_player(&self, entity: Entity) {
+        let world = self.ecs.world();
+        let (
+            Some(runtime_id),
+            Some(uuid),
+            Some(name),
+            Some(position),
+            Some(rotation),
+            Some(game_mode),
+        ) = (
+            world.get::<RuntimeEntityId>(entity),
+            world.get::<PlayerUuid>(entity),
+            world.get::<PlayerName>(entity),
+            world.get::<Position>(entity),
+            world.get::<Rotation>(entity),
+            world.get::<GameMode>(entity),
+        )
+        else {
+            return;
+        };
+
+        let remove = RemoveEntityPacket {
+            entity_id_self: runtime_id.0,
+        };
+        let add = build_add_player_packet(
+            runtime_id.0,
+            uuid.0,
+            &name.0,
+            position,
+            rotation,
+            *game_mode,
+            op_level(world, entity),
+        );
+        let world_id = WorldId::of(world, entity);
+        self.broadcast_in_world(world_id, McpePacket::from(remove), Some(entity));
+        self.broadcast_in_world(world_id, McpePacket::from(add), Some(entity));
+    }
+
+    /// Send a player's current health and hunger to its client.
+    pub(super) fn send_vitals(&self, entity: Entity) {
+        let world = self.ecs.world();
+        let (Some(session), Some(runtime_id)) = (
+            world.get::<PlayerSession>(entity),
+            world.get::<RuntimeEntityId>(entity),
+        ) else {
+            return;
+        };
+        let health = world.get::<Health>(entity).cloned().unwrap_or_default();
+        let hunger = world.get::<Hunger>(entity).cloned().unwrap_or_default();
+
+        let _ = session.send(McpePacket::from(UpdateAttributesPacket {
+            runtime_entity_id: runtime_id.0,
+            attributes: std::iter::once(attribute(
+                "minecraft:health",
+                health.current,
+                health.max,
+                20.0,
+                20.0,
+            ))
+            .chain(hunger_attributes(&hunger))
+            .collect(),
+            tick: self.current_tick as i64,
+        }));
+    }
+
+    /// Send a packet to every connected player, optionally skipping one.
+    pub(super) fn broadcast_packet(&self, packet: McpePacket, except: Option<Entity>) {
+        let world = self.ecs.world();
+        let Some(session_map) = world.get_resource::<SessionEntityMap>() else {
+            return;
+        };
+        for (_, other) in session_map.iter() {
+            if Some(other) == except {
+                continue;
+            }
+            if let Some(session) = world.get::<PlayerSession>(other) {
+                let _ = session.send(packet.clone());
+            }
+        }
+    }
+
+    /// Send a packet to every player in a world, optionally skipping one.
+    pub(super) fn broadcast_in_world(
+        &self,
+        world_id: WorldId,
+        packet: McpePacket,
+        except: Option<Entity>,
+    ) {
+        let world = self.ecs.world();
+        let Some(session_map) = world.get_resource::<SessionEntityMap>() else {
+            return;
+        };
+        for (_, other) in session_map.iter() {
+            if Some(other) == except || WorldId::of(world, other) != world_id {
+                continue;
+            }
+            if let Some(session) = world.get::<PlayerSession>(other) {
+                let _ = session.send(packet.clone());
+            }
+        }
+    }
+}
+
+fn spawn_position(spawn: &SpawnLocation) -> Vec3F {
+    Vec3F {
+        x: spawn.x,
+        y: spawn.y,
+        z: spawn.z,
+    }
+}
+
+/// Random horizontal push for items spilled on death.
+fn scatter_velocity() -> DVec3 {
+    let strength = rand::random::<f64>() * 0.5;
+    let angle = rand::random::<f64>() * std::f64::consts::TAU;
+    DVec3::new(-angle.sin() * strength, 0.2, angle.cos() * strength)
+}
@@ -0,0 +1,251 @@
+//! Dropped item entities.
+//!
+//! Items spilled on death or dropped by broken blocks live in the ECS as
+//! `DroppedItem` entities. They are shown to every player in their world, fall
+//! under the physics systems, and are picked up by players walking over them.
+
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use jolyne::valentine::types::Vec3F;
+use jolyne::valentine::{
+    AddItemEntityPacket, McpePacket, RemoveEntityPacket, TakeItemEntityPacket,
+};
+use tracing::debug;
+
+use super::GameServer;
+use super::types::EntityIds;
+use crate::entity::bundles::ItemBundle;
+use crate::entity::collision::Aabb;
+use crate::entity::components::{
+    Age, Dead, DespawnTimer, DroppedItem, GameMode, Hitbox, ItemOwner, ItemStackData,
+    MainInventory, OnGround, PLAYER_EYE_HEIGHT, PickupDelay, Player, PlayerSession, Position,
+    Rotation, RuntimeEntityId, RuntimeId, SpatialChunk, Velocity,
+};
+use crate::item::ItemStack;
+use crate::world::ecs::WorldId;
+
+/// How far beyond a player's hitbox items are picked up, horizontally and vertically.
+const PICKUP_REACH: DVec3 = DVec3::new(1.0, 0.5, 1.0);
+
+impl GameServer {
+    /// Spawn a dropped item entity and show it to every player in its world.
+    ///
+    /// Returns `None` if the stack is empty.
+    pub fn spawn_dropped_item(
+        &mut self,
+        world_id: WorldId,
+        position: DVec3,
+        item: ItemStack,
+        velocity: DVec3,
+        owner: Option<Entity>,
+    ) -> Option<Entity> {
+        if item.is_empty() {
+            return None;
+        }
+        let runtime_id = self
+            .ecs
+            .world_mut()
+            .get_resource_or_init::<EntityIds>()
+            .allocate();
+
+        let packet = self.add_item_packet(runtime_id, position, velocity, &item);
+        let entity = self
+            .ecs
+            .world_mut()
+            .spawn(ItemBundle {
+                dropped_item: DroppedItem,
+                item_data: ItemStackData(item),
+                position: Position(position),
+                velocity: Velocity(velocity),
+                rotation: Rotation::default(),
+                on_ground: OnGround::default(),
+                hitbox: Hitbox::ITEM,
+                runtime_id: RuntimeId(runtime_id),
+                pickup_delay: PickupDelay::default(),
+                item_owner: ItemOwner(owner),
+                despawn_timer: DespawnTimer::default(),
+                age: Age::default(),
+                world: world_id,
+                spatial_chunk: SpatialChunk::from_position(&Position(position)),
+            })
+            .id();
+
+        let world = self.ecs.world_mut();
+        let mut players = world.query_filtered::<(&PlayerSession, &WorldId), With<Player>>();
+        for (session, _) in players.iter(world).filter(|(_, w)| **w == world_id) {
+            let _ = session.send(McpePacket::from(packet.clone()));
+        }
+        debug!(entity = ?entity, runtime_id, "Spawned dropped item");
+        Some(entity)
+    }
+
+    /// Send every dropped item in a player's world to them, after joining or
+    /// changing worlds.
+    pub(super) fn send_dropped_items(&mut self, viewer: Entity) {
+        let world = self.ecs.world_mut();
+        let world_id = WorldId::of(world, viewer);
+        let items: Vec<(i64, DVec3, DVec3, ItemStack)> = world
+            .query_filtered::<(&RuntimeId, &Position, &Velocity, &ItemStackData, &WorldId), With<DroppedItem>>()
+            .iter(world)
+            .filter(|(.., item_world)| **item_world == world_id)
+            .map(|(rid, pos, vel, data, _)| (rid.0, pos.0, vel.0, data.0.clone()))
+            .collect();
+
+        let Some(session) = self.ecs.world().get::<PlayerSession>(viewer) else {
+            return;
+        };
+        for (runtime_id, position, velocity, item) in items {
+            let packet = self.add_item_packet(runtime_id, position, velocity, &item);
+            let _ = session.send(McpePacket::from(packet));
+        }
+    }
+
+    /// Let players pick up dropped items they are standing on.
+    ///
+    /// Items that don't fully fit stay on the ground with the remainder.
+    pub(super) fn tick_item_pickups(&mut self) {
+        let world = self.ecs.world_mut();
+        let items: Vec<(Entity, DVec3, WorldId)> = world
+            .query_filtered::<(Entity, &Position, &PickupDelay, &WorldId), With<DroppedItem>>()
+            .iter(world)
+            .filter(|(_, _, delay, _)| delay.can_pickup())
+            .map(|(entity, pos, _, world_id)| (entity, pos.0, *world_id))
+            .collect();
+        if items.is_empty() {
+            return;
+        }
+
+        let collectors: Vec<(Entity, Aabb, WorldId)> = world
+            .query_filtered::<(Entity, &Position, &GameMode, &WorldId), (With<Player>, Without<Dead>)>()
+            .iter(world)
+            .filter(|(_, _, mode, _)| **mode != GameMode::Spectator)
+            .map(|(entity, pos, _, world_id)| (entity, pickup_box(pos.0), *world_id))
+            .collect();
+
+        for (item, position, item_world) in items {
+            let item_box = Hitbox::ITEM.aabb_at(position);
+            if let Some(&(player, ..)) = collectors
+                .iter()
+                .find(|(_, reach, world_id)| *world_id == item_world && reach.intersects(&item_box))
+            {
+                self.pick_up_item(player, item);
+            }
+        }
+    }
+
+    /// Move a dropped item into a player's inventory.
+    fn pick_up_item(&mut self, player: Entity, item: Entity) {
+        let world = self.ecs.world_mut();
+        let Some((item_rid, position, stack)) = world
+            .get::<RuntimeId>(item)
+            .zip(world.get::<Position>(item))
+            .zip(world.get::<ItemStackData>(item))
+            .map(|((rid, pos), data)| (rid.0, pos.0, data.0.clone()))
+        else {
+            return;
+        };
+        let Some(player_rid) = world.get::<RuntimeEntityId>(player).map(|rid| rid.0) else {
+            return;
+        };
+        let Some(mut inventory) = world.get_mut::<MainInventory>(player) else {
+            return;
+        };
+
+        let before = inventory.0.slots().to_vec();
+        let (added, leftover) = inventory.0.add_item(stack);
+        if added == 0 {
+            return;
+        }
+        self.send_main_inventory_changes(player, &before);
+
+        let world = self.ecs.world_mut();
+        let take = TakeItemEntityPacket {
+            runtime_entity_id: item_rid,
+            target: player_rid as i32,
+        };
+        let mut players = world.query_filtered::<&PlayerSession, With<Player>>();
+        for session in players.iter(world) {
+            let _ = session.send(McpePacket::from(take.clone()));
+        }
+        world.despawn(item);
+        debug!(player = ?player, added, "Picked up dropped item");
+
+        // Bedrock cannot shrink an item entity, so the remainder is respawned
+        let world_id = WorldId::of(self.ecs.world(), player);
+        if let Some(rest) = self.spawn_dropped_item(world_id, position, leftover, DVec3::ZERO, None)
+        {
+            self.ecs.world_mut().entity_mut(rest).insert(PickupDelay(0));
+        }
+    }
+
+    /// AddItemEntity packet for a dropped item.
+    fn add_item_packet(
+        &self,
+        runtime_id: i64,
+        position: DVec3,
+        velocity: DVec3,
+        item: &ItemStack,
+    ) -> AddItemEntityPacket {
+        AddItemEntityPacket {
+            entity_id_self: runtime_id,
+            runtime_entity_id: runtime_id,
+            item: self.network_item(item, 0),
+            position: vec3f(position),
+            velocity: vec3f(velocity),
+            metadata: vec![],
+            is_from_fishing: false,
+        }
+    }
+}
+
+/// Observer: Remove dropped items from every client when the entity goes away.
+///
+/// Register with: `world.add_observer(broadcast_item_removal)`
+pub fn broadcast_item_removal(
+    trigger: On<Remove, DroppedItem>,
+    items: Query<&RuntimeId>,
+    players: Query<&PlayerSession, With<Player>>,
+) {
+    let Ok(runtime_id) = items.get(trigger.event().entity) else {
+        return;
+    };
+    let packet = RemoveEntityPacket {
+        entity_id_self: runtime_id.0,
+    };
+    for session in players.iter() {
+        let _ = session.send(McpePacket::from(packet.clone()));
+    }
+}
+
+/// The region a player collects items from, given its eye-level position.
+fn pickup_box(position: DVec3) -> Aabb {
+    let feet = position - DVec3::Y * PLAYER_EYE_HEIGHT;
+    let hitbox = Hitbox::PLAYER.aabb_at(feet);
+    Aabb::new(hitbox.min - PICKUP_REACH, hitbox.max + PICKUP_REACH)
+}
+
+fn vec3f(v: DVec3) -> Vec3F {
+    Vec3F {
+        x: v.x as f32,
+        y: v.y as f32,
+        z: v.z as f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pickup_box() {
+        let player = DVec3::new(0.5, 64.0 + PLAYER_EYE_HEIGHT, 0.5);
+        let reach = pickup_box(player);
+
+        let at_feet = Hitbox::ITEM.aabb_at(DVec3::new(1.5, 64.0, 0.5));
+        assert!(reach.intersects(&at_feet));
+        let below = Hitbox::ITEM.aabb_at(DVec3::new(0.5, 63.0, 0.5));
+        assert!(!reach.intersects(&below));
+        let too_far = Hitbox::ITEM.aabb_at(DVec3::new(2.5, 64.0, 0.5));
+        assert!(!reach.intersects(&too_far));
+    }
+}
@@ -1,5 +1,16 @@
+use crate::command::TargetArg;
 use crate::entity::components::{PlayerName, PlayerUuid, transform::Position};
-use abi_stable::std_types::{ROption, RStr, RString};
+use crate::permission::{self, OpLevel, Permissions};
+use crate::server::game::types::SessionEntityMap;
+use crate::server::game::{
+    change_world, create_world, load_world, set_difficulty, set_game_rule, set_time, set_weather,
+    unload_world,
+};
+use crate::world::{
+    CarverSelection, Difficulty, GameRuleValue, GameRules, WeatherKind, WorldConfig,
+    WorldGenerator, WorldId, Worlds,
+};
+use abi_stable::std_types::{ROption, RResult, RStr, RString, RVec};
 use bevy_ecs::prelude::*;
 use unastar_api::PluginAction;
 use unastar_api::native::{NativeActionQueue, PlayerInfo, PluginEntity, RawPluginHost, Vec3};
@@ -8,6 +19,30 @@ pub struct ServerHost<'a> {
     pub world: &'a mut World,
 }
 
+impl ServerHost<'_> {
+    /// XUID of a player named by a plugin: from its session when online,
+    /// otherwise the one recorded in the permissions.
+    fn player_xuid(&self, name: &str) -> Option<String> {
+        let online = self
+            .world
+            .get_resource::<SessionEntityMap>()
+            .and_then(|sessions| {
+                sessions.iter().map(|(_, entity)| entity).find(|&entity| {
+                    self.world
+                        .get::<PlayerName>(entity)
+                        .is_some_and(|player| player.0.eq_ignore_ascii_case(name))
+                })
+            });
+        match online {
+            Some(entity) => permission::player_xuid(self.world, entity).map(str::to_string),
+            None => self
+                .world
+                .get_resource::<Permissions>()
+                .and_then(|permissions| permissions.known_xuid(name)),
+        }
+    }
+}
+
 impl<'a> RawPluginHost for ServerHost<'a> {
     fn send_message(&mut self, player_uuid: RStr<'_>, message: RStr<'_>) {
         if let Some(mut queue) = self.world.get_resource_mut::<NativeActionQueue>() {
@@ -69,4 +104,222 @@ impl<'a> RawPluginHost for ServerHost<'a> {
 
         info.into()
     }
+
+    fn select_entities(
+        &mut self,
+        target: RStr<'_>,
+        executor: ROption<PluginEntity>,
+    ) -> RResult<RVec<PluginEntity>, RString> {
+        let executor = executor
+            .into_option()
+            .map(|entity| Entity::from_bits(entity.to_bits()))
+            .filter(|&entity| self.world.get_entity(entity).is_ok());
+        TargetArg::new(target.as_str())
+            .select(self.world, executor)
+            .map(|entities| entities.into_iter().map(PluginEntity::from).collect())
+            .map_err(RString::from)
+            .into()
+    }
+
+    fn op_level(&self, player_name: RStr<'_>) -> u8 {
+        let name = player_name.as_str();
+        let xuid = self.player_xuid(name);
+        self.world
+            .get_resource::<Permissions>()
+            .map_or(OpLevel::None, |permissions| {
+                permissions.level(name, xuid.as_deref())
+            }) as u8
+    }
+
+    fn has_permission(&self, player_name: RStr<'_>, node: RStr<'_>, default_level: u8) -> bool {
+        let Some(permissions) = self.world.get_resource::<Permissions>() else {
+            return false;
+        };
+        let name = player_name.as_str();
+        let default_level = OpLevel::from_u8(default_level).unwrap_or(OpLevel::Owner);
+        permissions.has(
+            name,
+            self.player_xuid(name).as_deref(),
+            node.as_str(),
+            default_level,
+        )
+    }
+
+    fn set_permission(
+        &mut self,
+        player_name: RStr<'_>,
+        node: RStr<'_>,
+        value: ROption<bool>,
+    ) -> RResult<(), RString> {
+        let Some(permissions) = self.world.get_resource::<Permissions>() else {
+            return RResult::RErr("Permissions are not available".into());
+        };
+        let (name, node) = (player_name.as_str(), node.as_str());
+        let xuid = self.player_xuid(name);
+        let xuid = xuid.as_deref();
+        match value.into_option() {
+            Some(true) => permissions.grant(name, xuid, node),
+            Some(false) => permissions.revoke(name, xuid, node),
+            None => permissions.unset(name, xuid, node),
+        }
+        .map_err(|e| RString::from(e.to_string()))
+        .into()
+    }
+
+    fn world_time(&self) -> i64 {
+        self.world
+            .get_resource::<Worlds>()
+            .map_or(0, |worlds| worlds.default_world().time().time)
+    }
+
+    fn set_world_time(&mut self, time: i64) {
+        set_time(self.world, WorldId::DEFAULT, time);
+    }
+
+    fn weather(&self) -> u8 {
+        self.world
+            .get_resource::<Worlds>()
+            .map_or(0, |worlds| worlds.default_world().weather().kind().id())
+    }
+
+    fn set_weather(&mut self, weather: u8, duration: i32) -> RResult<(), RString> {
+        let Some(kind) = WeatherKind::from_id(weather) else {
+            return RResult::RErr(format!("Unknown weather {weather}").into());
+        };
+        set_weather(
+            self.world,
+            WorldId::DEFAULT,
+            kind,
+            (duration > 0).then_some(duration),
+        );
+        RResult::ROk(())
+    }
+
+    fn difficulty(&self) -> u8 {
+        self.world
+            .get_resource::<Difficulty>()
+            .copied()
+            .unwrap_or_default()
+            .id()
+    }
+
+    fn set_difficulty(&mut self, difficulty: u8) -> RResult<(), RString> {
+        let Some(difficulty) = Difficulty::from_id(difficulty) else {
+            return RResult::RErr(format!("Unknown difficulty {difficulty}").into());
+        };
+        set_difficulty(self.world, difficulty);
+        RResult::ROk(())
+    }
+
+    fn game_rule(&self, name: RStr<'_>) -> ROption<bool> {
+        self.world
+            .get_resource::<GameRules>()
+            .and_then(|rules| match rules.get(name.as_str()) {
+                Some(GameRuleValue::Bool(on)) => Some(on),
+                _ => None,
+            })
+            .into()
+    }
+
+    fn set_game_rule(&mut self, name: RStr<'_>, value: bool) -> RResult<(), RString> {
+        if set_game_rule(self.world, name.as_str(), GameRuleValue::Bool(value)) {
+            RResult::ROk(())
+        } else {
+            RResult::RErr(format!("Unknown game rule {name}").into())
+        }
+    }
+
+    fn worlds(&self) -> RVec<RString> {
+        self.world
+            .get_resource::<Worlds>()
+            .map(|worlds| {
+                worlds
+                    .iter()
+                    .map(|(_, manager)| RString::from(manager.name()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn create_world(
+        &mut self,
+        name: RStr<'_>,
+        generator: u8,
+        seed: i64,
+        dimension: i32,
+    ) -> RResult<(), RString> {
+        let generator = match generator {
+            0 => WorldGenerator::SuperFlat,
+            1 => WorldGenerator::VoidSpawnPlatform {
+                platform_radius_chunks: 1,
+            },
+            2 => WorldGenerator::Vanilla {
+                seed,
+                carvers: CarverSelection::All,
+            },
+            3 => WorldGenerator::Nether {
+                seed,
+                carvers: CarverSelection::All,
+            },
+            4 => WorldGenerator::End { seed },
+            _ => return RResult::RErr(format!("Unknown generator {generator}").into()),
+        };
+        if !(0..=2).contains(&dimension) {
+            return RResult::RErr(format!("Unknown dimension {dimension}").into());
+        }
+        let config = WorldConfig {
+            dimension,
+            generator,
+            ..WorldConfig::default()
+        };
+        create_world(self.world, name.as_str(), config)
+            .map(|_| ())
+            .map_err(|e| RString::from(e.to_string()))
+            .into()
+    }
+
+    fn load_world(&mut self, name: RStr<'_>) -> RResult<(), RString> {
+        load_world(self.world, name.as_str())
+            .map(|_| ())
+            .map_err(|e| RString::from(e.to_string()))
+            .into()
+    }
+
+    fn unload_world(&mut self, name: RStr<'_>) -> RResult<(), RString> {
+        unload_world(self.world, name.as_str())
+            .map_err(|e| RString::from(e.to_string()))
+            .into()
+    }
+
+    fn entity_world(&self, entity: PluginEntity) -> ROption<RString> {
+        let entity = Entity::from_bits(entity.to_bits());
+        if self.world.get_entity(entity).is_err() {
+            return ROption::RNone;
+        }
+        let id = WorldId::of(self.world, entity);
+        self.world
+            .get_resource::<Worlds>()
+            .and_then(|worlds| worlds.name(id))
+            .map(RString::from)
+            .into()
+    }
+
+    fn change_world(
+        &mut self,
+        entity: PluginEntity,
+        world: RStr<'_>,
+        position: ROption<Vec3>,
+    ) -> RResult<(), RString> {
+        let entity = Entity::from_bits(entity.to_bits());
+        if self.world.get::<PlayerName>(entity).is_none() {
+            return RResult::RErr("Only players can change worlds".into());
+        }
+        let position = position
+            .into_option()
+            .map(|p| glam::DVec3::new(p.x, p.y, p.z));
+        change_world(self.world, entity, world.as_str(), position)
+            .map(|_| ())
+            .map_err(|e| RString::from(e.to_string()))
+            .into()
+    }
 }
@@ -0,0 +1,250 @@
+//! Hunger from the player's side: exhaustion from actions, eating, and the
+//! food bar on the client's HUD.
+//!
+//! The food bar itself is simulated by `entity::systems::hunger`.
+
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use jolyne::valentine::types::{
+    PlayerAttributesItem, TransactionTransactionDataItemRelease,
+    TransactionTransactionDataItemReleaseActionType,
+};
+use jolyne::valentine::{McpePacket, UpdateAttributesPacket};
+use rand::Rng;
+use tracing::debug;
+
+use super::GameServer;
+use super::join::attribute;
+use crate::ecs::resources::TickCounter;
+use crate::entity::components::transform::Position;
+use crate::entity::components::{
+    Effects, GameMode, HeldSlot, Hunger, MAX_FOOD_LEVEL, MainInventory, PlayerSession, PlayerState,
+    RuntimeEntityId, UsingItem,
+};
+use crate::item::{Food, ItemStack};
+use crate::world::ecs::WorldId;
+
+/// Exhaustion per block sprinted.
+const SPRINT_EXHAUSTION: f32 = 0.1;
+/// Exhaustion per block swum.
+const SWIM_EXHAUSTION: f32 = 0.01;
+/// Exhaustion of a jump.
+const JUMP_EXHAUSTION: f32 = 0.05;
+/// Exhaustion of a jump while sprinting.
+const SPRINT_JUMP_EXHAUSTION: f32 = 0.2;
+/// Exhaustion of landing an attack.
+pub(super) const ATTACK_EXHAUSTION: f32 = 0.1;
+
+/// Moves longer than this in one input are teleports, not walking.
+const MAX_MOVE_DISTANCE: f64 = 10.0;
+
+/// Ticks of latency allowed when a client says it finished eating.
+const EAT_TOLERANCE_TICKS: u64 = 4;
+
+/// Hunger, saturation and exhaustion attributes for the HUD.
+pub(super) fn hunger_attributes(hunger: &Hunger) -> Vec<PlayerAttributesItem> {
+    let max = MAX_FOOD_LEVEL as f32;
+    vec![
+        attribute(
+            "minecraft:player.hunger",
+            hunger.food_level as f32,
+            max,
+            max,
+            max,
+        ),
+        attribute(
+            "minecraft:player.saturation",
+            hunger.saturation,
+            max,
+            5.0,
+            max,
+        ),
+        attribute(
+            "minecraft:player.exhaustion",
+            hunger.exhaustion,
+            5.0,
+            0.0,
+            5.0,
+        ),
+    ]
+}
+
+/// Exhaustion from moving `distance` blocks horizontally in one input.
+pub(super) fn movement_exhaustion(state: &PlayerState, distance: f64, jumped: bool) -> f32 {
+    let mut exhaustion = 0.0;
+    if distance <= MAX_MOVE_DISTANCE && !state.flying && !state.gliding {
+        if state.swimming {
+            exhaustion += SWIM_EXHAUSTION * distance as f32;
+        } else if state.sprinting {
+            exhaustion += SPRINT_EXHAUSTION * distance as f32;
+        }
+    }
+    if jumped {
+        exhaustion += if state.sprinting {
+            SPRINT_JUMP_EXHAUSTION
+        } else {
+            JUMP_EXHAUSTION
+        };
+    }
+    exhaustion
+}
+
+/// Add exhaustion to a player in survival or adventure mode.
+pub(super) fn exhaust(world: &mut World, entity: Entity, amount: f32) {
+    if amount <= 0.0
+        || world
+            .get::<GameMode>(entity)
+            .is_some_and(|mode| !mode.allows_damage())
+    {
+        return;
+    }
+    if let Some(mut hunger) = world.get_mut::<Hunger>(entity) {
+        hunger.exhaust(amount);
+    }
+}
+
+/// System: Send players their food bar whenever it changes.
+pub(super) fn sync_player_hunger(
+    players: Query<(&PlayerSession, &RuntimeEntityId, &Hunger), Changed<Hunger>>,
+    tick: Res<TickCounter>,
+) {
+    for (session, runtime_id, hunger) in players.iter() {
+        let _ = session.send(McpePacket::from(UpdateAttributesPacket {
+            runtime_entity_id: runtime_id.0,
+            attributes: hunger_attributes(hunger),
+            tick: tick.current as i64,
+        }));
+    }
+}
+
+impl GameServer {
+    /// The player's held item and its food values, if it is edible.
+    fn held_food(&self, entity: Entity) -> Option<(u8, ItemStack, Food)> {
+        let world = self.ecs.world();
+        let slot = world.get::<HeldSlot>(entity)?.0;
+        let item = world.get::<MainInventory>(entity)?.hotbar(slot)?.clone();
+        let food = self.items.get_by_name(&item.item_id)?.food.clone()?;
+        Some((slot, item, food))
+    }
+
+    /// Start eating the held item, if the player can eat it.
+    ///
+    /// Called when the client uses an item in the air.
+    pub(super) fn start_using_item(&mut self, entity: Entity) {
+        let Some((slot, _, food)) = self.held_food(entity) else {
+            return;
+        };
+        let world = self.ecs.world_mut();
+        let can_eat = world
+            .get::<Hunger>(entity)
+            .is_some_and(|hunger| hunger.can_eat(food.always_edible));
+        if can_eat {
+            world.entity_mut(entity).insert(UsingItem {
+                slot,
+                started: self.current_tick,
+            });
+        }
+    }
+
+    /// Handle an `ItemRelease` transaction: the client finished eating, or
+    /// let go of the item early.
+    pub(super) fn handle_item_release(
+        &mut self,
+        entity: Entity,
+        release: &TransactionTransactionDataItemRelease,
+    ) {
+        let using = self.ecs.world_mut().entity_mut(entity).take::<UsingItem>();
+        if release.action_type != TransactionTransactionDataItemReleaseActionType::Consume {
+            return;
+        }
+        let Some(using) = using else {
+            debug!(?entity, "Consume without using an item");
+            return;
+        };
+        let Some((slot, item, food)) = self.held_food(entity) else {
+            return;
+        };
+        let elapsed = self.current_tick.saturating_sub(using.started);
+        if slot != using.slot || elapsed + EAT_TOLERANCE_TICKS < u64::from(food.eat_ticks) {
+            debug!(?entity, elapsed, "Rejected eating");
+            return;
+        }
+        self.eat(entity, slot, item, food);
+    }
+
+    /// Apply a food item and take it from the hotbar.
+    fn eat(&mut self, entity: Entity, slot: u8, item: ItemStack, food: Food) {
+        let world = self.ecs.world_mut();
+        match world.get_mut::<Hunger>(entity) {
+            Some(mut hunger) if hunger.can_eat(food.always_edible) => {
+                hunger.eat(food.nutrition, food.saturation)
+            }
+            _ => return,
+        }
+        if let Some(mut effects) = world.get_mut::<Effects>(entity) {
+            let mut rng = rand::thread_rng();
+            for effect in &food.effects {
+                if rng.r#gen::<f32>() < effect.chance {
+                    effects.add(effect.effect, effect.level, effect.duration_ticks);
+                }
+            }
+        }
+
+        let creative = world.get::<GameMode>(entity) == Some(&GameMode::Creative);
+        if creative {
+            return;
+        }
+        let Some(mut inventory) = world.get_mut::<MainInventory>(entity) else {
+            return;
+        };
+        let before = inventory.0.slots().to_vec();
+        let remaining = if item.count > 1 {
+            item.grow(-1)
+        } else {
+            ItemStack::empty()
+        };
+        let remainder = food.remainder.map(|id| ItemStack::new(id, 1));
+        let overflow = match remainder {
+            Some(remainder) if remaining.is_empty() => {
+                let _ = inventory.0.set_item(slot as usize, remainder);
+                None
+            }
+            Some(remainder) => {
+                let _ = inventory.0.set_item(slot as usize, remaining);
+                let (_, left) = inventory.0.add_item(remainder);
+                (!left.is_empty()).then_some(left)
+            }
+            None => {
+                let _ = inventory.0.set_item(slot as usize, remaining);
+                None
+            }
+        };
+        if let Some(left) = overflow {
+            let position = world.get::<Position>(entity).map_or(DVec3::ZERO, |p| p.0);
+            let world_id = WorldId::of(world, entity);
+            self.spawn_dropped_item(world_id, position, left, DVec3::ZERO, Some(entity));
+        }
+        self.send_main_inventory_changes(entity, &before);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_movement_exhaustion() {
+        let walking = PlayerState::default();
+        assert_eq!(movement_exhaustion(&walking, 0.2, false), 0.0);
+        assert_eq!(movement_exhaustion(&walking, 0.2, true), JUMP_EXHAUSTION);
+
+        let sprinting = PlayerState {
+            sprinting: true,
+            ..PlayerState::default()
+        };
+        assert!((movement_exhaustion(&sprinting, 0.5, false) - 0.05).abs() < 1e-6);
+        assert!((movement_exhaustion(&sprinting, 0.5, true) - 0.25).abs() < 1e-6);
+        // A teleport isn't a sprint
+        assert_eq!(movement_exhaustion(&sprinting, 100.0, false), 0.0);
+    }
+}
@@ -3,15 +3,23 @@
 //! Contains the send_join_packets method for sending initial game state.
 
 use super::GameServer;
-use crate::entity::components::{GameMode, PlayerSession, RuntimeEntityId};
+use crate::command::CommandSender;
+use crate::entity::components::{
+    ArmourInventory, Experience, GameMode, Health, HeldSlot, Hunger, MainInventory, OffhandSlot,
+    PlayerSession, RuntimeEntityId,
+};
+use crate::item::ItemStack;
+use crate::permission::op_level;
+use crate::world::WorldId;
+use bevy_ecs::entity::Entity;
+use bevy_ecs::world::World;
 use jolyne::valentine::items::ITEMS;
 use jolyne::valentine::types::{
-    AbilityLayers, AbilityLayersType, AbilitySet, CommandPermissionLevel, ContainerSlotType,
-    EntityProperties, FullContainerName, GameMode as ProtocolGameMode, Item, ItemLegacy,
-    ItemLegacyContent, ItemLegacyContentExtra, MetadataDictionary, MetadataDictionaryItem,
-    MetadataDictionaryItemKey, MetadataDictionaryItemType, MetadataDictionaryItemValue,
-    MetadataDictionaryItemValueDefault, MetadataFlags1, PermissionLevel, PlayerAttributesItem,
-    WindowIdVarint,
+    AbilityLayers, AbilityLayersType, AbilitySet, ContainerSlotType, EntityProperties,
+    FullContainerName, GameMode as ProtocolGameMode, Item, ItemLegacy, ItemLegacyContent,
+    ItemLegacyContentExtra, MetadataDictionary, MetadataDictionaryItem, MetadataDictionaryItemKey,
+    MetadataDictionaryItemType, MetadataDictionaryItemValue, MetadataDictionaryItemValueDefault,
+    MetadataFlags1, PlayerAttributesItem, WindowId, WindowIdVarint,
 };
 use jolyne::valentine::{
     ChunkRadiusUpdatePacket, SetEntityDataPacket, UpdateAbilitiesPacket, UpdateAttributesPacket,
@@ -19,13 +27,15 @@ use jolyne::valentine::{
 use jolyne::valentine::{
     CreativeContentPacket, CreativeContentPacketGroupsItem,
     CreativeContentPacketGroupsItemCategory, CreativeContentPacketItemsItem,
-    InventoryContentPacket, McpePacket, SetPlayerGameTypePacket,
+    InventoryContentPacket, McpePacket, PlayerHotbarPacket, SetPlayerGameTypePacket,
 };
 use tracing::debug;
 
 impl GameServer {
     /// Send all join packets to a newly spawned player.
-    pub(super) fn send_join_packets(&self, entity: bevy_ecs::entity::Entity) {
+    pub(super) fn send_join_packets(&mut self, entity: bevy_ecs::entity::Entity) {
+        let inventory = self.inventory_contents(entity);
+
         let world = self.ecs.world();
         let session = match world.get::<PlayerSession>(entity) {
             Some(s) => s,
@@ -59,93 +69,33 @@ impl GameServer {
         }));
         debug!("Sent SetPlayerGameType: {:?}", game_mode);
 
-        // Build abilities based on gamemode (following Dragonfly's approach)
-        let mut abilities =
-            AbilitySet::WALK_SPEED | AbilitySet::FLY_SPEED | AbilitySet::VERTICAL_FLY_SPEED;
-
-        // All modes can interact (except spectator limitations handled elsewhere)
-        if game_mode.can_break_blocks() {
-            abilities |= AbilitySet::BUILD | AbilitySet::MINE;
-        }
-        abilities |= AbilitySet::DOORS_AND_SWITCHES | AbilitySet::OPEN_CONTAINERS;
-        abilities |= AbilitySet::ATTACK_PLAYERS | AbilitySet::ATTACK_MOBS;
-
-        // Creative/Spectator: allow flight and invulnerability
-        if game_mode.allows_flight() {
-            abilities |= AbilitySet::MAY_FLY;
-        }
-        if !game_mode.allows_damage() {
-            abilities |= AbilitySet::INVULNERABLE;
-        }
-        // Creative: instant break
-        if game_mode.instant_break() {
-            abilities |= AbilitySet::INSTANT_BUILD;
-        }
-
-        let layer = AbilityLayers {
-            type_: AbilityLayersType::Base,
-            // Allowed = all abilities that CAN be toggled
-            allowed: AbilitySet::BUILD
-                | AbilitySet::MINE
-                | AbilitySet::DOORS_AND_SWITCHES
-                | AbilitySet::OPEN_CONTAINERS
-                | AbilitySet::ATTACK_PLAYERS
-                | AbilitySet::ATTACK_MOBS
-                | AbilitySet::WALK_SPEED
-                | AbilitySet::FLY_SPEED
-                | AbilitySet::VERTICAL_FLY_SPEED
-                | AbilitySet::MAY_FLY
-                | AbilitySet::INVULNERABLE
-                | AbilitySet::INSTANT_BUILD,
-            // Enabled = abilities that are currently active
-            enabled: abilities,
-            fly_speed: 0.05,         // Horizontal flight speed (Dragonfly default)
-            vertical_fly_speed: 1.0, // Vertical flight speed (Dragonfly default)
-            walk_speed: 0.1,
-        };
+        let _ = session.send(McpePacket::from(abilities_packet(world, entity)));
 
-        let _ = session.send(McpePacket::from(UpdateAbilitiesPacket {
-            entity_unique_id: runtime_id,
-            permission_level: PermissionLevel::Member,
-            command_permission: CommandPermissionLevel::Normal,
-            abilities: vec![layer],
-        }));
+        // Vitals come from the player's components, restored from saved data
+        let health = world.get::<Health>(entity).cloned().unwrap_or_default();
+        let hunger = world.get::<Hunger>(entity).cloned().unwrap_or_default();
+        let experience = world.get::<Experience>(entity).cloned().unwrap_or_default();
 
-        fn attr(
-            name: &str,
-            current: f32,
-            max: f32,
-            default: f32,
-            default_max: f32,
-        ) -> PlayerAttributesItem {
-            PlayerAttributesItem {
-                min: 0.0,
-                max,
-                current,
-                default_min: 0.0,
-                default_max,
-                default,
-                name: name.to_string(),
-                modifiers: vec![],
-            }
-        }
-
-        let attributes = vec![
-            attr("minecraft:health", 20.0, 20.0, 20.0, 20.0),
-            attr("minecraft:absorption", 0.0, f32::MAX, 0.0, f32::MAX),
-            attr("minecraft:movement", 0.1, f32::MAX, 0.1, f32::MAX),
-            attr("minecraft:player.hunger", 20.0, 20.0, 20.0, 20.0),
-            attr("minecraft:player.saturation", 20.0, 20.0, 20.0, 20.0),
-            attr("minecraft:player.exhaustion", 0.0, 5.0, 0.0, 5.0),
-            attr(
+        let mut attributes = vec![
+            attribute("minecraft:health", health.current, health.max, 20.0, 20.0),
+            attribute("minecraft:absorption", 0.0, f32::MAX, 0.0, f32::MAX),
+            attribute("minecraft:movement", 0.1, f32::MAX, 0.1, f32::MAX),
+            attribute(
                 "minecraft:player.level",
-                0.0,
+                experience.level as f32,
                 i32::MAX as f32,
                 0.0,
                 i32::MAX as f32,
             ),
-            attr("minecraft:player.experience", 0.0, 1.0, 0.0, 1.0),
+            attribute(
+                "minecraft:player.experience",
+                experience.progress,
+                1.0,
+                0.0,
+                1.0,
+            ),
         ];
+        attributes.extend(super::hunger::hunger_attributes(&hunger));
 
         let _ = session.send(McpePacket::from(UpdateAttributesPacket {
             runtime_entity_id: runtime_id,
@@ -221,22 +171,88 @@ impl GameServer {
 
         // Send inventory contents to enable inventory UI
         // Without these packets, the client won't allow opening the inventory
-        self.send_inventory_contents(session);
+        self.send_inventory_contents(session, inventory);
+        let selected_slot = world.get::<HeldSlot>(entity).map_or(0, |held| held.0);
+        let _ = session.send(McpePacket::from(PlayerHotbarPacket {
+            selected_slot: selected_slot as i32,
+            window_id: WindowId::Inventory,
+            select_slot: true,
+        }));
+
+        // Clock and sky, then rules and difficulty
+        for packet in super::time::time_packets(world, WorldId::of(world, entity))
+            .into_iter()
+            .chain(super::rules::rules_packets(world))
+        {
+            let _ = session.send(packet);
+        }
+
+        // Recipes for the crafting grid, furnaces and stonecutter
+        let _ = session.send(McpePacket::from(self.crafting_data.as_ref().clone()));
+        debug!(
+            recipes = self.crafting_data.recipes.len(),
+            "Sent CraftingData"
+        );
+
+        // Command list for autocompletion
+        let sender = CommandSender::Player {
+            entity,
+            session: session.session_id,
+        };
+        let _ = session.send(McpePacket::from(
+            self.commands
+                .to_packet(|command| sender.can_use(world, command)),
+        ));
 
         // Creative content packet causes client disconnect - needs investigation
         // TODO: Fix item format in creative content packet
         // self.send_creative_content(session);  // DISABLED FOR TEST - use jolyne's empty one
     }
 
-    /// Send initial inventory contents to the client.
+    /// Network form of a player's main, offhand and armour slots.
+    ///
+    /// Allocates stack network IDs for every non-empty slot.
+    pub(super) fn inventory_contents(&mut self, entity: Entity) -> InventoryContents {
+        let world = self.ecs.world();
+        let main: Vec<ItemStack> = world
+            .get::<MainInventory>(entity)
+            .map(|inv| inv.0.slots().to_vec())
+            .unwrap_or_else(|| vec![ItemStack::empty(); 36]);
+        let offhand = world
+            .get::<OffhandSlot>(entity)
+            .map(|offhand| offhand.0.clone())
+            .unwrap_or_default();
+        let armour: Vec<ItemStack> = world
+            .get::<ArmourInventory>(entity)
+            .map(|armour| armour.slots().to_vec())
+            .unwrap_or_else(|| vec![ItemStack::empty(); 4]);
+
+        InventoryContents {
+            main: main
+                .iter()
+                .map(|item| self.network_item_for(entity, item))
+                .collect(),
+            offhand: vec![self.network_item_for(entity, &offhand)],
+            armour: armour
+                .iter()
+                .map(|item| self.network_item_for(entity, item))
+                .collect(),
+        }
+    }
+
+    /// Send the full inventory contents to the client.
     ///
-    /// This is required for the inventory UI to work. We send empty inventories
-    /// for all player inventory windows:
+    /// This is required for the inventory UI to work. We send the player's
+    /// items for each player inventory window:
     /// - Main inventory (36 slots: 9 hotbar + 27 main)
     /// - Offhand (1 slot)
     /// - Armor (4 slots)
-    /// - UI (for crafting grid, cursor, etc.)
-    fn send_inventory_contents(&self, session: &PlayerSession) {
+    /// - UI (for crafting grid, cursor, etc.), always empty
+    pub(super) fn send_inventory_contents(
+        &self,
+        session: &PlayerSession,
+        contents: InventoryContents,
+    ) {
         debug!("Sending inventory contents to client");
 
         // Helper to create an empty item
@@ -248,19 +264,19 @@ impl GameServer {
             dynamic_container_id: None,
         };
 
-        // Main inventory: 36 empty slots (hotbar 0-8, main 9-35)
+        // Main inventory: 36 slots (hotbar 0-8, main 9-35)
         let result = session.send(McpePacket::from(InventoryContentPacket {
             window_id: WindowIdVarint::Inventory, // 0
-            input: vec![empty_item.clone(); 36],
+            input: contents.main,
             container: container_name.clone(),
             storage_item: empty_item.clone(),
         }));
         debug!("Sent main inventory (36 slots, window=0): {:?}", result);
 
-        // Offhand: 1 empty slot
+        // Offhand: 1 slot
         let result = session.send(McpePacket::from(InventoryContentPacket {
             window_id: WindowIdVarint::Offhand, // 119
-            input: vec![empty_item.clone(); 1],
+            input: contents.offhand,
             container: FullContainerName {
                 container_id: ContainerSlotType::Offhand,
                 dynamic_container_id: None,
@@ -269,10 +285,10 @@ impl GameServer {
         }));
         debug!("Sent offhand inventory (1 slot, window=119): {:?}", result);
 
-        // Armor: 4 empty slots (helmet, chestplate, leggings, boots)
+        // Armor: 4 slots (helmet, chestplate, leggings, boots)
         let result = session.send(McpePacket::from(InventoryContentPacket {
             window_id: WindowIdVarint::Armor, // 120
-            input: vec![empty_item.clone(); 4],
+            input: contents.armour,
             container: FullContainerName {
                 container_id: ContainerSlotType::Armor,
                 dynamic_container_id: None,
@@ -296,3 +312,94 @@ impl GameServer {
     }
 }
 
+/// Network items for the player inventory windows.
+pub(super) struct InventoryContents {
+    main: Vec<Item>,
+    offhand: Vec<Item>,
+    armour: Vec<Item>,
+}
+
+/// `UpdateAbilities` for a player, from their game mode and op level.
+pub(crate) fn abilities_packet(world: &World, entity: Entity) -> UpdateAbilitiesPacket {
+    let runtime_id = world
+        .get::<RuntimeEntityId>(entity)
+        .map(|r| r.0)
+        .unwrap_or(1);
+    let game_mode = world
+        .get::<GameMode>(entity)
+        .copied()
+        .unwrap_or(GameMode::Survival);
+    let op_level = op_level(world, entity);
+
+    // Build abilities based on gamemode (following Dragonfly's approach)
+    let mut abilities =
+        AbilitySet::WALK_SPEED | AbilitySet::FLY_SPEED | AbilitySet::VERTICAL_FLY_SPEED;
+
+    // All modes can interact (except spectator limitations handled elsewhere)
+    if game_mode.can_break_blocks() {
+        abilities |= AbilitySet::BUILD | AbilitySet::MINE;
+    }
+    abilities |= AbilitySet::DOORS_AND_SWITCHES | AbilitySet::OPEN_CONTAINERS;
+    abilities |= AbilitySet::ATTACK_PLAYERS | AbilitySet::ATTACK_MOBS;
+
+    // Creative/Spectator: allow flight and invulnerability
+    if game_mode.allows_flight() {
+        abilities |= AbilitySet::MAY_FLY;
+    }
+    if !game_mode.allows_damage() {
+        abilities |= AbilitySet::INVULNERABLE;
+    }
+    // Creative: instant break
+    if game_mode.instant_break() {
+        abilities |= AbilitySet::INSTANT_BUILD;
+    }
+
+    let layer = AbilityLayers {
+        type_: AbilityLayersType::Base,
+        // Allowed = all abilities that CAN be toggled
+        allowed: AbilitySet::BUILD
+            | AbilitySet::MINE
+            | AbilitySet::DOORS_AND_SWITCHES
+            | AbilitySet::OPEN_CONTAINERS
+            | AbilitySet::ATTACK_PLAYERS
+            | AbilitySet::ATTACK_MOBS
+            | AbilitySet::WALK_SPEED
+            | AbilitySet::FLY_SPEED
+            | AbilitySet::VERTICAL_FLY_SPEED
+            | AbilitySet::MAY_FLY
+            | AbilitySet::INVULNERABLE
+            | AbilitySet::INSTANT_BUILD,
+        // Enabled = abilities that are currently active
+        enabled: abilities,
+        fly_speed: 0.05,         // Horizontal flight speed (Dragonfly default)
+        vertical_fly_speed: 1.0, // Vertical flight speed (Dragonfly default)
+        walk_speed: 0.1,
+    };
+
+    UpdateAbilitiesPacket {
+        entity_unique_id: runtime_id,
+        permission_level: op_level.permission_level(),
+        command_permission: op_level.command_permission(),
+        abilities: vec![layer],
+    }
+}
+
+/// A player attribute with a minimum of zero.
+pub(super) fn attribute(
+    name: &str,
+    current: f32,
+    max: f32,
+    default: f32,
+    default_max: f32,
+) -> PlayerAttributesItem {
+    PlayerAttributesItem {
+        min: 0.0,
+        max,
+        current,
+        default_min: 0.0,
+        default_max,
+        default,
+        name: name.to_string(),
+        modifiers: vec![],
+    }
+}
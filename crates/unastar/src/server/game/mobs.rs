@@ -0,0 +1,393 @@
+//! Mob entities.
+//!
+//! [`spawn_mob`] creates a mob from its `MobDefinition` and shows it to
+//! every player in its world; players who join or enter the world later are
+//! sent the mobs already in it. Mobs are moved by the AI and physics systems
+//! on the server, and `broadcast_mob_movement` sends their moves to players
+//! nearby. A mob hitting a player swings its arm and knocks the player back,
+//! like a player's hit.
+
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use jolyne::valentine::types::{
+    AnimatePacketActionId, DeltaMoveFlags, EntityAttributesItem, EntityProperties,
+    MetadataDictionary, MetadataDictionaryItem, MetadataDictionaryItemKey,
+    MetadataDictionaryItemType, MetadataDictionaryItemValue, MetadataDictionaryItemValueDefault,
+    MetadataFlags1, Vec3F,
+};
+use jolyne::valentine::{
+    AddEntityPacket, AnimatePacket, McpePacket, MoveEntityDeltaPacket, RemoveEntityPacket,
+};
+use tracing::debug;
+
+use super::GameServer;
+use super::types::EntityIds;
+use crate::entity::bundles::MobBundle;
+use crate::entity::components::{
+    Age, AiState, DamageImmunity, Effects, FallDistance, Health, Hitbox, Hostile,
+    LastBroadcastPosition, Living, Mob, MobAge, MobType, OnGround, Player, PlayerSession, Position,
+    Rotation, RuntimeId, SpatialChunk, Speed, Velocity,
+};
+use crate::entity::damage::{DamageEvent, DamageSource};
+use crate::entity::mobs::MobDefinition;
+use crate::server::broadcast::{
+    EntityGrid, MOVEMENT_THRESHOLD_SQ, ROTATION_THRESHOLD, position_distance_sq,
+};
+use crate::world::ecs::WorldId;
+
+/// Age of a newly spawned baby: twenty minutes until it grows up.
+const BABY_AGE: i32 = -24000;
+
+/// Spawn a mob and show it to every player in its world.
+///
+/// `identifier` may leave out the `minecraft:` prefix. Returns `None` if
+/// there is no such mob type.
+pub fn spawn_mob(
+    world: &mut World,
+    world_id: WorldId,
+    identifier: &str,
+    position: DVec3,
+    baby: bool,
+) -> Option<Entity> {
+    let definition = MobDefinition::get(identifier)?;
+    let runtime_id = world.get_resource_or_init::<EntityIds>().allocate();
+    let mob_type = MobType::new(definition.identifier);
+    let health = Health::new(definition.health);
+    let rotation = Rotation::default();
+    let packet = add_entity_packet(
+        runtime_id,
+        &mob_type,
+        position,
+        &rotation,
+        &health,
+        baby,
+        &definition.hitbox,
+    );
+
+    let entity = world
+        .spawn(MobBundle {
+            mob: Mob,
+            mob_type,
+            living: Living,
+            position: Position(position),
+            velocity: Velocity::default(),
+            rotation,
+            on_ground: OnGround::default(),
+            hitbox: definition.hitbox,
+            runtime_id: RuntimeId(runtime_id),
+            health,
+            effects: Effects::default(),
+            damage_immunity: DamageImmunity::default(),
+            fall_distance: FallDistance::default(),
+            speed: Speed(definition.speed),
+            ai_state: AiState::default(),
+            goals: definition.goals(),
+            hostile: Hostile(definition.is_hostile()),
+            mob_age: MobAge {
+                ticks: if baby { BABY_AGE } else { 0 },
+            },
+            age: Age::default(),
+            world: world_id,
+            spatial_chunk: SpatialChunk::from_position(&Position(position)),
+            last_broadcast: LastBroadcastPosition {
+                x: position.x,
+                y: position.y,
+                z: position.z,
+                yaw: 0.0,
+                pitch: 0.0,
+            },
+        })
+        .id();
+
+    let mut players = world.query_filtered::<(&PlayerSession, &WorldId), With<Player>>();
+    for (session, _) in players.iter(world).filter(|(_, w)| **w == world_id) {
+        let _ = session.send(McpePacket::from(packet.clone()));
+    }
+    debug!(entity = ?entity, runtime_id, identifier = definition.identifier, "Spawned mob");
+    Some(entity)
+}
+
+/// AddEntity packet for a mob.
+fn add_entity_packet(
+    runtime_id: i64,
+    mob_type: &MobType,
+    position: DVec3,
+    rotation: &Rotation,
+    health: &Health,
+    baby: bool,
+    hitbox: &Hitbox,
+) -> AddEntityPacket {
+    let mut flags = MetadataFlags1::BREATHING
+        | MetadataFlags1::HAS_COLLISION
+        | MetadataFlags1::AFFECTED_BY_GRAVITY;
+    if baby {
+        flags |= MetadataFlags1::BABY;
+    }
+    let float = |key, value| MetadataDictionaryItem {
+        key,
+        type_: MetadataDictionaryItemType::Float,
+        value: MetadataDictionaryItemValue::Default(Box::new(Some(
+            MetadataDictionaryItemValueDefault::Float(value),
+        ))),
+    };
+    let metadata: MetadataDictionary = vec![
+        MetadataDictionaryItem {
+            key: MetadataDictionaryItemKey::Flags,
+            type_: MetadataDictionaryItemType::Long,
+            value: MetadataDictionaryItemValue::Flags(flags),
+        },
+        float(
+            MetadataDictionaryItemKey::BoundingboxWidth,
+            hitbox.width as f32,
+        ),
+        float(
+            MetadataDictionaryItemKey::BoundingboxHeight,
+            hitbox.height as f32,
+        ),
+    ];
+
+    AddEntityPacket {
+        unique_id: runtime_id,
+        runtime_id,
+        entity_type: mob_type.identifier.clone(),
+        position: vec3f(position),
+        velocity: Vec3F::default(),
+        pitch: rotation.pitch,
+        yaw: rotation.yaw,
+        head_yaw: rotation.head_yaw,
+        body_yaw: rotation.yaw,
+        attributes: vec![EntityAttributesItem {
+            name: "minecraft:health".to_string(),
+            min: 0.0,
+            value: health.current,
+            max: health.max,
+        }],
+        metadata,
+        properties: EntityProperties::default(),
+        links: vec![],
+    }
+}
+
+/// Byte angle as the protocol sends rotations: 256 steps per turn.
+fn byte_angle(degrees: f32) -> u8 {
+    (degrees.rem_euclid(360.0) / 360.0 * 256.0) as u8
+}
+
+/// Components a mob's move is broadcast from.
+type MovedMob = (
+    &'static RuntimeId,
+    &'static Position,
+    &'static Rotation,
+    &'static OnGround,
+    &'static WorldId,
+    &'static mut LastBroadcastPosition,
+);
+
+/// System: Send mob moves to players near the mob.
+pub(super) fn broadcast_mob_movement(
+    grid: Res<EntityGrid>,
+    mut mobs: Query<MovedMob, With<Mob>>,
+    sessions: Query<&PlayerSession>,
+) {
+    for (runtime_id, position, rotation, on_ground, world_id, mut last) in mobs.iter_mut() {
+        let moved = position_distance_sq(&last, position) > MOVEMENT_THRESHOLD_SQ
+            || (last.yaw - rotation.yaw).abs() > ROTATION_THRESHOLD
+            || (last.pitch - rotation.pitch).abs() > ROTATION_THRESHOLD;
+        if !moved {
+            continue;
+        }
+        *last = LastBroadcastPosition {
+            x: position.0.x,
+            y: position.0.y,
+            z: position.0.z,
+            yaw: rotation.yaw,
+            pitch: rotation.pitch,
+        };
+
+        let mut flags = DeltaMoveFlags::HAS_X
+            | DeltaMoveFlags::HAS_Y
+            | DeltaMoveFlags::HAS_Z
+            | DeltaMoveFlags::HAS_ROT_X
+            | DeltaMoveFlags::HAS_ROT_Y
+            | DeltaMoveFlags::HAS_ROT_Z;
+        if on_ground.0 {
+            flags |= DeltaMoveFlags::ON_GROUND;
+        }
+        let packet = McpePacket::from(MoveEntityDeltaPacket {
+            runtime_entity_id: runtime_id.0,
+            flags,
+            x: Some(position.0.x as f32),
+            y: Some(position.0.y as f32),
+            z: Some(position.0.z as f32),
+            rot_x: Some(byte_angle(rotation.pitch)),
+            rot_y: Some(byte_angle(rotation.yaw)),
+            rot_z: Some(byte_angle(rotation.head_yaw)),
+        });
+
+        let chunk = (
+            position.0.x.floor() as i32 >> 4,
+            position.0.z.floor() as i32 >> 4,
+        );
+        for viewer in grid.get_neighbors(*world_id, chunk, 1) {
+            if let Ok(session) = sessions.get(viewer) {
+                let _ = session.send(packet.clone());
+            }
+        }
+    }
+}
+
+/// Observer: Remove mobs from every client when the entity goes away.
+///
+/// Register with: `world.add_observer(broadcast_mob_removal)`
+pub(super) fn broadcast_mob_removal(
+    trigger: On<Remove, Mob>,
+    mobs: Query<&RuntimeId>,
+    players: Query<&PlayerSession, With<Player>>,
+) {
+    let Ok(runtime_id) = mobs.get(trigger.event().entity) else {
+        return;
+    };
+    let packet = RemoveEntityPacket {
+        entity_id_self: runtime_id.0,
+    };
+    for session in players.iter() {
+        let _ = session.send(McpePacket::from(packet.clone()));
+    }
+}
+
+/// Hits by mobs on players during the tick, handled after it.
+#[derive(Resource, Default)]
+pub(super) struct PendingMobHits(Vec<(Entity, Entity)>);
+
+/// Observer: Queue mob hits on players for `GameServer::process_mob_hits`.
+pub(super) fn queue_mob_hit(
+    trigger: On<DamageEvent>,
+    mobs: Query<(), With<Mob>>,
+    players: Query<(), With<Player>>,
+    mut pending: ResMut<PendingMobHits>,
+) {
+    let event = trigger.event();
+    if let DamageSource::Attack { attacker } = event.source
+        && mobs.contains(attacker)
+        && players.contains(event.entity)
+    {
+        pending.0.push((attacker, event.entity));
+    }
+}
+
+impl GameServer {
+    /// Send every mob in a player's world to them, after joining or
+    /// changing worlds.
+    pub(super) fn send_mobs(&mut self, viewer: Entity) {
+        let world = self.ecs.world_mut();
+        let world_id = WorldId::of(world, viewer);
+        let packets: Vec<AddEntityPacket> = world
+            .query_filtered::<(
+                &RuntimeId,
+                &MobType,
+                &Position,
+                &Rotation,
+                &Health,
+                &Hitbox,
+                Option<&MobAge>,
+                &WorldId,
+            ), With<Mob>>()
+            .iter(world)
+            .filter(|(.., mob_world)| **mob_world == world_id)
+            .map(|(rid, mob_type, pos, rot, health, hitbox, age, _)| {
+                let baby = age.is_some_and(MobAge::is_baby);
+                add_entity_packet(rid.0, mob_type, pos.0, rot, health, baby, hitbox)
+            })
+            .collect();
+
+        let Some(session) = world.get::<PlayerSession>(viewer) else {
+            return;
+        };
+        for packet in packets {
+            let _ = session.send(McpePacket::from(packet));
+        }
+    }
+
+    /// Swing the arm of every mob that hit a player this tick and knock the player back.
+    pub(super) fn process_mob_hits(&mut self) {
+        let hits = self
+            .ecs
+            .world_mut()
+            .get_resource_mut::<PendingMobHits>()
+            .map(|mut pending| std::mem::take(&mut pending.0))
+            .unwrap_or_default();
+        for (mob, player) in hits {
+            if let Some(runtime_id) = self.ecs.world().get::<RuntimeId>(mob).map(|rid| rid.0) {
+                self.broadcast_in_world(
+                    WorldId::of(self.ecs.world(), mob),
+                    McpePacket::from(AnimatePacket {
+                        action_id: AnimatePacketActionId::SwingArm,
+                        runtime_entity_id: runtime_id,
+                        data: 0.0,
+                        swing_source: None,
+                    }),
+                    None,
+                );
+            }
+            self.knock_back(mob, player, 0);
+            self.broadcast_hurt(player, false);
+        }
+    }
+}
+
+fn vec3f(v: DVec3) -> Vec3F {
+    Vec3F {
+        x: v.x as f32,
+        y: v.y as f32,
+        z: v.z as f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn_mob() {
+        let mut world = World::new();
+        let position = DVec3::new(0.5, 64.0, 0.5);
+        assert!(
+            spawn_mob(
+                &mut world,
+                WorldId::DEFAULT,
+                "minecraft:ghast",
+                position,
+                false
+            )
+            .is_none()
+        );
+
+        let cow = spawn_mob(&mut world, WorldId::DEFAULT, "cow", position, true).unwrap();
+        let zombie = spawn_mob(
+            &mut world,
+            WorldId::DEFAULT,
+            "minecraft:zombie",
+            position,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            world.get::<MobType>(cow).unwrap().identifier,
+            "minecraft:cow"
+        );
+        assert!(world.get::<MobAge>(cow).unwrap().is_baby());
+        assert!(!world.get::<Hostile>(cow).unwrap().0);
+        assert!(world.get::<Hostile>(zombie).unwrap().0);
+        assert_ne!(
+            world.get::<RuntimeId>(cow).unwrap().0,
+            world.get::<RuntimeId>(zombie).unwrap().0
+        );
+    }
+
+    #[test]
+    fn test_byte_angle() {
+        assert_eq!(byte_angle(0.0), 0);
+        assert_eq!(byte_angle(90.0), 64);
+        assert_eq!(byte_angle(-90.0), 192);
+    }
+}
@@ -3,37 +3,64 @@
 //! This module provides the pure ECS server implementation,
 //! replacing the legacy Server struct.
 
+mod access;
 mod blocks;
 mod chunks;
+mod combat;
 mod commands;
+mod containers;
+mod damage;
+mod death;
+mod drops;
 pub mod host;
+mod hunger;
 mod join;
+mod mobs;
 mod packet_domains;
 mod packet_router;
 mod packet_routing;
 mod packets;
+mod persistence;
 mod plugins;
+mod rules;
+mod smelting;
+mod spawning;
+mod stack_request;
+mod time;
 pub mod types;
+mod worlds;
 
 use bevy_ecs::prelude::*;
+use glam::DVec3;
 use jolyne::WorldTemplate;
 use jolyne::valentine::StartGamePacketDimension;
-use jolyne::valentine::types::{BlockCoordinates, Vec3F};
+use jolyne::valentine::types::{BlockCoordinates, LegacyEntityType, Vec3F};
+use jolyne::valentine::{
+    McpePacket, MovePlayerPacket, MovePlayerPacketMode, MovePlayerPacketTeleport,
+    MovePlayerPacketTeleportCause,
+};
 use std::sync::Arc;
 use tracing::{info, trace, warn};
 
-use crate::command::CommandRegistry;
+use crate::access::AccessControl;
+use crate::command::{CommandRegistry, PluginCommand};
 use crate::config::{PlayerDataStore, PlayerLastPosition, SpawnLocation};
-use crate::ecs::{CleanupSet, EntityLogicSet, NetworkSendSet, UnastarEcs};
+use crate::ecs::{CleanupSet, EntityLogicSet, NetworkSendSet, PhysicsSet, UnastarEcs};
 use crate::entity::bundles::PlayerBundle;
 use crate::entity::components::transform::{Position, Rotation};
 use crate::entity::components::{
-    ArmourInventory, BreakingState, ChunkRadius, CursorItem, GameMode, HeldSlot, InventoryOpened,
+    AirSupply, ArmourInventory, BreakingState, ChunkRadius, CursorItem, DamageImmunity, Effects,
+    Experience, FallDistance, GameMode, Health, HeldSlot, Hunger, InventoryOpened,
     ItemStackRequestState, LastBroadcastPosition, MainInventory, OffhandSlot, Player, PlayerInput,
-    PlayerName, PlayerSession, PlayerState, PlayerUuid, RuntimeEntityId, SpatialChunk,
+    PlayerName, PlayerSession, PlayerState, PlayerUuid, RuntimeEntityId, RuntimeIdIndex,
+    SpatialChunk, SpawnPoint, UiInventory,
+};
+use crate::entity::systems::{
+    effects, exhaust_on_damage, lifecycle, physics, tick_hunger, tick_mob_ai,
 };
 use crate::network::SessionId;
-use crate::registry::{BiomeRegistry, BlockRegistry, EntityRegistry, ItemRegistry};
+use crate::permission::Permissions;
+use crate::registry::{BiomeRegistry, BlockRegistry, EntityRegistry, ItemRegistry, RecipeRegistry};
 use crate::server::broadcast::{
     EntityGrid, broadcast_block_updates, broadcast_despawn_system, broadcast_movement_system,
     broadcast_spawn_system, cleanup_despawned_entities, sync_spatial_chunks, tick_block_breaking,
@@ -41,13 +68,22 @@ use crate::server::broadcast::{
 use crate::world::ecs::{
     BlockBroadcastEvent, ChunkLoadConfig, ChunkLoader, ChunkTickingState, LastPublisherState,
     PendingChunkGenerations, PlayerDespawnedEvent, PlayerSpawnedEvent, on_block_changed,
-    register_chunk_systems,
+    register_chunk_systems, update_block_entities,
 };
-use crate::world::{ChunkManager, WorldConfig};
+use crate::world::generator::jigsaw::TemplateManager;
+use crate::world::{Difficulty, GameRules, WorldConfig, WorldGenerator, WorldId, Worlds};
 
 // Re-export public types
 pub use super::config::ServerConfig;
-pub use types::{PlayerPersistenceData, PlayerSpawnData, SessionEntityMap};
+pub use access::{kick_denied_players, kick_player};
+pub use commands::sync_permissions;
+pub use mobs::spawn_mob;
+pub use rules::{set_difficulty, set_game_rule};
+pub use time::{set_time, set_weather};
+pub use types::{
+    PlayerPersistenceData, PlayerSpawnData, ServerRequest, ServerRequests, SessionEntityMap,
+};
+pub use worlds::{change_world, create_world, load_world, unload_world};
 
 /// The ECS-based game server.
 pub struct GameServer {
@@ -66,7 +102,8 @@ pub struct GameServer {
     pub entities: EntityRegistry,
     pub biomes: BiomeRegistry,
     pub blocks: BlockRegistry,
-    pub next_item_entity_id: i64,
+    pub recipes: Arc<RecipeRegistry>,
+    crafting_data: Arc<jolyne::valentine::CraftingDataPacket>,
 }
 
 impl GameServer {
@@ -83,6 +120,12 @@ impl GameServer {
         biomes.load_vanilla();
         let mut blocks = BlockRegistry::new();
         blocks.load_vanilla();
+        let mut recipes = RecipeRegistry::new();
+        if let Err(e) = recipes.load_vanilla() {
+            warn!("Failed to load recipes: {}. Crafting is disabled.", e);
+        }
+        let recipes = Arc::new(recipes);
+        let crafting_data = Arc::new(recipes.to_packet(&items, &blocks));
 
         let mut world_template = WorldTemplate::default();
         world_template.start_game_template.player_position = Vec3F {
@@ -102,6 +145,9 @@ impl GameServer {
             Arc::new(entities.to_available_entity_identifiers_packet());
         world_template.creative_content = Arc::new(Self::build_creative_content(&items, &blocks));
         world_template.start_game_template.block_properties = blocks.to_block_properties();
+        world_template.start_game_template.gamerules =
+            rules::start_game_rules(&GameRules::default());
+        world_template.start_game_template.difficulty = Difficulty::default().id() as i32;
 
         let world_template = Arc::new(world_template);
         let world_config = config.world;
@@ -110,8 +156,36 @@ impl GameServer {
             .insert_resource(types::ServerWorldTemplate(world_template.clone()));
         ecs.world_mut().insert_resource(SessionEntityMap::default());
         ecs.world_mut().insert_resource(EntityGrid::default());
+        ecs.world_mut().init_resource::<RuntimeIdIndex>();
+        ecs.world_mut().insert_resource(Permissions::default());
+        ecs.world_mut().insert_resource(AccessControl::default());
         ecs.world_mut()
-            .insert_resource(ChunkManager::new(world_config));
+            .insert_resource(types::ServerRequests::default());
+        ecs.world_mut().init_resource::<types::EntityIds>();
+        ecs.world_mut().insert_resource(Difficulty::default());
+        ecs.world_mut().insert_resource(GameRules::default());
+        let templates = match &config.structure_templates {
+            Some(dir) => Arc::new(TemplateManager::new(dir)),
+            None => {
+                let vanilla = std::iter::once(&world_config)
+                    .chain(config.worlds.iter().map(|entry| &entry.config))
+                    .any(|world| matches!(world.generator, WorldGenerator::Vanilla { .. }));
+                if vanilla {
+                    warn!(
+                        "No `server.structure_templates` directory set: villages, outposts \
+                         and trial chambers won't generate"
+                    );
+                }
+                Arc::default()
+            }
+        };
+        let mut worlds = Worlds::with_templates(world_config, templates);
+        for entry in &config.worlds {
+            if let Err(e) = worlds.register(&entry.name, entry.config) {
+                warn!(world = %entry.name, error = %e, "Skipping configured world");
+            }
+        }
+        ecs.world_mut().insert_resource(worlds);
         ecs.world_mut()
             .insert_resource(ChunkLoadConfig::from_server_config(&config));
         ecs.world_mut().init_resource::<PendingChunkGenerations>();
@@ -141,16 +215,59 @@ impl GameServer {
             plugin_registry.add_plugin(plugin, ecs.world_mut());
         }
 
+        let mut commands = CommandRegistry::with_defaults();
+        for (plugin, spec) in plugin_registry.commands() {
+            commands.register(PluginCommand::new(*plugin, spec.clone()));
+        }
+
         ecs.world_mut().insert_resource(plugin_registry);
         ecs.world_mut()
             .insert_resource(unastar_api::native::NativeActionQueue::default());
 
         ecs.world_mut().add_observer(on_block_changed);
+        ecs.world_mut().add_observer(update_block_entities);
+        ecs.world_mut().add_observer(physics::apply_knockback);
+        ecs.world_mut().add_observer(lifecycle::record_last_damage);
+        ecs.world_mut().add_observer(death::queue_death);
+        ecs.world_mut().add_observer(exhaust_on_damage);
+        ecs.world_mut().add_observer(drops::broadcast_item_removal);
+        ecs.world_mut().add_observer(mobs::broadcast_mob_removal);
+        ecs.world_mut().add_observer(mobs::queue_mob_hit);
+        ecs.world_mut().init_resource::<death::PendingDeaths>();
+        ecs.world_mut().init_resource::<mobs::PendingMobHits>();
+        ecs.world_mut()
+            .init_resource::<worlds::PendingWorldChanges>();
+        ecs.schedule_mut().add_systems(
+            (
+                physics::apply_gravity,
+                physics::apply_velocity,
+                physics::apply_drag,
+                physics::clamp_velocity,
+            )
+                .chain()
+                .in_set(PhysicsSet),
+        );
         register_chunk_systems(ecs.schedule_mut());
         ecs.schedule_mut().add_systems(
             (
                 tick_block_breaking,
+                time::tick_time_and_weather,
                 (sync_native_actions, plugins::process_plugin_actions).chain(),
+                (lifecycle::tick_mob_age, tick_mob_ai).chain(),
+                (
+                    effects::tick_damage_immunity,
+                    effects::tick_effects,
+                    effects::apply_regeneration,
+                    effects::apply_poison,
+                    effects::apply_wither,
+                    effects::tick_fire,
+                    tick_hunger,
+                    lifecycle::tick_item_pickup_delay,
+                    lifecycle::tick_item_despawn,
+                    lifecycle::detect_deaths,
+                    lifecycle::despawn_dead,
+                )
+                    .chain(),
             )
                 .in_set(EntityLogicSet),
         );
@@ -159,8 +276,11 @@ impl GameServer {
                 sync_spatial_chunks,
                 broadcast_spawn_system,
                 broadcast_movement_system,
+                mobs::broadcast_mob_movement,
                 broadcast_despawn_system,
                 broadcast_block_updates,
+                damage::sync_player_health,
+                hunger::sync_player_hunger,
             )
                 .chain()
                 .in_set(NetworkSendSet),
@@ -172,6 +292,8 @@ impl GameServer {
             .insert_resource(types::ItemRegistryResource(Arc::new(items.clone())));
         ecs.world_mut()
             .insert_resource(types::BlockRegistryResource(Arc::new(blocks.clone())));
+        ecs.world_mut()
+            .insert_resource(types::RecipeRegistryResource(recipes.clone()));
 
         info!("Registries loaded");
         Self {
@@ -179,7 +301,7 @@ impl GameServer {
             world_config,
             world_template,
             config,
-            commands: CommandRegistry::with_defaults(),
+            commands,
             current_tick: 0,
             player_data_store: None,
             save_previous_position: false,
@@ -190,10 +312,16 @@ impl GameServer {
             entities,
             biomes,
             blocks,
-            next_item_entity_id: 100000,
+            recipes,
+            crafting_data,
         }
     }
 
+    /// Use `permissions` for ops and permission nodes, e.g. one loaded from disk.
+    pub fn set_permissions(&mut self, permissions: Permissions) {
+        self.ecs.world_mut().insert_resource(permissions);
+    }
+
     pub fn set_player_data_store(&mut self, store: Arc<PlayerDataStore>, save_previous: bool) {
         self.player_data_store = Some(store);
         self.save_previous_position = save_previous;
@@ -212,14 +340,62 @@ impl GameServer {
         self.world_provider = Some(provider);
     }
 
-    pub async fn save_all_players(&self) -> usize {
-        // Implementation moved back here, simplified for brevity
-        0
-    }
-
+    /// Save every dirty loaded chunk in every world, including its block
+    /// entities.
+    ///
+    /// Returns the number of chunks saved.
     pub async fn save_all_chunks(&mut self) -> usize {
-        // Implementation moved back here, simplified for brevity
-        0
+        use crate::storage::ChunkColumn;
+        use crate::world::ecs::{BlockEntityWorldExt, ChunkData, ChunkPosition, ChunkStateFlags};
+
+        let worlds: Vec<(WorldId, Arc<dyn crate::storage::WorldProvider>, i32)> = self
+            .ecs
+            .world()
+            .resource::<Worlds>()
+            .iter()
+            .filter_map(|(id, manager)| Some((id, manager.provider()?, manager.dimension())))
+            .collect();
+
+        let mut saved = 0;
+        for (world_id, provider, dim) in worlds {
+            let columns: Vec<(Entity, ChunkPosition, ChunkColumn)> = {
+                let world = self.ecs.world_mut();
+                let dirty: Vec<(Entity, ChunkPosition)> = world
+                    .query::<(Entity, &ChunkPosition, &ChunkStateFlags, &WorldId)>()
+                    .iter(world)
+                    .filter(|(_, _, flags, id)| flags.is_dirty() && **id == world_id)
+                    .map(|(entity, pos, _, _)| (entity, *pos))
+                    .collect();
+                dirty
+                    .into_iter()
+                    .filter_map(|(entity, pos)| {
+                        let chunk = world.get::<ChunkData>(entity)?.inner.clone();
+                        let column = ChunkColumn::new(chunk)
+                            .with_block_entities(world.chunk_block_entities(entity));
+                        Some((entity, pos, column))
+                    })
+                    .collect()
+            };
+
+            for (entity, pos, column) in columns {
+                match provider.save_column(pos.to_chunk_pos(), dim, &column).await {
+                    Ok(()) => {
+                        saved += 1;
+                        if let Some(mut flags) =
+                            self.ecs.world_mut().get_mut::<ChunkStateFlags>(entity)
+                        {
+                            flags.clear_dirty();
+                        }
+                    }
+                    Err(e) => warn!(chunk = ?(pos.x, pos.z), error = %e, "Failed to save chunk"),
+                }
+            }
+
+            if let Err(e) = provider.flush().await {
+                warn!(world = ?world_id, error = %e, "Failed to flush world storage");
+            }
+        }
+        saved
     }
 
     pub fn spawn_player(&mut self, data: PlayerSpawnData) -> Entity {
@@ -252,6 +428,7 @@ impl GameServer {
                     data.display_name,
                     data.xuid,
                     data.uuid,
+                    data.address,
                     data.outbound_tx,
                 ),
                 runtime_id: RuntimeEntityId(runtime_id),
@@ -262,6 +439,7 @@ impl GameServer {
                 input: PlayerInput::default(),
                 chunk_radius: ChunkRadius(data.chunk_radius),
                 breaking_state: BreakingState::default(),
+                world: WorldId::DEFAULT,
                 spatial_chunk: SpatialChunk::from_position(&position),
                 last_broadcast: LastBroadcastPosition {
                     x: position.0.x,
@@ -277,14 +455,32 @@ impl GameServer {
                 offhand: OffhandSlot::default(),
                 held_slot: HeldSlot::default(),
                 cursor: CursorItem::default(),
+                ui_inventory: UiInventory::default(),
                 inventory_opened: InventoryOpened::default(),
                 item_stack_state: ItemStackRequestState::default(),
+                health: Health::default(),
+                hunger: Hunger::default(),
+                experience: Experience::default(),
+                air_supply: AirSupply::default(),
+                effects: Effects::default(),
+                spawn_point: SpawnPoint::default(),
+                damage_immunity: DamageImmunity::default(),
+                fall_distance: FallDistance::default(),
             })
             .id();
 
         if let Some(mut session_map) = self.ecs.world_mut().get_resource_mut::<SessionEntityMap>() {
             session_map.insert(data.session_id, entity);
         }
+        if let Some(saved) = &data.player_data {
+            self.apply_player_data(entity, saved);
+        }
+        // Players saved in another world go back to it if it is loaded;
+        // the spawn rules placed them in the default world otherwise
+        let saved_world = data.player_data.as_ref().and_then(|saved| {
+            let id = self.ecs.world().resource::<Worlds>().id(&saved.world)?;
+            (!id.is_default()).then(|| (id, DVec3::from_array(saved.position)))
+        });
 
         self.ecs.world_mut().write_message(PlayerSpawnedEvent {
             entity,
@@ -292,6 +488,12 @@ impl GameServer {
             runtime_id,
         });
         self.send_join_packets(entity);
+        if let Some((world_id, position)) = saved_world {
+            self.move_to_world(entity, world_id, Some(position));
+        } else {
+            self.send_dropped_items(entity);
+            self.send_mobs(entity);
+        }
         info!(session_id = data.session_id, "Player spawned as ECS entity");
         entity
     }
@@ -306,14 +508,36 @@ impl GameServer {
             }
         };
         if let Some(entity) = entity {
+            self.save_player(entity);
             self.ecs.world_mut().despawn(entity);
             info!(session_id, "Player despawned");
         }
     }
 
+    /// Move a player and tell its client.
+    ///
+    /// Keeps the current rotation when `rotation` is `None`. Chunk loading
+    /// follows the player to the new position.
+    pub fn teleport_player(
+        &mut self,
+        entity: Entity,
+        position: DVec3,
+        rotation: Option<Rotation>,
+        cause: MovePlayerPacketTeleportCause,
+    ) {
+        teleport_player(self.ecs.world_mut(), entity, position, rotation, cause);
+    }
+
     pub fn tick(&mut self) {
         self.current_tick += 1;
         self.ecs.tick();
+        self.process_deaths();
+        self.process_mob_hits();
+        self.tick_natural_spawning();
+        self.process_world_changes();
+        self.tick_item_pickups();
+        self.tick_furnaces();
+        self.tick_access();
         if self.current_tick % 100 == 0 {
             trace!(tick = self.current_tick, "Tick");
         }
@@ -412,6 +636,66 @@ impl Default for GameServer {
     }
 }
 
+/// Move a player and tell its client.
+///
+/// World-level form of [`GameServer::teleport_player`] for code that only
+/// has the ECS world, such as commands.
+pub fn teleport_player(
+    world: &mut World,
+    entity: Entity,
+    position: DVec3,
+    rotation: Option<Rotation>,
+    cause: MovePlayerPacketTeleportCause,
+) {
+    let Some(runtime_id) = world.get::<RuntimeEntityId>(entity).map(|r| r.0) else {
+        return;
+    };
+    if let Some(mut current) = world.get_mut::<Position>(entity) {
+        current.0 = position;
+    }
+    let rotation = match rotation {
+        Some(rotation) => {
+            world.entity_mut(entity).insert(rotation);
+            rotation
+        }
+        None => world.get::<Rotation>(entity).copied().unwrap_or_default(),
+    };
+    if let Some(mut fall_distance) = world.get_mut::<FallDistance>(entity) {
+        fall_distance.reset();
+    }
+    if let Some(mut chunk_loader) = world.get_mut::<ChunkLoader>(entity) {
+        let chunk_x = (position.x / 16.0).floor() as i32;
+        let chunk_z = (position.z / 16.0).floor() as i32;
+        chunk_loader.move_to(chunk_x, chunk_z);
+    }
+
+    let tick = world
+        .get_resource::<crate::ecs::resources::TickCounter>()
+        .map_or(0, |tick| tick.current as i64);
+    let packet = MovePlayerPacket {
+        runtime_id: runtime_id as i32,
+        position: Vec3F {
+            x: position.x as f32,
+            y: position.y as f32,
+            z: position.z as f32,
+        },
+        pitch: rotation.pitch,
+        yaw: rotation.yaw,
+        head_yaw: rotation.yaw,
+        mode: MovePlayerPacketMode::Teleport,
+        on_ground: false,
+        ridden_runtime_id: 0,
+        teleport: Some(MovePlayerPacketTeleport {
+            cause,
+            source_entity_type: LegacyEntityType::Player,
+        }),
+        tick,
+    };
+    if let Some(session) = world.get::<PlayerSession>(entity) {
+        let _ = session.send(McpePacket::from(packet));
+    }
+}
+
 /// System to bridge NativeActionQueue (from native plugins) to ActionQueue (processed by server).
 fn sync_native_actions(
     mut native_queue: ResMut<unastar_api::native::NativeActionQueue>,
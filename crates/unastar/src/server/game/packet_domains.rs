@@ -95,4 +95,9 @@ pub enum ChatPacket {
 pub enum SpawnPacket {
     /// Spawn-related player actions (respawn, dimension change acknowledgment)
     Action(PlayerActionPacket),
+
+    /// Respawn button pressed on the death screen
+    ///
+    /// The packet itself is handled by `GameServer::handle_respawn`.
+    Respawn,
 }
@@ -124,6 +124,14 @@ impl PacketRouter {
                     .push((session_id, entity, ChatPacket::Command((**pk).clone())));
             }
 
+            // ===== Spawn Domain =====
+            // RespawnPacket (NOT boxed)
+            McpePacketData::PacketRespawn(_) => {
+                queues
+                    .spawn
+                    .push((session_id, entity, SpawnPacket::Respawn));
+            }
+
             // ===== Discriminant-Based Routing =====
 
             // Box<PlayerActionPacket> - route by action field
@@ -4,15 +4,16 @@
 
 use bevy_ecs::entity::Entity;
 use glam::DVec3;
-use jolyne::valentine::ContainerSlotType;
-use tracing::{debug, info, trace, warn};
+use tracing::{debug, info, trace};
 
 use super::GameServer;
+use super::hunger;
 use super::types::SessionEntityMap;
 use crate::entity::components::transform::{Position, Rotation};
 use crate::entity::components::{
-    HeldSlot, InventoryOpened, PlayerInput, PlayerSession, PlayerState,
+    FallDistance, HeldSlot, InventoryOpened, PlayerInput, PlayerSession, PlayerState,
 };
+use crate::entity::systems::apply_fall_damage;
 use crate::network::SessionId;
 use jolyne::valentine::types::{Action, BlockCoordinates, InputFlag, WindowId, WindowType};
 use jolyne::valentine::{
@@ -106,6 +107,9 @@ impl GameServer {
             McpePacketData::PacketPlayerAction(pk) => {
                 self.handle_player_action(entity, pk);
             }
+            McpePacketData::PacketRespawn(pk) => {
+                self.handle_respawn(entity, pk);
+            }
             McpePacketData::PacketDisconnect(_) | McpePacketData::PacketInteract(_) => {
                 // Already handled above
             }
@@ -256,6 +260,32 @@ impl GameServer {
             }
         }
 
+        // Movement is client-authoritative, so falls are measured from the
+        // reported positions. Flying, gliding and swimming never build up a fall.
+        let exempt = world
+            .get::<PlayerState>(entity)
+            .is_some_and(|state| state.flying || state.gliding || state.swimming);
+        let landed_on_ground =
+            pk.input_data.contains(InputFlag::VERTICAL_COLLISION) && new_pos.y <= old_pos.y;
+        let landed = world
+            .get_mut::<FallDistance>(entity)
+            .and_then(|mut fall_distance| {
+                if exempt {
+                    fall_distance.reset();
+                    return None;
+                }
+                fall_distance.update(new_pos.y - old_pos.y, landed_on_ground)
+            });
+        if let Some(distance) = landed {
+            apply_fall_damage(world, entity, distance);
+        }
+
+        let exhaustion = world.get::<PlayerState>(entity).map_or(0.0, |state| {
+            let distance = (new_pos - old_pos).with_y(0.0).length();
+            hunger::movement_exhaustion(state, distance, jumped)
+        });
+        hunger::exhaust(world, entity, exhaustion);
+
         // Handle block actions (breaking blocks)
         self.handle_block_actions(entity, pk);
     }
@@ -392,194 +422,18 @@ impl GameServer {
                 debug!("Sent ContainerClose acknowledgement");
             }
 
+            self.return_crafting_items(entity);
+
             // Mark inventory as closed
             let world = self.ecs.world_mut();
             if let Some(mut opened) = world.get_mut::<InventoryOpened>(entity) {
                 opened.0 = false;
             }
+        } else if pk.window_id == WindowId::First {
+            // Block container (chest, furnace, ...)
+            self.close_container(entity, false);
         } else {
-            debug!(window_id = ?pk.window_id, "ContainerClose for non-inventory window (not handled)");
-        }
-    }
-
-    /// Handle ItemStackRequest - creative inventory picks, item moves, etc.
-    ///
-    /// This handles the core inventory transactions from the client:
-    /// - CraftCreative: Player clicked an item in creative inventory
-    /// - Place: Player placed an item into a slot
-    /// - Take: Player picked up an item
-    /// - Destroy: Player deleted an item (creative mode)
-    pub(super) fn handle_item_stack_request(
-        &mut self,
-        entity: Entity,
-        pk: &jolyne::valentine::ItemStackRequestPacket,
-    ) {
-        use crate::entity::components::{ItemStackRequestState, MainInventory};
-        use crate::item::ItemStack;
-        use jolyne::valentine::ItemStackResponsePacket;
-        use jolyne::valentine::types::FullContainerName;
-        use jolyne::valentine::types::{
-            ItemStackRequestActionsItemContent, ItemStackResponsesItem,
-            ItemStackResponsesItemContent, ItemStackResponsesItemContentContainersItem,
-            ItemStackResponsesItemContentContainersItemSlotsItem, ItemStackResponsesItemStatus,
-        };
-
-        for request in &pk.requests {
-            let request_id = request.request_id;
-            info!(
-                request_id,
-                actions = request.actions.len(),
-                "Processing ItemStackRequest"
-            );
-
-            // Track pending items from CraftCreative actions within this request
-            let mut pending_item: Option<ItemStack> = None;
-            let mut response_containers: Vec<ItemStackResponsesItemContentContainersItem> = vec![];
-
-            for action in &request.actions {
-                match &action.content {
-                    Some(ItemStackRequestActionsItemContent::CraftCreative(craft)) => {
-                        // Look up creative item by 1-indexed network ID
-                        let index = (craft.item_id - 1) as usize;
-
-                        info!(
-                            "CraftCreative: client requested item_id={}, index={}, total_items={}",
-                            craft.item_id,
-                            index,
-                            self.world_template.creative_content.items.len()
-                        );
-
-                        // Get creative content from GameServer
-                        if let Some(entry) = self.world_template.creative_content.items.get(index) {
-                            // Resolve proper item string ID from ItemRegistry
-                            let item_id = if let Some(registry) =
-                                self.ecs
-                                    .world()
-                                    .get_resource::<super::types::ItemRegistryResource>()
-                            {
-                                registry
-                                    .0
-                                    .get(entry.item.network_id as u32)
-                                    .map(|e| e.string_id.clone())
-                                    .unwrap_or_else(|| {
-                                        warn!(
-                                            "Creative item network_id {} not found in registry",
-                                            entry.item.network_id
-                                        );
-                                        format!("minecraft:network_{}", entry.item.network_id)
-                                    })
-                            } else {
-                                format!("minecraft:network_{}", entry.item.network_id)
-                            };
-
-                            // Convert to ItemStack
-                            let item = ItemStack::new(
-                                item_id.clone(),
-                                64, // Max stack in creative
-                            );
-                            info!(
-                                item_id = craft.item_id,
-                                network_id = entry.item.network_id,
-                                string_id = %item_id,
-                                "Creative craft request"
-                            );
-                            pending_item = Some(item);
-                        } else {
-                            debug!(item_id = craft.item_id, "Creative item not found");
-                        }
-                    }
-                    Some(ItemStackRequestActionsItemContent::Place(place)) => {
-                        // Place pending item into destination slot
-                        if let Some(item) = pending_item.take() {
-                            let dest_slot = place.destination.slot as usize;
-                            let dest_container = place.destination.slot_type.container_id;
-
-                            // Get new stack ID
-                            let stack_id = {
-                                let world = self.ecs.world_mut();
-                                if let Some(mut state) =
-                                    world.get_mut::<ItemStackRequestState>(entity)
-                                {
-                                    state.next_id()
-                                } else {
-                                    1
-                                }
-                            };
-
-                            // Update inventory based on container type
-                            if dest_container == ContainerSlotType::HotbarAndInventory {
-                                let world = self.ecs.world_mut();
-                                if let Some(mut inv) = world.get_mut::<MainInventory>(entity) {
-                                    let _ = inv.0.set_item(dest_slot, item.clone());
-                                    info!(
-                                        slot = dest_slot,
-                                        item_id = %item.item_id,
-                                        stack_id,
-                                        "Placed item in inventory"
-                                    );
-                                }
-                            }
-
-                            // Build response slot info
-                            response_containers.push(ItemStackResponsesItemContentContainersItem {
-                                slot_type: FullContainerName {
-                                    container_id: dest_container,
-                                    dynamic_container_id: place
-                                        .destination
-                                        .slot_type
-                                        .dynamic_container_id,
-                                },
-                                slots: vec![ItemStackResponsesItemContentContainersItemSlotsItem {
-                                    slot: dest_slot as u8,
-                                    hotbar_slot: dest_slot as u8,
-                                    count: item.count,
-                                    item_stack_id: stack_id,
-                                    custom_name: String::new(),
-                                    filtered_custom_name: String::new(),
-                                    durability_correction: 0,
-                                }],
-                            });
-                        }
-                    }
-                    Some(ItemStackRequestActionsItemContent::Take(take)) => {
-                        debug!(
-                            source_slot = take.source.slot,
-                            dest_slot = take.destination.slot,
-                            count = take.count,
-                            "Take action (not fully implemented)"
-                        );
-                        info!(
-                            source_slot = take.source.slot,
-                            dest_slot = take.destination.slot,
-                            count = take.count,
-                            "Take action details"
-                        );
-                    }
-                    Some(ItemStackRequestActionsItemContent::Destroy(_destroy)) => {
-                        // In creative mode, just acknowledge - item is deleted
-                        debug!("Destroy action - item deleted");
-                    }
-                    _ => {
-                        trace!(type_id = ?action.type_id, "Unhandled ItemStackRequest action type");
-                    }
-                }
-            }
-
-            // Send response
-            let world = self.ecs.world();
-            if let Some(session) = world.get::<crate::entity::components::PlayerSession>(entity) {
-                let response = ItemStackResponsePacket {
-                    responses: vec![ItemStackResponsesItem {
-                        status: ItemStackResponsesItemStatus::Ok,
-                        request_id,
-                        content: Some(ItemStackResponsesItemContent {
-                            containers: response_containers,
-                        }),
-                    }],
-                };
-                let _ = session.send(McpePacket::from(response));
-                debug!(request_id, "Sent ItemStackResponse");
-            }
+            debug!(window_id = ?pk.window_id, "ContainerClose for unknown window (not handled)");
         }
     }
 
@@ -619,6 +473,7 @@ impl GameServer {
                                 self.handle_block_click(entity, use_item);
                             }
                             TransactionUseItemActionType::ClickAir => {
+                                self.start_using_item(entity);
                                 // Emit PlayerItemUse event
                                 if let Some(mut event_buffer) =
                                     self.ecs
@@ -638,10 +493,33 @@ impl GameServer {
                 }
             }
             TransactionTransactionType::ItemUseOnEntity => {
-                debug!("ItemUseOnEntity transaction");
+                use jolyne::valentine::types::{
+                    TransactionTransactionData,
+                    TransactionTransactionDataItemUseOnEntityActionType as EntityActionType,
+                };
+                if let Some(TransactionTransactionData::ItemUseOnEntity(use_on_entity)) =
+                    &transaction.transaction_data
+                {
+                    match use_on_entity.action_type {
+                        EntityActionType::Attack => {
+                            self.handle_entity_attack(entity, use_on_entity.entity_runtime_id);
+                        }
+                        EntityActionType::Interact => {
+                            debug!(
+                                target = use_on_entity.entity_runtime_id,
+                                "Unhandled entity interaction"
+                            );
+                        }
+                    }
+                }
             }
             TransactionTransactionType::ItemRelease => {
-                debug!("ItemRelease transaction");
+                use jolyne::valentine::types::TransactionTransactionData;
+                if let Some(TransactionTransactionData::ItemRelease(release)) =
+                    &transaction.transaction_data
+                {
+                    self.handle_item_release(entity, release);
+                }
             }
         }
     }
@@ -1018,7 +896,7 @@ impl GameServer {
             }
             Action::Respawn => {
                 debug!("Player requested respawn");
-                // TODO: Implement respawn logic - reset position, health, etc.
+                self.respawn_player(entity);
             }
             Action::DimensionChangeAck => {
                 debug!("Player acknowledged dimension change");
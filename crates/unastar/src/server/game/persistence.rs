@@ -0,0 +1,268 @@
+//! Player data persistence.
+//!
+//! Players are snapshotted into `PlayerData` and written through the
+//! `PlayerProvider` when they disconnect and when the server shuts down.
+//! Saved data is loaded during the join sequence and applied to the player
+//! entity when it spawns.
+
+use bevy_ecs::entity::Entity;
+use glam::IVec3;
+use tracing::{debug, warn};
+
+use super::GameServer;
+use crate::config::{PlayerLastPosition, SpawnLocation};
+use crate::entity::components::{
+    ActiveEffect, AirSupply, ArmourInventory, Dead, EffectType, Effects, Experience, GameMode,
+    Health, HeldSlot, Hunger, MAX_FOOD_LEVEL, MainInventory, OffhandSlot, Player, PlayerUuid,
+    Position, Rotation, SpawnPoint,
+};
+use crate::storage::{PlayerData, PlayerSpawnPoint, SavedEffect};
+use crate::world::{ChunkManager, DEFAULT_WORLD, WorldId, Worlds};
+
+impl GameServer {
+    /// Snapshot a player's persistent state.
+    pub fn player_data(&self, entity: Entity) -> Option<PlayerData> {
+        let world = self.ecs.world();
+        let uuid = world.get::<PlayerUuid>(entity)?.0;
+        let position = world.get::<Position>(entity)?.0;
+        let rotation = world.get::<Rotation>(entity).copied().unwrap_or_default();
+        let health = world.get::<Health>(entity).cloned().unwrap_or_default();
+        let hunger = world.get::<Hunger>(entity).cloned().unwrap_or_default();
+        let experience = world.get::<Experience>(entity).cloned().unwrap_or_default();
+        let air = world.get::<AirSupply>(entity).cloned().unwrap_or_default();
+        let spawn_point = world.get::<SpawnPoint>(entity).copied().unwrap_or_default();
+        let manager = world.resource::<Worlds>().get(WorldId::of(world, entity));
+
+        let effects = world
+            .get::<Effects>(entity)
+            .map(|effects| {
+                effects
+                    .active
+                    .iter()
+                    .map(|(effect_type, effect)| SavedEffect {
+                        id: effect_type.0,
+                        amplifier: effect.level,
+                        duration_ticks: effect.duration_ticks,
+                        ambient: effect.ambient,
+                        show_particles: effect.show_particles,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let defaults = PlayerData::default();
+        let mut data = PlayerData {
+            uuid: uuid.to_string(),
+            position: position.to_array(),
+            rotation: [rotation.yaw, rotation.pitch],
+            dimension: manager.map_or(self.config.world.dimension, ChunkManager::dimension),
+            world: manager
+                .map_or(DEFAULT_WORLD, ChunkManager::name)
+                .to_string(),
+            game_mode: world
+                .get::<GameMode>(entity)
+                .map_or(defaults.game_mode, |mode| mode.id()),
+            health: health.current,
+            food: hunger.food_level,
+            saturation: hunger.saturation,
+            exhaustion: hunger.exhaustion,
+            experience: experience.level,
+            experience_progress: experience.progress,
+            air: air.current_ticks.min(i16::MAX as u32) as i16,
+            spawn_point: spawn_point.position.map(|pos| PlayerSpawnPoint {
+                position: pos.to_array(),
+                dimension: spawn_point.dimension,
+            }),
+            inventory: world
+                .get::<MainInventory>(entity)
+                .map_or(defaults.inventory, |inv| inv.0.slots().to_vec()),
+            armour: world
+                .get::<ArmourInventory>(entity)
+                .map_or(defaults.armour, |armour| armour.slots().to_vec()),
+            offhand: world
+                .get::<OffhandSlot>(entity)
+                .map_or(defaults.offhand, |offhand| offhand.0.clone()),
+            selected_slot: world.get::<HeldSlot>(entity).map_or(0, |held| held.0),
+            effects,
+            ..defaults
+        };
+
+        // A player that leaves from the death screen comes back respawned
+        if world.get::<Dead>(entity).is_some() {
+            let spawn = self.respawn_location(entity);
+            data.position = [spawn.x as f64, spawn.y as f64, spawn.z as f64];
+            data.rotation = [spawn.yaw, spawn.pitch];
+            data.health = health.max;
+            let hunger = Hunger::default();
+            data.food = hunger.food_level;
+            data.saturation = hunger.saturation;
+            data.exhaustion = hunger.exhaustion;
+            data.air = AirSupply::default().max_ticks as i16;
+            data.effects.clear();
+        }
+        Some(data)
+    }
+
+    /// Restore saved state onto a freshly spawned player.
+    ///
+    /// Position and rotation are not applied here; the join sequence has
+    /// already resolved them through the spawn rules.
+    pub(super) fn apply_player_data(&mut self, entity: Entity, data: &PlayerData) {
+        let world = self.ecs.world_mut();
+        let Ok(mut player) = world.get_entity_mut(entity) else {
+            return;
+        };
+
+        if let Some(mode) = GameMode::from_id(data.game_mode) {
+            player.insert(mode);
+        }
+        if let Some(mut health) = player.get_mut::<Health>() {
+            health.current = data.health.clamp(0.0, health.max);
+        }
+        if let Some(mut hunger) = player.get_mut::<Hunger>() {
+            hunger.food_level = data.food.clamp(0, MAX_FOOD_LEVEL);
+            hunger.saturation = data.saturation.clamp(0.0, hunger.food_level as f32);
+            hunger.exhaustion = data.exhaustion.max(0.0);
+        }
+        if let Some(mut experience) = player.get_mut::<Experience>() {
+            experience.level = data.experience.max(0);
+            experience.progress = data.experience_progress.clamp(0.0, 1.0);
+        }
+        if let Some(mut air) = player.get_mut::<AirSupply>() {
+            air.current_ticks = (data.air.max(0) as u32).min(air.max_ticks);
+        }
+        if let Some(mut spawn_point) = player.get_mut::<SpawnPoint>() {
+            *spawn_point = data
+                .spawn_point
+                .map(|spawn| SpawnPoint {
+                    position: Some(IVec3::from_array(spawn.position)),
+                    dimension: spawn.dimension,
+                })
+                .unwrap_or_default();
+        }
+        if let Some(mut effects) = player.get_mut::<Effects>() {
+            effects.active = data
+                .effects
+                .iter()
+                .filter(|effect| effect.duration_ticks > 0)
+                .map(|effect| {
+                    let active = ActiveEffect {
+                        level: effect.amplifier,
+                        duration_ticks: effect.duration_ticks,
+                        ambient: effect.ambient,
+                        show_particles: effect.show_particles,
+                    };
+                    (EffectType(effect.id), active)
+                })
+                .collect();
+        }
+
+        if let Some(mut inventory) = player.get_mut::<MainInventory>() {
+            for (slot, item) in data.inventory.iter().enumerate() {
+                let _ = inventory.0.set_item(slot, item.clone());
+            }
+        }
+        if let Some(mut armour) = player.get_mut::<ArmourInventory>() {
+            for (slot, item) in data.armour.iter().enumerate() {
+                let result = match slot {
+                    0 => armour.set_helmet(item.clone()),
+                    1 => armour.set_chestplate(item.clone()),
+                    2 => armour.set_leggings(item.clone()),
+                    3 => armour.set_boots(item.clone()),
+                    _ => continue,
+                };
+                if result.is_err() {
+                    warn!(entity = ?entity, slot, item = %item.item_id, "Dropped invalid saved armour");
+                }
+            }
+        }
+        if let Some(mut offhand) = player.get_mut::<OffhandSlot>() {
+            offhand.0 = data.offhand.clone();
+        }
+        if let Some(mut held) = player.get_mut::<HeldSlot>() {
+            held.set(data.selected_slot);
+        }
+
+        debug!(entity = ?entity, version = data.version, "Applied saved player data");
+    }
+
+    /// Save a player in the background.
+    ///
+    /// Writes through the `PlayerProvider` when one is configured, otherwise
+    /// falls back to the legacy last-position file if enabled.
+    pub(super) fn save_player(&self, entity: Entity) {
+        let Some(data) = self.player_data(entity) else {
+            return;
+        };
+
+        if let Some(provider) = self.player_provider.clone() {
+            if !self.save_on_disconnect {
+                return;
+            }
+            let Ok(uuid) = uuid::Uuid::parse_str(&data.uuid) else {
+                return;
+            };
+            tokio::spawn(async move {
+                match provider.save(uuid, &data).await {
+                    Ok(()) => debug!(%uuid, "Saved player data"),
+                    Err(e) => warn!(%uuid, error = %e, "Failed to save player data"),
+                }
+            });
+        } else if self.save_previous_position
+            && let Some(store) = self.player_data_store.clone()
+        {
+            let last = last_position(&data);
+            tokio::spawn(async move {
+                if let Err(e) = store.save_last_position(&data.uuid, &last).await {
+                    warn!(uuid = %data.uuid, error = %e, "Failed to save player position");
+                }
+            });
+        }
+    }
+
+    /// Save every connected player and wait for the writes to finish.
+    ///
+    /// Returns the number of players saved.
+    pub async fn save_all_players(&mut self) -> usize {
+        let Some(provider) = self.player_provider.clone() else {
+            return 0;
+        };
+
+        let world = self.ecs.world_mut();
+        let players: Vec<Entity> = world
+            .query_filtered::<Entity, bevy_ecs::query::With<Player>>()
+            .iter(world)
+            .collect();
+
+        let mut saved = 0;
+        for entity in players {
+            let Some(data) = self.player_data(entity) else {
+                continue;
+            };
+            let Ok(uuid) = uuid::Uuid::parse_str(&data.uuid) else {
+                continue;
+            };
+            match provider.save(uuid, &data).await {
+                Ok(()) => saved += 1,
+                Err(e) => warn!(%uuid, error = %e, "Failed to save player data"),
+            }
+        }
+        saved
+    }
+}
+
+/// Legacy last-position record for a player.
+fn last_position(data: &PlayerData) -> PlayerLastPosition {
+    let [x, y, z] = data.position;
+    let [yaw, pitch] = data.rotation;
+    PlayerLastPosition {
+        dimension: data.dimension,
+        location: SpawnLocation {
+            x: x as f32,
+            y: y as f32,
+            z: z as f32,
+            yaw,
+            pitch,
+        },
+    }
+}
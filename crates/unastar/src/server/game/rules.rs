@@ -0,0 +1,185 @@
+//! Game rules and difficulty.
+//!
+//! Both are ECS resources saved in `level.dat`. Joining players get the
+//! values in `StartGame` and again once they spawn, in case they changed
+//! since the server started; later changes are broadcast as they happen.
+
+use bevy_ecs::prelude::*;
+use jolyne::valentine::types::{
+    GameRuleI32, GameRuleI32Type, GameRuleI32Value, GameRuleVarint, GameRuleVarintType,
+    GameRuleVarintValue,
+};
+use jolyne::valentine::{GameRulesChangedPacket, McpePacket, SetDifficultyPacket};
+use std::sync::Arc;
+
+use super::GameServer;
+use super::types::ServerWorldTemplate;
+use crate::entity::components::PlayerSession;
+use crate::world::{Difficulty, GameRuleValue, GameRules};
+
+fn rule(name: &str, value: GameRuleValue) -> GameRuleI32 {
+    let (type_, value) = match value {
+        GameRuleValue::Bool(on) => (GameRuleI32Type::Bool, GameRuleI32Value::Bool(on)),
+        GameRuleValue::Int(value) => (GameRuleI32Type::Int, GameRuleI32Value::Int(value)),
+    };
+    GameRuleI32 {
+        name: name.to_ascii_lowercase(),
+        editable: true,
+        type_,
+        value: Some(value),
+    }
+}
+
+fn rules_changed_packet<'a>(
+    rules: impl IntoIterator<Item = (&'a str, GameRuleValue)>,
+) -> McpePacket {
+    McpePacket::from(GameRulesChangedPacket {
+        rules: rules
+            .into_iter()
+            .map(|(name, value)| rule(name, value))
+            .collect(),
+    })
+}
+
+fn difficulty_packet(difficulty: Difficulty) -> McpePacket {
+    McpePacket::from(SetDifficultyPacket {
+        difficulty: difficulty.id() as i32,
+    })
+}
+
+/// Game rules in the form `StartGame` carries them.
+pub(super) fn start_game_rules(rules: &GameRules) -> Vec<GameRuleVarint> {
+    rules
+        .iter()
+        .map(|(name, value)| {
+            let (type_, value) = match value {
+                GameRuleValue::Bool(on) => {
+                    (GameRuleVarintType::Bool, GameRuleVarintValue::Bool(on))
+                }
+                GameRuleValue::Int(value) => {
+                    (GameRuleVarintType::Int, GameRuleVarintValue::Int(value))
+                }
+            };
+            GameRuleVarint {
+                name: name.to_ascii_lowercase(),
+                editable: true,
+                type_,
+                value: Some(value),
+            }
+        })
+        .collect()
+}
+
+/// Packets that bring a joining player up to date.
+pub(super) fn rules_packets(world: &World) -> Vec<McpePacket> {
+    let rules = world
+        .get_resource::<GameRules>()
+        .copied()
+        .unwrap_or_default();
+    let difficulty = world
+        .get_resource::<Difficulty>()
+        .copied()
+        .unwrap_or_default();
+    vec![
+        rules_changed_packet(rules.iter()),
+        difficulty_packet(difficulty),
+    ]
+}
+
+fn broadcast(world: &mut World, packet: McpePacket) {
+    for session in world.query::<&PlayerSession>().iter(world) {
+        let _ = session.send(packet.clone());
+    }
+}
+
+/// Set a game rule and tell every player.
+///
+/// Returns `false` if there is no rule called `name` that takes this kind of
+/// value.
+pub fn set_game_rule(world: &mut World, name: &str, value: GameRuleValue) -> bool {
+    let mut rules = world
+        .get_resource::<GameRules>()
+        .copied()
+        .unwrap_or_default();
+    let Some(name) = GameRules::canonical_name(name) else {
+        return false;
+    };
+    if !rules.set(name, value) {
+        return false;
+    }
+    world.insert_resource(rules);
+    broadcast(world, rules_changed_packet([(name, value)]));
+    true
+}
+
+/// Set the difficulty and tell every player.
+pub fn set_difficulty(world: &mut World, difficulty: Difficulty) {
+    world.insert_resource(difficulty);
+    broadcast(world, difficulty_packet(difficulty));
+}
+
+impl GameServer {
+    /// The current game rules.
+    pub(super) fn game_rules(&self) -> GameRules {
+        self.ecs
+            .world()
+            .get_resource::<GameRules>()
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Put the loaded game rules and difficulty into `StartGame`.
+    ///
+    /// Only affects connections accepted afterwards, so call this before
+    /// the server starts listening.
+    pub(super) fn update_start_game_rules(&mut self) {
+        let world = self.ecs.world();
+        let rules = world
+            .get_resource::<GameRules>()
+            .copied()
+            .unwrap_or_default();
+        let difficulty = world
+            .get_resource::<Difficulty>()
+            .copied()
+            .unwrap_or_default();
+
+        let template = Arc::make_mut(&mut self.world_template);
+        template.start_game_template.gamerules = start_game_rules(&rules);
+        template.start_game_template.difficulty = difficulty.id() as i32;
+        let template = self.world_template.clone();
+        self.ecs
+            .world_mut()
+            .insert_resource(ServerWorldTemplate(template));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_game_rules() {
+        let rules = GameRules {
+            keep_inventory: true,
+            random_tick_speed: 3,
+            ..GameRules::default()
+        };
+        let sent = start_game_rules(&rules);
+        assert_eq!(sent.len(), rules.iter().count());
+        let value = |name: &str| {
+            let rule = sent.iter().find(|rule| rule.name == name).unwrap();
+            (rule.type_, rule.value.clone())
+        };
+        assert_eq!(
+            value("keepinventory"),
+            (
+                GameRuleVarintType::Bool,
+                Some(GameRuleVarintValue::Bool(true))
+            )
+        );
+        assert_eq!(
+            value("randomtickspeed"),
+            (GameRuleVarintType::Int, Some(GameRuleVarintValue::Int(3)))
+        );
+    }
+}
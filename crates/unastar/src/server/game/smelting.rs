@@ -0,0 +1,300 @@
+//! Furnace smelting.
+//!
+//! Every tick each loaded furnace block entity burns fuel and cooks its input
+//! using the furnace recipes of the `RecipeRegistry`:
+//! - Fuel is only lit while the input has a recipe whose result fits in the
+//!   output slot
+//! - An item takes `COOK_TICKS` of burning to smelt
+//! - The block swaps between `furnace` and `lit_furnace` as it starts and
+//!   stops burning
+//!
+//! Players viewing the furnace receive slot changes and the progress bars
+//! (`ContainerSetData`).
+
+use glam::IVec3;
+use jolyne::valentine::blocks::BLOCKS;
+use jolyne::valentine::types::WindowId;
+use jolyne::valentine::{ContainerSetDataPacket, McpePacket};
+use std::sync::LazyLock;
+
+use super::GameServer;
+use super::containers::container_viewers;
+use crate::entity::components::PlayerSession;
+use crate::item::ItemStack;
+use crate::registry::RecipeRegistry;
+use crate::world::block_entity::Furnace;
+use crate::world::ecs::{
+    BlockEntityWorldExt, ChunkData, ChunkStateFlags, WorldId, world_to_local_coords,
+};
+use crate::world::{BlockEntity, BlockEntityData};
+
+/// Ticks of burning needed to smelt one item.
+pub const COOK_TICKS: i16 = 200;
+
+/// Recipe block of furnace recipes.
+const FURNACE: &str = "furnace";
+
+const INPUT_SLOT: usize = 0;
+const FUEL_SLOT: usize = 1;
+const OUTPUT_SLOT: usize = 2;
+
+/// `ContainerSetData` properties of the furnace window.
+const PROPERTY_COOK_TIME: i32 = 0;
+const PROPERTY_BURN_TIME: i32 = 1;
+const PROPERTY_BURN_DURATION: i32 = 2;
+
+/// State ID ranges of the unlit and lit furnace, which share one state layout.
+static FURNACE_STATES: LazyLock<Option<(u32, u32, u32)>> = LazyLock::new(|| {
+    let find = |name: &str| BLOCKS.iter().find(|block| block.string_id() == name);
+    let unlit = find("minecraft:furnace")?;
+    let lit = find("minecraft:lit_furnace")?;
+    Some((
+        unlit.min_state_id(),
+        lit.min_state_id(),
+        unlit.max_state_id() - unlit.min_state_id(),
+    ))
+});
+
+/// The same furnace state, lit or unlit.
+fn furnace_block(runtime_id: u32, lit: bool) -> Option<u32> {
+    let (unlit_min, lit_min, span) = (*FURNACE_STATES)?;
+    let offset = [unlit_min, lit_min]
+        .into_iter()
+        .find_map(|min| runtime_id.checked_sub(min).filter(|&offset| offset <= span))?;
+    Some(if lit { lit_min } else { unlit_min } + offset)
+}
+
+/// Changes made by one furnace tick.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SmeltTick {
+    /// Slots whose contents changed.
+    pub slots: Vec<usize>,
+    /// Whether any progress value changed.
+    pub progress: bool,
+}
+
+/// Advance a furnace by one tick.
+pub fn smelt_tick(furnace: &mut Furnace, recipes: &RecipeRegistry) -> SmeltTick {
+    let mut tick = SmeltTick::default();
+    let before = (furnace.burn_time, furnace.cook_time, furnace.burn_duration);
+
+    if furnace.burn_time > 0 {
+        furnace.burn_time -= 1;
+    }
+
+    let result = recipes
+        .furnace_output(FURNACE, &furnace.items[INPUT_SLOT])
+        .map(|output| output.to_stack(1))
+        .filter(|result| fits(&furnace.items[OUTPUT_SLOT], result));
+
+    if furnace.burn_time == 0
+        && result.is_some()
+        && let Some(duration) = recipes.fuel_duration(&furnace.items[FUEL_SLOT])
+    {
+        furnace.burn_time = duration;
+        furnace.burn_duration = duration;
+        let fuel = &furnace.items[FUEL_SLOT];
+        furnace.items[FUEL_SLOT] = if fuel.item_id == "minecraft:lava_bucket" {
+            ItemStack::new("minecraft:bucket", 1)
+        } else {
+            fuel.grow(-1)
+        };
+        tick.slots.push(FUEL_SLOT);
+    }
+
+    match result {
+        Some(result) if furnace.burn_time > 0 => {
+            furnace.cook_time += 1;
+            if furnace.cook_time >= COOK_TICKS {
+                furnace.cook_time = 0;
+                furnace.items[INPUT_SLOT] = furnace.items[INPUT_SLOT].grow(-1);
+                let output = &furnace.items[OUTPUT_SLOT];
+                furnace.items[OUTPUT_SLOT] = if output.is_empty() {
+                    result
+                } else {
+                    output.add(&result).0
+                };
+                tick.slots.extend([INPUT_SLOT, OUTPUT_SLOT]);
+            }
+        }
+        _ => furnace.cook_time = 0,
+    }
+
+    tick.progress = before != (furnace.burn_time, furnace.cook_time, furnace.burn_duration);
+    tick
+}
+
+/// Whether a smelting result fits in the output slot.
+fn fits(output: &ItemStack, result: &ItemStack) -> bool {
+    output.is_empty()
+        || output
+            .merge(result)
+            .is_some_and(|(_, leftover)| leftover.is_empty())
+}
+
+impl GameServer {
+    /// Smelt in every loaded furnace and update viewers and lit states.
+    pub(super) fn tick_furnaces(&mut self) {
+        let recipes = self.recipes.clone();
+        let world = self.ecs.world_mut();
+        let mut changed: Vec<(WorldId, IVec3, SmeltTick, Furnace)> = Vec::new();
+        let mut furnaces = world.query::<(&mut BlockEntity, Option<&WorldId>)>();
+        for (mut block_entity, world_id) in furnaces.iter_mut(world) {
+            let position = block_entity.position;
+            let BlockEntityData::Furnace(furnace) = &mut block_entity.data else {
+                continue;
+            };
+            let tick = smelt_tick(furnace, &recipes);
+            if tick.slots.is_empty() && !tick.progress {
+                continue;
+            }
+            let world_id = world_id.copied().unwrap_or_default();
+            changed.push((world_id, position, tick, furnace.clone()));
+        }
+
+        for (world_id, pos, tick, furnace) in changed {
+            // Slot changes also mark the chunk dirty
+            self.sync_container_slots(world_id, pos, &tick.slots, None);
+            if tick.slots.is_empty()
+                && let Some(chunk_entity) = self.ecs.world().chunk_entity_at(world_id, pos)
+                && let Some(mut flags) = self
+                    .ecs
+                    .world_mut()
+                    .get_mut::<ChunkStateFlags>(chunk_entity)
+            {
+                flags.mark_dirty();
+            }
+            self.send_furnace_progress(world_id, pos, &furnace);
+            self.update_furnace_lit(world_id, pos, furnace.burn_time > 0);
+        }
+    }
+
+    /// Send the progress bars of a furnace to its viewers.
+    fn send_furnace_progress(&mut self, world_id: WorldId, pos: IVec3, furnace: &Furnace) {
+        let world = self.ecs.world_mut();
+        let properties = [
+            (PROPERTY_COOK_TIME, furnace.cook_time),
+            (PROPERTY_BURN_TIME, furnace.burn_time),
+            (PROPERTY_BURN_DURATION, furnace.burn_duration),
+        ];
+        for viewer in container_viewers(world, world_id, pos) {
+            let Some(session) = world.get::<PlayerSession>(viewer) else {
+                continue;
+            };
+            for (property, value) in properties {
+                let _ = session.send(McpePacket::from(ContainerSetDataPacket {
+                    window_id: WindowId::First,
+                    property,
+                    value: value as i32,
+                }));
+            }
+        }
+    }
+
+    /// Swap a furnace block between its lit and unlit state.
+    fn update_furnace_lit(&mut self, world_id: WorldId, pos: IVec3, lit: bool) {
+        let world = self.ecs.world();
+        let Some(chunk_entity) = world.chunk_entity_at(world_id, pos) else {
+            return;
+        };
+        let Some(chunk) = world.get::<ChunkData>(chunk_entity) else {
+            return;
+        };
+        let (x, y, z) = world_to_local_coords(pos.x, pos.y, pos.z);
+        let block = chunk.inner.get_block(x, y, z, 0);
+        if let Some(swapped) = furnace_block(block, lit)
+            && swapped != block
+        {
+            self.set_block(world_id, pos, swapped);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vanilla() -> RecipeRegistry {
+        let mut registry = RecipeRegistry::new();
+        registry.load_vanilla().unwrap();
+        registry
+    }
+
+    fn loaded(input: ItemStack, fuel: ItemStack) -> Furnace {
+        let mut furnace = Furnace::default();
+        furnace.items[INPUT_SLOT] = input;
+        furnace.items[FUEL_SLOT] = fuel;
+        furnace
+    }
+
+    #[test]
+    fn test_smelts_with_fuel() {
+        let recipes = vanilla();
+        let mut furnace = loaded(
+            ItemStack::new("minecraft:raw_iron", 2),
+            ItemStack::new("minecraft:coal", 1),
+        );
+
+        let tick = smelt_tick(&mut furnace, &recipes);
+        assert_eq!(tick.slots, vec![FUEL_SLOT]);
+        assert!(furnace.items[FUEL_SLOT].is_empty());
+        assert_eq!(furnace.burn_duration, 1600);
+
+        for _ in 1..COOK_TICKS {
+            smelt_tick(&mut furnace, &recipes);
+        }
+        assert_eq!(furnace.items[INPUT_SLOT].count, 1);
+        assert_eq!(furnace.items[OUTPUT_SLOT].item_id, "minecraft:iron_ingot");
+        assert_eq!(furnace.items[OUTPUT_SLOT].count, 1);
+        assert_eq!(furnace.cook_time, 0);
+    }
+
+    #[test]
+    fn test_needs_recipe_and_fuel() {
+        let recipes = vanilla();
+
+        // No recipe: the fuel is kept
+        let mut furnace = loaded(
+            ItemStack::new("minecraft:dirt", 1),
+            ItemStack::new("minecraft:coal", 1),
+        );
+        assert_eq!(smelt_tick(&mut furnace, &recipes), SmeltTick::default());
+        assert_eq!(furnace.items[FUEL_SLOT].count, 1);
+
+        // No fuel: nothing cooks
+        let mut furnace = loaded(
+            ItemStack::new("minecraft:raw_iron", 1),
+            ItemStack::new("minecraft:dirt", 1),
+        );
+        assert_eq!(smelt_tick(&mut furnace, &recipes), SmeltTick::default());
+
+        // Full output: the fuel is kept
+        let mut furnace = loaded(
+            ItemStack::new("minecraft:raw_iron", 1),
+            ItemStack::new("minecraft:coal", 1),
+        );
+        furnace.items[OUTPUT_SLOT] = ItemStack::new("minecraft:iron_ingot", 64);
+        smelt_tick(&mut furnace, &recipes);
+        assert_eq!(furnace.items[FUEL_SLOT].count, 1);
+    }
+
+    #[test]
+    fn test_lava_bucket_leaves_bucket() {
+        let recipes = vanilla();
+        let mut furnace = loaded(
+            ItemStack::new("minecraft:raw_iron", 1),
+            ItemStack::new("minecraft:lava_bucket", 1),
+        );
+        smelt_tick(&mut furnace, &recipes);
+        assert_eq!(furnace.items[FUEL_SLOT].item_id, "minecraft:bucket");
+        assert_eq!(furnace.burn_time, 20000);
+    }
+
+    #[test]
+    fn test_furnace_block_lit_state() {
+        let (unlit, lit, _) = FURNACE_STATES.unwrap();
+        assert_eq!(furnace_block(unlit + 2, true), Some(lit + 2));
+        assert_eq!(furnace_block(lit + 2, false), Some(unlit + 2));
+        assert_eq!(furnace_block(0, true), None);
+    }
+}
@@ -0,0 +1,227 @@
+//! Natural mob spawning.
+//!
+//! Every [`SPAWN_INTERVAL`] ticks, each player in an Overworld world gets one
+//! try at spawning a mob on the surface 24 to 48 blocks away: an animal on
+//! grass by day, or a zombie by night (a husk on sand). Hostile and passive
+//! mobs each have a cap per player in the world, hostile mobs don't spawn on
+//! Peaceful, and the `doMobSpawning` game rule turns spawning off. Hostile
+//! mobs with no player within [`DESPAWN_DISTANCE`] are removed so that the
+//! cap frees up as players move on.
+
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+
+use bevy_ecs::prelude::*;
+use glam::{DVec3, IVec3};
+use rand::Rng;
+use tracing::debug;
+
+use super::GameServer;
+use super::combat::loaded_block_at;
+use super::mobs::spawn_mob;
+use crate::entity::components::{Hostile, Mob, Player, Position};
+use crate::entity::mobs::MobDefinition;
+use crate::entity::pathfinding::{PathOptions, is_walkable};
+use crate::world::chunk::blocks;
+use crate::world::ecs::{ChunkData, WorldId, Worlds, world_to_chunk_coords, world_to_local_coords};
+use crate::world::{Difficulty, WorldTime};
+
+/// Ticks between spawn attempts.
+const SPAWN_INTERVAL: u64 = 20;
+/// Closest a mob spawns to a player, in blocks.
+const MIN_SPAWN_DISTANCE: f64 = 24.0;
+/// Furthest a mob spawns from a player, in blocks.
+const MAX_SPAWN_DISTANCE: f64 = 48.0;
+/// Hostile mobs allowed per player in the world.
+const HOSTILE_CAP: usize = 16;
+/// Animals allowed per player in the world.
+const PASSIVE_CAP: usize = 6;
+/// Hostile mobs further than this from every player are removed.
+const DESPAWN_DISTANCE: f64 = 128.0;
+
+/// Animals that spawn on grass.
+const ANIMALS: &[&str] = &[
+    "minecraft:cow",
+    "minecraft:pig",
+    "minecraft:sheep",
+    "minecraft:chicken",
+];
+
+/// Whether it is dark enough for hostile mobs, going by the time of day.
+fn is_night(time: WorldTime) -> bool {
+    (13000..23000).contains(&time.day_time())
+}
+
+/// The mob to spawn standing on `ground`, if any.
+fn spawn_choice(
+    ground: u32,
+    night: bool,
+    difficulty: Difficulty,
+    rng: &mut impl Rng,
+) -> Option<&'static str> {
+    if night {
+        if difficulty == Difficulty::Peaceful {
+            None
+        } else if ground == *blocks::SAND {
+            Some("minecraft:husk")
+        } else {
+            Some("minecraft:zombie")
+        }
+    } else if ground == *blocks::GRASS_BLOCK {
+        Some(ANIMALS[rng.gen_range(0..ANIMALS.len())])
+    } else {
+        None
+    }
+}
+
+impl GameServer {
+    /// Spawn mobs around players and remove hostile mobs left far behind.
+    pub(super) fn tick_natural_spawning(&mut self) {
+        if !self.current_tick.is_multiple_of(SPAWN_INTERVAL) || !self.game_rules().do_mob_spawning {
+            return;
+        }
+        let world = self.ecs.world_mut();
+        let difficulty = world
+            .get_resource::<Difficulty>()
+            .copied()
+            .unwrap_or_default();
+
+        let mut players: HashMap<WorldId, Vec<DVec3>> = HashMap::new();
+        for (position, world_id) in world
+            .query_filtered::<(&Position, Option<&WorldId>), With<Player>>()
+            .iter(world)
+        {
+            let world_id = world_id.copied().unwrap_or_default();
+            players.entry(world_id).or_default().push(position.0);
+        }
+
+        // Mobs in each world as (hostile, passive), and the ones to remove
+        let mut counts: HashMap<WorldId, (usize, usize)> = HashMap::new();
+        let mut far_away = Vec::new();
+        for (entity, position, hostile, world_id) in world
+            .query_filtered::<(Entity, &Position, &Hostile, &WorldId), With<Mob>>()
+            .iter(world)
+        {
+            let nearby = players.get(world_id).is_some_and(|players| {
+                players
+                    .iter()
+                    .any(|player| player.distance(position.0) < DESPAWN_DISTANCE)
+            });
+            if hostile.0 && !nearby {
+                far_away.push(entity);
+                continue;
+            }
+            let count = counts.entry(*world_id).or_default();
+            if hostile.0 {
+                count.0 += 1;
+            } else {
+                count.1 += 1;
+            }
+        }
+        for entity in far_away {
+            world.despawn(entity);
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut spawns = Vec::new();
+        let worlds = world.resource::<Worlds>();
+        for (&world_id, players) in &players {
+            let Some(manager) = worlds.get(world_id) else {
+                continue;
+            };
+            if manager.dimension() != 0 {
+                continue;
+            }
+            let night = is_night(manager.time());
+            let (hostile, passive) = counts.entry(world_id).or_default();
+            for player in players {
+                let angle = rng.gen_range(0.0..TAU);
+                let distance = rng.gen_range(MIN_SPAWN_DISTANCE..MAX_SPAWN_DISTANCE);
+                let x = (player.x + angle.cos() * distance).floor() as i32;
+                let z = (player.z + angle.sin() * distance).floor() as i32;
+
+                let (cx, cz) = world_to_chunk_coords(x, z);
+                let Some(chunk) = manager
+                    .get_by_coords(cx, cz)
+                    .and_then(|chunk| world.get::<ChunkData>(chunk))
+                else {
+                    continue;
+                };
+                let (lx, _, lz) = world_to_local_coords(x, 0, z);
+                let y = chunk.inner.height_map().at(lx, lz);
+                let ground = chunk.inner.get_block(lx, y - 1, lz, 0);
+                let Some(identifier) = spawn_choice(ground, night, difficulty, &mut rng) else {
+                    continue;
+                };
+                let Some(definition) = MobDefinition::get(identifier) else {
+                    continue;
+                };
+                let (count, cap) = if definition.is_hostile() {
+                    (&mut *hostile, HOSTILE_CAP)
+                } else {
+                    (&mut *passive, PASSIVE_CAP)
+                };
+                if *count >= cap * players.len() {
+                    continue;
+                }
+
+                let feet = IVec3::new(x, y as i32, z);
+                let position = DVec3::new(x as f64 + 0.5, feet.y as f64, z as f64 + 0.5);
+                let blocks = |x, y, z| loaded_block_at(world, world_id, x, y, z);
+                let options = PathOptions::for_height(definition.hitbox.height);
+                let too_close = players
+                    .iter()
+                    .any(|player| player.distance(position) < MIN_SPAWN_DISTANCE);
+                if too_close || !is_walkable(&blocks, feet, &options) {
+                    continue;
+                }
+                *count += 1;
+                spawns.push((world_id, identifier, position));
+            }
+        }
+
+        for (world_id, identifier, position) in spawns {
+            debug!(identifier, ?position, "Natural spawn");
+            spawn_mob(world, world_id, identifier, position, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn_choice() {
+        let mut rng = rand::thread_rng();
+        let animal = spawn_choice(*blocks::GRASS_BLOCK, false, Difficulty::Normal, &mut rng);
+        assert!(animal.is_some_and(|mob| ANIMALS.contains(&mob)));
+        assert_eq!(
+            spawn_choice(*blocks::STONE, false, Difficulty::Normal, &mut rng),
+            None
+        );
+
+        assert_eq!(
+            spawn_choice(*blocks::STONE, true, Difficulty::Easy, &mut rng),
+            Some("minecraft:zombie")
+        );
+        assert_eq!(
+            spawn_choice(*blocks::SAND, true, Difficulty::Hard, &mut rng),
+            Some("minecraft:husk")
+        );
+        assert_eq!(
+            spawn_choice(*blocks::GRASS_BLOCK, true, Difficulty::Peaceful, &mut rng),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_night() {
+        assert!(!is_night(WorldTime { time: 6000 }));
+        assert!(is_night(WorldTime { time: 18000 }));
+        assert!(is_night(WorldTime {
+            time: 24000 * 3 + 14000
+        }));
+        assert!(!is_night(WorldTime { time: 23500 }));
+    }
+}
@@ -0,0 +1,635 @@
+//! ItemStackRequest handling.
+//!
+//! The client describes inventory edits as a list of actions over slots in
+//! named containers (hotbar, cursor, an open chest, ...). Each request is
+//! applied to a staged copy of the slots it touches and only committed when
+//! every action succeeds, so a rejected request leaves server state untouched
+//! and the client rolls back its prediction.
+//!
+//! Crafting is validated against the `RecipeRegistry`: `CraftRecipe` must
+//! match the items in the crafting grid (or stonecutter input), and the
+//! request's `Consume` actions must pay exactly for the crafted output.
+
+use bevy_ecs::entity::Entity;
+use bevy_ecs::world::World;
+use glam::IVec3;
+use jolyne::valentine::types::{
+    ContainerSlotType, FullContainerName, ItemStackRequestActionsItemContent,
+    ItemStackResponsesItem, ItemStackResponsesItemContent,
+    ItemStackResponsesItemContentContainersItem,
+    ItemStackResponsesItemContentContainersItemSlotsItem, ItemStackResponsesItemStatus,
+    StackRequestSlotInfo,
+};
+use jolyne::valentine::{ItemStackRequestPacket, ItemStackResponsePacket, McpePacket};
+use tracing::{debug, trace, warn};
+
+use super::GameServer;
+use super::containers::{container_block_entities, container_item, set_container_item};
+use crate::entity::components::{
+    ArmourInventory, ContainerType, CursorItem, GameMode, ItemStackRequestState, MainInventory,
+    OffhandSlot, OpenContainer, PlayerSession, UiInventory,
+};
+use crate::item::ItemStack;
+use crate::registry::item::ItemRegistry;
+use crate::registry::recipe::{RecipeRegistry, STONECUTTER};
+use crate::world::ecs::WorldId;
+
+/// A server-side slot addressed by an item stack request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StackSlot {
+    /// Hotbar and main inventory (0-35).
+    Main(usize),
+    /// Armour slots (helmet, chestplate, leggings, boots).
+    Armour(usize),
+    Offhand,
+    Cursor,
+    /// Output of a `CraftCreative` or `CraftRecipe` action within the
+    /// current request.
+    CreatedOutput,
+    /// Slot in the player's open block container.
+    Container(usize),
+    /// Crafting grid or stonecutter input slot of the player's UI inventory.
+    Ui(usize),
+}
+
+impl StackSlot {
+    /// Map a protocol slot to a server slot.
+    fn resolve(info: &StackRequestSlotInfo) -> Option<Self> {
+        let slot = info.slot as usize;
+        match info.slot_type.container_id {
+            ContainerSlotType::HotbarAndInventory
+            | ContainerSlotType::Hotbar
+            | ContainerSlotType::Inventory => Some(Self::Main(slot)),
+            ContainerSlotType::Armor => Some(Self::Armour(slot)),
+            ContainerSlotType::Offhand => Some(Self::Offhand),
+            ContainerSlotType::Cursor => Some(Self::Cursor),
+            ContainerSlotType::CreativeOutput => Some(Self::CreatedOutput),
+            ContainerSlotType::CraftingInput | ContainerSlotType::StonecutterInput
+                if UiInventory::is_crafting_input(slot) =>
+            {
+                Some(Self::Ui(slot))
+            }
+            ContainerSlotType::Container
+            | ContainerSlotType::Barrel
+            | ContainerSlotType::Shulker
+            | ContainerSlotType::FurnaceIngredient
+            | ContainerSlotType::FurnaceFuel
+            | ContainerSlotType::FurnaceOutput => Some(Self::Container(slot)),
+            _ => None,
+        }
+    }
+}
+
+/// Move `count` items from `source` onto `destination`.
+///
+/// Returns the new `(source, destination)` stacks, or `None` if the source
+/// doesn't hold `count` items or the destination can't take them.
+fn transfer(
+    source: &ItemStack,
+    destination: &ItemStack,
+    count: u8,
+) -> Option<(ItemStack, ItemStack)> {
+    if count == 0 || source.is_empty() || count > source.count {
+        return None;
+    }
+    let (moved, remaining) = source.split(count);
+    if destination.is_empty() {
+        return Some((remaining, moved));
+    }
+    match destination.merge(&moved)? {
+        (merged, leftover) if leftover.is_empty() => Some((remaining, merged)),
+        _ => None,
+    }
+}
+
+/// A craft started by `CraftRecipe` or `CraftRecipeAuto`.
+struct Craft {
+    network_id: u32,
+    times: u8,
+    /// Auto-crafts take their ingredients straight from the inventory.
+    auto: bool,
+    /// Items taken by `Consume` actions so far.
+    consumed: Vec<ItemStack>,
+}
+
+/// Staged state for a single item stack request.
+struct StackRequest {
+    player: Entity,
+    /// Block entities of the open container, in slot order.
+    container: Vec<Entity>,
+    /// Lead position of the open container.
+    container_pos: Option<IVec3>,
+    /// Type of the open container window, if any.
+    container_type: Option<ContainerType>,
+    created: ItemStack,
+    craft: Option<Craft>,
+    staged: Vec<(StackSlot, ItemStack)>,
+    /// Slots reported back in the response, in first-touched order.
+    touched: Vec<(FullContainerName, u8, StackSlot)>,
+}
+
+impl StackRequest {
+    fn new(world: &World, player: Entity) -> Self {
+        let open = world.get::<OpenContainer>(player);
+        let container_type = open.map(|open| open.container_type);
+        let container_pos = open
+            .and_then(|open| open.position)
+            .map(|(x, y, z)| IVec3::new(x, y, z));
+        let container = container_pos
+            .map(|pos| container_block_entities(world, WorldId::of(world, player), pos))
+            .unwrap_or_default();
+
+        Self {
+            player,
+            container,
+            container_pos,
+            container_type,
+            created: ItemStack::empty(),
+            craft: None,
+            staged: Vec::new(),
+            touched: Vec::new(),
+        }
+    }
+
+    /// Resolve a protocol slot and record it for the response.
+    fn slot(&mut self, info: &StackRequestSlotInfo) -> Option<StackSlot> {
+        let slot = StackSlot::resolve(info)?;
+        if slot != StackSlot::CreatedOutput
+            && !self
+                .touched
+                .iter()
+                .any(|(name, index, _)| *name == info.slot_type && *index == info.slot)
+        {
+            self.touched.push((info.slot_type.clone(), info.slot, slot));
+        }
+        Some(slot)
+    }
+
+    /// Read a slot, seeing writes staged earlier in this request.
+    fn get(&self, world: &World, slot: StackSlot) -> Option<ItemStack> {
+        if let Some((_, item)) = self.staged.iter().find(|(s, _)| *s == slot) {
+            return Some(item.clone());
+        }
+        match slot {
+            StackSlot::Main(i) => world.get::<MainInventory>(self.player)?.0.item(i).cloned(),
+            StackSlot::Armour(i) => world
+                .get::<ArmourInventory>(self.player)?
+                .slots()
+                .get(i)
+                .cloned(),
+            StackSlot::Offhand => Some(world.get::<OffhandSlot>(self.player)?.0.clone()),
+            StackSlot::Cursor => Some(world.get::<CursorItem>(self.player)?.0.clone()),
+            StackSlot::CreatedOutput => Some(self.created.clone()),
+            StackSlot::Container(i) => container_item(world, &self.container, i),
+            StackSlot::Ui(i) => world.get::<UiInventory>(self.player)?.0.item(i).cloned(),
+        }
+    }
+
+    /// The crafting grid as it stands after the actions staged so far.
+    fn crafting_grid(&self, world: &World) -> Option<Vec<ItemStack>> {
+        let mut ui = world.get::<UiInventory>(self.player)?.clone();
+        for (slot, item) in &self.staged {
+            if let StackSlot::Ui(i) = *slot {
+                ui.0.set_item(i, item.clone()).ok()?;
+            }
+        }
+        Some(ui.crafting_grid())
+    }
+
+    /// Stage a write to a slot.
+    ///
+    /// Returns `None` if the item isn't allowed in the slot.
+    fn set(&mut self, world: &World, slot: StackSlot, item: ItemStack) -> Option<()> {
+        match slot {
+            StackSlot::CreatedOutput => {
+                self.created = item;
+                return Some(());
+            }
+            StackSlot::Armour(i) => {
+                let mut armour = world.get::<ArmourInventory>(self.player)?.clone();
+                set_armour(&mut armour, i, item.clone())?;
+            }
+            _ => {}
+        }
+
+        match self.staged.iter_mut().find(|(s, _)| *s == slot) {
+            Some((_, staged)) => *staged = item,
+            None => self.staged.push((slot, item)),
+        }
+        Some(())
+    }
+
+    /// Start crafting a recipe, placing its output in the created output slot.
+    ///
+    /// Regular crafts must match the staged crafting grid; auto-crafts (recipe
+    /// book) are only checked against what they consume.
+    fn craft(
+        &mut self,
+        world: &World,
+        recipes: &RecipeRegistry,
+        network_id: i32,
+        times: u8,
+        auto: bool,
+    ) -> Option<()> {
+        if self.craft.is_some() || times == 0 {
+            return None;
+        }
+        let recipe = recipes.get(u32::try_from(network_id).ok()?)?;
+        let stonecutter = recipe.block == STONECUTTER;
+
+        let station = if stonecutter {
+            Some(ContainerType::Stonecutter)
+        } else if recipe.needs_crafting_table() {
+            Some(ContainerType::CraftingTable)
+        } else {
+            None
+        };
+        if station.is_some() && self.container_type != station {
+            return None;
+        }
+
+        if !auto {
+            let grid = if stonecutter {
+                vec![self.get(world, StackSlot::Ui(UiInventory::STONECUTTER_INPUT))?]
+            } else {
+                self.crafting_grid(world)?
+            };
+            if !recipes.matches_grid(recipe, &grid) {
+                return None;
+            }
+        }
+
+        self.created = recipe.output.to_stack(times);
+        self.craft = Some(Craft {
+            network_id: recipe.network_id,
+            times,
+            auto,
+            consumed: Vec::new(),
+        });
+        Some(())
+    }
+
+    /// Check the deprecated crafting results against the recipe being crafted.
+    fn check_results(
+        &self,
+        recipes: &RecipeRegistry,
+        items: &ItemRegistry,
+        results: &[jolyne::valentine::types::ItemLegacy],
+    ) -> Option<()> {
+        // Creative picks also report their results here.
+        let Some(craft) = &self.craft else {
+            return Some(());
+        };
+        let output = &recipes.get(craft.network_id)?.output;
+        let network_id = items.get_by_name(&output.item)?.id as i32;
+        results
+            .iter()
+            .all(|result| result.network_id == network_id)
+            .then_some(())
+    }
+
+    /// Check that a craft started in this request was paid for in full.
+    fn finish(&self, recipes: &RecipeRegistry) -> Option<()> {
+        let Some(craft) = &self.craft else {
+            return Some(());
+        };
+        let recipe = recipes.get(craft.network_id)?;
+        recipes
+            .matches_consumed(recipe, &craft.consumed, craft.times)
+            .then_some(())
+    }
+
+    /// Apply one action to the staged state.
+    fn apply(
+        &mut self,
+        world: &World,
+        recipes: &RecipeRegistry,
+        action: &ItemStackRequestActionsItemContent,
+    ) -> Option<()> {
+        match action {
+            ItemStackRequestActionsItemContent::Take(mv)
+            | ItemStackRequestActionsItemContent::Place(mv)
+            | ItemStackRequestActionsItemContent::TakeOutContainer(mv)
+            | ItemStackRequestActionsItemContent::PlaceInContainer(mv) => {
+                let source = self.slot(&mv.source)?;
+                let destination = self.slot(&mv.destination)?;
+                let (source_item, destination_item) = transfer(
+                    &self.get(world, source)?,
+                    &self.get(world, destination)?,
+                    mv.count,
+                )?;
+                self.set(world, source, source_item)?;
+                self.set(world, destination, destination_item)
+            }
+            ItemStackRequestActionsItemContent::Swap(swap) => {
+                let source = self.slot(&swap.source)?;
+                let destination = self.slot(&swap.destination)?;
+                let source_item = self.get(world, source)?;
+                let destination_item = self.get(world, destination)?;
+                self.set(world, source, destination_item)?;
+                self.set(world, destination, source_item)
+            }
+            ItemStackRequestActionsItemContent::Destroy(destroy) => {
+                let source = self.slot(&destroy.source)?;
+                let item = self.get(world, source)?;
+                if destroy.count == 0 || destroy.count > item.count {
+                    return None;
+                }
+                self.set(world, source, item.grow(-(destroy.count as i16)))
+            }
+            ItemStackRequestActionsItemContent::CraftRecipe(craft) => self.craft(
+                world,
+                recipes,
+                craft.recipe_network_id,
+                craft.times_crafted,
+                false,
+            ),
+            ItemStackRequestActionsItemContent::CraftRecipeAuto(craft) => self.craft(
+                world,
+                recipes,
+                craft.recipe_network_id,
+                craft.times_crafted,
+                true,
+            ),
+            ItemStackRequestActionsItemContent::Consume(consume) => {
+                let source = self.slot(&consume.source)?;
+                let auto = self.craft.as_ref()?.auto;
+                if !auto && !matches!(source, StackSlot::Ui(_)) {
+                    return None;
+                }
+                let item = self.get(world, source)?;
+                if consume.count == 0 || consume.count > item.count {
+                    return None;
+                }
+                let (taken, remaining) = item.split(consume.count);
+                self.craft.as_mut()?.consumed.push(taken);
+                self.set(world, source, remaining)
+            }
+            _ => Some(()),
+        }
+    }
+
+    /// Write staged slots back to the world.
+    ///
+    /// Returns the container slots that changed.
+    fn commit(&self, world: &mut World) -> Vec<usize> {
+        let mut container_slots = Vec::new();
+        for (slot, item) in &self.staged {
+            let item = if item.is_empty() {
+                ItemStack::empty()
+            } else {
+                item.clone()
+            };
+            match *slot {
+                StackSlot::Main(i) => {
+                    if let Some(mut inv) = world.get_mut::<MainInventory>(self.player) {
+                        let _ = inv.0.set_item(i, item);
+                    }
+                }
+                StackSlot::Armour(i) => {
+                    if let Some(mut armour) = world.get_mut::<ArmourInventory>(self.player) {
+                        let _ = set_armour(&mut armour, i, item);
+                    }
+                }
+                StackSlot::Offhand => {
+                    if let Some(mut offhand) = world.get_mut::<OffhandSlot>(self.player) {
+                        offhand.0 = item;
+                    }
+                }
+                StackSlot::Cursor => {
+                    if let Some(mut cursor) = world.get_mut::<CursorItem>(self.player) {
+                        cursor.0 = item;
+                    }
+                }
+                StackSlot::CreatedOutput => {}
+                StackSlot::Container(i) => {
+                    if set_container_item(world, &self.container, i, item) {
+                        container_slots.push(i);
+                    }
+                }
+                StackSlot::Ui(i) => {
+                    if let Some(mut ui) = world.get_mut::<UiInventory>(self.player) {
+                        let _ = ui.0.set_item(i, item);
+                    }
+                }
+            }
+        }
+        container_slots
+    }
+}
+
+/// Set an armour slot by index, validating the item type.
+fn set_armour(armour: &mut ArmourInventory, slot: usize, item: ItemStack) -> Option<()> {
+    let result = match slot {
+        0 => armour.set_helmet(item),
+        1 => armour.set_chestplate(item),
+        2 => armour.set_leggings(item),
+        3 => armour.set_boots(item),
+        _ => return None,
+    };
+    result.ok().map(|_| ())
+}
+
+impl GameServer {
+    /// Handle ItemStackRequest - creative inventory picks, item moves, etc.
+    ///
+    /// This handles the core inventory transactions from the client:
+    /// - CraftCreative: Player clicked an item in creative inventory (creative mode only)
+    /// - Take/Place: Player moved items between slots
+    /// - Swap: Player swapped two slots
+    /// - Destroy: Player deleted an item (creative mode)
+    /// - CraftRecipe/CraftRecipeAuto/Consume: Player crafted a recipe
+    ///
+    /// Slots may belong to the player's inventory or to an open container;
+    /// container changes are mirrored to every other viewer.
+    pub(super) fn handle_item_stack_request(
+        &mut self,
+        entity: Entity,
+        pk: &ItemStackRequestPacket,
+    ) {
+        for request in &pk.requests {
+            let request_id = request.request_id;
+            debug!(
+                request_id,
+                actions = request.actions.len(),
+                "Processing ItemStackRequest"
+            );
+
+            let creative = self.ecs.world().get::<GameMode>(entity) == Some(&GameMode::Creative);
+            let mut txn = StackRequest::new(self.ecs.world(), entity);
+            let mut ok = true;
+            for action in &request.actions {
+                let Some(content) = &action.content else {
+                    continue;
+                };
+                if let ItemStackRequestActionsItemContent::CraftCreative(craft) = content {
+                    if !creative {
+                        debug!(request_id, "Creative craft outside creative mode");
+                        ok = false;
+                        break;
+                    }
+                    match self.creative_item(craft.item_id) {
+                        Some(item) => txn.created = item,
+                        None => {
+                            debug!(item_id = craft.item_id, "Creative item not found");
+                            ok = false;
+                        }
+                    }
+                } else if let ItemStackRequestActionsItemContent::ResultsDeprecated(results) =
+                    content
+                {
+                    if txn
+                        .check_results(&self.recipes, &self.items, &results.result_items)
+                        .is_none()
+                    {
+                        debug!(request_id, "Crafting results don't match the recipe");
+                        ok = false;
+                    }
+                } else if txn
+                    .apply(self.ecs.world(), &self.recipes, content)
+                    .is_none()
+                {
+                    debug!(request_id, type_id = ?action.type_id, "Rejected ItemStackRequest action");
+                    ok = false;
+                } else {
+                    trace!(request_id, type_id = ?action.type_id, "Applied ItemStackRequest action");
+                }
+                if !ok {
+                    break;
+                }
+            }
+
+            if ok && txn.finish(&self.recipes).is_none() {
+                debug!(request_id, "Crafting ingredients don't match the recipe");
+                ok = false;
+            }
+
+            let response = if ok {
+                let containers = self.stack_response_containers(entity, &txn);
+                let changed = txn.commit(self.ecs.world_mut());
+                if let Some(pos) = txn.container_pos {
+                    let world_id = WorldId::of(self.ecs.world(), entity);
+                    self.sync_container_slots(world_id, pos, &changed, Some(entity));
+                }
+                ItemStackResponsesItem {
+                    status: ItemStackResponsesItemStatus::Ok,
+                    request_id,
+                    content: Some(ItemStackResponsesItemContent { containers }),
+                }
+            } else {
+                ItemStackResponsesItem {
+                    status: ItemStackResponsesItemStatus::Error,
+                    request_id,
+                    content: None,
+                }
+            };
+
+            if let Some(session) = self.ecs.world().get::<PlayerSession>(entity) {
+                let _ = session.send(McpePacket::from(ItemStackResponsePacket {
+                    responses: vec![response],
+                }));
+                debug!(request_id, ok, "Sent ItemStackResponse");
+            }
+        }
+    }
+
+    /// Final state of every touched slot, grouped by container.
+    fn stack_response_containers(
+        &mut self,
+        entity: Entity,
+        txn: &StackRequest,
+    ) -> Vec<ItemStackResponsesItemContentContainersItem> {
+        let mut containers: Vec<ItemStackResponsesItemContentContainersItem> = Vec::new();
+        for (name, index, slot) in &txn.touched {
+            let item = txn
+                .get(self.ecs.world(), *slot)
+                .filter(|item| !item.is_empty())
+                .unwrap_or_default();
+            let stack_id = if item.is_empty() {
+                0
+            } else {
+                self.ecs
+                    .world_mut()
+                    .get_mut::<ItemStackRequestState>(entity)
+                    .map_or(0, |mut state| state.next_id())
+            };
+
+            let slot_info = ItemStackResponsesItemContentContainersItemSlotsItem {
+                slot: *index,
+                hotbar_slot: *index,
+                count: item.count,
+                item_stack_id: stack_id,
+                custom_name: String::new(),
+                filtered_custom_name: String::new(),
+                durability_correction: 0,
+            };
+            match containers.iter_mut().find(|c| c.slot_type == *name) {
+                Some(container) => container.slots.push(slot_info),
+                None => containers.push(ItemStackResponsesItemContentContainersItem {
+                    slot_type: name.clone(),
+                    slots: vec![slot_info],
+                }),
+            }
+        }
+        containers
+    }
+
+    /// Resolve a creative item by its 1-indexed network ID.
+    fn creative_item(&self, item_id: i32) -> Option<ItemStack> {
+        let index = (item_id as usize).checked_sub(1)?;
+        let entry = self.world_template.creative_content.items.get(index)?;
+        let Some(item) = self.items.get(entry.item.network_id as u32) else {
+            warn!(
+                "Creative item network_id {} not found in registry",
+                entry.item.network_id
+            );
+            return None;
+        };
+
+        let mut stack = ItemStack::new(item.string_id.clone(), 1);
+        stack.count = stack.max_stack_size();
+        Some(stack)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_to_empty() {
+        let source = ItemStack::new("minecraft:diamond", 10);
+        let (source, destination) = transfer(&source, &ItemStack::empty(), 4).unwrap();
+        assert_eq!(source.count, 6);
+        assert_eq!(destination.count, 4);
+        assert_eq!(destination.item_id, "minecraft:diamond");
+
+        // Taking the whole stack empties the source
+        let (source, destination) = transfer(&destination, &ItemStack::empty(), 4).unwrap();
+        assert!(source.is_empty());
+        assert_eq!(destination.count, 4);
+    }
+
+    #[test]
+    fn test_transfer_merge() {
+        let source = ItemStack::new("minecraft:diamond", 10);
+        let destination = ItemStack::new("minecraft:diamond", 60);
+        let (source, destination) = transfer(&source, &destination, 4).unwrap();
+        assert_eq!(source.count, 6);
+        assert_eq!(destination.count, 64);
+
+        // Overflowing the destination is rejected
+        let full = ItemStack::new("minecraft:diamond", 64);
+        assert!(transfer(&source, &full, 1).is_none());
+    }
+
+    #[test]
+    fn test_transfer_rejects_invalid() {
+        let source = ItemStack::new("minecraft:diamond", 10);
+        let dirt = ItemStack::new("minecraft:dirt", 1);
+        assert!(transfer(&source, &dirt, 1).is_none());
+        assert!(transfer(&source, &ItemStack::empty(), 11).is_none());
+        assert!(transfer(&source, &ItemStack::empty(), 0).is_none());
+        assert!(transfer(&ItemStack::empty(), &ItemStack::empty(), 1).is_none());
+    }
+}
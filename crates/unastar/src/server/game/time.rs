@@ -0,0 +1,319 @@
+//! Day/night cycle and weather.
+//!
+//! Every world's time and weather advance in the ECS schedule; players are
+//! sent their world's `SetTime` every second and a level event whenever rain
+//! or thunder starts or stops there. Only Overworld worlds have weather.
+
+use bevy_ecs::prelude::*;
+use jolyne::valentine::types::Vec3F;
+use jolyne::valentine::{LevelEventPacket, LevelEventPacketEvent, McpePacket, SetTimePacket};
+use std::collections::HashMap;
+use tracing::warn;
+
+use super::GameServer;
+use crate::ecs::resources::TickCounter;
+use crate::entity::components::PlayerSession;
+use crate::world::{ChunkManager, Difficulty, GameRules, WeatherKind, WorldId, WorldTime, Worlds};
+
+/// How often clients are re-sent the time, in ticks.
+const TIME_SYNC_INTERVAL: u64 = 20;
+
+/// Level event data for full rain or thunder intensity.
+const FULL_INTENSITY: i32 = 65535;
+
+fn set_time_packet(time: WorldTime) -> McpePacket {
+    McpePacket::from(SetTimePacket {
+        time: time.packet_time(),
+    })
+}
+
+fn level_event(event: LevelEventPacketEvent, data: i32) -> McpePacket {
+    McpePacket::from(LevelEventPacket {
+        event,
+        position: Vec3F::default(),
+        data,
+    })
+}
+
+/// Level events that take clients from `before` to `after`.
+fn weather_packets(before: WeatherKind, after: WeatherKind) -> Vec<McpePacket> {
+    let raining = |kind| kind != WeatherKind::Clear;
+    let thundering = |kind| kind == WeatherKind::Thunder;
+    let mut packets = Vec::new();
+    if raining(before) != raining(after) {
+        packets.push(if raining(after) {
+            level_event(LevelEventPacketEvent::StartRain, FULL_INTENSITY)
+        } else {
+            level_event(LevelEventPacketEvent::StopRain, 0)
+        });
+    }
+    if thundering(before) != thundering(after) {
+        packets.push(if thundering(after) {
+            level_event(LevelEventPacketEvent::StartThunder, FULL_INTENSITY)
+        } else {
+            level_event(LevelEventPacketEvent::StopThunder, 0)
+        });
+    }
+    packets
+}
+
+/// Level events that show `kind` whatever the client showed before.
+fn sky_packets(kind: WeatherKind) -> Vec<McpePacket> {
+    let rain = if kind == WeatherKind::Clear {
+        level_event(LevelEventPacketEvent::StopRain, 0)
+    } else {
+        level_event(LevelEventPacketEvent::StartRain, FULL_INTENSITY)
+    };
+    let thunder = if kind == WeatherKind::Thunder {
+        level_event(LevelEventPacketEvent::StartThunder, FULL_INTENSITY)
+    } else {
+        level_event(LevelEventPacketEvent::StopThunder, 0)
+    };
+    vec![rain, thunder]
+}
+
+/// Packets that bring a player joining or entering a world up to date.
+pub(super) fn time_packets(world: &World, id: WorldId) -> Vec<McpePacket> {
+    let Some(manager) = world.resource::<Worlds>().get(id) else {
+        return Vec::new();
+    };
+    let sky = if manager.has_weather() {
+        manager.weather().kind()
+    } else {
+        WeatherKind::Clear
+    };
+    let mut packets = vec![set_time_packet(manager.time())];
+    packets.extend(sky_packets(sky));
+    packets
+}
+
+/// Send packets to every player in a world.
+fn broadcast(world: &mut World, id: WorldId, packets: &[McpePacket]) {
+    if packets.is_empty() {
+        return;
+    }
+    for (session, player_world) in world
+        .query::<(&PlayerSession, Option<&WorldId>)>()
+        .iter(world)
+    {
+        if player_world.copied().unwrap_or_default() != id {
+            continue;
+        }
+        for packet in packets {
+            let _ = session.send(packet.clone());
+        }
+    }
+}
+
+/// System: Advance every world's clock and weather cycle, unless the
+/// `doDaylightCycle` and `doWeatherCycle` rules stop them.
+pub(super) fn tick_time_and_weather(
+    mut worlds: ResMut<Worlds>,
+    rules: Res<GameRules>,
+    tick: Res<TickCounter>,
+    sessions: Query<(&PlayerSession, Option<&WorldId>)>,
+) {
+    let sync = tick.current.is_multiple_of(TIME_SYNC_INTERVAL);
+    let mut rng = rand::thread_rng();
+    let mut updates: HashMap<WorldId, Vec<McpePacket>> = HashMap::new();
+    for (id, manager) in worlds.iter_mut() {
+        if rules.do_daylight_cycle {
+            manager.time_mut().time += 1;
+        }
+        let mut packets = Vec::new();
+        if manager.has_weather() {
+            let before = manager.weather().kind();
+            if rules.do_weather_cycle {
+                manager.weather_mut().tick(&mut rng);
+            }
+            packets = weather_packets(before, manager.weather().kind());
+        }
+        if sync {
+            packets.push(set_time_packet(manager.time()));
+        }
+        if !packets.is_empty() {
+            updates.insert(id, packets);
+        }
+    }
+
+    for (session, id) in sessions.iter() {
+        let Some(packets) = updates.get(&id.copied().unwrap_or_default()) else {
+            continue;
+        };
+        for packet in packets {
+            let _ = session.send(packet.clone());
+        }
+    }
+}
+
+/// Set a world's time and tell the players in it.
+pub fn set_time(world: &mut World, id: WorldId, time: i64) {
+    let time = WorldTime { time };
+    {
+        let mut worlds = world.resource_mut::<Worlds>();
+        let Some(manager) = worlds.get_mut(id) else {
+            return;
+        };
+        *manager.time_mut() = time;
+    }
+    broadcast(world, id, &[set_time_packet(time)]);
+}
+
+/// Change a world's weather for `duration` ticks, or a random duration, and
+/// tell the players in it. Worlds without weather are left alone.
+pub fn set_weather(world: &mut World, id: WorldId, kind: WeatherKind, duration: Option<i32>) {
+    let before = {
+        let mut worlds = world.resource_mut::<Worlds>();
+        let Some(manager) = worlds.get_mut(id).filter(|manager| manager.has_weather()) else {
+            return;
+        };
+        let before = manager.weather().kind();
+        manager
+            .weather_mut()
+            .set(kind, duration, &mut rand::thread_rng());
+        before
+    };
+    broadcast(world, id, &weather_packets(before, kind));
+}
+
+impl GameServer {
+    /// Restore the default world's time and weather, and the difficulty and
+    /// game rules, from its `level.dat`.
+    ///
+    /// Difficulty and game rules apply to the whole server, so other worlds
+    /// only take their time and weather from their own file.
+    pub async fn load_level_data(&mut self) {
+        let Some(provider) = self.world_provider.clone() else {
+            return;
+        };
+        match provider.load_level_data().await {
+            Ok(Some(data)) => {
+                let world = self.ecs.world_mut();
+                world.insert_resource(data.difficulty);
+                world.insert_resource(data.game_rules);
+                world
+                    .resource_mut::<Worlds>()
+                    .default_world_mut()
+                    .set_level_data(data);
+                self.update_start_game_rules();
+            }
+            Ok(None) => {}
+            Err(e) => warn!(error = %e, "Failed to load level data"),
+        }
+    }
+
+    /// Restore a world's time and weather from its `level.dat`, e.g. after
+    /// it was loaded at runtime.
+    pub async fn load_world_level_data(&mut self, id: WorldId) {
+        let Some(provider) = self
+            .ecs
+            .world()
+            .resource::<Worlds>()
+            .get(id)
+            .and_then(ChunkManager::provider)
+        else {
+            return;
+        };
+        let data = match provider.load_level_data().await {
+            Ok(Some(data)) => data,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(world = ?id, error = %e, "Failed to load level data");
+                return;
+            }
+        };
+
+        let world = self.ecs.world_mut();
+        // The world may have been unloaded while the file was read
+        let Some(manager) = world.resource_mut::<Worlds>().into_inner().get_mut(id) else {
+            return;
+        };
+        manager.set_level_data(data);
+        // Players may have arrived before the file was read
+        let packets = time_packets(world, id);
+        broadcast(world, id, &packets);
+    }
+
+    /// Write every loaded world's time and weather, and the difficulty and
+    /// game rules, to the world's `level.dat`.
+    pub async fn save_level_data(&mut self) {
+        let world = self.ecs.world();
+        let difficulty = world
+            .get_resource::<Difficulty>()
+            .copied()
+            .unwrap_or_default();
+        let game_rules = world
+            .get_resource::<GameRules>()
+            .copied()
+            .unwrap_or_default();
+        let levels: Vec<_> = world
+            .resource::<Worlds>()
+            .iter()
+            .filter_map(|(_, manager)| {
+                let mut data = manager.level_data();
+                data.difficulty = difficulty;
+                data.game_rules = game_rules;
+                Some((manager.name().to_string(), manager.provider()?, data))
+            })
+            .collect();
+        for (name, provider, data) in levels {
+            if let Err(e) = provider.save_level_data(&data).await {
+                warn!(world = %name, error = %e, "Failed to save level data");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::WorldConfig;
+    use jolyne::valentine::McpePacketData;
+
+    #[test]
+    fn test_weather_packets() {
+        let events = |before, after| -> Vec<LevelEventPacketEvent> {
+            weather_packets(before, after)
+                .into_iter()
+                .map(|packet| match packet.data {
+                    McpePacketData::PacketLevelEvent(event) => event.event,
+                    _ => panic!("expected a level event"),
+                })
+                .collect()
+        };
+        assert!(events(WeatherKind::Rain, WeatherKind::Rain).is_empty());
+        assert_eq!(
+            events(WeatherKind::Clear, WeatherKind::Thunder),
+            vec![
+                LevelEventPacketEvent::StartRain,
+                LevelEventPacketEvent::StartThunder
+            ]
+        );
+        assert_eq!(
+            events(WeatherKind::Thunder, WeatherKind::Rain),
+            vec![LevelEventPacketEvent::StopThunder]
+        );
+    }
+
+    #[test]
+    fn test_time_and_weather_per_world() {
+        let mut world = World::new();
+        let mut worlds = Worlds::default();
+        let nether = WorldConfig {
+            dimension: 1,
+            ..WorldConfig::default()
+        };
+        let nether = worlds.create("nether", nether).unwrap();
+        world.insert_resource(worlds);
+
+        set_time(&mut world, nether, 18000);
+        set_weather(&mut world, WorldId::DEFAULT, WeatherKind::Rain, Some(100));
+        set_weather(&mut world, nether, WeatherKind::Thunder, Some(100));
+        let worlds = world.resource::<Worlds>();
+        let (overworld, nether) = (worlds.default_world(), worlds.get(nether).unwrap());
+        assert_eq!((overworld.time().time, nether.time().time), (0, 18000));
+        assert_eq!(overworld.weather().kind(), WeatherKind::Rain);
+        // No weather outside the Overworld
+        assert_eq!(nether.weather().kind(), WeatherKind::Clear);
+    }
+}
@@ -6,18 +6,43 @@ use bevy_ecs::prelude::*;
 use glam::DVec3;
 use jolyne::valentine::McpePacket;
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 
 use crate::config::PlayerLastPosition;
 use crate::network::SessionId;
+use crate::storage::PlayerData;
+use crate::world::WorldId;
 
-use jolyne::valentine::{TextPacket, TextPacketType};
+use jolyne::valentine::types::DisconnectPacketContent;
+use jolyne::valentine::{DisconnectFailReason, DisconnectPacket, TextPacket, TextPacketType};
 
 /// Wrapper for WorldTemplate to serve as an ECS Resource.
 #[derive(Resource)]
 pub struct ServerWorldTemplate(pub Arc<jolyne::WorldTemplate>);
 
+/// Allocates runtime IDs for entities the server spawns itself, such as
+/// dropped items and mobs.
+///
+/// Starts well above the IDs given to players.
+#[derive(Resource, Debug)]
+pub struct EntityIds(i64);
+
+impl Default for EntityIds {
+    fn default() -> Self {
+        Self(100000)
+    }
+}
+
+impl EntityIds {
+    pub fn allocate(&mut self) -> i64 {
+        let id = self.0;
+        self.0 += 1;
+        id
+    }
+}
+
 /// Wrapper for ItemRegistry to serve as an ECS Resource.
 #[derive(Resource)]
 pub struct ItemRegistryResource(pub Arc<crate::registry::ItemRegistry>);
@@ -26,6 +51,10 @@ pub struct ItemRegistryResource(pub Arc<crate::registry::ItemRegistry>);
 #[derive(Resource)]
 pub struct BlockRegistryResource(pub Arc<crate::registry::BlockRegistry>);
 
+/// Wrapper for RecipeRegistry to serve as an ECS Resource.
+#[derive(Resource)]
+pub struct RecipeRegistryResource(pub Arc<crate::registry::RecipeRegistry>);
+
 /// Mapping from session ID to ECS entity.
 #[derive(Resource, Default)]
 pub struct SessionEntityMap {
@@ -62,17 +91,51 @@ impl SessionEntityMap {
     }
 }
 
+/// A server-wide action requested by a command, handled by the runtime
+/// between ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerRequest {
+    /// Kick everyone, save and exit.
+    Stop,
+    /// Save players and chunks.
+    SaveAll,
+    /// Read the time and weather of a world loaded at runtime from its
+    /// `level.dat`.
+    LoadLevelData(WorldId),
+    /// Write the chunks and `level.dat` of worlds unloaded during the tick.
+    SaveUnloadedWorlds,
+}
+
+/// Requests queued since the runtime last drained them.
+#[derive(Resource, Default)]
+pub struct ServerRequests(Vec<ServerRequest>);
+
+impl ServerRequests {
+    pub fn push(&mut self, request: ServerRequest) {
+        if !self.0.contains(&request) {
+            self.0.push(request);
+        }
+    }
+
+    pub fn drain(&mut self) -> Vec<ServerRequest> {
+        std::mem::take(&mut self.0)
+    }
+}
+
 /// Data needed to spawn a player entity.
 pub struct PlayerSpawnData {
     pub session_id: SessionId,
     pub display_name: String,
     pub xuid: Option<String>,
     pub uuid: Option<String>,
+    pub address: SocketAddr,
     pub runtime_id: i64,
     pub position: DVec3,
     /// Bounded outbound channel to prevent memory explosion on slow connections.
     pub outbound_tx: mpsc::Sender<McpePacket>,
     pub chunk_radius: i32,
+    /// Saved state loaded during the join sequence, if any.
+    pub player_data: Option<Box<PlayerData>>,
 }
 
 /// Data for persisting player state (position, etc.)
@@ -94,3 +157,29 @@ pub fn system_text(message: &str) -> TextPacket {
         filtered_message: None,
     }
 }
+
+/// Create a packet that closes the connection, showing `message` to the player.
+pub fn disconnect_packet(reason: DisconnectFailReason, message: &str) -> DisconnectPacket {
+    DisconnectPacket {
+        reason,
+        hide_disconnect_reason: false,
+        content: Some(DisconnectPacketContent {
+            message: message.to_string(),
+            filtered_message: String::new(),
+        }),
+    }
+}
+
+/// Create a text packet that the client translates, such as a death message.
+pub fn translated_text(key: &str, parameters: Vec<String>) -> TextPacket {
+    TextPacket {
+        type_: TextPacketType::Translation,
+        needs_translation: true,
+        source_name: String::new(),
+        message: key.to_string(),
+        parameters,
+        xuid: String::new(),
+        platform_chat_id: String::new(),
+        filtered_message: None,
+    }
+}
@@ -0,0 +1,472 @@
+//! Moving players between worlds, and loading and unloading worlds at
+//! runtime.
+//!
+//! Commands and plugins only have the ECS world, so moves and unloads are
+//! queued in [`PendingWorldChanges`] and applied by
+//! `GameServer::process_world_changes` after the tick. A move despawns
+//! everything in the old world for the player, streams the new world's
+//! chunks, and sends `ChangeDimension` when the dimension differs; otherwise
+//! the player is teleported. An unloaded world's chunks and `level.dat` are
+//! written by the runtime between ticks, like `/save-all`.
+
+use std::sync::Arc;
+
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use jolyne::valentine::types::Vec3F;
+use jolyne::valentine::{
+    AddPlayerPacket, ChangeDimensionPacket, McpePacket, MovePlayerPacketTeleportCause,
+    RemoveEntityPacket,
+};
+use tracing::{info, warn};
+
+use super::GameServer;
+use super::types::{ServerRequest, ServerRequests};
+use crate::entity::components::{
+    DroppedItem, GameMode, Mob, OpenContainer, PLAYER_EYE_HEIGHT, Player, PlayerName,
+    PlayerSession, PlayerUuid, Position, Rotation, RuntimeEntityId, RuntimeId, SpatialChunk,
+};
+use crate::permission::op_level;
+use crate::server::broadcast::build_add_player_packet;
+use crate::storage::{ChunkColumn, LevelData, WorldProvider};
+use crate::world::ecs::{
+    BlockEntityWorldExt, ChunkData, ChunkLoader, ChunkPosition, ChunkStateFlags, LastPublisherState,
+};
+use crate::world::{
+    ChunkManager, ChunkPos, Difficulty, GameRules, WorldConfig, WorldError, WorldId, Worlds,
+};
+
+/// A queued world change.
+#[derive(Debug, Clone, Copy)]
+enum WorldChange {
+    /// Move a player, to the world spawn when no position is given.
+    Move {
+        player: Entity,
+        world: WorldId,
+        position: Option<DVec3>,
+    },
+    /// Move everyone out of a world, save it and unload it.
+    Unload(WorldId),
+}
+
+/// World changes requested during the tick, applied after it.
+#[derive(Resource, Default)]
+pub(super) struct PendingWorldChanges(Vec<WorldChange>);
+
+/// What an unloaded world still has to write to its storage.
+struct WorldSave {
+    name: String,
+    dimension: i32,
+    provider: Arc<dyn WorldProvider>,
+    columns: Vec<(ChunkPos, ChunkColumn)>,
+    level_data: LevelData,
+}
+
+/// Saves of worlds unloaded during the tick, written by the runtime.
+#[derive(Resource, Default)]
+pub(super) struct PendingWorldSaves(Vec<WorldSave>);
+
+/// Create a world and load it.
+pub fn create_world(
+    world: &mut World,
+    name: &str,
+    config: WorldConfig,
+) -> Result<WorldId, WorldError> {
+    let id = world.resource_mut::<Worlds>().create(name, config)?;
+    info!(world = name, ?config.generator, "Created world");
+    Ok(id)
+}
+
+/// Load a world configured in `[[worlds]]` or created before.
+///
+/// Its time and weather are read from its `level.dat` between ticks.
+pub fn load_world(world: &mut World, name: &str) -> Result<WorldId, WorldError> {
+    let id = world.resource_mut::<Worlds>().load(name)?;
+    world
+        .get_resource_or_init::<ServerRequests>()
+        .push(ServerRequest::LoadLevelData(id));
+    info!(world = name, "Loaded world");
+    Ok(id)
+}
+
+/// Queue a world to be unloaded after the tick.
+///
+/// Players in it are moved to the default world, and its chunks are saved.
+pub fn unload_world(world: &mut World, name: &str) -> Result<(), WorldError> {
+    let worlds = world.resource::<Worlds>();
+    let id = worlds
+        .id(name)
+        .ok_or_else(|| WorldError::NotLoaded(name.to_string()))?;
+    if id.is_default() {
+        return Err(WorldError::DefaultWorld);
+    }
+    queue(world, WorldChange::Unload(id));
+    Ok(())
+}
+
+/// Queue a player to move to another loaded world after the tick.
+///
+/// Without a position the player arrives at the world spawn.
+pub fn change_world(
+    world: &mut World,
+    player: Entity,
+    name: &str,
+    position: Option<DVec3>,
+) -> Result<WorldId, WorldError> {
+    let id = world
+        .resource::<Worlds>()
+        .id(name)
+        .ok_or_else(|| WorldError::NotLoaded(name.to_string()))?;
+    queue(
+        world,
+        WorldChange::Move {
+            player,
+            world: id,
+            position,
+        },
+    );
+    Ok(id)
+}
+
+fn queue(world: &mut World, change: WorldChange) {
+    world
+        .get_resource_or_init::<PendingWorldChanges>()
+        .0
+        .push(change);
+}
+
+impl GameServer {
+    /// Load the `[[worlds]]` entries marked to load at startup.
+    pub fn load_configured_worlds(&mut self) {
+        let entries: Vec<String> = self
+            .config
+            .worlds
+            .iter()
+            .filter(|entry| entry.load)
+            .map(|entry| entry.name.clone())
+            .collect();
+        for name in entries {
+            if let Err(e) = load_world(self.ecs.world_mut(), &name) {
+                warn!(world = %name, error = %e, "Failed to load world");
+            }
+        }
+    }
+
+    /// Apply the world moves and unloads queued during the tick.
+    pub(super) fn process_world_changes(&mut self) {
+        let changes = self
+            .ecs
+            .world_mut()
+            .get_resource_mut::<PendingWorldChanges>()
+            .map(|mut pending| std::mem::take(&mut pending.0))
+            .unwrap_or_default();
+        for change in changes {
+            match change {
+                WorldChange::Move {
+                    player,
+                    world,
+                    position,
+                } => self.move_to_world(player, world, position),
+                WorldChange::Unload(world) => self.unload(world),
+            }
+        }
+    }
+
+    /// Move a player into a loaded world now, with their feet at `position`.
+    pub(super) fn move_to_world(
+        &mut self,
+        player: Entity,
+        target: WorldId,
+        position: Option<DVec3>,
+    ) {
+        let world = self.ecs.world();
+        if world.get::<Player>(player).is_none() {
+            return;
+        }
+        let from = WorldId::of(world, player);
+        let worlds = world.resource::<Worlds>();
+        let Some(manager) = worlds.get(target) else {
+            return;
+        };
+        let dimension = manager.dimension();
+        let old_dimension = worlds.get(from).map_or(dimension, ChunkManager::dimension);
+        let feet = position.unwrap_or_else(|| self.world_spawn(target));
+        let position = feet + DVec3::Y * PLAYER_EYE_HEIGHT;
+
+        if from == target {
+            self.teleport_player(
+                player,
+                position,
+                None,
+                MovePlayerPacketTeleportCause::Unknown,
+            );
+            return;
+        }
+
+        if world.get::<OpenContainer>(player).is_some() {
+            self.close_container(player, true);
+        }
+        self.hide_world(player, from);
+
+        // Removing the loader and spatial chunk drops the player from the old
+        // world's chunk viewers and entity grid; re-inserting them with the
+        // new WorldId streams the new world.
+        let world = self.ecs.world_mut();
+        let radius = world
+            .get::<ChunkLoader>(player)
+            .map_or(4, ChunkLoader::radius);
+        let mut loader = ChunkLoader::new(radius);
+        loader.move_to(
+            (position.x / 16.0).floor() as i32,
+            (position.z / 16.0).floor() as i32,
+        );
+        if !loader.has_pending() {
+            loader.force_reload();
+        }
+        let mut entity = world.entity_mut(player);
+        entity.remove::<(ChunkLoader, SpatialChunk)>();
+        entity.insert((
+            target,
+            Position(position),
+            SpatialChunk::from_position(&Position(position)),
+            loader,
+            LastPublisherState::default(),
+        ));
+
+        if dimension != old_dimension {
+            if let Some(session) = world.get::<PlayerSession>(player) {
+                let _ = session.send(McpePacket::from(ChangeDimensionPacket {
+                    dimension,
+                    position: Vec3F {
+                        x: position.x as f32,
+                        y: position.y as f32,
+                        z: position.z as f32,
+                    },
+                    respawn: false,
+                    loading_screen_id: None,
+                }));
+            }
+        } else {
+            self.teleport_player(
+                player,
+                position,
+                None,
+                MovePlayerPacketTeleportCause::Unknown,
+            );
+        }
+
+        // Each world has its own clock and sky
+        let world = self.ecs.world();
+        if let Some(session) = world.get::<PlayerSession>(player) {
+            for packet in super::time::time_packets(world, target) {
+                let _ = session.send(packet);
+            }
+        }
+
+        self.show_world(player, target);
+        info!(entity = ?player, world = ?target, pos = ?position, "Player changed world");
+    }
+
+    /// Where players arrive in a world without a given position.
+    fn world_spawn(&self, id: WorldId) -> DVec3 {
+        if id.is_default() {
+            let spawn = self.default_spawn();
+            return DVec3::new(spawn.x as f64, spawn.y as f64, spawn.z as f64);
+        }
+        self.ecs
+            .world()
+            .resource::<Worlds>()
+            .get(id)
+            .map_or(DVec3::new(0.5, 17.0, 0.5), ChunkManager::spawn_position)
+    }
+
+    /// Remove every entity in a world from a leaving player's client, and
+    /// the player from everyone else's.
+    fn hide_world(&mut self, player: Entity, id: WorldId) {
+        let world = self.ecs.world_mut();
+        let Some(own) = world.get::<RuntimeEntityId>(player).map(|rid| rid.0) else {
+            return;
+        };
+        let players: Vec<(Entity, i64)> = world
+            .query_filtered::<(Entity, &RuntimeEntityId, &WorldId), With<Player>>()
+            .iter(world)
+            .filter(|(other, _, other_world)| *other != player && **other_world == id)
+            .map(|(other, rid, _)| (other, rid.0))
+            .collect();
+        let others: Vec<i64> = world
+            .query_filtered::<(&RuntimeId, &WorldId), Or<(With<Mob>, With<DroppedItem>)>>()
+            .iter(world)
+            .filter(|(_, other_world)| **other_world == id)
+            .map(|(rid, _)| rid.0)
+            .collect();
+
+        let remove = |runtime_id| {
+            McpePacket::from(RemoveEntityPacket {
+                entity_id_self: runtime_id,
+            })
+        };
+        for &(other, _) in &players {
+            if let Some(session) = world.get::<PlayerSession>(other) {
+                let _ = session.send(remove(own));
+            }
+        }
+        if let Some(session) = world.get::<PlayerSession>(player) {
+            for runtime_id in players.iter().map(|&(_, rid)| rid).chain(others) {
+                let _ = session.send(remove(runtime_id));
+            }
+        }
+    }
+
+    /// Show a player that just arrived in a world to the players there, and
+    /// everything in the world to the player.
+    fn show_world(&mut self, player: Entity, id: WorldId) {
+        let world = self.ecs.world_mut();
+        let others: Vec<Entity> = world
+            .query_filtered::<(Entity, &WorldId), With<Player>>()
+            .iter(world)
+            .filter(|(other, other_world)| *other != player && **other_world == id)
+            .map(|(other, _)| other)
+            .collect();
+
+        if let Some(add) = add_player_packet(world, player) {
+            for &other in &others {
+                if let Some(session) = world.get::<PlayerSession>(other) {
+                    let _ = session.send(McpePacket::from(add.clone()));
+                }
+            }
+        }
+        if let Some(session) = world.get::<PlayerSession>(player) {
+            for &other in &others {
+                if let Some(add) = add_player_packet(world, other) {
+                    let _ = session.send(McpePacket::from(add));
+                }
+            }
+        }
+        self.send_mobs(player);
+        self.send_dropped_items(player);
+    }
+
+    /// Move everyone out of a world and unload it, queueing the save of its
+    /// dirty chunks and `level.dat` for the runtime.
+    fn unload(&mut self, id: WorldId) {
+        let world = self.ecs.world_mut();
+        let occupants: Vec<Entity> = world
+            .query_filtered::<(Entity, &WorldId), With<Player>>()
+            .iter(world)
+            .filter(|(_, player_world)| **player_world == id)
+            .map(|(player, _)| player)
+            .collect();
+        for player in occupants {
+            self.move_to_world(player, WorldId::DEFAULT, None);
+        }
+
+        let world = self.ecs.world_mut();
+        let Some(manager) = world.resource::<Worlds>().get(id) else {
+            return;
+        };
+        let name = manager.name().to_string();
+        let dimension = manager.dimension();
+        let provider = manager.provider();
+        let mut level_data = manager.level_data();
+        level_data.difficulty = world
+            .get_resource::<Difficulty>()
+            .copied()
+            .unwrap_or_default();
+        level_data.game_rules = world
+            .get_resource::<GameRules>()
+            .copied()
+            .unwrap_or_default();
+
+        let entities: Vec<Entity> = world
+            .query_filtered::<(Entity, &WorldId), Without<Player>>()
+            .iter(world)
+            .filter(|(_, entity_world)| **entity_world == id)
+            .map(|(entity, _)| entity)
+            .collect();
+        let columns: Vec<_> = entities
+            .iter()
+            .filter(|&&entity| {
+                world
+                    .get::<ChunkStateFlags>(entity)
+                    .is_some_and(ChunkStateFlags::is_dirty)
+            })
+            .filter_map(|&entity| {
+                let pos = world.get::<ChunkPosition>(entity)?.to_chunk_pos();
+                let chunk = world.get::<ChunkData>(entity)?.inner.clone();
+                let column =
+                    ChunkColumn::new(chunk).with_block_entities(world.chunk_block_entities(entity));
+                Some((pos, column))
+            })
+            .collect();
+
+        if let Some(provider) = provider {
+            world
+                .get_resource_or_init::<PendingWorldSaves>()
+                .0
+                .push(WorldSave {
+                    name: name.clone(),
+                    dimension,
+                    provider,
+                    columns,
+                    level_data,
+                });
+            world
+                .get_resource_or_init::<ServerRequests>()
+                .push(ServerRequest::SaveUnloadedWorlds);
+        }
+
+        for entity in entities {
+            world.despawn(entity);
+        }
+        if let Err(e) = world.resource_mut::<Worlds>().remove(id) {
+            warn!(world = %name, error = %e, "Failed to unload world");
+            return;
+        }
+        info!(world = %name, "Unloaded world");
+    }
+
+    /// Write the chunks and `level.dat` of worlds unloaded since the last
+    /// call, and close their storage.
+    pub async fn save_unloaded_worlds(&mut self) {
+        let saves = self
+            .ecs
+            .world_mut()
+            .get_resource_mut::<PendingWorldSaves>()
+            .map(|mut pending| std::mem::take(&mut pending.0))
+            .unwrap_or_default();
+        for save in saves {
+            let WorldSave {
+                name,
+                dimension,
+                provider,
+                columns,
+                level_data,
+            } = save;
+            let result = async {
+                for (pos, column) in &columns {
+                    provider.save_column(*pos, dimension, column).await?;
+                }
+                provider.save_level_data(&level_data).await?;
+                provider.close().await
+            }
+            .await;
+            match result {
+                Ok(()) => info!(world = %name, chunks = columns.len(), "Saved world"),
+                Err(e) => warn!(world = %name, error = %e, "Failed to save world"),
+            }
+        }
+    }
+}
+
+/// AddPlayer packet showing a player to others.
+fn add_player_packet(world: &World, player: Entity) -> Option<AddPlayerPacket> {
+    Some(build_add_player_packet(
+        world.get::<RuntimeEntityId>(player)?.0,
+        world.get::<PlayerUuid>(player)?.0,
+        &world.get::<PlayerName>(player)?.0,
+        world.get::<Position>(player)?,
+        world.get::<Rotation>(player)?,
+        *world.get::<GameMode>(player)?,
+        op_level(world, player),
+    ))
+}
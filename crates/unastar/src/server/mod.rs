@@ -4,6 +4,7 @@ pub mod broadcast;
 pub mod config;
 pub mod connect;
 pub mod game;
+pub mod rcon;
 pub mod runtime;
 pub mod timer;
 
@@ -7,7 +7,7 @@ use tracing::info;
 
 use crate::server::game::SessionEntityMap;
 use crate::server::broadcast::EntityGrid;
-use crate::world::ChunkManager;
+use crate::world::Worlds;
 
 /// Monitor resource usage and log stats every 5 seconds (100 ticks).
 pub fn monitor_resource_usage(
@@ -15,7 +15,7 @@ pub fn monitor_resource_usage(
     entities: Query<Entity>,
     sessions: Option<Res<SessionEntityMap>>,
     grid: Option<Res<EntityGrid>>,
-    chunks: Option<Res<ChunkManager>>,
+    worlds: Option<Res<Worlds>>,
 ) {
     *tick_counter += 1;
 
@@ -24,7 +24,9 @@ pub fn monitor_resource_usage(
 
         let entity_count = entities.iter().count();
         let session_count = sessions.map(|s| s.len()).unwrap_or(0);
-        let chunk_count = chunks.map(|c| c.len()).unwrap_or(0);
+        let chunk_count = worlds
+            .map(|w| w.iter().map(|(_, manager)| manager.len()).sum::<usize>())
+            .unwrap_or(0);
         
         // Count occupied buckets in grid
         let grid_bucket_count = grid.map(|g| g.bucket_count()).unwrap_or(0);
@@ -0,0 +1,372 @@
+//! Remote administration over the Source RCON protocol.
+//!
+//! Clients authenticate with the configured password and then send command
+//! lines, which are forwarded to the tick loop and run as
+//! [`CommandSender::Rcon`](crate::command::CommandSender::Rcon). The command
+//! output comes back as the response body.
+//!
+//! Failed logins slow the client's address down: each failure doubles the
+//! wait before its next attempt is checked, and a connection is closed after
+//! a few failures.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, error, info, warn};
+
+use crate::config::RconConfig;
+
+const SERVERDATA_AUTH: i32 = 3;
+const SERVERDATA_AUTH_RESPONSE: i32 = 2;
+const SERVERDATA_EXECCOMMAND: i32 = 2;
+const SERVERDATA_RESPONSE_VALUE: i32 = 0;
+
+/// Request id sent back when authentication fails.
+const AUTH_FAILED_ID: i32 = -1;
+
+/// Largest packet, excluding the length prefix.
+const MAX_PACKET_SIZE: usize = 4096;
+/// Largest response body sent in one packet; longer output is split.
+const MAX_RESPONSE_BODY: usize = MAX_PACKET_SIZE - 10;
+
+/// Wait after the first failed login from an address.
+const AUTH_BACKOFF: Duration = Duration::from_secs(1);
+/// Longest wait between login attempts.
+const MAX_AUTH_BACKOFF: Duration = Duration::from_secs(60);
+/// Failed logins after which a connection is closed.
+const MAX_AUTH_FAILURES: u32 = 3;
+
+/// A command line waiting to be run on the tick loop.
+pub struct RconCommand {
+    pub line: String,
+    /// Receives the command output.
+    pub reply: oneshot::Sender<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Packet {
+    id: i32,
+    kind: i32,
+    body: String,
+}
+
+/// Failed logins per client address, shared by all connections.
+struct AuthThrottle {
+    base: Duration,
+    failures: Mutex<HashMap<IpAddr, (u32, Instant)>>,
+}
+
+impl AuthThrottle {
+    fn new(base: Duration) -> Self {
+        Self {
+            base,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// How long an address must wait before its next attempt is checked.
+    fn wait(&self, ip: IpAddr) -> Duration {
+        self.failures
+            .lock()
+            .get(&ip)
+            .map_or(Duration::ZERO, |(_, until)| {
+                until.saturating_duration_since(Instant::now())
+            })
+    }
+
+    fn record(&self, ip: IpAddr, success: bool) {
+        let mut failures = self.failures.lock();
+        let now = Instant::now();
+        // Forget addresses that stopped trying
+        failures.retain(|_, (_, until)| now.duration_since(*until) < MAX_AUTH_BACKOFF);
+        if success {
+            failures.remove(&ip);
+            return;
+        }
+        let (count, until) = failures.entry(ip).or_insert((0, now));
+        *count += 1;
+        let backoff = self.base.saturating_mul(1 << (*count - 1).min(16));
+        *until = now + backoff.min(MAX_AUTH_BACKOFF);
+    }
+}
+
+/// Compare passwords in time independent of where they differ.
+fn password_matches(given: &str, password: &str) -> bool {
+    let (given, password) = (given.as_bytes(), password.as_bytes());
+    let mut diff = given.len() ^ password.len();
+    for (i, &expected) in password.iter().enumerate() {
+        diff |= usize::from(given.get(i).copied().unwrap_or(0) ^ expected);
+    }
+    diff == 0
+}
+
+/// Read one packet, or `None` if the client closed the connection.
+async fn read_packet<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Option<Packet>> {
+    let size = match reader.read_i32_le().await {
+        Ok(size) => size,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    // id, kind and the two terminating nulls
+    if !(10..=MAX_PACKET_SIZE as i32).contains(&size) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid packet size {size}"),
+        ));
+    }
+    let mut payload = vec![0; size as usize];
+    reader.read_exact(&mut payload).await?;
+
+    let id = i32::from_le_bytes(payload[0..4].try_into().unwrap());
+    let kind = i32::from_le_bytes(payload[4..8].try_into().unwrap());
+    let body = &payload[8..];
+    let end = body.iter().position(|&b| b == 0).unwrap_or(body.len());
+    Ok(Some(Packet {
+        id,
+        kind,
+        body: String::from_utf8_lossy(&body[..end]).into_owned(),
+    }))
+}
+
+async fn write_packet<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    id: i32,
+    kind: i32,
+    body: &str,
+) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(14 + body.len());
+    buf.extend_from_slice(&(10 + body.len() as i32).to_le_bytes());
+    buf.extend_from_slice(&id.to_le_bytes());
+    buf.extend_from_slice(&kind.to_le_bytes());
+    buf.extend_from_slice(body.as_bytes());
+    buf.extend_from_slice(&[0, 0]);
+    writer.write_all(&buf).await
+}
+
+/// Split `body` into packet-sized pieces on character boundaries.
+fn chunks(body: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = body;
+    while rest.len() > MAX_RESPONSE_BODY {
+        let mut end = MAX_RESPONSE_BODY;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks.push(rest);
+    chunks
+}
+
+/// Serve one client until it disconnects.
+async fn handle_connection<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    addr: SocketAddr,
+    password: &str,
+    throttle: &AuthThrottle,
+    commands: &mpsc::Sender<RconCommand>,
+) -> io::Result<()> {
+    let mut authenticated = false;
+    let mut failures = 0;
+    while let Some(packet) = read_packet(&mut stream).await? {
+        match packet.kind {
+            SERVERDATA_AUTH => {
+                tokio::time::sleep(throttle.wait(addr.ip())).await;
+                authenticated = password_matches(&packet.body, password);
+                throttle.record(addr.ip(), authenticated);
+                if authenticated {
+                    info!(%addr, "RCON client authenticated");
+                    write_packet(&mut stream, packet.id, SERVERDATA_AUTH_RESPONSE, "").await?;
+                } else {
+                    warn!(%addr, "RCON authentication failed");
+                    write_packet(&mut stream, AUTH_FAILED_ID, SERVERDATA_AUTH_RESPONSE, "").await?;
+                    failures += 1;
+                    if failures >= MAX_AUTH_FAILURES {
+                        return Ok(());
+                    }
+                }
+            }
+            SERVERDATA_EXECCOMMAND if authenticated => {
+                info!(%addr, command = %packet.body, "RCON command");
+                let (reply, response) = oneshot::channel();
+                let command = RconCommand {
+                    line: packet.body,
+                    reply,
+                };
+                if commands.send(command).await.is_err() {
+                    // Server shutting down
+                    return Ok(());
+                }
+                let output = response.await.unwrap_or_default();
+                for chunk in chunks(&output) {
+                    write_packet(&mut stream, packet.id, SERVERDATA_RESPONSE_VALUE, chunk).await?;
+                }
+            }
+            SERVERDATA_EXECCOMMAND => {
+                write_packet(&mut stream, AUTH_FAILED_ID, SERVERDATA_AUTH_RESPONSE, "").await?;
+            }
+            kind => {
+                let body = format!("Unknown request {kind:x}");
+                write_packet(&mut stream, packet.id, SERVERDATA_RESPONSE_VALUE, &body).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bind the RCON listener and serve clients in the background.
+///
+/// Commands are forwarded to `commands` for the tick loop to run.
+pub async fn spawn_rcon_listener(
+    config: &RconConfig,
+    commands: mpsc::Sender<RconCommand>,
+) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(&config.bind_address).await?;
+    let local_addr = listener.local_addr()?;
+    let password: Arc<str> = config.password.as_str().into();
+    let throttle = Arc::new(AuthThrottle::new(AUTH_BACKOFF));
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, addr)) => {
+                    debug!(%addr, "RCON connection accepted");
+                    let password = password.clone();
+                    let throttle = throttle.clone();
+                    let commands = commands.clone();
+                    tokio::spawn(async move {
+                        let result =
+                            handle_connection(stream, addr, &password, &throttle, &commands).await;
+                        if let Err(e) = result {
+                            debug!(%addr, error = %e, "RCON connection closed");
+                        }
+                    });
+                }
+                Err(e) => {
+                    error!("RCON accept failed: {:?}", e);
+                }
+            }
+        }
+    });
+    Ok(local_addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_session() {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let (tx, mut rx) = mpsc::channel::<RconCommand>(4);
+        let addr: SocketAddr = "127.0.0.1:25575".parse().unwrap();
+        let throttle = Arc::new(AuthThrottle::new(Duration::from_millis(1)));
+        let server_throttle = throttle.clone();
+        tokio::spawn(async move {
+            let _ = handle_connection(server, addr, "hunter2", &server_throttle, &tx).await;
+        });
+        tokio::spawn(async move {
+            while let Some(command) = rx.recv().await {
+                let output = match command.line.as_str() {
+                    "long" => "x".repeat(MAX_RESPONSE_BODY + 10),
+                    line => format!("ran {line}"),
+                };
+                let _ = command.reply.send(output);
+            }
+        });
+        let mut client = client;
+
+        // Commands are refused before authenticating
+        write_packet(&mut client, 1, SERVERDATA_EXECCOMMAND, "list")
+            .await
+            .unwrap();
+        let packet = read_packet(&mut client).await.unwrap().unwrap();
+        assert_eq!(
+            (packet.id, packet.kind),
+            (AUTH_FAILED_ID, SERVERDATA_AUTH_RESPONSE)
+        );
+
+        write_packet(&mut client, 2, SERVERDATA_AUTH, "wrong")
+            .await
+            .unwrap();
+        let packet = read_packet(&mut client).await.unwrap().unwrap();
+        assert_eq!(packet.id, AUTH_FAILED_ID);
+        assert!(throttle.wait(addr.ip()) > Duration::ZERO);
+
+        write_packet(&mut client, 3, SERVERDATA_AUTH, "hunter2")
+            .await
+            .unwrap();
+        let packet = read_packet(&mut client).await.unwrap().unwrap();
+        assert_eq!((packet.id, packet.kind), (3, SERVERDATA_AUTH_RESPONSE));
+        assert_eq!(throttle.wait(addr.ip()), Duration::ZERO);
+
+        write_packet(&mut client, 4, SERVERDATA_EXECCOMMAND, "list")
+            .await
+            .unwrap();
+        let packet = read_packet(&mut client).await.unwrap().unwrap();
+        assert_eq!(
+            packet,
+            Packet {
+                id: 4,
+                kind: SERVERDATA_RESPONSE_VALUE,
+                body: "ran list".to_string(),
+            }
+        );
+
+        write_packet(&mut client, 5, SERVERDATA_EXECCOMMAND, "long")
+            .await
+            .unwrap();
+        let first = read_packet(&mut client).await.unwrap().unwrap();
+        let second = read_packet(&mut client).await.unwrap().unwrap();
+        assert_eq!(first.body.len(), MAX_RESPONSE_BODY);
+        assert_eq!((second.id, second.body.len()), (5, 10));
+
+        write_packet(&mut client, 6, 7, "").await.unwrap();
+        let packet = read_packet(&mut client).await.unwrap().unwrap();
+        assert_eq!(packet.body, "Unknown request 7");
+    }
+
+    #[tokio::test]
+    async fn test_failed_logins() {
+        let (mut client, server) = tokio::io::duplex(64 * 1024);
+        let (tx, _rx) = mpsc::channel::<RconCommand>(4);
+        let addr: SocketAddr = "127.0.0.1:25575".parse().unwrap();
+        let throttle = AuthThrottle::new(Duration::from_millis(1));
+        let session = tokio::spawn(async move {
+            handle_connection(server, addr, "hunter2", &throttle, &tx).await
+        });
+
+        for id in 0..MAX_AUTH_FAILURES as i32 {
+            write_packet(&mut client, id, SERVERDATA_AUTH, "hunter")
+                .await
+                .unwrap();
+            let packet = read_packet(&mut client).await.unwrap().unwrap();
+            assert_eq!(packet.id, AUTH_FAILED_ID);
+        }
+        // The connection is closed after too many failures
+        assert!(session.await.unwrap().is_ok());
+        assert_eq!(read_packet(&mut client).await.unwrap(), None);
+
+        assert!(password_matches("hunter2", "hunter2"));
+        assert!(!password_matches("hunter", "hunter2"));
+        assert!(!password_matches("hunter22", "hunter2"));
+        assert!(!password_matches("", "hunter2"));
+    }
+
+    #[test]
+    fn test_chunks_split_on_char_boundaries() {
+        let body = "é".repeat(MAX_RESPONSE_BODY);
+        let chunks = chunks(&body);
+        assert_eq!(chunks.concat(), body);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= MAX_RESPONSE_BODY));
+        assert_eq!(super::chunks(""), vec![""]);
+    }
+}
@@ -3,6 +3,7 @@
 //! Provides the `UnastarServer` struct that ties together configuration,
 //! networking, and the game server tick loop.
 
+use bevy_ecs::prelude::*;
 use jolyne::{BedrockListener, BedrockListenerConfig};
 use p384::SecretKey;
 use rand::thread_rng;
@@ -12,16 +13,26 @@ use tokio::sync::{broadcast, mpsc};
 use tokio_raknet::RaknetListener;
 use tracing::{error, info, trace, warn};
 
+use crate::access::AccessControl;
+use crate::command::{CommandOutput, CommandSender};
 use crate::config::{PlayerDataStore, UnastarConfig};
+use crate::console::{self, Completer, Console};
+use crate::entity::components::{Player, PlayerName};
 use crate::network::{NetworkEvent, spawn_network_task};
+use crate::permission::Permissions;
 use crate::plugin::PluginManager;
-use crate::server::connect::{accept_join_sequence, spawn_to_dvec3};
+use crate::server::connect::{JoinError, accept_join_sequence, spawn_to_dvec3};
+use crate::server::game::{ServerRequest, ServerRequests, kick_player};
+use crate::server::rcon::{RconCommand, spawn_rcon_listener};
 use crate::server::{GameServer, PlayerSpawnData};
-use crate::storage::LevelDBPlayerProvider;
+use crate::storage::{LevelDBPlayerProvider, PlayerProvider};
 
 /// Tick rate (20 TPS = 50ms per tick).
 const TICK_DURATION: Duration = Duration::from_millis(50);
 
+/// How often the console's player name completions are refreshed, in ticks.
+const CONSOLE_PLAYERS_INTERVAL: u64 = 20;
+
 /// The main server runtime.
 ///
 /// Orchestrates:
@@ -31,6 +42,9 @@ const TICK_DURATION: Duration = Duration::from_millis(50);
 pub struct UnastarServer {
     config: Arc<UnastarConfig>,
     player_data_store: Arc<PlayerDataStore>,
+    player_provider: Option<Arc<dyn PlayerProvider>>,
+    permissions: Permissions,
+    access: AccessControl,
     server: GameServer,
     server_key: SecretKey,
     plugin_manager: PluginManager,
@@ -46,14 +60,23 @@ impl UnastarServer {
         let mut server = GameServer::with_config(config.server_config());
         server.set_player_data_store(player_data_store.clone(), save_previous_position);
 
+        let permissions = Permissions::load(config.permissions_path())?;
+        info!(ops = permissions.ops().len(), "Loaded permissions");
+        server.set_permissions(permissions.clone());
+
+        let access = AccessControl::load(&config.config_dir)?;
+        server.set_access(access.clone());
+
         // Initialize LevelDB player provider (if enabled)
+        let mut player_provider: Option<Arc<dyn PlayerProvider>> = None;
         if config.players.leveldb_enabled {
             let player_db_path = config.players.data_dir.join("db");
             match LevelDBPlayerProvider::open(&player_db_path) {
                 Ok(provider) => {
                     info!(path = %player_db_path.display(), "Opened player LevelDB");
-                    server
-                        .set_player_provider(Arc::new(provider), config.players.save_on_disconnect);
+                    let provider: Arc<dyn PlayerProvider> = Arc::new(provider);
+                    server.set_player_provider(provider.clone(), config.players.save_on_disconnect);
+                    player_provider = Some(provider);
                 }
                 Err(e) => {
                     warn!(error = %e, "Failed to open player LevelDB, persistence disabled");
@@ -61,59 +84,41 @@ impl UnastarServer {
             }
         }
 
-        // Initialize world provider based on config
-        let world_db_path = std::path::PathBuf::from("worlds")
-            .join("default")
-            .join("db");
-        let dimension = config.server_config().world.dimension;
-        let storage_provider = config.server_config().world.storage_provider;
-
-        let provider: Option<Arc<dyn crate::storage::WorldProvider>> = match storage_provider {
-            crate::world::StorageProvider::LevelDb => {
-                match crate::storage::LevelDBWorldProvider::open(&world_db_path, dimension) {
-                    Ok(provider) => {
-                        info!(path = %world_db_path.display(), "Opened world LevelDB");
-                        Some(Arc::new(provider))
-                    }
-                    Err(e) => {
-                        warn!(error = %e, "Failed to open world LevelDB, chunk persistence disabled");
-                        None
-                    }
-                }
+        // Each world is stored under worlds/<name>/db
+        let worlds_dir = std::path::PathBuf::from("worlds");
+        let world_db_path = worlds_dir.join(crate::world::DEFAULT_WORLD).join("db");
+        let world_config = config.server_config().world;
+        let provider = match crate::storage::open_world_provider(&world_db_path, &world_config) {
+            Ok(provider) => {
+                info!(path = %world_db_path.display(), storage = ?world_config.storage_provider, "Opened world storage");
+                Some(provider)
             }
-            crate::world::StorageProvider::BlazeDb => {
-                let cache_capacity = config.server_config().world.blazedb_cache_chunks;
-                let blaze_config = crate::storage::blazedb::BlazeConfig {
-                    cache_capacity,
-                    ..Default::default()
-                };
-                match crate::storage::BlazeDBProvider::open(&world_db_path, Some(blaze_config)) {
-                    Ok(provider) => {
-                        info!(path = %world_db_path.display(), cache_capacity, "Opened world BlazeDB");
-                        Some(provider as Arc<dyn crate::storage::WorldProvider>)
-                    }
-                    Err(e) => {
-                        warn!(error = %e, "Failed to open world BlazeDB, chunk persistence disabled");
-                        None
-                    }
-                }
+            Err(e) => {
+                warn!(error = %e, "Failed to open world storage, chunk persistence disabled");
+                None
             }
         };
 
-        if let Some(provider) = provider {
-            server.set_world_provider(provider.clone());
-
-            // Also set provider on ChunkManager resource for load-before-generate
-            {
-                let world = server.ecs.world_mut();
-                if let Some(mut chunk_manager) =
-                    world.get_resource_mut::<crate::world::ecs::ChunkManager>()
-                {
-                    chunk_manager.set_provider(provider);
-                }
+        {
+            let mut worlds = server
+                .ecs
+                .world_mut()
+                .resource_mut::<crate::world::Worlds>();
+            worlds.set_storage_dir(&worlds_dir);
+            if let Some(provider) = &provider {
+                // Load-before-generate for the default world
+                worlds.default_world_mut().set_provider(provider.clone());
             }
         }
 
+        if let Some(provider) = provider {
+            server.set_world_provider(provider);
+
+            // Time and weather from level.dat
+            server.load_level_data().await;
+        }
+        server.load_configured_worlds();
+
         // Server key for encryption
         let server_key = SecretKey::random(&mut thread_rng());
 
@@ -132,6 +137,9 @@ impl UnastarServer {
         Ok(Self {
             config,
             player_data_store,
+            player_provider,
+            permissions,
+            access,
             server,
             server_key,
             plugin_manager,
@@ -163,6 +171,14 @@ impl UnastarServer {
         let local_addr = listener.local_addr();
         info!("Listening on {:?}", local_addr);
 
+        // Remote administration; with RCON disabled the sender is dropped and
+        // the channel stays closed
+        let (rcon_tx, mut rcon_rx) = mpsc::channel::<RconCommand>(16);
+        if self.config.server.rcon.enabled {
+            let rcon_addr = spawn_rcon_listener(&self.config.server.rcon, rcon_tx).await?;
+            info!(%rcon_addr, "RCON listening");
+        }
+
         // Consolidated event channel
         let (event_tx, mut event_rx) = mpsc::unbounded_channel::<NetworkEvent>();
 
@@ -176,6 +192,9 @@ impl UnastarServer {
             self.server_key.clone(),
             self.config.clone(),
             self.player_data_store.clone(),
+            self.player_provider.clone(),
+            self.permissions.clone(),
+            self.access.clone(),
             event_tx,
             tick_tx.clone(),
         );
@@ -197,6 +216,10 @@ impl UnastarServer {
         // Shutdown signal
         let mut shutdown = Box::pin(tokio::signal::ctrl_c());
 
+        // Console commands run through the same registry as player commands
+        let mut console = Console::start(Completer::new(&self.server.commands));
+        let mut console_open = true;
+
         loop {
             tokio::select! {
                 _ = interval.tick() => {
@@ -241,19 +264,23 @@ impl UnastarServer {
                                 display_name,
                                 xuid,
                                 uuid,
+                                address,
                                 runtime_id,
                                 initial_position,
                                 outbound_tx,
+                                player_data,
                             } => {
                                 let spawn_data = PlayerSpawnData {
                                     session_id,
                                     display_name,
                                     xuid,
                                     uuid,
+                                    address,
                                     runtime_id,
                                     position: initial_position,
                                     outbound_tx,
                                     chunk_radius: default_chunk_radius,
+                                    player_data,
                                 };
                                 self.server.spawn_player(spawn_data);
                             }
@@ -279,6 +306,13 @@ impl UnastarServer {
                     // Signal all network tasks to flush their buffers
                     let _ = tick_tx.send(());
 
+                    if tick_count.is_multiple_of(CONSOLE_PLAYERS_INTERVAL) {
+                        console.set_players(self.online_players());
+                    }
+                    if self.handle_requests().await {
+                        return Ok(());
+                    }
+
                     let total_elapsed = tick_start.elapsed();
 
                     // Log slow ticks (> 20ms = taking more than half our budget)
@@ -300,32 +334,124 @@ impl UnastarServer {
                         );
                     }
                 }
+                line = console.next_line(), if console_open => {
+                    let Some(line) = line else {
+                        // Stdin closed, e.g. when running detached
+                        console_open = false;
+                        continue;
+                    };
+                    let output = self.run_command(CommandSender::Console, &line);
+                    for text in output.lines() {
+                        console::print(&text);
+                    }
+                    if self.handle_requests().await {
+                        return Ok(());
+                    }
+                }
+                Some(command) = rcon_rx.recv() => {
+                    let output = self.run_command(CommandSender::Rcon, &command.line);
+                    let _ = command.reply.send(output.lines().collect::<Vec<_>>().join("\n"));
+                    if self.handle_requests().await {
+                        return Ok(());
+                    }
+                }
                 _ = &mut shutdown => {
                     info!("Shutdown signal received, saving data...");
+                    self.shutdown().await;
+                    return Ok(());
+                }
+            }
+        }
+    }
 
-                    // Save all player data
-                    let players_saved = self.server.save_all_players().await;
-                    info!(players = players_saved, "Player data saved");
-
-                    // Save all modified chunks
-                    let chunks_saved = self.server.save_all_chunks().await;
-                    info!(chunks = chunks_saved, "Chunk data saved");
+    /// Run a command line from a sender outside the game.
+    fn run_command(&mut self, sender: CommandSender, line: &str) -> CommandOutput {
+        self.server
+            .commands
+            .execute(self.server.ecs.world_mut(), sender, line)
+    }
 
-                    info!("Server shutdown complete");
-                    return Ok(());
+    /// Handle requests queued by commands. Returns true once the server has
+    /// shut down.
+    async fn handle_requests(&mut self) -> bool {
+        let requests = match self
+            .server
+            .ecs
+            .world_mut()
+            .get_resource_mut::<ServerRequests>()
+        {
+            Some(mut requests) => requests.drain(),
+            None => return false,
+        };
+        for request in requests {
+            match request {
+                ServerRequest::SaveAll => {
+                    let players = self.server.save_all_players().await;
+                    let chunks = self.server.save_all_chunks().await;
+                    self.server.save_level_data().await;
+                    info!(players, chunks, "Saved the game");
+                }
+                ServerRequest::LoadLevelData(world) => {
+                    self.server.load_world_level_data(world).await;
+                }
+                ServerRequest::SaveUnloadedWorlds => self.server.save_unloaded_worlds().await,
+                ServerRequest::Stop => {
+                    info!("Stopping the server, saving data...");
+                    self.shutdown().await;
+                    return true;
                 }
             }
         }
+        false
+    }
+
+    /// Disconnect everyone and save players and chunks.
+    async fn shutdown(&mut self) {
+        let world = self.server.ecs.world_mut();
+        let players: Vec<_> = world
+            .query_filtered::<Entity, With<Player>>()
+            .iter(world)
+            .collect();
+        for entity in players {
+            kick_player(world, entity, "Server closed");
+        }
+
+        // Save all player data
+        let players_saved = self.server.save_all_players().await;
+        info!(players = players_saved, "Player data saved");
+
+        // Save all modified chunks
+        self.server.save_unloaded_worlds().await;
+        let chunks_saved = self.server.save_all_chunks().await;
+        info!(chunks = chunks_saved, "Chunk data saved");
+
+        self.server.save_level_data().await;
+
+        info!("Server shutdown complete");
+    }
+
+    /// Names of the players currently online.
+    fn online_players(&mut self) -> Vec<String> {
+        let world = self.server.ecs.world_mut();
+        world
+            .query_filtered::<&PlayerName, With<Player>>()
+            .iter(world)
+            .map(|name| name.0.clone())
+            .collect()
     }
 }
 
 /// Spawn the accept loop as a background task.
+#[allow(clippy::too_many_arguments)]
 fn spawn_accept_loop(
     mut listener: BedrockListener<RaknetListener>,
     template: Arc<jolyne::WorldTemplate>,
     key: SecretKey,
     config: Arc<UnastarConfig>,
     player_data_store: Arc<PlayerDataStore>,
+    player_provider: Option<Arc<dyn PlayerProvider>>,
+    permissions: Permissions,
+    access: AccessControl,
     event_tx: mpsc::UnboundedSender<NetworkEvent>,
     tick_tx: broadcast::Sender<()>,
 ) {
@@ -342,6 +468,9 @@ fn spawn_accept_loop(
                     let key = key.clone();
                     let config = config.clone();
                     let player_data_store = player_data_store.clone();
+                    let player_provider = player_provider.clone();
+                    let permissions = permissions.clone();
+                    let access = access.clone();
                     let event_tx = event_tx.clone();
                     let tick_rx = tick_tx.subscribe();
                     let session_id = next_session_id;
@@ -353,12 +482,15 @@ fn spawn_accept_loop(
                             &key,
                             &config,
                             &player_data_store,
+                            player_provider.as_deref(),
+                            &permissions,
+                            &access,
                             session_id,
                             handshake_stream,
                         )
                         .await
                         {
-                            Ok((mut play_stream, identity, initial_position)) => {
+                            Ok((mut play_stream, identity, initial_position, player_data)) => {
                                 let display_name = identity
                                     .display_name
                                     .as_deref()
@@ -384,9 +516,11 @@ fn spawn_accept_loop(
                                         display_name: display_name.clone(),
                                         xuid: identity.xuid.clone(),
                                         uuid: identity.uuid.clone(),
+                                        address: addr,
                                         runtime_id: session_id as i64,
                                         initial_position: spawn_to_dvec3(&initial_position),
                                         outbound_tx: outbound_tx.clone(),
+                                        player_data: player_data.map(Box::new),
                                     })
                                     .is_err()
                                 {
@@ -403,7 +537,10 @@ fn spawn_accept_loop(
                                     tick_rx,
                                 );
                             }
-                            Err(e) => {
+                            Err(JoinError::Denied { name, reason }) => {
+                                info!(%addr, %name, reason = %reason, "Login refused");
+                            }
+                            Err(JoinError::Protocol(e)) => {
                                 error!(%addr, "Handshake failed: {:?}", e);
                             }
                         }
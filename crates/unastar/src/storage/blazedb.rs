@@ -19,8 +19,10 @@ use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
 use super::cache::ShardedCache;
+use super::level::LevelData;
 use super::morton;
 use super::provider::{ChunkColumn, StorageError, StorageResult, WorldProvider};
+use crate::world::block_entity::{decode_block_entities, encode_block_entities};
 use crate::world::{Chunk, ChunkPos};
 
 /// Magic bytes at start of each chunk entry.
@@ -448,6 +450,7 @@ impl BlazeDBProvider {
         }
 
         // Format: biome_len(4) + biomes + subchunk_count(1) + [y(1) + len(4) + data]...
+        //         + light_len(4) + light + block_entities_len(4) + block_entities
         let mut out = Vec::new();
 
         // Biomes
@@ -462,6 +465,16 @@ impl BlazeDBProvider {
             out.extend_from_slice(&data);
         }
 
+        // Light
+        let light = col.chunk.encode_light();
+        out.extend_from_slice(&(light.len() as u32).to_le_bytes());
+        out.extend_from_slice(&light);
+
+        // Block entities (vanilla little-endian NBT)
+        let block_entities = encode_block_entities(&col.block_entities);
+        out.extend_from_slice(&(block_entities.len() as u32).to_le_bytes());
+        out.extend_from_slice(&block_entities);
+
         out
     }
 
@@ -488,6 +501,7 @@ impl BlazeDBProvider {
 
         // Subchunks
         if cursor >= data.len() {
+            chunk.compute_light();
             return Ok(ChunkColumn::new(chunk));
         }
 
@@ -516,7 +530,37 @@ impl BlazeDBProvider {
             cursor += len;
         }
 
-        Ok(ChunkColumn::new(chunk))
+        // Light (absent in data written before light was persisted)
+        let mut light_loaded = false;
+        if cursor + 4 <= data.len() {
+            let len = u32::from_le_bytes(data[cursor..cursor + 4].try_into().unwrap()) as usize;
+            cursor += 4;
+            if cursor + len <= data.len() {
+                match chunk.decode_light(&data[cursor..cursor + len]) {
+                    Ok(()) => light_loaded = true,
+                    Err(e) => warn!("Failed to decode light: {}", e),
+                }
+                cursor += len;
+            }
+        }
+        if !light_loaded {
+            chunk.compute_light();
+        }
+
+        // Block entities (absent in data written before they were persisted)
+        let mut block_entities = Vec::new();
+        if cursor + 4 <= data.len() {
+            let len = u32::from_le_bytes(data[cursor..cursor + 4].try_into().unwrap()) as usize;
+            cursor += 4;
+            if cursor + len <= data.len() {
+                match decode_block_entities(&data[cursor..cursor + len]) {
+                    Ok(decoded) => block_entities = decoded,
+                    Err(e) => warn!("Failed to decode block entities: {}", e),
+                }
+            }
+        }
+
+        Ok(ChunkColumn::new(chunk).with_block_entities(block_entities))
     }
 }
 
@@ -632,6 +676,15 @@ impl WorldProvider for BlazeDBProvider {
         Ok(())
     }
 
+    async fn load_level_data(&self) -> StorageResult<Option<LevelData>> {
+        // Kept in the database directory, which is the world's own
+        LevelData::load(&self.path).await
+    }
+
+    async fn save_level_data(&self, data: &LevelData) -> StorageResult<()> {
+        data.save(&self.path).await
+    }
+
     async fn flush(&self) -> StorageResult<()> {
         // Sync data file
         {
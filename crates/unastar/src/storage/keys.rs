@@ -11,6 +11,8 @@ pub const KEY_3D_DATA: u8 = b'+'; // 0x2b (biomes)
 pub const KEY_BLOCK_ENTITIES: u8 = b'1'; // 0x31
 pub const KEY_ENTITIES_OLD: u8 = b'2'; // 0x32 (legacy)
 pub const KEY_FINALISATION: u8 = b'6'; // 0x36
+/// Sky and block light (Unastar extension, ignored by vanilla).
+pub const KEY_LIGHT: u8 = b'L'; // 0x4c
 
 // Entity keys.
 pub const KEY_ENTITY_IDS: &[u8] = b"digp";
@@ -59,6 +61,11 @@ pub fn block_entities_key(pos: ChunkPos, dim: i32) -> Vec<u8> {
     chunk_key(pos, dim, &[KEY_BLOCK_ENTITIES])
 }
 
+/// Build the light data key.
+pub fn light_key(pos: ChunkPos, dim: i32) -> Vec<u8> {
+    chunk_key(pos, dim, &[KEY_LIGHT])
+}
+
 /// Build the finalisation key.
 pub fn finalisation_key(pos: ChunkPos, dim: i32) -> Vec<u8> {
     chunk_key(pos, dim, &[KEY_FINALISATION])
@@ -0,0 +1,214 @@
+//! World metadata stored in `level.dat`.
+//!
+//! LevelDB worlds keep the file next to their `db` directory, as Bedrock
+//! does; BlazeDB keeps it inside its database directory. Either way each
+//! world has its own. The file uses the Bedrock layout: a little-endian
+//! storage version and length, then a little-endian NBT compound. Keys the
+//! server doesn't model are kept as they were read.
+
+use std::path::{Path, PathBuf};
+use zuri_nbt::encoding::LittleEndian;
+use zuri_nbt::{NBTTag, tag};
+
+use super::provider::{StorageError, StorageResult};
+use crate::world::{Difficulty, GameRuleValue, GameRules, Weather, WorldTime};
+
+/// Name of the metadata file in a world directory.
+pub const LEVEL_DAT_FILE: &str = "level.dat";
+
+/// Storage version written in the `level.dat` header.
+const STORAGE_VERSION: i32 = 10;
+
+/// Directory that holds `level.dat` for a world's `db` directory.
+pub(super) fn world_dir(db_path: &Path) -> PathBuf {
+    db_path.parent().unwrap_or(db_path).to_path_buf()
+}
+
+/// World-level state that outlives a restart.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LevelData {
+    pub time: WorldTime,
+    pub weather: Weather,
+    pub difficulty: Difficulty,
+    pub game_rules: GameRules,
+    /// Everything read from the file, including keys not modelled above.
+    nbt: tag::Compound,
+}
+
+impl LevelData {
+    /// Serialize to an NBT compound.
+    pub fn to_nbt(&self) -> tag::Compound {
+        let weather = &self.weather;
+        let level = |on: bool| if on { 1.0f32 } else { 0.0 };
+        let mut compound = self.nbt.clone();
+        for (key, value) in [
+            ("Time", tag::Long(self.time.time).into()),
+            ("rainTime", tag::Int(weather.rain_time).into()),
+            ("rainLevel", tag::Float(level(weather.raining)).into()),
+            ("lightningTime", tag::Int(weather.thunder_time).into()),
+            (
+                "lightningLevel",
+                tag::Float(level(weather.thundering)).into(),
+            ),
+            ("clearWeatherTime", tag::Int(weather.clear_time).into()),
+            ("Difficulty", tag::Int(self.difficulty.id() as i32).into()),
+        ] {
+            compound.0.insert(key.into(), value);
+        }
+        // Bedrock keeps game rules at the top level, named in lowercase
+        for (name, value) in self.game_rules.iter() {
+            let value = match value {
+                GameRuleValue::Bool(on) => tag::Byte(on as u8).into(),
+                GameRuleValue::Int(value) => tag::Int(value).into(),
+            };
+            compound.0.insert(name.to_ascii_lowercase(), value);
+        }
+        compound
+    }
+
+    /// Deserialize from an NBT compound.
+    ///
+    /// Missing fields fall back to their defaults.
+    pub fn from_nbt(compound: &tag::Compound) -> Self {
+        let nbt = NBTTag::Compound(compound.clone());
+        let view = nbt.view();
+        let mut game_rules = GameRules::default();
+        for (name, default) in GameRules::default().iter() {
+            let stored = view.at_key(name.to_ascii_lowercase());
+            let value = match default {
+                GameRuleValue::Bool(_) => stored.byte().map(|on| GameRuleValue::Bool(on != 0)),
+                GameRuleValue::Int(_) => stored.int().map(GameRuleValue::Int),
+            };
+            if let Ok(value) = value {
+                game_rules.set(name, value);
+            }
+        }
+        Self {
+            time: WorldTime {
+                time: view.at("Time").long().unwrap_or(0),
+            },
+            weather: Weather {
+                raining: view.at("rainLevel").float().unwrap_or(0.0) > 0.0,
+                thundering: view.at("lightningLevel").float().unwrap_or(0.0) > 0.0,
+                rain_time: view.at("rainTime").int().unwrap_or(0),
+                thunder_time: view.at("lightningTime").int().unwrap_or(0),
+                clear_time: view.at("clearWeatherTime").int().unwrap_or(0),
+            },
+            difficulty: view
+                .at("Difficulty")
+                .int()
+                .ok()
+                .and_then(|id| Difficulty::from_id(id as u8))
+                .unwrap_or_default(),
+            game_rules,
+            nbt: compound.clone(),
+        }
+    }
+
+    /// Encode as a `level.dat` file.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut nbt = Vec::new();
+        // Writing into a Vec cannot fail.
+        let _ = NBTTag::Compound(self.to_nbt()).write(&mut nbt, LittleEndian);
+        let mut buf = Vec::with_capacity(8 + nbt.len());
+        buf.extend_from_slice(&STORAGE_VERSION.to_le_bytes());
+        buf.extend_from_slice(&(nbt.len() as i32).to_le_bytes());
+        buf.extend_from_slice(&nbt);
+        buf
+    }
+
+    /// Decode a `level.dat` file.
+    pub fn decode(bytes: &[u8]) -> StorageResult<Self> {
+        let Some(mut body) = bytes.get(8..) else {
+            return Err(StorageError::Deserialization(
+                "level.dat is shorter than its header".to_string(),
+            ));
+        };
+        match NBTTag::read(&mut body, LittleEndian) {
+            Ok(NBTTag::Compound(compound)) => Ok(Self::from_nbt(&compound)),
+            Ok(other) => Err(StorageError::Deserialization(format!(
+                "expected level.dat compound, found {}",
+                other.tag_type()
+            ))),
+            Err(e) => Err(StorageError::Deserialization(e.to_string())),
+        }
+    }
+
+    /// Read `level.dat` in `dir`. Returns `Ok(None)` for a new world.
+    pub async fn load(dir: &Path) -> StorageResult<Option<Self>> {
+        match tokio::fs::read(dir.join(LEVEL_DAT_FILE)).await {
+            Ok(bytes) => Self::decode(&bytes).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write `level.dat` in `dir`.
+    pub async fn save(&self, dir: &Path) -> StorageResult<()> {
+        tokio::fs::create_dir_all(dir).await?;
+        tokio::fs::write(dir.join(LEVEL_DAT_FILE), self.encode()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_data_roundtrip() {
+        let compound = tag::Compound::builder()
+            .with_string("LevelName", "Bedrock level")
+            .with_long("Time", 1234)
+            .with_byte("keepinventory", 1)
+            .with_int("randomtickspeed", 3)
+            .build();
+        let mut data = LevelData::from_nbt(&compound);
+        assert_eq!(data.time.time, 1234);
+        assert!(data.game_rules.keep_inventory);
+        assert_eq!(data.game_rules.random_tick_speed, 3);
+
+        data.time.time = 30000;
+        data.weather = Weather {
+            raining: true,
+            thundering: false,
+            rain_time: 500,
+            thunder_time: 9000,
+            clear_time: 0,
+        };
+        data.difficulty = Difficulty::Hard;
+        data.game_rules.do_daylight_cycle = false;
+        data.game_rules.spawn_radius = 12;
+        let decoded = LevelData::decode(&data.encode()).unwrap();
+        assert_eq!(decoded.time, data.time);
+        assert_eq!(decoded.weather, data.weather);
+        assert_eq!(decoded.difficulty, Difficulty::Hard);
+        assert_eq!(decoded.game_rules, data.game_rules);
+        // Keys the server doesn't model survive a save
+        let nbt = NBTTag::Compound(decoded.to_nbt());
+        assert_eq!(
+            nbt.view().at("LevelName").string().unwrap(),
+            "Bedrock level"
+        );
+
+        assert!(LevelData::decode(&[10, 0]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_level_data_per_world() {
+        let dir = std::env::temp_dir().join(format!("unastar-level-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let (lobby, arena) = (dir.join("lobby"), dir.join("arena"));
+        let mut data = LevelData::default();
+        data.time.time = 1000;
+        data.save(&lobby).await.unwrap();
+        data.time.time = 2000;
+        data.save(&arena).await.unwrap();
+
+        let time = |data: Option<LevelData>| data.unwrap().time.time;
+        assert_eq!(time(LevelData::load(&lobby).await.unwrap()), 1000);
+        assert_eq!(time(LevelData::load(&arena).await.unwrap()), 2000);
+        assert!(LevelData::load(&dir.join("new")).await.unwrap().is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
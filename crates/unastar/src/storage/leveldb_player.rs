@@ -15,7 +15,9 @@ use crate::storage::provider::{PlayerData, PlayerProvider, StorageError, Storage
 /// LevelDB-based player provider.
 ///
 /// Stores player data in a separate database at `players/db`.
-/// Key = UUID bytes (16 bytes), Value = JSON-encoded PlayerData.
+/// Key = UUID bytes (16 bytes), Value = NBT-encoded PlayerData.
+/// Version 1 JSON values are migrated when loaded and rewritten as NBT on
+/// the next save.
 pub struct LevelDBPlayerProvider {
     /// Thread-safe database handle.
     db: Arc<DB>,
@@ -50,11 +52,7 @@ impl PlayerProvider for LevelDBPlayerProvider {
         task::spawn_blocking(move || {
             let read_opts = ReadOptions::new();
             match db.get(&key, &read_opts) {
-                Ok(Some(data)) => {
-                    let player_data: PlayerData = serde_json::from_slice(&data)
-                        .map_err(|e| StorageError::Deserialization(e.to_string()))?;
-                    Ok(Some(player_data))
-                }
+                Ok(Some(data)) => PlayerData::decode(&data).map(Some),
                 Ok(None) => Ok(None),
                 Err(e) => Err(StorageError::Database(e.to_string())),
             }
@@ -66,8 +64,7 @@ impl PlayerProvider for LevelDBPlayerProvider {
     async fn save(&self, uuid: Uuid, data: &PlayerData) -> StorageResult<()> {
         let db = self.db.clone();
         let key = uuid.as_bytes().to_vec();
-        let value =
-            serde_json::to_vec(data).map_err(|e| StorageError::Serialization(e.to_string()))?;
+        let value = data.encode();
 
         task::spawn_blocking(move || {
             let write_opts = WriteOptions::new();
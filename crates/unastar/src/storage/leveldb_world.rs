@@ -4,12 +4,14 @@
 
 use async_trait::async_trait;
 use bleveldb::{DB, Options, ReadOptions, WriteBatch, WriteOptions};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::task;
 
 use crate::storage::keys;
+use crate::storage::level::{LevelData, world_dir};
 use crate::storage::provider::{ChunkColumn, StorageError, StorageResult, WorldProvider};
+use crate::world::block_entity::{decode_block_entities, encode_block_entities};
 use crate::world::{Chunk, ChunkPos};
 
 /// LevelDB-based world provider.
@@ -20,6 +22,8 @@ pub struct LevelDBWorldProvider {
     db: Arc<DB>,
     /// Dimension ID for key encoding.
     dimension: i32,
+    /// World directory holding `level.dat`, the parent of `db`.
+    world_dir: PathBuf,
 }
 
 impl LevelDBWorldProvider {
@@ -42,6 +46,7 @@ impl LevelDBWorldProvider {
         Ok(Self {
             db: Arc::new(db),
             dimension,
+            world_dir: world_dir(path.as_ref()),
         })
     }
 
@@ -83,10 +88,44 @@ impl WorldProvider for LevelDBWorldProvider {
                         // Missing subchunks are fine - they're just empty (air)
                     }
 
+                    // Light is not part of the vanilla format; recompute if absent
+                    let light_key = keys::light_key(pos, dim);
+                    let light_loaded = match db.get(&light_key, &read_opts) {
+                        Ok(Some(data)) => match chunk.decode_light(&data) {
+                            Ok(()) => true,
+                            Err(e) => {
+                                tracing::warn!(
+                                    chunk = ?(pos.x, pos.z),
+                                    error = %e,
+                                    "Failed to decode light, recomputing"
+                                );
+                                false
+                            }
+                        },
+                        _ => false,
+                    };
+                    if !light_loaded {
+                        chunk.compute_light();
+                    }
+
+                    let block_entities_key = keys::block_entities_key(pos, dim);
+                    let block_entities = match db.get(&block_entities_key, &read_opts) {
+                        Ok(Some(data)) => decode_block_entities(&data).unwrap_or_else(|e| {
+                            tracing::warn!(
+                                chunk = ?(pos.x, pos.z),
+                                error = %e,
+                                "Failed to decode block entities"
+                            );
+                            Vec::new()
+                        }),
+                        _ => Vec::new(),
+                    };
+
                     // TODO: Load biome data from key3DData
-                    // TODO: Load block entities
 
-                    Ok(Some(ChunkColumn::new(chunk)))
+                    Ok(Some(
+                        ChunkColumn::new(chunk).with_block_entities(block_entities),
+                    ))
                 }
                 Ok(None) => Ok(None),
                 Err(e) => Err(StorageError::Database(e)),
@@ -107,6 +146,12 @@ impl WorldProvider for LevelDBWorldProvider {
         let biome_data = col.chunk.encode_biomes();
         let biome_key = keys::biome_key(pos, dim);
 
+        let light_data = col.chunk.encode_light();
+        let light_key = keys::light_key(pos, dim);
+
+        let block_entity_data = encode_block_entities(&col.block_entities);
+        let block_entities_key = keys::block_entities_key(pos, dim);
+
         // Collect subchunk data
         let mut subchunk_entries: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
         for y_index in -4..20i8 {
@@ -135,7 +180,17 @@ impl WorldProvider for LevelDBWorldProvider {
                 batch.put(&key, &data);
             }
 
-            // TODO: Block entities, entities
+            // Light
+            batch.put(&light_key, &light_data);
+
+            // Block entities (the key is absent when a chunk has none)
+            if block_entity_data.is_empty() {
+                batch.delete(&block_entities_key);
+            } else {
+                batch.put(&block_entities_key, &block_entity_data);
+            }
+
+            // TODO: Entities
 
             // Write batch atomically - method is on WriteBatch, takes &db
             batch
@@ -148,6 +203,14 @@ impl WorldProvider for LevelDBWorldProvider {
         .map_err(|e| StorageError::Database(format!("Join error: {e}")))?
     }
 
+    async fn load_level_data(&self) -> StorageResult<Option<LevelData>> {
+        LevelData::load(&self.world_dir).await
+    }
+
+    async fn save_level_data(&self, data: &LevelData) -> StorageResult<()> {
+        data.save(&self.world_dir).await
+    }
+
     async fn flush(&self) -> StorageResult<()> {
         let db = self.db.clone();
         task::spawn_blocking(move || {
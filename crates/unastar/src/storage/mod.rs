@@ -9,6 +9,7 @@
 //! - BlazeDB - high-performance with spatial indexing
 
 mod keys;
+mod level;
 mod provider;
 
 // LevelDB implementations
@@ -22,6 +23,30 @@ pub mod morton;
 
 pub use blazedb::BlazeDBProvider;
 pub use keys::*;
+pub use level::{LEVEL_DAT_FILE, LevelData};
 pub use leveldb_player::LevelDBPlayerProvider;
 pub use leveldb_world::LevelDBWorldProvider;
 pub use provider::*;
+
+use std::path::Path;
+use std::sync::Arc;
+
+/// Open the chunk storage configured for a world at `path`.
+pub fn open_world_provider(
+    path: &Path,
+    config: &crate::world::WorldConfig,
+) -> StorageResult<Arc<dyn WorldProvider>> {
+    match config.storage_provider {
+        crate::world::StorageProvider::LevelDb => Ok(Arc::new(LevelDBWorldProvider::open(
+            path,
+            config.dimension,
+        )?)),
+        crate::world::StorageProvider::BlazeDb => {
+            let blaze_config = blazedb::BlazeConfig {
+                cache_capacity: config.blazedb_cache_chunks,
+                ..Default::default()
+            };
+            Ok(BlazeDBProvider::open(path, Some(blaze_config))?)
+        }
+    }
+}
@@ -3,8 +3,12 @@
 use async_trait::async_trait;
 use std::io;
 use uuid::Uuid;
+use zuri_nbt::encoding::LittleEndian;
+use zuri_nbt::{NBTTag, tag};
 
-use crate::world::{Chunk, ChunkPos};
+use super::level::LevelData;
+use crate::item::{ItemStack, slots_from_nbt, slots_to_nbt};
+use crate::world::{BlockEntity, Chunk, ChunkPos, DEFAULT_WORLD};
 
 /// Result type for storage operations.
 pub type StorageResult<T> = Result<T, StorageError>;
@@ -35,8 +39,8 @@ pub struct ChunkColumn {
     pub chunk: Chunk,
     /// Entities in this chunk (serialized NBT).
     pub entities: Vec<Vec<u8>>,
-    /// Block entities (serialized NBT).
-    pub block_entities: Vec<Vec<u8>>,
+    /// Block entities (chests, signs, ...) in this chunk.
+    pub block_entities: Vec<BlockEntity>,
 }
 
 impl ChunkColumn {
@@ -48,6 +52,12 @@ impl ChunkColumn {
             block_entities: Vec::new(),
         }
     }
+
+    /// Attach block entities to the column.
+    pub fn with_block_entities(mut self, block_entities: Vec<BlockEntity>) -> Self {
+        self.block_entities = block_entities;
+        self
+    }
 }
 
 /// Provider for chunk/column persistence.
@@ -63,6 +73,20 @@ pub trait WorldProvider: Send + Sync + 'static {
     /// Save a chunk column to storage.
     async fn save_column(&self, pos: ChunkPos, dim: i32, col: &ChunkColumn) -> StorageResult<()>;
 
+    /// Load world metadata such as time and weather.
+    ///
+    /// Returns `Ok(None)` for a new world. Providers without metadata
+    /// storage always return `None`.
+    async fn load_level_data(&self) -> StorageResult<Option<LevelData>> {
+        Ok(None)
+    }
+
+    /// Save world metadata.
+    async fn save_level_data(&self, data: &LevelData) -> StorageResult<()> {
+        let _ = data;
+        Ok(())
+    }
+
     /// Flush any pending writes to disk.
     async fn flush(&self) -> StorageResult<()>;
 
@@ -70,9 +94,44 @@ pub trait WorldProvider: Send + Sync + 'static {
     async fn close(&self) -> StorageResult<()>;
 }
 
+/// Current `PlayerData` schema version.
+///
+/// - 1: JSON with position, rotation, health, food and XP level only
+/// - 2: little-endian NBT adding inventory, armour, offhand, effects, air
+///   and spawn point
+pub const PLAYER_DATA_VERSION: i32 = 2;
+
+/// Number of main inventory slots (hotbar + main).
+const INVENTORY_SLOTS: usize = 36;
+/// Number of armour slots.
+const ARMOUR_SLOTS: usize = 4;
+
+/// A player's respawn point (bed or respawn anchor).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerSpawnPoint {
+    pub position: [i32; 3],
+    pub dimension: i32,
+}
+
+/// A saved status effect.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedEffect {
+    pub id: u8,
+    pub amplifier: u8,
+    pub duration_ticks: u32,
+    pub ambient: bool,
+    pub show_particles: bool,
+}
+
 /// Player data for persistence.
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+///
+/// Stored as a little-endian NBT compound using the vanilla key names where
+/// they exist. Item stacks use the saved-item format, so enchantments,
+/// damage and custom names survive a round trip.
+#[derive(Debug, Clone, PartialEq)]
 pub struct PlayerData {
+    /// Schema version the data was read from (`PLAYER_DATA_VERSION` when new).
+    pub version: i32,
     /// Player UUID.
     pub uuid: String,
     /// Last position.
@@ -81,28 +140,272 @@ pub struct PlayerData {
     pub rotation: [f32; 2],
     /// Dimension ID.
     pub dimension: i32,
-    /// Game mode.
+    /// Name of the world the player was in.
+    pub world: String,
+    /// Game mode (Bedrock game type ID).
     pub game_mode: u8,
     /// Health.
     pub health: f32,
     /// Hunger.
     pub food: i32,
+    /// Food saturation.
+    pub saturation: f32,
+    /// Food exhaustion.
+    pub exhaustion: f32,
     /// Experience level.
     pub experience: i32,
-    // TODO: Inventory, effects, etc.
+    /// Progress towards the next level (0.0 to 1.0).
+    pub experience_progress: f32,
+    /// Remaining air in ticks.
+    pub air: i16,
+    /// Respawn point, if the player has set one.
+    pub spawn_point: Option<PlayerSpawnPoint>,
+    /// Hotbar and main inventory (36 slots).
+    pub inventory: Vec<ItemStack>,
+    /// Helmet, chestplate, leggings, boots.
+    pub armour: Vec<ItemStack>,
+    /// Offhand item.
+    pub offhand: ItemStack,
+    /// Selected hotbar slot (0-8).
+    pub selected_slot: u8,
+    /// Active status effects.
+    pub effects: Vec<SavedEffect>,
 }
 
 impl Default for PlayerData {
     fn default() -> Self {
         Self {
+            version: PLAYER_DATA_VERSION,
             uuid: String::new(),
             position: [0.5, 17.0, 0.5],
             rotation: [0.0, 0.0],
             dimension: 0,
+            world: DEFAULT_WORLD.to_string(),
             game_mode: 1, // Creative
             health: 20.0,
             food: 20,
+            saturation: 5.0,
+            exhaustion: 0.0,
             experience: 0,
+            experience_progress: 0.0,
+            air: 300,
+            spawn_point: None,
+            inventory: vec![ItemStack::empty(); INVENTORY_SLOTS],
+            armour: vec![ItemStack::empty(); ARMOUR_SLOTS],
+            offhand: ItemStack::empty(),
+            selected_slot: 0,
+            effects: Vec::new(),
+        }
+    }
+}
+
+impl PlayerData {
+    /// Serialize to an NBT compound.
+    pub fn to_nbt(&self) -> tag::Compound {
+        let [x, y, z] = self.position;
+        let [yaw, pitch] = self.rotation;
+        let mut builder = tag::Compound::builder()
+            .with_int("Version", PLAYER_DATA_VERSION)
+            .with_string("UUID", self.uuid.as_str())
+            .with_list(
+                "Pos",
+                tag::List(vec![
+                    tag::Double(x).into(),
+                    tag::Double(y).into(),
+                    tag::Double(z).into(),
+                ]),
+            )
+            .with_list(
+                "Rotation",
+                tag::List(vec![tag::Float(yaw).into(), tag::Float(pitch).into()]),
+            )
+            .with_int("DimensionId", self.dimension)
+            .with_string("World", self.world.as_str())
+            .with_int("PlayerGameMode", self.game_mode as i32)
+            .with_float("Health", self.health)
+            .with_int("Food", self.food)
+            .with_float("FoodSaturationLevel", self.saturation)
+            .with_float("FoodExhaustionLevel", self.exhaustion)
+            .with_int("PlayerLevel", self.experience)
+            .with_float("PlayerLevelProgress", self.experience_progress)
+            .with_short("Air", self.air)
+            .with_int("SelectedInventorySlot", self.selected_slot as i32);
+        if let Some(spawn) = self.spawn_point {
+            let [x, y, z] = spawn.position;
+            builder = builder
+                .with_int("SpawnX", x)
+                .with_int("SpawnY", y)
+                .with_int("SpawnZ", z)
+                .with_int("SpawnDimension", spawn.dimension);
+        }
+
+        let effects: Vec<NBTTag> = self
+            .effects
+            .iter()
+            .map(|effect| {
+                tag::Compound::builder()
+                    .with_byte("Id", effect.id)
+                    .with_byte("Amplifier", effect.amplifier)
+                    .with_int("Duration", effect.duration_ticks as i32)
+                    .with_byte("Ambient", effect.ambient as u8)
+                    .with_byte("ShowParticles", effect.show_particles as u8)
+                    .build()
+                    .into()
+            })
+            .collect();
+
+        let mut compound = builder
+            .with_list("ActiveEffects", tag::List(effects))
+            .build();
+        compound
+            .0
+            .insert("Inventory".into(), slots_to_nbt(&self.inventory));
+        compound
+            .0
+            .insert("Armor".into(), slots_to_nbt(&self.armour));
+        compound.0.insert(
+            "Offhand".into(),
+            slots_to_nbt(std::slice::from_ref(&self.offhand)),
+        );
+        compound
+    }
+
+    /// Deserialize from an NBT compound.
+    ///
+    /// Missing fields fall back to their defaults.
+    pub fn from_nbt(compound: &tag::Compound) -> Self {
+        let nbt = NBTTag::Compound(compound.clone());
+        let view = nbt.view();
+        let defaults = Self::default();
+
+        let pos = view.at("Pos");
+        let rotation = view.at("Rotation");
+        let spawn_point = match (
+            view.at("SpawnX").int(),
+            view.at("SpawnY").int(),
+            view.at("SpawnZ").int(),
+        ) {
+            (Ok(x), Ok(y), Ok(z)) => Some(PlayerSpawnPoint {
+                position: [x, y, z],
+                dimension: view.at("SpawnDimension").int().unwrap_or(0),
+            }),
+            _ => None,
+        };
+        let effects = view
+            .at("ActiveEffects")
+            .iter_list()
+            .filter_map(|effect| {
+                Some(SavedEffect {
+                    id: effect.at("Id").byte().ok()?,
+                    amplifier: effect.at("Amplifier").byte().unwrap_or(0),
+                    duration_ticks: effect.at("Duration").int().ok()?.max(0) as u32,
+                    ambient: effect.at("Ambient").byte().unwrap_or(0) != 0,
+                    show_particles: effect.at("ShowParticles").byte().unwrap_or(1) != 0,
+                })
+            })
+            .collect();
+
+        Self {
+            version: view.at("Version").int().unwrap_or(PLAYER_DATA_VERSION),
+            uuid: view.at("UUID").string().unwrap_or_default().to_string(),
+            position: [0, 1, 2].map(|i| pos.at(i).double().unwrap_or(defaults.position[i])),
+            rotation: [0, 1].map(|i| rotation.at(i).float().unwrap_or(defaults.rotation[i])),
+            dimension: view.at("DimensionId").int().unwrap_or(defaults.dimension),
+            world: view
+                .at("World")
+                .string()
+                .map_or(defaults.world, str::to_string),
+            game_mode: view
+                .at("PlayerGameMode")
+                .int()
+                .map_or(defaults.game_mode, |mode| mode as u8),
+            health: view.at("Health").float().unwrap_or(defaults.health),
+            food: view.at("Food").int().unwrap_or(defaults.food),
+            saturation: view
+                .at("FoodSaturationLevel")
+                .float()
+                .unwrap_or(defaults.saturation),
+            exhaustion: view.at("FoodExhaustionLevel").float().unwrap_or(0.0),
+            experience: view.at("PlayerLevel").int().unwrap_or(0),
+            experience_progress: view.at("PlayerLevelProgress").float().unwrap_or(0.0),
+            air: view.at("Air").short().unwrap_or(defaults.air),
+            spawn_point,
+            inventory: slots_from_nbt(&view.at("Inventory"), INVENTORY_SLOTS),
+            armour: slots_from_nbt(&view.at("Armor"), ARMOUR_SLOTS),
+            offhand: slots_from_nbt(&view.at("Offhand"), 1).remove(0),
+            selected_slot: view
+                .at("SelectedInventorySlot")
+                .int()
+                .map_or(0, |slot| slot.clamp(0, 8) as u8),
+            effects,
+        }
+    }
+
+    /// Encode for storage as little-endian NBT.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        // Writing into a Vec cannot fail; every list holds a single tag type.
+        let _ = NBTTag::Compound(self.to_nbt()).write(&mut buf, LittleEndian);
+        buf
+    }
+
+    /// Decode stored player data, migrating older schema versions.
+    ///
+    /// Version 1 records were JSON objects; anything else is read as NBT.
+    pub fn decode(bytes: &[u8]) -> StorageResult<Self> {
+        if bytes.first() == Some(&b'{') {
+            let legacy: LegacyPlayerData = serde_json::from_slice(bytes)
+                .map_err(|e| StorageError::Deserialization(e.to_string()))?;
+            return Ok(legacy.into());
+        }
+
+        let mut cursor = bytes;
+        match NBTTag::read(&mut cursor, LittleEndian) {
+            Ok(NBTTag::Compound(compound)) => {
+                let data = Self::from_nbt(&compound);
+                if data.version > PLAYER_DATA_VERSION {
+                    return Err(StorageError::Deserialization(format!(
+                        "player data version {} is newer than supported version {}",
+                        data.version, PLAYER_DATA_VERSION
+                    )));
+                }
+                Ok(data)
+            }
+            Ok(other) => Err(StorageError::Deserialization(format!(
+                "expected player data compound, found {}",
+                other.tag_type()
+            ))),
+            Err(e) => Err(StorageError::Deserialization(e.to_string())),
+        }
+    }
+}
+
+/// Version 1 player data, stored as JSON.
+#[derive(serde::Deserialize)]
+struct LegacyPlayerData {
+    uuid: String,
+    position: [f64; 3],
+    rotation: [f32; 2],
+    dimension: i32,
+    game_mode: u8,
+    health: f32,
+    food: i32,
+    experience: i32,
+}
+
+impl From<LegacyPlayerData> for PlayerData {
+    fn from(legacy: LegacyPlayerData) -> Self {
+        Self {
+            version: 1,
+            uuid: legacy.uuid,
+            position: legacy.position,
+            rotation: legacy.rotation,
+            dimension: legacy.dimension,
+            game_mode: legacy.game_mode,
+            health: legacy.health,
+            food: legacy.food,
+            experience: legacy.experience,
+            ..Self::default()
         }
     }
 }
@@ -126,3 +429,88 @@ pub trait PlayerProvider: Send + Sync + 'static {
     /// Close the provider.
     async fn close(&self) -> StorageResult<()>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_player_data_roundtrip() {
+        let mut enchants = Vec::new();
+        NBTTag::Compound(
+            tag::Compound::builder()
+                .with_compound(
+                    "display",
+                    tag::Compound::builder()
+                        .with_string("Name", "Excalibur")
+                        .build(),
+                )
+                .build(),
+        )
+        .write(&mut enchants, LittleEndian)
+        .unwrap();
+
+        let mut data = PlayerData {
+            uuid: "6a4e1f9c-3b1d-4c8e-9f0a-2b7d5e8c1a3f".into(),
+            position: [12.5, 70.0, -3.25],
+            rotation: [90.0, -15.0],
+            world: "survival_nether".into(),
+            game_mode: 0,
+            health: 13.5,
+            food: 9,
+            saturation: 2.5,
+            exhaustion: 1.25,
+            experience: 30,
+            experience_progress: 0.25,
+            air: 120,
+            spawn_point: Some(PlayerSpawnPoint {
+                position: [100, 64, -20],
+                dimension: 0,
+            }),
+            selected_slot: 4,
+            effects: vec![SavedEffect {
+                id: 1,
+                amplifier: 2,
+                duration_ticks: 600,
+                ambient: false,
+                show_particles: true,
+            }],
+            ..PlayerData::default()
+        };
+        data.inventory[0] = ItemStack::new("minecraft:diamond_sword", 1)
+            .with_damage(12)
+            .with_nbt(enchants);
+        data.inventory[35] = ItemStack::new("minecraft:dirt", 64);
+        data.armour[0] = ItemStack::new("minecraft:iron_helmet", 1);
+        data.offhand = ItemStack::new("minecraft:shield", 1);
+
+        let decoded = PlayerData::decode(&data.encode()).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_player_data_migrates_json() {
+        let json = br#"{"uuid":"abc","position":[1.0,2.0,3.0],"rotation":[4.0,5.0],
+            "dimension":0,"game_mode":2,"health":7.0,"food":11,"experience":3}"#;
+        let data = PlayerData::decode(json).unwrap();
+        assert_eq!(data.version, 1);
+        assert_eq!(data.position, [1.0, 2.0, 3.0]);
+        assert_eq!(data.game_mode, 2);
+        assert_eq!(data.food, 11);
+        assert_eq!(data.inventory.len(), 36);
+        assert!(data.inventory.iter().all(ItemStack::is_empty));
+    }
+
+    #[test]
+    fn test_player_data_rejects_newer_version() {
+        let mut compound = PlayerData::default().to_nbt();
+        compound
+            .0
+            .insert("Version".into(), tag::Int(PLAYER_DATA_VERSION + 1).into());
+        let mut bytes = Vec::new();
+        NBTTag::Compound(compound)
+            .write(&mut bytes, LittleEndian)
+            .unwrap();
+        assert!(PlayerData::decode(&bytes).is_err());
+    }
+}
@@ -0,0 +1,503 @@
+//! Block entities (tile entities).
+//!
+//! Block entities hold per-block state that does not fit in a block runtime ID:
+//! container contents, sign text, furnace progress, banner patterns.
+//!
+//! On disk they are stored in the vanilla format: concatenated little-endian
+//! NBT compounds under the chunk's block-entity key. Clients receive the same
+//! compounds encoded as network NBT in `BlockActorData` packets.
+//!
+//! Types without typed support are kept as [`BlockEntityData::Other`] so that
+//! imported worlds round-trip without losing data.
+
+use bevy_ecs::prelude::Component;
+use glam::IVec3;
+use zuri_nbt::encoding::{LittleEndian, NetworkLittleEndian};
+use zuri_nbt::{NBTTag, tag};
+
+use crate::item::{ItemStack, slots_from_nbt, slots_to_nbt};
+
+/// Number of slots in a single chest.
+pub const CHEST_SLOTS: usize = 27;
+/// Number of slots in a furnace (input, fuel, output).
+pub const FURNACE_SLOTS: usize = 3;
+/// Number of slots in a hopper.
+pub const HOPPER_SLOTS: usize = 5;
+
+/// Default sign text colour (opaque black, ARGB).
+pub const DEFAULT_SIGN_COLOR: i32 = 0xFF00_0000_u32 as i32;
+
+/// A block entity at a world position.
+///
+/// Spawned as its own ECS entity while the owning chunk is loaded.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct BlockEntity {
+    /// World block position.
+    pub position: IVec3,
+    /// Custom name set with an anvil, if any.
+    pub custom_name: Option<String>,
+    /// Type-specific data.
+    pub data: BlockEntityData,
+}
+
+/// Type-specific block entity data.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockEntityData {
+    Chest(Chest),
+    Sign(Sign),
+    Furnace(Furnace),
+    Hopper(Hopper),
+    Banner(Banner),
+    /// A block entity type without typed support, kept verbatim.
+    Other {
+        id: String,
+        nbt: tag::Compound,
+    },
+}
+
+/// Chest or trapped chest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chest {
+    pub items: Vec<ItemStack>,
+    /// Position (x, z) of the other half of a double chest.
+    pub pair: Option<(i32, i32)>,
+    /// Whether this half leads the pair.
+    pub pair_lead: bool,
+}
+
+impl Default for Chest {
+    fn default() -> Self {
+        Self {
+            items: vec![ItemStack::empty(); CHEST_SLOTS],
+            pair: None,
+            pair_lead: false,
+        }
+    }
+}
+
+/// One side of a sign.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignText {
+    pub text: String,
+    /// Text colour (ARGB).
+    pub color: i32,
+    /// Glow ink applied.
+    pub glowing: bool,
+}
+
+impl Default for SignText {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            color: DEFAULT_SIGN_COLOR,
+            glowing: false,
+        }
+    }
+}
+
+/// Standing or wall sign.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Sign {
+    pub front: SignText,
+    pub back: SignText,
+    /// Waxed signs cannot be edited.
+    pub waxed: bool,
+}
+
+/// Furnace smelting state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Furnace {
+    /// Input, fuel and output slots.
+    pub items: Vec<ItemStack>,
+    /// Remaining burn ticks of the current fuel.
+    pub burn_time: i16,
+    /// Progress of the current item in ticks.
+    pub cook_time: i16,
+    /// Total burn ticks of the current fuel.
+    pub burn_duration: i16,
+    /// Experience stored from smelting, awarded on output.
+    pub stored_xp: i32,
+}
+
+impl Default for Furnace {
+    fn default() -> Self {
+        Self {
+            items: vec![ItemStack::empty(); FURNACE_SLOTS],
+            burn_time: 0,
+            cook_time: 0,
+            burn_duration: 0,
+            stored_xp: 0,
+        }
+    }
+}
+
+/// Hopper contents and cooldown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hopper {
+    pub items: Vec<ItemStack>,
+    /// Ticks until the next item transfer.
+    pub transfer_cooldown: i32,
+}
+
+impl Default for Hopper {
+    fn default() -> Self {
+        Self {
+            items: vec![ItemStack::empty(); HOPPER_SLOTS],
+            transfer_cooldown: 0,
+        }
+    }
+}
+
+/// A single banner pattern layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BannerPattern {
+    /// Pattern code (e.g. `"bs"` for bottom stripe).
+    pub pattern: String,
+    /// Dye colour index.
+    pub color: i32,
+}
+
+/// Standing or wall banner.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Banner {
+    /// Base dye colour index.
+    pub base: i32,
+    pub patterns: Vec<BannerPattern>,
+    /// Banner type (0 = normal, 1 = ominous).
+    pub banner_type: i32,
+}
+
+impl BlockEntityData {
+    /// Default data for a freshly placed block, or `None` if the block has no block entity.
+    pub fn for_block(string_id: &str) -> Option<Self> {
+        let name = string_id.strip_prefix("minecraft:").unwrap_or(string_id);
+        match name {
+            "chest" | "trapped_chest" => Some(Self::Chest(Chest::default())),
+            "furnace" | "lit_furnace" => Some(Self::Furnace(Furnace::default())),
+            "hopper" => Some(Self::Hopper(Hopper::default())),
+            "standing_banner" | "wall_banner" => Some(Self::Banner(Banner::default())),
+            _ if name.ends_with("_sign") && !name.ends_with("hanging_sign") => {
+                Some(Self::Sign(Sign::default()))
+            }
+            _ => None,
+        }
+    }
+
+    /// The block entity `id` string used in NBT.
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Chest(_) => "Chest",
+            Self::Sign(_) => "Sign",
+            Self::Furnace(_) => "Furnace",
+            Self::Hopper(_) => "Hopper",
+            Self::Banner(_) => "Banner",
+            Self::Other { id, .. } => id,
+        }
+    }
+
+    /// Container slots, if this block entity holds items.
+    pub fn items(&self) -> Option<&[ItemStack]> {
+        match self {
+            Self::Chest(chest) => Some(&chest.items),
+            Self::Furnace(furnace) => Some(&furnace.items),
+            Self::Hopper(hopper) => Some(&hopper.items),
+            _ => None,
+        }
+    }
+
+    /// Mutable container slots, if this block entity holds items.
+    pub fn items_mut(&mut self) -> Option<&mut [ItemStack]> {
+        match self {
+            Self::Chest(chest) => Some(&mut chest.items),
+            Self::Furnace(furnace) => Some(&mut furnace.items),
+            Self::Hopper(hopper) => Some(&mut hopper.items),
+            _ => None,
+        }
+    }
+}
+
+impl BlockEntity {
+    /// Create a block entity at a position.
+    pub fn new(position: IVec3, data: BlockEntityData) -> Self {
+        Self {
+            position,
+            custom_name: None,
+            data,
+        }
+    }
+
+    /// Serialize to the vanilla block entity compound.
+    pub fn to_nbt(&self) -> tag::Compound {
+        let mut map = match &self.data {
+            BlockEntityData::Other { nbt, .. } => nbt.0.clone(),
+            _ => Default::default(),
+        };
+        let mut put = |key: &str, value: NBTTag| {
+            map.insert(key.to_string(), value);
+        };
+
+        put("id", tag::String::from(self.data.id()).into());
+        put("x", tag::Int(self.position.x).into());
+        put("y", tag::Int(self.position.y).into());
+        put("z", tag::Int(self.position.z).into());
+        put("isMovable", tag::Byte(1).into());
+        if let Some(name) = &self.custom_name {
+            put("CustomName", tag::String::from(name.as_str()).into());
+        }
+
+        match &self.data {
+            BlockEntityData::Chest(chest) => {
+                put("Items", slots_to_nbt(&chest.items));
+                if let Some((x, z)) = chest.pair {
+                    put("pairx", tag::Int(x).into());
+                    put("pairz", tag::Int(z).into());
+                    put("pairlead", tag::Byte(chest.pair_lead as u8).into());
+                }
+            }
+            BlockEntityData::Sign(sign) => {
+                put("FrontText", sign_text_to_nbt(&sign.front));
+                put("BackText", sign_text_to_nbt(&sign.back));
+                put("IsWaxed", tag::Byte(sign.waxed as u8).into());
+            }
+            BlockEntityData::Furnace(furnace) => {
+                put("Items", slots_to_nbt(&furnace.items));
+                put("BurnTime", tag::Short(furnace.burn_time).into());
+                put("CookTime", tag::Short(furnace.cook_time).into());
+                put("BurnDuration", tag::Short(furnace.burn_duration).into());
+                put("StoredXPInt", tag::Int(furnace.stored_xp).into());
+            }
+            BlockEntityData::Hopper(hopper) => {
+                put("Items", slots_to_nbt(&hopper.items));
+                put(
+                    "TransferCooldown",
+                    tag::Int(hopper.transfer_cooldown).into(),
+                );
+            }
+            BlockEntityData::Banner(banner) => {
+                put("Base", tag::Int(banner.base).into());
+                put("Type", tag::Int(banner.banner_type).into());
+                let patterns: Vec<NBTTag> = banner
+                    .patterns
+                    .iter()
+                    .map(|p| {
+                        tag::Compound::builder()
+                            .with_string("Pattern", p.pattern.as_str())
+                            .with_int("Color", p.color)
+                            .build()
+                            .into()
+                    })
+                    .collect();
+                put("Patterns", tag::List(patterns).into());
+            }
+            BlockEntityData::Other { .. } => {}
+        }
+
+        map.into()
+    }
+
+    /// Deserialize from a vanilla block entity compound.
+    ///
+    /// Returns `None` if the compound has no `id` or position.
+    pub fn from_nbt(compound: &tag::Compound) -> Option<Self> {
+        let nbt = NBTTag::Compound(compound.clone());
+        let view = nbt.view();
+        let id = view.at("id").string().ok()?;
+        let position = IVec3::new(
+            view.at("x").int().ok()?,
+            view.at("y").int().ok()?,
+            view.at("z").int().ok()?,
+        );
+        let custom_name = view
+            .at("CustomName")
+            .string()
+            .ok()
+            .filter(|name| !name.is_empty())
+            .map(str::to_string);
+
+        let short = |key: &str| view.at(key).short().unwrap_or(0);
+        let int = |key: &str| view.at(key).int().unwrap_or(0);
+
+        let data = match id {
+            "Chest" => BlockEntityData::Chest(Chest {
+                items: items_from_nbt(&nbt, CHEST_SLOTS),
+                pair: match (view.at("pairx").int(), view.at("pairz").int()) {
+                    (Ok(x), Ok(z)) => Some((x, z)),
+                    _ => None,
+                },
+                pair_lead: view.at("pairlead").byte().unwrap_or(0) != 0,
+            }),
+            "Sign" => BlockEntityData::Sign(Sign {
+                front: sign_text_from_nbt(&nbt, "FrontText"),
+                back: sign_text_from_nbt(&nbt, "BackText"),
+                waxed: view.at("IsWaxed").byte().unwrap_or(0) != 0,
+            }),
+            "Furnace" => BlockEntityData::Furnace(Furnace {
+                items: items_from_nbt(&nbt, FURNACE_SLOTS),
+                burn_time: short("BurnTime"),
+                cook_time: short("CookTime"),
+                burn_duration: short("BurnDuration"),
+                stored_xp: int("StoredXPInt"),
+            }),
+            "Hopper" => BlockEntityData::Hopper(Hopper {
+                items: items_from_nbt(&nbt, HOPPER_SLOTS),
+                transfer_cooldown: int("TransferCooldown"),
+            }),
+            "Banner" => BlockEntityData::Banner(Banner {
+                base: int("Base"),
+                banner_type: int("Type"),
+                patterns: view
+                    .at("Patterns")
+                    .iter_list()
+                    .filter_map(|p| {
+                        Some(BannerPattern {
+                            pattern: p.at("Pattern").string().ok()?.to_string(),
+                            color: p.at("Color").int().unwrap_or(0),
+                        })
+                    })
+                    .collect(),
+            }),
+            _ => BlockEntityData::Other {
+                id: id.to_string(),
+                nbt: compound.clone(),
+            },
+        };
+
+        Some(Self {
+            position,
+            custom_name,
+            data,
+        })
+    }
+
+    /// Encode as network NBT for a `BlockActorData` packet.
+    pub fn to_network_nbt(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        // Writing into a Vec cannot fail; the compound holds no mixed lists.
+        let _ = NBTTag::Compound(self.to_nbt()).write(&mut buf, NetworkLittleEndian);
+        buf
+    }
+}
+
+/// Encode block entities in the vanilla on-disk format.
+pub fn encode_block_entities(block_entities: &[BlockEntity]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for block_entity in block_entities {
+        let _ = NBTTag::Compound(block_entity.to_nbt()).write(&mut buf, LittleEndian);
+    }
+    buf
+}
+
+/// Decode block entities from the vanilla on-disk format.
+///
+/// Compounds without an id or position are skipped.
+pub fn decode_block_entities(data: &[u8]) -> Result<Vec<BlockEntity>, String> {
+    let mut cursor = data;
+    let mut block_entities = Vec::new();
+    while !cursor.is_empty() {
+        match NBTTag::read(&mut cursor, LittleEndian) {
+            Ok(NBTTag::Compound(compound)) => {
+                block_entities.extend(BlockEntity::from_nbt(&compound));
+            }
+            Ok(other) => {
+                return Err(format!(
+                    "Expected block entity compound, found {}",
+                    other.tag_type()
+                ));
+            }
+            Err(e) => return Err(format!("Invalid block entity NBT: {e}")),
+        }
+    }
+    Ok(block_entities)
+}
+
+fn items_from_nbt(nbt: &NBTTag, slots: usize) -> Vec<ItemStack> {
+    slots_from_nbt(&nbt.view().at("Items"), slots)
+}
+
+fn sign_text_to_nbt(text: &SignText) -> NBTTag {
+    tag::Compound::builder()
+        .with_string("Text", text.text.as_str())
+        .with_int("SignTextColor", text.color)
+        .with_byte("IgnoreLighting", text.glowing as u8)
+        .with_byte("HideGlowOutline", 0u8)
+        .with_byte("PersistFormatting", 1u8)
+        .with_string("TextOwner", "")
+        .build()
+        .into()
+}
+
+fn sign_text_from_nbt(nbt: &NBTTag, key: &str) -> SignText {
+    let view = nbt.view();
+    let side = view.at(key);
+    SignText {
+        text: side.at("Text").string().unwrap_or_default().to_string(),
+        color: side.at("SignTextColor").int().unwrap_or(DEFAULT_SIGN_COLOR),
+        glowing: side.at("IgnoreLighting").byte().unwrap_or(0) != 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_block() {
+        assert!(matches!(
+            BlockEntityData::for_block("minecraft:chest"),
+            Some(BlockEntityData::Chest(_))
+        ));
+        assert!(matches!(
+            BlockEntityData::for_block("minecraft:spruce_wall_sign"),
+            Some(BlockEntityData::Sign(_))
+        ));
+        assert!(BlockEntityData::for_block("minecraft:stone").is_none());
+    }
+
+    #[test]
+    fn test_chest_roundtrip() {
+        let mut chest = Chest::default();
+        chest.items[3] = ItemStack::new("minecraft:diamond", 5);
+        chest.pair = Some((11, -4));
+        chest.pair_lead = true;
+        let mut block_entity =
+            BlockEntity::new(IVec3::new(10, 64, -4), BlockEntityData::Chest(chest));
+        block_entity.custom_name = Some("Loot".into());
+
+        let decoded = BlockEntity::from_nbt(&block_entity.to_nbt()).expect("decode");
+        assert_eq!(decoded, block_entity);
+    }
+
+    #[test]
+    fn test_disk_roundtrip_keeps_unknown() {
+        let sign = BlockEntity::new(
+            IVec3::new(0, 70, 0),
+            BlockEntityData::Sign(Sign {
+                front: SignText {
+                    text: "Hello".into(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        );
+        let unknown_nbt = tag::Compound::builder()
+            .with_string("id", "Beehive")
+            .with_int("x", 1)
+            .with_int("y", 2)
+            .with_int("z", 3)
+            .with_byte("ShouldSpawnBees", 1u8)
+            .build();
+        let unknown = BlockEntity::from_nbt(&unknown_nbt).expect("decode");
+
+        let data = encode_block_entities(&[sign.clone(), unknown]);
+        let decoded = decode_block_entities(&data).expect("decode");
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0], sign);
+        match &decoded[1].data {
+            BlockEntityData::Other { id, nbt } => {
+                assert_eq!(id, "Beehive");
+                assert!(nbt.0.contains_key("ShouldSpawnBees"));
+            }
+            other => panic!("expected unknown block entity, got {other:?}"),
+        }
+    }
+}
@@ -5,6 +5,8 @@
 
 use bytes::{BufMut, BytesMut};
 
+use super::light::{LightArray, MAX_LIGHT};
+
 /// Constants for sub-chunk request modes.
 pub mod request_mode {
     /// Client requests sub-chunks on demand via SubChunkRequest packet.
@@ -28,6 +30,12 @@ pub const SUBCHUNK_SIZE: usize = 16;
 /// Total blocks per subchunk.
 pub const BLOCKS_PER_SUBCHUNK: usize = SUBCHUNK_SIZE * SUBCHUNK_SIZE * SUBCHUNK_SIZE;
 
+/// Block storage layer holding the main block.
+pub const LAYER_BLOCK: usize = 0;
+
+/// Block storage layer holding a waterlogging liquid.
+pub const LAYER_LIQUID: usize = 1;
+
 /// Sub-chunk version for network encoding.
 const SUBCHUNK_VERSION: u8 = 9;
 
@@ -68,6 +76,76 @@ pub mod blocks {
         BLOCK_LOOKUP.get(name).copied().unwrap_or(*AIR)
     }
 
+    /// Get block ID by name, or `None` if there is no such block.
+    #[inline]
+    pub fn find_block_id(name: &str) -> Option<u32> {
+        BLOCK_LOOKUP.get(name).copied()
+    }
+
+    /// Runtime IDs of every water state (still and flowing, all levels).
+    static WATER_STATES: LazyLock<Vec<(u32, u32)>> = LazyLock::new(|| {
+        BLOCKS
+            .iter()
+            .filter(|block| {
+                matches!(
+                    block.string_id(),
+                    "minecraft:water" | "minecraft:flowing_water"
+                )
+            })
+            .map(|block| (block.min_state_id(), block.max_state_id()))
+            .collect()
+    });
+
+    /// Check whether a runtime ID is any state of water.
+    pub fn is_water(runtime_id: u32) -> bool {
+        WATER_STATES
+            .iter()
+            .any(|&(min, max)| (min..=max).contains(&runtime_id))
+    }
+
+    /// Runtime IDs of source water: the default, zero depth state of still
+    /// and flowing water.
+    static WATER_SOURCES: LazyLock<Vec<u32>> = LazyLock::new(|| {
+        BLOCKS
+            .iter()
+            .filter(|block| {
+                matches!(
+                    block.string_id(),
+                    "minecraft:water" | "minecraft:flowing_water"
+                )
+            })
+            .map(|block| block.default_state_id())
+            .collect()
+    });
+
+    /// Check whether a runtime ID is a water source (liquid depth 0).
+    ///
+    /// Only source water waterlogs a block placed into it.
+    pub fn is_water_source(runtime_id: u32) -> bool {
+        WATER_SOURCES.contains(&runtime_id)
+    }
+
+    /// Runtime IDs of every lava state (still and flowing, all levels).
+    static LAVA_STATES: LazyLock<Vec<(u32, u32)>> = LazyLock::new(|| {
+        BLOCKS
+            .iter()
+            .filter(|block| {
+                matches!(
+                    block.string_id(),
+                    "minecraft:lava" | "minecraft:flowing_lava"
+                )
+            })
+            .map(|block| (block.min_state_id(), block.max_state_id()))
+            .collect()
+    });
+
+    /// Check whether a runtime ID is any state of lava.
+    pub fn is_lava(runtime_id: u32) -> bool {
+        LAVA_STATES
+            .iter()
+            .any(|&(min, max)| (min..=max).contains(&runtime_id))
+    }
+
     // Core blocks
     pub static AIR: LazyLock<u32> = LazyLock::new(|| lookup("minecraft:air"));
     pub static STONE: LazyLock<u32> = LazyLock::new(|| lookup("minecraft:stone"));
@@ -182,6 +260,10 @@ pub mod blocks {
         LazyLock::new(|| lookup("minecraft:raw_copper_block"));
     pub static RAW_IRON_BLOCK: LazyLock<u32> =
         LazyLock::new(|| lookup("minecraft:raw_iron_block"));
+
+    // Nether and End
+    pub static NETHERRACK: LazyLock<u32> = LazyLock::new(|| lookup("minecraft:netherrack"));
+    pub static END_STONE: LazyLock<u32> = LazyLock::new(|| lookup("minecraft:end_stone"));
 }
 
 /// Height map for a chunk - tracks highest light-blocking block per column.
@@ -327,7 +409,7 @@ pub struct Chunk {
     pub x: i32,
     pub z: i32,
     /// Sub-chunks (index 0 = Y=-64 to Y=-48, etc.)
-    sub_chunks: Vec<SubChunk>,
+    pub(super) sub_chunks: Vec<SubChunk>,
     /// Biome ID for each sub-chunk section (simplified: one per vertical section)
     biome_ids: Vec<u32>,
     /// Height map tracking highest light-blocking block per column
@@ -340,8 +422,13 @@ pub struct Chunk {
 /// A single 16x16x16 sub-chunk.
 #[derive(Debug, Clone)]
 pub struct SubChunk {
-    /// Block storage (single layer for now).
-    storage: PalettedStorage,
+    /// Block storage layers. Layer 0 always exists; layer 1 holds the
+    /// liquid of waterlogged blocks and is only allocated when used.
+    layers: Vec<PalettedStorage>,
+    /// Sky light levels.
+    pub(super) sky_light: LightArray,
+    /// Block light levels.
+    pub(super) block_light: LightArray,
 }
 
 /// Valid bit widths for block storage (matches Bedrock protocol).
@@ -437,6 +524,8 @@ pub struct PalettedStorage {
     size: PaletteSize,
     /// Bit-packed indices into palette. Empty when size == Bits0.
     indices: Vec<u32>,
+    /// Number of entries that are not air, so emptiness checks never scan.
+    non_air: u16,
 }
 
 impl Chunk {
@@ -592,9 +681,10 @@ impl Chunk {
     /// * `x` - Local X coordinate (0-15)
     /// * `y` - World Y coordinate (-64 to 319)
     /// * `z` - Local Z coordinate (0-15)
+    /// * `layer` - Storage layer ([`LAYER_BLOCK`] or [`LAYER_LIQUID`])
     ///
-    /// Returns the block runtime ID, or AIR if out of bounds.
-    pub fn get_block(&self, x: u8, y: i16, z: u8) -> u32 {
+    /// Returns the block runtime ID, or AIR if out of bounds or the layer is unused.
+    pub fn get_block(&self, x: u8, y: i16, z: u8, layer: usize) -> u32 {
         let adjusted_y = (y as i32) - MIN_Y;
         if adjusted_y < 0 || adjusted_y >= (SUBCHUNK_COUNT as i32 * 16) {
             return *blocks::AIR;
@@ -605,7 +695,7 @@ impl Chunk {
 
         self.sub_chunks
             .get(subchunk_idx)
-            .map(|s| s.get_block(x, local_y, z))
+            .map(|s| s.get_block(x, local_y, z, layer))
             .unwrap_or(*blocks::AIR)
     }
 
@@ -615,10 +705,11 @@ impl Chunk {
     /// * `x` - Local X coordinate (0-15)
     /// * `y` - World Y coordinate (-64 to 319)
     /// * `z` - Local Z coordinate (0-15)
+    /// * `layer` - Storage layer ([`LAYER_BLOCK`] or [`LAYER_LIQUID`])
     /// * `block_id` - Block runtime ID to set
     ///
     /// Returns the previous block runtime ID, or None if out of bounds.
-    pub fn set_block(&mut self, x: u8, y: i16, z: u8, block_id: u32) -> Option<u32> {
+    pub fn set_block(&mut self, x: u8, y: i16, z: u8, layer: usize, block_id: u32) -> Option<u32> {
         let adjusted_y = (y as i32) - MIN_Y;
         if adjusted_y < 0 || adjusted_y >= (SUBCHUNK_COUNT as i32 * 16) {
             return None;
@@ -627,10 +718,12 @@ impl Chunk {
         let subchunk_idx = (adjusted_y / 16) as usize;
         let local_y = (adjusted_y % 16) as u8;
 
-        let old = self.sub_chunks[subchunk_idx].set_block(x, local_y, z, block_id);
+        let old = self.sub_chunks[subchunk_idx].set_block(x, local_y, z, layer, block_id);
 
-        // Update height map if needed
-        self.update_heightmap_for_block(x, y, z, block_id);
+        // Height map tracks the main block layer only
+        if layer == LAYER_BLOCK {
+            self.update_heightmap_for_block(x, y, z, block_id);
+        }
 
         Some(old)
     }
@@ -649,7 +742,7 @@ impl Chunk {
             // Scan downward to find new highest non-air block
             let mut new_height = MIN_Y as i16;
             for check_y in (MIN_Y as i16..y).rev() {
-                if self.get_block(x, check_y, z) != *blocks::AIR {
+                if self.get_block(x, check_y, z, LAYER_BLOCK) != *blocks::AIR {
                     new_height = check_y + 1;
                     break;
                 }
@@ -691,28 +784,28 @@ impl Chunk {
 
         let mut offset = 3;
 
-        // Decode each storage layer (usually just 1)
+        // Decode each storage layer (block layer, then waterlogging liquid)
+        let mut layers = Vec::with_capacity(storage_count as usize);
         for _ in 0..storage_count {
             if offset >= data.len() {
                 break;
             }
 
-            let storage = PalettedStorage::decode(&data[offset..])?;
-            offset += storage.1; // Skip bytes consumed
-
-            // Replace the subchunk's storage with the decoded one
-            self.sub_chunks[array_idx].storage = storage.0;
-
-            // Only use first storage layer
-            break;
+            let (storage, consumed) = PalettedStorage::decode(&data[offset..])?;
+            offset += consumed;
+            layers.push(storage);
+        }
+        if layers.is_empty() {
+            layers.push(PalettedStorage::single_block(*blocks::AIR));
         }
+        self.sub_chunks[array_idx].layers = layers;
 
         // Update heightmap for this subchunk
         let base_y = y_index * 16;
         for x in 0u8..16 {
             for z in 0u8..16 {
                 for local_y in (0..16).rev() {
-                    let block = self.sub_chunks[array_idx].get_block(x, local_y, z);
+                    let block = self.sub_chunks[array_idx].get_block(x, local_y, z, LAYER_BLOCK);
                     if block != *blocks::AIR {
                         let world_y = (base_y + local_y as i32) as i16;
                         let current = self.height_map.at(x, z);
@@ -739,38 +832,62 @@ impl SubChunk {
     /// Create an empty (all air) sub-chunk.
     pub fn empty() -> Self {
         Self {
-            storage: PalettedStorage::single_block(*blocks::AIR),
+            layers: vec![PalettedStorage::single_block(*blocks::AIR)],
+            sky_light: LightArray::uniform(MAX_LIGHT),
+            block_light: LightArray::uniform(0),
         }
     }
 
-    /// Check if this subchunk is all air.
+    /// Check if this subchunk is all air in every layer.
     pub fn is_empty(&self) -> bool {
-        self.storage.palette.len() == 1 && self.storage.palette[0] == *blocks::AIR
+        self.layers.iter().all(PalettedStorage::is_air)
     }
 
-    /// Fill an entire Y layer with a block.
+    /// Number of allocated storage layers.
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Fill an entire Y layer of the block layer with a block.
     pub fn fill_layer(&mut self, y: usize, block_id: u32) {
-        self.storage.fill_layer(y, block_id);
+        self.layers[LAYER_BLOCK].fill_layer(y, block_id);
     }
 
-    /// Fill the entire subchunk with a single block type.
+    /// Fill the entire block layer with a single block type.
     /// Uses single-value palette for maximum efficiency.
     pub fn fill_solid(&mut self, block_id: u32) {
-        self.storage = PalettedStorage::single_block(block_id);
+        self.layers[LAYER_BLOCK] = PalettedStorage::single_block(block_id);
     }
 
-    /// Get the block at local coordinates (0-15 for each axis).
+    /// Get the block at local coordinates (0-15 for each axis) in a layer.
+    ///
+    /// Unallocated layers read as air.
     #[inline]
-    pub fn get_block(&self, x: u8, y: u8, z: u8) -> u32 {
-        self.storage.get_block(x as usize, y as usize, z as usize)
+    pub fn get_block(&self, x: u8, y: u8, z: u8, layer: usize) -> u32 {
+        self.layers
+            .get(layer)
+            .map(|storage| storage.get_block(x as usize, y as usize, z as usize))
+            .unwrap_or(*blocks::AIR)
+    }
+
+    /// Runtime IDs present in the block layer's palette.
+    pub(super) fn palette(&self) -> &[u32] {
+        &self.layers[LAYER_BLOCK].palette
     }
 
-    /// Set the block at local coordinates (0-15 for each axis).
+    /// Set the block at local coordinates (0-15 for each axis) in a layer.
+    /// Missing layers are allocated on demand.
     /// Returns the previous block runtime ID.
     #[inline]
-    pub fn set_block(&mut self, x: u8, y: u8, z: u8, block_id: u32) -> u32 {
-        self.storage
-            .set_block(x as usize, y as usize, z as usize, block_id)
+    pub fn set_block(&mut self, x: u8, y: u8, z: u8, layer: usize, block_id: u32) -> u32 {
+        if layer >= self.layers.len() {
+            if block_id == *blocks::AIR {
+                return *blocks::AIR;
+            }
+            self.layers
+                .resize_with(layer + 1, || PalettedStorage::single_block(*blocks::AIR));
+        }
+        self.layers[layer].set_block(x as usize, y as usize, z as usize, block_id)
     }
 
     /// Encode this sub-chunk for network transmission.
@@ -781,26 +898,67 @@ impl SubChunk {
         // - Y index: i8 (relative to world min Y)
         // - For each storage: paletted data
 
+        // Trailing all-air layers are not written (layer 0 always is)
+        let layer_count = self
+            .layers
+            .iter()
+            .rposition(|storage| !storage.is_air())
+            .map_or(1, |last| last + 1);
+
         buf.put_u8(SUBCHUNK_VERSION);
-        buf.put_u8(1); // Single storage layer
+        buf.put_u8(layer_count as u8);
         buf.put_i8(y_index);
 
-        self.storage.encode(buf);
+        for storage in &self.layers[..layer_count] {
+            storage.encode(buf);
+        }
     }
 }
 
 impl PalettedStorage {
+    /// Check whether this storage contains only air.
+    ///
+    /// The palette never shrinks, so this relies on the non-air count
+    /// rather than the palette.
+    fn is_air(&self) -> bool {
+        self.non_air == 0
+    }
+
     /// Create storage with a single block type (0 bytes for indices).
     fn single_block(runtime_id: u32) -> Self {
         Self {
             palette: vec![runtime_id],
             size: PaletteSize::Bits0,
             indices: vec![],
+            non_air: if runtime_id == *blocks::AIR {
+                0
+            } else {
+                BLOCKS_PER_SUBCHUNK as u16
+            },
+        }
+    }
+
+    /// Count the entries that are not air by scanning the indices.
+    fn count_non_air(&self) -> u16 {
+        let air = *blocks::AIR;
+        if self.palette.iter().all(|&id| id == air) {
+            return 0;
         }
+        (0..BLOCKS_PER_SUBCHUNK)
+            .filter(|&idx| self.get_block(idx >> 8, idx & 0xF, (idx >> 4) & 0xF) != air)
+            .count() as u16
     }
 
     /// Fill an entire Y layer with a block.
     fn fill_layer(&mut self, y: usize, block_id: u32) {
+        let air = *blocks::AIR;
+        let replaced = (0..SUBCHUNK_SIZE)
+            .flat_map(|x| (0..SUBCHUNK_SIZE).map(move |z| (x, z)))
+            .filter(|&(x, z)| self.get_block(x, y, z) != air)
+            .count() as u16;
+        let filled = if block_id == air { 0 } else { 256 };
+        self.non_air = self.non_air - replaced + filled;
+
         let palette_idx = self.get_or_add_palette_entry(block_id);
 
         // If still single-value, nothing more to do
@@ -856,6 +1014,13 @@ impl PalettedStorage {
             return old;
         }
 
+        let air = *blocks::AIR;
+        if old == air {
+            self.non_air += 1;
+        } else if block_id == air {
+            self.non_air -= 1;
+        }
+
         let palette_idx = self.get_or_add_palette_entry(block_id);
 
         // If still single-value, nothing more to do
@@ -992,14 +1157,7 @@ impl PalettedStorage {
                 read_signed_varint32(&data[offset..]).ok_or("Failed to read palette value")?;
             offset += consumed;
 
-            return Ok((
-                Self {
-                    palette: vec![value as u32],
-                    size: PaletteSize::Bits0,
-                    indices: vec![],
-                },
-                offset,
-            ));
+            return Ok((Self::single_block(value as u32), offset));
         }
 
         // Multi-value: read packed indices
@@ -1031,7 +1189,14 @@ impl PalettedStorage {
             palette.push(value as u32);
         }
 
-        Ok((Self { palette, size, indices }, offset))
+        let mut storage = Self {
+            palette,
+            size,
+            indices,
+            non_air: 0,
+        };
+        storage.non_air = storage.count_non_air();
+        Ok((storage, offset))
     }
 }
 
@@ -1163,6 +1328,61 @@ mod tests {
         assert_eq!(data[2] as i8, 0);
     }
 
+    #[test]
+    fn test_waterlogged_layer_roundtrip() {
+        let mut chunk = Chunk::new(0, 0);
+        chunk.set_block(3, 10, 5, LAYER_BLOCK, *blocks::STONE);
+        chunk.set_block(3, 10, 5, LAYER_LIQUID, *blocks::WATER);
+
+        assert_eq!(chunk.get_block(3, 10, 5, LAYER_BLOCK), *blocks::STONE);
+        assert_eq!(chunk.get_block(3, 10, 5, LAYER_LIQUID), *blocks::WATER);
+        // Unallocated layers read as air
+        assert_eq!(chunk.get_block(4, 10, 5, LAYER_LIQUID), *blocks::AIR);
+
+        let data = chunk.encode_subchunk(0).expect("subchunk data");
+        assert_eq!(data[1], 2);
+
+        let mut decoded = Chunk::new(0, 0);
+        decoded.decode_subchunk(0, &data).expect("decode");
+        assert_eq!(decoded.get_block(3, 10, 5, LAYER_BLOCK), *blocks::STONE);
+        assert_eq!(decoded.get_block(3, 10, 5, LAYER_LIQUID), *blocks::WATER);
+
+        // Clearing the liquid drops the extra layer from the encoding
+        chunk.set_block(3, 10, 5, LAYER_LIQUID, *blocks::AIR);
+        let data = chunk.encode_subchunk(0).expect("subchunk data");
+        assert_eq!(data[1], 1);
+    }
+
+    #[test]
+    fn test_water_source() {
+        assert!(blocks::is_water_source(*blocks::WATER));
+        assert!(blocks::is_water(*blocks::WATER + 1));
+        assert!(!blocks::is_water_source(*blocks::WATER + 1));
+        assert!(!blocks::is_water_source(*blocks::STONE));
+    }
+
+    #[test]
+    fn test_storage_non_air_count() {
+        let mut storage = PalettedStorage::single_block(*blocks::AIR);
+        assert!(storage.is_air());
+
+        storage.set_block(1, 2, 3, *blocks::STONE);
+        storage.fill_layer(5, *blocks::DIRT);
+        assert_eq!(storage.non_air, 257);
+        assert_eq!(storage.non_air, storage.count_non_air());
+
+        // The palette keeps its entries once the blocks are cleared again
+        storage.set_block(1, 2, 3, *blocks::AIR);
+        storage.fill_layer(5, *blocks::AIR);
+        assert!(storage.is_air());
+        assert_eq!(storage.palette.len(), 3);
+
+        let mut buf = BytesMut::new();
+        PalettedStorage::single_block(*blocks::STONE).encode(&mut buf);
+        let (decoded, _) = PalettedStorage::decode(&buf).unwrap();
+        assert_eq!(decoded.non_air, 4096);
+    }
+
     #[test]
     fn test_palette_size_word_counts() {
         assert_eq!(PaletteSize::Bits0.word_count(), 0);
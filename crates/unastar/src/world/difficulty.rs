@@ -0,0 +1,60 @@
+//! World difficulty.
+
+use bevy_ecs::prelude::*;
+
+/// How punishing the world is: hunger damage, mob damage and spawning.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    Peaceful,
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Peaceful => "peaceful",
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    /// Parse a difficulty name, or its first letter as `/difficulty` accepts.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "peaceful" | "p" => Some(Difficulty::Peaceful),
+            "easy" | "e" => Some(Difficulty::Easy),
+            "normal" | "n" => Some(Difficulty::Normal),
+            "hard" | "h" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    /// Bedrock difficulty ID, as stored in `level.dat` and shared with plugins.
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    /// Damage a mob's hit of `amount` on Normal does to a player on this difficulty.
+    pub fn scale_mob_damage(self, amount: f32) -> f32 {
+        match self {
+            Difficulty::Peaceful => 0.0,
+            Difficulty::Easy => (amount / 2.0 + 1.0).min(amount),
+            Difficulty::Normal => amount,
+            Difficulty::Hard => amount * 1.5,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Difficulty::Peaceful),
+            1 => Some(Difficulty::Easy),
+            2 => Some(Difficulty::Normal),
+            3 => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,407 @@
+//! Block entity ECS integration.
+//!
+//! Block entities live as their own ECS entities while their chunk is loaded:
+//! - Spawned from the stored `ChunkColumn` when a chunk loads, tagged with
+//!   the chunk's `WorldId`
+//! - Tracked per chunk in `ChunkBlockEntities`
+//! - Collected back into the column when the chunk is saved
+//! - Sent to chunk viewers as `BlockActorData` packets
+//! - Created and removed with their block by the `BlockChanged` observer
+//! - Paired into double chests when a chest is placed beside another
+
+use bevy_ecs::prelude::*;
+use glam::IVec3;
+use jolyne::valentine::bedrock::codec::Nbt;
+use jolyne::valentine::blocks::BLOCKS;
+use jolyne::valentine::states::CardinalDirection;
+use jolyne::valentine::types::BlockCoordinates;
+use jolyne::valentine::{BlockEntityDataPacket, McpePacket};
+use tracing::trace;
+
+use crate::entity::components::PlayerSession;
+use crate::registry::block::string_id;
+use crate::world::chunk::LAYER_BLOCK;
+use crate::world::ecs::events::BlockChanged;
+use crate::world::ecs::{
+    ChunkBlockEntities, ChunkData, ChunkStateFlags, ChunkViewers, WorldId, Worlds,
+    world_to_chunk_coords, world_to_local_coords,
+};
+use crate::world::{BlockEntity, BlockEntityData};
+
+/// Spawn block entities for a chunk being loaded.
+///
+/// Returns the `ChunkBlockEntities` component for the chunk entity.
+pub fn spawn_block_entities(
+    commands: &mut Commands,
+    world: WorldId,
+    block_entities: Vec<BlockEntity>,
+) -> ChunkBlockEntities {
+    let mut tracked = ChunkBlockEntities::default();
+    for block_entity in block_entities {
+        let pos = block_entity.position;
+        let entity = commands.spawn((block_entity, world)).id();
+        if let Some(old) = tracked.insert(pos, entity) {
+            commands.entity(old).despawn();
+        }
+    }
+    tracked
+}
+
+/// Observer: Create or remove block entities when their block changes.
+///
+/// State changes that keep the block entity type (rotating a chest,
+/// lighting a furnace) keep the existing block entity and its contents.
+/// The new block entity reaches viewers with the batched block update.
+///
+/// Register with: `world.add_observer(update_block_entities)`
+pub fn update_block_entities(
+    trigger: On<BlockChanged>,
+    mut commands: Commands,
+    mut chunks: Query<(&mut ChunkBlockEntities, Option<&WorldId>)>,
+    block_entities: Query<&BlockEntity>,
+) {
+    let event = trigger.event();
+    let Ok((mut tracked, world)) = chunks.get_mut(event.chunk_entity) else {
+        return;
+    };
+
+    let pos = event.block_pos;
+    let new_data = string_id(event.new_block).and_then(BlockEntityData::for_block);
+    let existing = tracked.get(pos);
+    let existing_id = existing
+        .and_then(|entity| block_entities.get(entity).ok())
+        .map(|block_entity| block_entity.data.id());
+
+    if existing_id.is_some() && existing_id == new_data.as_ref().map(BlockEntityData::id) {
+        return;
+    }
+
+    let world = world.copied().unwrap_or_default();
+    if let Some(entity) = existing {
+        tracked.remove(pos);
+        commands.entity(entity).despawn();
+        if existing_id == Some("Chest") {
+            commands.queue(move |ecs: &mut World| unpair_chest(ecs, world, pos));
+        }
+    }
+    if let Some(data) = new_data {
+        let is_chest = matches!(data, BlockEntityData::Chest(_));
+        let entity = commands.spawn((BlockEntity::new(pos, data), world)).id();
+        tracked.insert(pos, entity);
+        if is_chest {
+            let block = event.new_block;
+            commands.queue(move |ecs: &mut World| pair_chest(ecs, world, pos, block));
+        }
+    }
+}
+
+/// The two positions beside a chest, left and right of its facing.
+///
+/// Returns `None` if the runtime ID is not a chest or trapped chest.
+fn chest_sides(runtime_id: u32, pos: IVec3) -> Option<[IVec3; 2]> {
+    let block = BLOCKS.iter().find(|block| {
+        matches!(
+            block.string_id(),
+            "minecraft:chest" | "minecraft:trapped_chest"
+        ) && (block.min_state_id()..=block.max_state_id()).contains(&runtime_id)
+    })?;
+    let facing = CardinalDirection::from_raw((runtime_id - block.min_state_id()) as u8)?;
+    let step = match facing {
+        CardinalDirection::North | CardinalDirection::South => IVec3::X,
+        CardinalDirection::East | CardinalDirection::West => IVec3::Z,
+    };
+    Some([pos - step, pos + step])
+}
+
+/// Runtime ID of the block at a position, if its chunk is loaded.
+fn block_at(ecs: &World, world: WorldId, pos: IVec3) -> Option<u32> {
+    let chunk_entity = ecs.chunk_entity_at(world, pos)?;
+    let (x, y, z) = world_to_local_coords(pos.x, pos.y, pos.z);
+    Some(
+        ecs.get::<ChunkData>(chunk_entity)?
+            .inner
+            .get_block(x, y, z, LAYER_BLOCK),
+    )
+}
+
+/// Get the chest data of a block entity, if it is a chest.
+fn chest_mut(ecs: &mut World, entity: Entity) -> Option<Mut<'_, BlockEntity>> {
+    let block_entity = ecs.get_mut::<BlockEntity>(entity)?;
+    matches!(block_entity.data, BlockEntityData::Chest(_)).then_some(block_entity)
+}
+
+/// Pair a newly placed chest with an unpaired chest beside it.
+///
+/// Chests only pair with the same block facing the same way. The existing
+/// chest leads the pair, so its contents keep the first 27 slots.
+fn pair_chest(ecs: &mut World, world: WorldId, pos: IVec3, runtime_id: u32) {
+    let Some(sides) = chest_sides(runtime_id, pos) else {
+        return;
+    };
+    let Some(placed) = ecs.block_entity_at(world, pos) else {
+        return;
+    };
+
+    for side in sides {
+        if block_at(ecs, world, side) != Some(runtime_id) {
+            continue;
+        }
+        let Some(partner) = ecs.block_entity_at(world, side) else {
+            continue;
+        };
+        let Some(mut block_entity) = chest_mut(ecs, partner) else {
+            continue;
+        };
+        let BlockEntityData::Chest(chest) = &mut block_entity.data else {
+            continue;
+        };
+        if chest.pair.is_some() {
+            continue;
+        }
+        chest.pair = Some((pos.x, pos.z));
+        chest.pair_lead = true;
+
+        if let Some(mut block_entity) = chest_mut(ecs, placed)
+            && let BlockEntityData::Chest(chest) = &mut block_entity.data
+        {
+            chest.pair = Some((side.x, side.z));
+            chest.pair_lead = false;
+        }
+        ecs.sync_block_entity(partner);
+        ecs.sync_block_entity(placed);
+        return;
+    }
+}
+
+/// Unpair the chest that was paired with a removed chest.
+fn unpair_chest(ecs: &mut World, world: WorldId, pos: IVec3) {
+    for step in [IVec3::X, IVec3::Z] {
+        for side in [pos - step, pos + step] {
+            let Some(partner) = ecs.block_entity_at(world, side) else {
+                continue;
+            };
+            let Some(mut block_entity) = chest_mut(ecs, partner) else {
+                continue;
+            };
+            let BlockEntityData::Chest(chest) = &mut block_entity.data else {
+                continue;
+            };
+            if chest.pair != Some((pos.x, pos.z)) {
+                continue;
+            }
+            chest.pair = None;
+            chest.pair_lead = false;
+            ecs.sync_block_entity(partner);
+        }
+    }
+}
+
+/// Build the `BlockActorData` packet for a block entity.
+pub fn block_entity_packet(block_entity: &BlockEntity) -> McpePacket {
+    McpePacket::from(BlockEntityDataPacket {
+        position: BlockCoordinates {
+            x: block_entity.position.x,
+            y: block_entity.position.y,
+            z: block_entity.position.z,
+        },
+        nbt: Nbt(block_entity.to_network_nbt().into()),
+    })
+}
+
+/// Extension trait for block entity access on `World`.
+pub trait BlockEntityWorldExt {
+    /// Find the chunk entity containing a block position in a world, if loaded.
+    fn chunk_entity_at(&self, world: WorldId, pos: IVec3) -> Option<Entity>;
+
+    /// Get the block entity at a position.
+    fn block_entity_at(&self, world: WorldId, pos: IVec3) -> Option<Entity>;
+
+    /// Place a block entity, replacing any existing one at its position.
+    ///
+    /// Marks the chunk dirty and sends the new state to chunk viewers.
+    /// Returns `None` if the chunk is not loaded.
+    fn set_block_entity(&mut self, world: WorldId, block_entity: BlockEntity) -> Option<Entity>;
+
+    /// Remove and despawn the block entity at a position.
+    fn remove_block_entity(&mut self, world: WorldId, pos: IVec3) -> Option<BlockEntity>;
+
+    /// Mark a block entity's chunk dirty and resend it to chunk viewers.
+    ///
+    /// Call after mutating a `BlockEntity` component.
+    fn sync_block_entity(&mut self, entity: Entity);
+
+    /// Clone all block entities belonging to a chunk (for saving).
+    fn chunk_block_entities(&self, chunk_entity: Entity) -> Vec<BlockEntity>;
+}
+
+impl BlockEntityWorldExt for World {
+    fn chunk_entity_at(&self, world: WorldId, pos: IVec3) -> Option<Entity> {
+        let (cx, cz) = world_to_chunk_coords(pos.x, pos.z);
+        self.get_resource::<Worlds>()?
+            .get(world)?
+            .get_by_coords(cx, cz)
+    }
+
+    fn block_entity_at(&self, world: WorldId, pos: IVec3) -> Option<Entity> {
+        let chunk_entity = self.chunk_entity_at(world, pos)?;
+        self.get::<ChunkBlockEntities>(chunk_entity)?.get(pos)
+    }
+
+    fn set_block_entity(&mut self, world: WorldId, block_entity: BlockEntity) -> Option<Entity> {
+        let pos = block_entity.position;
+        let chunk_entity = self.chunk_entity_at(world, pos)?;
+        let entity = self.spawn((block_entity, world)).id();
+
+        let Some(mut tracked) = self.get_mut::<ChunkBlockEntities>(chunk_entity) else {
+            self.despawn(entity);
+            return None;
+        };
+        if let Some(old) = tracked.insert(pos, entity) {
+            self.despawn(old);
+        }
+
+        self.sync_block_entity(entity);
+        Some(entity)
+    }
+
+    fn remove_block_entity(&mut self, world: WorldId, pos: IVec3) -> Option<BlockEntity> {
+        let chunk_entity = self.chunk_entity_at(world, pos)?;
+        let entity = self
+            .get_mut::<ChunkBlockEntities>(chunk_entity)?
+            .remove(pos)?;
+        if let Some(mut flags) = self.get_mut::<ChunkStateFlags>(chunk_entity) {
+            flags.mark_dirty();
+        }
+
+        let block_entity = self.get::<BlockEntity>(entity).cloned();
+        self.despawn(entity);
+        block_entity
+    }
+
+    fn sync_block_entity(&mut self, entity: Entity) {
+        let Some(block_entity) = self.get::<BlockEntity>(entity) else {
+            return;
+        };
+        let pos = block_entity.position;
+        let packet = block_entity_packet(block_entity);
+        let world = self.get::<WorldId>(entity).copied().unwrap_or_default();
+
+        let Some(chunk_entity) = self.chunk_entity_at(world, pos) else {
+            return;
+        };
+        if let Some(mut flags) = self.get_mut::<ChunkStateFlags>(chunk_entity) {
+            flags.mark_dirty();
+        }
+
+        let Some(viewers) = self.get::<ChunkViewers>(chunk_entity) else {
+            return;
+        };
+        for viewer in viewers.iter() {
+            if let Some(session) = self.get::<PlayerSession>(viewer) {
+                let _ = session.send(packet.clone());
+            }
+        }
+        trace!(pos = ?pos, viewers = viewers.len(), "Synced block entity");
+    }
+
+    fn chunk_block_entities(&self, chunk_entity: Entity) -> Vec<BlockEntity> {
+        let Some(tracked) = self.get::<ChunkBlockEntities>(chunk_entity) else {
+            return Vec::new();
+        };
+        tracked
+            .iter()
+            .filter_map(|(_, entity)| self.get::<BlockEntity>(entity).cloned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::Chunk;
+    use crate::world::ecs::ChunkPosition;
+    use crate::world::ecs::events::BlockChanged;
+
+    /// Runtime ID of a chest facing the given cardinal direction.
+    fn chest(facing: CardinalDirection) -> u32 {
+        let chest = BLOCKS
+            .iter()
+            .find(|block| block.string_id() == "minecraft:chest")
+            .unwrap();
+        chest.min_state_id() + facing as u32
+    }
+
+    fn place(ecs: &mut World, chunk: Entity, pos: IVec3, block: u32) {
+        let (x, y, z) = world_to_local_coords(pos.x, pos.y, pos.z);
+        let old_block = {
+            let mut data = ecs.get_mut::<ChunkData>(chunk).unwrap();
+            let old = data.inner.get_block(x, y, z, LAYER_BLOCK);
+            data.inner.set_block(x, y, z, LAYER_BLOCK, block);
+            old
+        };
+        ecs.trigger(BlockChanged {
+            chunk_entity: chunk,
+            block_pos: pos,
+            old_block,
+            new_block: block,
+        });
+        ecs.flush();
+    }
+
+    fn pair(ecs: &World, pos: IVec3) -> Option<((i32, i32), bool)> {
+        let entity = ecs.block_entity_at(WorldId::DEFAULT, pos)?;
+        match &ecs.get::<BlockEntity>(entity)?.data {
+            BlockEntityData::Chest(chest) => chest.pair.map(|pair| (pair, chest.pair_lead)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_chests_pair_on_placement() {
+        let mut ecs = World::new();
+        ecs.add_observer(update_block_entities);
+        let chunk = ecs
+            .spawn((
+                ChunkData::new(Chunk::new(0, 0)),
+                ChunkBlockEntities::default(),
+                WorldId::DEFAULT,
+            ))
+            .id();
+        let mut worlds = Worlds::default();
+        worlds
+            .default_world_mut()
+            .insert(ChunkPosition::new(0, 0), chunk);
+        ecs.insert_resource(worlds);
+
+        let first = IVec3::new(1, 64, 1);
+        let second = IVec3::new(2, 64, 1);
+        place(&mut ecs, chunk, first, chest(CardinalDirection::North));
+        assert_eq!(pair(&ecs, first), None);
+
+        // A chest facing another way beside it stays single
+        place(
+            &mut ecs,
+            chunk,
+            first + IVec3::Z,
+            chest(CardinalDirection::East),
+        );
+        assert_eq!(pair(&ecs, first), None);
+
+        place(&mut ecs, chunk, second, chest(CardinalDirection::North));
+        assert_eq!(pair(&ecs, first), Some(((2, 1), true)));
+        assert_eq!(pair(&ecs, second), Some(((1, 1), false)));
+
+        // A third chest cannot join a double chest
+        place(
+            &mut ecs,
+            chunk,
+            IVec3::new(3, 64, 1),
+            chest(CardinalDirection::North),
+        );
+        assert_eq!(pair(&ecs, IVec3::new(3, 64, 1)), None);
+
+        // Breaking one half leaves the other single
+        place(&mut ecs, chunk, second, 0);
+        assert_eq!(pair(&ecs, first), None);
+    }
+}
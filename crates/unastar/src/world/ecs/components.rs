@@ -3,7 +3,8 @@
 use crate::world::Chunk;
 use bevy_ecs::prelude::*;
 use bitflags::bitflags;
-use std::collections::HashSet;
+use glam::IVec3;
+use std::collections::{HashMap, HashSet};
 use tokio::sync::oneshot;
 
 bitflags! {
@@ -286,12 +287,51 @@ impl ChunkEntities {
     }
 }
 
+/// Component mapping block positions in this chunk to their block entity.
+///
+/// Each block entity (chest, sign, ...) is its own ECS entity holding a
+/// [`BlockEntity`](crate::world::BlockEntity) component. They are spawned
+/// when the chunk loads and despawned when it unloads.
+#[derive(Component, Debug, Default)]
+pub struct ChunkBlockEntities {
+    entities: HashMap<IVec3, Entity>,
+}
+
+impl ChunkBlockEntities {
+    /// Insert a block entity, returning the one it replaced.
+    pub fn insert(&mut self, pos: IVec3, entity: Entity) -> Option<Entity> {
+        self.entities.insert(pos, entity)
+    }
+
+    pub fn remove(&mut self, pos: IVec3) -> Option<Entity> {
+        self.entities.remove(&pos)
+    }
+
+    pub fn get(&self, pos: IVec3) -> Option<Entity> {
+        self.entities.get(&pos).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (IVec3, Entity)> + '_ {
+        self.entities.iter().map(|(pos, entity)| (*pos, *entity))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
 // =============================================================================
 // Async Chunk Generation Resources (Phase 1 performance optimization)
 // =============================================================================
 
 /// A single pending chunk generation request.
 pub struct PendingGeneration {
+    /// World the chunk belongs to.
+    pub world: super::WorldId,
     /// Chunk X coordinate.
     pub x: i32,
     /// Chunk Z coordinate.
@@ -313,8 +353,19 @@ pub struct PendingChunkGenerations {
 
 impl PendingChunkGenerations {
     /// Add a new pending generation.
-    pub fn add(&mut self, x: i32, z: i32, receiver: oneshot::Receiver<Chunk>) {
-        self.pending.push(PendingGeneration { x, z, receiver });
+    pub fn add(
+        &mut self,
+        world: super::WorldId,
+        x: i32,
+        z: i32,
+        receiver: oneshot::Receiver<Chunk>,
+    ) {
+        self.pending.push(PendingGeneration {
+            world,
+            x,
+            z,
+            receiver,
+        });
     }
 
     /// Get the count of pending generations.
@@ -352,9 +403,9 @@ impl ChunkStateFlags {
 /// - New: `ticking_state.should_tick.clear()` → zero allocation
 #[derive(Resource, Default)]
 pub struct ChunkTickingState {
-    /// Reusable set of chunk coordinates that should be ticking.
+    /// Reusable set of world and chunk coordinates that should be ticking.
     /// Cleared at start of each tick, filled with chunks in simulation distance.
-    pub should_tick: HashSet<(i32, i32)>,
+    pub should_tick: HashSet<(super::WorldId, i32, i32)>,
 }
 
 #[cfg(test)]
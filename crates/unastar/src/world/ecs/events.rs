@@ -52,6 +52,8 @@ pub struct BlockBroadcastEvent {
     pub block_pos: IVec3,
     /// New block runtime ID.
     pub new_block: u32,
+    /// Storage layer that changed (0 = block, 1 = waterlogging liquid).
+    pub layer: usize,
 }
 
 // =============================================================================
@@ -119,6 +121,7 @@ mod tests {
             chunk_entity: Entity::PLACEHOLDER,
             block_pos: IVec3::new(5, 32, 15),
             new_block: 42,
+            layer: 0,
         };
         assert_eq!(event.block_pos.y, 32);
         assert_eq!(event.new_block, 42);
@@ -76,8 +76,12 @@ impl ChunkGenerationWorker {
             // Spawn without awaiting - allows parallel generation!
             // Each task runs on tokio's blocking thread pool.
             tokio::spawn(async move {
-                let result =
-                    tokio::task::spawn_blocking(move || gen_clone.generate_chunk(x, z)).await;
+                let result = tokio::task::spawn_blocking(move || {
+                    let mut chunk = gen_clone.generate_chunk(x, z);
+                    chunk.compute_light();
+                    chunk
+                })
+                .await;
 
                 match result {
                     Ok(chunk) => {
@@ -18,7 +18,7 @@ use bevy_ecs::prelude::*;
 use bevy_ecs::world::DeferredWorld;
 use std::collections::HashSet;
 
-use crate::world::ecs::{ChunkManager, ChunkViewers};
+use crate::world::ecs::{ChunkViewers, WorldId, Worlds};
 
 /// Per-player chunk loader component.
 ///
@@ -254,16 +254,24 @@ impl Component for ChunkLoader {
             if loaded_chunks.is_empty() {
                 return;
             }
+            let world_id = world
+                .get::<WorldId>(player_entity)
+                .copied()
+                .unwrap_or_default();
 
-            // Get chunk entities from ChunkManager
+            // Get chunk entities from the player's world ChunkManager
             let chunk_entities: Vec<bevy_ecs::entity::Entity> = {
-                let Some(chunk_manager) = world.get_resource::<ChunkManager>() else {
+                let Some(mut worlds) = world.get_resource_mut::<Worlds>() else {
                     tracing::warn!(
                         player = ?player_entity,
-                        "ChunkManager not available during ChunkLoader cleanup"
+                        "Worlds not available during ChunkLoader cleanup"
                     );
                     return;
                 };
+                let Some(chunk_manager) = worlds.get_mut(world_id) else {
+                    return;
+                };
+                chunk_manager.forget_viewer(player_entity);
 
                 loaded_chunks
                     .iter()
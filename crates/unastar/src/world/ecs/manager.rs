@@ -1,25 +1,39 @@
-//! Chunk manager resource for O(1) chunk entity lookup.
+//! Per-world chunk manager for O(1) chunk entity lookup.
 
 use bevy_ecs::prelude::*;
+use glam::DVec3;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use super::block_entities::spawn_block_entities;
 use super::components::{
-    ChunkData, ChunkEntities, ChunkPosition, ChunkState, ChunkStateFlags, ChunkViewers,
+    ChunkBlockEntities, ChunkData, ChunkEntities, ChunkPosition, ChunkState, ChunkStateFlags,
+    ChunkViewers,
 };
 use super::generation_worker::ChunkGenerationWorker;
-use crate::storage::WorldProvider;
-use crate::world::{Chunk, ChunkPos, WorldConfig, WorldGenerator};
+use super::worlds::{DEFAULT_WORLD, WorldId, Worlds};
+use crate::storage::{ChunkColumn, LevelData, WorldProvider};
+use crate::world::generator::Dimension;
+use crate::world::generator::jigsaw::TemplateManager;
+use crate::world::{Chunk, ChunkPos, Weather, WorldConfig, WorldTime};
 
-/// Global resource for chunk entity management.
+/// Spawn position of a world before [`ChunkManager::find_spawn`], matching the
+/// default world template.
+const DEFAULT_SPAWN: DVec3 = DVec3::new(0.5, 17.0, 0.5);
+
+/// Chunk entity management for one world.
 ///
 /// Provides O(1) lookup from chunk coordinates to ECS entity,
-/// and handles chunk generation when needed.
+/// and handles chunk generation when needed. Every loaded world owns one
+/// manager inside the [`Worlds`](super::Worlds) resource.
 ///
 /// NOTE: Chunk data is stored in the ECS ChunkData component, not in this manager.
 /// This manager only tracks the mapping from coordinates to entities.
-#[derive(Resource)]
 pub struct ChunkManager {
+    /// World this manager belongs to; chunk entities are tagged with it.
+    pub(super) id: WorldId,
+    /// Name of the world, also its storage directory.
+    pub(super) name: String,
     /// Map from chunk coordinates to ECS entity.
     chunks: HashMap<(i32, i32), Entity>,
     /// World configuration for generation.
@@ -34,18 +48,31 @@ pub struct ChunkManager {
     pub pending_generation: HashMap<(i32, i32), Vec<Entity>>,
     /// Async chunk generation worker (only for vanilla generation).
     generation_worker: Option<ChunkGenerationWorker>,
+    /// Where players arrive in this world, found when it is loaded.
+    spawn: DVec3,
+    /// Time of day in this world.
+    time: WorldTime,
+    /// Weather in this world; only Overworld worlds have any.
+    weather: Weather,
+    /// The world's `level.dat`, kept so keys the server doesn't model are
+    /// written back unchanged.
+    level_data: LevelData,
 }
 
 impl ChunkManager {
     /// Create a new chunk manager with the given world configuration.
     pub fn new(world_config: WorldConfig) -> Self {
-        // Pre-create VanillaGenerator if using vanilla world type (Arc for sharing)
-        let vanilla_generator = match &world_config.generator {
-            WorldGenerator::Vanilla { seed } => Some(Arc::new(
-                crate::world::generator::VanillaGenerator::new(*seed),
-            )),
-            _ => None,
-        };
+        Self::with_templates(world_config, Arc::default())
+    }
+
+    /// Create a chunk manager whose vanilla generator builds jigsaw
+    /// structures from `templates`.
+    pub fn with_templates(world_config: WorldConfig, templates: Arc<TemplateManager>) -> Self {
+        // Pre-create VanillaGenerator if using a vanilla world type (Arc for sharing)
+        let vanilla_generator = world_config
+            .generator
+            .terrain()
+            .map(|generator| Arc::new(generator.with_templates(templates)));
 
         // Create async generation worker for vanilla generation
         let generation_worker = vanilla_generator
@@ -53,6 +80,8 @@ impl ChunkManager {
             .map(|generator| ChunkGenerationWorker::spawn(generator.clone()));
 
         Self {
+            id: WorldId::DEFAULT,
+            name: DEFAULT_WORLD.to_string(),
             chunks: HashMap::new(),
             world_config,
             provider: None,
@@ -60,9 +89,23 @@ impl ChunkManager {
             pending_viewers: HashMap::new(),
             pending_generation: HashMap::new(),
             generation_worker,
+            spawn: DEFAULT_SPAWN,
+            time: WorldTime::default(),
+            weather: Weather::default(),
+            level_data: LevelData::default(),
         }
     }
 
+    /// Id of the world this manager belongs to.
+    pub fn id(&self) -> WorldId {
+        self.id
+    }
+
+    /// Name of the world this manager belongs to.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Set the world provider for chunk loading.
     pub fn set_provider(&mut self, provider: Arc<dyn WorldProvider>) {
         self.provider = Some(provider);
@@ -178,7 +221,9 @@ impl ChunkManager {
                     chunk.fill_subchunk_solid(4, *STONE);
                 }
             }
-            WorldGenerator::Vanilla { .. } => {
+            WorldGenerator::Vanilla { .. }
+            | WorldGenerator::Nether { .. }
+            | WorldGenerator::End { .. } => {
                 // Use cached VanillaGenerator for terrain generation
                 if let Some(ref genr) = self.vanilla_generator {
                     chunk = genr.generate_chunk(x, z);
@@ -188,16 +233,18 @@ impl ChunkManager {
             }
         }
 
+        chunk.compute_light();
         chunk
     }
 
     /// Load or generate a chunk at the given position.
     ///
-    /// Returns (chunk, was_loaded) where was_loaded indicates if the chunk
-    /// was loaded from disk (true) or newly generated (false).
+    /// Returns (column, was_loaded) where was_loaded indicates if the chunk
+    /// was loaded from disk (true) or newly generated (false). Generated
+    /// columns have no block entities.
     ///
     /// Newly generated chunks have the DIRTY flag set in ChunkStateFlags for persistence.
-    pub fn load_or_generate_chunk(&self, x: i32, z: i32) -> (Chunk, bool) {
+    pub fn load_or_generate_chunk(&self, x: i32, z: i32) -> (ChunkColumn, bool) {
         let pos = ChunkPos::new(x, z);
         let dim = self.world_config.dimension;
 
@@ -213,13 +260,13 @@ impl ChunkManager {
 
                 if let Ok(Ok(Some(column))) = result {
                     // Loaded successfully
-                    return (column.chunk, true);
+                    return (column, true);
                 }
             }
         }
 
         // Fall through to generation
-        (self.generate_chunk(x, z), false)
+        (ChunkColumn::new(self.generate_chunk(x, z)), false)
     }
 
     /// Get or create a chunk entity using deferred Commands.
@@ -248,7 +295,8 @@ impl ChunkManager {
         }
 
         // Chunk doesn't exist - try to load from disk, otherwise generate
-        let (chunk_data, was_loaded) = self.load_or_generate_chunk(x, z);
+        let (column, was_loaded) = self.load_or_generate_chunk(x, z);
+        let chunk_data = column.chunk;
         let pos = ChunkPosition::new(x, z);
 
         // Encode BEFORE spawning to avoid clone (Phase 3 optimization)
@@ -263,13 +311,16 @@ impl ChunkManager {
             state_flags.mark_dirty();
         }
 
+        let block_entities = spawn_block_entities(commands, self.id, column.block_entities);
         let entity = commands
             .spawn((
                 pos,
+                self.id,
                 ChunkData::new(chunk_data), // Move, not clone!
                 ChunkState::Loaded,
                 ChunkViewers::default(),
                 ChunkEntities::default(),
+                block_entities,
                 state_flags,
             ))
             .id();
@@ -346,35 +397,106 @@ impl ChunkManager {
     pub fn pending_generation_count(&self) -> usize {
         self.pending_generation.len()
     }
+
+    /// Drop a viewer from the pending viewer and generation lists, e.g. when
+    /// the player leaves this world before its chunks arrive.
+    pub fn forget_viewer(&mut self, viewer: Entity) {
+        for viewers in self
+            .pending_viewers
+            .values_mut()
+            .chain(self.pending_generation.values_mut())
+        {
+            viewers.retain(|&v| v != viewer);
+        }
+    }
+
+    /// Where players arrive in this world when no position is given.
+    ///
+    /// The default world places players through the spawn rules instead.
+    pub fn spawn_position(&self) -> DVec3 {
+        self.spawn
+    }
+
+    /// Time of day in this world.
+    pub fn time(&self) -> WorldTime {
+        self.time
+    }
+
+    pub fn time_mut(&mut self) -> &mut WorldTime {
+        &mut self.time
+    }
+
+    /// Weather in this world.
+    pub fn weather(&self) -> Weather {
+        self.weather
+    }
+
+    pub fn weather_mut(&mut self) -> &mut Weather {
+        &mut self.weather
+    }
+
+    /// Whether this world has weather. The Nether and the End don't.
+    pub fn has_weather(&self) -> bool {
+        self.world_config.dimension == 0
+    }
+
+    /// Restore time and weather from the world's `level.dat`.
+    pub fn set_level_data(&mut self, data: LevelData) {
+        self.time = data.time;
+        self.weather = data.weather;
+        self.level_data = data;
+    }
+
+    /// The world's `level.dat` with the current time and weather.
+    pub fn level_data(&self) -> LevelData {
+        let mut data = self.level_data.clone();
+        data.time = self.time;
+        data.weather = self.weather;
+        data
+    }
+
+    /// Find the spawn position: the surface of the centre of chunk (0, 0),
+    /// loading or generating that chunk.
+    ///
+    /// Nether and End worlds ask the generator for a floor instead, since
+    /// the top of the Nether is the bedrock ceiling.
+    pub(super) fn find_spawn(&mut self) {
+        if let Some(generator) = self
+            .vanilla_generator
+            .as_ref()
+            .filter(|generator| generator.dimension() != Dimension::Overworld)
+        {
+            let (x, y, z) = generator.find_safe_spawn();
+            self.spawn = DVec3::new(x as f64 + 0.5, y as f64, z as f64 + 0.5);
+            return;
+        }
+
+        let (column, _) = self.load_or_generate_chunk(0, 0);
+        let height = column.chunk.height_map().at(0, 0);
+        // Nothing solid in the column (void worlds): keep the default height
+        if height > crate::world::chunk::MIN_Y as i16 {
+            self.spawn = DVec3::new(DEFAULT_SPAWN.x, height as f64, DEFAULT_SPAWN.z);
+        }
+    }
 }
 
 /// Extension trait for spawning chunk entities directly on World.
 /// This is needed for synchronous chunk creation outside of ECS systems.
 pub trait ChunkManagerWorldExt {
-    /// Get or create a chunk entity synchronously.
+    /// Get or create a chunk entity synchronously in a loaded world.
     /// Unlike get_or_create(), this spawns immediately without deferral.
-    fn get_or_create_chunk(&mut self, x: i32, z: i32) -> Entity;
+    fn get_or_create_chunk(&mut self, world: WorldId, x: i32, z: i32) -> Option<Entity>;
 }
 
 impl ChunkManagerWorldExt for bevy_ecs::world::World {
-    fn get_or_create_chunk(&mut self, x: i32, z: i32) -> Entity {
-        // Check if entity already exists
-        {
-            let chunk_manager = self
-                .get_resource::<ChunkManager>()
-                .expect("ChunkManager must exist");
+    fn get_or_create_chunk(&mut self, world: WorldId, x: i32, z: i32) -> Option<Entity> {
+        // Check if entity already exists, otherwise generate chunk data
+        let (chunk_data, pos) = {
+            let chunk_manager = self.get_resource::<Worlds>()?.get(world)?;
             if let Some(entity) = chunk_manager.get_by_coords(x, z) {
-                return entity;
+                return Some(entity);
             }
-        }
-
-        // Generate chunk data and spawn entity
-        let (chunk_data, pos) = {
-            let chunk_manager = self
-                .get_resource::<ChunkManager>()
-                .expect("ChunkManager must exist");
-            let chunk = chunk_manager.generate_chunk(x, z);
-            (chunk, ChunkPosition::new(x, z))
+            (chunk_manager.generate_chunk(x, z), ChunkPosition::new(x, z))
         };
 
         // Spawn entity with components
@@ -385,21 +507,22 @@ impl ChunkManagerWorldExt for bevy_ecs::world::World {
         let entity = self
             .spawn((
                 pos,
+                world,
                 ChunkData::new(chunk_data),
                 ChunkState::Loaded,
                 ChunkViewers::default(),
                 ChunkEntities::default(),
+                ChunkBlockEntities::default(),
                 state_flags,
             ))
             .id();
 
-        // Register in ChunkManager
-        let mut chunk_manager = self
-            .get_resource_mut::<ChunkManager>()
-            .expect("ChunkManager must exist");
-        chunk_manager.insert(pos, entity);
+        // Register in the world's ChunkManager
+        self.resource_mut::<Worlds>()
+            .get_mut(world)?
+            .insert(pos, entity);
 
-        entity
+        Some(entity)
     }
 }
 
@@ -1,8 +1,11 @@
 //! Chunk ECS components, resources, and systems.
 //!
+//! Chunks belong to one of several named worlds; the `Worlds` resource owns a
+//! `ChunkManager` per loaded world and entities carry a `WorldId`.
 //! Chunks are ECS entities with components for global state (loaded, dirty, ticking).
 //! - `ChunkViewers` tracks player sessions with this chunk in their view radius.
 //! - `ChunkEntities` tracks non-player entities physically inside the chunk.
+//! - `ChunkBlockEntities` tracks block entities (chests, signs, ...) spawned with the chunk.
 //! - Per-player sent chunks are tracked via `ChunkLoader` on player entities.
 //!
 //! ## Systems
@@ -24,16 +27,22 @@
 //! ChunkLoader has an on_remove hook that automatically removes the player from
 //! all ChunkViewers when the entity is despawned. No explicit cleanup system needed.
 
+pub mod block_entities;
 pub mod components;
 pub mod events;
 pub mod generation_worker;
 pub mod loader;
 pub mod manager;
 pub mod systems;
+pub mod worlds;
 
+pub use block_entities::{
+    BlockEntityWorldExt, block_entity_packet, spawn_block_entities, update_block_entities,
+};
 pub use components::{
-    ChunkData, ChunkEntities, ChunkFlags, ChunkPendingUnload, ChunkPosition, ChunkState,
-    ChunkStateFlags, ChunkTickingState, ChunkViewers, PendingChunkGenerations, PendingGeneration,
+    ChunkBlockEntities, ChunkData, ChunkEntities, ChunkFlags, ChunkPendingUnload, ChunkPosition,
+    ChunkState, ChunkStateFlags, ChunkTickingState, ChunkViewers, PendingChunkGenerations,
+    PendingGeneration,
 };
 pub use events::{BlockBroadcastEvent, BlockChanged, PlayerDespawnedEvent, PlayerSpawnedEvent};
 pub use loader::ChunkLoader;
@@ -42,3 +51,4 @@ pub use systems::{
     ChunkLoadConfig, LastPublisherState, broadcast_block_update, on_block_changed,
     register_chunk_systems, world_to_chunk_coords, world_to_local_coords,
 };
+pub use worlds::{DEFAULT_WORLD, WorldError, WorldId, Worlds};
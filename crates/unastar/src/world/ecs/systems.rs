@@ -12,14 +12,18 @@
 //! - Player cleanup uses ChunkLoader's known set, not full world scan
 //! - Publisher updates only sent on position/radius change, not per-chunk
 
+use bevy_ecs::entity::EntityHashSet;
 use bevy_ecs::prelude::*;
 use tracing::{debug, trace, warn};
 
 use crate::entity::components::{ChunkRadius, Player, PlayerSession, Position};
+use crate::world::BlockEntity;
 use crate::world::ecs::{
-    ChunkData, ChunkEntities, ChunkLoader, ChunkManager, ChunkPendingUnload, ChunkPosition,
-    ChunkState, ChunkStateFlags, ChunkTickingState, ChunkViewers, PendingChunkGenerations,
+    ChunkBlockEntities, ChunkData, ChunkEntities, ChunkLoader, ChunkPendingUnload, ChunkPosition,
+    ChunkState, ChunkStateFlags, ChunkTickingState, ChunkViewers, PendingChunkGenerations, WorldId,
+    Worlds,
 };
+use crate::world::light::LightRegion;
 use jolyne::valentine::types::{BlockCoordinates, UpdateBlockFlags};
 use jolyne::valentine::{
     LevelChunkPacket, McpePacket, NetworkChunkPublisherUpdatePacket, UpdateBlockPacket,
@@ -30,8 +34,6 @@ use jolyne::valentine::{
 pub struct ChunkLoadConfig {
     /// Maximum chunks to send per player per tick.
     pub chunks_per_tick: usize,
-    /// Dimension ID for chunk packets.
-    pub dimension: i32,
     /// Simulation distance in chunks.
     pub simulation_distance: i32,
     /// Grace period ticks before unloading chunks with no viewers.
@@ -42,7 +44,6 @@ impl Default for ChunkLoadConfig {
     fn default() -> Self {
         Self {
             chunks_per_tick: 16, // Increased from 8 after generator optimization
-            dimension: 0,
             simulation_distance: 6,
             unload_grace_ticks: 100,
         }
@@ -54,7 +55,6 @@ impl ChunkLoadConfig {
     pub fn from_server_config(config: &crate::server::ServerConfig) -> Self {
         Self {
             chunks_per_tick: 8, // Low value to avoid slow ticks during terrain generation
-            dimension: config.world.dimension,
             simulation_distance: config.simulation_distance,
             unload_grace_ticks: config.chunk_unload_ticks,
         }
@@ -88,11 +88,12 @@ pub struct LastPublisherState {
 /// System: Update ChunkLoaders when players move.
 /// Evicts old chunks and queues new ones.
 pub fn update_chunk_loaders(
-    chunk_manager: Res<ChunkManager>,
+    worlds: Res<Worlds>,
     mut players: Query<
         (
             Entity,
             &Position,
+            &WorldId,
             &ChunkRadius,
             &mut ChunkLoader,
             &mut LastPublisherState,
@@ -101,7 +102,12 @@ pub fn update_chunk_loaders(
     >,
     mut chunks: Query<&mut ChunkViewers>,
 ) {
-    for (player_entity, position, radius, mut loader, mut publisher_state) in players.iter_mut() {
+    for (player_entity, position, world_id, radius, mut loader, mut publisher_state) in
+        players.iter_mut()
+    {
+        let Some(chunk_manager) = worlds.get(*world_id) else {
+            continue;
+        };
         let chunk_x = (position.0.x / 16.0).floor() as i32;
         let chunk_z = (position.0.z / 16.0).floor() as i32;
         let current_pos = loader.position();
@@ -171,11 +177,12 @@ pub fn update_chunk_loaders(
 pub fn process_chunk_load_queues(
     config: Res<ChunkLoadConfig>,
     mut commands: Commands,
-    mut chunk_manager: ResMut<ChunkManager>,
+    mut worlds: ResMut<Worlds>,
     mut players: Query<
         (
             Entity,
             &Position,
+            &WorldId,
             &PlayerSession,
             &mut ChunkLoader,
             &mut LastPublisherState,
@@ -184,7 +191,12 @@ pub fn process_chunk_load_queues(
     >,
     mut chunks: Query<(&mut ChunkViewers, &ChunkData)>,
 ) {
-    for (player_entity, position, session, mut loader, mut publisher_state) in players.iter_mut() {
+    for (player_entity, position, world_id, session, mut loader, mut publisher_state) in
+        players.iter_mut()
+    {
+        let Some(chunk_manager) = worlds.get_mut(*world_id) else {
+            continue;
+        };
         let sent_this_tick = loader.sent_this_tick;
         loader.sent_this_tick = 0; // Reset for this tick
 
@@ -301,7 +313,7 @@ pub fn process_chunk_load_queues(
             let send_result = session.send(McpePacket::from(LevelChunkPacket {
                 x: cx,
                 z: cz,
-                dimension: config.dimension,
+                dimension: chunk_manager.dimension(),
                 sub_chunk_count: crate::world::request_mode::LIMITED,
                 highest_subchunk_count: Some(highest_subchunk),
                 blobs: None,
@@ -371,21 +383,23 @@ pub fn process_chunk_load_queues(
 ///
 /// Non-blocking: generation happens in background, this just queues requests.
 pub fn request_chunk_generation(
-    mut chunk_manager: ResMut<ChunkManager>,
+    mut worlds: ResMut<Worlds>,
     mut pending_gens: ResMut<PendingChunkGenerations>,
     config: Res<ChunkLoadConfig>,
-    mut players: Query<(Entity, &PlayerSession, &mut ChunkLoader), With<Player>>,
+    mut players: Query<(Entity, &WorldId, &PlayerSession, &mut ChunkLoader), With<Player>>,
     chunks: Query<&ChunkData>,
 ) {
-    // Skip if no async generation available (non-vanilla worlds)
-    if !chunk_manager.has_async_generation() {
-        return;
-    }
-
     // Limit total pending generations to prevent memory growth
     const MAX_PENDING: usize = 64;
 
-    for (player_entity, session, mut loader) in players.iter_mut() {
+    for (player_entity, world_id, session, mut loader) in players.iter_mut() {
+        // Skip if no async generation available (non-vanilla worlds)
+        let Some(chunk_manager) = worlds
+            .get_mut(*world_id)
+            .filter(|manager| manager.has_async_generation())
+        else {
+            continue;
+        };
         let mut processed = 0;
 
         while processed < config.chunks_per_tick {
@@ -411,7 +425,7 @@ pub fn request_chunk_generation(
                     let packet = LevelChunkPacket {
                         x: cx,
                         z: cz,
-                        dimension: config.dimension,
+                        dimension: chunk_manager.dimension(),
                         sub_chunk_count: crate::world::request_mode::LIMITED,
                         highest_subchunk_count: Some(highest_subchunk),
                         blobs: None,
@@ -443,7 +457,7 @@ pub fn request_chunk_generation(
 
             // Request async generation
             if let Some(receiver) = chunk_manager.request_generation(cx, cz, player_entity) {
-                pending_gens.add(cx, cz, receiver);
+                pending_gens.add(*world_id, cx, cz, receiver);
                 loader.mark_loaded(cx, cz); // Mark as loaded to prevent re-requesting
                 processed += 1;
                 debug!(player = ?player_entity, chunk = ?(cx, cz), "Requested async chunk generation.");
@@ -455,11 +469,9 @@ pub fn request_chunk_generation(
 /// System: Process completed async chunk generations.
 pub fn process_completed_generations(
     mut commands: Commands,
-    mut chunk_manager: ResMut<ChunkManager>,
+    mut worlds: ResMut<Worlds>,
     mut pending_gens: ResMut<PendingChunkGenerations>,
-    config: Res<ChunkLoadConfig>,
     sessions: Query<&PlayerSession>,
-    mut loaders: Query<&mut ChunkLoader>,
 ) {
     if pending_gens.is_empty() {
         return;
@@ -472,13 +484,16 @@ pub fn process_completed_generations(
     pending_gens.pending.retain_mut(|pending| {
         match pending.receiver.try_recv() {
             Ok(chunk) => {
-                completed.push((pending.x, pending.z, chunk));
+                completed.push((pending.world, pending.x, pending.z, chunk));
                 false // Remove from pending
             }
             Err(tokio::sync::oneshot::error::TryRecvError::Empty) => true, // Keep
             Err(tokio::sync::oneshot::error::TryRecvError::Closed) => {
-                warn!(chunk = ?(pending.x, pending.z), "Generation channel closed unexpectedly");
-                let _ = chunk_manager.complete_generation(pending.x, pending.z);
+                // Unloading a world drops its generation worker
+                if let Some(chunk_manager) = worlds.get_mut(pending.world) {
+                    warn!(chunk = ?(pending.x, pending.z), "Generation channel closed unexpectedly");
+                    let _ = chunk_manager.complete_generation(pending.x, pending.z);
+                }
                 false // Remove from pending
             }
         }
@@ -489,7 +504,12 @@ pub fn process_completed_generations(
     }
 
     // Process completed chunks
-    for (x, z, chunk) in completed {
+    for (world_id, x, z, chunk) in completed {
+        // The world may have been unloaded while the chunk was generating
+        let Some(chunk_manager) = worlds.get_mut(world_id) else {
+            continue;
+        };
+
         // Get viewers that were waiting for this chunk
         let viewers = chunk_manager.complete_generation(x, z).unwrap_or_default();
 
@@ -499,14 +519,18 @@ pub fn process_completed_generations(
 
         // Spawn chunk entity
         let pos = ChunkPosition::new(x, z);
-        let entity = commands.spawn((
-            pos,
-            ChunkData::new(chunk), // Move, not clone
-            ChunkState::Loaded,
-            ChunkViewers::default(),
-            ChunkEntities::default(),
-            ChunkStateFlags::new_generated(),
-        )).id();
+        let entity = commands
+            .spawn((
+                pos,
+                world_id,
+                ChunkData::new(chunk), // Move, not clone
+                ChunkState::Loaded,
+                ChunkViewers::default(),
+                ChunkEntities::default(),
+                ChunkBlockEntities::default(),
+                ChunkStateFlags::new_generated(),
+            ))
+            .id();
 
         chunk_manager.insert(pos, entity);
 
@@ -518,7 +542,7 @@ pub fn process_completed_generations(
                 let packet = LevelChunkPacket {
                     x,
                     z,
-                    dimension: config.dimension,
+                    dimension: chunk_manager.dimension(),
                     sub_chunk_count: crate::world::request_mode::LIMITED,
                     highest_subchunk_count: Some(highest_subchunk),
                     blobs: None,
@@ -611,19 +635,33 @@ pub fn cancel_chunk_unloads(
 /// Ticks down grace period, saves modified chunks, and despawns when expired.
 pub fn process_chunk_unloads(
     mut commands: Commands,
-    mut chunk_manager: ResMut<ChunkManager>,
+    mut worlds: ResMut<Worlds>,
     mut chunks: Query<(
         Entity,
         &ChunkPosition,
+        &WorldId,
         &mut ChunkPendingUnload,
         Option<&ChunkEntities>,
+        Option<&ChunkBlockEntities>,
         Option<&ChunkData>,
         Option<&ChunkStateFlags>,
     )>,
+    block_entities: Query<&BlockEntity>,
 ) {
-    for (entity, pos, mut pending, chunk_entities, chunk_data, state_flags) in chunks.iter_mut() {
+    for (
+        entity,
+        pos,
+        world_id,
+        mut pending,
+        chunk_entities,
+        chunk_block_entities,
+        chunk_data,
+        state_flags,
+    ) in chunks.iter_mut()
+    {
         if pending.tick() {
             // Grace period expired - unload the chunk
+            let chunk_manager = worlds.get_mut(*world_id);
 
             // First, freeze any non-player entities in the chunk
             if let Some(entities) = chunk_entities {
@@ -640,10 +678,19 @@ pub fn process_chunk_unloads(
             let is_dirty = state_flags.map(|f| f.is_dirty()).unwrap_or(false);
             if is_dirty {
                 if let Some(chunk_data) = chunk_data {
-                    if let Some(provider) = chunk_manager.provider() {
+                    if let Some((provider, dim)) = chunk_manager
+                        .as_ref()
+                        .and_then(|manager| Some((manager.provider()?, manager.dimension())))
+                    {
                         let chunk_pos = crate::world::ChunkPos::new(pos.x, pos.z);
-                        let dim = chunk_manager.dimension();
-                        let column = crate::storage::ChunkColumn::new(chunk_data.inner.clone());
+                        let column = crate::storage::ChunkColumn::new(chunk_data.inner.clone())
+                            .with_block_entities(
+                                chunk_block_entities
+                                    .into_iter()
+                                    .flat_map(|tracked| tracked.iter())
+                                    .filter_map(|(_, e)| block_entities.get(e).ok().cloned())
+                                    .collect(),
+                            );
 
                         // Block on async save (same pattern as load_or_generate_chunk)
                         if let Ok(handle) = tokio::runtime::Handle::try_current() {
@@ -675,8 +722,17 @@ pub fn process_chunk_unloads(
                 }
             }
 
+            // Block entities live only as long as their chunk
+            if let Some(tracked) = chunk_block_entities {
+                for (_, block_entity) in tracked.iter() {
+                    commands.entity(block_entity).despawn();
+                }
+            }
+
             // Remove from chunk manager
-            chunk_manager.remove_by_coords(pos.x, pos.z);
+            if let Some(chunk_manager) = chunk_manager {
+                chunk_manager.remove_by_coords(pos.x, pos.z);
+            }
 
             // Despawn the chunk entity
             commands.entity(entity).despawn();
@@ -688,34 +744,33 @@ pub fn process_chunk_unloads(
 
 /// System: Flush pending viewers from ChunkManager to ECS components.
 /// Ensures that even if a chunk was just spawned, its viewers are eventually synchronized.
-pub fn flush_pending_viewers(
-    mut chunk_manager: ResMut<ChunkManager>,
-    mut chunks: Query<&mut ChunkViewers>,
-) {
-    if chunk_manager.is_empty() {
-        return;
-    }
+pub fn flush_pending_viewers(mut worlds: ResMut<Worlds>, mut chunks: Query<&mut ChunkViewers>) {
+    for (_, chunk_manager) in worlds.iter_mut() {
+        if chunk_manager.is_empty() {
+            continue;
+        }
 
-    // We use a temporary vector to avoid borrowing issues while draining
-    let mut still_pending = std::collections::HashMap::new();
+        // We use a temporary vector to avoid borrowing issues while draining
+        let mut still_pending = std::collections::HashMap::new();
 
-    // Drain pending viewers and try to apply them to chunk entities
-    let pending_items: Vec<_> = chunk_manager.pending_viewers.drain().collect();
+        // Drain pending viewers and try to apply them to chunk entities
+        let pending_items: Vec<_> = chunk_manager.pending_viewers.drain().collect();
 
-    for (pos, viewers) in pending_items {
-        if let Some(chunk_entity) = chunk_manager.get_by_coords(pos.0, pos.1) {
-            if let Ok(mut viewers_comp) = chunks.get_mut(chunk_entity) {
-                for viewer in viewers {
-                    viewers_comp.insert(viewer);
+        for (pos, viewers) in pending_items {
+            if let Some(chunk_entity) = chunk_manager.get_by_coords(pos.0, pos.1) {
+                if let Ok(mut viewers_comp) = chunks.get_mut(chunk_entity) {
+                    for viewer in viewers {
+                        viewers_comp.insert(viewer);
+                    }
+                } else {
+                    // Entity exists in manager but component not ready yet (likely spawned this tick)
+                    still_pending.insert(pos, viewers);
                 }
-            } else {
-                // Entity exists in manager but component not ready yet (likely spawned this tick)
-                still_pending.insert(pos, viewers);
             }
         }
-    }
 
-    chunk_manager.pending_viewers = still_pending;
+        chunk_manager.pending_viewers = still_pending;
+    }
 }
 
 /// System: Update ChunkStateFlags::TICKING based on simulation distance.
@@ -726,14 +781,14 @@ pub fn flush_pending_viewers(
 pub fn update_chunk_ticking(
     config: Res<ChunkLoadConfig>,
     mut ticking_state: ResMut<ChunkTickingState>,
-    players: Query<&Position, With<Player>>,
-    mut chunks: Query<(&ChunkPosition, &mut ChunkStateFlags)>,
+    players: Query<(&Position, &WorldId), With<Player>>,
+    mut chunks: Query<(&ChunkPosition, &WorldId, &mut ChunkStateFlags)>,
 ) {
     let sim_dist = config.simulation_distance;
 
     // Fast path: no players = clear all ticking flags
     if players.is_empty() {
-        for (_, mut state) in chunks.iter_mut() {
+        for (_, _, mut state) in chunks.iter_mut() {
             if state.is_ticking() {
                 state.set_ticking(false);
             }
@@ -745,21 +800,21 @@ pub fn update_chunk_ticking(
     // O(Players × SimDist²) - e.g., 50 players × 6² = 1,800 entries max
     ticking_state.should_tick.clear();
 
-    for pos in players.iter() {
+    for (pos, &world) in players.iter() {
         let cx = (pos.0.x / 16.0).floor() as i32;
         let cz = (pos.0.z / 16.0).floor() as i32;
 
         for x in (cx - sim_dist)..=(cx + sim_dist) {
             for z in (cz - sim_dist)..=(cz + sim_dist) {
-                ticking_state.should_tick.insert((x, z));
+                ticking_state.should_tick.insert((world, x, z));
             }
         }
     }
 
     // Update chunk flags - no archetype changes!
     // Simply mutate the bitflag instead of inserting/removing components
-    for (pos, mut state) in chunks.iter_mut() {
-        let is_in_range = ticking_state.should_tick.contains(&pos.as_tuple());
+    for (pos, &world, mut state) in chunks.iter_mut() {
+        let is_in_range = ticking_state.should_tick.contains(&(world, pos.x, pos.z));
         if state.is_ticking() != is_in_range {
             state.set_ticking(is_in_range);
 
@@ -861,19 +916,34 @@ use super::events::BlockChanged;
 /// This observer fires synchronously within the same tick as the block change,
 /// enabling immediate game logic reactions:
 /// - Mark chunk dirty for persistence
+/// - Update sky and block light around the changed block, including the
+///   loaded neighbour chunks the light reaches into
 /// - TODO: Check neighbor blocks for physics (sand falling, etc.)
-/// - TODO: Update lighting
 /// - TODO: Trigger redstone updates
 ///
 /// Register with: `world.add_observer(on_block_changed)`
-pub fn on_block_changed(trigger: On<BlockChanged>, mut chunks: Query<&mut ChunkStateFlags>) {
+pub fn on_block_changed(
+    trigger: On<BlockChanged>,
+    worlds: Res<Worlds>,
+    chunk_worlds: Query<&WorldId>,
+    mut chunks: LitChunks,
+) {
     let event = trigger.event();
 
-    // Mark chunk dirty for persistence
-    if let Ok(mut state) = chunks.get_mut(event.chunk_entity) {
+    if let Ok((_, _, mut state)) = chunks.get_mut(event.chunk_entity) {
+        // Mark chunk dirty for persistence
         state.mark_dirty();
     }
 
+    if let Ok(&world) = chunk_worlds.get(event.chunk_entity) {
+        let pos = event.block_pos;
+        let (chunk_x, chunk_z) = world_to_chunk_coords(pos.x, pos.z);
+        let entities = light_neighbourhood(&worlds, world, chunk_x, chunk_z);
+        relight_region(&mut chunks, entities, chunk_x, chunk_z, |region| {
+            region.update(pos.x, pos.y, pos.z)
+        });
+    }
+
     trace!(
         chunk = ?event.chunk_entity,
         pos = ?event.block_pos,
@@ -883,10 +953,84 @@ pub fn on_block_changed(trigger: On<BlockChanged>, mut chunks: Query<&mut ChunkS
     );
 
     // TODO: Check neighbor blocks for physics (sand falling, etc.)
-    // TODO: Update lighting
     // TODO: Trigger redstone updates
 }
 
+// ============================================================================
+// Cross-chunk Lighting
+// ============================================================================
+
+/// Chunks whose light can be updated, with their dirty flags.
+type LitChunks<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static ChunkPosition,
+        &'static mut ChunkData,
+        &'static mut ChunkStateFlags,
+    ),
+>;
+
+/// System: Spread light across the borders of newly loaded chunks.
+///
+/// Chunks are lit on their own when generated or loaded; once the entity
+/// exists, light from the loaded neighbours flows in and the chunk's own
+/// light flows out to them.
+pub fn stitch_chunk_light(
+    worlds: Res<Worlds>,
+    added: Query<(&ChunkPosition, &WorldId), Added<ChunkPosition>>,
+    mut chunks: LitChunks,
+) {
+    for (pos, &world) in &added {
+        let entities = light_neighbourhood(&worlds, world, pos.x, pos.z);
+        relight_region(&mut chunks, entities, pos.x, pos.z, |region| {
+            region.stitch()
+        });
+    }
+}
+
+/// Chunk entities of a chunk and its loaded neighbours.
+fn light_neighbourhood(worlds: &Worlds, world: WorldId, x: i32, z: i32) -> EntityHashSet {
+    let Some(chunk_manager) = worlds.get(world) else {
+        return EntityHashSet::default();
+    };
+    (-1..=1)
+        .flat_map(|dz| (-1..=1).map(move |dx| (x + dx, z + dz)))
+        .filter_map(|(x, z)| chunk_manager.get_by_coords(x, z))
+        .collect()
+}
+
+/// Run a light update over the region around a chunk and mark every chunk
+/// whose light changed dirty.
+fn relight_region(
+    chunks: &mut LitChunks,
+    entities: EntityHashSet,
+    centre_x: i32,
+    centre_z: i32,
+    update: impl for<'a> FnOnce(&mut LightRegion<'a>),
+) {
+    let mut items: Vec<_> = chunks.iter_many_unique_mut(entities).collect();
+    let positions: Vec<ChunkPosition> = items.iter().map(|(pos, _, _)| **pos).collect();
+
+    let changed: Vec<bool> = {
+        let mut region = LightRegion::new(centre_x, centre_z);
+        for (_, data, _) in items.iter_mut() {
+            region.insert(&mut data.inner);
+        }
+        update(&mut region);
+        positions
+            .iter()
+            .map(|pos| region.changed(pos.x, pos.z))
+            .collect()
+    };
+
+    for ((_, _, state), changed) in items.iter_mut().zip(changed) {
+        if changed {
+            state.mark_dirty();
+        }
+    }
+}
+
 /// Plugin-like function to add all chunk systems to a schedule.
 /// Call this during ECS setup.
 ///
@@ -915,6 +1059,7 @@ pub fn register_chunk_systems(schedule: &mut bevy_ecs::schedule::Schedule) {
             request_chunk_generation,      // NEW: Non-blocking async request
             process_completed_generations, // NEW: Process async results
             process_chunk_load_queues,     // Handles sync cases + existing chunks
+            stitch_chunk_light,
             handle_radius_changes,
             schedule_chunk_unloads,
             cancel_chunk_unloads,
@@ -934,7 +1079,6 @@ mod tests {
     fn test_chunk_load_config_default() {
         let config = ChunkLoadConfig::default();
         assert_eq!(config.chunks_per_tick, 16); // Increased from 8 after generator optimization
-        assert_eq!(config.dimension, 0);
         assert_eq!(config.simulation_distance, 6);
         assert_eq!(config.unload_grace_ticks, 100);
     }
@@ -0,0 +1,356 @@
+//! Named worlds hosted by one server.
+//!
+//! Each world has its own generator, storage provider and set of chunk
+//! entities, managed by a [`ChunkManager`]. Chunks, players, mobs and dropped
+//! items carry a [`WorldId`] component saying which world they are in.
+//!
+//! The `default` world is always loaded; players join it unless their saved
+//! data names another loaded world. Other worlds come from `[[worlds]]`
+//! entries in the config or are created at runtime, and can be loaded and
+//! unloaded while the server runs.
+
+use bevy_ecs::prelude::*;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::manager::ChunkManager;
+use crate::world::WorldConfig;
+use crate::world::generator::jigsaw::TemplateManager;
+
+/// Name of the world every server has and players join by default.
+pub const DEFAULT_WORLD: &str = "default";
+
+/// File next to a world's database holding its generator and storage
+/// settings, so runtime-created worlds can be loaded again after a restart.
+pub const WORLD_CONFIG_FILE: &str = "world.toml";
+
+/// Which world an entity is in.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct WorldId(pub u32);
+
+impl WorldId {
+    /// The `default` world.
+    pub const DEFAULT: Self = Self(0);
+
+    pub fn is_default(self) -> bool {
+        self == Self::DEFAULT
+    }
+
+    /// World an entity is in. Entities without a `WorldId`, such as
+    /// projectiles, are in the default world.
+    pub fn of(world: &World, entity: Entity) -> Self {
+        world.get::<Self>(entity).copied().unwrap_or_default()
+    }
+}
+
+/// Errors creating, loading or unloading worlds.
+#[derive(Debug, thiserror::Error)]
+pub enum WorldError {
+    #[error("World {0} already exists")]
+    AlreadyExists(String),
+
+    #[error("World {0} is already loaded")]
+    AlreadyLoaded(String),
+
+    #[error("No world named {0}")]
+    NotFound(String),
+
+    #[error("World {0} is not loaded")]
+    NotLoaded(String),
+
+    #[error("The default world cannot be unloaded")]
+    DefaultWorld,
+
+    #[error("Invalid world name {0:?}: use letters, digits, '_' and '-'")]
+    InvalidName(String),
+
+    #[error("Storage error for world {name}: {message}")]
+    Storage { name: String, message: String },
+}
+
+/// Every world the server knows about, and the chunk managers of the loaded
+/// ones.
+#[derive(Resource)]
+pub struct Worlds {
+    loaded: BTreeMap<WorldId, ChunkManager>,
+    /// Worlds that can be loaded by name: from the config, or created at
+    /// runtime.
+    known: BTreeMap<String, WorldConfig>,
+    next_id: u32,
+    /// Directory holding one `<name>/db` per world. Without one, worlds have
+    /// no storage and their chunks are regenerated every time.
+    storage_dir: Option<PathBuf>,
+    /// Jigsaw structure templates, shared by every world's generator.
+    templates: Arc<TemplateManager>,
+}
+
+impl Worlds {
+    /// Create with only the default world loaded.
+    pub fn new(default: WorldConfig) -> Self {
+        Self::with_templates(default, Arc::default())
+    }
+
+    /// Create with only the default world loaded, generating jigsaw
+    /// structures in every world from `templates`.
+    pub fn with_templates(default: WorldConfig, templates: Arc<TemplateManager>) -> Self {
+        let mut known = BTreeMap::new();
+        known.insert(DEFAULT_WORLD.to_string(), default);
+        let mut loaded = BTreeMap::new();
+        loaded.insert(
+            WorldId::DEFAULT,
+            ChunkManager::with_templates(default, templates.clone()),
+        );
+        Self {
+            loaded,
+            known,
+            next_id: 1,
+            storage_dir: None,
+            templates,
+        }
+    }
+
+    /// Keep world storage under `dir`, one `<name>/db` per world. Only worlds
+    /// loaded after this get a provider; set the default world's with
+    /// [`ChunkManager::set_provider`].
+    pub fn set_storage_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.storage_dir = Some(dir.into());
+    }
+
+    /// Database path of a world, if worlds are stored on disk.
+    pub fn storage_path(&self, name: &str) -> Option<PathBuf> {
+        self.storage_dir
+            .as_ref()
+            .map(|dir| dir.join(name).join("db"))
+    }
+
+    /// Make a world loadable by name without loading it.
+    pub fn register(&mut self, name: &str, config: WorldConfig) -> Result<(), WorldError> {
+        validate_name(name)?;
+        if self.known.contains_key(name) {
+            return Err(WorldError::AlreadyExists(name.to_string()));
+        }
+        self.known.insert(name.to_string(), config);
+        Ok(())
+    }
+
+    /// Create a new world and load it. With storage on disk, its settings are
+    /// saved so it can be loaded by name after a restart.
+    pub fn create(&mut self, name: &str, config: WorldConfig) -> Result<WorldId, WorldError> {
+        validate_name(name)?;
+        if self.known.contains_key(name) || self.read_saved_config(name).is_some() {
+            return Err(WorldError::AlreadyExists(name.to_string()));
+        }
+        if let Some(dir) = &self.storage_dir {
+            write_saved_config(&dir.join(name), &config).map_err(|e| WorldError::Storage {
+                name: name.to_string(),
+                message: e.to_string(),
+            })?;
+        }
+        self.known.insert(name.to_string(), config);
+        self.load(name)
+    }
+
+    /// Load a known world, or one created at runtime before a restart.
+    pub fn load(&mut self, name: &str) -> Result<WorldId, WorldError> {
+        if self.id(name).is_some() {
+            return Err(WorldError::AlreadyLoaded(name.to_string()));
+        }
+        let config = match self.known.get(name) {
+            Some(config) => *config,
+            None => {
+                let config = self
+                    .read_saved_config(name)
+                    .ok_or_else(|| WorldError::NotFound(name.to_string()))?;
+                self.known.insert(name.to_string(), config);
+                config
+            }
+        };
+
+        let mut manager = ChunkManager::with_templates(config, self.templates.clone());
+        if let Some(path) = self.storage_path(name) {
+            let provider = crate::storage::open_world_provider(&path, &config).map_err(|e| {
+                WorldError::Storage {
+                    name: name.to_string(),
+                    message: e.to_string(),
+                }
+            })?;
+            manager.set_provider(provider);
+        }
+
+        manager.find_spawn();
+
+        let id = WorldId(self.next_id);
+        self.next_id += 1;
+        manager.id = id;
+        manager.name = name.to_string();
+        self.loaded.insert(id, manager);
+        Ok(id)
+    }
+
+    /// Stop tracking a loaded world and return its chunk manager. Its chunk
+    /// entities are left for the caller to save and despawn.
+    pub fn remove(&mut self, id: WorldId) -> Result<ChunkManager, WorldError> {
+        if id.is_default() {
+            return Err(WorldError::DefaultWorld);
+        }
+        self.loaded
+            .remove(&id)
+            .ok_or_else(|| WorldError::NotLoaded(format!("#{}", id.0)))
+    }
+
+    /// Chunk manager of a loaded world.
+    pub fn get(&self, id: WorldId) -> Option<&ChunkManager> {
+        self.loaded.get(&id)
+    }
+
+    /// Mutable chunk manager of a loaded world.
+    pub fn get_mut(&mut self, id: WorldId) -> Option<&mut ChunkManager> {
+        self.loaded.get_mut(&id)
+    }
+
+    /// Chunk manager of the default world.
+    pub fn default_world(&self) -> &ChunkManager {
+        &self.loaded[&WorldId::DEFAULT]
+    }
+
+    /// Mutable chunk manager of the default world.
+    pub fn default_world_mut(&mut self) -> &mut ChunkManager {
+        self.loaded
+            .get_mut(&WorldId::DEFAULT)
+            .expect("default world is always loaded")
+    }
+
+    /// Id of a loaded world by name.
+    pub fn id(&self, name: &str) -> Option<WorldId> {
+        self.loaded
+            .iter()
+            .find(|(_, manager)| manager.name() == name)
+            .map(|(&id, _)| id)
+    }
+
+    /// Name of a loaded world.
+    pub fn name(&self, id: WorldId) -> Option<&str> {
+        self.get(id).map(ChunkManager::name)
+    }
+
+    /// Loaded worlds, default first.
+    pub fn iter(&self) -> impl Iterator<Item = (WorldId, &ChunkManager)> {
+        self.loaded.iter().map(|(&id, manager)| (id, manager))
+    }
+
+    /// Loaded worlds, default first.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (WorldId, &mut ChunkManager)> {
+        self.loaded.iter_mut().map(|(&id, manager)| (id, manager))
+    }
+
+    /// Names and settings of every known world, loaded or not.
+    pub fn known(&self) -> impl Iterator<Item = (&str, &WorldConfig)> {
+        self.known
+            .iter()
+            .map(|(name, config)| (name.as_str(), config))
+    }
+
+    fn read_saved_config(&self, name: &str) -> Option<WorldConfig> {
+        let path = self
+            .storage_dir
+            .as_ref()?
+            .join(name)
+            .join(WORLD_CONFIG_FILE);
+        let contents = std::fs::read_to_string(path).ok()?;
+        toml_edit::de::from_str(&contents).ok()
+    }
+}
+
+impl Default for Worlds {
+    fn default() -> Self {
+        Self::new(WorldConfig::default())
+    }
+}
+
+fn write_saved_config(dir: &Path, config: &WorldConfig) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let contents = toml_edit::ser::to_string_pretty(config)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    std::fs::write(dir.join(WORLD_CONFIG_FILE), contents)
+}
+
+/// Check a world name is usable; world names double as directory names.
+pub fn validate_name(name: &str) -> Result<(), WorldError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(WorldError::InvalidName(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::WorldGenerator;
+
+    fn flat() -> WorldConfig {
+        WorldConfig {
+            generator: WorldGenerator::SuperFlat,
+            ..WorldConfig::default()
+        }
+    }
+
+    #[test]
+    fn default_world_is_always_loaded() {
+        let mut worlds = Worlds::default();
+        assert_eq!(worlds.id(DEFAULT_WORLD), Some(WorldId::DEFAULT));
+        assert_eq!(worlds.name(WorldId::DEFAULT), Some(DEFAULT_WORLD));
+        assert!(matches!(
+            worlds.remove(WorldId::DEFAULT),
+            Err(WorldError::DefaultWorld)
+        ));
+    }
+
+    #[test]
+    fn create_load_and_remove() {
+        let mut worlds = Worlds::default();
+        let lobby = worlds.create("lobby", flat()).unwrap();
+        assert_ne!(lobby, WorldId::DEFAULT);
+        assert_eq!(worlds.get(lobby).unwrap().id(), lobby);
+        assert_eq!(worlds.get(lobby).unwrap().name(), "lobby");
+        assert!(matches!(
+            worlds.create("lobby", flat()),
+            Err(WorldError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            worlds.load("lobby"),
+            Err(WorldError::AlreadyLoaded(_))
+        ));
+
+        worlds.remove(lobby).unwrap();
+        assert_eq!(worlds.id("lobby"), None);
+        let reloaded = worlds.load("lobby").unwrap();
+        assert_ne!(reloaded, lobby);
+    }
+
+    #[test]
+    fn registered_worlds_load_by_name() {
+        let mut worlds = Worlds::default();
+        worlds.register("survival_nether", flat()).unwrap();
+        assert_eq!(worlds.id("survival_nether"), None);
+        assert!(worlds.load("survival_nether").is_ok());
+        assert!(matches!(worlds.load("arena"), Err(WorldError::NotFound(_))));
+    }
+
+    #[test]
+    fn rejects_names_that_are_not_directory_safe() {
+        let mut worlds = Worlds::default();
+        for name in ["", "../escape", "with space", "a/b"] {
+            assert!(matches!(
+                worlds.create(name, flat()),
+                Err(WorldError::InvalidName(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Game rules.
+
+use bevy_ecs::prelude::*;
+
+/// Value of a game rule: a switch or a number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameRuleValue {
+    Bool(bool),
+    Int(i32),
+}
+
+impl std::fmt::Display for GameRuleValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameRuleValue::Bool(on) => on.fmt(f),
+            GameRuleValue::Int(value) => value.fmt(f),
+        }
+    }
+}
+
+/// World-wide settings for gameplay mechanics, as set by `/gamerule`.
+///
+/// Rules are named in camelCase like the command; names are matched without
+/// regard to case, which is how Bedrock stores them in `level.dat` and sends
+/// them to clients.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameRules {
+    /// Players keep their inventory and experience when they die.
+    pub keep_inventory: bool,
+    /// The time of day advances.
+    pub do_daylight_cycle: bool,
+    /// The weather changes on its own.
+    pub do_weather_cycle: bool,
+    /// Mobs spawn on their own.
+    pub do_mob_spawning: bool,
+    /// Players heal from a full hunger bar.
+    pub natural_regeneration: bool,
+    /// Players take damage from falling.
+    pub fall_damage: bool,
+    /// Players take damage from fire and lava.
+    pub fire_damage: bool,
+    /// Players take damage from drowning.
+    pub drowning_damage: bool,
+    /// Players can attack each other.
+    pub pvp: bool,
+    /// Players see their coordinates on screen.
+    pub show_coordinates: bool,
+    /// Deaths are announced in chat.
+    pub show_death_messages: bool,
+    /// Players respawn without the death screen.
+    pub do_immediate_respawn: bool,
+    /// Blocks given a random tick per subchunk each tick.
+    pub random_tick_speed: i32,
+    /// Blocks around the world spawn that new players may appear within.
+    pub spawn_radius: i32,
+    /// Share of players that must sleep to skip the night.
+    pub players_sleeping_percentage: i32,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            keep_inventory: false,
+            do_daylight_cycle: true,
+            do_weather_cycle: true,
+            do_mob_spawning: true,
+            natural_regeneration: true,
+            fall_damage: true,
+            fire_damage: true,
+            drowning_damage: true,
+            pvp: true,
+            show_coordinates: false,
+            show_death_messages: true,
+            do_immediate_respawn: false,
+            random_tick_speed: 1,
+            spawn_radius: 5,
+            players_sleeping_percentage: 100,
+        }
+    }
+}
+
+impl GameRules {
+    /// Names of the switches, in camelCase.
+    pub const BOOL_NAMES: [&'static str; 12] = [
+        "keepInventory",
+        "doDaylightCycle",
+        "doWeatherCycle",
+        "doMobSpawning",
+        "naturalRegeneration",
+        "fallDamage",
+        "fireDamage",
+        "drowningDamage",
+        "pvp",
+        "showCoordinates",
+        "showDeathMessages",
+        "doImmediateRespawn",
+    ];
+
+    /// Names of the numeric rules, in camelCase.
+    pub const INT_NAMES: [&'static str; 3] = [
+        "randomTickSpeed",
+        "spawnRadius",
+        "playersSleepingPercentage",
+    ];
+
+    fn bools_mut(&mut self) -> [&mut bool; 12] {
+        [
+            &mut self.keep_inventory,
+            &mut self.do_daylight_cycle,
+            &mut self.do_weather_cycle,
+            &mut self.do_mob_spawning,
+            &mut self.natural_regeneration,
+            &mut self.fall_damage,
+            &mut self.fire_damage,
+            &mut self.drowning_damage,
+            &mut self.pvp,
+            &mut self.show_coordinates,
+            &mut self.show_death_messages,
+            &mut self.do_immediate_respawn,
+        ]
+    }
+
+    fn ints_mut(&mut self) -> [&mut i32; 3] {
+        [
+            &mut self.random_tick_speed,
+            &mut self.spawn_radius,
+            &mut self.players_sleeping_percentage,
+        ]
+    }
+
+    fn position(names: &[&str], name: &str) -> Option<usize> {
+        names
+            .iter()
+            .position(|rule| rule.eq_ignore_ascii_case(name))
+    }
+
+    /// The camelCase name of a rule.
+    pub fn canonical_name(name: &str) -> Option<&'static str> {
+        Self::BOOL_NAMES
+            .into_iter()
+            .chain(Self::INT_NAMES)
+            .find(|rule| rule.eq_ignore_ascii_case(name))
+    }
+
+    /// Every rule and its value, switches first.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, GameRuleValue)> {
+        let mut rules = *self;
+        let bools = rules.bools_mut().map(|on| GameRuleValue::Bool(*on));
+        let ints = rules.ints_mut().map(|value| GameRuleValue::Int(*value));
+        Self::BOOL_NAMES
+            .into_iter()
+            .zip(bools)
+            .chain(Self::INT_NAMES.into_iter().zip(ints))
+    }
+
+    /// The value of a rule, or `None` if there is no such rule.
+    pub fn get(&self, name: &str) -> Option<GameRuleValue> {
+        let mut rules = *self;
+        if let Some(index) = Self::position(&Self::BOOL_NAMES, name) {
+            return Some(GameRuleValue::Bool(*rules.bools_mut()[index]));
+        }
+        let index = Self::position(&Self::INT_NAMES, name)?;
+        Some(GameRuleValue::Int(*rules.ints_mut()[index]))
+    }
+
+    /// Set a rule. Returns `false` if there is no such rule or it takes the
+    /// other kind of value.
+    pub fn set(&mut self, name: &str, value: GameRuleValue) -> bool {
+        match value {
+            GameRuleValue::Bool(on) => match Self::position(&Self::BOOL_NAMES, name) {
+                Some(index) => *self.bools_mut()[index] = on,
+                None => return false,
+            },
+            GameRuleValue::Int(value) => match Self::position(&Self::INT_NAMES, name) {
+                Some(index) => *self.ints_mut()[index] = value,
+                None => return false,
+            },
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_rules() {
+        let mut rules = GameRules::default();
+        assert_eq!(rules.get("keepInventory"), Some(GameRuleValue::Bool(false)));
+        assert!(rules.set("KEEPINVENTORY", GameRuleValue::Bool(true)));
+        assert!(rules.keep_inventory);
+        assert!(rules.set("pvp", GameRuleValue::Bool(false)));
+        assert!(!rules.pvp);
+        assert!(!rules.set("noSuchRule", GameRuleValue::Bool(true)));
+        assert_eq!(rules.get("noSuchRule"), None);
+        assert_eq!(
+            GameRules::canonical_name("dodaylightcycle"),
+            Some("doDaylightCycle")
+        );
+
+        assert_eq!(rules.get("randomTickSpeed"), Some(GameRuleValue::Int(1)));
+        assert!(rules.set("randomtickspeed", GameRuleValue::Int(3)));
+        assert_eq!(rules.random_tick_speed, 3);
+        // Each rule only takes its own kind of value
+        assert!(!rules.set("randomTickSpeed", GameRuleValue::Bool(true)));
+        assert!(!rules.set("pvp", GameRuleValue::Int(0)));
+
+        let count = GameRules::BOOL_NAMES.len() + GameRules::INT_NAMES.len();
+        assert_eq!(rules.iter().count(), count);
+        assert!(
+            rules
+                .iter()
+                .any(|(name, value)| name == "keepInventory" && value == GameRuleValue::Bool(true))
+        );
+    }
+}
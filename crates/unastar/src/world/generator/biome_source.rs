@@ -0,0 +1,138 @@
+//! Per-dimension biome sources.
+//!
+//! The overworld picks biomes from all six climate parameters through
+//! [`BiomeNoise`]. The Nether uses a small multi-noise preset over
+//! temperature and humidity, and the End derives its biomes from the
+//! island density.
+
+use super::climate::BiomeNoise;
+use super::constants::Biome;
+use super::noise::EndIslandNoise;
+
+/// Source of biomes for a dimension.
+pub trait BiomeSource: Send + Sync {
+    /// Get the biome at a block position.
+    fn get_biome(&self, x: i32, y: i32, z: i32) -> Biome;
+}
+
+impl BiomeSource for BiomeNoise {
+    fn get_biome(&self, x: i32, y: i32, z: i32) -> Biome {
+        BiomeNoise::get_biome(self, x, y, z)
+    }
+}
+
+impl<T: BiomeSource + ?Sized> BiomeSource for std::sync::Arc<T> {
+    fn get_biome(&self, x: i32, y: i32, z: i32) -> Biome {
+        (**self).get_biome(x, y, z)
+    }
+}
+
+/// Java's `MultiNoiseBiomeSourceParameterList.Preset.NETHER`.
+///
+/// Each entry is (temperature, humidity, offset), scaled by 10000.
+/// All other parameters are zero for every Nether biome.
+const NETHER_BIOMES: [(Biome, i64, i64, i64); 5] = [
+    (Biome::NetherWastes, 0, 0, 0),
+    (Biome::SoulSandValley, 0, -5000, 0),
+    (Biome::CrimsonForest, 4000, 0, 0),
+    (Biome::WarpedForest, 0, 5000, 3750),
+    (Biome::BasaltDeltas, -5000, 0, 1750),
+];
+
+/// Nether biome source: nearest preset point in temperature/humidity space.
+#[derive(Debug, Clone)]
+pub struct NetherBiomeSource {
+    climate: BiomeNoise,
+}
+
+impl NetherBiomeSource {
+    /// Create the Nether biome source from a world seed.
+    pub fn from_seed(seed: i64) -> Self {
+        Self {
+            climate: BiomeNoise::from_seed(seed),
+        }
+    }
+}
+
+impl BiomeSource for NetherBiomeSource {
+    fn get_biome(&self, x: i32, y: i32, z: i32) -> Biome {
+        let climate = self.climate.sample_climate(x, y, z);
+        let (temperature, humidity) = (climate[0], climate[1]);
+
+        NETHER_BIOMES
+            .iter()
+            .min_by_key(|(_, t, h, offset)| {
+                (temperature - t).pow(2) + (humidity - h).pow(2) + offset.pow(2)
+            })
+            .map_or(Biome::NetherWastes, |(biome, ..)| *biome)
+    }
+}
+
+/// End biome source, matching Java's `TheEndBiomeSource`.
+///
+/// The central island is always `the_end`; the outer islands are split by
+/// the island density sampled at the middle of each 16-block section.
+#[derive(Debug, Clone)]
+pub struct EndBiomeSource {
+    islands: EndIslandNoise,
+}
+
+impl EndBiomeSource {
+    /// Create the End biome source from a world seed.
+    pub fn from_seed(seed: i64) -> Self {
+        Self {
+            islands: EndIslandNoise::new(seed),
+        }
+    }
+}
+
+impl BiomeSource for EndBiomeSource {
+    fn get_biome(&self, x: i32, _y: i32, z: i32) -> Biome {
+        let section_x = (x >> 4) as i64;
+        let section_z = (z >> 4) as i64;
+        if section_x * section_x + section_z * section_z <= 4096 {
+            return Biome::TheEnd;
+        }
+
+        let erosion = self
+            .islands
+            .sample(((x >> 4) * 2 + 1) * 8, ((z >> 4) * 2 + 1) * 8);
+        if erosion > 0.25 {
+            Biome::EndHighlands
+        } else if erosion >= -0.0625 {
+            Biome::EndMidlands
+        } else if erosion < -0.21875 {
+            Biome::SmallEndIslands
+        } else {
+            Biome::EndBarrens
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nether_biomes() {
+        let source = NetherBiomeSource::from_seed(12345);
+        for x in (-4096..4096).step_by(256) {
+            let biome = source.get_biome(x, 64, -x);
+            assert!(NETHER_BIOMES.iter().any(|(b, ..)| *b == biome), "{biome:?}");
+        }
+    }
+
+    #[test]
+    fn test_end_biomes() {
+        let source = EndBiomeSource::from_seed(12345);
+        assert_eq!(source.get_biome(0, 64, 0), Biome::TheEnd);
+        assert_eq!(source.get_biome(1000, 64, 0), Biome::TheEnd);
+
+        // Past the central island everything is one of the outer biomes
+        let outer = source.get_biome(5000, 64, 0);
+        assert!(matches!(
+            outer,
+            Biome::EndHighlands | Biome::EndMidlands | Biome::EndBarrens | Biome::SmallEndIslands
+        ));
+    }
+}
@@ -1,296 +1,248 @@
-//! Cave carver implementation.
+//! Cave carver (Java's `CaveWorldCarver` and `NetherWorldCarver`).
 //!
-//! Creates winding underground tunnels matching vanilla Minecraft's cave carver.
-
-use super::{CaveCarverConfig, WorldCarver};
-use crate::world::chunk::{blocks, Chunk};
-use crate::world::generator::aquifer::FluidPicker;
-use crate::world::generator::xoroshiro::Xoroshiro128;
-use std::f64::consts::PI;
-
-/// Cave carver that creates winding underground tunnels.
-#[derive(Debug, Clone)]
-pub struct CaveCarver {
-    config: CaveCarverConfig,
+//! A start chunk sends out up to 15 tunnels (10 in the Nether), some of
+//! them from a round room. Each tunnel winds for up to 112 steps, growing
+//! wider towards its middle, and thick tunnels split into two branches on
+//! the way.
+
+use unastar_noise::carver::{CaveSettings, ConfiguredCarver};
+
+use super::{CarvingContext, can_reach};
+use crate::world::generator::feature::{mth_cos, mth_sin};
+use crate::world::generator::xoroshiro::JavaRandom;
+
+/// Steps of a full tunnel: `(range * 2 - 1) * 16` for a range of 4 chunks.
+const TUNNEL_LENGTH: i32 = 112;
+
+/// Cave settings shared by the tunnels of one start.
+struct Cave<'c> {
+    carver: &'c ConfiguredCarver,
+    nether: bool,
+    horizontal_radius_multiplier: f64,
+    vertical_radius_multiplier: f64,
+    /// Relative height at and below which blocks are kept.
+    floor_level: f64,
 }
 
-impl CaveCarver {
-    /// Create a new cave carver with default configuration.
-    pub fn new() -> Self {
-        Self {
-            config: CaveCarverConfig::default(),
+/// Carve the caves started in a chunk.
+pub(super) fn carve(
+    ctx: &mut CarvingContext<'_>,
+    carver: &ConfiguredCarver,
+    settings: &CaveSettings,
+    nether: bool,
+    random: &mut JavaRandom,
+    chunk_x: i32,
+    chunk_z: i32,
+) {
+    let bound = if nether { 10 } else { 15 };
+    let count = random.next_int(bound) + 1;
+    let count = random.next_int(count as u32) + 1;
+    let count = random.next_int(count as u32);
+
+    for _ in 0..count {
+        let x = (chunk_x * 16 + random.next_int(16)) as f64;
+        let y = carver.y.sample(random, ctx.min_y, ctx.max_y()) as f64;
+        let z = (chunk_z * 16 + random.next_int(16)) as f64;
+        let cave = Cave {
+            carver,
+            nether,
+            horizontal_radius_multiplier: settings.horizontal_radius_multiplier.sample(random)
+                as f64,
+            vertical_radius_multiplier: settings.vertical_radius_multiplier.sample(random) as f64,
+            floor_level: settings.floor_level.sample(random) as f64,
+        };
+
+        let mut tunnels = 1;
+        if random.next_int(4) == 0 {
+            let y_scale = carver.y_scale.sample(random) as f64;
+            let radius = 1.0 + random.next_float() * 6.0;
+            cave.create_room(ctx, x, y, z, radius, y_scale);
+            tunnels += random.next_int(4);
         }
-    }
-
-    /// Create a new cave carver with custom configuration.
-    pub fn with_config(config: CaveCarverConfig) -> Self {
-        Self { config }
-    }
 
-    /// Create a seeded RNG for the given chunk position.
-    fn create_rng(&self, seed: i64, chunk_x: i32, chunk_z: i32) -> Xoroshiro128 {
-        let pos_seed = seed
-            .wrapping_add(chunk_x as i64 * 341873128712)
-            .wrapping_add(chunk_z as i64 * 132897987541);
-        Xoroshiro128::from_seed(pos_seed)
+        for _ in 0..tunnels {
+            let yaw = random.next_float() * std::f32::consts::TAU;
+            let pitch = (random.next_float() - 0.5) / 4.0;
+            let thickness = cave.thickness(random);
+            let length = TUNNEL_LENGTH - random.next_int((TUNNEL_LENGTH / 4) as u32);
+            let y_scale = if nether { 5.0 } else { 1.0 };
+            let tunnel = Tunnel {
+                x,
+                y,
+                z,
+                thickness,
+                yaw,
+                pitch,
+                branch_index: 0,
+                branch_count: length,
+                y_scale,
+            };
+            cave.create_tunnel(ctx, random.next_long(), tunnel);
+        }
     }
+}
 
-    /// Carve a single cave branch.
-    fn carve_branch(
-        &self,
-        chunk: &mut Chunk,
-        rng: &mut Xoroshiro128,
-        start_x: f64,
-        start_y: f64,
-        start_z: f64,
-        horizontal_radius: f64,
-        vertical_radius: f64,
-        yaw: f64,
-        pitch: f64,
-        branch_count: i32,
-        branch_index: i32,
-        fluid_picker: Option<&dyn FluidPicker>,
-    ) {
-        let chunk_center_x = (chunk.x * 16) as f64 + 8.0;
-        let chunk_center_z = (chunk.z * 16) as f64 + 8.0;
-
-        let mut x = start_x;
-        let mut y = start_y;
-        let mut z = start_z;
-        let mut current_yaw = yaw;
-        let mut current_pitch = pitch;
-        let mut current_h_radius = horizontal_radius;
-        let mut current_v_radius = vertical_radius;
-
-        let yaw_delta = 0.0f64;
-        let pitch_delta = 0.0f64;
-
-        // Progress through the branch
-        for i in branch_index..branch_count {
-            let progress = i as f64 / branch_count as f64;
-
-            // Calculate current radius with sine wave modulation
-            let radius_modifier = 1.0 + (progress * PI).sin() * horizontal_radius * 2.0;
-            let h_rad =
-                current_h_radius * radius_modifier * self.config.horizontal_radius_multiplier;
-            let v_rad =
-                current_v_radius * radius_modifier * self.config.vertical_radius_multiplier;
-
-            // Move along the direction
-            let cos_pitch = current_pitch.cos();
-            let sin_pitch = current_pitch.sin();
-            let cos_yaw = current_yaw.cos();
-            let sin_yaw = current_yaw.sin();
-
-            x += cos_yaw * cos_pitch;
-            y += sin_pitch;
-            z += sin_yaw * cos_pitch;
-
-            // Check if we're close enough to the chunk to carve
-            let dist_x = x - chunk_center_x;
-            let dist_z = z - chunk_center_z;
-            let dist_sq = dist_x * dist_x + dist_z * dist_z;
-
-            // Only carve if within reasonable distance of chunk
-            if dist_sq < (16.0 + h_rad * 2.0).powi(2) {
-                self.carve_sphere(chunk, x, y, z, h_rad, v_rad, fluid_picker);
-            }
-
-            // Update direction with some random variation
-            current_pitch = current_pitch * 0.7 + pitch_delta * 0.05;
-            current_yaw += yaw_delta * 0.05;
-
-            // Add small random perturbation
-            if rng.next_float() < 0.02 {
-                current_yaw += (rng.next_float() - 0.5) as f64 * PI * 0.25;
-            }
-            if rng.next_float() < 0.05 {
-                current_pitch += (rng.next_float() - 0.5) as f64 * 0.5;
-            }
-
-            // Clamp pitch
-            current_pitch = current_pitch.clamp(-PI / 4.0, PI / 4.0);
+/// Where a tunnel starts and how it is shaped.
+#[derive(Debug, Clone, Copy)]
+struct Tunnel {
+    x: f64,
+    y: f64,
+    z: f64,
+    thickness: f32,
+    yaw: f32,
+    pitch: f32,
+    /// Step the tunnel starts at; branches start where they split off.
+    branch_index: i32,
+    branch_count: i32,
+    /// Vertical radius relative to the horizontal one.
+    y_scale: f64,
+}
 
-            // Random radius variations
-            if rng.next_float() < 0.25 {
-                current_h_radius *= 0.9 + rng.next_float() as f64 * 0.2;
-                current_v_radius *= 0.9 + rng.next_float() as f64 * 0.2;
-            }
+impl Cave<'_> {
+    fn thickness(&self, random: &mut JavaRandom) -> f32 {
+        if self.nether {
+            return (random.next_float() * 2.0 + random.next_float()) * 2.0;
+        }
+        let mut thickness = random.next_float() * 2.0 + random.next_float();
+        if random.next_int(10) == 0 {
+            thickness *= random.next_float() * random.next_float() * 3.0 + 1.0;
         }
+        thickness
     }
 
-    /// Carve a spheroid at the given position.
-    fn carve_sphere(
+    fn create_room(
         &self,
-        chunk: &mut Chunk,
-        center_x: f64,
-        center_y: f64,
-        center_z: f64,
-        h_radius: f64,
-        v_radius: f64,
-        fluid_picker: Option<&dyn FluidPicker>,
+        ctx: &mut CarvingContext<'_>,
+        x: f64,
+        y: f64,
+        z: f64,
+        radius: f32,
+        y_scale: f64,
     ) {
-        let chunk_min_x = chunk.x * 16;
-        let chunk_min_z = chunk.z * 16;
-
-        // Calculate block bounds
-        let min_x = ((center_x - h_radius).floor() as i32).max(chunk_min_x);
-        let max_x = ((center_x + h_radius).ceil() as i32).min(chunk_min_x + 15);
-        let min_y = ((center_y - v_radius).floor() as i32).max(self.config.min_y);
-        let max_y = ((center_y + v_radius).ceil() as i32).min(self.config.max_y);
-        let min_z = ((center_z - h_radius).floor() as i32).max(chunk_min_z);
-        let max_z = ((center_z + h_radius).ceil() as i32).min(chunk_min_z + 15);
-
-        // Don't carve below floor level
-        let floor_y = (center_y + v_radius * self.config.floor_level) as i32;
-
-        for bx in min_x..=max_x {
-            let local_x = (bx - chunk_min_x) as u8;
-            let dx = (bx as f64 + 0.5 - center_x) / h_radius;
-
-            for bz in min_z..=max_z {
-                let local_z = (bz - chunk_min_z) as u8;
-                let dz = (bz as f64 + 0.5 - center_z) / h_radius;
+        let horizontal = 1.5 + (mth_sin(std::f32::consts::FRAC_PI_2) * radius) as f64;
+        let vertical = horizontal * y_scale;
+        ctx.carve_ellipsoid(
+            self.carver,
+            x + 1.0,
+            y,
+            z,
+            horizontal,
+            vertical,
+            |rx, ry, rz, _| self.should_skip(rx, ry, rz),
+        );
+    }
 
-                let h_dist_sq = dx * dx + dz * dz;
-                if h_dist_sq >= 1.0 {
-                    continue;
+    fn create_tunnel(&self, ctx: &mut CarvingContext<'_>, seed: i64, tunnel: Tunnel) {
+        let Tunnel {
+            mut x,
+            mut y,
+            mut z,
+            thickness,
+            mut yaw,
+            mut pitch,
+            branch_index,
+            branch_count,
+            y_scale,
+        } = tunnel;
+        let mut random = JavaRandom::from_seed(seed);
+        let split = random.next_int((branch_count / 2) as u32) + branch_count / 4;
+        let steep = random.next_int(6) == 0;
+        let mut yaw_change = 0.0f32;
+        let mut pitch_change = 0.0f32;
+
+        for step in branch_index..branch_count {
+            let horizontal = 1.5
+                + (mth_sin(std::f32::consts::PI * step as f32 / branch_count as f32) * thickness)
+                    as f64;
+            let vertical = horizontal * y_scale;
+            let cos_pitch = mth_cos(pitch);
+            x += (mth_cos(yaw) * cos_pitch) as f64;
+            y += mth_sin(pitch) as f64;
+            z += (mth_sin(yaw) * cos_pitch) as f64;
+            pitch *= if steep { 0.92 } else { 0.7 };
+            pitch += pitch_change * 0.1;
+            yaw += yaw_change * 0.1;
+            pitch_change *= 0.9;
+            yaw_change *= 0.75;
+            pitch_change += (random.next_float() - random.next_float()) * random.next_float() * 2.0;
+            yaw_change += (random.next_float() - random.next_float()) * random.next_float() * 4.0;
+
+            if step == split && thickness > 1.0 {
+                for turn in [-std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2] {
+                    let seed = random.next_long();
+                    let branch = Tunnel {
+                        x,
+                        y,
+                        z,
+                        thickness: random.next_float() * 0.5 + 0.5,
+                        yaw: yaw + turn,
+                        pitch: pitch / 3.0,
+                        branch_index: step,
+                        branch_count,
+                        y_scale: 1.0,
+                    };
+                    self.create_tunnel(ctx, seed, branch);
                 }
+                return;
+            }
 
-                for by in (min_y.max(floor_y)..=max_y).rev() {
-                    let dy = (by as f64 + 0.5 - center_y) / v_radius;
-                    let dist_sq = h_dist_sq + dy * dy;
-
-                    if dist_sq < 1.0 {
-                        // Check fluid picker - don't carve into water
-                        if let Some(fp) = fluid_picker {
-                            let fluid_status = fp.compute_fluid(bx, by, bz);
-                            if fluid_status.fluid_level > by {
-                                continue; // Don't carve below water level
-                            }
-                        }
-
-                        // Only carve stone-like blocks
-                        let current = chunk.get_block(local_x, by as i16, local_z);
-                        if self.should_carve_block(current) {
-                            chunk.set_block(local_x, by as i16, local_z, *blocks::AIR);
-                        }
-                    }
+            if random.next_int(4) != 0 {
+                if !can_reach(ctx, x, z, step, branch_count, thickness) {
+                    return;
                 }
+                ctx.carve_ellipsoid(
+                    self.carver,
+                    x,
+                    y,
+                    z,
+                    horizontal * self.horizontal_radius_multiplier,
+                    vertical * self.vertical_radius_multiplier,
+                    |rx, ry, rz, _| self.should_skip(rx, ry, rz),
+                );
             }
         }
     }
 
-    /// Check if a block should be carved (stone-like blocks only).
-    fn should_carve_block(&self, block_id: u32) -> bool {
-        block_id == *blocks::STONE
-            || block_id == *blocks::DEEPSLATE
-            || block_id == *blocks::GRANITE
-            || block_id == *blocks::DIORITE
-            || block_id == *blocks::ANDESITE
-            || block_id == *blocks::TUFF
-            || block_id == *blocks::DIRT
-            || block_id == *blocks::SAND
-            || block_id == *blocks::SANDSTONE
-            || block_id == *blocks::GRAVEL
-    }
-}
-
-impl Default for CaveCarver {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl WorldCarver for CaveCarver {
-    fn is_start_chunk(&self, seed: i64, chunk_x: i32, chunk_z: i32) -> bool {
-        let mut rng = self.create_rng(seed, chunk_x, chunk_z);
-        rng.next_float() < self.config.probability
-    }
-
-    fn carve(
-        &self,
-        chunk: &mut Chunk,
-        seed: i64,
-        chunk_x: i32,
-        chunk_z: i32,
-        fluid_picker: Option<&dyn FluidPicker>,
-    ) {
-        // Check neighboring chunks for cave starts that might extend into this chunk
-        for dx in -8..=8 {
-            for dz in -8..=8 {
-                let cx = chunk_x + dx;
-                let cz = chunk_z + dz;
-
-                if self.is_start_chunk(seed, cx, cz) {
-                    let mut rng = self.create_rng(seed, cx, cz);
-
-                    // Skip the probability check we already did
-                    let _ = rng.next_float();
-
-                    // Generate cave parameters
-                    let cave_count = rng.next_int(15) as i32 + 1;
-
-                    for _ in 0..cave_count {
-                        // Cave starting position within the starting chunk
-                        let start_x = (cx * 16) as f64 + rng.next_float() as f64 * 16.0;
-                        let start_y = self.config.min_y as f64
-                            + rng.next_float() as f64
-                                * (self.config.max_y - self.config.min_y) as f64;
-                        let start_z = (cz * 16) as f64 + rng.next_float() as f64 * 16.0;
-
-                        // Cave branch parameters
-                        let branch_count = rng.next_int(40) as i32 + 40;
-                        let h_radius = 1.0 + rng.next_float() as f64 * 4.0;
-                        let v_radius = 0.5 + rng.next_float() as f64 * 2.0;
-                        let yaw = rng.next_float() as f64 * PI * 2.0;
-                        let pitch = (rng.next_float() as f64 - 0.5) * PI / 4.0;
-
-                        self.carve_branch(
-                            chunk,
-                            &mut rng,
-                            start_x,
-                            start_y,
-                            start_z,
-                            h_radius,
-                            v_radius,
-                            yaw,
-                            pitch,
-                            branch_count,
-                            0,
-                            fluid_picker,
-                        );
-                    }
-                }
-            }
-        }
+    /// Keep blocks at or below the floor level and outside the ellipsoid.
+    fn should_skip(&self, rel_x: f64, rel_y: f64, rel_z: f64) -> bool {
+        rel_y <= self.floor_level || rel_x * rel_x + rel_y * rel_y + rel_z * rel_z >= 1.0
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use unastar_noise::configured_carver;
 
     #[test]
-    fn test_cave_carver_creation() {
-        let carver = CaveCarver::new();
-        assert!(carver.config.probability > 0.0);
-    }
-
-    #[test]
-    fn test_cave_carver_determinism() {
-        let carver = CaveCarver::new();
-        let result1 = carver.is_start_chunk(12345, 0, 0);
-        let result2 = carver.is_start_chunk(12345, 0, 0);
-        assert_eq!(result1, result2, "Cave carver should be deterministic");
+    fn test_cave_thickness() {
+        let carver = configured_carver("cave").unwrap();
+        let cave = |nether| Cave {
+            carver,
+            nether,
+            horizontal_radius_multiplier: 1.0,
+            vertical_radius_multiplier: 1.0,
+            floor_level: -0.7,
+        };
+        let mut random = JavaRandom::from_seed(42);
+        for _ in 0..100 {
+            assert!((0.0..12.0).contains(&cave(false).thickness(&mut random)));
+            assert!((0.0..6.0).contains(&cave(true).thickness(&mut random)));
+        }
     }
 
     #[test]
-    fn test_should_carve_block() {
-        let carver = CaveCarver::new();
-        assert!(carver.should_carve_block(*blocks::STONE));
-        assert!(carver.should_carve_block(*blocks::DEEPSLATE));
-        assert!(!carver.should_carve_block(*blocks::AIR));
-        assert!(!carver.should_carve_block(*blocks::WATER));
+    fn test_should_skip() {
+        let carver = configured_carver("cave").unwrap();
+        let cave = Cave {
+            carver,
+            nether: false,
+            horizontal_radius_multiplier: 1.0,
+            vertical_radius_multiplier: 1.0,
+            floor_level: -0.7,
+        };
+        assert!(!cave.should_skip(0.0, 0.0, 0.0));
+        assert!(cave.should_skip(0.0, -0.8, 0.0), "below the floor");
+        assert!(cave.should_skip(0.8, 0.0, 0.8), "outside the ellipsoid");
     }
 }
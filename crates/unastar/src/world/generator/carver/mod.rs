@@ -1,135 +1,399 @@
 //! Cave and canyon carvers for world generation.
 //!
-//! Carvers are applied after basic terrain generation to create caves and
-//! canyons. They operate on chunks and carve out air spaces based on noise
-//! and geometric algorithms.
+//! Carvers run after the surface rules and before features, cutting
+//! tunnels through the terrain. The carvers of each biome and their
+//! settings come from the `configured_carver` JSON (see
+//! [`unastar_noise::carver`]).
 //!
 //! ## Carver Types
 //!
-//! - **Cave Carver**: Creates winding cave tunnels underground
-//! - **Canyon Carver**: Creates deep ravines from the surface
+//! - **Cave Carver**: Winding tunnels, sometimes starting from a round room
+//! - **Nether Cave Carver**: Wider caves carved to air and lava only
+//! - **Canyon Carver**: Long, narrow and deep ravines
 //!
 //! ## Generation Process
 //!
-//! Carvers are applied in the `CARVERS` step of world generation:
-//! 1. Determine if chunk is a carver start chunk
-//! 2. Generate carver positions/parameters from seed
-//! 3. Carve air pockets respecting aquifer boundaries
+//! Java's `ChunkGenerator.applyCarvers`:
+//! 1. Every chunk up to eight chunks away may start tunnels reaching into
+//!    the chunk being carved
+//! 2. Each carver of the start chunk's biome starts there with its
+//!    configured probability, from a random seeded by the world seed, the
+//!    carver's index and the start chunk
+//! 3. Carved blocks below the lava level become lava; the others ask the
+//!    aquifer, so caves fill up to the local water level and stop at
+//!    aquifer barriers
 //!
 //! ## Java Parity
 //!
-//! This implementation aims to match vanilla Java Edition's carver behavior
-//! for deterministic world generation.
+//! Tunnels follow Java's algorithms and random calls, so caves and canyons
+//! appear where they do in Java for the same seed and biomes.
 
-mod cave;
 mod canyon;
+mod cave;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use unastar_noise::carver::{CarverKind, ConfiguredCarver};
+use unastar_noise::{CONFIGURED_CARVERS, configured_carver};
+
+use super::feature::{self, region};
+use super::jigsaw::large_feature_random;
+use super::surface::SurfaceSystem;
+use super::xoroshiro::JavaRandom;
+use crate::world::chunk::{Chunk, blocks};
+use crate::world::generator::BiomeSource;
+
+/// Chunks around a chunk whose tunnels may reach into it.
+const RANGE: i32 = 8;
+
+/// Which of the biomes' carvers a world runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CarverSelection {
+    /// Every carver of each biome.
+    #[default]
+    All,
+    /// Only the cave carvers.
+    Caves,
+    /// Only the canyon carvers.
+    Canyons,
+    /// No carvers.
+    None,
+    /// Only the named configured carvers, such as
+    /// `{ only = ["cave", "canyon"] }`.
+    Only(CarverSet),
+}
 
-pub use cave::CaveCarver;
-pub use canyon::CanyonCarver;
+impl CarverSelection {
+    /// Whether a configured carver, by name, runs.
+    pub fn includes(self, name: &str, carver: &ConfiguredCarver) -> bool {
+        match self {
+            Self::All => true,
+            Self::Caves => matches!(carver.kind, CarverKind::Cave(_) | CarverKind::NetherCave(_)),
+            Self::Canyons => matches!(carver.kind, CarverKind::Canyon { .. }),
+            Self::None => false,
+            Self::Only(set) => set.contains(name),
+        }
+    }
+}
 
-use crate::world::chunk::Chunk;
-use crate::world::generator::aquifer::FluidPicker;
+const _: () = assert!(CONFIGURED_CARVERS.len() <= 64);
 
-/// Trait for world carvers.
+/// A set of configured carvers, one bit per entry of [`CONFIGURED_CARVERS`].
 ///
-/// Carvers modify chunks by carving out air spaces for caves and canyons.
-pub trait WorldCarver: Send + Sync {
-    /// Check if this chunk is a starting point for the carver.
-    ///
-    /// Starting chunks initiate carving that may extend into neighboring chunks.
-    fn is_start_chunk(&self, seed: i64, chunk_x: i32, chunk_z: i32) -> bool;
+/// Written in config files as a list of names, with or without the
+/// `minecraft:` prefix.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CarverSet(u64);
 
-    /// Carve the chunk.
-    ///
-    /// This may be called even if `is_start_chunk` is false, as carving from
-    /// neighboring chunks can extend into this chunk.
+impl CarverSet {
+    fn index(name: &str) -> Option<usize> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        CONFIGURED_CARVERS.iter().position(|carver| *carver == name)
+    }
+
+    /// A set of configured carvers by name, or the first unknown name.
+    pub fn from_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<Self, &'a str> {
+        let mut set = Self::default();
+        for name in names {
+            set.0 |= 1 << Self::index(name).ok_or(name)?;
+        }
+        Ok(set)
+    }
+
+    /// Whether a configured carver is in the set.
+    pub fn contains(self, name: &str) -> bool {
+        Self::index(name).is_some_and(|index| self.0 & (1 << index) != 0)
+    }
+
+    /// Names of the carvers in the set.
+    pub fn names(self) -> impl Iterator<Item = &'static str> {
+        CONFIGURED_CARVERS
+            .iter()
+            .enumerate()
+            .filter(move |(index, _)| self.0 & (1 << index) != 0)
+            .map(|(_, name)| *name)
+    }
+}
+
+impl Serialize for CarverSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.names())
+    }
+}
+
+impl<'de> Deserialize<'de> for CarverSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let names = Vec::<String>::deserialize(deserializer)?;
+        Self::from_names(names.iter().map(String::as_str)).map_err(|name| {
+            serde::de::Error::custom(format!(
+                "unknown configured carver `{name}`, expected one of {}",
+                CONFIGURED_CARVERS.join(", ")
+            ))
+        })
+    }
+}
+
+/// A carver.
+pub trait WorldCarver {
+    /// Whether a chunk starts the carver, from the chunk's carver random.
+    fn is_start_chunk(&self, random: &mut JavaRandom) -> bool;
+
+    /// Carve the tunnels started in a chunk into the context's chunk.
     fn carve(
         &self,
-        chunk: &mut Chunk,
-        seed: i64,
+        ctx: &mut CarvingContext<'_>,
+        random: &mut JavaRandom,
         chunk_x: i32,
         chunk_z: i32,
-        fluid_picker: Option<&dyn FluidPicker>,
     );
 }
 
-/// Configuration for cave carvers.
-#[derive(Debug, Clone)]
-pub struct CaveCarverConfig {
-    /// Probability of a chunk being a cave start point (0.0 to 1.0).
-    pub probability: f32,
-    /// Minimum Y level for caves.
+impl WorldCarver for ConfiguredCarver {
+    fn is_start_chunk(&self, random: &mut JavaRandom) -> bool {
+        random.next_float() <= self.probability
+    }
+
+    fn carve(
+        &self,
+        ctx: &mut CarvingContext<'_>,
+        random: &mut JavaRandom,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) {
+        match &self.kind {
+            CarverKind::Cave(settings) => {
+                cave::carve(ctx, self, settings, false, random, chunk_x, chunk_z)
+            }
+            CarverKind::NetherCave(settings) => {
+                cave::carve(ctx, self, settings, true, random, chunk_x, chunk_z)
+            }
+            CarverKind::Canyon {
+                vertical_rotation,
+                shape,
+            } => canyon::carve(
+                ctx,
+                self,
+                vertical_rotation,
+                shape,
+                random,
+                chunk_x,
+                chunk_z,
+            ),
+        }
+    }
+}
+
+/// Decides what a carved block becomes: `Some` block, or `None` to keep it
+/// (an aquifer barrier). Called with world coordinates.
+pub type Substance<'a> = dyn FnMut(i32, i32, i32) -> Option<u32> + 'a;
+
+/// The chunk being carved and what carving it needs.
+pub struct CarvingContext<'a> {
+    pub chunk: &'a mut Chunk,
+    /// Bottom of the generated height range.
     pub min_y: i32,
-    /// Maximum Y level for caves.
-    pub max_y: i32,
-    /// Horizontal radius multiplier.
-    pub horizontal_radius_multiplier: f64,
-    /// Vertical radius multiplier.
-    pub vertical_radius_multiplier: f64,
-    /// Y scale for the floor cutoff.
-    pub floor_level: f64,
+    /// Height of the generated height range.
+    pub gen_depth: i32,
+    /// Surface rules, for dirt uncovered below grass.
+    surface: &'a SurfaceSystem,
+    substance: &'a mut Substance<'a>,
+    /// Blocks already carved, shared by every carver of the chunk.
+    mask: Vec<u64>,
 }
 
-impl Default for CaveCarverConfig {
-    fn default() -> Self {
+impl<'a> CarvingContext<'a> {
+    /// Create a context carving `chunk` between `min_y` and
+    /// `min_y + gen_depth`.
+    pub fn new(
+        chunk: &'a mut Chunk,
+        min_y: i32,
+        gen_depth: i32,
+        surface: &'a SurfaceSystem,
+        substance: &'a mut Substance<'a>,
+    ) -> Self {
         Self {
-            probability: 0.14285715,
-            min_y: -64,
-            max_y: 180,
-            horizontal_radius_multiplier: 1.0,
-            vertical_radius_multiplier: 1.0,
-            floor_level: -0.7,
+            chunk,
+            min_y,
+            gen_depth,
+            surface,
+            substance,
+            mask: vec![0; (16 * 16 * gen_depth as usize).div_ceil(64)],
         }
     }
-}
 
-/// Configuration for canyon carvers.
-#[derive(Debug, Clone)]
-pub struct CanyonCarverConfig {
-    /// Probability of a chunk being a canyon start point.
-    pub probability: f32,
-    /// Minimum Y level for canyons.
-    pub min_y: i32,
-    /// Maximum Y level for canyons.
-    pub max_y: i32,
-    /// Vertical rotation range.
-    pub vertical_rotation_range: f64,
-    /// Shape configuration.
-    pub shape: CanyonShape,
+    /// Top of the generated height range, which `below_top` anchors count
+    /// down from.
+    pub fn max_y(&self) -> i32 {
+        self.min_y + self.gen_depth - 1
+    }
+
+    /// Mark a block as carved. Returns whether it already was.
+    fn mark(&mut self, local_x: i32, y: i32, local_z: i32) -> bool {
+        let index = (local_x | local_z << 4 | (y - self.min_y) << 8) as usize;
+        let bit = 1u64 << (index % 64);
+        let carved = self.mask[index / 64] & bit != 0;
+        self.mask[index / 64] |= bit;
+        carved
+    }
+
+    /// Carve the blocks of an ellipsoid inside the chunk (Java's
+    /// `WorldCarver.carveEllipsoid`).
+    ///
+    /// `skip` gets the block's position relative to the centre, scaled by
+    /// the radii, and its Y, and returns whether to leave the block.
+    #[allow(clippy::too_many_arguments)]
+    fn carve_ellipsoid(
+        &mut self,
+        carver: &ConfiguredCarver,
+        x: f64,
+        y: f64,
+        z: f64,
+        horizontal_radius: f64,
+        vertical_radius: f64,
+        skip: impl Fn(f64, f64, f64, i32) -> bool,
+    ) {
+        let (min_x, min_z) = (self.chunk.x * 16, self.chunk.z * 16);
+        let reach = 16.0 + horizontal_radius * 2.0;
+        if (x - (min_x + 7) as f64).abs() > reach || (z - (min_z + 7) as f64).abs() > reach {
+            return;
+        }
+
+        let start_x = ((x - horizontal_radius).floor() as i32 - min_x - 1).max(0);
+        let end_x = ((x + horizontal_radius).floor() as i32 - min_x).min(15);
+        let start_z = ((z - horizontal_radius).floor() as i32 - min_z - 1).max(0);
+        let end_z = ((z + horizontal_radius).floor() as i32 - min_z).min(15);
+        let bottom = ((y - vertical_radius).floor() as i32 - 1).max(self.min_y + 1);
+        let top = ((y + vertical_radius).floor() as i32 + 1).min(self.max_y() - 7);
+
+        for local_x in start_x..=end_x {
+            let rel_x = ((min_x + local_x) as f64 + 0.5 - x) / horizontal_radius;
+            for local_z in start_z..=end_z {
+                let rel_z = ((min_z + local_z) as f64 + 0.5 - z) / horizontal_radius;
+                if rel_x * rel_x + rel_z * rel_z >= 1.0 {
+                    continue;
+                }
+                let mut reached_surface = false;
+                for block_y in (bottom + 1..=top).rev() {
+                    let rel_y = (block_y as f64 - 0.5 - y) / vertical_radius;
+                    if skip(rel_x, rel_y, rel_z, block_y) || self.mark(local_x, block_y, local_z) {
+                        continue;
+                    }
+                    self.carve_block(carver, local_x, block_y, local_z, &mut reached_surface);
+                }
+            }
+        }
+    }
+
+    /// Carve one block (Java's `WorldCarver.carveBlock`).
+    ///
+    /// Dirt left under a carved column that went through grass or mycelium
+    /// gets the surface block.
+    fn carve_block(
+        &mut self,
+        carver: &ConfiguredCarver,
+        local_x: i32,
+        y: i32,
+        local_z: i32,
+        reached_surface: &mut bool,
+    ) {
+        let (lx, lz) = (local_x as u8, local_z as u8);
+        let block = self.chunk.get_block(lx, y as i16, lz, 0);
+        if matches!(
+            region::block_name(block),
+            "minecraft:grass_block" | "minecraft:mycelium"
+        ) {
+            *reached_surface = true;
+        }
+        if !carver
+            .replaceable
+            .iter()
+            .any(|name| region::matches_block(block, name))
+        {
+            return;
+        }
+
+        let (x, z) = (self.chunk.x * 16 + local_x, self.chunk.z * 16 + local_z);
+        let carved = match carver.kind {
+            // NetherWorldCarver ignores the lava level and the aquifer
+            CarverKind::NetherCave(_) if y <= self.min_y + 31 => *blocks::LAVA,
+            CarverKind::NetherCave(_) => *blocks::AIR,
+            _ if y <= carver.lava_level.resolve(self.min_y, self.max_y()) => *blocks::LAVA,
+            _ => match (self.substance)(x, y, z) {
+                Some(block) => block,
+                None => return,
+            },
+        };
+        self.chunk.set_block(lx, y as i16, lz, 0, carved);
+
+        if *reached_surface
+            && y > self.min_y
+            && self.chunk.get_block(lx, (y - 1) as i16, lz, 0) == *blocks::DIRT
+        {
+            let has_fluid = carved != *blocks::AIR;
+            if let Some(top) = self
+                .surface
+                .top_material(self.chunk, x, y - 1, z, has_fluid)
+            {
+                self.chunk.set_block(lx, (y - 1) as i16, lz, 0, top);
+            }
+        }
+    }
 }
 
-/// Canyon shape configuration.
-#[derive(Debug, Clone)]
-pub struct CanyonShape {
-    /// Distance factor.
-    pub distance_factor: f64,
-    /// Thickness.
-    pub thickness: f64,
-    /// Width factor.
-    pub width_smoothness: i32,
-    /// Horizontal radius multiplier.
-    pub horizontal_radius_factor: f64,
-    /// Vertical radius default factor.
-    pub vertical_radius_default_factor: f64,
-    /// Vertical radius center factor.
-    pub vertical_radius_center_factor: f64,
+/// Whether a tunnel at `x, z` with the given steps left can still reach
+/// the chunk being carved (Java's `WorldCarver.canReach`).
+fn can_reach(
+    ctx: &CarvingContext<'_>,
+    x: f64,
+    z: f64,
+    branch_index: i32,
+    branch_count: i32,
+    thickness: f32,
+) -> bool {
+    let dx = x - (ctx.chunk.x * 16 + 7) as f64;
+    let dz = z - (ctx.chunk.z * 16 + 7) as f64;
+    let remaining = (branch_count - branch_index) as f64;
+    let reach = (thickness + 2.0 + 16.0) as f64;
+    dx * dx + dz * dz - remaining * remaining <= reach * reach
 }
 
-impl Default for CanyonCarverConfig {
-    fn default() -> Self {
-        Self {
-            probability: 0.02,
-            min_y: 10,
-            max_y: 67,
-            vertical_rotation_range: 3.0,
-            shape: CanyonShape {
-                distance_factor: 0.0,
-                thickness: 3.0,
-                width_smoothness: 3,
-                horizontal_radius_factor: 1.0,
-                vertical_radius_default_factor: 1.0,
-                vertical_radius_center_factor: 0.0,
-            },
+/// Carve a chunk with the tunnels started up to eight chunks away (Java's
+/// `ChunkGenerator.applyCarvers`).
+///
+/// The carvers of a start chunk are those of its biome at Y 0, filtered by
+/// `selection`. A carver keeps its index in the biome's list either way, so
+/// turning carvers off leaves the others' tunnels where they were.
+pub fn apply_carvers(
+    ctx: &mut CarvingContext<'_>,
+    seed: i64,
+    biome_source: &dyn BiomeSource,
+    selection: CarverSelection,
+) {
+    if selection == CarverSelection::None {
+        return;
+    }
+
+    let (chunk_x, chunk_z) = (ctx.chunk.x, ctx.chunk.z);
+    for dx in -RANGE..=RANGE {
+        for dz in -RANGE..=RANGE {
+            let (start_x, start_z) = (chunk_x + dx, chunk_z + dz);
+            let biome = biome_source.get_biome(start_x * 16, 0, start_z * 16);
+            let Some(features) = feature::biome_features(biome) else {
+                continue;
+            };
+            for (index, name) in features.carvers().iter().enumerate() {
+                let Some(carver) = configured_carver(name) else {
+                    continue;
+                };
+                if !selection.includes(name, carver) {
+                    continue;
+                }
+                let mut random =
+                    large_feature_random(seed.wrapping_add(index as i64), start_x, start_z);
+                if carver.is_start_chunk(&mut random) {
+                    carver.carve(ctx, &mut random, start_x, start_z);
+                }
+            }
         }
     }
 }
@@ -137,18 +401,149 @@ impl Default for CanyonCarverConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::world::generator::{Biome, BiomeNoise};
+    use crate::world::{WorldConfig, WorldGenerator};
+    use unastar_noise::build_vanilla_surface_rule;
+
+    /// Every column plains, so every chunk runs the overworld carvers.
+    struct Plains;
+
+    impl BiomeSource for Plains {
+        fn get_biome(&self, _x: i32, _y: i32, _z: i32) -> Biome {
+            Biome::Plains
+        }
+    }
+
+    /// A chunk of stone from Y -64 to 63.
+    fn stone_chunk(chunk_x: i32, chunk_z: i32) -> Chunk {
+        let mut chunk = Chunk::new(chunk_x, chunk_z);
+        for x in 0..16 {
+            for z in 0..16 {
+                for y in -64..64 {
+                    chunk.set_block(x, y, z, 0, *blocks::STONE);
+                }
+            }
+        }
+        chunk
+    }
+
+    fn carve(chunk: &mut Chunk, seed: i64, selection: CarverSelection) {
+        let surface = SurfaceSystem::new(
+            seed,
+            build_vanilla_surface_rule(seed),
+            BiomeNoise::from_seed(seed),
+        );
+        let mut substance = |_: i32, _: i32, _: i32| Some(*blocks::AIR);
+        let mut ctx = CarvingContext::new(chunk, -64, 384, &surface, &mut substance);
+        apply_carvers(&mut ctx, seed, &Plains, selection);
+    }
+
+    fn count(chunk: &Chunk, block: u32) -> usize {
+        let mut count = 0;
+        for x in 0..16 {
+            for z in 0..16 {
+                for y in -64..64 {
+                    count += (chunk.get_block(x, y, z, 0) == block) as usize;
+                }
+            }
+        }
+        count
+    }
 
     #[test]
-    fn test_cave_config_default() {
-        let config = CaveCarverConfig::default();
-        assert!(config.probability > 0.0 && config.probability < 1.0);
-        assert!(config.min_y < config.max_y);
+    fn test_carver_selection() {
+        let cave = configured_carver("cave").unwrap();
+        let canyon = configured_carver("canyon").unwrap();
+        assert!(CarverSelection::All.includes("cave", cave));
+        assert!(CarverSelection::Caves.includes("cave", cave));
+        assert!(!CarverSelection::Caves.includes("canyon", canyon));
+        assert!(CarverSelection::Canyons.includes("canyon", canyon));
+        assert!(!CarverSelection::None.includes("cave", cave));
+
+        let set = CarverSet::from_names(["minecraft:cave_extra_underground", "canyon"]).unwrap();
+        let only = CarverSelection::Only(set);
+        assert!(only.includes("minecraft:canyon", canyon));
+        assert!(only.includes("cave_extra_underground", cave));
+        assert!(!only.includes("cave", cave));
+        assert_eq!(
+            set.names().collect::<Vec<_>>(),
+            ["canyon", "cave_extra_underground"]
+        );
+        assert_eq!(CarverSet::from_names(["cave", "ravine"]), Err("ravine"));
     }
 
     #[test]
-    fn test_canyon_config_default() {
-        let config = CanyonCarverConfig::default();
-        assert!(config.probability > 0.0 && config.probability < 1.0);
-        assert!(config.min_y < config.max_y);
+    fn test_carver_set_config() {
+        let parse = |carvers: &str| {
+            toml_edit::de::from_str::<WorldConfig>(&format!(
+                "[generator]\nkind = \"vanilla\"\nseed = 1\ncarvers = {carvers}"
+            ))
+        };
+        let config = parse(r#"{ only = ["minecraft:cave", "canyon"] }"#).unwrap();
+        let set = CarverSet::from_names(["cave", "canyon"]).unwrap();
+        assert_eq!(
+            config.generator,
+            WorldGenerator::Vanilla {
+                seed: 1,
+                carvers: CarverSelection::Only(set)
+            }
+        );
+
+        // Saved world settings read back the same
+        let saved = toml_edit::ser::to_string_pretty(&config).unwrap();
+        assert_eq!(
+            toml_edit::de::from_str::<WorldConfig>(&saved).unwrap(),
+            config
+        );
+
+        assert!(parse(r#"{ only = ["ravine"] }"#).is_err());
+        assert!(parse(r#""caves""#).is_ok());
+    }
+
+    #[test]
+    fn test_apply_carvers() {
+        let mut carved = stone_chunk(0, 0);
+        carve(&mut carved, 12345, CarverSelection::All);
+        let air = count(&carved, *blocks::AIR);
+        assert!(air > 0, "no caves carved");
+
+        // Deterministic, and each block is carved once whatever the order
+        let mut again = stone_chunk(0, 0);
+        carve(&mut again, 12345, CarverSelection::All);
+        assert_eq!(count(&again, *blocks::AIR), air);
+
+        // Lava below the lava level (above_bottom 8), never bedrock-deep
+        for x in 0..16 {
+            for z in 0..16 {
+                assert_eq!(carved.get_block(x, -64, z, 0), *blocks::STONE);
+                for y in -55..64 {
+                    assert_ne!(carved.get_block(x, y, z, 0), *blocks::LAVA);
+                }
+            }
+        }
+
+        let mut untouched = stone_chunk(0, 0);
+        carve(&mut untouched, 12345, CarverSelection::None);
+        assert_eq!(count(&untouched, *blocks::AIR), 0);
+    }
+
+    #[test]
+    fn test_aquifer_barrier() {
+        // A substance of None (an aquifer barrier) keeps every block
+        let mut chunk = stone_chunk(0, 0);
+        let surface =
+            SurfaceSystem::new(1, build_vanilla_surface_rule(1), BiomeNoise::from_seed(1));
+        let mut substance = |_: i32, y: i32, _: i32| (y < 0).then_some(*blocks::WATER);
+        let mut ctx = CarvingContext::new(&mut chunk, -64, 384, &surface, &mut substance);
+        apply_carvers(&mut ctx, 12345, &Plains, CarverSelection::All);
+
+        assert!(count(&chunk, *blocks::WATER) > 0);
+        for x in 0..16 {
+            for z in 0..16 {
+                for y in 0..64 {
+                    assert_eq!(chunk.get_block(x, y, z, 0), *blocks::STONE);
+                }
+            }
+        }
     }
 }
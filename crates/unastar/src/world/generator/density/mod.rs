@@ -35,6 +35,9 @@ pub use unastar_noise::{
     compute_vein_gap, compute_vein_ridged, compute_vein_toggle,
 };
 
+// Nether and End routers (same item names, so kept in their own modules)
+pub use unastar_noise::{end_compiled, nether_compiled};
+
 // Re-export context types
 pub use context::{ContextProvider, SinglePointContext};
 
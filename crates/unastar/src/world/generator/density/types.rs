@@ -3,7 +3,7 @@
 //! The NoiseRegistry is the only type kept here - all other types
 //! are now provided by the unastar_noise crate.
 
-use crate::world::generator::noise::{DoublePerlinNoise, BlendedNoise};
+use crate::world::generator::noise::{BlendedNoise, DoublePerlinNoise, EndIslandNoise};
 use crate::world::generator::xoroshiro::Xoroshiro128;
 use std::simd::prelude::*;
 
@@ -18,12 +18,30 @@ pub struct NoiseRegistry {
     noises: Vec<DoublePerlinNoise>,
     /// BlendedNoise for base_3d_noise (OldBlendedNoise)
     blended_noise: BlendedNoise,
+    /// Island height field for the End's `end_islands` density function
+    end_islands: EndIslandNoise,
     seed: i64,
 }
 
 impl NoiseRegistry {
     /// Create a new noise registry with all noises instantiated from the seed.
+    ///
+    /// The base 3D noise uses the overworld's parameters (from worldgen JSON):
+    /// xz_scale=0.25, y_scale=0.125, xz_factor=80.0, y_factor=160.0, smear_scale_multiplier=8.0
     pub fn new(seed: i64) -> Self {
+        Self::with_base_3d_noise(seed, 0.25, 0.125, 80.0, 160.0, 8.0)
+    }
+
+    /// Create a noise registry whose base 3D noise (OldBlendedNoise) uses the
+    /// given parameters, as read from a dimension's `base_3d_noise` entry.
+    pub fn with_base_3d_noise(
+        seed: i64,
+        xz_scale: f64,
+        y_scale: f64,
+        xz_factor: f64,
+        y_factor: f64,
+        smear_scale_multiplier: f64,
+    ) -> Self {
         // Pre-allocate Vec with exact size
         let mut noises = Vec::with_capacity(NoiseRef::COUNT);
 
@@ -40,18 +58,23 @@ impl NoiseRegistry {
         // Use a specific seed salt for BlendedNoise
         let blended_seed = seed.wrapping_mul(31).wrapping_add(1000);
         let mut blended_rng = Xoroshiro128::from_seed(blended_seed);
-        // Default parameters for overworld base_3d_noise (from worldgen JSON):
-        // xz_scale=0.25, y_scale=0.125, xz_factor=80.0, y_factor=160.0, smear_scale_multiplier=8.0
         let blended_noise = BlendedNoise::new(
             &mut blended_rng,
-            0.25,   // xz_scale
-            0.125,  // y_scale
-            80.0,   // xz_factor
-            160.0,  // y_factor
-            8.0,    // smear_scale_multiplier
+            xz_scale,
+            y_scale,
+            xz_factor,
+            y_factor,
+            smear_scale_multiplier,
         );
 
-        Self { noises, blended_noise, seed }
+        let end_islands = EndIslandNoise::new(seed);
+
+        Self {
+            noises,
+            blended_noise,
+            end_islands,
+            seed,
+        }
     }
 
     /// Get a noise by reference - O(1) array index.
@@ -97,4 +120,9 @@ impl NoiseSource for NoiseRegistry {
     fn sample_blended_noise_4(&self, x: f64, y: f64x4, z: f64, _xz_scale: f64, _y_scale: f64, _xz_factor: f64, _y_factor: f64, _smear_scale_multiplier: f64) -> f64x4 {
         self.blended_noise.sample_4(x, y, z)
     }
+
+    #[inline]
+    fn sample_end_islands(&self, x: i32, z: i32) -> f64 {
+        self.end_islands.sample(x, z)
+    }
 }
@@ -0,0 +1,397 @@
+//! Feature decoration: trees, ores, flowers, lakes and the rest of each
+//! biome's placed features.
+//!
+//! This module re-exports the generated feature types from `unastar_noise`
+//! and places them into chunks, following Java's
+//! `ChunkGenerator.applyBiomeDecoration`:
+//!
+//! 1. Each chunk gets a decoration seed from the world seed and its position
+//! 2. For every generation step, the placed features of the biomes around
+//!    the chunk run in order, each from a seed derived from the decoration
+//!    seed, the feature and the step
+//! 3. Placement modifiers turn the chunk origin into positions, and the
+//!    configured feature is placed at each
+//!
+//! Jigsaw structures crossing the chunk are placed at the start of their
+//! generation step, before its features (see [`jigsaw`](super::jigsaw)).
+//!
+//! ## Neighbouring chunks
+//!
+//! Features may write into the chunks around the one they start in. Each
+//! chunk is decorated once, over the proto chunks (terrain without
+//! features) of the [`DecorationRegion`] around it, and the blocks it sets
+//! are kept as a [`Decoration`]. A chunk is finished by applying the blocks
+//! its own decoration and its eight neighbours' set in it, so a tree on a
+//! chunk border is placed once and written into both chunks.
+//!
+//! Unlike Java, features started in different chunks don't see each
+//! other's blocks. Where they overlap, the decorations are applied from the
+//! north-west neighbour to the south-east one, and the later block wins.
+//!
+//! ## Java Parity
+//!
+//! Feature seeds use the legacy random and each feature's index in
+//! `ALL_PLACED_FEATURES`, so positions differ from Java for the same seed.
+//! Blocks are placed in their default Bedrock state, and tree decorators
+//! (vines, cocoa, beehives) are skipped.
+
+mod place;
+mod placement;
+pub mod region;
+mod tree;
+
+pub(crate) use place::{mth_cos, mth_sin};
+pub use region::{BlockWrite, DecorationRegion};
+pub use unastar_noise::feature::*;
+pub use unastar_noise::{
+    ALL_PLACED_FEATURES, BiomeFeatures, GenerationStep, configured_feature, placed_feature,
+};
+use unastar_noise::{JIGSAW_STRUCTURES, jigsaw_structure};
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use glam::IVec3;
+use unastar_noise::DoublePerlinNoise;
+
+use super::jigsaw::StructureStart;
+use super::xoroshiro::{JavaRandom, Xoroshiro128};
+use crate::world::chunk::Chunk;
+use crate::world::generator::{Biome, BiomeSource};
+
+/// Generation steps in the order they run.
+const STEPS: [GenerationStep; 11] = [
+    GenerationStep::RawGeneration,
+    GenerationStep::Lakes,
+    GenerationStep::LocalModifications,
+    GenerationStep::UndergroundStructures,
+    GenerationStep::SurfaceStructures,
+    GenerationStep::Strongholds,
+    GenerationStep::UndergroundOres,
+    GenerationStep::UndergroundDecoration,
+    GenerationStep::FluidSprings,
+    GenerationStep::VegetalDecoration,
+    GenerationStep::TopLayerModification,
+];
+
+/// The blocks set by one chunk's features and structure pieces, in that
+/// chunk and the eight around it.
+pub struct Decoration {
+    /// Blocks set in each chunk of the region, in row-major order.
+    writes: Vec<Vec<BlockWrite>>,
+}
+
+impl Decoration {
+    /// Set the blocks placed in the chunk `(dx, dz)` chunks away from the
+    /// decorated one.
+    pub fn apply(&self, dx: i32, dz: i32, chunk: &mut Chunk) {
+        debug_assert!(dx.abs() <= 1 && dz.abs() <= 1);
+        for write in &self.writes[((dz + 1) * 3 + dx + 1) as usize] {
+            chunk.set_block(write.x, write.y, write.z, 0, write.block);
+        }
+    }
+}
+
+/// Decorate the centre chunk of a region.
+///
+/// `structures` are the structure starts crossing the centre chunk.
+pub fn decorate(
+    region: DecorationRegion,
+    seed: i64,
+    biome_source: &dyn BiomeSource,
+    structures: &[Arc<StructureStart>],
+) -> Decoration {
+    let (center_x, center_z) = region.center();
+    let mut ctx = FeatureContext::new(region, biome_source);
+    ctx.structures = structures;
+    ctx.decorate_chunk(seed, center_x, center_z);
+    Decoration {
+        writes: ctx.region.into_writes(),
+    }
+}
+
+/// Feature lists of a biome, matched by its vanilla name.
+pub(super) fn biome_features(biome: Biome) -> Option<BiomeFeatures> {
+    // `WindsweptHills` -> `windswept_hills`
+    let mut name = String::new();
+    for (i, c) in format!("{biome:?}").chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        } else {
+            name.push(c);
+        }
+    }
+    BiomeFeatures::from_name(&name)
+}
+
+/// State shared by everything placed while decorating a region.
+pub struct FeatureContext<'a> {
+    /// Blocks of the chunks being decorated.
+    pub region: DecorationRegion,
+    /// Random of the feature being placed.
+    pub random: JavaRandom,
+    biome_source: &'a dyn BiomeSource,
+    /// Structure starts crossing the region.
+    structures: &'a [Arc<StructureStart>],
+    /// Biomes by quart position.
+    biomes: HashMap<IVec3, Biome>,
+    /// Noises of noise-based state providers, by seed.
+    noises: HashMap<i64, DoublePerlinNoise>,
+    /// Top-level placed feature being placed, for the biome filter.
+    top_feature: Option<&'static str>,
+}
+
+impl<'a> FeatureContext<'a> {
+    pub fn new(region: DecorationRegion, biome_source: &'a dyn BiomeSource) -> Self {
+        Self {
+            region,
+            random: JavaRandom::from_seed(0),
+            biome_source,
+            structures: &[],
+            biomes: HashMap::new(),
+            noises: HashMap::new(),
+            top_feature: None,
+        }
+    }
+
+    /// Run every generation step's features for one chunk of the region.
+    pub fn decorate_chunk(&mut self, seed: i64, chunk_x: i32, chunk_z: i32) {
+        let origin = IVec3::new(chunk_x * 16, self.region.min_y(), chunk_z * 16);
+
+        // Java's WorldgenRandom.setDecorationSeed
+        let mut random = JavaRandom::from_seed(seed);
+        let a = random.next_long() | 1;
+        let b = random.next_long() | 1;
+        let decoration_seed = (origin.x as i64)
+            .wrapping_mul(a)
+            .wrapping_add((origin.z as i64).wrapping_mul(b))
+            ^ seed;
+
+        let features = self.nearby_biome_features(chunk_x, chunk_z);
+        for step in STEPS {
+            self.place_structures(seed, decoration_seed, step, chunk_x, chunk_z);
+
+            // Features of all nearby biomes, in biome list order
+            let mut names: Vec<&'static str> = Vec::new();
+            for biome in &features {
+                for name in biome.get_features(step) {
+                    if !names.contains(name) {
+                        names.push(name);
+                    }
+                }
+            }
+
+            for name in names {
+                let Some(feature) = placed_feature(name) else {
+                    continue;
+                };
+                // Java's WorldgenRandom.setFeatureSeed
+                let index = ALL_PLACED_FEATURES.binary_search(&name).unwrap_or(0) as i64;
+                self.random.set_seed(
+                    decoration_seed
+                        .wrapping_add(index)
+                        .wrapping_add(10000 * step as i64),
+                );
+                self.top_feature = Some(name);
+                placement::place(self, feature, origin);
+            }
+        }
+        self.top_feature = None;
+    }
+
+    /// Place the parts of the structures of a generation step inside a
+    /// chunk. Each structure gets a feature seed from its index among the
+    /// step's structures.
+    fn place_structures(
+        &mut self,
+        seed: i64,
+        decoration_seed: i64,
+        step: GenerationStep,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) {
+        if self.structures.is_empty() {
+            return;
+        }
+        let structures = self.structures;
+        self.top_feature = None;
+
+        let step_structures = JIGSAW_STRUCTURES
+            .iter()
+            .filter(|name| jigsaw_structure(name).is_some_and(|s| s.step == step));
+        for (index, name) in step_structures.enumerate() {
+            self.random.set_seed(
+                decoration_seed
+                    .wrapping_add(index as i64)
+                    .wrapping_add(10000 * step as i64),
+            );
+            for start in structures.iter().filter(|start| start.structure == *name) {
+                start.place_in_chunk(self, seed, chunk_x, chunk_z);
+            }
+        }
+    }
+
+    /// Place a placed feature by name, as jigsaw feature pieces do.
+    pub fn place_feature(&mut self, name: &str, pos: IVec3) -> bool {
+        match placed_feature(name) {
+            Some(feature) => placement::place(self, feature, pos),
+            None => false,
+        }
+    }
+
+    /// Feature lists of the biomes in and around a chunk, in biome ID order.
+    fn nearby_biome_features(&mut self, chunk_x: i32, chunk_z: i32) -> Vec<BiomeFeatures> {
+        let mut biomes = Vec::new();
+        for dz in -1..=1 {
+            for dx in -1..=1 {
+                let x = (chunk_x + dx) * 16 + 8;
+                let z = (chunk_z + dz) * 16 + 8;
+                for y in (self.region.min_y()..=self.region.max_y()).step_by(64) {
+                    let biome = self.biome_at(IVec3::new(x, y, z));
+                    if !biomes.contains(&biome) {
+                        biomes.push(biome);
+                    }
+                }
+            }
+        }
+        biomes.sort_by_key(|biome| *biome as u8);
+
+        let mut features = Vec::new();
+        for biome in biomes {
+            if let Some(biome_features) = biome_features(biome)
+                && !features.contains(&biome_features)
+            {
+                features.push(biome_features);
+            }
+        }
+        features
+    }
+
+    /// Biome at a block position, sampled once per quart.
+    pub fn biome_at(&mut self, pos: IVec3) -> Biome {
+        let quart = pos >> 2;
+        *self.biomes.entry(quart).or_insert_with(|| {
+            self.biome_source
+                .get_biome(quart.x << 2, quart.y << 2, quart.z << 2)
+        })
+    }
+
+    /// Whether the biome at a position lists the top-level feature (Java's
+    /// `BiomeFilter`). Nested features always pass.
+    pub fn biome_has_top_feature(&mut self, pos: IVec3) -> bool {
+        let Some(name) = self.top_feature else {
+            return true;
+        };
+        let Some(features) = biome_features(self.biome_at(pos)) else {
+            return false;
+        };
+        STEPS
+            .iter()
+            .any(|step| features.get_features(*step).contains(&name))
+    }
+
+    /// Sample the noise of a noise-based state provider at a scaled position.
+    pub fn noise_value(
+        &mut self,
+        seed: i64,
+        noise: &NoiseParameters,
+        pos: IVec3,
+        scale: f64,
+    ) -> f64 {
+        let noise = self.noises.entry(seed).or_insert_with(|| {
+            let mut random = Xoroshiro128::from_seed(seed);
+            DoublePerlinNoise::new(&mut random, noise.amplitudes, noise.first_octave)
+        });
+        noise.sample(
+            pos.x as f64 * scale,
+            pos.y as f64 * scale,
+            pos.z as f64 * scale,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::chunk::blocks;
+
+    struct PlainsOnly;
+
+    impl BiomeSource for PlainsOnly {
+        fn get_biome(&self, _x: i32, _y: i32, _z: i32) -> Biome {
+            Biome::Plains
+        }
+    }
+
+    /// Stone up to Y=63 under a layer of grass, around chunk (0, 0).
+    fn flat_region() -> DecorationRegion {
+        let chunks = (0..9)
+            .map(|i| {
+                let mut chunk = Chunk::new(i % 3 - 1, i / 3 - 1);
+                for x in 0..16 {
+                    for z in 0..16 {
+                        for y in -64..64 {
+                            chunk.set_block(x, y, z, 0, *blocks::STONE);
+                        }
+                        chunk.set_block(x, 64, z, 0, *blocks::GRASS_BLOCK);
+                    }
+                }
+                chunk
+            })
+            .collect();
+        DecorationRegion::new(0, 0, chunks, -64, 319)
+    }
+
+    #[test]
+    fn test_biome_features_by_name() {
+        assert_eq!(biome_features(Biome::Plains), Some(BiomeFeatures::Plains));
+        assert_eq!(
+            biome_features(Biome::WindsweptHills),
+            Some(BiomeFeatures::WindsweptHills)
+        );
+    }
+
+    #[test]
+    fn test_place_tree() {
+        let mut ctx = FeatureContext::new(flat_region(), &PlainsOnly);
+        let oak = configured_feature("oak").unwrap();
+        let pos = IVec3::new(8, 65, 8);
+
+        assert!(place::place(&mut ctx, oak, pos));
+        assert_eq!(ctx.region.get_block(pos), *blocks::OAK_LOG);
+        // Grass under the trunk turns to dirt
+        assert_eq!(ctx.region.get_block(pos - IVec3::Y), *blocks::DIRT);
+        assert_eq!(
+            ctx.region.get_block(pos + IVec3::new(1, 3, 0)),
+            *blocks::OAK_LEAVES
+        );
+    }
+
+    #[test]
+    fn test_decorate_chunk() {
+        // Only the blocks the features set
+        let mut chunk = Chunk::new(0, 0);
+        decorate(flat_region(), 12345, &PlainsOnly, &[]).apply(0, 0, &mut chunk);
+
+        let mut ores = 0;
+        let mut grass = 0;
+        for x in 0..16 {
+            for z in 0..16 {
+                for y in -64..64 {
+                    let block = chunk.get_block(x, y, z, 0);
+                    if region::block_name(block).ends_with("_ore") {
+                        ores += 1;
+                    }
+                }
+                if chunk.get_block(x, 65, z, 0) == *blocks::GRASS {
+                    grass += 1;
+                }
+            }
+        }
+        assert!(ores > 0, "no ores placed");
+        assert!(grass > 0, "no grass placed");
+    }
+}
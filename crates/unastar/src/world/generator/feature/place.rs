@@ -0,0 +1,625 @@
+//! Configured feature placement.
+//!
+//! Ports of Java's `Feature.place` for the supported feature types. Block
+//! states come from the feature JSON by Java name and are resolved to
+//! Bedrock runtime IDs when set; blocks Bedrock doesn't have are skipped.
+
+use glam::IVec3;
+
+use super::region::{self, DecorationRegion};
+use super::{
+    BlockState, BlockStateProvider, ConfiguredFeature, DiskConfig, FeatureContext, LakeConfig,
+    OreConfig, OreTarget, RandomPatchConfig, SpringConfig, pick_weighted, placement, tree,
+};
+use crate::world::chunk::blocks;
+
+/// Horizontal neighbours, in Java's `Direction` order (N, S, W, E).
+const HORIZONTAL: [IVec3; 4] = [
+    IVec3::new(0, 0, -1),
+    IVec3::new(0, 0, 1),
+    IVec3::new(-1, 0, 0),
+    IVec3::new(1, 0, 0),
+];
+
+/// Java's `Mth.sin`, which reads a 65536-entry table.
+pub(crate) fn mth_sin(value: f32) -> f32 {
+    let index = (value * 10430.378) as i32 & 65535;
+    (index as f64 * std::f64::consts::PI * 2.0 / 65536.0).sin() as f32
+}
+
+/// Java's `Mth.cos`.
+pub(crate) fn mth_cos(value: f32) -> f32 {
+    let index = (value * 10430.378 + 16384.0) as i32 & 65535;
+    (index as f64 * std::f64::consts::PI * 2.0 / 65536.0).sin() as f32
+}
+
+/// Place a configured feature at a position. Returns whether anything was
+/// placed.
+pub fn place(ctx: &mut FeatureContext, feature: &ConfiguredFeature, pos: IVec3) -> bool {
+    match feature {
+        ConfiguredFeature::Tree(config) => tree::place(ctx, config, pos),
+        ConfiguredFeature::Ore(config) => place_ore(ctx, config, pos),
+        ConfiguredFeature::ScatteredOre(config) => place_scattered_ore(ctx, config, pos),
+        ConfiguredFeature::RandomPatch(config) => place_random_patch(ctx, config, pos),
+        ConfiguredFeature::Disk(config) => place_disk(ctx, config, pos),
+        ConfiguredFeature::Lake(config) => place_lake(ctx, config, pos),
+        ConfiguredFeature::Spring(config) => place_spring(ctx, config, pos),
+        ConfiguredFeature::SimpleBlock { to_place } => place_simple_block(ctx, to_place, pos),
+        ConfiguredFeature::RandomSelector { features, default } => {
+            for (chance, feature) in *features {
+                if ctx.random.next_float() < *chance {
+                    return placement::place(ctx, feature, pos);
+                }
+            }
+            placement::place(ctx, default, pos)
+        }
+        ConfiguredFeature::SimpleRandomSelector(features) => {
+            if features.is_empty() {
+                return false;
+            }
+            let index = ctx.random.next_int(features.len() as u32) as usize;
+            placement::place(ctx, features[index], pos)
+        }
+        ConfiguredFeature::RandomBooleanSelector {
+            feature_true,
+            feature_false,
+        } => {
+            let feature = if ctx.random.next_int(2) != 0 {
+                feature_true
+            } else {
+                feature_false
+            };
+            placement::place(ctx, feature, pos)
+        }
+        ConfiguredFeature::Unsupported(_) => false,
+    }
+}
+
+/// Pick a block state from a provider (Java's `BlockStateProvider.getState`).
+pub(super) fn get_state(
+    ctx: &mut FeatureContext,
+    provider: &BlockStateProvider,
+    pos: IVec3,
+) -> Option<BlockState> {
+    match *provider {
+        BlockStateProvider::Simple(state) | BlockStateProvider::Rotated(state) => Some(state),
+        BlockStateProvider::Weighted(entries) => pick_weighted(&mut ctx.random, entries).copied(),
+        BlockStateProvider::RandomizedInt(source) => get_state(ctx, source, pos),
+        BlockStateProvider::NoiseThreshold {
+            seed,
+            noise,
+            scale,
+            threshold,
+            high_chance,
+            default_state,
+            low_states,
+            high_states,
+        } => {
+            let value = ctx.noise_value(seed, &noise, pos, scale as f64);
+            if value < threshold as f64 {
+                pick(ctx, low_states)
+            } else if ctx.random.next_float() < high_chance {
+                pick(ctx, high_states)
+            } else {
+                Some(default_state)
+            }
+        }
+        BlockStateProvider::Noise {
+            seed,
+            noise,
+            scale,
+            states,
+        } => {
+            let value = ctx.noise_value(seed, &noise, pos, scale as f64);
+            let index = ((1.0 + value) / 2.0).clamp(0.0, 0.9999) * states.len() as f64;
+            states.get(index as usize).copied()
+        }
+        BlockStateProvider::Unsupported => None,
+    }
+}
+
+/// Java's `Util.getRandom`.
+fn pick(ctx: &mut FeatureContext, states: &[BlockState]) -> Option<BlockState> {
+    if states.is_empty() {
+        return None;
+    }
+    Some(states[ctx.random.next_int(states.len() as u32) as usize])
+}
+
+/// Set a block from the feature JSON. Returns false if Bedrock has no
+/// such block or the position is outside the region.
+pub(super) fn set_state(region: &mut DecorationRegion, pos: IVec3, state: &BlockState) -> bool {
+    match region::resolve_block(state.name) {
+        Some(block) => region.set_block(pos, block),
+        None => false,
+    }
+}
+
+/// Plants that grow on dirt, grass and farmland.
+const DIRT_PLANTS: &[&str] = &[
+    "short_grass",
+    "grass",
+    "fern",
+    "tall_grass",
+    "large_fern",
+    "dandelion",
+    "poppy",
+    "blue_orchid",
+    "allium",
+    "azure_bluet",
+    "red_tulip",
+    "orange_tulip",
+    "white_tulip",
+    "pink_tulip",
+    "oxeye_daisy",
+    "cornflower",
+    "lily_of_the_valley",
+    "torchflower",
+    "open_eyeblossom",
+    "closed_eyeblossom",
+    "sunflower",
+    "lilac",
+    "rose_bush",
+    "peony",
+    "pitcher_plant",
+    "sweet_berry_bush",
+    "pink_petals",
+    "wildflowers",
+    "bush",
+    "firefly_bush",
+    "mangrove_propagule",
+];
+
+/// Whether a block (by Java name) can stay at a position (Java's
+/// `BlockState.canSurvive`), for plants and other blocks that need support.
+pub fn can_survive(region: &DecorationRegion, name: &str, pos: IVec3) -> bool {
+    let below = region.get_block(pos - IVec3::Y);
+    let below_name = region::block_name(below);
+    let on_dirt = region::is_dirt(below) || below_name == "minecraft:farmland";
+    let on_sand = region::in_tag(below, "minecraft:sand");
+
+    match name.strip_prefix("minecraft:").unwrap_or(name) {
+        "sugar_cane" => {
+            below_name == "minecraft:reeds"
+                || (on_dirt || on_sand)
+                    && HORIZONTAL
+                        .iter()
+                        .any(|side| region::is_water(region.get_block(pos - IVec3::Y + *side)))
+        }
+        "cactus" => {
+            (below_name == "minecraft:cactus" || on_sand)
+                && HORIZONTAL.iter().all(|side| {
+                    let block = region.get_block(pos + *side);
+                    !region::is_solid(block) && !region::is_lava(block)
+                })
+        }
+        "lily_pad" => region::is_water(below) && region.is_air(pos),
+        "dead_bush" | "short_dry_grass" | "tall_dry_grass" => {
+            on_dirt || on_sand || below_name.ends_with("terracotta")
+        }
+        "crimson_fungus" | "warped_fungus" | "crimson_roots" | "warped_roots"
+        | "nether_sprouts" => {
+            on_dirt
+                || matches!(
+                    below_name,
+                    "minecraft:crimson_nylium" | "minecraft:warped_nylium" | "minecraft:soul_soil"
+                )
+        }
+        "brown_mushroom" | "red_mushroom" => {
+            matches!(below_name, "minecraft:mycelium" | "minecraft:podzol")
+                || region::is_solid(below)
+        }
+        "snow" => {
+            region::is_solid(below)
+                && !matches!(
+                    below_name,
+                    "minecraft:ice" | "minecraft:packed_ice" | "minecraft:barrier"
+                )
+        }
+        plant if plant.ends_with("_sapling") || DIRT_PLANTS.contains(&plant) => on_dirt,
+        // Full blocks stand anywhere, everything else needs a solid floor
+        _ => region::resolve_block(name).is_some_and(region::is_solid) || region::is_solid(below),
+    }
+}
+
+/// Java's `SimpleBlockFeature`.
+fn place_simple_block(ctx: &mut FeatureContext, to_place: &BlockStateProvider, pos: IVec3) -> bool {
+    let Some(state) = get_state(ctx, to_place, pos) else {
+        return false;
+    };
+    if !can_survive(&ctx.region, state.name, pos) {
+        return false;
+    }
+    let Some(block) = region::resolve_block(state.name) else {
+        return false;
+    };
+
+    if let Some(upper) = region::double_plant_upper(block) {
+        if !ctx.region.is_air(pos + IVec3::Y) {
+            return false;
+        }
+        ctx.region.set_block(pos + IVec3::Y, upper);
+    }
+    ctx.region.set_block(pos, block);
+    true
+}
+
+/// Java's `RandomPatchFeature`.
+fn place_random_patch(ctx: &mut FeatureContext, config: &RandomPatchConfig, pos: IVec3) -> bool {
+    let xz = (config.xz_spread + 1) as u32;
+    let y = (config.y_spread + 1) as u32;
+
+    let mut placed = 0;
+    for _ in 0..config.tries {
+        let dx = ctx.random.next_int(xz) - ctx.random.next_int(xz);
+        let dy = ctx.random.next_int(y) - ctx.random.next_int(y);
+        let dz = ctx.random.next_int(xz) - ctx.random.next_int(xz);
+        if placement::place(ctx, config.feature, pos + IVec3::new(dx, dy, dz)) {
+            placed += 1;
+        }
+    }
+    placed > 0
+}
+
+/// Java's `OreConfiguration.canPlaceOre`: the target matches, and the ore
+/// isn't exposed to air unless the exposure check is skipped.
+fn can_place_ore(
+    ctx: &mut FeatureContext,
+    config: &OreConfig,
+    target: &OreTarget,
+    block: u32,
+    pos: IVec3,
+) -> bool {
+    if !region::rule_test(&target.target, block, &mut ctx.random) {
+        return false;
+    }
+
+    let chance = config.discard_chance_on_air_exposure;
+    let skip_air_check = if chance <= 0.0 {
+        true
+    } else if chance >= 1.0 {
+        false
+    } else {
+        ctx.random.next_float() >= chance
+    };
+    if skip_air_check {
+        return true;
+    }
+
+    let adjacent_to_air = [IVec3::NEG_Y, IVec3::Y]
+        .iter()
+        .chain(HORIZONTAL.iter())
+        .any(|side| ctx.region.is_air(pos + *side));
+    !adjacent_to_air
+}
+
+/// Replace the block at a position with the first matching ore target.
+fn try_place_ore(ctx: &mut FeatureContext, config: &OreConfig, pos: IVec3) -> bool {
+    let block = ctx.region.get_block(pos);
+    for target in config.targets {
+        if can_place_ore(ctx, config, target, block, pos) {
+            return set_state(&mut ctx.region, pos, &target.state);
+        }
+    }
+    false
+}
+
+/// Java's `OreFeature`: a blob of `size` spheres strung along a line.
+fn place_ore(ctx: &mut FeatureContext, config: &OreConfig, pos: IVec3) -> bool {
+    let angle = ctx.random.next_float() * std::f32::consts::PI;
+    let spread = config.size as f32 / 8.0;
+    let margin = ((config.size as f32 / 16.0 * 2.0 + 1.0) / 2.0).ceil() as i32;
+    let min_x = pos.x as f64 + (angle as f64).sin() * spread as f64;
+    let max_x = pos.x as f64 - (angle as f64).sin() * spread as f64;
+    let min_z = pos.z as f64 + (angle as f64).cos() * spread as f64;
+    let max_z = pos.z as f64 - (angle as f64).cos() * spread as f64;
+    let min_y = (pos.y + ctx.random.next_int(3) - 2) as f64;
+    let max_y = (pos.y + ctx.random.next_int(3) - 2) as f64;
+
+    let start_x = pos.x - (spread.ceil() as i32) - margin;
+    let start_y = pos.y - 2 - margin;
+    let start_z = pos.z - (spread.ceil() as i32) - margin;
+    let width = 2 * (spread.ceil() as i32 + margin);
+    let height = 2 * (2 + margin);
+
+    // Only place if some of the bounding box is at or below the surface
+    for x in start_x..=start_x + width {
+        for z in start_z..=start_z + width {
+            if start_y <= ctx.region.height(super::Heightmap::OceanFloorWg, x, z) {
+                let line = [min_x, max_x, min_y, max_y, min_z, max_z];
+                let origin = IVec3::new(start_x, start_y, start_z);
+                return place_ore_spheres(ctx, config, line, origin, width, height);
+            }
+        }
+    }
+    false
+}
+
+/// Java's `OreFeature.doPlace`.
+fn place_ore_spheres(
+    ctx: &mut FeatureContext,
+    config: &OreConfig,
+    [min_x, max_x, min_y, max_y, min_z, max_z]: [f64; 6],
+    origin: IVec3,
+    width: i32,
+    height: i32,
+) -> bool {
+    let size = config.size.max(0) as usize;
+
+    // Sphere centres and radii along the line
+    let mut spheres = vec![[0.0f64; 4]; size];
+    for (i, sphere) in spheres.iter_mut().enumerate() {
+        let t = i as f32 / size as f32;
+        let radius = ctx.random.next_double() * size as f64 / 16.0;
+        sphere[0] = min_x + t as f64 * (max_x - min_x);
+        sphere[1] = min_y + t as f64 * (max_y - min_y);
+        sphere[2] = min_z + t as f64 * (max_z - min_z);
+        sphere[3] = ((mth_sin(std::f32::consts::PI * t) + 1.0) as f64 * radius + 1.0) / 2.0;
+    }
+
+    // Drop spheres contained in another
+    for i in 0..size.saturating_sub(1) {
+        if spheres[i][3] <= 0.0 {
+            continue;
+        }
+        for j in i + 1..size {
+            if spheres[j][3] <= 0.0 {
+                continue;
+            }
+            let dx = spheres[i][0] - spheres[j][0];
+            let dy = spheres[i][1] - spheres[j][1];
+            let dz = spheres[i][2] - spheres[j][2];
+            let dr = spheres[i][3] - spheres[j][3];
+            if dr * dr > dx * dx + dy * dy + dz * dz {
+                if dr > 0.0 {
+                    spheres[j][3] = -1.0;
+                } else {
+                    spheres[i][3] = -1.0;
+                }
+            }
+        }
+    }
+
+    let mut visited = vec![false; (width * height * width).max(0) as usize];
+    let mut placed = 0;
+    for [cx, cy, cz, radius] in spheres {
+        if radius < 0.0 {
+            continue;
+        }
+        let x0 = ((cx - radius).floor() as i32).max(origin.x);
+        let y0 = ((cy - radius).floor() as i32).max(origin.y);
+        let z0 = ((cz - radius).floor() as i32).max(origin.z);
+        let x1 = ((cx + radius).floor() as i32).max(x0);
+        let y1 = ((cy + radius).floor() as i32).max(y0);
+        let z1 = ((cz + radius).floor() as i32).max(z0);
+
+        for x in x0..=x1 {
+            let fx = (x as f64 + 0.5 - cx) / radius;
+            if fx * fx >= 1.0 {
+                continue;
+            }
+            for y in y0..=y1 {
+                let fy = (y as f64 + 0.5 - cy) / radius;
+                if fx * fx + fy * fy >= 1.0 {
+                    continue;
+                }
+                for z in z0..=z1 {
+                    let fz = (z as f64 + 0.5 - cz) / radius;
+                    if fx * fx + fy * fy + fz * fz >= 1.0 || ctx.region.is_outside_build_height(y) {
+                        continue;
+                    }
+                    let index =
+                        (x - origin.x) + (y - origin.y) * width + (z - origin.z) * width * height;
+                    let Some(seen) = visited.get_mut(index as usize) else {
+                        continue;
+                    };
+                    if *seen {
+                        continue;
+                    }
+                    *seen = true;
+                    if try_place_ore(ctx, config, IVec3::new(x, y, z)) {
+                        placed += 1;
+                    }
+                }
+            }
+        }
+    }
+    placed > 0
+}
+
+/// Java's `ScatteredOreFeature`: single ores spread around the origin.
+fn place_scattered_ore(ctx: &mut FeatureContext, config: &OreConfig, pos: IVec3) -> bool {
+    let count = ctx.random.next_int((config.size + 1) as u32);
+    for i in 0..count {
+        let magnitude = i.min(7) as f32;
+        let mut offset = [0; 3];
+        for axis in &mut offset {
+            let value = (ctx.random.next_float() - ctx.random.next_float()) * magnitude;
+            *axis = (value + 0.5).floor() as i32;
+        }
+        try_place_ore(ctx, config, pos + IVec3::from_array(offset));
+    }
+    true
+}
+
+/// Java's `DiskFeature`: replace matching blocks in a cylinder.
+fn place_disk(ctx: &mut FeatureContext, config: &DiskConfig, pos: IVec3) -> bool {
+    let top = pos.y + config.half_height;
+    let bottom = pos.y - config.half_height - 1;
+    let radius = config.radius.sample(&mut ctx.random);
+
+    let mut placed = false;
+    for dz in -radius..=radius {
+        for dx in -radius..=radius {
+            if dx * dx + dz * dz > radius * radius {
+                continue;
+            }
+            for y in (bottom + 1..=top).rev() {
+                let column = IVec3::new(pos.x + dx, y, pos.z + dz);
+                if !placement::test(&ctx.region, &config.target, column) {
+                    continue;
+                }
+
+                let provider = config
+                    .state_provider
+                    .rules
+                    .iter()
+                    .find(|(predicate, _)| placement::test(&ctx.region, predicate, column))
+                    .map_or(config.state_provider.fallback, |(_, provider)| *provider);
+                if let Some(state) = get_state(ctx, &provider, column) {
+                    set_state(&mut ctx.region, column, &state);
+                }
+                placed = true;
+            }
+        }
+    }
+    placed
+}
+
+/// Java's `LakeFeature`: a blob of fluid sunk into the ground, with air
+/// above and an optional barrier around it.
+fn place_lake(ctx: &mut FeatureContext, config: &LakeConfig, pos: IVec3) -> bool {
+    if pos.y <= ctx.region.min_y() + 4 {
+        return false;
+    }
+    let origin = pos - IVec3::new(0, 4, 0);
+
+    // 16x8x16 shape from a handful of ellipsoids
+    let mut shape = [false; 2048];
+    let index = |x: i32, y: i32, z: i32| ((x * 16 + z) * 8 + y) as usize;
+    let blobs = ctx.random.next_int(4) + 4;
+    for _ in 0..blobs {
+        let size_x = ctx.random.next_double() * 6.0 + 3.0;
+        let size_y = ctx.random.next_double() * 4.0 + 2.0;
+        let size_z = ctx.random.next_double() * 6.0 + 3.0;
+        let center_x = ctx.random.next_double() * (16.0 - size_x - 2.0) + 1.0 + size_x / 2.0;
+        let center_y = ctx.random.next_double() * (8.0 - size_y - 4.0) + 2.0 + size_y / 2.0;
+        let center_z = ctx.random.next_double() * (16.0 - size_z - 2.0) + 1.0 + size_z / 2.0;
+
+        for x in 1..15 {
+            for z in 1..15 {
+                for y in 1..7 {
+                    let dx = (x as f64 - center_x) / (size_x / 2.0);
+                    let dy = (y as f64 - center_y) / (size_y / 2.0);
+                    let dz = (z as f64 - center_z) / (size_z / 2.0);
+                    if dx * dx + dy * dy + dz * dz < 1.0 {
+                        shape[index(x, y, z)] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    // Whether a position is just outside the shape
+    let is_edge = |x: i32, y: i32, z: i32| {
+        !shape[index(x, y, z)]
+            && (x < 15 && shape[index(x + 1, y, z)]
+                || x > 0 && shape[index(x - 1, y, z)]
+                || z < 15 && shape[index(x, y, z + 1)]
+                || z > 0 && shape[index(x, y, z - 1)]
+                || y < 7 && shape[index(x, y + 1, z)]
+                || y > 0 && shape[index(x, y - 1, z)])
+    };
+
+    let fluid =
+        get_state(ctx, &config.fluid, origin).and_then(|state| region::resolve_block(state.name));
+    let Some(fluid) = fluid else {
+        return false;
+    };
+
+    // Give up if the lake would spill or touch other fluids above the surface
+    for x in 0..16 {
+        for z in 0..16 {
+            for y in 0..8 {
+                if !is_edge(x, y, z) {
+                    continue;
+                }
+                let block = ctx.region.get_block(origin + IVec3::new(x, y, z));
+                let is_fluid = region::is_water(block) || region::is_lava(block);
+                if y >= 4 && is_fluid {
+                    return false;
+                }
+                if y < 4 && !region::is_solid(block) && block != fluid {
+                    return false;
+                }
+            }
+        }
+    }
+
+    for x in 0..16 {
+        for z in 0..16 {
+            for y in 0..8 {
+                let pos = origin + IVec3::new(x, y, z);
+                if shape[index(x, y, z)] && region::can_feature_replace(ctx.region.get_block(pos)) {
+                    let block = if y >= 4 { *blocks::AIR } else { fluid };
+                    ctx.region.set_block(pos, block);
+                }
+            }
+        }
+    }
+
+    let barrier =
+        get_state(ctx, &config.barrier, origin).and_then(|state| region::resolve_block(state.name));
+    if let Some(barrier) = barrier.filter(|&barrier| barrier != *blocks::AIR) {
+        for x in 0..16 {
+            for z in 0..16 {
+                for y in 0..8 {
+                    if is_edge(x, y, z) && (y < 4 || ctx.random.next_int(2) != 0) {
+                        let pos = origin + IVec3::new(x, y, z);
+                        let block = ctx.region.get_block(pos);
+                        if region::is_solid(block)
+                            && region::can_feature_replace(block)
+                            && !region::is_leaves(block)
+                            && !region::in_tag(block, "minecraft:logs")
+                        {
+                            ctx.region.set_block(pos, barrier);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    true
+}
+
+/// Java's `SpringFeature`: a single fluid source in a wall with the right
+/// number of open and solid sides.
+fn place_spring(ctx: &mut FeatureContext, config: &SpringConfig, pos: IVec3) -> bool {
+    let region = &ctx.region;
+    let is_valid = |pos: IVec3| {
+        let block = region.get_block(pos);
+        config
+            .valid_blocks
+            .iter()
+            .any(|name| region::matches_block(block, name))
+    };
+
+    if !is_valid(pos + IVec3::Y) {
+        return false;
+    }
+    if config.requires_block_below && !is_valid(pos - IVec3::Y) {
+        return false;
+    }
+    if !region.is_air(pos) && !is_valid(pos) {
+        return false;
+    }
+
+    let sides = [
+        IVec3::new(-1, 0, 0),
+        IVec3::new(1, 0, 0),
+        IVec3::new(0, 0, -1),
+        IVec3::new(0, 0, 1),
+        IVec3::NEG_Y,
+    ];
+    let rocks = sides.iter().filter(|side| is_valid(pos + **side)).count() as i32;
+    let holes = sides
+        .iter()
+        .filter(|side| region.is_air(pos + **side))
+        .count() as i32;
+
+    if rocks == config.rock_count && holes == config.hole_count {
+        set_state(&mut ctx.region, pos, &config.state);
+    }
+    true
+}
@@ -0,0 +1,246 @@
+//! Placement modifiers and block predicates.
+//!
+//! A placed feature starts from the chunk origin; each modifier maps every
+//! position to zero or more new ones (Java's `PlacementModifier.getPositions`).
+//! Like Java's lazy streams, positions run through the rest of the chain,
+//! and the feature is placed, before the next position is produced, which
+//! keeps the random calls in Java's order.
+
+use std::sync::LazyLock;
+
+use glam::IVec3;
+use unastar_noise::SimplexNoise;
+
+use super::region::{self, DecorationRegion};
+use super::{
+    BlockPredicate, ConfiguredFeature, FeatureContext, Heightmap, IntProvider, PlacedFeature,
+    PlacementModifier, place,
+};
+use crate::world::chunk::blocks;
+use crate::world::generator::xoroshiro::Xoroshiro128;
+
+/// Java's `Biome.BIOME_INFO_NOISE`, used by the noise count modifiers.
+static BIOME_INFO_NOISE: LazyLock<SimplexNoise> =
+    LazyLock::new(|| SimplexNoise::new(&mut Xoroshiro128::from_seed(2345)));
+
+/// Place a placed feature from an origin. Returns whether anything was
+/// placed (Java's `PlacedFeature.placeWithContext`).
+pub fn place(ctx: &mut FeatureContext, feature: &PlacedFeature, origin: IVec3) -> bool {
+    place_with_modifiers(ctx, feature.feature, feature.placement, origin)
+}
+
+fn place_with_modifiers(
+    ctx: &mut FeatureContext,
+    feature: &ConfiguredFeature,
+    modifiers: &[PlacementModifier],
+    pos: IVec3,
+) -> bool {
+    let Some((modifier, rest)) = modifiers.split_first() else {
+        return place::place(ctx, feature, pos);
+    };
+
+    let mut placed = false;
+    for pos in positions(ctx, modifier, pos) {
+        placed |= place_with_modifiers(ctx, feature, rest, pos);
+    }
+    placed
+}
+
+/// Positions one modifier produces from a position.
+fn positions(ctx: &mut FeatureContext, modifier: &PlacementModifier, pos: IVec3) -> Vec<IVec3> {
+    let keep = |passed: bool| if passed { vec![pos] } else { Vec::new() };
+
+    match *modifier {
+        PlacementModifier::Count(count) => vec![pos; count.sample(&mut ctx.random).max(0) as usize],
+        PlacementModifier::CountOnEveryLayer(count) => count_on_every_layer(ctx, count, pos),
+        PlacementModifier::RarityFilter { chance } => {
+            keep(ctx.random.next_float() < 1.0 / chance as f32)
+        }
+        PlacementModifier::InSquare => {
+            let x = ctx.random.next_int(16) + pos.x;
+            let z = ctx.random.next_int(16) + pos.z;
+            vec![IVec3::new(x, pos.y, z)]
+        }
+        PlacementModifier::Biome => keep(ctx.biome_has_top_feature(pos)),
+        PlacementModifier::HeightRange(height) => {
+            let y = height.sample(&mut ctx.random, ctx.region.min_y(), ctx.region.max_y());
+            vec![IVec3::new(pos.x, y, pos.z)]
+        }
+        PlacementModifier::Heightmap(heightmap) => {
+            let y = ctx.region.height(heightmap, pos.x, pos.z);
+            if y > ctx.region.min_y() {
+                vec![IVec3::new(pos.x, y, pos.z)]
+            } else {
+                Vec::new()
+            }
+        }
+        PlacementModifier::NoiseThresholdCount {
+            noise_level,
+            below_noise,
+            above_noise,
+        } => {
+            let noise = BIOME_INFO_NOISE.get_value_2d(pos.x as f64 / 200.0, pos.z as f64 / 200.0);
+            let count = if noise < noise_level {
+                below_noise
+            } else {
+                above_noise
+            };
+            vec![pos; count.max(0) as usize]
+        }
+        PlacementModifier::NoiseBasedCount {
+            noise_to_count_ratio,
+            noise_factor,
+            noise_offset,
+        } => {
+            let noise = BIOME_INFO_NOISE
+                .get_value_2d(pos.x as f64 / noise_factor, pos.z as f64 / noise_factor);
+            let count = ((noise + noise_offset) * noise_to_count_ratio as f64).ceil() as i32;
+            vec![pos; count.max(0) as usize]
+        }
+        PlacementModifier::SurfaceWaterDepthFilter { max_water_depth } => {
+            let floor = ctx.region.height(Heightmap::OceanFloor, pos.x, pos.z);
+            let surface = ctx.region.height(Heightmap::WorldSurface, pos.x, pos.z);
+            keep(surface - floor <= max_water_depth)
+        }
+        PlacementModifier::SurfaceRelativeThresholdFilter {
+            heightmap,
+            min_inclusive,
+            max_inclusive,
+        } => {
+            let height = ctx.region.height(heightmap, pos.x, pos.z) as i64;
+            let y = pos.y as i64;
+            keep(height + min_inclusive as i64 <= y && y <= height + max_inclusive as i64)
+        }
+        PlacementModifier::BlockPredicateFilter(predicate) => {
+            keep(test(&ctx.region, &predicate, pos))
+        }
+        PlacementModifier::RandomOffset {
+            xz_spread,
+            y_spread,
+        } => {
+            let x = pos.x + xz_spread.sample(&mut ctx.random);
+            let y = pos.y + y_spread.sample(&mut ctx.random);
+            let z = pos.z + xz_spread.sample(&mut ctx.random);
+            vec![IVec3::new(x, y, z)]
+        }
+        PlacementModifier::EnvironmentScan {
+            direction_of_search,
+            max_steps,
+            target_condition,
+            allowed_search_condition,
+        } => {
+            let allowed = |region: &DecorationRegion, pos: IVec3| {
+                allowed_search_condition.is_none_or(|condition| test(region, &condition, pos))
+            };
+            let step = IVec3::from_array(direction_of_search.offset());
+            let region = &ctx.region;
+
+            let mut pos = pos;
+            if !allowed(region, pos) {
+                return Vec::new();
+            }
+            for _ in 0..max_steps {
+                if test(region, &target_condition, pos) {
+                    return vec![pos];
+                }
+                pos += step;
+                if region.is_outside_build_height(pos.y) {
+                    return Vec::new();
+                }
+                if !allowed(region, pos) {
+                    break;
+                }
+            }
+            if test(region, &target_condition, pos) {
+                vec![pos]
+            } else {
+                Vec::new()
+            }
+        }
+        PlacementModifier::FixedPlacement(positions) => positions
+            .iter()
+            .map(|&p| IVec3::from_array(p))
+            .filter(|p| p.x >> 4 == pos.x >> 4 && p.z >> 4 == pos.z >> 4)
+            .collect(),
+        PlacementModifier::Unsupported => Vec::new(),
+    }
+}
+
+/// Java's `CountOnEveryLayerPlacement`: `count` positions on each floor
+/// of a column, going down layer by layer until a layer has none.
+fn count_on_every_layer(ctx: &mut FeatureContext, count: IntProvider, pos: IVec3) -> Vec<IVec3> {
+    let mut positions = Vec::new();
+    let mut layer = 0;
+    loop {
+        let mut found = false;
+        let mut i = 0;
+        // Java resamples the count on every iteration
+        while i < count.sample(&mut ctx.random) {
+            let x = ctx.random.next_int(16) + pos.x;
+            let z = ctx.random.next_int(16) + pos.z;
+            let top = ctx.region.height(Heightmap::MotionBlocking, x, z);
+            if let Some(y) = find_on_ground_y(&ctx.region, x, top, z, layer) {
+                positions.push(IVec3::new(x, y, z));
+                found = true;
+            }
+            i += 1;
+        }
+        if !found {
+            return positions;
+        }
+        layer += 1;
+    }
+}
+
+/// Y above the `layer`th floor (counting from the top) below `y`.
+fn find_on_ground_y(region: &DecorationRegion, x: i32, y: i32, z: i32, layer: i32) -> Option<i32> {
+    let mut floors = 0;
+    let mut above = region.get_block(IVec3::new(x, y, z));
+    for y in (region.min_y() + 1..=y).rev() {
+        let block = region.get_block(IVec3::new(x, y - 1, z));
+        if !region::is_empty(block) && region::is_empty(above) && block != *blocks::BEDROCK {
+            if floors == layer {
+                return Some(y);
+            }
+            floors += 1;
+        }
+        above = block;
+    }
+    None
+}
+
+/// Test a block predicate at a position.
+pub fn test(region: &DecorationRegion, predicate: &BlockPredicate, pos: IVec3) -> bool {
+    let at = |offset: [i32; 3]| region.get_block(pos + IVec3::from_array(offset));
+
+    match *predicate {
+        BlockPredicate::True => true,
+        BlockPredicate::Not(predicate) => !test(region, predicate, pos),
+        BlockPredicate::AllOf(predicates) => predicates.iter().all(|p| test(region, p, pos)),
+        BlockPredicate::AnyOf(predicates) => predicates.iter().any(|p| test(region, p, pos)),
+        BlockPredicate::MatchingBlocks { offset, blocks } => {
+            let block = at(offset);
+            blocks.iter().any(|name| region::matches_block(block, name))
+        }
+        BlockPredicate::MatchingBlockTag { offset, tag } => region::in_tag(at(offset), tag),
+        BlockPredicate::MatchingFluids { offset, fluids } => {
+            let block = at(offset);
+            fluids.iter().any(|fluid| match *fluid {
+                "minecraft:water" | "minecraft:flowing_water" => region::is_water(block),
+                "minecraft:lava" | "minecraft:flowing_lava" => region::is_lava(block),
+                _ => false,
+            })
+        }
+        BlockPredicate::Solid { offset } => region::is_solid(at(offset)),
+        BlockPredicate::Replaceable { offset } => region::is_replaceable(at(offset)),
+        BlockPredicate::WouldSurvive { offset, state } => {
+            place::can_survive(region, state.name, pos + IVec3::from_array(offset))
+        }
+        // Full blocks are sturdy on every face
+        BlockPredicate::HasSturdyFace { offset, .. } => region::is_solid(at(offset)),
+        BlockPredicate::InsideWorldBounds { offset } => {
+            !region.is_outside_build_height(pos.y + offset[1])
+        }
+        BlockPredicate::Unsupported => false,
+    }
+}
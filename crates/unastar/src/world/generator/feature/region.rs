@@ -0,0 +1,593 @@
+//! Block access for feature placement.
+//!
+//! [`DecorationRegion`] is the equivalent of Java's `WorldGenRegion`: the
+//! 3x3 chunks around the chunk being decorated, addressed in world
+//! coordinates. Reads outside the region see air and writes are dropped.
+//! Every block set is also recorded as a [`BlockWrite`], so the blocks
+//! features leave in the neighbours can be applied to them later.
+//!
+//! Feature JSON names blocks by their Java IDs; [`resolve_block`] maps them
+//! to Bedrock runtime IDs, and the block tags used by features are matched
+//! by name.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use glam::IVec3;
+use jolyne::valentine::blocks::BLOCKS;
+use parking_lot::RwLock;
+
+use super::{Heightmap, RuleTest};
+use crate::world::chunk::{Chunk, blocks};
+use crate::world::generator::xoroshiro::JavaRandom;
+
+/// Block classification flags, indexed by runtime ID.
+const SOLID: u8 = 1 << 0;
+const WATER: u8 = 1 << 1;
+const LAVA: u8 = 1 << 2;
+const LEAVES: u8 = 1 << 3;
+const LOG: u8 = 1 << 4;
+const REPLACEABLE: u8 = 1 << 5;
+const DIRT: u8 = 1 << 6;
+const DOUBLE_PLANT: u8 = 1 << 7;
+
+/// Name and flags of every block state.
+struct BlockInfo {
+    name: &'static str,
+    flags: u8,
+}
+
+/// Block names in Java's `replaceable` tag (besides air and fluids).
+const REPLACEABLE_BLOCKS: &[&str] = &[
+    "minecraft:short_grass",
+    "minecraft:fern",
+    "minecraft:deadbush",
+    "minecraft:seagrass",
+    "minecraft:fire",
+    "minecraft:soul_fire",
+    "minecraft:snow_layer",
+    "minecraft:vine",
+    "minecraft:glow_lichen",
+    "minecraft:tall_grass",
+    "minecraft:large_fern",
+    "minecraft:crimson_roots",
+    "minecraft:warped_roots",
+    "minecraft:nether_sprouts",
+    "minecraft:hanging_roots",
+    "minecraft:short_dry_grass",
+    "minecraft:tall_dry_grass",
+    "minecraft:bush",
+];
+
+/// Two-block-tall plants, whose only state property is `upper_block_bit`.
+const DOUBLE_PLANTS: &[&str] = &[
+    "minecraft:tall_grass",
+    "minecraft:large_fern",
+    "minecraft:sunflower",
+    "minecraft:lilac",
+    "minecraft:rose_bush",
+    "minecraft:peony",
+    "minecraft:pitcher_plant",
+];
+
+/// Block names in Java's `dirt` tag.
+const DIRT_BLOCKS: &[&str] = &[
+    "minecraft:dirt",
+    "minecraft:grass_block",
+    "minecraft:podzol",
+    "minecraft:coarse_dirt",
+    "minecraft:mycelium",
+    "minecraft:dirt_with_roots",
+    "minecraft:moss_block",
+    "minecraft:pale_moss_block",
+    "minecraft:mud",
+    "minecraft:muddy_mangrove_roots",
+];
+
+static BLOCK_INFO: LazyLock<Vec<BlockInfo>> = LazyLock::new(|| {
+    let max_state = BLOCKS
+        .iter()
+        .map(|block| block.max_state_id())
+        .max()
+        .unwrap_or(0);
+    let mut table: Vec<BlockInfo> = (0..=max_state)
+        .map(|_| BlockInfo {
+            name: "minecraft:air",
+            flags: REPLACEABLE,
+        })
+        .collect();
+
+    for block in BLOCKS.iter() {
+        let name = block.string_id();
+        let mut flags = 0;
+        if matches!(name, "minecraft:water" | "minecraft:flowing_water") {
+            flags |= WATER | REPLACEABLE;
+        } else if matches!(name, "minecraft:lava" | "minecraft:flowing_lava") {
+            flags |= LAVA | REPLACEABLE;
+        } else if name == "minecraft:air" || REPLACEABLE_BLOCKS.contains(&name) {
+            flags |= REPLACEABLE;
+        } else if block.hardness() != 0.0 {
+            // Anything that takes time to break blocks motion
+            flags |= SOLID;
+        }
+        if name.ends_with("_leaves") {
+            flags |= LEAVES;
+        }
+        if name.ends_with("_log") || name.ends_with("_wood") || name.ends_with("_stem") {
+            flags |= LOG;
+        }
+        if DIRT_BLOCKS.contains(&name) {
+            flags |= DIRT;
+        }
+        if DOUBLE_PLANTS.contains(&name) {
+            flags |= DOUBLE_PLANT;
+        }
+
+        for state in block.min_state_id()..=block.max_state_id() {
+            table[state as usize] = BlockInfo { name, flags };
+        }
+    }
+    table
+});
+
+#[inline]
+fn flags(runtime_id: u32) -> u8 {
+    BLOCK_INFO
+        .get(runtime_id as usize)
+        .map_or(0, |info| info.flags)
+}
+
+/// Bedrock name of a block state.
+#[inline]
+pub fn block_name(runtime_id: u32) -> &'static str {
+    BLOCK_INFO
+        .get(runtime_id as usize)
+        .map_or("minecraft:air", |info| info.name)
+}
+
+/// Whether a block blocks motion (Java's `BlockState.isSolid`).
+#[inline]
+pub fn is_solid(runtime_id: u32) -> bool {
+    flags(runtime_id) & SOLID != 0
+}
+
+/// Whether a block is air or a fluid.
+#[inline]
+pub fn is_empty(runtime_id: u32) -> bool {
+    runtime_id == *blocks::AIR || flags(runtime_id) & (WATER | LAVA) != 0
+}
+
+#[inline]
+pub fn is_water(runtime_id: u32) -> bool {
+    flags(runtime_id) & WATER != 0
+}
+
+#[inline]
+pub fn is_lava(runtime_id: u32) -> bool {
+    flags(runtime_id) & LAVA != 0
+}
+
+#[inline]
+pub fn is_leaves(runtime_id: u32) -> bool {
+    flags(runtime_id) & LEAVES != 0
+}
+
+/// Whether a block is in Java's `replaceable` tag.
+#[inline]
+pub fn is_replaceable(runtime_id: u32) -> bool {
+    flags(runtime_id) & REPLACEABLE != 0
+}
+
+/// Whether a block is in Java's `dirt` tag.
+#[inline]
+pub fn is_dirt(runtime_id: u32) -> bool {
+    flags(runtime_id) & DIRT != 0
+}
+
+/// Upper half of a two-block-tall plant, or `None` for other blocks.
+///
+/// `upper_block_bit` is the lowest state bit, so the upper half is the
+/// state right after the (lower half) default state.
+#[inline]
+pub fn double_plant_upper(runtime_id: u32) -> Option<u32> {
+    (flags(runtime_id) & DOUBLE_PLANT != 0).then_some(runtime_id + 1)
+}
+
+/// Whether features may overwrite a block (Java's
+/// `features_cannot_replace` tag).
+pub fn can_feature_replace(runtime_id: u32) -> bool {
+    !matches!(
+        block_name(runtime_id),
+        "minecraft:bedrock"
+            | "minecraft:mob_spawner"
+            | "minecraft:chest"
+            | "minecraft:end_portal_frame"
+            | "minecraft:reinforced_deepslate"
+            | "minecraft:trial_spawner"
+            | "minecraft:vault"
+    )
+}
+
+/// Java's `TreeFeature.validTreePos`: somewhere a log or leaves may go.
+#[inline]
+pub fn is_valid_tree_pos(runtime_id: u32) -> bool {
+    flags(runtime_id) & (REPLACEABLE | LEAVES) != 0 && !is_lava(runtime_id)
+}
+
+/// Whether a block is in one of the block tags used by features and
+/// carvers.
+///
+/// Unknown tags match nothing.
+pub fn in_tag(runtime_id: u32, tag: &str) -> bool {
+    let name = block_name(runtime_id);
+    match tag.strip_prefix('#').unwrap_or(tag) {
+        "minecraft:stone_ore_replaceables" => matches!(
+            name,
+            "minecraft:stone" | "minecraft:granite" | "minecraft:diorite" | "minecraft:andesite"
+        ),
+        "minecraft:deepslate_ore_replaceables" => {
+            matches!(name, "minecraft:deepslate" | "minecraft:tuff")
+        }
+        "minecraft:base_stone_overworld" => matches!(
+            name,
+            "minecraft:stone"
+                | "minecraft:granite"
+                | "minecraft:diorite"
+                | "minecraft:andesite"
+                | "minecraft:tuff"
+                | "minecraft:deepslate"
+        ),
+        "minecraft:base_stone_nether" => matches!(
+            name,
+            "minecraft:netherrack" | "minecraft:basalt" | "minecraft:blackstone"
+        ),
+        "minecraft:dirt" => is_dirt(runtime_id),
+        "minecraft:sand" => matches!(
+            name,
+            "minecraft:sand" | "minecraft:red_sand" | "minecraft:suspicious_sand"
+        ),
+        "minecraft:leaves" => is_leaves(runtime_id),
+        "minecraft:logs" => flags(runtime_id) & LOG != 0,
+        "minecraft:replaceable" => is_replaceable(runtime_id),
+        "minecraft:features_cannot_replace" => !can_feature_replace(runtime_id),
+        "minecraft:replaceable_by_trees" => {
+            is_valid_tree_pos(runtime_id) && runtime_id != *blocks::AIR
+        }
+        "minecraft:overworld_carver_replaceables" => {
+            in_tag(runtime_id, "minecraft:base_stone_overworld")
+                || in_tag(runtime_id, "minecraft:sand")
+                || is_dirt(runtime_id)
+                || is_terracotta(name)
+                || matches!(
+                    name,
+                    "minecraft:iron_ore"
+                        | "minecraft:deepslate_iron_ore"
+                        | "minecraft:copper_ore"
+                        | "minecraft:deepslate_copper_ore"
+                        | "minecraft:water"
+                        | "minecraft:gravel"
+                        | "minecraft:suspicious_gravel"
+                        | "minecraft:sandstone"
+                        | "minecraft:red_sandstone"
+                        | "minecraft:calcite"
+                        | "minecraft:snow_layer"
+                        | "minecraft:packed_ice"
+                        | "minecraft:raw_iron_block"
+                        | "minecraft:raw_copper_block"
+                )
+        }
+        "minecraft:nether_carver_replaceables" => {
+            in_tag(runtime_id, "minecraft:base_stone_overworld")
+                || in_tag(runtime_id, "minecraft:base_stone_nether")
+                || is_dirt(runtime_id)
+                || matches!(
+                    name,
+                    "minecraft:crimson_nylium"
+                        | "minecraft:warped_nylium"
+                        | "minecraft:nether_wart_block"
+                        | "minecraft:warped_wart_block"
+                        | "minecraft:soul_sand"
+                        | "minecraft:soul_soil"
+                )
+        }
+        "minecraft:azalea_grows_on" => {
+            is_dirt(runtime_id)
+                || matches!(
+                    name,
+                    "minecraft:sand" | "minecraft:red_sand" | "minecraft:snow" | "minecraft:clay"
+                )
+        }
+        _ => false,
+    }
+}
+
+/// Whether a block name is in Java's `terracotta` tag: plain and dyed,
+/// but not glazed, terracotta.
+fn is_terracotta(name: &str) -> bool {
+    name.ends_with("terracotta") && !name.ends_with("glazed_terracotta")
+}
+
+/// Whether a block passes a rule test (Java's `RuleTest.test`).
+pub fn rule_test(test: &RuleTest, runtime_id: u32, random: &mut JavaRandom) -> bool {
+    match *test {
+        RuleTest::AlwaysTrue => true,
+        RuleTest::TagMatch(tag) => in_tag(runtime_id, tag),
+        RuleTest::BlockMatch(name) => matches_block(runtime_id, name),
+        RuleTest::RandomBlockMatch {
+            block: name,
+            probability,
+        } => matches_block(runtime_id, name) && random.next_float() < probability,
+        RuleTest::Unsupported => false,
+    }
+}
+
+/// Java block names whose Bedrock name differs.
+const BEDROCK_NAMES: &[(&str, &str)] = &[
+    ("minecraft:lily_pad", "minecraft:waterlily"),
+    ("minecraft:sugar_cane", "minecraft:reeds"),
+    ("minecraft:dead_bush", "minecraft:deadbush"),
+    ("minecraft:cobweb", "minecraft:web"),
+    ("minecraft:magma_block", "minecraft:magma"),
+    ("minecraft:dirt_path", "minecraft:grass_path"),
+    ("minecraft:snow", "minecraft:snow_layer"),
+    ("minecraft:snow_block", "minecraft:snow"),
+    ("minecraft:melon", "minecraft:melon_block"),
+    ("minecraft:rooted_dirt", "minecraft:dirt_with_roots"),
+    ("minecraft:cave_air", "minecraft:air"),
+    ("minecraft:void_air", "minecraft:air"),
+    ("minecraft:spawner", "minecraft:mob_spawner"),
+    ("minecraft:nether_quartz_ore", "minecraft:quartz_ore"),
+    ("minecraft:grass", "minecraft:short_grass"),
+];
+
+/// Map a Java block name to its Bedrock name.
+pub fn bedrock_name(name: &str) -> &str {
+    BEDROCK_NAMES
+        .iter()
+        .find(|(java, _)| *java == name)
+        .map_or(name, |(_, bedrock)| bedrock)
+}
+
+/// Resolve a Java block name to a Bedrock runtime ID (default state).
+///
+/// Returns `None` for blocks Bedrock doesn't have, so features skip them
+/// rather than placing air.
+pub fn resolve_block(name: &str) -> Option<u32> {
+    static CACHE: LazyLock<RwLock<HashMap<String, Option<u32>>>> = LazyLock::new(Default::default);
+
+    if let Some(id) = CACHE.read().get(name) {
+        return *id;
+    }
+    let id = blocks::find_block_id(bedrock_name(name));
+    CACHE.write().insert(name.to_string(), id);
+    id
+}
+
+/// Whether a block matches a name or a `#tag` from feature JSON.
+pub fn matches_block(runtime_id: u32, name_or_tag: &str) -> bool {
+    if name_or_tag.starts_with('#') {
+        in_tag(runtime_id, name_or_tag)
+    } else {
+        block_name(runtime_id) == bedrock_name(name_or_tag)
+    }
+}
+
+/// A block set by a feature, in coordinates local to its chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockWrite {
+    pub x: u8,
+    pub y: i16,
+    pub z: u8,
+    pub block: u32,
+}
+
+/// The 3x3 chunks around a chunk being decorated.
+pub struct DecorationRegion {
+    /// Chunk coordinates of the centre chunk.
+    center_x: i32,
+    center_z: i32,
+    /// Chunks in row-major order, `(dz + 1) * 3 + (dx + 1)`.
+    chunks: Vec<Chunk>,
+    /// Blocks set in each chunk, in the order they were set.
+    writes: Vec<Vec<BlockWrite>>,
+    /// Lowest and highest block Y features may use.
+    min_y: i32,
+    max_y: i32,
+}
+
+impl DecorationRegion {
+    /// Create a region from the 3x3 chunks around `(center_x, center_z)`,
+    /// given in row-major order starting from the north-west corner.
+    pub fn new(center_x: i32, center_z: i32, chunks: Vec<Chunk>, min_y: i32, max_y: i32) -> Self {
+        debug_assert_eq!(chunks.len(), 9);
+        Self {
+            center_x,
+            center_z,
+            chunks,
+            writes: vec![Vec::new(); 9],
+            min_y,
+            max_y,
+        }
+    }
+
+    /// Chunk coordinates of the centre chunk.
+    pub fn center(&self) -> (i32, i32) {
+        (self.center_x, self.center_z)
+    }
+
+    /// Take the blocks set in each chunk, in row-major order like the
+    /// chunks.
+    pub fn into_writes(self) -> Vec<Vec<BlockWrite>> {
+        self.writes
+    }
+
+    /// Lowest block Y.
+    pub fn min_y(&self) -> i32 {
+        self.min_y
+    }
+
+    /// Highest block Y.
+    pub fn max_y(&self) -> i32 {
+        self.max_y
+    }
+
+    /// Whether `y` is outside the dimension's build height.
+    pub fn is_outside_build_height(&self, y: i32) -> bool {
+        y < self.min_y || y > self.max_y
+    }
+
+    /// Index of the chunk holding a block column, if it is in the region.
+    fn chunk_index(&self, x: i32, z: i32) -> Option<usize> {
+        let dx = (x >> 4) - self.center_x;
+        let dz = (z >> 4) - self.center_z;
+        if (-1..=1).contains(&dx) && (-1..=1).contains(&dz) {
+            Some(((dz + 1) * 3 + dx + 1) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Get the block at a world position. Outside the region this is air.
+    pub fn get_block(&self, pos: IVec3) -> u32 {
+        match self.chunk_index(pos.x, pos.z) {
+            Some(index) if !self.is_outside_build_height(pos.y) => self.chunks[index].get_block(
+                (pos.x & 15) as u8,
+                pos.y as i16,
+                (pos.z & 15) as u8,
+                0,
+            ),
+            _ => *blocks::AIR,
+        }
+    }
+
+    /// Set the block at a world position. Returns false outside the region.
+    pub fn set_block(&mut self, pos: IVec3, block: u32) -> bool {
+        if self.is_outside_build_height(pos.y) {
+            return false;
+        }
+        let Some(index) = self.chunk_index(pos.x, pos.z) else {
+            return false;
+        };
+        let write = BlockWrite {
+            x: (pos.x & 15) as u8,
+            y: pos.y as i16,
+            z: (pos.z & 15) as u8,
+            block,
+        };
+        let set = self.chunks[index]
+            .set_block(write.x, write.y, write.z, 0, block)
+            .is_some();
+        if set {
+            self.writes[index].push(write);
+        }
+        set
+    }
+
+    /// Whether the block at a position is air.
+    pub fn is_air(&self, pos: IVec3) -> bool {
+        self.get_block(pos) == *blocks::AIR
+    }
+
+    /// Y above the highest block matching a heightmap type in a column.
+    ///
+    /// Returns the region's minimum Y for empty columns and columns outside
+    /// the region.
+    pub fn height(&self, heightmap: Heightmap, x: i32, z: i32) -> i32 {
+        let Some(index) = self.chunk_index(x, z) else {
+            return self.min_y;
+        };
+        let chunk = &self.chunks[index];
+        let (local_x, local_z) = ((x & 15) as u8, (z & 15) as u8);
+
+        // The chunk tracks the highest non-air block (WORLD_SURFACE)
+        let top = (chunk.height_map().at(local_x, local_z) as i32).min(self.max_y + 1);
+        let matches = |block: u32| match heightmap {
+            Heightmap::WorldSurface | Heightmap::WorldSurfaceWg => block != *blocks::AIR,
+            Heightmap::OceanFloor | Heightmap::OceanFloorWg => is_solid(block),
+            Heightmap::MotionBlocking => is_solid(block) || is_water(block) || is_lava(block),
+            Heightmap::MotionBlockingNoLeaves => {
+                (is_solid(block) || is_water(block) || is_lava(block)) && !is_leaves(block)
+            }
+        };
+
+        for y in (self.min_y..top).rev() {
+            let block = chunk.get_block(local_x, y as i16, local_z, 0);
+            if matches(block) {
+                return y + 1;
+            }
+        }
+        self.min_y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_flags() {
+        assert!(is_solid(*blocks::STONE));
+        assert!(!is_solid(*blocks::WATER));
+        assert!(is_water(*blocks::WATER));
+        assert!(is_replaceable(*blocks::GRASS));
+        assert!(is_leaves(*blocks::OAK_LEAVES));
+        assert!(is_dirt(*blocks::GRASS_BLOCK));
+        assert_eq!(
+            double_plant_upper(*blocks::TALL_GRASS),
+            Some(*blocks::TALL_GRASS + 1)
+        );
+        assert_eq!(double_plant_upper(*blocks::GRASS), None);
+        assert!(in_tag(
+            *blocks::DEEPSLATE,
+            "minecraft:deepslate_ore_replaceables"
+        ));
+        assert!(!in_tag(
+            *blocks::DEEPSLATE,
+            "minecraft:stone_ore_replaceables"
+        ));
+    }
+
+    #[test]
+    fn test_resolve_block() {
+        assert_eq!(resolve_block("minecraft:lily_pad"), Some(*blocks::LILY_PAD));
+        assert_eq!(
+            resolve_block("minecraft:snow_block"),
+            Some(*blocks::SNOW_BLOCK)
+        );
+        assert_eq!(resolve_block("minecraft:not_a_block"), None);
+        assert!(matches_block(*blocks::SAND, "minecraft:sand"));
+    }
+
+    #[test]
+    fn test_region_access() {
+        let chunks = (0..9)
+            .map(|i| {
+                let mut chunk = Chunk::new(i % 3 - 1, i / 3 - 1);
+                chunk.set_block(0, 64, 0, 0, *blocks::STONE);
+                chunk
+            })
+            .collect();
+        let mut region = DecorationRegion::new(0, 0, chunks, -64, 319);
+
+        // North-west neighbour's (0, 64, 0) is at world (-16, 64, -16)
+        assert_eq!(region.get_block(IVec3::new(-16, 64, -16)), *blocks::STONE);
+        assert_eq!(region.height(Heightmap::WorldSurface, -16, -16), 65);
+        assert_eq!(region.height(Heightmap::OceanFloor, 5, 5), -64);
+
+        // Outside the region
+        assert!(!region.set_block(IVec3::new(32, 64, 0), *blocks::STONE));
+        assert_eq!(region.get_block(IVec3::new(-17, 64, 0)), *blocks::AIR);
+
+        assert!(region.set_block(IVec3::new(3, 70, 4), *blocks::DIRT));
+        assert!(region.set_block(IVec3::new(-1, 70, 4), *blocks::DIRT));
+        let writes = region.into_writes();
+        let dirt = |x, z| BlockWrite {
+            x,
+            y: 70,
+            z,
+            block: *blocks::DIRT,
+        };
+        assert_eq!(writes[4], vec![dirt(3, 4)]);
+        assert_eq!(writes[3], vec![dirt(15, 4)]);
+    }
+}
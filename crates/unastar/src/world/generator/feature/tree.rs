@@ -0,0 +1,638 @@
+//! Tree feature: trunk placers and foliage placers.
+//!
+//! Ports of Java's `TreeFeature` with the straight, forking, giant, mega
+//! jungle, dark oak and fancy trunk placers and their foliage placers. The
+//! bending, upwards branching and cherry trunks grow straight, and the
+//! cherry foliage is a blob. Tree decorators aren't placed.
+
+use glam::IVec3;
+
+use super::place::{get_state, mth_cos, mth_sin};
+use super::region;
+use super::{FeatureContext, FoliagePlacer, FoliagePlacerKind, TreeConfig, TrunkPlacerKind};
+
+/// Leaves tried by the random spread foliage placer (vanilla trees use 50
+/// to 70; the JSON value isn't generated).
+const LEAF_PLACEMENT_ATTEMPTS: i32 = 50;
+
+/// Horizontal directions in Java's `Direction.Plane.HORIZONTAL` order.
+const HORIZONTAL: [IVec3; 4] = [
+    IVec3::new(0, 0, -1),
+    IVec3::new(1, 0, 0),
+    IVec3::new(0, 0, 1),
+    IVec3::new(-1, 0, 0),
+];
+
+/// Where a foliage placer grows leaves (Java's `FoliageAttachment`).
+#[derive(Debug, Clone, Copy)]
+struct Attachment {
+    pos: IVec3,
+    radius_offset: i32,
+    double_trunk: bool,
+}
+
+impl Attachment {
+    fn new(pos: IVec3, radius_offset: i32, double_trunk: bool) -> Self {
+        Self {
+            pos,
+            radius_offset,
+            double_trunk,
+        }
+    }
+}
+
+/// Java's `TreeFeature.doPlace`.
+pub fn place(ctx: &mut FeatureContext, config: &TreeConfig, pos: IVec3) -> bool {
+    let foliage = &config.foliage_placer;
+    let height = config.trunk_placer.tree_height(&mut ctx.random);
+    let foliage_height = foliage_height(ctx, foliage, height);
+    let trunk_length = height - foliage_height;
+    let radius = foliage_radius(ctx, foliage, trunk_length);
+
+    if pos.y < ctx.region.min_y() + 1 || pos.y + height + 1 > ctx.region.max_y() + 1 {
+        return false;
+    }
+    let free_height = max_free_tree_height(ctx, height, pos);
+    if free_height < height {
+        return false;
+    }
+
+    let attachments = place_trunk(ctx, config, free_height, pos);
+    for attachment in attachments {
+        let offset = foliage.offset.sample(&mut ctx.random);
+        place_foliage(ctx, config, attachment, foliage_height, radius, offset);
+    }
+    true
+}
+
+/// Java's `FoliagePlacer.foliageHeight`.
+fn foliage_height(ctx: &mut FeatureContext, foliage: &FoliagePlacer, tree_height: i32) -> i32 {
+    match foliage.kind {
+        FoliagePlacerKind::Acacia => 0,
+        FoliagePlacerKind::DarkOak => 4,
+        FoliagePlacerKind::Spruce => (tree_height - foliage.height.sample(&mut ctx.random)).max(4),
+        _ => foliage.height.sample(&mut ctx.random),
+    }
+}
+
+/// Java's `FoliagePlacer.foliageRadius`.
+fn foliage_radius(ctx: &mut FeatureContext, foliage: &FoliagePlacer, trunk_length: i32) -> i32 {
+    let radius = foliage.radius.sample(&mut ctx.random);
+    if foliage.kind == FoliagePlacerKind::Pine {
+        radius + ctx.random.next_int((trunk_length + 1).max(1) as u32)
+    } else {
+        radius
+    }
+}
+
+/// Java's `TrunkPlacer.isFree`.
+fn is_free(ctx: &FeatureContext, pos: IVec3) -> bool {
+    let block = ctx.region.get_block(pos);
+    region::is_valid_tree_pos(block) || region::in_tag(block, "minecraft:logs")
+}
+
+/// Java's `TreeFeature.getMaxFreeTreeHeight`, with a two-layer minimum
+/// size: the trunk column, then a 3x3 ring from one block up.
+fn max_free_tree_height(ctx: &FeatureContext, height: i32, pos: IVec3) -> i32 {
+    for y in 0..=height + 1 {
+        let size = if y < 1 { 0 } else { 1 };
+        for dx in -size..=size {
+            for dz in -size..=size {
+                let pos = pos + IVec3::new(dx, y, dz);
+                if !is_free(ctx, pos) {
+                    return y - 2;
+                }
+            }
+        }
+    }
+    height
+}
+
+/// Java's `TrunkPlacer.placeLog`.
+fn place_log(ctx: &mut FeatureContext, config: &TreeConfig, pos: IVec3) -> bool {
+    if !region::is_valid_tree_pos(ctx.region.get_block(pos)) {
+        return false;
+    }
+    if let Some(state) = get_state(ctx, &config.trunk_provider, pos) {
+        super::place::set_state(&mut ctx.region, pos, &state);
+    }
+    true
+}
+
+/// Java's `TrunkPlacer.setDirtAt`: turn the ground under the trunk to dirt.
+fn set_dirt_at(ctx: &mut FeatureContext, config: &TreeConfig, pos: IVec3) {
+    let block = ctx.region.get_block(pos);
+    let name = region::block_name(block);
+    let is_dirt =
+        region::is_dirt(block) && name != "minecraft:grass_block" && name != "minecraft:mycelium";
+    if (config.force_dirt || !is_dirt)
+        && let Some(state) = get_state(ctx, &config.dirt_provider, pos)
+    {
+        super::place::set_state(&mut ctx.region, pos, &state);
+    }
+}
+
+/// Place the trunk and return where foliage grows.
+fn place_trunk(
+    ctx: &mut FeatureContext,
+    config: &TreeConfig,
+    height: i32,
+    pos: IVec3,
+) -> Vec<Attachment> {
+    match config.trunk_placer.kind {
+        TrunkPlacerKind::Forking => place_forking_trunk(ctx, config, height, pos),
+        TrunkPlacerKind::Giant => place_giant_trunk(ctx, config, height, pos),
+        TrunkPlacerKind::MegaJungle => {
+            let mut attachments = place_giant_trunk(ctx, config, height, pos);
+
+            // Branches with their own foliage
+            let mut y = height - 2 - ctx.random.next_int(4);
+            while y > height / 2 {
+                let angle = ctx.random.next_float() * std::f32::consts::PI * 2.0;
+                let (mut dx, mut dz) = (0, 0);
+                for i in 0..5 {
+                    dx = (1.5 + mth_cos(angle) * i as f32) as i32;
+                    dz = (1.5 + mth_sin(angle) * i as f32) as i32;
+                    place_log(ctx, config, pos + IVec3::new(dx, y - 3 + i / 2, dz));
+                }
+                attachments.push(Attachment::new(pos + IVec3::new(dx, y, dz), -2, false));
+                y -= 2 + ctx.random.next_int(4);
+            }
+            attachments
+        }
+        TrunkPlacerKind::DarkOak => place_dark_oak_trunk(ctx, config, height, pos),
+        TrunkPlacerKind::Fancy => place_fancy_trunk(ctx, config, height, pos),
+        TrunkPlacerKind::Straight
+        | TrunkPlacerKind::Bending
+        | TrunkPlacerKind::UpwardsBranching
+        | TrunkPlacerKind::Cherry => {
+            set_dirt_at(ctx, config, pos - IVec3::Y);
+            for y in 0..height {
+                place_log(ctx, config, pos + IVec3::new(0, y, 0));
+            }
+            vec![Attachment::new(pos + IVec3::new(0, height, 0), 0, false)]
+        }
+    }
+}
+
+/// Java's `ForkingTrunkPlacer` (acacia): a trunk that leans one way and
+/// may fork another.
+fn place_forking_trunk(
+    ctx: &mut FeatureContext,
+    config: &TreeConfig,
+    height: i32,
+    pos: IVec3,
+) -> Vec<Attachment> {
+    set_dirt_at(ctx, config, pos - IVec3::Y);
+
+    let mut attachments = Vec::new();
+    let direction = HORIZONTAL[ctx.random.next_int(4) as usize];
+    let lean_start = height - ctx.random.next_int(4) - 1;
+    let mut lean = 3 - ctx.random.next_int(3);
+    let (mut x, mut z) = (pos.x, pos.z);
+    let mut top = None;
+    for i in 0..height {
+        let y = pos.y + i;
+        if i >= lean_start && lean > 0 {
+            x += direction.x;
+            z += direction.z;
+            lean -= 1;
+        }
+        if place_log(ctx, config, IVec3::new(x, y, z)) {
+            top = Some(y + 1);
+        }
+    }
+    if let Some(top) = top {
+        attachments.push(Attachment::new(IVec3::new(x, top, z), 1, false));
+    }
+
+    let (mut x, mut z) = (pos.x, pos.z);
+    let fork = HORIZONTAL[ctx.random.next_int(4) as usize];
+    if fork != direction {
+        let mut i = lean_start - ctx.random.next_int(2) - 1;
+        let mut length = 1 + ctx.random.next_int(3);
+        let mut top = None;
+        while i < height && length > 0 {
+            if i >= 1 {
+                let y = pos.y + i;
+                x += fork.x;
+                z += fork.z;
+                if place_log(ctx, config, IVec3::new(x, y, z)) {
+                    top = Some(y + 1);
+                }
+            }
+            i += 1;
+            length -= 1;
+        }
+        if let Some(top) = top {
+            attachments.push(Attachment::new(IVec3::new(x, top, z), 0, false));
+        }
+    }
+    attachments
+}
+
+/// Java's `GiantTrunkPlacer`: a 2x2 trunk.
+fn place_giant_trunk(
+    ctx: &mut FeatureContext,
+    config: &TreeConfig,
+    height: i32,
+    pos: IVec3,
+) -> Vec<Attachment> {
+    let below = pos - IVec3::Y;
+    for offset in [IVec3::ZERO, IVec3::X, IVec3::Z, IVec3::X + IVec3::Z] {
+        set_dirt_at(ctx, config, below + offset);
+    }
+
+    for y in 0..height {
+        let place_if_free = |ctx: &mut FeatureContext, dx: i32, dz: i32| {
+            let pos = pos + IVec3::new(dx, y, dz);
+            if is_free(ctx, pos) {
+                place_log(ctx, config, pos);
+            }
+        };
+        place_if_free(ctx, 0, 0);
+        if y < height - 1 {
+            place_if_free(ctx, 1, 0);
+            place_if_free(ctx, 1, 1);
+            place_if_free(ctx, 0, 1);
+        }
+    }
+    vec![Attachment::new(pos + IVec3::new(0, height, 0), 0, true)]
+}
+
+/// Java's `DarkOakTrunkPlacer`: a leaning 2x2 trunk with short branches
+/// around the top.
+fn place_dark_oak_trunk(
+    ctx: &mut FeatureContext,
+    config: &TreeConfig,
+    height: i32,
+    pos: IVec3,
+) -> Vec<Attachment> {
+    let below = pos - IVec3::Y;
+    for offset in [IVec3::ZERO, IVec3::X, IVec3::Z, IVec3::X + IVec3::Z] {
+        set_dirt_at(ctx, config, below + offset);
+    }
+
+    let mut attachments = Vec::new();
+    let direction = HORIZONTAL[ctx.random.next_int(4) as usize];
+    let lean_start = height - ctx.random.next_int(4);
+    let mut lean = 2 - ctx.random.next_int(3);
+    let (mut x, mut z) = (pos.x, pos.z);
+    let top = pos.y + height - 1;
+    for i in 0..height {
+        if i >= lean_start && lean > 0 {
+            x += direction.x;
+            z += direction.z;
+            lean -= 1;
+        }
+        let log = IVec3::new(x, pos.y + i, z);
+        let block = ctx.region.get_block(log);
+        if block == *crate::world::chunk::blocks::AIR || region::is_leaves(block) {
+            for offset in [IVec3::ZERO, IVec3::X, IVec3::Z, IVec3::X + IVec3::Z] {
+                place_log(ctx, config, log + offset);
+            }
+        }
+    }
+    attachments.push(Attachment::new(IVec3::new(x, top, z), 0, true));
+
+    for dx in -1..=2 {
+        for dz in -1..=2 {
+            if (!(0..=1).contains(&dx) || !(0..=1).contains(&dz)) && ctx.random.next_int(3) == 0 {
+                let length = ctx.random.next_int(3) + 2;
+                for i in 0..length {
+                    place_log(ctx, config, IVec3::new(pos.x + dx, top - i - 1, pos.z + dz));
+                }
+                attachments.push(Attachment::new(
+                    IVec3::new(pos.x + dx, top, pos.z + dz),
+                    0,
+                    false,
+                ));
+            }
+        }
+    }
+    attachments
+}
+
+/// Java's `FancyTrunkPlacer` (big oak): a tall trunk with branches ending
+/// in foliage clusters.
+fn place_fancy_trunk(
+    ctx: &mut FeatureContext,
+    config: &TreeConfig,
+    height: i32,
+    pos: IVec3,
+) -> Vec<Attachment> {
+    let height = height + 2;
+    let trunk_height = (height as f64 * 0.618).floor() as i32;
+    set_dirt_at(ctx, config, pos - IVec3::Y);
+
+    let trunk_top = pos.y + trunk_height;
+    // (foliage position, branch base Y)
+    let mut clusters = vec![(pos + IVec3::new(0, height - 5, 0), trunk_top)];
+    for y in (0..=height - 5).rev() {
+        let shape = fancy_tree_shape(height, y);
+        if shape < 0.0 {
+            continue;
+        }
+
+        let distance = shape as f64 * (ctx.random.next_float() as f64 + 0.328);
+        let angle = (ctx.random.next_float() * 2.0) as f64 * std::f64::consts::PI;
+        let dx = (distance * angle.sin() + 0.5).floor() as i32;
+        let dz = (distance * angle.cos() + 0.5).floor() as i32;
+        let end = pos + IVec3::new(dx, y - 1, dz);
+        if !make_limb(ctx, config, end, end + IVec3::new(0, 5, 0), false) {
+            continue;
+        }
+
+        let (ox, oz) = (pos.x - end.x, pos.z - end.z);
+        let base = end.y as f64 - ((ox * ox + oz * oz) as f64).sqrt() * 0.381;
+        let base_y = if base > trunk_top as f64 {
+            trunk_top
+        } else {
+            base as i32
+        };
+        let base = IVec3::new(pos.x, base_y, pos.z);
+        if make_limb(ctx, config, base, end, false) {
+            clusters.push((end, base_y));
+        }
+    }
+
+    make_limb(ctx, config, pos, pos + IVec3::new(0, trunk_height, 0), true);
+
+    // Branches from the trunk to each cluster
+    let keep_branch = |base_y: i32| (base_y - pos.y) as f64 >= height as f64 * 0.2;
+    for &(cluster, base_y) in &clusters {
+        let base = IVec3::new(pos.x, base_y, pos.z);
+        if base != cluster && keep_branch(base_y) {
+            make_limb(ctx, config, base, cluster, true);
+        }
+    }
+
+    clusters
+        .into_iter()
+        .filter(|&(_, base_y)| keep_branch(base_y))
+        .map(|(cluster, _)| Attachment::new(cluster, 0, false))
+        .collect()
+}
+
+/// Java's `FancyTrunkPlacer.treeShape`.
+fn fancy_tree_shape(height: i32, y: i32) -> f32 {
+    if (y as f32) < height as f32 * 0.3 {
+        return -1.0;
+    }
+    let half = height as f32 / 2.0;
+    let offset = half - y as f32;
+    let mut radius = (half * half - offset * offset).sqrt();
+    if offset == 0.0 {
+        radius = half;
+    } else if offset.abs() >= half {
+        return 0.0;
+    }
+    radius * 0.5
+}
+
+/// Java's `FancyTrunkPlacer.makeLimb`: a straight line of logs, or with
+/// `place` false, whether the line is free.
+fn make_limb(
+    ctx: &mut FeatureContext,
+    config: &TreeConfig,
+    start: IVec3,
+    end: IVec3,
+    place: bool,
+) -> bool {
+    if !place && start == end {
+        return true;
+    }
+    let delta = end - start;
+    let steps = delta.abs().max_element();
+    let step = delta.as_vec3() / steps as f32;
+    for i in 0..=steps {
+        let offset = (step * i as f32 + 0.5).floor().as_ivec3();
+        let pos = start + offset;
+        if place {
+            place_log(ctx, config, pos);
+        } else if !is_free(ctx, pos) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Place the foliage of one attachment.
+fn place_foliage(
+    ctx: &mut FeatureContext,
+    config: &TreeConfig,
+    attachment: Attachment,
+    height: i32,
+    radius: i32,
+    offset: i32,
+) {
+    let kind = config.foliage_placer.kind;
+    let large = attachment.double_trunk;
+    let center = attachment.pos;
+    let row = |ctx: &mut FeatureContext, center: IVec3, range: i32, y: i32| {
+        place_leaves_row(ctx, config, kind, center, range, y, large);
+    };
+
+    match kind {
+        FoliagePlacerKind::Blob | FoliagePlacerKind::Cherry => {
+            for y in (offset - height..=offset).rev() {
+                let range = (radius + attachment.radius_offset - 1 - y / 2).max(0);
+                row(ctx, center, range, y);
+            }
+        }
+        FoliagePlacerKind::Bush => {
+            for y in (offset - height..=offset).rev() {
+                let range = radius + attachment.radius_offset - 1 - y;
+                row(ctx, center, range, y);
+            }
+        }
+        FoliagePlacerKind::Fancy => {
+            for y in (offset - height..=offset).rev() {
+                let edge = y == offset || y == offset - height;
+                row(ctx, center, radius + if edge { 0 } else { 1 }, y);
+            }
+        }
+        FoliagePlacerKind::Jungle => {
+            let rows = if large {
+                height
+            } else {
+                1 + ctx.random.next_int(2)
+            };
+            for y in (offset - rows..=offset).rev() {
+                row(ctx, center, radius + attachment.radius_offset + 1 - y, y);
+            }
+        }
+        FoliagePlacerKind::Spruce => {
+            let mut range = ctx.random.next_int(2);
+            let mut max_range = 1;
+            let mut min_range = 0;
+            for y in (-height..=offset).rev() {
+                row(ctx, center, range, y);
+                if range >= max_range {
+                    range = min_range;
+                    min_range = 1;
+                    max_range = (max_range + 1).min(radius + attachment.radius_offset);
+                } else {
+                    range += 1;
+                }
+            }
+        }
+        FoliagePlacerKind::Pine => {
+            let mut range = 0;
+            for y in (offset - height..=offset).rev() {
+                row(ctx, center, range, y);
+                if range >= 1 && y == offset - height + 1 {
+                    range -= 1;
+                } else if range < radius + attachment.radius_offset {
+                    range += 1;
+                }
+            }
+        }
+        FoliagePlacerKind::MegaPine => {
+            let mut previous = 0;
+            for y in center.y - height + offset..=center.y + offset {
+                let depth = center.y - y;
+                let taper = if height > 0 {
+                    (depth as f32 / height as f32 * 3.5).floor() as i32
+                } else {
+                    0
+                };
+                let range = radius + attachment.radius_offset + taper;
+                let widened = if depth > 0 && range == previous && y & 1 == 0 {
+                    range + 1
+                } else {
+                    range
+                };
+                row(ctx, IVec3::new(center.x, y, center.z), widened, 0);
+                previous = range;
+            }
+        }
+        FoliagePlacerKind::Acacia => {
+            let center = center + IVec3::new(0, offset, 0);
+            row(ctx, center, radius + attachment.radius_offset, -1 - height);
+            row(ctx, center, radius - 1, -height);
+            row(ctx, center, radius + attachment.radius_offset - 1, 0);
+        }
+        FoliagePlacerKind::DarkOak => {
+            let center = center + IVec3::new(0, offset, 0);
+            if large {
+                row(ctx, center, radius + 2, -1);
+                row(ctx, center, radius + 3, 0);
+                row(ctx, center, radius + 2, 1);
+                if ctx.random.next_int(2) != 0 {
+                    row(ctx, center, radius, 2);
+                }
+            } else {
+                row(ctx, center, radius + 2, -1);
+                row(ctx, center, radius + 1, 0);
+            }
+        }
+        FoliagePlacerKind::RandomSpread => {
+            let (radius, height) = (radius.max(1) as u32, height.max(1) as u32);
+            for _ in 0..LEAF_PLACEMENT_ATTEMPTS {
+                let dx = ctx.random.next_int(radius) - ctx.random.next_int(radius);
+                let dy = ctx.random.next_int(height) - ctx.random.next_int(height);
+                let dz = ctx.random.next_int(radius) - ctx.random.next_int(radius);
+                try_place_leaf(ctx, config, center + IVec3::new(dx, dy, dz));
+            }
+        }
+    }
+}
+
+/// Java's `FoliagePlacer.placeLeavesRow`: a square of leaves, minus the
+/// locations the placer skips.
+fn place_leaves_row(
+    ctx: &mut FeatureContext,
+    config: &TreeConfig,
+    kind: FoliagePlacerKind,
+    center: IVec3,
+    range: i32,
+    y: i32,
+    large: bool,
+) {
+    let extra = if large { 1 } else { 0 };
+    for dx in -range..=range + extra {
+        for dz in -range..=range + extra {
+            if !should_skip_signed(ctx, kind, dx, y, dz, range, large) {
+                try_place_leaf(ctx, config, center + IVec3::new(dx, y, dz));
+            }
+        }
+    }
+}
+
+/// Java's `FoliagePlacer.shouldSkipLocationSigned`.
+fn should_skip_signed(
+    ctx: &mut FeatureContext,
+    kind: FoliagePlacerKind,
+    dx: i32,
+    y: i32,
+    dz: i32,
+    range: i32,
+    large: bool,
+) -> bool {
+    // Dark oak trims the corners of its big middle layer
+    if kind == FoliagePlacerKind::DarkOak
+        && y == 0
+        && large
+        && (dx == -range || dx >= range)
+        && (dz == -range || dz >= range)
+    {
+        return true;
+    }
+
+    let (x, z) = if large {
+        (dx.abs().min((dx - 1).abs()), dz.abs().min((dz - 1).abs()))
+    } else {
+        (dx.abs(), dz.abs())
+    };
+    should_skip(ctx, kind, x, y, z, range)
+}
+
+/// Java's `FoliagePlacer.shouldSkipLocation`, per placer.
+fn should_skip(
+    ctx: &mut FeatureContext,
+    kind: FoliagePlacerKind,
+    x: i32,
+    y: i32,
+    z: i32,
+    range: i32,
+) -> bool {
+    match kind {
+        FoliagePlacerKind::Blob | FoliagePlacerKind::Cherry => {
+            x == range && z == range && (ctx.random.next_int(2) == 0 || y == 0)
+        }
+        FoliagePlacerKind::Bush => x == range && z == range && ctx.random.next_int(2) == 0,
+        FoliagePlacerKind::Fancy => {
+            (x as f32 + 0.5).powi(2) + (z as f32 + 0.5).powi(2) > (range * range) as f32
+        }
+        FoliagePlacerKind::Spruce | FoliagePlacerKind::Pine => {
+            x == range && z == range && range > 0
+        }
+        FoliagePlacerKind::Jungle | FoliagePlacerKind::MegaPine => {
+            x + z >= 7 || x * x + z * z > range * range
+        }
+        FoliagePlacerKind::Acacia => {
+            if y == 0 {
+                (x > 1 || z > 1) && x != 0 && z != 0
+            } else {
+                x == range && z == range && range > 0
+            }
+        }
+        FoliagePlacerKind::DarkOak => match y {
+            -1 => x == range && z == range,
+            1 => x + z > range * 2 - 2,
+            _ => false,
+        },
+        FoliagePlacerKind::RandomSpread => false,
+    }
+}
+
+/// Java's `FoliagePlacer.tryPlaceLeaf`.
+fn try_place_leaf(ctx: &mut FeatureContext, config: &TreeConfig, pos: IVec3) {
+    if !region::is_valid_tree_pos(ctx.region.get_block(pos)) {
+        return;
+    }
+    if let Some(state) = get_state(ctx, &config.foliage_provider, pos) {
+        super::place::set_state(&mut ctx.region, pos, &state);
+    }
+}
@@ -0,0 +1,668 @@
+//! Jigsaw assembly: growing a structure from its start piece.
+//!
+//! This follows Java's `JigsawPlacement.addPieces`. The start piece is
+//! picked from the start pool, then pieces are attached breadth first to
+//! the jigsaws of the pieces already placed, up to the structure's maximum
+//! depth. A piece only goes where it fits in the free space: inside the
+//! box `max_distance_from_center` around the start, and outside every
+//! other piece, except pieces attached inside their parent's box, which
+//! share the parent's space instead.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+
+use glam::IVec3;
+use unastar_noise::structure::{PoolAlias, Projection};
+use unastar_noise::{GenerationStep, JigsawStructure, PoolElement, TemplatePool, template_pool};
+
+use super::template::{JigsawBlock, TemplateManager};
+use crate::world::generator::BiomeSource;
+use crate::world::generator::xoroshiro::{JavaRandom, get_seed};
+
+/// A box of blocks, inclusive at both ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min: IVec3,
+    pub max: IVec3,
+}
+
+impl BoundingBox {
+    /// The box spanning two corners.
+    pub fn from_corners(a: IVec3, b: IVec3) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    pub fn moved(self, offset: IVec3) -> Self {
+        Self {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    pub fn is_inside(&self, pos: IVec3) -> bool {
+        self.min.cmple(pos).all() && pos.cmple(self.max).all()
+    }
+
+    /// Whether `other` lies entirely inside this box.
+    pub fn contains(&self, other: &BoundingBox) -> bool {
+        self.min.cmple(other.min).all() && other.max.cmple(self.max).all()
+    }
+
+    /// The smallest box holding both boxes.
+    pub fn union(self, other: BoundingBox) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    pub fn y_span(&self) -> i32 {
+        self.max.y - self.min.y + 1
+    }
+}
+
+/// Rotation of a piece around its position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    None,
+    Clockwise90,
+    Clockwise180,
+    CounterClockwise90,
+}
+
+impl Rotation {
+    /// Rotations in Java's `Rotation.values()` order.
+    pub const ALL: [Rotation; 4] = [
+        Rotation::None,
+        Rotation::Clockwise90,
+        Rotation::Clockwise180,
+        Rotation::CounterClockwise90,
+    ];
+
+    /// Rotate a position or direction around the origin.
+    pub fn apply(self, pos: IVec3) -> IVec3 {
+        match self {
+            Rotation::None => pos,
+            Rotation::Clockwise90 => IVec3::new(-pos.z, pos.y, pos.x),
+            Rotation::Clockwise180 => IVec3::new(-pos.x, pos.y, -pos.z),
+            Rotation::CounterClockwise90 => IVec3::new(pos.z, pos.y, -pos.x),
+        }
+    }
+}
+
+/// A placed pool element.
+#[derive(Debug, Clone)]
+pub struct PoolPiece {
+    pub element: &'static PoolElement,
+    /// Where the element's origin goes.
+    pub position: IVec3,
+    pub rotation: Rotation,
+    pub bounding_box: BoundingBox,
+}
+
+/// An assembled structure.
+pub struct StructureStart {
+    /// Structure name, without the `minecraft:` prefix.
+    pub structure: &'static str,
+    /// Generation step the pieces are placed in.
+    pub step: GenerationStep,
+    /// Pieces, the start piece first.
+    pub pieces: Vec<PoolPiece>,
+    pub(super) templates: Arc<TemplateManager>,
+}
+
+impl StructureStart {
+    /// Box around every piece.
+    pub fn bounding_box(&self) -> BoundingBox {
+        self.pieces
+            .iter()
+            .map(|piece| piece.bounding_box)
+            .reduce(BoundingBox::union)
+            .expect("a structure start has a start piece")
+    }
+}
+
+/// What assembly needs from the world.
+pub struct AssemblyContext<'a> {
+    pub seed: i64,
+    pub templates: &'a Arc<TemplateManager>,
+    pub biome_source: &'a dyn BiomeSource,
+    /// Java's `getFirstFreeHeight` with `WORLD_SURFACE_WG`: Y above the
+    /// highest non-air block of a column in the unfinished terrain.
+    pub first_free_height: &'a mut dyn FnMut(i32, i32) -> i32,
+    /// Lowest and highest block Y of the dimension.
+    pub min_y: i32,
+    pub max_y: i32,
+}
+
+/// Java's `WorldgenRandom.setLargeFeatureSeed`.
+pub fn large_feature_random(seed: i64, chunk_x: i32, chunk_z: i32) -> JavaRandom {
+    let mut random = JavaRandom::from_seed(seed);
+    let a = random.next_long();
+    let b = random.next_long();
+    random.set_seed((chunk_x as i64).wrapping_mul(a) ^ (chunk_z as i64).wrapping_mul(b) ^ seed);
+    random
+}
+
+/// Java's `Util.shuffle`.
+pub(super) fn shuffle<T>(list: &mut [T], random: &mut JavaRandom) {
+    for i in (2..=list.len()).rev() {
+        let j = random.next_int(i as u32) as usize;
+        list.swap(i - 1, j);
+    }
+}
+
+/// Pick from weighted entries (Java's `WeightedRandom.getRandomItem`).
+fn weighted<T: Copy>(entries: &[(T, i32)], random: &mut JavaRandom) -> Option<T> {
+    let total: i32 = entries.iter().map(|(_, weight)| weight).sum();
+    if total <= 0 {
+        return None;
+    }
+    let mut pick = random.next_int(total as u32);
+    for &(entry, weight) in entries {
+        pick -= weight;
+        if pick < 0 {
+            return Some(entry);
+        }
+    }
+    None
+}
+
+/// Name without the `minecraft:` prefix.
+fn strip(name: &str) -> &str {
+    name.strip_prefix("minecraft:").unwrap_or(name)
+}
+
+/// Resolve a structure's pool aliases (Java's `PoolAliasLookup.create`).
+fn resolve_aliases(
+    aliases: &[PoolAlias],
+    pos: IVec3,
+    seed: i64,
+) -> HashMap<&'static str, &'static str> {
+    let mut resolved = HashMap::new();
+    if aliases.is_empty() {
+        return resolved;
+    }
+    let factory_seed = JavaRandom::from_seed(seed).next_long();
+    let mut random = JavaRandom::from_seed(get_seed(pos.x, pos.y, pos.z) ^ factory_seed);
+    resolve_into(aliases, &mut random, &mut resolved);
+    resolved
+}
+
+fn resolve_into(
+    aliases: &[PoolAlias],
+    random: &mut JavaRandom,
+    resolved: &mut HashMap<&'static str, &'static str>,
+) {
+    for alias in aliases {
+        match *alias {
+            PoolAlias::Direct { alias, target } => {
+                resolved.insert(strip(alias), target);
+            }
+            PoolAlias::Random { alias, targets } => {
+                if let Some(target) = weighted(targets, random) {
+                    resolved.insert(strip(alias), target);
+                }
+            }
+            PoolAlias::RandomGroup { groups } => {
+                if let Some(group) = weighted(groups, random) {
+                    resolve_into(group, random, resolved);
+                }
+            }
+        }
+    }
+}
+
+/// Pool elements repeated by weight, as Java's `StructureTemplatePool`
+/// keeps them.
+fn expand(pool: &'static TemplatePool) -> Vec<&'static PoolElement> {
+    pool.elements
+        .iter()
+        .flat_map(|(element, weight)| std::iter::repeat_n(element, (*weight).max(0) as usize))
+        .collect()
+}
+
+/// The single jigsaw of a feature element.
+fn feature_jigsaw(pos: IVec3) -> JigsawBlock {
+    JigsawBlock::new(
+        pos,
+        IVec3::NEG_Y,
+        IVec3::Z,
+        "minecraft:bottom",
+        "minecraft:empty",
+        "minecraft:empty",
+    )
+}
+
+/// Bounding box of an element placed at `pos`, or `None` for empty
+/// elements and missing templates.
+pub(super) fn element_box(
+    templates: &TemplateManager,
+    element: &PoolElement,
+    pos: IVec3,
+    rotation: Rotation,
+) -> Option<BoundingBox> {
+    match element {
+        PoolElement::Single { location, .. } => {
+            let template = templates.get(location)?;
+            let far = rotation.apply(template.size - IVec3::ONE);
+            Some(BoundingBox::from_corners(IVec3::ZERO, far).moved(pos))
+        }
+        PoolElement::List { elements, .. } => elements
+            .iter()
+            .filter(|element| !matches!(element, PoolElement::Empty))
+            .map(|element| element_box(templates, element, pos, rotation))
+            .reduce(|a, b| Some(a?.union(b?)))
+            .flatten(),
+        PoolElement::Feature { .. } => Some(BoundingBox::from_corners(pos, pos)),
+        PoolElement::Empty => None,
+    }
+}
+
+/// Jigsaws of an element placed at `pos`, in template order.
+fn element_jigsaws(
+    templates: &TemplateManager,
+    element: &PoolElement,
+    pos: IVec3,
+    rotation: Rotation,
+) -> Vec<JigsawBlock> {
+    match element {
+        PoolElement::Single { location, .. } => {
+            let Some(template) = templates.get(location) else {
+                return Vec::new();
+            };
+            template
+                .jigsaws
+                .iter()
+                .map(|jigsaw| JigsawBlock {
+                    pos: pos + rotation.apply(jigsaw.pos),
+                    front: rotation.apply(jigsaw.front),
+                    top: rotation.apply(jigsaw.top),
+                    ..jigsaw.clone()
+                })
+                .collect()
+        }
+        PoolElement::List { elements, .. } => elements
+            .first()
+            .map(|element| element_jigsaws(templates, element, pos, rotation))
+            .unwrap_or_default(),
+        PoolElement::Feature { .. } => vec![feature_jigsaw(pos)],
+        PoolElement::Empty => Vec::new(),
+    }
+}
+
+/// Java's `getShuffledJigsawBlocks`: the jigsaws shuffled, then sorted by
+/// selection priority.
+fn shuffled_jigsaws(
+    templates: &TemplateManager,
+    element: &PoolElement,
+    pos: IVec3,
+    rotation: Rotation,
+    random: &mut JavaRandom,
+) -> Vec<JigsawBlock> {
+    let mut jigsaws = element_jigsaws(templates, element, pos, rotation);
+    if matches!(
+        element,
+        PoolElement::Single { .. } | PoolElement::List { .. }
+    ) {
+        shuffle(&mut jigsaws, random);
+        jigsaws.sort_by_key(|jigsaw| std::cmp::Reverse(jigsaw.selection_priority));
+    }
+    jigsaws
+}
+
+/// Java's `JigsawBlock.canAttach`.
+fn can_attach(parent: &JigsawBlock, child: &JigsawBlock) -> bool {
+    parent.front == -child.front
+        && (parent.rollable || parent.top == child.top)
+        && strip(&parent.target) == strip(&child.name)
+}
+
+/// Free space pieces may be placed in.
+struct FreeSpace {
+    bounds: BoundingBox,
+    /// Boxes of the pieces already placed in the space.
+    taken: Vec<BoundingBox>,
+}
+
+impl FreeSpace {
+    fn fits(&self, bb: &BoundingBox) -> bool {
+        self.bounds.contains(bb) && !self.taken.iter().any(|taken| taken.intersects(bb))
+    }
+}
+
+/// A piece waiting for its jigsaws to be filled.
+struct PieceState {
+    piece: usize,
+    free: usize,
+    depth: i32,
+}
+
+struct Assembler<'a, 'b> {
+    ctx: &'b mut AssemblyContext<'a>,
+    random: JavaRandom,
+    aliases: HashMap<&'static str, &'static str>,
+    max_depth: i32,
+    use_expansion_hack: bool,
+    pieces: Vec<PoolPiece>,
+    free: Vec<FreeSpace>,
+    /// Pieces to expand, by placement priority, first in first out.
+    queue: BTreeMap<i32, VecDeque<PieceState>>,
+    /// Tallest element of each pool, for the expansion hack.
+    max_sizes: HashMap<String, i32>,
+}
+
+/// Assemble a jigsaw structure starting in a chunk (Java's
+/// `JigsawStructure.findGenerationPoint`).
+///
+/// Returns `None` if there is no start piece or its position isn't in one
+/// of the structure's biomes.
+pub fn assemble(
+    ctx: &mut AssemblyContext,
+    name: &'static str,
+    structure: &JigsawStructure,
+    chunk_x: i32,
+    chunk_z: i32,
+) -> Option<StructureStart> {
+    let mut random = large_feature_random(ctx.seed, chunk_x, chunk_z);
+    let start_y = structure
+        .start_height
+        .sample(&mut random, ctx.min_y, ctx.max_y);
+    let start_pos = IVec3::new(chunk_x * 16, start_y, chunk_z * 16);
+    let aliases = resolve_aliases(structure.pool_aliases, start_pos, ctx.seed);
+
+    let rotation = Rotation::ALL[random.next_int(4) as usize];
+    let pool_name = aliases
+        .get(strip(structure.start_pool))
+        .copied()
+        .unwrap_or(structure.start_pool);
+    let pool = template_pool(pool_name).or_else(|| template_pool(structure.start_pool))?;
+    let elements = expand(pool);
+    if elements.is_empty() {
+        return None;
+    }
+    let element = elements[random.next_int(elements.len() as u32) as usize];
+    if matches!(element, PoolElement::Empty) {
+        return None;
+    }
+
+    // Move the piece so the start jigsaw is at the start position
+    let jigsaw_pos = match structure.start_jigsaw_name {
+        Some(jigsaw_name) => {
+            shuffled_jigsaws(ctx.templates, element, start_pos, rotation, &mut random)
+                .into_iter()
+                .find(|jigsaw| strip(&jigsaw.name) == strip(jigsaw_name))?
+                .pos
+        }
+        None => start_pos,
+    };
+    let offset = jigsaw_pos - start_pos;
+    let mut position = start_pos - offset;
+    let mut bb = element_box(ctx.templates, element, position, rotation)?;
+
+    let center_x = (bb.max.x + bb.min.x) / 2;
+    let center_z = (bb.max.z + bb.min.z) / 2;
+    let ground_y = match structure.project_start_to_heightmap {
+        Some(_) => start_pos.y + (ctx.first_free_height)(center_x, center_z),
+        None => position.y,
+    };
+    // Ground level delta of the element; 1 for every placeable element
+    let shift = IVec3::new(0, ground_y - (bb.min.y + 1), 0);
+    bb = bb.moved(shift);
+    position += shift;
+    let center_y = ground_y + offset.y;
+
+    let biome = ctx
+        .biome_source
+        .get_biome(center_x & !3, center_y & !3, center_z & !3);
+    if !super::is_valid_biome(structure.biomes, biome) {
+        return None;
+    }
+
+    let mut assembler = Assembler {
+        random,
+        aliases,
+        max_depth: structure.size,
+        use_expansion_hack: structure.use_expansion_hack,
+        pieces: vec![PoolPiece {
+            element,
+            position,
+            rotation,
+            bounding_box: bb,
+        }],
+        free: Vec::new(),
+        queue: BTreeMap::new(),
+        max_sizes: HashMap::new(),
+        ctx,
+    };
+    if structure.size > 0 {
+        let distance = structure.max_distance_from_center;
+        let (bottom, top) = structure.dimension_padding;
+        let bounds = BoundingBox {
+            min: IVec3::new(
+                center_x - distance,
+                (center_y - distance).max(assembler.ctx.min_y + bottom),
+                center_z - distance,
+            ),
+            max: IVec3::new(
+                center_x + distance,
+                (center_y + distance).min(assembler.ctx.max_y - top),
+                center_z + distance,
+            ),
+        };
+        assembler.free.push(FreeSpace {
+            bounds,
+            taken: vec![bb],
+        });
+        assembler.place_children(PieceState {
+            piece: 0,
+            free: 0,
+            depth: 0,
+        });
+        while let Some(state) = assembler.next_state() {
+            assembler.place_children(state);
+        }
+    }
+
+    Some(StructureStart {
+        structure: strip(name),
+        step: structure.step,
+        pieces: assembler.pieces,
+        templates: assembler.ctx.templates.clone(),
+    })
+}
+
+impl Assembler<'_, '_> {
+    fn next_state(&mut self) -> Option<PieceState> {
+        let mut entry = self.queue.last_entry()?;
+        let state = entry.get_mut().pop_front();
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+        state
+    }
+
+    /// Look up the pool a jigsaw targets, following aliases.
+    fn pool<'n>(&self, name: &'n str) -> Option<(&'n str, &'static TemplatePool)> {
+        let name = self.aliases.get(strip(name)).copied().unwrap_or(name);
+        let pool = template_pool(name)?;
+        Some((name, pool))
+    }
+
+    /// Tallest element of a pool (Java's `StructureTemplatePool.getMaxSize`).
+    fn max_size(&mut self, name: &str, pool: &'static TemplatePool) -> i32 {
+        if let Some(&size) = self.max_sizes.get(name) {
+            return size;
+        }
+        let templates = self.ctx.templates;
+        let size = pool
+            .elements
+            .iter()
+            .filter_map(|(element, _)| element_box(templates, element, IVec3::ZERO, Rotation::None))
+            .map(|bb| bb.y_span())
+            .max()
+            .unwrap_or(0);
+        self.max_sizes.insert(name.to_string(), size);
+        size
+    }
+
+    /// Room reserved above a small piece for the tallest piece its inward
+    /// jigsaws could lead to (Java's expansion hack for villages).
+    fn expansion(&mut self, jigsaws: &[JigsawBlock], bb: &BoundingBox) -> i32 {
+        let mut expansion = 0;
+        for jigsaw in jigsaws {
+            if !bb.is_inside(jigsaw.pos + jigsaw.front) {
+                continue;
+            }
+            let Some((name, pool)) = self.pool(&jigsaw.pool) else {
+                continue;
+            };
+            let mut size = self.max_size(name, pool);
+            if let Some(fallback) = template_pool(pool.fallback) {
+                size = size.max(self.max_size(pool.fallback, fallback));
+            }
+            expansion = expansion.max(size);
+        }
+        expansion
+    }
+
+    /// Attach pieces to the jigsaws of a piece (Java's
+    /// `Placer.tryPlacingChildren`).
+    fn place_children(&mut self, state: PieceState) {
+        let templates = self.ctx.templates.clone();
+        let parent = self.pieces[state.piece].clone();
+        let rigid = parent.element.projection() == Projection::Rigid;
+        let parent_min_y = parent.bounding_box.min.y;
+        let mut internal_free: Option<usize> = None;
+
+        let jigsaws = shuffled_jigsaws(
+            &templates,
+            parent.element,
+            parent.position,
+            parent.rotation,
+            &mut self.random,
+        );
+        'jigsaws: for jigsaw in jigsaws {
+            let connection = jigsaw.pos + jigsaw.front;
+            let jigsaw_y = jigsaw.pos.y - parent_min_y;
+            let mut surface: Option<i32> = None;
+
+            let Some((target_name, target)) = self.pool(&jigsaw.pool) else {
+                continue;
+            };
+            if target.elements.is_empty() && strip(target_name) != "empty" {
+                continue;
+            }
+            let Some(fallback) = template_pool(target.fallback) else {
+                continue;
+            };
+            if fallback.elements.is_empty() && strip(target.fallback) != "empty" {
+                continue;
+            }
+
+            // Pieces attached inside their parent only need to fit the parent
+            let free = if parent.bounding_box.is_inside(connection) {
+                *internal_free.get_or_insert_with(|| {
+                    self.free.push(FreeSpace {
+                        bounds: parent.bounding_box,
+                        taken: Vec::new(),
+                    });
+                    self.free.len() - 1
+                })
+            } else {
+                state.free
+            };
+
+            let mut candidates: Vec<&PoolElement> = Vec::new();
+            if state.depth != self.max_depth {
+                let mut elements = expand(target);
+                shuffle(&mut elements, &mut self.random);
+                candidates.extend(elements);
+            }
+            let mut elements = expand(fallback);
+            shuffle(&mut elements, &mut self.random);
+            candidates.extend(elements);
+
+            for candidate in candidates {
+                if matches!(candidate, PoolElement::Empty) {
+                    break;
+                }
+                let mut rotations = Rotation::ALL;
+                shuffle(&mut rotations, &mut self.random);
+                for rotation in rotations {
+                    let child_jigsaws = shuffled_jigsaws(
+                        &templates,
+                        candidate,
+                        IVec3::ZERO,
+                        rotation,
+                        &mut self.random,
+                    );
+                    let Some(origin_box) =
+                        element_box(&templates, candidate, IVec3::ZERO, rotation)
+                    else {
+                        continue;
+                    };
+                    let expansion = if self.use_expansion_hack && origin_box.y_span() <= 16 {
+                        self.expansion(&child_jigsaws, &origin_box)
+                    } else {
+                        0
+                    };
+
+                    for child_jigsaw in &child_jigsaws {
+                        if !can_attach(&jigsaw, child_jigsaw) {
+                            continue;
+                        }
+                        let offset = connection - child_jigsaw.pos;
+                        let child_box = origin_box.moved(offset);
+                        let child_rigid = candidate.projection() == Projection::Rigid;
+                        let child_jigsaw_y = child_jigsaw.pos.y;
+                        let step_y = jigsaw_y - child_jigsaw_y + jigsaw.front.y;
+                        let child_min_y = if rigid && child_rigid {
+                            parent_min_y + step_y
+                        } else {
+                            let height = *surface.get_or_insert_with(|| {
+                                (self.ctx.first_free_height)(jigsaw.pos.x, jigsaw.pos.z)
+                            });
+                            height - child_jigsaw_y
+                        };
+                        let shift = IVec3::new(0, child_min_y - child_box.min.y, 0);
+                        let mut child_box = child_box.moved(shift);
+                        if expansion > 0 {
+                            let height = (expansion + 1).max(child_box.max.y - child_box.min.y);
+                            child_box.max.y = child_box.max.y.max(child_box.min.y + height);
+                        }
+
+                        if !self.free[free].fits(&child_box) {
+                            continue;
+                        }
+                        self.free[free].taken.push(child_box);
+                        self.pieces.push(PoolPiece {
+                            element: candidate,
+                            position: offset + shift,
+                            rotation,
+                            bounding_box: child_box,
+                        });
+                        if state.depth < self.max_depth {
+                            self.queue
+                                .entry(jigsaw.placement_priority)
+                                .or_default()
+                                .push_back(PieceState {
+                                    piece: self.pieces.len() - 1,
+                                    free,
+                                    depth: state.depth + 1,
+                                });
+                        }
+                        continue 'jigsaws;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,450 @@
+//! Jigsaw structures: villages, pillager outposts, trial chambers and the
+//! other structures grown from template pools.
+//!
+//! Generation follows Java's in three parts:
+//!
+//! 1. A structure set's random spread placement picks one chunk per region
+//!    of `spacing` chunks, which its frequency and exclusion zone may veto
+//!    ([`start_chunks`])
+//! 2. One of the set's structures is assembled there ([`generate_start`]):
+//!    the start piece comes from the start pool, and pieces are attached to
+//!    its jigsaws until the maximum depth
+//! 3. While decorating, each chunk places the parts of the pieces crossing
+//!    it in the structure's generation step
+//!    ([`StructureStart::place_in_chunk`])
+//!
+//! ## Templates
+//!
+//! Pool elements name structure templates, which aren't part of the
+//! worldgen data. They are loaded from Bedrock `.mcstructure` exports in
+//! the directory set by `server.structure_templates` (see
+//! [`TemplateManager`]); structures whose templates are missing don't
+//! generate.
+//!
+//! ## Java Parity
+//!
+//! Terrain isn't adapted around pieces
+//! (Java's beardifier), block entities and entities aren't placed, and
+//! structure feature seeds only count jigsaw structures.
+
+mod assembly;
+mod place;
+mod state;
+mod template;
+
+pub use assembly::{
+    AssemblyContext, BoundingBox, PoolPiece, Rotation, StructureStart, assemble,
+    large_feature_random,
+};
+pub use template::{JigsawBlock, StructureTemplate, TemplateBlock, TemplateManager};
+
+use unastar_noise::structure::{FrequencyReductionMethod, StructurePlacement};
+use unastar_noise::{STRUCTURE_SETS, StructureSet, jigsaw_structure, structure_set};
+
+use crate::world::generator::Biome;
+use crate::world::generator::xoroshiro::JavaRandom;
+
+/// Chunks around a chunk that structure starts can reach it from: pieces
+/// stay within `max_distance_from_center` of the start, at most 128 blocks.
+pub const START_SEARCH_RADIUS: i32 = 128 / 16 + 1;
+
+/// Biomes of the `has_structure` biome tags used by jigsaw structures.
+const HAS_STRUCTURE: &[(&str, &[&str])] = &[
+    ("village_plains", &["plains", "meadow"]),
+    ("village_desert", &["desert"]),
+    ("village_savanna", &["savanna"]),
+    ("village_snowy", &["snowy_plains"]),
+    ("village_taiga", &["taiga"]),
+    (
+        "pillager_outpost",
+        &[
+            "desert",
+            "plains",
+            "savanna",
+            "snowy_plains",
+            "taiga",
+            "meadow",
+            "frozen_peaks",
+            "jagged_peaks",
+            "stony_peaks",
+            "snowy_slopes",
+            "cherry_grove",
+            "grove",
+        ],
+    ),
+    (
+        "trail_ruins",
+        &[
+            "taiga",
+            "snowy_taiga",
+            "old_growth_pine_taiga",
+            "old_growth_spruce_taiga",
+            "old_growth_birch_forest",
+            "jungle",
+        ],
+    ),
+    ("ancient_city", &["deep_dark"]),
+    (
+        "bastion_remnant",
+        &[
+            "crimson_forest",
+            "nether_wastes",
+            "soul_sand_valley",
+            "warped_forest",
+        ],
+    ),
+];
+
+/// Whether a biome is one of a structure's biomes, given as names and
+/// `#minecraft:has_structure/...` tags.
+pub fn is_valid_biome(biomes: &[&str], biome: Biome) -> bool {
+    biomes.iter().any(|entry| match entry.strip_prefix('#') {
+        Some(tag) => {
+            let Some(structure) = tag.strip_prefix("minecraft:has_structure/") else {
+                return false;
+            };
+            if structure == "trial_chambers" {
+                // Every overworld biome but the deep dark
+                return !matches!(
+                    biome,
+                    Biome::DeepDark
+                        | Biome::TheVoid
+                        | Biome::NetherWastes
+                        | Biome::SoulSandValley
+                        | Biome::CrimsonForest
+                        | Biome::WarpedForest
+                        | Biome::BasaltDeltas
+                        | Biome::TheEnd
+                        | Biome::SmallEndIslands
+                        | Biome::EndMidlands
+                        | Biome::EndHighlands
+                        | Biome::EndBarrens
+                );
+            }
+            HAS_STRUCTURE
+                .iter()
+                .find(|(name, _)| *name == structure)
+                .is_some_and(|(_, names)| {
+                    names
+                        .iter()
+                        .any(|name| Biome::from_name(name) == Some(biome))
+                })
+        }
+        None => Biome::from_name(entry) == Some(biome),
+    })
+}
+
+/// Structure sets of jigsaw structures with a random spread placement.
+pub fn jigsaw_sets() -> impl Iterator<Item = (&'static str, &'static StructureSet)> {
+    STRUCTURE_SETS.iter().filter_map(|name| {
+        let set = structure_set(name)?;
+        let jigsaw = set
+            .structures
+            .iter()
+            .any(|(structure, _)| jigsaw_structure(structure).is_some());
+        (jigsaw && matches!(set.placement, StructurePlacement::RandomSpread { .. }))
+            .then_some((*name, set))
+    })
+}
+
+/// Java's `WorldgenRandom.setLargeFeatureWithSalt`.
+fn large_feature_with_salt(seed: i64, x: i32, z: i32, salt: i32) -> JavaRandom {
+    JavaRandom::from_seed(
+        (x as i64)
+            .wrapping_mul(341873128712)
+            .wrapping_add((z as i64).wrapping_mul(132897987541))
+            .wrapping_add(seed)
+            .wrapping_add(salt as i64),
+    )
+}
+
+/// Chunks between two corners (inclusive) holding starts of a set: the
+/// placement chunks that pass the frequency and exclusion zone checks
+/// (Java's `StructurePlacement.isStructureChunk`).
+pub fn start_chunks(
+    set: &StructureSet,
+    seed: i64,
+    min: (i32, i32),
+    max: (i32, i32),
+) -> Vec<(i32, i32)> {
+    let StructurePlacement::RandomSpread {
+        salt,
+        spacing,
+        separation,
+        spread_type,
+        frequency,
+        frequency_reduction_method,
+        exclusion_zone,
+    } = set.placement
+    else {
+        return Vec::new();
+    };
+
+    let mut chunks = Vec::new();
+    for region_x in min.0.div_euclid(spacing)..=max.0.div_euclid(spacing) {
+        for region_z in min.1.div_euclid(spacing)..=max.1.div_euclid(spacing) {
+            // Java's RandomSpreadStructurePlacement.getPotentialStructureChunk
+            let mut random = large_feature_with_salt(seed, region_x, region_z, salt);
+            let chunk_x =
+                region_x * spacing + spread_type.evaluate(&mut random, spacing - separation);
+            let chunk_z =
+                region_z * spacing + spread_type.evaluate(&mut random, spacing - separation);
+            if chunk_x < min.0 || chunk_x > max.0 || chunk_z < min.1 || chunk_z > max.1 {
+                continue;
+            }
+
+            if frequency < 1.0
+                && !should_generate(
+                    frequency_reduction_method,
+                    frequency,
+                    seed,
+                    salt,
+                    chunk_x,
+                    chunk_z,
+                )
+            {
+                continue;
+            }
+            if let Some((other, distance)) = exclusion_zone
+                && let Some(other) = structure_set(other)
+                && !start_chunks(
+                    other,
+                    seed,
+                    (chunk_x - distance, chunk_z - distance),
+                    (chunk_x + distance, chunk_z + distance),
+                )
+                .is_empty()
+            {
+                continue;
+            }
+            chunks.push((chunk_x, chunk_z));
+        }
+    }
+    chunks
+}
+
+/// Java's `StructurePlacement.FrequencyReductionMethod`.
+fn should_generate(
+    method: FrequencyReductionMethod,
+    frequency: f32,
+    seed: i64,
+    salt: i32,
+    chunk_x: i32,
+    chunk_z: i32,
+) -> bool {
+    match method {
+        FrequencyReductionMethod::Default => {
+            large_feature_with_salt(seed, chunk_x, chunk_z, salt).next_float() < frequency
+        }
+        FrequencyReductionMethod::LegacyType1 => {
+            let (x, z) = (chunk_x >> 4, chunk_z >> 4);
+            let mut random = JavaRandom::from_seed((x ^ z << 4) as i64 ^ seed);
+            random.skip(1);
+            random.next_int((1.0 / frequency) as u32) == 0
+        }
+        FrequencyReductionMethod::LegacyType2 => {
+            large_feature_with_salt(seed, chunk_x, chunk_z, 10387320).next_float() < frequency
+        }
+        FrequencyReductionMethod::LegacyType3 => {
+            large_feature_random(seed, chunk_x, chunk_z).next_double() < frequency as f64
+        }
+    }
+}
+
+/// Assemble one of a set's structures at a start chunk (Java's
+/// `ChunkGenerator.createStructures`).
+///
+/// Structures are tried in a weighted random order until one fits its
+/// biome.
+pub fn generate_start(
+    ctx: &mut AssemblyContext,
+    set: &StructureSet,
+    chunk_x: i32,
+    chunk_z: i32,
+) -> Option<StructureStart> {
+    let try_structure = |ctx: &mut AssemblyContext, name: &'static str| {
+        assemble(ctx, name, jigsaw_structure(name)?, chunk_x, chunk_z)
+    };
+    if let [(name, _)] = set.structures {
+        return try_structure(ctx, name);
+    }
+
+    let mut entries = set.structures.to_vec();
+    let mut total: i32 = entries.iter().map(|(_, weight)| weight).sum();
+    let mut random = large_feature_random(ctx.seed, chunk_x, chunk_z);
+    while !entries.is_empty() && total > 0 {
+        let mut pick = random.next_int(total as u32);
+        let index = entries
+            .iter()
+            .position(|(_, weight)| {
+                pick -= weight;
+                pick < 0
+            })
+            .unwrap_or(entries.len() - 1);
+        let (name, weight) = entries[index];
+        if let Some(start) = try_structure(ctx, name) {
+            return Some(start);
+        }
+        entries.remove(index);
+        total -= weight;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use glam::IVec3;
+
+    use super::*;
+    use crate::world::chunk::{Chunk, blocks};
+    use crate::world::generator::BiomeSource;
+    use crate::world::generator::feature::{self, DecorationRegion};
+
+    struct PlainsOnly;
+
+    impl BiomeSource for PlainsOnly {
+        fn get_biome(&self, _x: i32, _y: i32, _z: i32) -> Biome {
+            Biome::Plains
+        }
+    }
+
+    /// A solid box of one block.
+    fn solid(size: IVec3, block: u32) -> StructureTemplate {
+        let mut template = StructureTemplate {
+            size,
+            ..Default::default()
+        };
+        for x in 0..size.x {
+            for y in 0..size.y {
+                for z in 0..size.z {
+                    let pos = IVec3::new(x, y, z);
+                    template.blocks.push(TemplateBlock { pos, block });
+                }
+            }
+        }
+        template
+    }
+
+    /// Outpost templates: a plank plate with a stone tower on top.
+    fn outpost_templates() -> Arc<TemplateManager> {
+        let templates = Arc::new(TemplateManager::new("/nonexistent"));
+        let mut plate = solid(IVec3::new(5, 1, 5), *blocks::SPRUCE_PLANKS);
+        plate.jigsaws.push(JigsawBlock::new(
+            IVec3::new(2, 0, 2),
+            IVec3::Y,
+            IVec3::NEG_Z,
+            "minecraft:bottom",
+            "minecraft:tower",
+            "minecraft:pillager_outpost/towers",
+        ));
+        templates.insert("pillager_outpost/base_plate", plate);
+
+        let mut tower = solid(IVec3::new(3, 4, 3), *blocks::STONE);
+        tower.jigsaws.push(JigsawBlock::new(
+            IVec3::new(1, 0, 1),
+            IVec3::NEG_Y,
+            IVec3::NEG_Z,
+            "minecraft:tower",
+            "minecraft:empty",
+            "minecraft:empty",
+        ));
+        templates.insert("pillager_outpost/watchtower", tower.clone());
+        templates.insert("pillager_outpost/watchtower_overgrown", tower);
+        templates
+    }
+
+    /// Stone up to Y=63 under a layer of grass, around chunk (0, 0).
+    fn flat_region() -> DecorationRegion {
+        let chunks = (0..9)
+            .map(|i| {
+                let mut chunk = Chunk::new(i % 3 - 1, i / 3 - 1);
+                for x in 0..16 {
+                    for z in 0..16 {
+                        for y in -64..64 {
+                            chunk.set_block(x, y, z, 0, *blocks::STONE);
+                        }
+                        chunk.set_block(x, 64, z, 0, *blocks::GRASS_BLOCK);
+                    }
+                }
+                chunk
+            })
+            .collect();
+        DecorationRegion::new(0, 0, chunks, -64, 319)
+    }
+
+    #[test]
+    fn test_assemble_and_place() {
+        let templates = outpost_templates();
+        let mut first_free_height = |_, _| 65;
+        let mut ctx = AssemblyContext {
+            seed: 12345,
+            templates: &templates,
+            biome_source: &PlainsOnly,
+            first_free_height: &mut first_free_height,
+            min_y: -64,
+            max_y: 319,
+        };
+        let outpost = jigsaw_structure("pillager_outpost").unwrap();
+        let start = assemble(&mut ctx, "pillager_outpost", outpost, 0, 0).unwrap();
+
+        // The plate sinks into the ground and the tower stands on it
+        let boxes: Vec<_> = start
+            .pieces
+            .iter()
+            .map(|piece| piece.bounding_box)
+            .collect();
+        let [plate, tower] = boxes[..] else {
+            panic!("expected two pieces, got {}", boxes.len());
+        };
+        assert_eq!((plate.min.y, plate.y_span()), (64, 1));
+        assert_eq!((tower.min.y, tower.y_span()), (65, 4));
+        assert!(plate.contains(&BoundingBox {
+            min: IVec3::new(tower.min.x, 64, tower.min.z),
+            max: IVec3::new(tower.max.x, 64, tower.max.z),
+        }));
+
+        let mut chunk = Chunk::new(0, 0);
+        feature::decorate(flat_region(), 12345, &PlainsOnly, &[Arc::new(start)])
+            .apply(0, 0, &mut chunk);
+        for (bb, block) in [(plate, *blocks::SPRUCE_PLANKS), (tower, *blocks::STONE)] {
+            for x in bb.min.x.max(0)..=bb.max.x.min(15) {
+                for z in bb.min.z.max(0)..=bb.max.z.min(15) {
+                    for y in bb.min.y..=bb.max.y {
+                        assert_eq!(chunk.get_block(x as u8, y as i16, z as u8, 0), block);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_valid_biome() {
+        let village = jigsaw_structure("village_plains").unwrap();
+        assert!(is_valid_biome(village.biomes, Biome::Plains));
+        assert!(!is_valid_biome(village.biomes, Biome::Desert));
+
+        let chambers = jigsaw_structure("trial_chambers").unwrap();
+        assert!(is_valid_biome(chambers.biomes, Biome::Desert));
+        assert!(!is_valid_biome(chambers.biomes, Biome::DeepDark));
+    }
+
+    #[test]
+    fn test_start_chunks() {
+        let (_, villages) = jigsaw_sets().find(|(name, _)| *name == "villages").unwrap();
+        // One placement chunk per 34x34 chunk region
+        let chunks = start_chunks(villages, 12345, (0, 0), (33, 33));
+        assert_eq!(chunks.len(), 1);
+        let (x, z) = chunks[0];
+        assert!((0..26).contains(&x) && (0..26).contains(&z));
+
+        // Outposts keep 10 chunks away from villages
+        let (_, outposts) = jigsaw_sets()
+            .find(|(name, _)| *name == "pillager_outposts")
+            .unwrap();
+        for (x, z) in start_chunks(outposts, 12345, (-200, -200), (200, 200)) {
+            assert!(start_chunks(villages, 12345, (x - 10, z - 10), (x + 10, z + 10)).is_empty());
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Placing assembled pieces into the chunks being decorated.
+//!
+//! Java's `StructureStart.placeInChunk`: each chunk places the parts of the
+//! pieces inside it. A template's blocks all go through the processors
+//! first, so a piece comes out the same in every chunk it crosses, then
+//! only the blocks in the chunk are turned with the piece and set.
+
+use glam::IVec3;
+use unastar_noise::PoolElement;
+use unastar_noise::structure::{PosRuleTest, Processor, Projection};
+
+use super::assembly::{PoolPiece, StructureStart, shuffle};
+use super::template::StructureTemplate;
+use crate::world::chunk::blocks;
+use crate::world::generator::feature::{FeatureContext, Heightmap, IntProvider, region};
+use crate::world::generator::xoroshiro::{JavaRandom, get_seed};
+
+/// A template block on its way into the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BlockInfo {
+    /// Position in the template.
+    local: IVec3,
+    /// World position.
+    pos: IVec3,
+    block: u32,
+}
+
+impl StructureStart {
+    /// Place the parts of the pieces inside a chunk.
+    pub fn place_in_chunk(&self, ctx: &mut FeatureContext, seed: i64, chunk_x: i32, chunk_z: i32) {
+        let start = &self.pieces[0].bounding_box;
+        let pivot = IVec3::new(
+            start.min.x + (start.max.x - start.min.x + 1) / 2,
+            start.min.y,
+            start.min.z + (start.max.z - start.min.z + 1) / 2,
+        );
+        let (min_x, min_z) = (chunk_x * 16, chunk_z * 16);
+
+        for piece in &self.pieces {
+            let bb = &piece.bounding_box;
+            if bb.max.x < min_x
+                || bb.min.x > min_x + 15
+                || bb.max.z < min_z
+                || bb.min.z > min_z + 15
+            {
+                continue;
+            }
+            self.place_element(ctx, seed, piece, piece.element, pivot, chunk_x, chunk_z);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn place_element(
+        &self,
+        ctx: &mut FeatureContext,
+        seed: i64,
+        piece: &PoolPiece,
+        element: &PoolElement,
+        pivot: IVec3,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) {
+        match *element {
+            PoolElement::Single {
+                location,
+                processors,
+                projection,
+                legacy,
+            } => {
+                let Some(template) = self.templates.get(location) else {
+                    return;
+                };
+                let infos = process(
+                    ctx, seed, &template, piece, processors, projection, legacy, pivot,
+                );
+                for info in infos {
+                    if info.pos.x >> 4 == chunk_x && info.pos.z >> 4 == chunk_z {
+                        let block = template.rotate(info.block, piece.rotation);
+                        ctx.region.set_block(info.pos, block);
+                    }
+                }
+            }
+            PoolElement::List { elements, .. } => {
+                for element in elements {
+                    self.place_element(ctx, seed, piece, element, pivot, chunk_x, chunk_z);
+                }
+            }
+            PoolElement::Feature { feature, .. } => {
+                ctx.place_feature(feature, piece.position);
+            }
+            PoolElement::Empty => {}
+        }
+    }
+}
+
+/// Run a template's blocks through the processors (Java's
+/// `StructureTemplate.processBlockInfos`).
+#[allow(clippy::too_many_arguments)]
+fn process(
+    ctx: &FeatureContext,
+    seed: i64,
+    template: &StructureTemplate,
+    piece: &PoolPiece,
+    processors: &[Processor],
+    projection: Projection,
+    legacy: bool,
+    pivot: IVec3,
+) -> Vec<BlockInfo> {
+    let rotation = piece.rotation;
+    let mut originals = Vec::new();
+    let mut processed = Vec::new();
+
+    'blocks: for block in &template.blocks {
+        let original = BlockInfo {
+            local: block.pos,
+            pos: piece.position + rotation.apply(block.pos),
+            block: block.block,
+        };
+        let mut info = original;
+        for processor in processors {
+            match process_block(ctx, processor, &original, info, pivot) {
+                Some(next) => info = next,
+                None => continue 'blocks,
+            }
+        }
+        if projection == Projection::TerrainMatching {
+            // Java's GravityProcessor, with an offset of -1
+            let ground = ctx
+                .region
+                .height(Heightmap::WorldSurfaceWg, info.pos.x, info.pos.z);
+            info.pos.y = ground - 1 + original.local.y;
+        }
+        // Legacy elements don't place air
+        if legacy && info.block == *blocks::AIR {
+            continue;
+        }
+        originals.push(original);
+        processed.push(info);
+    }
+
+    for processor in processors {
+        if let Processor::Capped { delegate, limit } = processor {
+            apply_capped(
+                ctx,
+                seed,
+                piece.position,
+                delegate,
+                limit,
+                &originals,
+                &mut processed,
+                pivot,
+            );
+        }
+    }
+    processed
+}
+
+/// Apply a processor to one block. Returns `None` if the block is dropped.
+fn process_block(
+    ctx: &FeatureContext,
+    processor: &Processor,
+    original: &BlockInfo,
+    info: BlockInfo,
+    pivot: IVec3,
+) -> Option<BlockInfo> {
+    match *processor {
+        Processor::BlockRot {
+            integrity,
+            rottable_blocks,
+        } => {
+            let mut random = JavaRandom::from_seed(get_seed(info.pos.x, info.pos.y, info.pos.z));
+            let protected = rottable_blocks.is_some_and(|tag| !region::in_tag(original.block, tag));
+            (protected || random.next_float() <= integrity).then_some(info)
+        }
+        Processor::Rule(rules) => {
+            let mut random = JavaRandom::from_seed(get_seed(info.pos.x, info.pos.y, info.pos.z));
+            let existing = ctx.region.get_block(info.pos);
+            for rule in rules {
+                if region::rule_test(&rule.input_predicate, info.block, &mut random)
+                    && region::rule_test(&rule.location_predicate, existing, &mut random)
+                    && pos_rule_test(&rule.position_predicate, info.pos, pivot, &mut random)
+                {
+                    return Some(match region::resolve_block(rule.output_state.name) {
+                        Some(block) => BlockInfo { block, ..info },
+                        None => info,
+                    });
+                }
+            }
+            Some(info)
+        }
+        Processor::ProtectedBlocks(tag) => {
+            (!region::in_tag(ctx.region.get_block(info.pos), tag)).then_some(info)
+        }
+        // Applied once every block is processed
+        Processor::Capped { .. } | Processor::Unsupported => Some(info),
+    }
+}
+
+/// Java's `PosRuleTest`s: a chance that moves linearly with the distance
+/// from the start piece.
+fn pos_rule_test(test: &PosRuleTest, pos: IVec3, pivot: IVec3, random: &mut JavaRandom) -> bool {
+    let (distance, min_chance, max_chance, min_dist, max_dist) = match *test {
+        PosRuleTest::AlwaysTrue => return true,
+        PosRuleTest::LinearPos {
+            min_chance,
+            max_chance,
+            min_dist,
+            max_dist,
+        } => {
+            let d = (pos - pivot).abs();
+            (d.x + d.y + d.z, min_chance, max_chance, min_dist, max_dist)
+        }
+        PosRuleTest::AxisAlignedLinearPos {
+            axis,
+            min_chance,
+            max_chance,
+            min_dist,
+            max_dist,
+        } => (
+            (pos - pivot).abs()[axis],
+            min_chance,
+            max_chance,
+            min_dist,
+            max_dist,
+        ),
+    };
+    let chance = random.next_float();
+    // Mth.clampedLerp(min_chance, max_chance, Mth.inverseLerp(...))
+    let t = (distance - min_dist) as f32 / (max_dist - min_dist) as f32;
+    let threshold = if t < 0.0 {
+        min_chance
+    } else if t > 1.0 {
+        max_chance
+    } else {
+        min_chance + t * (max_chance - min_chance)
+    };
+    chance <= threshold
+}
+
+/// Java's `CappedProcessor.finalizeProcessing`: apply the delegate to at
+/// most `limit` blocks, picked at random.
+#[allow(clippy::too_many_arguments)]
+fn apply_capped(
+    ctx: &FeatureContext,
+    seed: i64,
+    position: IVec3,
+    delegate: &Processor,
+    limit: &IntProvider,
+    originals: &[BlockInfo],
+    processed: &mut [BlockInfo],
+    pivot: IVec3,
+) {
+    if processed.is_empty() {
+        return;
+    }
+    let factory_seed = JavaRandom::from_seed(seed).next_long();
+    let mut random =
+        JavaRandom::from_seed(get_seed(position.x, position.y, position.z) ^ factory_seed);
+    let limit = (limit.sample(&mut random).max(0) as usize).min(processed.len());
+    if limit == 0 {
+        return;
+    }
+
+    let mut order: Vec<usize> = (0..processed.len()).collect();
+    shuffle(&mut order, &mut random);
+    let mut changed = 0;
+    for index in order {
+        if changed >= limit {
+            break;
+        }
+        let info = processed[index];
+        if let Some(next) = process_block(ctx, delegate, &originals[index], info, pivot)
+            && next != info
+        {
+            processed[index] = next;
+            changed += 1;
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! Block states of `.mcstructure` palettes.
+//!
+//! A palette entry names a block and lists all of its states. Its runtime ID
+//! follows valentine's layout: the states, sorted by name, are the digits of
+//! a mixed-radix number added to the block's first runtime ID, the first
+//! state being the least significant. Bytes are bits, numbers count from
+//! zero and strings count in the order of valentine's enums, so
+//! `minecraft:cardinal_direction` is south, west, north, east.
+//!
+//! Templates face one way and are rotated with their piece, so states
+//! holding a direction or an axis are rotated too, like Java's
+//! `BlockState.rotate`. The sides of walls and vines are left as they are.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::LazyLock;
+
+use glam::IVec3;
+use jolyne::valentine::blocks::BLOCKS;
+use jolyne::valentine::states;
+use zuri_nbt::NBTTag;
+
+use super::assembly::Rotation;
+
+/// Values of a string state, by state name without the `minecraft:` prefix.
+static STRING_STATES: LazyLock<HashMap<&'static str, Vec<String>>> = LazyLock::new(|| {
+    macro_rules! string_states {
+        ($($state:literal => $values:ident),* $(,)?) => {
+            HashMap::from([$((
+                $state,
+                values(states::$values::from_raw, states::$values::COUNT),
+            )),*])
+        };
+    }
+    string_states! {
+        "attachment" => Attachment,
+        "bamboo_leaf_size" => BambooLeafSize,
+        "bamboo_stalk_thickness" => BambooStalkThickness,
+        "big_dripleaf_tilt" => BigDripleafTilt,
+        "block_face" => BlockFace,
+        "cardinal_direction" => CardinalDirection,
+        "cauldron_liquid" => CauldronLiquid,
+        "cracked_state" => CrackedState,
+        "creaking_heart_state" => CreakingHeartState,
+        "dripstone_thickness" => DripstoneThickness,
+        "facing_direction" => FacingDirection,
+        "lever_direction" => LeverDirection,
+        "orientation" => Orientation,
+        "pale_moss_carpet_side_east" => PaleMossCarpetSideEast,
+        "pale_moss_carpet_side_north" => PaleMossCarpetSideNorth,
+        "pale_moss_carpet_side_south" => PaleMossCarpetSideSouth,
+        "pale_moss_carpet_side_west" => PaleMossCarpetSideWest,
+        "pillar_axis" => PillarAxis,
+        "portal_axis" => PortalAxis,
+        "sea_grass_type" => SeaGrassType,
+        "structure_block_type" => StructureBlockType,
+        "torch_facing_direction" => TorchFacingDirection,
+        "turtle_egg_count" => TurtleEggCount,
+        "vault_state" => VaultState,
+        "vertical_half" => VerticalHalf,
+        "wall_connection_type_east" => WallConnectionTypeEast,
+        "wall_connection_type_north" => WallConnectionTypeNorth,
+        "wall_connection_type_south" => WallConnectionTypeSouth,
+        "wall_connection_type_west" => WallConnectionTypeWest,
+    }
+});
+
+/// The snake_case values of one of valentine's enums, in order.
+fn values<T: Debug>(from_raw: fn(u8) -> Option<T>, count: u32) -> Vec<String> {
+    (0..count as u8)
+        .filter_map(from_raw)
+        .map(|value| {
+            let mut name = String::new();
+            for (i, c) in format!("{value:?}").chars().enumerate() {
+                if c.is_ascii_uppercase() && i > 0 {
+                    name.push('_');
+                }
+                name.push(c.to_ascii_lowercase());
+            }
+            name
+        })
+        .collect()
+}
+
+/// Number of values of the numeric states that turn with the block.
+fn int_count(state: &str) -> Option<u32> {
+    match state {
+        "facing_direction" => Some(6),
+        "direction" | "weirdo_direction" => Some(4),
+        "ground_sign_direction" => Some(16),
+        _ => None,
+    }
+}
+
+/// Directions or axes a state's values stand for, if it turns with the
+/// block, and whether they are axes.
+fn directions(block: &str, state: &str) -> Option<(&'static [IVec3], bool)> {
+    const SWNE: &[IVec3] = &[IVec3::Z, IVec3::NEG_X, IVec3::NEG_Z, IVec3::X];
+    const EWSN: &[IVec3] = &[IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z];
+    const FACES: &[IVec3] = &[
+        IVec3::NEG_Y,
+        IVec3::Y,
+        IVec3::NEG_Z,
+        IVec3::Z,
+        IVec3::NEG_X,
+        IVec3::X,
+    ];
+    match state {
+        "cardinal_direction" => Some((SWNE, false)),
+        "direction" if block.ends_with("trapdoor") => Some((EWSN, false)),
+        "direction" => Some((SWNE, false)),
+        "weirdo_direction" => Some((EWSN, false)),
+        "facing_direction" | "block_face" => Some((FACES, false)),
+        "torch_facing_direction" => Some((
+            &[
+                IVec3::ZERO,
+                IVec3::NEG_X,
+                IVec3::X,
+                IVec3::NEG_Z,
+                IVec3::Z,
+                IVec3::Y,
+            ],
+            false,
+        )),
+        "pillar_axis" => Some((&[IVec3::Y, IVec3::X, IVec3::Z], true)),
+        "portal_axis" => Some((&[IVec3::ZERO, IVec3::X, IVec3::Z], true)),
+        _ => None,
+    }
+}
+
+/// A state's value as a digit of the runtime ID.
+struct Digit {
+    state: String,
+    value: u32,
+    /// Number of values, where known from the state alone.
+    count: Option<u32>,
+}
+
+/// Runtime IDs of a palette entry turned by each [`Rotation`], in
+/// [`Rotation::ALL`] order.
+///
+/// Returns `None` if Bedrock has no such block. States that can't be read
+/// give the block's default state.
+pub fn resolve<'a>(
+    name: &str,
+    states: impl Iterator<Item = (&'a str, &'a NBTTag)>,
+) -> Option<[u32; 4]> {
+    let block = BLOCKS.iter().find(|block| block.string_id() == name)?;
+    let default = [block.default_state_id(); 4];
+
+    let mut digits = Vec::new();
+    let mut states: Vec<_> = states.collect();
+    states.sort_by_key(|(state, _)| *state);
+    for (state, tag) in states {
+        let state = state.strip_prefix("minecraft:").unwrap_or(state);
+        let (value, count) = match tag {
+            NBTTag::Byte(byte) => (byte.0 as u32, Some(2)),
+            NBTTag::Int(int) => (int.0.max(0) as u32, int_count(state)),
+            NBTTag::String(string) => {
+                let Some(values) = STRING_STATES.get(state) else {
+                    tracing::debug!(name, state, "Unknown block state in template palette");
+                    return Some(default);
+                };
+                let Some(value) = values.iter().position(|value| *value == string.0) else {
+                    return Some(default);
+                };
+                (value as u32, Some(values.len() as u32))
+            }
+            _ => return Some(default),
+        };
+        digits.push(Digit {
+            state: state.to_string(),
+            value,
+            count,
+        });
+    }
+
+    // A single state of unknown size takes what the others leave
+    let total = block.max_state_id() - block.min_state_id() + 1;
+    let known: u32 = digits.iter().filter_map(|digit| digit.count).product();
+    let mut unknown = digits.iter_mut().filter(|digit| digit.count.is_none());
+    match (unknown.next(), unknown.next()) {
+        (None, _) if known == total => {}
+        (Some(digit), None) if known > 0 && total.is_multiple_of(known) => {
+            digit.count = Some(total / known);
+        }
+        _ => return Some(default),
+    }
+
+    let mut ids = default;
+    for (id, rotation) in ids.iter_mut().zip(Rotation::ALL) {
+        let mut offset = 0;
+        let mut multiplier = 1;
+        for digit in &digits {
+            let count = digit.count.unwrap_or(1);
+            let value = rotate(name, &digit.state, digit.value, count, rotation);
+            if value >= count {
+                return Some(default);
+            }
+            offset += value * multiplier;
+            multiplier *= count;
+        }
+        *id = block.min_state_id() + offset;
+    }
+    Some(ids)
+}
+
+/// Turn a state's value with the block.
+fn rotate(block: &str, state: &str, value: u32, count: u32, rotation: Rotation) -> u32 {
+    if state == "ground_sign_direction" {
+        return (value + rotation as u32 * count / 4) % count;
+    }
+    let Some((directions, axis)) = directions(block, state) else {
+        return value;
+    };
+    let Some(&direction) = directions.get(value as usize) else {
+        return value;
+    };
+    let mut turned = rotation.apply(direction);
+    if axis {
+        turned = turned.abs();
+    }
+    directions
+        .iter()
+        .position(|&direction| direction == turned)
+        .map_or(value, |value| value as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zuri_nbt::tag;
+
+    fn resolve_states(name: &str, states: &[(&str, NBTTag)]) -> Option<[u32; 4]> {
+        resolve(name, states.iter().map(|(state, tag)| (*state, tag)))
+    }
+
+    /// First and default runtime IDs of a block.
+    fn block(name: &str) -> (u32, u32) {
+        let block = BLOCKS
+            .iter()
+            .find(|block| block.string_id() == name)
+            .unwrap();
+        (block.min_state_id(), block.default_state_id())
+    }
+
+    #[test]
+    fn test_cardinal_direction() {
+        // South, west, north, east
+        let (chest, _) = block("minecraft:chest");
+        let facing = |direction: &str| {
+            resolve_states(
+                "minecraft:chest",
+                &[(
+                    "minecraft:cardinal_direction",
+                    tag::String(direction.to_string()).into(),
+                )],
+            )
+            .unwrap()
+        };
+        assert_eq!(facing("south"), [chest, chest + 1, chest + 2, chest + 3]);
+        assert_eq!(facing("east"), [chest + 3, chest, chest + 1, chest + 2]);
+    }
+
+    #[test]
+    fn test_stairs() {
+        // upside_down_bit, then weirdo_direction: east, west, south, north
+        let (stairs, _) = block("minecraft:oak_stairs");
+        let ids = resolve_states(
+            "minecraft:oak_stairs",
+            &[
+                ("weirdo_direction", tag::Int(0).into()),
+                ("upside_down_bit", tag::Byte(1).into()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(ids, [stairs + 1, stairs + 5, stairs + 3, stairs + 7]);
+    }
+
+    #[test]
+    fn test_pillar_axis() {
+        let (log, _) = block("minecraft:oak_log");
+        let ids = resolve_states(
+            "minecraft:oak_log",
+            &[("pillar_axis", tag::String("x".to_string()).into())],
+        )
+        .unwrap();
+        assert_eq!(ids, [log + 1, log + 2, log + 1, log + 2]);
+    }
+
+    #[test]
+    fn test_unreadable_states() {
+        let (_, stone) = block("minecraft:stone");
+        assert_eq!(resolve_states("minecraft:stone", &[]), Some([stone; 4]));
+
+        let (_, chest) = block("minecraft:chest");
+        let ids = resolve_states(
+            "minecraft:chest",
+            &[(
+                "minecraft:cardinal_direction",
+                tag::String("sideways".to_string()).into(),
+            )],
+        );
+        assert_eq!(ids, Some([chest; 4]));
+        assert_eq!(resolve_states("minecraft:no_such_block", &[]), None);
+    }
+}
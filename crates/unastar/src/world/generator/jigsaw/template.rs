@@ -0,0 +1,396 @@
+//! Structure templates, loaded from Bedrock `.mcstructure` files.
+//!
+//! A `.mcstructure` file is a little-endian NBT compound holding the
+//! template size, a block palette and one palette index per block, with
+//! the Z coordinate varying fastest. Index `-1` is a structure void and
+//! keeps whatever is already in the world. Palette entries keep all their
+//! states, and are turned with the piece (see [`state`](super::state)).
+//!
+//! Jigsaw blocks are read into [`JigsawBlock`]s and, as Java's
+//! `JigsawReplacementProcessor` does, placed as their final state. Block
+//! entities other than jigsaws, entities and the waterlogging layer are
+//! skipped.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use glam::IVec3;
+use parking_lot::RwLock;
+use zuri_nbt::NBTTag;
+use zuri_nbt::encoding::LittleEndian;
+use zuri_nbt::view::View;
+
+use super::assembly::Rotation;
+use super::state;
+use crate::world::chunk::blocks;
+use crate::world::generator::feature::region;
+
+/// A block of a template, relative to the template's origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TemplateBlock {
+    pub pos: IVec3,
+    /// Runtime ID, facing the way the template does.
+    pub block: u32,
+}
+
+/// A jigsaw block: where another piece can attach.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JigsawBlock {
+    pub pos: IVec3,
+    /// Direction the jigsaw faces; attached pieces face the other way.
+    pub front: IVec3,
+    /// Up direction of the jigsaw, which aligned joints must match.
+    pub top: IVec3,
+    /// Name other jigsaws target.
+    pub name: String,
+    /// Name of the jigsaw an attached piece connects with.
+    pub target: String,
+    /// Pool attached pieces are picked from.
+    pub pool: String,
+    /// Whether an attached piece may turn around the front axis.
+    pub rollable: bool,
+    /// Jigsaws with higher priority are tried first.
+    pub selection_priority: i32,
+    /// Pieces attached to jigsaws with higher priority expand first.
+    pub placement_priority: i32,
+}
+
+impl JigsawBlock {
+    /// A jigsaw facing `front`, with the default joint for its facing.
+    pub fn new(pos: IVec3, front: IVec3, top: IVec3, name: &str, target: &str, pool: &str) -> Self {
+        Self {
+            pos,
+            front,
+            top,
+            name: name.to_string(),
+            target: target.to_string(),
+            pool: pool.to_string(),
+            rollable: front.y != 0,
+            selection_priority: 0,
+            placement_priority: 0,
+        }
+    }
+}
+
+/// A structure template: the blocks of one piece.
+#[derive(Debug, Clone, Default)]
+pub struct StructureTemplate {
+    pub size: IVec3,
+    /// Blocks to place, without structure voids. Jigsaws hold their final
+    /// state.
+    pub blocks: Vec<TemplateBlock>,
+    pub jigsaws: Vec<JigsawBlock>,
+    /// Runtime IDs of the blocks that face a way, turned by each
+    /// [`Rotation`] in [`Rotation::ALL`] order.
+    pub rotations: HashMap<u32, [u32; 4]>,
+}
+
+/// Facing of a jigsaw by its `facing_direction` state.
+const FACINGS: [IVec3; 6] = [
+    IVec3::NEG_Y,
+    IVec3::Y,
+    IVec3::NEG_Z,
+    IVec3::Z,
+    IVec3::NEG_X,
+    IVec3::X,
+];
+
+/// Top of an up or down facing jigsaw by its `rotation` state.
+const ROTATIONS: [IVec3; 4] = [IVec3::NEG_Z, IVec3::X, IVec3::Z, IVec3::NEG_X];
+
+impl StructureTemplate {
+    /// Decode a `.mcstructure` file.
+    pub fn from_mcstructure(bytes: &[u8]) -> Result<Self, String> {
+        let mut buf = bytes;
+        let nbt = NBTTag::read(&mut buf, LittleEndian).map_err(|e| e.to_string())?;
+        let root = nbt.view();
+
+        let size: Vec<i32> = root
+            .at("size")
+            .iter_list()
+            .filter_map(|v| v.int().ok())
+            .collect();
+        let [sx, sy, sz] = size[..] else {
+            return Err("size is not three ints".to_string());
+        };
+        let size = IVec3::new(sx, sy, sz);
+
+        let structure = root.at("structure");
+        let palette = structure.at("palette").at("default");
+        let states: Vec<View> = palette.at("block_palette").iter_list().collect();
+        let block_data = palette.at("block_position_data");
+
+        let mut template = StructureTemplate {
+            size,
+            ..Default::default()
+        };
+
+        // Palette index -> runtime ID, or None for blocks Bedrock doesn't have
+        // and structure blocks, which Java's templates never place
+        let names: Vec<&str> = states
+            .iter()
+            .map(|state| state.at("name").string().unwrap_or(""))
+            .collect();
+        let mut palette_ids = Vec::with_capacity(states.len());
+        for (state, name) in states.iter().zip(&names) {
+            if *name == "minecraft:structure_block" {
+                palette_ids.push(None);
+                continue;
+            }
+            let ids = state::resolve(name, state.at("states").iter_compound());
+            if let Some(ids) = ids.filter(|ids| ids.iter().any(|&id| id != ids[0])) {
+                template.rotations.insert(ids[0], ids);
+            }
+            palette_ids.push(ids.map(|ids| ids[0]));
+        }
+
+        let indices = structure.at("block_indices").at_index(0);
+        for (i, index) in indices.iter_list().enumerate() {
+            let Ok(index) = index.int() else {
+                continue;
+            };
+            let Some(&Some(block)) = palette_ids.get(index as usize) else {
+                continue;
+            };
+            let i = i as i32;
+            let pos = IVec3::new(i / (sy * sz), i / sz % sy, i % sz);
+
+            if names[index as usize] != "minecraft:jigsaw" {
+                template.blocks.push(TemplateBlock { pos, block });
+                continue;
+            }
+
+            let state = &states[index as usize];
+            let facing = state.at("states").at("facing_direction").int().unwrap_or(0);
+            let rotation = state.at("states").at("rotation").int().unwrap_or(0);
+            let front = FACINGS[facing.clamp(0, 5) as usize];
+            let top = if front.y == 0 {
+                IVec3::Y
+            } else {
+                ROTATIONS[rotation.rem_euclid(4) as usize]
+            };
+
+            let data = block_data.at_key(i.to_string()).at("block_entity_data");
+            let string = |key: &str| data.at(key).string().unwrap_or("").to_string();
+            let mut jigsaw = JigsawBlock::new(
+                pos,
+                front,
+                top,
+                &string("name"),
+                &string("target"),
+                &string("target_pool"),
+            );
+            match data.at("joint").string() {
+                Ok("rollable") => jigsaw.rollable = true,
+                Ok("aligned") => jigsaw.rollable = false,
+                _ => {}
+            }
+            jigsaw.selection_priority = data.at("selection_priority").int().unwrap_or(0);
+            jigsaw.placement_priority = data.at("placement_priority").int().unwrap_or(0);
+            template.jigsaws.push(jigsaw);
+
+            // Placed as its final state; structure voids keep the world block
+            let final_state = match data.at("final_state").string() {
+                Ok(name) => name.split('[').next().unwrap_or(name),
+                Err(_) => "minecraft:air",
+            };
+            if final_state != "minecraft:structure_void" {
+                let block = region::resolve_block(final_state).unwrap_or(*blocks::AIR);
+                template.blocks.push(TemplateBlock { pos, block });
+            }
+        }
+
+        Ok(template)
+    }
+
+    /// A block of the template, turned with its piece.
+    pub fn rotate(&self, block: u32, rotation: Rotation) -> u32 {
+        self.rotations
+            .get(&block)
+            .map_or(block, |ids| ids[rotation as usize])
+    }
+}
+
+/// Loads and caches the templates of pool elements.
+///
+/// A location such as `minecraft:village/plains/houses/plains_small_house_1`
+/// is read from `village/plains/houses/plains_small_house_1.mcstructure`
+/// in the template directory; other namespaces get a directory of their
+/// own. Missing templates are logged once and treated as absent, so the
+/// pieces using them are never placed. Without a directory, only inserted
+/// templates exist.
+#[derive(Default)]
+pub struct TemplateManager {
+    dir: Option<PathBuf>,
+    templates: RwLock<HashMap<String, Option<Arc<StructureTemplate>>>>,
+}
+
+impl TemplateManager {
+    /// Create a manager loading templates from `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: Some(dir.into()),
+            templates: RwLock::default(),
+        }
+    }
+
+    /// Get a template by location, loading it on first use.
+    pub fn get(&self, location: &str) -> Option<Arc<StructureTemplate>> {
+        let location = location.strip_prefix("minecraft:").unwrap_or(location);
+        if let Some(template) = self.templates.read().get(location) {
+            return template.clone();
+        }
+
+        let dir = self.dir.as_ref()?;
+        let path = dir.join(format!("{}.mcstructure", location.replace(':', "/")));
+        let template = match std::fs::read(&path) {
+            Ok(bytes) => match StructureTemplate::from_mcstructure(&bytes) {
+                Ok(template) => Some(Arc::new(template)),
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "Invalid structure template");
+                    None
+                }
+            },
+            Err(_) => {
+                tracing::warn!(path = %path.display(), "Missing structure template");
+                None
+            }
+        };
+        self.templates
+            .write()
+            .insert(location.to_string(), template.clone());
+        template
+    }
+
+    /// Add a template under a location, replacing any loaded one.
+    pub fn insert(&self, location: &str, template: StructureTemplate) {
+        let location = location.strip_prefix("minecraft:").unwrap_or(location);
+        self.templates
+            .write()
+            .insert(location.to_string(), Some(Arc::new(template)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zuri_nbt::tag;
+
+    fn string(value: &str) -> NBTTag {
+        tag::String(value.to_string()).into()
+    }
+
+    /// A 1x2x2 template: stone at (0, 0, 0), a structure void at (0, 0, 1)
+    /// and an up-facing jigsaw at (0, 1, 0).
+    fn mcstructure() -> Vec<u8> {
+        let block = |name: &str, states: tag::Compound| -> NBTTag {
+            tag::Compound::builder()
+                .with("name", string(name))
+                .with("states", states)
+                .with_int("version", 0)
+                .build()
+                .into()
+        };
+        let jigsaw_states = tag::Compound::builder()
+            .with_int("facing_direction", 1)
+            .with_int("rotation", 2)
+            .build();
+        let jigsaw_data = tag::Compound::builder()
+            .with("name", string("minecraft:bottom"))
+            .with("target", string("minecraft:top"))
+            .with("target_pool", string("minecraft:village/plains/decor"))
+            .with("final_state", string("minecraft:dirt"))
+            .with("joint", string("aligned"))
+            .build();
+        let indices = |values: &[i32]| -> NBTTag {
+            tag::List(values.iter().map(|&v| tag::Int(v).into()).collect()).into()
+        };
+
+        let palette = tag::Compound::builder()
+            .with(
+                "block_palette",
+                tag::List(vec![
+                    block("minecraft:stone", tag::Compound::default()),
+                    block("minecraft:jigsaw", jigsaw_states),
+                ]),
+            )
+            .with(
+                "block_position_data",
+                tag::Compound::builder()
+                    .with(
+                        "2",
+                        tag::Compound::builder()
+                            .with("block_entity_data", jigsaw_data)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build();
+        let root = tag::Compound::builder()
+            .with_int("format_version", 1)
+            .with("size", indices(&[1, 2, 2]))
+            .with(
+                "structure",
+                tag::Compound::builder()
+                    .with(
+                        "block_indices",
+                        tag::List(vec![indices(&[0, -1, 1, -1]), indices(&[-1, -1, -1, -1])]),
+                    )
+                    .with(
+                        "palette",
+                        tag::Compound::builder().with("default", palette).build(),
+                    )
+                    .build(),
+            )
+            .build();
+
+        let mut bytes = Vec::new();
+        NBTTag::Compound(root)
+            .write(&mut bytes, LittleEndian)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_load_mcstructure() {
+        let template = StructureTemplate::from_mcstructure(&mcstructure()).unwrap();
+        assert_eq!(template.size, IVec3::new(1, 2, 2));
+        assert_eq!(
+            template.blocks,
+            vec![
+                TemplateBlock {
+                    pos: IVec3::ZERO,
+                    block: *blocks::STONE,
+                },
+                TemplateBlock {
+                    pos: IVec3::new(0, 1, 0),
+                    block: *blocks::DIRT,
+                },
+            ]
+        );
+
+        let jigsaw = &template.jigsaws[0];
+        assert_eq!(jigsaw.pos, IVec3::new(0, 1, 0));
+        assert_eq!(jigsaw.front, IVec3::Y);
+        assert_eq!(jigsaw.top, IVec3::Z);
+        assert_eq!(jigsaw.pool, "minecraft:village/plains/decor");
+        assert!(!jigsaw.rollable);
+    }
+
+    #[test]
+    fn test_missing_template() {
+        let manager = TemplateManager::new("/nonexistent");
+        assert!(
+            manager
+                .get("minecraft:village/plains/town_centers/plains_fountain_01")
+                .is_none()
+        );
+
+        manager.insert("village/test", StructureTemplate::default());
+        assert!(manager.get("minecraft:village/test").is_some());
+
+        let manager = TemplateManager::default();
+        assert!(manager.get("minecraft:village/test").is_none());
+    }
+}
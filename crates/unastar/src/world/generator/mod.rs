@@ -5,11 +5,14 @@
 
 // TODO: Refactor aquifer and ore_veinifier to use AOT-compiled density functions
 pub mod aquifer;
+mod biome_source;
 pub mod carver;
 mod climate;
 mod constants;
 pub mod density;
+pub mod feature;
 pub mod flat;
+pub mod jigsaw;
 pub mod noise;
 pub mod ore_veinifier;
 mod structures;
@@ -17,9 +20,11 @@ pub mod surface;
 mod terrain;
 pub mod xoroshiro;
 
+pub use biome_source::{BiomeSource, EndBiomeSource, NetherBiomeSource};
+pub use carver::{CarverSelection, CarverSet};
 pub use climate::BiomeNoise;
 pub use constants::Biome;
 pub use structures::{
     StructureConfig, StructurePos, StructureType, find_structures_in_area, get_structure_pos,
 };
-pub use terrain::VanillaGenerator;
+pub use terrain::{Dimension, VanillaGenerator};
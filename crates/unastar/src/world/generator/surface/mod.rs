@@ -20,7 +20,7 @@
 //! if let Some(block_id) = rule.try_apply(&ctx, &|name| {
 //!     blocks::get_block_id(name)
 //! }) {
-//!     chunk.set_block(x, y, z, block_id);
+//!     chunk.set_block(x, y, z, 0, block_id);
 //! }
 //! ```
 
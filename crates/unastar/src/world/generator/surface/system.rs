@@ -11,7 +11,7 @@ use unastar_noise::surface::{Rule, SurfaceContext};
 use unastar_noise::xoroshiro::Xoroshiro128;
 
 use crate::world::chunk::{blocks, Chunk};
-use crate::world::generator::climate::BiomeNoise;
+use crate::world::generator::BiomeSource;
 
 /// System for applying surface rules to terrain.
 ///
@@ -22,14 +22,18 @@ pub struct SurfaceSystem {
     pub default_block: u32,
     /// Sea level (water surface).
     pub sea_level: i32,
+    /// Lowest block Y of the dimension.
+    pub min_y: i32,
+    /// Top of the dimension, which `below_top` anchors count down from.
+    pub max_y: i32,
     /// Noise for surface depth variation.
     surface_noise: DoublePerlinNoise,
     /// Secondary noise for extra variation.
     surface_secondary_noise: DoublePerlinNoise,
     /// Main surface rule.
     rule: Box<dyn Rule>,
-    /// Biome noise for sampling biomes.
-    biome_noise: BiomeNoise,
+    /// Biome source for sampling biomes.
+    biome_source: Box<dyn BiomeSource>,
 }
 
 impl SurfaceSystem {
@@ -38,8 +42,11 @@ impl SurfaceSystem {
     /// # Arguments
     /// * `seed` - World seed for noise generation
     /// * `rule` - The surface rule to apply
-    /// * `biome_noise` - Biome noise for sampling biomes
-    pub fn new(seed: i64, rule: Box<dyn Rule>, biome_noise: BiomeNoise) -> Self {
+    /// * `biome_source` - Biome source for sampling biomes
+    ///
+    /// The system starts out with overworld bounds and stone as the default
+    /// block; other dimensions overwrite the public fields.
+    pub fn new(seed: i64, rule: Box<dyn Rule>, biome_source: impl BiomeSource + 'static) -> Self {
         // Create surface noise using the seed
         let mut rng = Xoroshiro128::from_seed(seed.wrapping_add(0x1234567890ABCDEF));
         let surface_noise = DoublePerlinNoise::new(&mut rng, &[1.0, 1.0, 1.0], -6);
@@ -50,10 +57,12 @@ impl SurfaceSystem {
         Self {
             default_block: *blocks::STONE,
             sea_level: 63,
+            min_y: -64,
+            max_y: 320,
             surface_noise,
             surface_secondary_noise,
             rule,
-            biome_noise,
+            biome_source: Box::new(biome_source),
         }
     }
 
@@ -124,6 +133,22 @@ impl SurfaceSystem {
         false
     }
 
+    /// Find the lowest Y of the solid run that contains `y`.
+    ///
+    /// Used for the stone depth below a block, which ceiling rules test
+    /// (Nether soul sand ceilings, sandstone under sand).
+    fn solid_run_bottom(chunk: &Chunk, local_x: u8, y: i32, local_z: u8, min_y: i32) -> i32 {
+        let mut bottom = y;
+        while bottom > min_y {
+            let below = chunk.get_block(local_x, (bottom - 1) as i16, local_z, 0);
+            if below == *blocks::AIR || below == *blocks::WATER || below == *blocks::LAVA {
+                break;
+            }
+            bottom -= 1;
+        }
+        bottom
+    }
+
     /// Build surface for a chunk.
     ///
     /// This iterates over all columns in the chunk and applies surface rules
@@ -131,8 +156,8 @@ impl SurfaceSystem {
     ///
     /// Uses SIMD to batch noise sampling for 4 X columns at a time.
     pub fn build_surface(&self, chunk: &mut Chunk, chunk_x: i32, chunk_z: i32) {
-        let min_y = -64i32;
-        let max_y = 320i32;
+        let min_y = self.min_y;
+        let max_y = self.max_y;
 
         let mut ctx = SurfaceContext::new(chunk_x, chunk_z, min_y, max_y);
 
@@ -177,7 +202,7 @@ impl SurfaceSystem {
                     let min_surface_level = surface_y - surface_depth;
 
                     // Cache biome at surface for the whole column
-                    let column_biome = self.biome_noise.get_biome(world_x, surface_y, world_z);
+                    let column_biome = self.biome_source.get_biome(world_x, surface_y, world_z);
 
                     ctx.update_xz(
                         world_x,
@@ -190,12 +215,13 @@ impl SurfaceSystem {
 
                     // Track stone depth as we go down from the surface
                     let mut stone_depth_above = 0;
+                    let mut run_bottom = min_y;
                     let mut water_height = i32::MIN;
                     let mut in_stone = false;
 
                     // Iterate from surface down
                     for y in (min_y..=surface_y).rev() {
-                        let block = chunk.get_block(local_x, y as i16, local_z);
+                        let block = chunk.get_block(local_x, y as i16, local_z, 0);
 
                         // Skip air
                         if block == *blocks::AIR {
@@ -204,8 +230,8 @@ impl SurfaceSystem {
                             continue;
                         }
 
-                        // Track water level (Java: r = u + 1 when first hitting fluid)
-                        if block == *blocks::WATER {
+                        // Track fluid level (Java: r = u + 1 when first hitting fluid)
+                        if block == *blocks::WATER || block == *blocks::LAVA {
                             if water_height == i32::MIN {
                                 water_height = y + 1;
                             }
@@ -218,15 +244,23 @@ impl SurfaceSystem {
                         if !in_stone {
                             in_stone = true;
                             stone_depth_above = 0;
+                            run_bottom = Self::solid_run_bottom(chunk, local_x, y, local_z, min_y);
                         }
 
                         // Only apply rules to the default block (stone)
                         if block == self.default_block {
-                            ctx.update_y(y, stone_depth_above, 0, water_height, column_biome);
+                            let stone_depth_below = y - run_bottom + 1;
+                            ctx.update_y(
+                                y,
+                                stone_depth_above,
+                                stone_depth_below,
+                                water_height,
+                                column_biome,
+                            );
 
                             if let Some(new_block) = self.rule.try_apply(&ctx, &get_block) {
                                 if new_block != block {
-                                    chunk.set_block(local_x, y as i16, local_z, new_block);
+                                    chunk.set_block(local_x, y as i16, local_z, 0, new_block);
                                 }
                             }
                         }
@@ -237,6 +271,40 @@ impl SurfaceSystem {
             }
         }
     }
+
+    /// Surface block for dirt a carver has exposed (Java's
+    /// `SurfaceSystem.topMaterial`).
+    ///
+    /// The rule is applied as if the block at the world position were a one
+    /// block thick run, under a fluid surface one block up when `has_fluid`
+    /// is set. Returns `None` if no rule matches.
+    pub fn top_material(
+        &self,
+        chunk: &Chunk,
+        x: i32,
+        y: i32,
+        z: i32,
+        has_fluid: bool,
+    ) -> Option<u32> {
+        let (local_x, local_z) = ((x & 15) as u8, (z & 15) as u8);
+        let surface_y = chunk.height_map().at(local_x, local_z) as i32;
+        let surface_depth = self.get_surface_depth(x, z);
+
+        let mut ctx = SurfaceContext::new(chunk.x, chunk.z, self.min_y, self.max_y);
+        ctx.update_xz(
+            x,
+            z,
+            surface_depth,
+            self.get_surface_secondary(x, z),
+            self.is_steep(chunk, local_x, local_z),
+            surface_y - surface_depth,
+        );
+        let water_height = if has_fluid { y + 1 } else { i32::MIN };
+        ctx.update_y(y, 1, 1, water_height, self.biome_source.get_biome(x, y, z));
+
+        self.rule
+            .try_apply(&ctx, &|name| blocks::get_block_id(name))
+    }
 }
 
 impl std::fmt::Debug for SurfaceSystem {
@@ -244,6 +312,8 @@ impl std::fmt::Debug for SurfaceSystem {
         f.debug_struct("SurfaceSystem")
             .field("default_block", &self.default_block)
             .field("sea_level", &self.sea_level)
+            .field("min_y", &self.min_y)
+            .field("max_y", &self.max_y)
             .finish()
     }
 }
@@ -251,8 +321,9 @@ impl std::fmt::Debug for SurfaceSystem {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use unastar_noise::surface::{BiomeCheck, BlockRule, SequenceRule, TestRule};
+    use crate::world::generator::BiomeNoise;
     use unastar_noise::Biome;
+    use unastar_noise::surface::{BiomeCheck, BlockRule, SequenceRule, TestRule};
 
     fn create_test_system() -> SurfaceSystem {
         let seed = 12345i64;
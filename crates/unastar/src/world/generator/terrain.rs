@@ -6,19 +6,77 @@
 //! - Aquifer system for underground water/lava pockets
 //! - Surface rules for biome-based block placement
 
+use super::carver::{self, CarverSelection, CarvingContext};
 use super::constants::Biome;
 use super::density::{
-    CachingNoiseChunk, FunctionContext, NoiseRegistry,
-    FlatCacheGrid, ColumnContext, ColumnContextGrid, compute_final_density,
+    CachingNoiseChunk, ColumnContext, ColumnContextGrid, FlatCacheGrid, FunctionContext,
+    FunctionContext4, NoiseRegistry, compute_final_density, compute_final_density_4, end_compiled,
+    lerp, lerp3, nether_compiled,
+};
+use super::feature::{self, Decoration, DecorationRegion};
+use super::jigsaw::{
+    self, AssemblyContext, BoundingBox, START_SEARCH_RADIUS, StructureStart, TemplateManager,
 };
-use super::aquifer::NoiseBasedAquifer;
 use super::ore_veinifier::OreVeinifier;
-use super::surface::SurfaceSystem;
-use unastar_noise::build_vanilla_surface_rule;
-use super::xoroshiro::{JavaRandom, PositionalRandomFactory};
-use crate::world::chunk::{blocks, Chunk};
-use crate::world::generator::BiomeNoise;
+use super::surface::{Rule, SurfaceSystem};
+use super::xoroshiro::PositionalRandomFactory;
+use crate::world::chunk::{Chunk, blocks};
+use crate::world::generator::{BiomeNoise, BiomeSource, EndBiomeSource, NetherBiomeSource};
+use glam::IVec3;
+use lru::LruCache;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::simd::prelude::*;
+use std::sync::Arc;
+use unastar_noise::{
+    ColumnContext4, StructureSet, build_end_surface_rule, build_nether_surface_rule,
+    build_vanilla_surface_rule,
+};
+
+/// Bottom of the Nether and End noise settings.
+const NOISE_MIN_Y: i32 = 0;
+/// Height of the Nether and End noise settings.
+const NOISE_HEIGHT: i32 = 128;
+/// Proto chunks kept for decorating their neighbours.
+const PROTO_CHUNK_CACHE_SIZE: usize = 256;
+/// Decorations kept for finishing their neighbours.
+const DECORATION_CACHE_SIZE: usize = 256;
+/// Assembled structure starts (or their absence) kept by set and chunk.
+const STRUCTURE_START_CACHE_SIZE: usize = 1024;
+
+/// Structure starts by structure set and start chunk.
+type StructureStartCache = LruCache<(&'static str, i32, i32), Option<Arc<StructureStart>>>;
+
+/// Which dimension's noise settings a [`VanillaGenerator`] follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    /// `minecraft:overworld`
+    Overworld,
+    /// `minecraft:nether`: netherrack over a lava sea, bedrock floor and ceiling.
+    Nether,
+    /// `minecraft:end`: the central island and the outer islands.
+    End,
+}
+
+impl Dimension {
+    /// Sea level from the dimension's noise settings.
+    pub fn sea_level(self) -> i32 {
+        match self {
+            Dimension::Overworld => 63,
+            Dimension::Nether => 32,
+            Dimension::End => 0,
+        }
+    }
+
+    /// Lowest and highest block Y of the dimension's build height.
+    pub fn build_height(self) -> (i32, i32) {
+        match self {
+            Dimension::Overworld => (-64, 319),
+            Dimension::Nether | Dimension::End => (0, 255),
+        }
+    }
+}
 
 /// Vanilla terrain generator using 3D density functions.
 ///
@@ -29,8 +87,10 @@ use std::simd::prelude::*;
 pub struct VanillaGenerator {
     /// World seed
     pub seed: i64,
-    /// Biome/Climate noise system (MultiNoise)
-    biome_noise: BiomeNoise,
+    /// Dimension whose noise router and surface rules are used
+    dimension: Dimension,
+    /// Biome source for the dimension (MultiNoise in the overworld)
+    biome_source: Arc<dyn BiomeSource>,
     /// Noise registry with instantiated noises from seed
     noises: NoiseRegistry,
     /// Surface rules system for biome-based surface blocks
@@ -38,22 +98,77 @@ pub struct VanillaGenerator {
     /// Positional random factory for ore vein generation.
     /// Created via `PositionalRandomFactory::fork_ore_random()`.
     ore_random: PositionalRandomFactory,
+    /// Recently generated chunks without features, shared by neighbouring
+    /// chunks' decoration.
+    proto_chunks: Mutex<LruCache<(i32, i32), Arc<Chunk>>>,
+    /// Blocks recently decorated chunks set in themselves and their
+    /// neighbours.
+    decorations: Mutex<LruCache<(i32, i32), Arc<Decoration>>>,
+    /// Templates of jigsaw structure pieces.
+    templates: Arc<TemplateManager>,
+    /// Recently assembled jigsaw structures by structure set and start chunk.
+    structure_starts: Mutex<StructureStartCache>,
+    /// Which of the biomes' carvers run.
+    carvers: CarverSelection,
 }
 
 impl VanillaGenerator {
     /// Sea level
     pub const SEA_LEVEL: i32 = 63;
 
-    /// Create a new vanilla generator with the given seed.
+    /// Create a new overworld generator with the given seed.
     pub fn new(seed: i64) -> Self {
-        let biome_noise = BiomeNoise::from_seed(seed);
+        Self::with_dimension(seed, Dimension::Overworld)
+    }
 
-        // Create noise registry with all noises instantiated from seed
-        let noises = NoiseRegistry::new(seed);
+    /// Create a Nether generator with the given seed.
+    pub fn nether(seed: i64) -> Self {
+        Self::with_dimension(seed, Dimension::Nether)
+    }
+
+    /// Create an End generator with the given seed.
+    pub fn end(seed: i64) -> Self {
+        Self::with_dimension(seed, Dimension::End)
+    }
 
-        // Build surface rules system using generated vanilla rules from JSON
-        let surface_rule = build_vanilla_surface_rule(seed);
-        let surface_system = SurfaceSystem::new(seed, surface_rule, biome_noise.clone());
+    /// Create a generator for a dimension with the given seed.
+    pub fn with_dimension(seed: i64, dimension: Dimension) -> Self {
+        // Noise registry (with the dimension's base_3d_noise parameters),
+        // generated surface rules and biome source for each dimension
+        let (noises, surface_rule, biome_source): (_, Box<dyn Rule>, Arc<dyn BiomeSource>) =
+            match dimension {
+                Dimension::Overworld => (
+                    NoiseRegistry::new(seed),
+                    build_vanilla_surface_rule(seed),
+                    Arc::new(BiomeNoise::from_seed(seed)),
+                ),
+                Dimension::Nether => (
+                    NoiseRegistry::with_base_3d_noise(seed, 0.25, 0.375, 80.0, 60.0, 8.0),
+                    build_nether_surface_rule(seed),
+                    Arc::new(NetherBiomeSource::from_seed(seed)),
+                ),
+                Dimension::End => (
+                    NoiseRegistry::with_base_3d_noise(seed, 0.25, 0.25, 80.0, 160.0, 4.0),
+                    build_end_surface_rule(seed),
+                    Arc::new(EndBiomeSource::from_seed(seed)),
+                ),
+            };
+
+        let mut surface_system = SurfaceSystem::new(seed, surface_rule, biome_source.clone());
+        surface_system.sea_level = dimension.sea_level();
+        match dimension {
+            Dimension::Overworld => {}
+            Dimension::Nether => {
+                surface_system.default_block = *blocks::NETHERRACK;
+                surface_system.min_y = NOISE_MIN_Y;
+                surface_system.max_y = NOISE_MIN_Y + NOISE_HEIGHT - 1;
+            }
+            Dimension::End => {
+                surface_system.default_block = *blocks::END_STONE;
+                surface_system.min_y = NOISE_MIN_Y;
+                surface_system.max_y = NOISE_MIN_Y + NOISE_HEIGHT - 1;
+            }
+        }
 
         // Create positional random factory for ore vein generation
         // Java: this.oreRandom = this.random.fromHashOf("minecraft:ore").forkPositional()
@@ -62,25 +177,62 @@ impl VanillaGenerator {
 
         Self {
             seed,
-            biome_noise,
+            dimension,
+            biome_source,
             noises,
             surface_system,
             ore_random,
+            proto_chunks: Mutex::new(LruCache::new(
+                NonZeroUsize::new(PROTO_CHUNK_CACHE_SIZE).unwrap(),
+            )),
+            decorations: Mutex::new(LruCache::new(
+                NonZeroUsize::new(DECORATION_CACHE_SIZE).unwrap(),
+            )),
+            templates: Arc::default(),
+            structure_starts: Mutex::new(LruCache::new(
+                NonZeroUsize::new(STRUCTURE_START_CACHE_SIZE).unwrap(),
+            )),
+            carvers: CarverSelection::default(),
         }
     }
 
+    /// Run only the selected carvers of each biome.
+    pub fn with_carvers(mut self, carvers: CarverSelection) -> Self {
+        self.carvers = carvers;
+        self
+    }
+
+    /// Place jigsaw structures from the given templates. Without any, no
+    /// jigsaw structure pieces are placed.
+    pub fn with_templates(mut self, templates: Arc<TemplateManager>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// The dimension this generator produces terrain for.
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+
     /// Get biome at position based on climate parameters.
     fn get_biome(&self, x: i32, z: i32) -> Biome {
         // Use Y=64 (sea level) for standard biome check
-        self.biome_noise.get_biome(x, 64, z)
+        self.biome_source.get_biome(x, 64, z)
     }
 
     /// Find a safe spawn location by sampling terrain.
     ///
     /// Searches outward from origin for a location above sea level.
+    /// In the Nether and End, picks a floor with two blocks of air above
+    /// it in the origin chunk instead.
     pub fn find_safe_spawn(&self) -> (i32, i32, i32) {
+        if self.dimension != Dimension::Overworld {
+            return self.find_standing_spot();
+        }
+
         // Cache grids per chunk to avoid recreating them
-        let mut grid_cache: std::collections::HashMap<(i32, i32), FlatCacheGrid> = std::collections::HashMap::new();
+        let mut grid_cache: std::collections::HashMap<(i32, i32), FlatCacheGrid> =
+            std::collections::HashMap::new();
 
         for radius in 0i32..64 {
             for dx in -radius..=radius {
@@ -137,10 +289,190 @@ impl VanillaGenerator {
             Biome::Meadow => 177,
             Biome::FlowerForest => 132,
             Biome::SnowyMountains => 13,
+            Biome::NetherWastes => 8,
+            Biome::SoulSandValley => 178,
+            Biome::CrimsonForest => 179,
+            Biome::WarpedForest => 180,
+            Biome::BasaltDeltas => 181,
+            Biome::TheEnd
+            | Biome::SmallEndIslands
+            | Biome::EndMidlands
+            | Biome::EndHighlands
+            | Biome::EndBarrens => 9,
             _ => 1, // Default to Plains for others
         }
     }
 
+    /// Generate a chunk: terrain, then jigsaw structures and the biomes'
+    /// features.
+    ///
+    /// Features may cross into neighbouring chunks, so the chunk gets the
+    /// blocks set by its own decoration and those of the eight around it
+    /// (see [`feature`]).
+    pub fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk {
+        let mut chunk = (*self.proto_chunk(chunk_x, chunk_z)).clone();
+        for dz in -1..=1 {
+            for dx in -1..=1 {
+                self.decoration(chunk_x + dx, chunk_z + dz)
+                    .apply(-dx, -dz, &mut chunk);
+            }
+        }
+
+        // Sample center biome
+        let center_biome = self.get_biome(chunk_x * 16 + 8, chunk_z * 16 + 8);
+        chunk.set_biome(Self::to_bedrock_biome_id(center_biome));
+
+        chunk
+    }
+
+    /// Jigsaw structures crossing a chunk or the eight around it.
+    ///
+    /// Structures are only generated in the Overworld.
+    fn structure_starts_near(&self, chunk_x: i32, chunk_z: i32) -> Vec<Arc<StructureStart>> {
+        if self.dimension != Dimension::Overworld {
+            return Vec::new();
+        }
+        let (min_y, max_y) = self.dimension.build_height();
+        let region = BoundingBox::from_corners(
+            IVec3::new(chunk_x * 16 - 16, min_y, chunk_z * 16 - 16),
+            IVec3::new(chunk_x * 16 + 31, max_y, chunk_z * 16 + 31),
+        );
+
+        let mut starts = Vec::new();
+        for (name, set) in jigsaw::jigsaw_sets() {
+            let search = START_SEARCH_RADIUS + 1;
+            let chunks = jigsaw::start_chunks(
+                set,
+                self.seed,
+                (chunk_x - search, chunk_z - search),
+                (chunk_x + search, chunk_z + search),
+            );
+            for (start_x, start_z) in chunks {
+                if let Some(start) = self.structure_start(name, set, start_x, start_z)
+                    && start.bounding_box().intersects(&region)
+                {
+                    starts.push(start);
+                }
+            }
+        }
+        starts
+    }
+
+    /// Get a set's structure start at a chunk from the cache, assembling it
+    /// if needed.
+    fn structure_start(
+        &self,
+        name: &'static str,
+        set: &StructureSet,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) -> Option<Arc<StructureStart>> {
+        if let Some(start) = self.structure_starts.lock().get(&(name, chunk_x, chunk_z)) {
+            return start.clone();
+        }
+
+        let (min_y, max_y) = self.dimension.build_height();
+        let mut grids = HashMap::new();
+        let mut first_free_height = |x, z| self.base_height(x, z, &mut grids);
+        let mut ctx = AssemblyContext {
+            seed: self.seed,
+            templates: &self.templates,
+            biome_source: &*self.biome_source,
+            first_free_height: &mut first_free_height,
+            min_y,
+            max_y,
+        };
+        let start = jigsaw::generate_start(&mut ctx, set, chunk_x, chunk_z).map(Arc::new);
+        self.structure_starts
+            .lock()
+            .put((name, chunk_x, chunk_z), start.clone());
+        start
+    }
+
+    /// Java's `getBaseHeight` with `WORLD_SURFACE_WG`: Y above the highest
+    /// solid block of a column in the noise terrain, or sea level over
+    /// water.
+    ///
+    /// Density is sampled at cell corners and interpolated in between, as
+    /// in [`Self::generate_proto_chunk`].
+    fn base_height(&self, x: i32, z: i32, grids: &mut HashMap<(i32, i32), FlatCacheGrid>) -> i32 {
+        const CELL_HEIGHT: i32 = 8;
+        let (min_y, max_y) = self.dimension.build_height();
+        let sea_level = self.dimension.sea_level();
+        let grid = grids
+            .entry((x >> 4, z >> 4))
+            .or_insert_with(|| FlatCacheGrid::new(x >> 4, z >> 4, &self.noises));
+        let col = ColumnContext4::new_4([x; 4], [z; 4], &self.noises, grid);
+
+        // Corners from the top down, four at a time
+        let top_corner = max_y + 1;
+        let mut above: Option<f64> = None;
+        for batch in (0..).step_by(4) {
+            let ys = std::array::from_fn(|i| top_corner - CELL_HEIGHT * (batch + i as i32));
+            let ctx = FunctionContext4::new(x, ys, z);
+            let densities = compute_final_density_4(&ctx, &self.noises, grid, &col).to_array();
+            for (y, density) in ys.into_iter().zip(densities) {
+                if y < min_y {
+                    return sea_level;
+                }
+                if density > 0.0 {
+                    // Highest solid block of the cell above the corner
+                    let solid = above.map_or(0, |above| {
+                        (0..CELL_HEIGHT)
+                            .rev()
+                            .find(|&dy| lerp(dy as f64 / CELL_HEIGHT as f64, density, above) > 0.0)
+                            .unwrap_or(0)
+                    });
+                    return (y + solid + 1).min(max_y + 1).max(sea_level);
+                }
+                above = Some(density);
+            }
+        }
+        sea_level
+    }
+
+    /// Get a chunk's decoration from the cache, decorating it over cached
+    /// proto chunks if needed.
+    fn decoration(&self, chunk_x: i32, chunk_z: i32) -> Arc<Decoration> {
+        if let Some(decoration) = self.decorations.lock().get(&(chunk_x, chunk_z)) {
+            return decoration.clone();
+        }
+
+        let mut chunks = Vec::with_capacity(9);
+        for dz in -1..=1 {
+            for dx in -1..=1 {
+                chunks.push((*self.proto_chunk(chunk_x + dx, chunk_z + dz)).clone());
+            }
+        }
+        let (min_y, max_y) = self.dimension.build_height();
+        let region = DecorationRegion::new(chunk_x, chunk_z, chunks, min_y, max_y);
+        let structures = self.structure_starts_near(chunk_x, chunk_z);
+        let decoration = Arc::new(feature::decorate(
+            region,
+            self.seed,
+            &*self.biome_source,
+            &structures,
+        ));
+        self.decorations
+            .lock()
+            .put((chunk_x, chunk_z), decoration.clone());
+        decoration
+    }
+
+    /// Get a proto chunk from the cache, generating it if needed.
+    fn proto_chunk(&self, chunk_x: i32, chunk_z: i32) -> Arc<Chunk> {
+        if let Some(chunk) = self.proto_chunks.lock().get(&(chunk_x, chunk_z)) {
+            return chunk.clone();
+        }
+
+        // Generate without holding the lock so other workers aren't blocked
+        let chunk = Arc::new(self.generate_proto_chunk(chunk_x, chunk_z));
+        self.proto_chunks
+            .lock()
+            .put((chunk_x, chunk_z), chunk.clone());
+        chunk
+    }
+
     /// Generate a chunk using 3D density functions with cell-based caching.
     ///
     /// This is the Java Edition-style terrain generation using density functions
@@ -155,8 +487,20 @@ impl VanillaGenerator {
     /// 6. Use trilinear interpolation for density (compute at 8 corners, interpolate interior)
     /// 7. Use aquifer system to determine fluid placement when density <= 0
     /// 8. Apply surface rules for biome-specific blocks
-    pub fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk {
-        use super::aquifer::{OverworldFluidPicker, NoiseBasedAquifer};
+    /// 9. Carve caves and canyons, filled from the same aquifer
+    ///
+    /// Nether and End generators hand off to `generate_nether_chunk` and
+    /// `generate_end_chunk`, which skip the aquifer and ore veins.
+    ///
+    /// The result is a proto chunk: features are added by [`Self::generate_chunk`].
+    fn generate_proto_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk {
+        use super::aquifer::{NoiseBasedAquifer, OverworldFluidPicker};
+
+        match self.dimension {
+            Dimension::Overworld => {}
+            Dimension::Nether => return self.generate_nether_chunk(chunk_x, chunk_z),
+            Dimension::End => return self.generate_end_chunk(chunk_x, chunk_z),
+        }
 
         let mut chunk = Chunk::new(chunk_x, chunk_z);
 
@@ -264,13 +608,20 @@ impl VanillaGenerator {
                                         let block_z = base_block_z + z_in_cell;
                                         let ctx = FunctionContext::new(block_x, block_y, block_z);
                                         let block = ore_veinifier.compute(&ctx).unwrap_or(*blocks::STONE);
-                                        chunk.set_block(local_x, block_y as i16, local_z, block);
+                                        chunk.set_block(local_x, block_y as i16, local_z, 0, block);
                                     }
                                 } else {
                                     // Outside vein range - just place stone
                                     for z_in_cell in 0..4i32 {
-                                        let local_z = ((cell_z as i32) * cell_width + z_in_cell) as u8;
-                                        chunk.set_block(local_x, block_y as i16, local_z, *blocks::STONE);
+                                        let local_z =
+                                            ((cell_z as i32) * cell_width + z_in_cell) as u8;
+                                        chunk.set_block(
+                                            local_x,
+                                            block_y as i16,
+                                            local_z,
+                                            0,
+                                            *blocks::STONE,
+                                        );
                                     }
                                 }
                             } else {
@@ -283,19 +634,42 @@ impl VanillaGenerator {
                                     if density > 0.0 {
                                         // Solid block - check veinifier for ore veins (if in range)
                                         if in_vein_range {
-                                            let ctx = FunctionContext::new(block_x, block_y, block_z);
-                                            let block = ore_veinifier.compute(&ctx).unwrap_or(*blocks::STONE);
-                                            chunk.set_block(local_x, block_y as i16, local_z, block);
+                                            let ctx =
+                                                FunctionContext::new(block_x, block_y, block_z);
+                                            let block = ore_veinifier
+                                                .compute(&ctx)
+                                                .unwrap_or(*blocks::STONE);
+                                            chunk.set_block(
+                                                local_x,
+                                                block_y as i16,
+                                                local_z,
+                                                0,
+                                                block,
+                                            );
                                         } else {
-                                            chunk.set_block(local_x, block_y as i16, local_z, *blocks::STONE);
+                                            chunk.set_block(
+                                                local_x,
+                                                block_y as i16,
+                                                local_z,
+                                                0,
+                                                *blocks::STONE,
+                                            );
                                         }
                                     } else {
                                         // Use aquifer to determine what to place (water/lava/air)
                                         // This matches Java's behavior - aquifer handles all non-solid blocks
                                         // including ocean water via globalFluidPicker
                                         let ctx = FunctionContext::new(block_x, block_y, block_z);
-                                        if let Some(block_id) = aquifer.compute_substance(&ctx, density) {
-                                            chunk.set_block(local_x, block_y as i16, local_z, block_id);
+                                        if let Some(block_id) =
+                                            aquifer.compute_substance(&ctx, density)
+                                        {
+                                            chunk.set_block(
+                                                local_x,
+                                                block_y as i16,
+                                                local_z,
+                                                0,
+                                                block_id,
+                                            );
                                         }
                                         // None from aquifer means air - default, no need to set
                                     }
@@ -313,510 +687,245 @@ impl VanillaGenerator {
         // Apply surface rules
         self.surface_system.build_surface(&mut chunk, chunk_x, chunk_z);
 
-        // Sample center biome
-        let center_biome = self.get_biome(chunk_x * 16 + 8, chunk_z * 16 + 8);
-        chunk.set_biome(Self::to_bedrock_biome_id(center_biome));
+        // Carvers ask the aquifer at density 0, so caves below the local
+        // water level flood and aquifer barriers stay solid
+        let mut substance =
+            |x, y, z| aquifer.compute_substance(&FunctionContext::new(x, y, z), 0.0);
+        let mut ctx = CarvingContext::new(
+            &mut chunk,
+            min_y,
+            height,
+            &self.surface_system,
+            &mut substance,
+        );
+        carver::apply_carvers(&mut ctx, self.seed, &*self.biome_source, self.carvers);
 
         chunk
     }
 
+    /// Generate a Nether chunk.
+    ///
+    /// Netherrack is shaped by the nether router; open space below the sea
+    /// level (Y=32) fills with lava, since the Nether has no aquifers. The
+    /// nether surface rules then add the bedrock floor and ceiling and the
+    /// biome blocks (soul sand, nylium, basalt), and the nether caves are
+    /// carved.
+    fn generate_nether_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk {
+        let mut chunk = Chunk::new(chunk_x, chunk_z);
+        let sea_level = Dimension::Nether.sea_level();
 
-    /// Carve caves into the chunk using vanilla worm algorithm.
-    fn carve_caves<F: super::aquifer::FluidPicker>(&self, chunk: &mut Chunk, chunk_x: i32, chunk_z: i32, aquifer: &mut NoiseBasedAquifer<F>) {
-        use std::f64::consts::PI;
-
-        // Check nearby chunks for cave starts that might reach into this chunk
-        let range = 8; // Cave range in chunks
-        for cx in (chunk_x - range)..=(chunk_x + range) {
-            for cz in (chunk_z - range)..=(chunk_z + range) {
-                // Seed RNG for this chunk
-                let chunk_seed = self
-                    .seed
-                    .wrapping_add((cx as i64).wrapping_mul(341873128712))
-                    .wrapping_add((cz as i64).wrapping_mul(132897987541));
-                let mut rng = JavaRandom::from_seed(chunk_seed);
-
-                // Number of cave starts = rand(rand(rand(15)+1)+1)
-                // But only 1 in 7 chunks have caves
-                let r1 = rng.next_int(15) + 1;
-                let r2 = rng.next_int(r1 as u32) + 1;
-                let mut cave_count = rng.next_int(r2 as u32);
-                if rng.next_int(7) != 0 {
-                    cave_count = 0;
+        // Nether noise settings: size_horizontal=1, size_vertical=2 (4x8x4 cells)
+        let cell_height = 8;
+        Self::fill_interpolated(
+            &mut chunk,
+            4,
+            cell_height,
+            |block_x, block_z, column| {
+                for (i, density) in column.iter_mut().enumerate() {
+                    let y = NOISE_MIN_Y + i as i32 * cell_height;
+                    let ctx = FunctionContext::new(block_x, y, block_z);
+                    *density = nether_compiled::compute_final_density(&ctx, &self.noises);
                 }
+            },
+            |y, density| {
+                if density > 0.0 {
+                    Some(*blocks::NETHERRACK)
+                } else if y < sea_level {
+                    Some(*blocks::LAVA)
+                } else {
+                    None
+                }
+            },
+        );
 
-                for _ in 0..cave_count {
-                    // Start position
-                    let start_x = (cx * 16 + rng.next_int(16)) as f64;
-                    let y_bound = rng.next_int(120) + 8;
-                    let start_y = rng.next_int(y_bound.max(1) as u32) as f64;
-                    let start_z = (cz * 16 + rng.next_int(16)) as f64;
-
-                    // Number of branches
-                    let mut branches = 1;
-                    if rng.next_int(4) == 0 {
-                        // Large room
-                        self.carve_cave_room(
-                            chunk, chunk_x, chunk_z, &mut rng, start_x, start_y, start_z, aquifer,
-                        );
-                        branches += rng.next_int(4);
-                    }
+        self.surface_system
+            .build_surface(&mut chunk, chunk_x, chunk_z);
+
+        // Nether caves are carved to air and lava without an aquifer
+        let mut substance = |_, _, _| Some(*blocks::AIR);
+        let mut ctx = CarvingContext::new(
+            &mut chunk,
+            NOISE_MIN_Y,
+            NOISE_HEIGHT,
+            &self.surface_system,
+            &mut substance,
+        );
+        carver::apply_carvers(&mut ctx, self.seed, &*self.biome_source, self.carvers);
 
-                    for _ in 0..branches {
-                        let yaw = rng.next_float() * PI as f32 * 2.0;
-                        let pitch = (rng.next_float() - 0.5) * 2.0 / 8.0;
-                        let mut width = rng.next_float() * 2.0 + rng.next_float();
+        chunk
+    }
 
-                        if rng.next_int(10) == 0 {
-                            width *= rng.next_float() * rng.next_float() * 3.0 + 1.0;
-                        }
+    /// Generate an End chunk.
+    ///
+    /// End stone is shaped by the end router, whose `end_islands` density
+    /// gives the central island and the outer islands past 1024 blocks.
+    /// Everything else is void.
+    fn generate_end_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk {
+        let mut chunk = Chunk::new(chunk_x, chunk_z);
 
-                        self.carve_cave_tunnel(
-                            chunk,
-                            chunk_x,
-                            chunk_z,
-                            rng.next_long(),
-                            start_x,
-                            start_y,
-                            start_z,
-                            width,
-                            yaw,
-                            pitch,
-                            0,
-                            0,
-                            1.0,
-                            aquifer,
-                        );
-                    }
+        // End noise settings: size_horizontal=2, size_vertical=1 (8x4x8 cells)
+        let cell_height = 4;
+        Self::fill_interpolated(
+            &mut chunk,
+            8,
+            cell_height,
+            |block_x, block_z, column| {
+                let col =
+                    end_compiled::ColumnContext::new_standalone(block_x, block_z, &self.noises);
+                for (i, density) in column.iter_mut().enumerate() {
+                    let y = NOISE_MIN_Y + i as i32 * cell_height;
+                    let ctx = FunctionContext::new(block_x, y, block_z);
+                    *density = end_compiled::compute_final_density(&ctx, &self.noises, &col);
                 }
-            }
-        }
-    }
-
-    /// Carve a large cave room.
-    fn carve_cave_room<F: super::aquifer::FluidPicker>(
-        &self,
-        chunk: &mut Chunk,
-        chunk_x: i32,
-        chunk_z: i32,
-        rng: &mut JavaRandom,
-        x: f64,
-        y: f64,
-        z: f64,
-        aquifer: &mut NoiseBasedAquifer<F>,
-    ) {
-        let width = 1.0 + rng.next_float() * 6.0;
-        self.carve_cave_tunnel(
-            chunk,
-            chunk_x,
-            chunk_z,
-            rng.next_long(),
-            x,
-            y,
-            z,
-            width,
-            0.0,
-            0.0,
-            -1,
-            -1,
-            0.5,
-            aquifer,
+            },
+            |_, density| (density > 0.0).then(|| *blocks::END_STONE),
         );
+
+        self.surface_system
+            .build_surface(&mut chunk, chunk_x, chunk_z);
+
+        chunk
     }
 
-    /// Carve a cave tunnel (worm algorithm from vanilla).
-    fn carve_cave_tunnel<F: super::aquifer::FluidPicker>(
-        &self,
+    /// Fill the Y 0..128 range of a chunk from density sampled at cell corners.
+    ///
+    /// `sample_column` fills the densities of one corner column, one entry per
+    /// cell boundary from `NOISE_MIN_Y` upwards. Densities inside a cell are
+    /// trilinearly interpolated and `block_for(y, density)` picks the block
+    /// (`None` leaves air).
+    fn fill_interpolated(
         chunk: &mut Chunk,
-        chunk_x: i32,
-        chunk_z: i32,
-        seed: i64,
-        mut x: f64,
-        mut y: f64,
-        mut z: f64,
-        width: f32,
-        mut yaw: f32,
-        mut pitch: f32,
-        start_idx: i32,
-        end_idx: i32,
-        height_ratio: f64,
-        aquifer: &mut NoiseBasedAquifer<F>,
+        cell_width: i32,
+        cell_height: i32,
+        mut sample_column: impl FnMut(i32, i32, &mut [f64]),
+        block_for: impl Fn(i32, f64) -> Option<u32>,
     ) {
-        use std::f64::consts::PI;
-
-        let center_x = (chunk_x * 16 + 8) as f64;
-        let center_z = (chunk_z * 16 + 8) as f64;
-
-        let mut yaw_change = 0.0f32;
-        let mut pitch_change = 0.0f32;
-
-        let mut rng = JavaRandom::from_seed(seed);
-
-        let range: i32 = 8 * 16 - 16;
-        let mut end_idx = end_idx;
-        if end_idx <= 0 {
-            end_idx = range - rng.next_int((range / 4) as u32);
-        }
-
-        let mut start_idx = start_idx;
-        let is_room = start_idx == -1;
-        if is_room {
-            start_idx = end_idx / 2;
-        }
-
-        let branch_point = rng.next_int((end_idx / 2).max(1) as u32) + end_idx / 4;
-        let steep_tunnel = rng.next_int(6) == 0;
-
-        for i in start_idx..end_idx {
-            // Cave size varies with sin function
-            let radius = 1.5 + (((i as f64) * PI / (end_idx as f64)).sin() * width as f64);
-            let v_radius = radius * height_ratio;
-
-            // Move in direction
-            let cos_pitch = pitch.cos();
-            let sin_pitch = pitch.sin();
-            x += (yaw.cos() * cos_pitch) as f64;
-            y += sin_pitch as f64;
-            z += (yaw.sin() * cos_pitch) as f64;
-
-            // Pitch changes
-            if steep_tunnel {
-                pitch *= 0.92;
-            } else {
-                pitch *= 0.7;
-            }
-
-            pitch += pitch_change * 0.1;
-            yaw += yaw_change * 0.1;
-
-            pitch_change *= 0.9;
-            yaw_change *= 0.75;
-
-            pitch_change += (rng.next_float() - rng.next_float()) * rng.next_float() * 2.0;
-            yaw_change += (rng.next_float() - rng.next_float()) * rng.next_float() * 4.0;
-
-            // Branch at midpoint
-            if !is_room && i == branch_point && width > 1.0 && end_idx > 0 {
-                self.carve_cave_tunnel(
-                    chunk,
-                    chunk_x,
-                    chunk_z,
-                    rng.next_long(),
-                    x,
-                    y,
-                    z,
-                    rng.next_float() * 0.5 + 0.5,
-                    yaw - PI as f32 / 2.0,
-                    pitch / 3.0,
-                    i,
-                    end_idx,
-                    1.0,
-                    aquifer,
-                );
-                self.carve_cave_tunnel(
-                    chunk,
-                    chunk_x,
-                    chunk_z,
-                    rng.next_long(),
-                    x,
-                    y,
-                    z,
-                    rng.next_float() * 0.5 + 0.5,
-                    yaw + PI as f32 / 2.0,
-                    pitch / 3.0,
-                    i,
-                    end_idx,
-                    1.0,
-                    aquifer,
+        let cells_xz = 16 / cell_width;
+        let corners_xz = (cells_xz + 1) as usize;
+        let corners_y = (NOISE_HEIGHT / cell_height + 1) as usize;
+
+        // Corner densities, indexed [(corner_x * corners_xz + corner_z) * corners_y + corner_y]
+        let mut corners = vec![0.0; corners_xz * corners_xz * corners_y];
+        for corner_x in 0..corners_xz {
+            for corner_z in 0..corners_xz {
+                let start = (corner_x * corners_xz + corner_z) * corners_y;
+                sample_column(
+                    chunk.x * 16 + corner_x as i32 * cell_width,
+                    chunk.z * 16 + corner_z as i32 * cell_width,
+                    &mut corners[start..start + corners_y],
                 );
-                return;
             }
-
-            // Skip some positions randomly
-            if is_room || rng.next_int(4) != 0 {
-                // Check if in range of target chunk
-                let dx = x - center_x;
-                let dz = z - center_z;
-                let remaining = (end_idx - i) as f64;
-                let check_rad = (width + 2.0 + 16.0) as f64;
-
-                if dx * dx + dz * dz - remaining * remaining > check_rad * check_rad {
-                    return;
-                }
-
-                // Check if we should carve in this chunk
-                if x >= center_x - 16.0 - radius * 2.0
-                    && z >= center_z - 16.0 - radius * 2.0
-                    && x <= center_x + 16.0 + radius * 2.0
-                    && z <= center_z + 16.0 + radius * 2.0
-                {
-                    // Carve blocks in ellipsoid
-                    // IMPORTANT: Clamp to chunk bounds [0, 16) to prevent accessing neighboring chunks
-                    let min_x = ((x - radius).floor() as i32 - chunk_x * 16).clamp(0, 16);
-                    let max_x = ((x + radius).floor() as i32 - chunk_x * 16 + 1).clamp(0, 16);
-                    let min_y = ((y - v_radius).floor() as i32).clamp(1, 248);
-                    let max_y = ((y + v_radius).floor() as i32 + 1).clamp(1, 248);
-                    let min_z = ((z - radius).floor() as i32 - chunk_z * 16).clamp(0, 16);
-                    let max_z = ((z + radius).floor() as i32 - chunk_z * 16 + 1).clamp(0, 16);
-
-                    for lx in min_x..max_x {
-                        // Safety check: ensure lx is within chunk bounds
-                        if lx < 0 || lx >= 16 {
-                            continue;
-                        }
-                        let dx = ((lx + chunk_x * 16) as f64 + 0.5 - x) / radius;
-
-                        for lz in min_z..max_z {
-                            // Safety check: ensure lz is within chunk bounds
-                            if lz < 0 || lz >= 16 {
-                                continue;
-                            }
-                            let dz = ((lz + chunk_z * 16) as f64 + 0.5 - z) / radius;
-
-                            if dx * dx + dz * dz < 1.0 {
-                                for ly in (min_y..max_y).rev() {
-                                    let dy = ((ly - 1) as f64 + 0.5 - y) / v_radius;
-
-                                    if dy > -0.7 && dx * dx + dy * dy + dz * dz < 1.0 {
-                                        let current =
-                                            chunk.get_block(lx as u8, ly as i16, lz as u8);
-                                        // Only carve stone, dirt, grass - not water or bedrock
-                                        if current != *blocks::WATER && current != *blocks::BEDROCK
-                                        {
-                                            // Java carvers use aquifer.computeSubstance(pos, 0.0)
-                                            // density=0.0 forces aquifer to decide fluid vs air
-                                            // Java: lava_level is "above_bottom: 8" = -64 + 8 = -56
-                                            const CARVER_LAVA_LEVEL: i32 = -56;
-
-                                            let world_x = lx + chunk_x * 16;
-                                            let world_z = lz + chunk_z * 16;
-                                            let ctx = FunctionContext::new(world_x, ly, world_z);
-
-                                            // Java carvers use aquifer.computeSubstance(pos, 0.0)
-                                            // density=0.0 forces aquifer to decide fluid vs air
-                                            let block = if ly <= CARVER_LAVA_LEVEL {
-                                                *blocks::LAVA
-                                            } else {
-                                                match aquifer.compute_substance(&ctx, 0.0) {
-                                                    Some(block_id) => block_id,
-                                                    None => continue, // Barrier - skip carving this block
-                                                }
-                                            };
-                                            chunk.set_block(lx as u8, ly as i16, lz as u8, block);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    if is_room {
-                        break;
+        }
+        let corner = |x: usize, y: usize, z: usize| corners[(x * corners_xz + z) * corners_y + y];
+
+        for local_x in 0..16 {
+            let cx = (local_x / cell_width) as usize;
+            let tx = (local_x % cell_width) as f64 / cell_width as f64;
+            for local_z in 0..16 {
+                let cz = (local_z / cell_width) as usize;
+                let tz = (local_z % cell_width) as f64 / cell_width as f64;
+                for dy in 0..NOISE_HEIGHT {
+                    let cy = (dy / cell_height) as usize;
+                    let ty = (dy % cell_height) as f64 / cell_height as f64;
+                    let density = lerp3(
+                        tx,
+                        ty,
+                        tz,
+                        corner(cx, cy, cz),
+                        corner(cx + 1, cy, cz),
+                        corner(cx, cy + 1, cz),
+                        corner(cx + 1, cy + 1, cz),
+                        corner(cx, cy, cz + 1),
+                        corner(cx + 1, cy, cz + 1),
+                        corner(cx, cy + 1, cz + 1),
+                        corner(cx + 1, cy + 1, cz + 1),
+                    );
+
+                    let y = NOISE_MIN_Y + dy;
+                    if let Some(block) = block_for(y, density) {
+                        chunk.set_block(local_x as u8, y as i16, local_z as u8, 0, block);
                     }
                 }
             }
         }
     }
 
-    /// Carve ravines (vanilla-accurate from MapGenRavine.java)
-    /// Ravines are rarer but larger than caves, with a distinctive tall/narrow shape
-    fn carve_ravines<F: super::aquifer::FluidPicker>(&self, chunk: &mut Chunk, chunk_x: i32, chunk_z: i32, aquifer: &mut NoiseBasedAquifer<F>) {
-        use std::f64::consts::PI;
-
-        // Check nearby chunks for ravine starts
-        let range = 8;
-        for cx in (chunk_x - range)..=(chunk_x + range) {
-            for cz in (chunk_z - range)..=(chunk_z + range) {
-                // Seed RNG for this chunk
-                let chunk_seed = self
-                    .seed
-                    .wrapping_add((cx as i64).wrapping_mul(341873128712))
-                    .wrapping_add((cz as i64).wrapping_mul(132897987541))
-                    .wrapping_mul(0x12345678);
-                let mut rng = JavaRandom::from_seed(chunk_seed);
-
-                // Ravines are rare: 1 in 50 chunks
-                if rng.next_int(50) != 0 {
-                    continue;
+    /// Find the highest floor with two blocks of air above it in the origin chunk.
+    fn find_standing_spot(&self) -> (i32, i32, i32) {
+        let chunk = self.generate_chunk(0, 0);
+        let is_air = |x: u8, y: i32, z: u8| chunk.get_block(x, y as i16, z, 0) == *blocks::AIR;
+
+        for local_x in 0u8..16 {
+            for local_z in 0u8..16 {
+                for y in (NOISE_MIN_Y..NOISE_MIN_Y + NOISE_HEIGHT - 2).rev() {
+                    let block = chunk.get_block(local_x, y as i16, local_z, 0);
+                    if block != *blocks::AIR
+                        && block != *blocks::LAVA
+                        && is_air(local_x, y + 1, local_z)
+                        && is_air(local_x, y + 2, local_z)
+                    {
+                        return (local_x as i32, y + 1, local_z as i32);
+                    }
                 }
-
-                // Start position - ravines start higher than caves (Y 20-60)
-                let start_x = (cx * 16 + rng.next_int(16)) as f64;
-                let y_bound = rng.next_int(40) + 8;
-                let y_range = rng.next_int(y_bound.max(1) as u32) + 20;
-                let start_y = y_range as f64;
-                let start_z = (cz * 16 + rng.next_int(16)) as f64;
-
-                let yaw = rng.next_float() * PI as f32 * 2.0;
-                let pitch = (rng.next_float() - 0.5) * 2.0 / 8.0;
-                // Ravines are wider than caves
-                let width = (rng.next_float() * 2.0 + rng.next_float()) * 2.0;
-
-                self.carve_ravine_tunnel(
-                    chunk,
-                    chunk_x,
-                    chunk_z,
-                    rng.next_long(),
-                    start_x,
-                    start_y,
-                    start_z,
-                    width,
-                    yaw,
-                    pitch,
-                    0,
-                    0,
-                    3.0, // Height ratio of 3 makes ravines tall/narrow
-                    aquifer,
-                );
             }
         }
-    }
-
-    /// Carve a ravine tunnel (similar to cave but taller and shallower)
-    fn carve_ravine_tunnel<F: super::aquifer::FluidPicker>(
-        &self,
-        chunk: &mut Chunk,
-        chunk_x: i32,
-        chunk_z: i32,
-        seed: i64,
-        mut x: f64,
-        mut y: f64,
-        mut z: f64,
-        width: f32,
-        mut yaw: f32,
-        mut pitch: f32,
-        start_idx: i32,
-        end_idx: i32,
-        height_ratio: f64,
-        aquifer: &mut NoiseBasedAquifer<F>,
-    ) {
-        use std::f64::consts::PI;
 
-        let center_x = (chunk_x * 16 + 8) as f64;
-        let center_z = (chunk_z * 16 + 8) as f64;
+        // Nothing to stand on in the origin chunk
+        (0, self.dimension.sea_level() + 2, 0)
+    }
+}
 
-        let mut yaw_change = 0.0f32;
-        let mut pitch_change = 0.0f32;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let mut rng = JavaRandom::from_seed(seed);
+    #[test]
+    fn test_nether_chunk() {
+        let generator = VanillaGenerator::nether(12345);
+        let chunk = generator.generate_chunk(0, 0);
 
-        let range: i32 = 8 * 16 - 16;
-        let mut end_idx = end_idx;
-        if end_idx <= 0 {
-            end_idx = range - rng.next_int((range / 4) as u32);
-        }
+        // Bedrock floor and ceiling come from the nether surface rules
+        assert_eq!(chunk.get_block(0, 0, 0, 0), *blocks::BEDROCK);
+        assert_eq!(chunk.get_block(0, 127, 0, 0), *blocks::BEDROCK);
+        assert_eq!(chunk.get_block(0, 200, 0, 0), *blocks::AIR);
 
-        let mut start_idx = start_idx;
-        let is_room = start_idx == -1;
-        if is_room {
-            start_idx = end_idx / 2;
+        // Nothing above the sea level is lava
+        for y in 32..128 {
+            for x in 0..16 {
+                assert_ne!(chunk.get_block(x, y, 0, 0), *blocks::LAVA);
+            }
         }
+    }
 
-        for i in start_idx..end_idx {
-            // Ravine size - wider than caves
-            let radius = 1.5 + (((i as f64) * PI / (end_idx as f64)).sin() * width as f64);
-            // Random width variation per step
-            let width_mult = rng.next_float() * 0.25 + 0.75;
-            let h_radius = radius * width_mult as f64;
-            let v_radius = radius * height_ratio * width_mult as f64;
-
-            // Move in direction (ravines move more horizontally)
-            let cos_pitch = pitch.cos();
-            let sin_pitch = pitch.sin();
-            x += (yaw.cos() * cos_pitch) as f64;
-            y += (sin_pitch * 0.3) as f64; // Less vertical movement
-            z += (yaw.sin() * cos_pitch) as f64;
-
-            // Direction changes (ravines curve less than caves)
-            pitch *= 0.7;
-            pitch += pitch_change * 0.05;
-            yaw += yaw_change * 0.05;
-
-            pitch_change *= 0.8;
-            yaw_change *= 0.5;
-
-            pitch_change += (rng.next_float() - rng.next_float()) * rng.next_float() * 2.0;
-            yaw_change += (rng.next_float() - rng.next_float()) * rng.next_float() * 4.0;
-
-            if is_room || rng.next_int(4) != 0 {
-                let dx = x - center_x;
-                let dz = z - center_z;
-                let remaining = (end_idx - i) as f64;
-                let check_rad = (width + 2.0 + 16.0) as f64;
-
-                if dx * dx + dz * dz - remaining * remaining > check_rad * check_rad {
-                    return;
-                }
-
-                if x >= center_x - 16.0 - h_radius * 2.0
-                    && z >= center_z - 16.0 - h_radius * 2.0
-                    && x <= center_x + 16.0 + h_radius * 2.0
-                    && z <= center_z + 16.0 + h_radius * 2.0
-                {
-                    // IMPORTANT: Clamp to chunk bounds [0, 16) to prevent accessing neighboring chunks
-                    let min_x = ((x - h_radius).floor() as i32 - chunk_x * 16).clamp(0, 16);
-                    let max_x = ((x + h_radius).floor() as i32 - chunk_x * 16 + 1).clamp(0, 16);
-                    let min_y = ((y - v_radius).floor() as i32).clamp(1, 248);
-                    let max_y = ((y + v_radius).floor() as i32 + 1).clamp(1, 248);
-                    let min_z = ((z - h_radius).floor() as i32 - chunk_z * 16).clamp(0, 16);
-                    let max_z = ((z + h_radius).floor() as i32 - chunk_z * 16 + 1).clamp(0, 16);
-
-                    for lx in min_x..max_x {
-                        // Safety check: ensure lx is within chunk bounds
-                        if lx < 0 || lx >= 16 {
-                            continue;
-                        }
-                        let dx = ((lx + chunk_x * 16) as f64 + 0.5 - x) / h_radius;
-
-                        for lz in min_z..max_z {
-                            // Safety check: ensure lz is within chunk bounds
-                            if lz < 0 || lz >= 16 {
-                                continue;
-                            }
-                            let dz = ((lz + chunk_z * 16) as f64 + 0.5 - z) / h_radius;
+    #[test]
+    fn test_end_chunk() {
+        let generator = VanillaGenerator::end(12345);
 
-                            if dx * dx + dz * dz < 1.0 {
-                                for ly in (min_y..max_y).rev() {
-                                    let dy = ((ly - 1) as f64 + 0.5 - y) / v_radius;
+        // The central island sits on the origin
+        let chunk = generator.generate_chunk(0, 0);
+        assert!((0..128).any(|y| chunk.get_block(8, y, 8, 0) == *blocks::END_STONE));
+        let (_, y, _) = generator.find_safe_spawn();
+        assert!(y > 0 && y < 128);
 
-                                    // Ravine shape: wider at bottom (inverted from caves)
-                                    if dx * dx + dz * dz + (dy * dy) / 6.0 < 1.0 {
-                                        let current =
-                                            chunk.get_block(lx as u8, ly as i16, lz as u8);
-                                        if current != *blocks::WATER && current != *blocks::BEDROCK
-                                        {
-                                            // Java carvers use aquifer.computeSubstance(pos, 0.0)
-                                            // density=0.0 forces aquifer to decide fluid vs air
-                                            // Java: lava_level is "above_bottom: 8" = -64 + 8 = -56
-                                            const CARVER_LAVA_LEVEL: i32 = -56;
-
-                                            let world_x = lx + chunk_x * 16;
-                                            let world_z = lz + chunk_z * 16;
-                                            let ctx = FunctionContext::new(world_x, ly, world_z);
-
-                                            // Java carvers use aquifer.computeSubstance(pos, 0.0)
-                                            // density=0.0 forces aquifer to decide fluid vs air
-                                            let block = if ly <= CARVER_LAVA_LEVEL {
-                                                *blocks::LAVA
-                                            } else {
-                                                match aquifer.compute_substance(&ctx, 0.0) {
-                                                    Some(block_id) => block_id,
-                                                    None => continue, // Barrier - skip carving this block
-                                                }
-                                            };
-                                            chunk.set_block(lx as u8, ly as i16, lz as u8, block);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+        // Between the central island and the outer islands is void
+        let chunk = generator.generate_chunk(40, 0);
+        assert!((0..128).all(|y| chunk.get_block(8, y, 8, 0) == *blocks::AIR));
+    }
 
-                    if is_room {
-                        break;
-                    }
-                }
-            }
+    #[test]
+    fn test_base_height() {
+        let generator = VanillaGenerator::new(12345);
+        let chunk = generator.generate_proto_chunk(0, 0);
+        let mut grids = HashMap::new();
+
+        // Structures are placed on the noise terrain's surface
+        for (x, z) in [(0, 0), (7, 3), (15, 15)] {
+            let top = (-64..320)
+                .rev()
+                .find(|&y| chunk.get_block(x, y, z, 0) != *blocks::AIR)
+                .unwrap();
+            let height = generator.base_height(x as i32, z as i32, &mut grids);
+            assert!((height - (top as i32 + 1)).abs() <= 1, "{height} vs {top}");
         }
     }
 }
@@ -0,0 +1,760 @@
+//! Sky light and block light storage and propagation.
+//!
+//! Each sub-chunk stores two nibble arrays (sky and block light, 0-15).
+//! Arrays stay in a compact "uniform" form until a single value differs,
+//! so fully lit sky sections and unlit underground sections cost nothing.
+//!
+//! Propagation is a breadth-first flood fill using each block's light filter
+//! from the block registry. Incremental updates after a block change remove
+//! light that depended on the old block and re-flood from the surviving
+//! brighter neighbours, so only the affected region is touched.
+//!
+//! Light crosses chunk borders through a [`LightRegion`], a chunk together
+//! with its loaded neighbours: a newly loaded chunk is stitched to the
+//! chunks around it, and block changes relight the neighbours as well.
+//! Chunks that are not loaded act as unlit boundaries until they are.
+
+use std::collections::VecDeque;
+use std::sync::LazyLock;
+
+use bytes::{BufMut, BytesMut};
+
+use super::chunk::{BLOCKS_PER_SUBCHUNK, Chunk, MIN_Y, SUBCHUNK_COUNT};
+
+/// Maximum light level.
+pub const MAX_LIGHT: u8 = 15;
+
+/// Version byte of the serialized light format.
+const LIGHT_FORMAT_VERSION: u8 = 1;
+
+/// Number of bytes in a packed nibble array.
+const NIBBLE_BYTES: usize = BLOCKS_PER_SUBCHUNK / 2;
+
+/// Highest world Y coordinate (exclusive).
+const MAX_Y: i32 = MIN_Y + SUBCHUNK_COUNT as i32 * 16;
+
+/// Light emission and filter values indexed by block runtime ID.
+static LIGHT_PROPERTIES: LazyLock<Vec<(u8, u8)>> = LazyLock::new(|| {
+    use jolyne::valentine::blocks::BLOCKS;
+
+    let max_state = BLOCKS
+        .iter()
+        .map(|block| block.max_state_id())
+        .max()
+        .unwrap_or(0);
+    let mut table = vec![(0, MAX_LIGHT); max_state as usize + 1];
+    for block in BLOCKS.iter() {
+        let props = (
+            block.emit_light().min(MAX_LIGHT),
+            block.filter_light().min(MAX_LIGHT),
+        );
+        for state in block.min_state_id()..=block.max_state_id() {
+            table[state as usize] = props;
+        }
+    }
+    table
+});
+
+/// Light level emitted by a block.
+#[inline]
+pub fn light_emission(runtime_id: u32) -> u8 {
+    LIGHT_PROPERTIES
+        .get(runtime_id as usize)
+        .map_or(0, |props| props.0)
+}
+
+/// Light absorbed when passing through a block (15 = opaque).
+#[inline]
+pub fn light_filter(runtime_id: u32) -> u8 {
+    LIGHT_PROPERTIES
+        .get(runtime_id as usize)
+        .map_or(MAX_LIGHT, |props| props.1)
+}
+
+/// Kind of light channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightKind {
+    /// Light from the sky, full strength straight down.
+    Sky,
+    /// Light emitted by blocks such as torches and glowstone.
+    Block,
+}
+
+/// 16x16x16 nibble array of light levels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LightArray {
+    /// Packed nibbles in XZY order, or `None` when every value is `uniform`.
+    data: Option<Box<[u8; NIBBLE_BYTES]>>,
+    /// Value of every entry when `data` is `None`.
+    uniform: u8,
+}
+
+impl LightArray {
+    /// Create an array with every entry set to `level`.
+    pub const fn uniform(level: u8) -> Self {
+        Self {
+            data: None,
+            uniform: level,
+        }
+    }
+
+    /// Whether every entry holds the same value.
+    pub fn is_uniform(&self) -> bool {
+        self.data.is_none()
+    }
+
+    /// Set every entry to `level`, releasing the packed storage.
+    pub fn fill(&mut self, level: u8) {
+        self.data = None;
+        self.uniform = level & 0xF;
+    }
+
+    /// Get the light level at local coordinates (0-15 each).
+    #[inline]
+    pub fn get(&self, x: u8, y: u8, z: u8) -> u8 {
+        match &self.data {
+            None => self.uniform,
+            Some(data) => {
+                let idx = Self::index(x, y, z);
+                (data[idx >> 1] >> ((idx & 1) * 4)) & 0xF
+            }
+        }
+    }
+
+    /// Set the light level at local coordinates (0-15 each).
+    #[inline]
+    pub fn set(&mut self, x: u8, y: u8, z: u8, level: u8) {
+        let level = level & 0xF;
+        if self.data.is_none() {
+            if level == self.uniform {
+                return;
+            }
+            let packed = self.uniform | (self.uniform << 4);
+            self.data = Some(Box::new([packed; NIBBLE_BYTES]));
+        }
+        let data = self.data.as_mut().expect("light data allocated above");
+        let idx = Self::index(x, y, z);
+        let shift = (idx & 1) * 4;
+        data[idx >> 1] = (data[idx >> 1] & !(0xF << shift)) | (level << shift);
+    }
+
+    /// Collapse the packed storage if every entry holds the same value.
+    pub fn compact(&mut self) {
+        let Some(data) = &self.data else {
+            return;
+        };
+        let first = data[0];
+        if first & 0xF == first >> 4 && data.iter().all(|&b| b == first) {
+            self.fill(first & 0xF);
+        }
+    }
+
+    #[inline]
+    fn index(x: u8, y: u8, z: u8) -> usize {
+        ((x as usize) << 8) | ((z as usize) << 4) | y as usize
+    }
+
+    /// Serialize as either `[0, level]` or `[1, 2048 packed bytes]`.
+    fn encode(&self, buf: &mut BytesMut) {
+        match &self.data {
+            None => {
+                buf.put_u8(0);
+                buf.put_u8(self.uniform);
+            }
+            Some(data) => {
+                buf.put_u8(1);
+                buf.put_slice(&data[..]);
+            }
+        }
+    }
+
+    /// Deserialize an array, returning it with the number of bytes consumed.
+    fn decode(data: &[u8]) -> Result<(Self, usize), String> {
+        match data.first() {
+            Some(0) => {
+                let level = *data.get(1).ok_or("Light data truncated")?;
+                Ok((Self::uniform(level & 0xF), 2))
+            }
+            Some(1) => {
+                let bytes = data
+                    .get(1..1 + NIBBLE_BYTES)
+                    .ok_or("Light data truncated")?;
+                let mut packed = Box::new([0u8; NIBBLE_BYTES]);
+                packed.copy_from_slice(bytes);
+                Ok((
+                    Self {
+                        data: Some(packed),
+                        uniform: 0,
+                    },
+                    1 + NIBBLE_BYTES,
+                ))
+            }
+            Some(tag) => Err(format!("Unknown light array tag: {}", tag)),
+            None => Err("Light data truncated".to_string()),
+        }
+    }
+}
+
+impl Default for LightArray {
+    fn default() -> Self {
+        Self::uniform(0)
+    }
+}
+
+/// The six face-adjacent offsets.
+const NEIGHBOURS: [(i32, i32, i32); 6] = [
+    (1, 0, 0),
+    (-1, 0, 0),
+    (0, 1, 0),
+    (0, -1, 0),
+    (0, 0, 1),
+    (0, 0, -1),
+];
+
+/// Chunk-local position (x, world y, z).
+type LocalPos = (u8, i32, u8);
+
+/// World position used by the flood fill.
+type LightPos = (i32, i32, i32);
+
+/// Light reaching a neighbour from a cell at `level`.
+#[inline]
+fn attenuate(kind: LightKind, level: u8, filter: u8, downward: bool) -> u8 {
+    if kind == LightKind::Sky && downward && level == MAX_LIGHT && filter == 0 {
+        MAX_LIGHT
+    } else {
+        level.saturating_sub(filter.max(1))
+    }
+}
+
+impl Chunk {
+    /// Get the sky light level at local X/Z and world Y.
+    pub fn sky_light(&self, x: u8, y: i16, z: u8) -> u8 {
+        if y as i32 >= MAX_Y {
+            return MAX_LIGHT;
+        }
+        self.light(LightKind::Sky, (x, y as i32, z))
+    }
+
+    /// Get the block light level at local X/Z and world Y.
+    pub fn block_light(&self, x: u8, y: i16, z: u8) -> u8 {
+        self.light(LightKind::Block, (x, y as i32, z))
+    }
+
+    /// Combined light level (the brighter of sky and block light).
+    pub fn light_level(&self, x: u8, y: i16, z: u8) -> u8 {
+        self.sky_light(x, y, z).max(self.block_light(x, y, z))
+    }
+
+    fn light(&self, kind: LightKind, pos: LocalPos) -> u8 {
+        if !(MIN_Y..MAX_Y).contains(&pos.1) {
+            return 0;
+        }
+        let adjusted = pos.1 - MIN_Y;
+        let sub = &self.sub_chunks[(adjusted / 16) as usize];
+        let local_y = (adjusted % 16) as u8;
+        match kind {
+            LightKind::Sky => sub.sky_light.get(pos.0, local_y, pos.2),
+            LightKind::Block => sub.block_light.get(pos.0, local_y, pos.2),
+        }
+    }
+
+    fn set_light(&mut self, kind: LightKind, pos: LocalPos, level: u8) {
+        let adjusted = pos.1 - MIN_Y;
+        let sub = &mut self.sub_chunks[(adjusted / 16) as usize];
+        let local_y = (adjusted % 16) as u8;
+        match kind {
+            LightKind::Sky => sub.sky_light.set(pos.0, local_y, pos.2, level),
+            LightKind::Block => sub.block_light.set(pos.0, local_y, pos.2, level),
+        }
+    }
+
+    #[inline]
+    fn block_at(&self, pos: LocalPos) -> u32 {
+        self.get_block(pos.0, pos.1 as i16, pos.2, 0)
+    }
+
+    /// Recompute all sky and block light in this chunk from scratch.
+    ///
+    /// Called for freshly generated chunks and chunks loaded without
+    /// stored light. Light from neighbouring chunks is added afterwards by
+    /// [`LightRegion::stitch`].
+    pub fn compute_light(&mut self) {
+        let highest = self.highest_subchunk() as usize;
+        for (idx, sub) in self.sub_chunks.iter_mut().enumerate() {
+            // Everything above the highest non-empty section sees the sky.
+            let sky = if idx >= highest { MAX_LIGHT } else { 0 };
+            sub.sky_light.fill(sky);
+            sub.block_light.fill(0);
+        }
+
+        let (base_x, base_z) = (self.x * 16, self.z * 16);
+
+        // Sky: straight-down columns, then spread sideways into overhangs.
+        let top = MIN_Y + highest as i32 * 16;
+        let mut sky = VecDeque::new();
+        for x in 0u8..16 {
+            for z in 0u8..16 {
+                let mut level = MAX_LIGHT;
+                for y in (MIN_Y..top).rev() {
+                    let filter = light_filter(self.block_at((x, y, z)));
+                    level = level.saturating_sub(filter);
+                    if level == 0 {
+                        break;
+                    }
+                    self.set_light(LightKind::Sky, (x, y, z), level);
+                    sky.push_back((base_x + x as i32, y, base_z + z as i32));
+                }
+            }
+        }
+
+        // Block light: seed every emitter, skipping sections without one.
+        let mut block = VecDeque::new();
+        for idx in 0..self.sub_chunks.len() {
+            if !self.sub_chunks[idx]
+                .palette()
+                .iter()
+                .any(|&id| light_emission(id) > 0)
+            {
+                continue;
+            }
+            let base_y = MIN_Y + idx as i32 * 16;
+            for x in 0u8..16 {
+                for z in 0u8..16 {
+                    for local_y in 0..16 {
+                        let pos = (x, base_y + local_y, z);
+                        let emission = light_emission(self.block_at(pos));
+                        if emission > 0 {
+                            self.set_light(LightKind::Block, pos, emission);
+                            block.push_back((base_x + x as i32, pos.1, base_z + z as i32));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut region = LightRegion::new(self.x, self.z);
+        region.insert(self);
+        region.propagate(LightKind::Sky, &mut sky);
+        region.propagate(LightKind::Block, &mut block);
+
+        for sub in &mut self.sub_chunks {
+            sub.sky_light.compact();
+            sub.block_light.compact();
+        }
+    }
+
+    /// Update light after the block at local X/Z and world Y changed,
+    /// looking at this chunk only.
+    ///
+    /// The new block must already be stored in the chunk. Use
+    /// [`LightRegion::update`] to carry the change into loaded neighbours.
+    pub fn update_light(&mut self, x: u8, y: i16, z: u8) {
+        let (world_x, world_z) = (self.x * 16 + x as i32, self.z * 16 + z as i32);
+        let mut region = LightRegion::new(self.x, self.z);
+        region.insert(self);
+        region.update(world_x, y as i32, world_z);
+    }
+
+    /// Serialize all light arrays for persistence.
+    pub fn encode_light(&self) -> Vec<u8> {
+        let mut buf = BytesMut::with_capacity(2 + self.sub_chunks.len() * 4);
+        buf.put_u8(LIGHT_FORMAT_VERSION);
+        buf.put_u8(self.sub_chunks.len() as u8);
+        for sub in &self.sub_chunks {
+            sub.sky_light.encode(&mut buf);
+            sub.block_light.encode(&mut buf);
+        }
+        buf.to_vec()
+    }
+
+    /// Restore light arrays written by [`Chunk::encode_light`].
+    pub fn decode_light(&mut self, data: &[u8]) -> Result<(), String> {
+        if data.len() < 2 {
+            return Err("Light data too short".to_string());
+        }
+        if data[0] != LIGHT_FORMAT_VERSION {
+            return Err(format!("Unsupported light format version: {}", data[0]));
+        }
+        let count = data[1] as usize;
+        if count != self.sub_chunks.len() {
+            return Err(format!(
+                "Light data has {} sections, expected {}",
+                count,
+                self.sub_chunks.len()
+            ));
+        }
+
+        let mut offset = 2;
+        let mut decoded = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (sky, used) = LightArray::decode(&data[offset..])?;
+            offset += used;
+            let (block, used) = LightArray::decode(&data[offset..])?;
+            offset += used;
+            decoded.push((sky, block));
+        }
+
+        for (sub, (sky, block)) in self.sub_chunks.iter_mut().zip(decoded) {
+            sub.sky_light = sky;
+            sub.block_light = block;
+        }
+        Ok(())
+    }
+}
+
+/// A chunk and its loaded neighbours, lit as one area.
+///
+/// Light travels at most 15 blocks, so a change in the centre chunk can
+/// only reach the 3x3 chunks around it. Chunks that are not in the region
+/// act as unlit boundaries.
+pub struct LightRegion<'a> {
+    centre_x: i32,
+    centre_z: i32,
+    /// Chunks indexed by `(dx + 1) + (dz + 1) * 3`.
+    chunks: [Option<&'a mut Chunk>; 9],
+    /// Whether any light value of the chunk in the same slot changed.
+    changed: [bool; 9],
+}
+
+impl<'a> LightRegion<'a> {
+    /// Create an empty region around the chunk at `centre_x`/`centre_z`.
+    pub fn new(centre_x: i32, centre_z: i32) -> Self {
+        Self {
+            centre_x,
+            centre_z,
+            chunks: Default::default(),
+            changed: [false; 9],
+        }
+    }
+
+    /// Add the centre chunk or one of its neighbours.
+    ///
+    /// Returns `false` (and ignores the chunk) if it is further away.
+    pub fn insert(&mut self, chunk: &'a mut Chunk) -> bool {
+        match self.slot(chunk.x, chunk.z) {
+            Some(slot) => {
+                self.chunks[slot] = Some(chunk);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether light in the chunk at `chunk_x`/`chunk_z` was changed.
+    pub fn changed(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        self.slot(chunk_x, chunk_z)
+            .is_some_and(|slot| self.changed[slot])
+    }
+
+    /// Spread light across the borders of the centre chunk.
+    ///
+    /// Called once a chunk has been loaded or generated, so light from its
+    /// neighbours reaches into it and its own light reaches into them.
+    pub fn stitch(&mut self) {
+        let (base_x, base_z) = (self.centre_x * 16, self.centre_z * 16);
+        for kind in [LightKind::Sky, LightKind::Block] {
+            let mut queue = VecDeque::new();
+            for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                if self.chunks[Self::index(dx, dz)].is_none() {
+                    continue;
+                }
+                for along in 0..16 {
+                    // The centre's edge column and the neighbour's facing one.
+                    let columns = match (dx, dz) {
+                        (1, 0) => [(15, along), (16, along)],
+                        (-1, 0) => [(0, along), (-1, along)],
+                        (0, 1) => [(along, 15), (along, 16)],
+                        _ => [(along, 0), (along, -1)],
+                    };
+                    for (x, z) in columns {
+                        for y in MIN_Y..MAX_Y {
+                            let pos = (base_x + x, y, base_z + z);
+                            if self.light(kind, pos) > 1 {
+                                queue.push_back(pos);
+                            }
+                        }
+                    }
+                }
+            }
+            self.propagate(kind, &mut queue);
+        }
+    }
+
+    /// Update light after the block at a world position changed.
+    ///
+    /// The new block must already be stored in its chunk.
+    pub fn update(&mut self, x: i32, y: i32, z: i32) {
+        if !(MIN_Y..MAX_Y).contains(&y) || self.locate((x, y, z)).is_none() {
+            return;
+        }
+        self.relight(LightKind::Sky, (x, y, z));
+        self.relight(LightKind::Block, (x, y, z));
+    }
+
+    #[inline]
+    fn index(dx: i32, dz: i32) -> usize {
+        ((dx + 1) + (dz + 1) * 3) as usize
+    }
+
+    fn slot(&self, chunk_x: i32, chunk_z: i32) -> Option<usize> {
+        let (dx, dz) = (chunk_x - self.centre_x, chunk_z - self.centre_z);
+        ((-1..=1).contains(&dx) && (-1..=1).contains(&dz)).then(|| Self::index(dx, dz))
+    }
+
+    /// Slot and chunk-local position of a loaded world position.
+    #[inline]
+    fn locate(&self, pos: LightPos) -> Option<(usize, LocalPos)> {
+        if !(MIN_Y..MAX_Y).contains(&pos.1) {
+            return None;
+        }
+        let slot = self.slot(pos.0 >> 4, pos.2 >> 4)?;
+        self.chunks[slot].as_ref()?;
+        Some((slot, ((pos.0 & 15) as u8, pos.1, (pos.2 & 15) as u8)))
+    }
+
+    #[inline]
+    fn neighbour(&self, pos: LightPos, offset: (i32, i32, i32)) -> Option<LightPos> {
+        let next = (pos.0 + offset.0, pos.1 + offset.1, pos.2 + offset.2);
+        self.locate(next).map(|_| next)
+    }
+
+    fn light(&self, kind: LightKind, pos: LightPos) -> u8 {
+        match self.locate(pos) {
+            Some((slot, local)) => self.chunks[slot]
+                .as_ref()
+                .map_or(0, |chunk| chunk.light(kind, local)),
+            None => 0,
+        }
+    }
+
+    fn set_light(&mut self, kind: LightKind, pos: LightPos, level: u8) {
+        if let Some((slot, local)) = self.locate(pos)
+            && let Some(chunk) = self.chunks[slot].as_mut()
+        {
+            chunk.set_light(kind, local, level);
+            self.changed[slot] = true;
+        }
+    }
+
+    fn block_at(&self, pos: LightPos) -> u32 {
+        match self.locate(pos) {
+            Some((slot, local)) => self.chunks[slot]
+                .as_ref()
+                .map_or(0, |chunk| chunk.block_at(local)),
+            None => 0,
+        }
+    }
+
+    /// Remove light that may have depended on `pos` and re-flood the area.
+    fn relight(&mut self, kind: LightKind, pos: LightPos) {
+        let mut removal = VecDeque::new();
+        let mut refill = VecDeque::new();
+
+        let old = self.light(kind, pos);
+        if old > 0 {
+            self.set_light(kind, pos, 0);
+            removal.push_back((pos, old));
+        }
+
+        while let Some((cell, level)) = removal.pop_front() {
+            for offset in NEIGHBOURS {
+                let Some(next) = self.neighbour(cell, offset) else {
+                    continue;
+                };
+                let next_level = self.light(kind, next);
+                if next_level == 0 {
+                    continue;
+                }
+                let sky_column = kind == LightKind::Sky
+                    && offset.1 == -1
+                    && level == MAX_LIGHT
+                    && next_level == MAX_LIGHT;
+                if next_level < level || sky_column {
+                    self.set_light(kind, next, 0);
+                    removal.push_back((next, next_level));
+                } else {
+                    refill.push_back(next);
+                }
+            }
+        }
+
+        // Sources at the changed position itself.
+        let block = self.block_at(pos);
+        match kind {
+            LightKind::Block => {
+                let emission = light_emission(block);
+                if emission > 0 {
+                    self.set_light(kind, pos, emission);
+                    refill.push_back(pos);
+                }
+            }
+            LightKind::Sky => {
+                if pos.1 == MAX_Y - 1 && light_filter(block) == 0 {
+                    self.set_light(kind, pos, MAX_LIGHT);
+                    refill.push_back(pos);
+                }
+            }
+        }
+
+        // Lit neighbours may now shine into the changed position.
+        for offset in NEIGHBOURS {
+            if let Some(next) = self.neighbour(pos, offset)
+                && self.light(kind, next) > 0
+            {
+                refill.push_back(next);
+            }
+        }
+
+        self.propagate(kind, &mut refill);
+    }
+
+    /// Flood light outward from every queued cell.
+    fn propagate(&mut self, kind: LightKind, queue: &mut VecDeque<LightPos>) {
+        while let Some(cell) = queue.pop_front() {
+            let level = self.light(kind, cell);
+            if level <= 1 {
+                continue;
+            }
+            for offset in NEIGHBOURS {
+                let Some(next) = self.neighbour(cell, offset) else {
+                    continue;
+                };
+                let filter = light_filter(self.block_at(next));
+                let reached = attenuate(kind, level, filter, offset.1 == -1);
+                if reached > self.light(kind, next) {
+                    self.set_light(kind, next, reached);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::chunk::blocks;
+
+    #[test]
+    fn test_light_array_uniform_and_set() {
+        let mut array = LightArray::uniform(15);
+        assert_eq!(array.get(3, 4, 5), 15);
+        array.set(3, 4, 5, 15);
+        assert!(array.is_uniform());
+        array.set(3, 4, 5, 7);
+        assert!(!array.is_uniform());
+        assert_eq!(array.get(3, 4, 5), 7);
+        assert_eq!(array.get(3, 5, 5), 15);
+    }
+
+    #[test]
+    fn test_sky_light_flat() {
+        let mut chunk = Chunk::new(0, 0);
+        chunk.fill_floor(4, *blocks::STONE);
+        chunk.compute_light();
+
+        assert_eq!(chunk.sky_light(8, 4, 8), 15);
+        assert_eq!(chunk.sky_light(8, 100, 8), 15);
+        assert_eq!(chunk.sky_light(8, 3, 8), 0);
+    }
+
+    #[test]
+    fn test_block_light_spreads_and_updates() {
+        let mut chunk = Chunk::new(0, 0);
+        chunk.fill_subchunk_solid(4, *blocks::STONE);
+        chunk.compute_light();
+
+        // Carve a small cave and place glowstone inside it.
+        let glowstone = blocks::get_block_id("minecraft:glowstone");
+        for x in 4..12 {
+            chunk.set_block(x, 8, 8, 0, *blocks::AIR);
+            chunk.update_light(x, 8, 8);
+        }
+        chunk.set_block(4, 8, 8, 0, glowstone);
+        chunk.update_light(4, 8, 8);
+
+        assert_eq!(chunk.block_light(4, 8, 8), 15);
+        assert_eq!(chunk.block_light(5, 8, 8), 14);
+        assert_eq!(chunk.block_light(11, 8, 8), 8);
+        assert_eq!(chunk.sky_light(8, 8, 8), 0);
+
+        // Removing the glowstone darkens the tunnel again.
+        chunk.set_block(4, 8, 8, 0, *blocks::AIR);
+        chunk.update_light(4, 8, 8);
+        assert_eq!(chunk.block_light(5, 8, 8), 0);
+        assert_eq!(chunk.block_light(11, 8, 8), 0);
+    }
+
+    #[test]
+    fn test_sky_light_blocked_and_restored() {
+        let mut chunk = Chunk::new(0, 0);
+        chunk.fill_floor(1, *blocks::STONE);
+        chunk.compute_light();
+        assert_eq!(chunk.sky_light(8, 1, 8), 15);
+
+        chunk.set_block(8, 5, 8, 0, *blocks::STONE);
+        chunk.update_light(8, 5, 8);
+        assert_eq!(chunk.sky_light(8, 4, 8), 14);
+        assert_eq!(chunk.sky_light(8, 1, 8), 14);
+
+        chunk.set_block(8, 5, 8, 0, *blocks::AIR);
+        chunk.update_light(8, 5, 8);
+        assert_eq!(chunk.sky_light(8, 1, 8), 15);
+    }
+
+    #[test]
+    fn test_light_roundtrip() {
+        let mut chunk = Chunk::new(0, 0);
+        chunk.fill_floor(4, *blocks::STONE);
+        chunk.set_block(2, 10, 2, 0, blocks::get_block_id("minecraft:glowstone"));
+        chunk.compute_light();
+
+        let encoded = chunk.encode_light();
+        let mut restored = Chunk::new(0, 0);
+        restored.decode_light(&encoded).unwrap();
+        assert_eq!(restored.block_light(3, 10, 2), 14);
+        assert_eq!(restored.sky_light(8, 4, 8), 15);
+        assert_eq!(restored.sky_light(8, 3, 8), 0);
+    }
+
+    #[test]
+    fn test_light_crosses_chunk_border() {
+        let glowstone = blocks::get_block_id("minecraft:glowstone");
+        let mut west = Chunk::new(0, 0);
+        west.fill_floor(4, *blocks::STONE);
+        west.set_block(15, 10, 8, 0, glowstone);
+        west.compute_light();
+        let mut east = Chunk::new(1, 0);
+        east.fill_floor(4, *blocks::STONE);
+        east.compute_light();
+        assert_eq!(east.block_light(0, 10, 8), 0);
+
+        // Loading the east chunk next to the west one brings the glow across.
+        let mut region = LightRegion::new(1, 0);
+        region.insert(&mut east);
+        region.insert(&mut west);
+        region.stitch();
+        assert!(region.changed(1, 0));
+        assert!(!region.changed(0, 0));
+        assert_eq!(east.block_light(0, 10, 8), 14);
+        assert_eq!(east.block_light(5, 10, 8), 9);
+
+        // Removing the glowstone darkens both sides of the border.
+        west.set_block(15, 10, 8, 0, *blocks::AIR);
+        let mut region = LightRegion::new(0, 0);
+        region.insert(&mut west);
+        region.insert(&mut east);
+        region.update(15, 10, 8);
+        assert_eq!(west.block_light(15, 10, 8), 0);
+        assert_eq!(east.block_light(0, 10, 8), 0);
+        assert_eq!(east.block_light(5, 10, 8), 0);
+    }
+
+    #[test]
+    fn test_region_ignores_distant_chunks() {
+        let mut far = Chunk::new(3, 0);
+        let mut region = LightRegion::new(0, 0);
+        assert!(!region.insert(&mut far));
+    }
+}
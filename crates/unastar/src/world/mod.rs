@@ -1,12 +1,23 @@
 //! World module - chunk storage, generation, and ECS integration.
 
+pub mod block_entity;
 pub mod chunk;
+pub mod difficulty;
 pub mod ecs;
+pub mod gamerules;
 pub mod generator;
+pub mod light;
+pub mod time;
 
+pub use block_entity::{BlockEntity, BlockEntityData};
 pub use chunk::{Chunk, HeightMapType, SUBCHUNK_COUNT, request_mode};
-pub use ecs::{ChunkData, ChunkManager, ChunkPosition, ChunkState};
-pub use generator::VanillaGenerator;
+pub use difficulty::Difficulty;
+pub use ecs::{
+    ChunkData, ChunkManager, ChunkPosition, ChunkState, DEFAULT_WORLD, WorldError, WorldId, Worlds,
+};
+pub use gamerules::{GameRuleValue, GameRules};
+pub use generator::{CarverSelection, CarverSet, VanillaGenerator};
+pub use time::{Weather, WeatherKind, WorldTime};
 
 use serde::{Deserialize, Serialize};
 
@@ -70,7 +81,37 @@ pub enum WorldGenerator {
     /// A 3x3 (or larger) stone platform in an otherwise-void world.
     VoidSpawnPlatform { platform_radius_chunks: u32 },
     /// Vanilla-style terrain generation with biomes.
-    Vanilla { seed: i64 },
+    Vanilla {
+        seed: i64,
+        /// Which of the biomes' carvers cut caves and canyons.
+        #[serde(default)]
+        carvers: CarverSelection,
+    },
+    /// Vanilla Nether terrain: netherrack caves over a lava sea.
+    Nether {
+        seed: i64,
+        /// Which of the biomes' carvers cut caves.
+        #[serde(default)]
+        carvers: CarverSelection,
+    },
+    /// Vanilla End terrain: the central island and the outer islands.
+    End { seed: i64 },
+}
+
+impl WorldGenerator {
+    /// Build the noise-based terrain generator for the vanilla kinds.
+    pub fn terrain(&self) -> Option<VanillaGenerator> {
+        match *self {
+            Self::Vanilla { seed, carvers } => {
+                Some(VanillaGenerator::new(seed).with_carvers(carvers))
+            }
+            Self::Nether { seed, carvers } => {
+                Some(VanillaGenerator::nether(seed).with_carvers(carvers))
+            }
+            Self::End { seed } => Some(VanillaGenerator::end(seed)),
+            Self::SuperFlat | Self::VoidSpawnPlatform { .. } => None,
+        }
+    }
 }
 
 /// Storage provider for world persistence.
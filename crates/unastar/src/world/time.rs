@@ -0,0 +1,236 @@
+//! World time and weather.
+//!
+//! Every world has its own clock and weather, kept by its chunk manager.
+//! Both advance once per tick. Weather follows the vanilla cycle: rain and
+//! thunder each toggle after a random number of ticks, and `/weather clear`
+//! holds off both for a while.
+
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+/// Ticks in a full day.
+pub const DAY_LENGTH: i64 = 24000;
+
+/// Ticks until rain starts when none is scheduled.
+const RAIN_DELAY: RangeInclusive<i32> = 12000..=180000;
+/// How long rain lasts.
+const RAIN_DURATION: RangeInclusive<i32> = 12000..=24000;
+/// Ticks until thunder starts when none is scheduled.
+const THUNDER_DELAY: RangeInclusive<i32> = 12000..=180000;
+/// How long thunder lasts.
+const THUNDER_DURATION: RangeInclusive<i32> = 3600..=15600;
+/// Clear weather set by a command without a duration.
+const CLEAR_DURATION: RangeInclusive<i32> = 12000..=180000;
+
+/// Time of day in a world.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorldTime {
+    /// Ticks since the world was created, moved by `/time set` and `/time add`.
+    pub time: i64,
+}
+
+impl WorldTime {
+    /// Ticks into the current day (0 is sunrise, 6000 noon).
+    pub fn day_time(self) -> i64 {
+        self.time.rem_euclid(DAY_LENGTH)
+    }
+
+    /// Number of whole days that have passed.
+    pub fn day(self) -> i64 {
+        self.time.div_euclid(DAY_LENGTH)
+    }
+
+    /// Value sent in `SetTime`.
+    pub fn packet_time(self) -> i32 {
+        // The client only cares about the time of day and the moon phase
+        self.time.rem_euclid(DAY_LENGTH * 8) as i32
+    }
+}
+
+/// Named times accepted by `/time set`.
+pub fn named_time(name: &str) -> Option<i64> {
+    Some(match name {
+        "sunrise" => 23000,
+        "day" => 1000,
+        "noon" => 6000,
+        "sunset" => 12000,
+        "night" => 13000,
+        "midnight" => 18000,
+        _ => return None,
+    })
+}
+
+/// What the sky looks like.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeatherKind {
+    Clear,
+    Rain,
+    Thunder,
+}
+
+impl WeatherKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "clear",
+            WeatherKind::Rain => "rain",
+            WeatherKind::Thunder => "thunder",
+        }
+    }
+
+    /// Parse a weather name as used by `/weather`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "clear" => Some(WeatherKind::Clear),
+            "rain" => Some(WeatherKind::Rain),
+            "thunder" => Some(WeatherKind::Thunder),
+            _ => None,
+        }
+    }
+
+    /// Id shared with plugins: 0 clear, 1 rain, 2 thunder.
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(WeatherKind::Clear),
+            1 => Some(WeatherKind::Rain),
+            2 => Some(WeatherKind::Thunder),
+            _ => None,
+        }
+    }
+}
+
+/// Weather state machine.
+///
+/// The counters are ticks until the next change; zero means nothing is
+/// scheduled yet and a random duration is picked on the next tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Weather {
+    pub raining: bool,
+    pub thundering: bool,
+    pub rain_time: i32,
+    pub thunder_time: i32,
+    /// Ticks of forced clear weather left.
+    pub clear_time: i32,
+}
+
+impl Weather {
+    /// Weather as it appears. Thunder only shows while it is raining.
+    pub fn kind(&self) -> WeatherKind {
+        match (self.raining, self.thundering) {
+            (true, true) => WeatherKind::Thunder,
+            (true, false) => WeatherKind::Rain,
+            (false, _) => WeatherKind::Clear,
+        }
+    }
+
+    /// Change the weather for `duration` ticks, or a random vanilla duration.
+    pub fn set(&mut self, kind: WeatherKind, duration: Option<i32>, rng: &mut impl Rng) {
+        match kind {
+            WeatherKind::Clear => {
+                *self = Self {
+                    clear_time: duration.unwrap_or_else(|| rng.gen_range(CLEAR_DURATION)),
+                    ..Self::default()
+                };
+            }
+            WeatherKind::Rain | WeatherKind::Thunder => {
+                let range = if kind == WeatherKind::Thunder {
+                    THUNDER_DURATION
+                } else {
+                    RAIN_DURATION
+                };
+                let duration = duration.unwrap_or_else(|| rng.gen_range(range));
+                *self = Self {
+                    raining: true,
+                    thundering: kind == WeatherKind::Thunder,
+                    rain_time: duration,
+                    thunder_time: duration,
+                    clear_time: 0,
+                };
+            }
+        }
+    }
+
+    /// Advance one tick.
+    pub fn tick(&mut self, rng: &mut impl Rng) {
+        if self.clear_time > 0 {
+            self.clear_time -= 1;
+            // Nothing is scheduled, so a fresh delay is picked once it runs out
+            self.rain_time = 0;
+            self.thunder_time = 0;
+            self.raining = false;
+            self.thundering = false;
+            return;
+        }
+
+        if self.thunder_time > 0 {
+            self.thunder_time -= 1;
+            if self.thunder_time == 0 {
+                self.thundering = !self.thundering;
+            }
+        } else if self.thundering {
+            self.thunder_time = rng.gen_range(THUNDER_DURATION);
+        } else {
+            self.thunder_time = rng.gen_range(THUNDER_DELAY);
+        }
+
+        if self.rain_time > 0 {
+            self.rain_time -= 1;
+            if self.rain_time == 0 {
+                self.raining = !self.raining;
+            }
+        } else if self.raining {
+            self.rain_time = rng.gen_range(RAIN_DURATION);
+        } else {
+            self.rain_time = rng.gen_range(RAIN_DELAY);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_time() {
+        let time = WorldTime {
+            time: 3 * DAY_LENGTH + 6000,
+        };
+        assert_eq!((time.day(), time.day_time()), (3, 6000));
+        let time = WorldTime { time: -1000 };
+        assert_eq!((time.day(), time.day_time()), (-1, 23000));
+        assert_eq!(named_time("midnight"), Some(18000));
+        assert_eq!(named_time("teatime"), None);
+    }
+
+    #[test]
+    fn test_weather_cycle() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut weather = Weather::default();
+        weather.set(WeatherKind::Rain, Some(3), &mut rng);
+        assert_eq!(weather.kind(), WeatherKind::Rain);
+        for _ in 0..3 {
+            weather.tick(&mut rng);
+        }
+        assert_eq!(weather.kind(), WeatherKind::Clear);
+        // The next spell of rain is scheduled straight away
+        weather.tick(&mut rng);
+        assert!(RAIN_DELAY.contains(&weather.rain_time));
+
+        weather.set(WeatherKind::Thunder, Some(100), &mut rng);
+        assert_eq!(weather.kind(), WeatherKind::Thunder);
+
+        weather.set(WeatherKind::Clear, Some(2), &mut rng);
+        for _ in 0..2 {
+            weather.tick(&mut rng);
+            assert_eq!(weather.kind(), WeatherKind::Clear);
+        }
+        weather.tick(&mut rng);
+        assert_eq!(weather.kind(), WeatherKind::Clear);
+        assert!(RAIN_DELAY.contains(&weather.rain_time));
+    }
+}
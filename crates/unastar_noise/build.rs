@@ -38,15 +38,52 @@ fn main() {
         .expect("Failed to parse noise settings");
     let biomes = codegen::parser::biome::parse_all(&json_root.join("biome"))
         .expect("Failed to parse biome definitions");
+    let placed_features = codegen::parser::feature::parse_placed(&json_root.join("placed_feature"))
+        .expect("Failed to parse placed features");
+    let configured_features =
+        codegen::parser::feature::parse_configured(&json_root.join("configured_feature"))
+            .expect("Failed to parse configured features");
+    let structures = codegen::parser::structure::parse_structures(&json_root.join("structure"))
+        .expect("Failed to parse structures");
+    let structure_sets =
+        codegen::parser::structure::parse_structure_sets(&json_root.join("structure_set"))
+            .expect("Failed to parse structure sets");
+    let template_pools =
+        codegen::parser::structure::parse_template_pools(&json_root.join("template_pool"))
+            .expect("Failed to parse template pools");
+    let processor_lists =
+        codegen::parser::structure::parse_processor_lists(&json_root.join("processor_list"))
+            .expect("Failed to parse processor lists");
+    let carvers = codegen::parser::carver::parse_all(&json_root.join("configured_carver"))
+        .expect("Failed to parse configured carvers");
 
     println!("cargo:warning=Parsed {} noise definitions", noises.len());
     println!("cargo:warning=Parsed {} density functions", density_functions.len());
     println!("cargo:warning=Parsed {} noise settings", noise_settings.len());
     println!("cargo:warning=Parsed {} biome definitions", biomes.len());
+    println!("cargo:warning=Parsed {} placed features", placed_features.len());
+    println!("cargo:warning=Parsed {} configured features", configured_features.len());
+    println!("cargo:warning=Parsed {} structures", structures.len());
+    println!("cargo:warning=Parsed {} structure sets", structure_sets.len());
+    println!("cargo:warning=Parsed {} template pools", template_pools.len());
+    println!("cargo:warning=Parsed {} processor lists", processor_lists.len());
+    println!("cargo:warning=Parsed {} configured carvers", carvers.len());
 
     // Generate Rust code
-    codegen::emitter::emit_all(&output_dir, &noises, &density_functions, &noise_settings, &biomes)
-        .expect("Failed to emit generated code");
+    let worldgen = codegen::parser::ParsedWorldgen {
+        noises,
+        density_functions,
+        noise_settings,
+        biomes,
+        placed_features,
+        configured_features,
+        structures,
+        structure_sets,
+        template_pools,
+        processor_lists,
+        carvers,
+    };
+    codegen::emitter::emit_all(&output_dir, &worldgen).expect("Failed to emit generated code");
 
     println!("cargo:warning=Generated worldgen code in {:?}", output_dir);
 }
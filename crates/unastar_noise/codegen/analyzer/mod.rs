@@ -128,6 +128,16 @@ impl DependencyGraph {
         }
         visited.insert(id.clone());
 
+        // The emitter redirects a Cache2D wrapper's inner node to the cached
+        // ColumnContext field, so a bare reference to it also needs `col`.
+        let is_cache_2d_inner = self
+            .nodes
+            .values()
+            .any(|n| n.is_cache_2d && n.dependencies.first() == Some(id));
+        if is_cache_2d_inner {
+            return true;
+        }
+
         if let Some(node) = self.nodes.get(id) {
             if node.is_cache_2d {
                 return true;
@@ -119,6 +119,30 @@ pub fn emit_biome_features(
     code.push_str("        }\n");
     code.push_str("    }\n\n");
 
+    // Generate carver list lookup
+    code.push_str("    /// Get the configured carver list for a biome, in carving order.\n");
+    code.push_str("    pub fn carvers(&self) -> &'static [&'static str] {\n");
+    code.push_str("        match self {\n");
+    for biome_name in &biome_names {
+        let carvers = biomes[biome_name].carvers.clone().into_vec();
+        if carvers.is_empty() {
+            continue;
+        }
+        let pascal_name = biome::to_pascal_case(biome_name);
+        let carvers: Vec<String> = carvers
+            .iter()
+            .map(|carver| format!("\"{}\"", biome::strip_minecraft_prefix(carver)))
+            .collect();
+        code.push_str(&format!(
+            "            BiomeFeatures::{} => &[{}],\n",
+            pascal_name,
+            carvers.join(", ")
+        ));
+    }
+    code.push_str("            _ => &[],\n");
+    code.push_str("        }\n");
+    code.push_str("    }\n\n");
+
     // Generate from_name function for string lookup
     code.push_str("    /// Get biome from snake_case name (e.g., \"plains\", \"old_growth_pine_taiga\").\n");
     code.push_str("    pub fn from_name(name: &str) -> Option<Self> {\n");
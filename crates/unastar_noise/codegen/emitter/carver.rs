@@ -0,0 +1,161 @@
+//! Configured carver emitter.
+//!
+//! Every configured carver becomes a private static, looked up by name, and
+//! their names are listed for worlds that pick which carvers run.
+
+use super::feature::{emit_anchor, emit_height_provider};
+use crate::codegen::parser::biome::StringOrArray;
+use crate::codegen::parser::carver::{
+    CanyonShapeJson, CarverJson, ConfiguredCarverJson, FloatProviderJson, TypedFloatProvider,
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Emit carvers.rs with every configured carver.
+pub fn emit_carvers(
+    output_dir: &Path,
+    carvers: &HashMap<String, ConfiguredCarverJson>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut names: Vec<&String> = carvers.keys().collect();
+    names.sort();
+
+    let statics = names.iter().map(|name| {
+        let ident = carver_ident(name);
+        let value = emit_carver(&carvers[*name]);
+        quote! { static #ident: ConfiguredCarver = #value; }
+    });
+    let arms = names.iter().map(|name| {
+        let ident = carver_ident(name);
+        let name = name.as_str();
+        quote! { #name => Some(&#ident), }
+    });
+    let list = names.iter().map(|name| name.as_str());
+
+    let code = quote! {
+        // Generated configured carvers - do not edit manually.
+
+        use crate::carver::*;
+        use crate::feature::HeightProvider;
+        use crate::surface::VerticalAnchor;
+
+        #(#statics)*
+
+        /// Names of every configured carver, sorted, without the `minecraft:` prefix.
+        pub const CONFIGURED_CARVERS: &[&str] = &[#(#list),*];
+
+        /// Look up a configured carver by name, with or without the `minecraft:` prefix.
+        pub fn configured_carver(name: &str) -> Option<&'static ConfiguredCarver> {
+            match name.strip_prefix("minecraft:").unwrap_or(name) {
+                #(#arms)*
+                _ => None,
+            }
+        }
+    };
+
+    std::fs::write(output_dir.join("carvers.rs"), code.to_string())?;
+
+    Ok(())
+}
+
+fn carver_ident(name: &str) -> proc_macro2::Ident {
+    format_ident!("CARVER_{}", name.to_uppercase())
+}
+
+/// Emit a `ConfiguredCarver` value.
+fn emit_carver(carver: &ConfiguredCarverJson) -> TokenStream {
+    let (common, kind) = match carver {
+        ConfiguredCarverJson::Cave(cave) | ConfiguredCarverJson::NetherCave(cave) => {
+            let horizontal = emit_float_provider(&cave.horizontal_radius_multiplier);
+            let vertical = emit_float_provider(&cave.vertical_radius_multiplier);
+            let floor = emit_float_provider(&cave.floor_level);
+            let settings = quote! {
+                CaveSettings {
+                    horizontal_radius_multiplier: #horizontal,
+                    vertical_radius_multiplier: #vertical,
+                    floor_level: #floor,
+                }
+            };
+            let kind = if matches!(carver, ConfiguredCarverJson::Cave(_)) {
+                quote! { CarverKind::Cave(#settings) }
+            } else {
+                quote! { CarverKind::NetherCave(#settings) }
+            };
+            (&cave.carver, kind)
+        }
+        ConfiguredCarverJson::Canyon(canyon) => {
+            let rotation = emit_float_provider(&canyon.vertical_rotation);
+            let shape = emit_canyon_shape(&canyon.shape);
+            let kind = quote! {
+                CarverKind::Canyon {
+                    vertical_rotation: #rotation,
+                    shape: #shape,
+                }
+            };
+            (&canyon.carver, kind)
+        }
+    };
+
+    let CarverJson {
+        probability,
+        y,
+        y_scale,
+        lava_level,
+        replaceable,
+    } = common;
+    let y = emit_height_provider(y);
+    let y_scale = emit_float_provider(y_scale);
+    let lava_level = emit_anchor(lava_level);
+    let replaceable = match replaceable {
+        StringOrArray::Single(name) => vec![name.as_str()],
+        StringOrArray::Array(names) => names.iter().map(String::as_str).collect(),
+    };
+    quote! {
+        ConfiguredCarver {
+            probability: #probability,
+            y: #y,
+            y_scale: #y_scale,
+            lava_level: #lava_level,
+            replaceable: &[#(#replaceable),*],
+            kind: #kind,
+        }
+    }
+}
+
+fn emit_canyon_shape(shape: &CanyonShapeJson) -> TokenStream {
+    let distance = emit_float_provider(&shape.distance_factor);
+    let thickness = emit_float_provider(&shape.thickness);
+    let smoothness = shape.width_smoothness;
+    let horizontal = emit_float_provider(&shape.horizontal_radius_factor);
+    let default = shape.vertical_radius_default_factor;
+    let center = shape.vertical_radius_center_factor;
+    quote! {
+        CanyonShape {
+            distance_factor: #distance,
+            thickness: #thickness,
+            width_smoothness: #smoothness,
+            horizontal_radius_factor: #horizontal,
+            vertical_radius_default_factor: #default,
+            vertical_radius_center_factor: #center,
+        }
+    }
+}
+
+fn emit_float_provider(provider: &FloatProviderJson) -> TokenStream {
+    match provider {
+        FloatProviderJson::Constant(value)
+        | FloatProviderJson::Typed(TypedFloatProvider::Constant { value }) => {
+            quote! { FloatProvider::Constant(#value) }
+        }
+        FloatProviderJson::Typed(TypedFloatProvider::Uniform {
+            min_inclusive,
+            max_exclusive,
+        }) => {
+            quote! { FloatProvider::Uniform { min_inclusive: #min_inclusive, max_exclusive: #max_exclusive } }
+        }
+        FloatProviderJson::Typed(TypedFloatProvider::Trapezoid { min, max, plateau }) => {
+            quote! { FloatProvider::Trapezoid { min: #min, max: #max, plateau: #plateau } }
+        }
+    }
+}
@@ -1145,7 +1145,9 @@ impl<'a> AotEmitter<'a> {
                 }
             }
 
-            DensityFunctionDef::EndIslands {} => quote! { 0.0_f64 },
+            DensityFunctionDef::EndIslands {} => quote! {
+                noises.sample_end_islands(ctx.block_x, ctx.block_z)
+            },
 
             DensityFunctionDef::Invert { .. } => {
                 let v = self.emit_node(&node.dependencies[0]);
@@ -1434,7 +1436,9 @@ impl<'a> AotEmitter<'a> {
                 }
             }
 
-            DensityFunctionDef::EndIslands {} => quote! { f64x4::splat(0.0_f64) },
+            DensityFunctionDef::EndIslands {} => quote! {
+                f64x4::splat(noises.sample_end_islands(ctx.block_x, ctx.block_z))
+            },
 
             DensityFunctionDef::Invert { .. } => {
                 let v = self.emit_node_simd(&node.dependencies[0]);
@@ -2135,9 +2139,9 @@ impl<'a> AotEmitter<'a> {
 
             DensityFunctionDef::Interpolated { .. } => self.emit_fc_dep(0, node),
 
-            DensityFunctionDef::OldBlendedNoise { .. } | DensityFunctionDef::EndIslands {} => {
-                quote! { 0.0_f64 }
-            }
+            DensityFunctionDef::OldBlendedNoise { .. } => quote! { 0.0_f64 },
+
+            DensityFunctionDef::EndIslands {} => quote! { noises.sample_end_islands(bx, bz) },
 
             DensityFunctionDef::Invert { .. } => {
                 let v = self.emit_fc_dep(0, node);
@@ -3250,8 +3254,10 @@ impl<'a> AotEmitter<'a> {
 
             DensityFunctionDef::Interpolated { .. } => self.emit_cc_dep(0, node),
 
-            DensityFunctionDef::OldBlendedNoise { .. } | DensityFunctionDef::EndIslands {} => {
-                quote! { 0.0_f64 }
+            DensityFunctionDef::OldBlendedNoise { .. } => quote! { 0.0_f64 },
+
+            DensityFunctionDef::EndIslands {} => {
+                quote! { noises.sample_end_islands(block_x, block_z) }
             }
 
             DensityFunctionDef::Invert { .. } => {
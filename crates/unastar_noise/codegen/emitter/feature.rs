@@ -0,0 +1,768 @@
+//! Placed and configured feature code emitter.
+//!
+//! Every named feature becomes a private static; inline features are emitted
+//! in place. Named references resolve to the statics, so features can refer
+//! to each other in any order. Lookup functions map names to the statics.
+
+use crate::codegen::parser::biome::strip_minecraft_prefix;
+use crate::codegen::parser::feature::{
+    BlockPredicateJson, ConfiguredFeatureJson, ConfiguredFeatureRef, FoliagePlacerJson,
+    HeightProviderJson, IntProviderJson, PlacedFeatureJson, PlacedFeatureRef,
+    PlacementModifierJson, RuleTestJson, StateProviderJson, TrunkPlacerJson, TypedHeightProvider,
+    TypedIntProvider,
+};
+use crate::codegen::parser::noise::NoiseParams;
+use crate::codegen::parser::surface_rule::{BlockState, VerticalAnchor};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Emit features.rs with every placed and configured feature.
+pub fn emit_features(
+    output_dir: &Path,
+    placed: &HashMap<String, PlacedFeatureJson>,
+    configured: &HashMap<String, ConfiguredFeatureJson>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let emitter = FeatureEmitter { placed, configured };
+
+    let mut configured_names: Vec<&String> = configured.keys().collect();
+    configured_names.sort();
+    let mut placed_names: Vec<&String> = placed.keys().collect();
+    placed_names.sort();
+
+    let configured_statics = configured_names.iter().map(|name| {
+        let ident = configured_ident(name);
+        let value = emitter.emit_configured(&configured[*name]);
+        quote! { static #ident: ConfiguredFeature = #value; }
+    });
+    let placed_statics = placed_names.iter().map(|name| {
+        let ident = placed_ident(name);
+        let value = emitter.emit_placed(&placed[*name]);
+        quote! { static #ident: PlacedFeature = #value; }
+    });
+
+    let configured_arms = configured_names.iter().map(|name| {
+        let ident = configured_ident(name);
+        let name = name.as_str();
+        quote! { #name => Some(&#ident), }
+    });
+    let placed_arms = placed_names.iter().map(|name| {
+        let ident = placed_ident(name);
+        let name = name.as_str();
+        quote! { #name => Some(&#ident), }
+    });
+
+    let code = quote! {
+        // Generated placed and configured features - do not edit manually.
+
+        use crate::feature::*;
+        use crate::surface::VerticalAnchor;
+
+        #(#configured_statics)*
+
+        #(#placed_statics)*
+
+        /// Look up a placed feature by name, with or without the `minecraft:` prefix.
+        pub fn placed_feature(name: &str) -> Option<&'static PlacedFeature> {
+            match name.strip_prefix("minecraft:").unwrap_or(name) {
+                #(#placed_arms)*
+                _ => None,
+            }
+        }
+
+        /// Look up a configured feature by name, with or without the `minecraft:` prefix.
+        pub fn configured_feature(name: &str) -> Option<&'static ConfiguredFeature> {
+            match name.strip_prefix("minecraft:").unwrap_or(name) {
+                #(#configured_arms)*
+                _ => None,
+            }
+        }
+    };
+
+    std::fs::write(output_dir.join("features.rs"), code.to_string())?;
+
+    Ok(())
+}
+
+fn configured_ident(name: &str) -> proc_macro2::Ident {
+    format_ident!("CONFIGURED_{}", name.to_uppercase())
+}
+
+fn placed_ident(name: &str) -> proc_macro2::Ident {
+    format_ident!("PLACED_{}", name.to_uppercase())
+}
+
+struct FeatureEmitter<'a> {
+    placed: &'a HashMap<String, PlacedFeatureJson>,
+    configured: &'a HashMap<String, ConfiguredFeatureJson>,
+}
+
+impl FeatureEmitter<'_> {
+    /// Emit a `PlacedFeature` value.
+    fn emit_placed(&self, placed: &PlacedFeatureJson) -> TokenStream {
+        let feature = self.emit_configured_ref(&placed.feature);
+        let placement = placed.placement.iter().map(emit_modifier);
+        quote! {
+            PlacedFeature {
+                feature: #feature,
+                placement: &[#(#placement),*],
+            }
+        }
+    }
+
+    /// Emit a `&'static PlacedFeature`.
+    fn emit_placed_ref(&self, feature: &PlacedFeatureRef) -> TokenStream {
+        match feature {
+            PlacedFeatureRef::Named(name) => {
+                let name = strip_minecraft_prefix(name);
+                if self.placed.contains_key(name) {
+                    let ident = placed_ident(name);
+                    quote! { &#ident }
+                } else {
+                    let name = format!("minecraft:{name}");
+                    quote! {
+                        &PlacedFeature {
+                            feature: &ConfiguredFeature::Unsupported(#name),
+                            placement: &[],
+                        }
+                    }
+                }
+            }
+            PlacedFeatureRef::Inline(placed) => {
+                let value = self.emit_placed(placed);
+                quote! { &#value }
+            }
+        }
+    }
+
+    /// Emit a `&'static ConfiguredFeature`.
+    fn emit_configured_ref(&self, feature: &ConfiguredFeatureRef) -> TokenStream {
+        match feature {
+            ConfiguredFeatureRef::Named(name) => {
+                let name = strip_minecraft_prefix(name);
+                if self.configured.contains_key(name) {
+                    let ident = configured_ident(name);
+                    quote! { &#ident }
+                } else {
+                    let name = format!("minecraft:{name}");
+                    quote! { &ConfiguredFeature::Unsupported(#name) }
+                }
+            }
+            ConfiguredFeatureRef::Inline(configured) => {
+                let value = self.emit_configured(configured);
+                quote! { &#value }
+            }
+        }
+    }
+
+    /// Emit a `ConfiguredFeature` value.
+    fn emit_configured(&self, feature: &ConfiguredFeatureJson) -> TokenStream {
+        match feature {
+            ConfiguredFeatureJson::Tree(config) => {
+                let trunk_provider = emit_state_provider(&config.trunk_provider);
+                let foliage_provider = emit_state_provider(&config.foliage_provider);
+                let dirt_provider = emit_state_provider(&config.dirt_provider);
+                let trunk_placer = emit_trunk_placer(&config.trunk_placer);
+                let foliage_placer = emit_foliage_placer(&config.foliage_placer);
+                let force_dirt = config.force_dirt;
+                let ignore_vines = config.ignore_vines;
+                quote! {
+                    ConfiguredFeature::Tree(&TreeConfig {
+                        trunk_provider: #trunk_provider,
+                        foliage_provider: #foliage_provider,
+                        dirt_provider: #dirt_provider,
+                        trunk_placer: #trunk_placer,
+                        foliage_placer: #foliage_placer,
+                        force_dirt: #force_dirt,
+                        ignore_vines: #ignore_vines,
+                    })
+                }
+            }
+            ConfiguredFeatureJson::Ore(config) | ConfiguredFeatureJson::ScatteredOre(config) => {
+                let size = config.size;
+                let discard = config.discard_chance_on_air_exposure;
+                let targets = config.targets.iter().map(|target| {
+                    let rule = emit_rule_test(&target.target);
+                    let state = emit_block_state(&target.state);
+                    quote! { OreTarget { target: #rule, state: #state } }
+                });
+                let variant = if matches!(feature, ConfiguredFeatureJson::Ore(_)) {
+                    quote! { Ore }
+                } else {
+                    quote! { ScatteredOre }
+                };
+                quote! {
+                    ConfiguredFeature::#variant(OreConfig {
+                        size: #size,
+                        discard_chance_on_air_exposure: #discard,
+                        targets: &[#(#targets),*],
+                    })
+                }
+            }
+            ConfiguredFeatureJson::RandomPatch(config) => {
+                let tries = config.tries;
+                let xz_spread = config.xz_spread;
+                let y_spread = config.y_spread;
+                let feature = self.emit_placed_ref(&config.feature);
+                quote! {
+                    ConfiguredFeature::RandomPatch(RandomPatchConfig {
+                        tries: #tries,
+                        xz_spread: #xz_spread,
+                        y_spread: #y_spread,
+                        feature: #feature,
+                    })
+                }
+            }
+            ConfiguredFeatureJson::Disk(config) => {
+                let fallback = emit_state_provider(&config.state_provider.fallback);
+                let rules = config.state_provider.rules.iter().map(|rule| {
+                    let predicate = emit_predicate(&rule.if_true);
+                    let then = emit_state_provider(&rule.then);
+                    quote! { (#predicate, #then) }
+                });
+                let target = emit_predicate(&config.target);
+                let radius = emit_int_provider(&config.radius);
+                let half_height = config.half_height;
+                quote! {
+                    ConfiguredFeature::Disk(DiskConfig {
+                        state_provider: RuleBasedStateProvider {
+                            fallback: #fallback,
+                            rules: &[#(#rules),*],
+                        },
+                        target: #target,
+                        radius: #radius,
+                        half_height: #half_height,
+                    })
+                }
+            }
+            ConfiguredFeatureJson::Lake(config) => {
+                let fluid = emit_state_provider(&config.fluid);
+                let barrier = emit_state_provider(&config.barrier);
+                quote! {
+                    ConfiguredFeature::Lake(&LakeConfig { fluid: #fluid, barrier: #barrier })
+                }
+            }
+            ConfiguredFeatureJson::Spring(config) => {
+                let state = emit_block_state(&config.state);
+                let requires_block_below = config.requires_block_below;
+                let rock_count = config.rock_count;
+                let hole_count = config.hole_count;
+                let valid_blocks = config.valid_blocks.clone().into_vec();
+                quote! {
+                    ConfiguredFeature::Spring(SpringConfig {
+                        state: #state,
+                        requires_block_below: #requires_block_below,
+                        rock_count: #rock_count,
+                        hole_count: #hole_count,
+                        valid_blocks: &[#(#valid_blocks),*],
+                    })
+                }
+            }
+            ConfiguredFeatureJson::SimpleBlock(config) => {
+                let to_place = emit_state_provider(&config.to_place);
+                quote! { ConfiguredFeature::SimpleBlock { to_place: #to_place } }
+            }
+            ConfiguredFeatureJson::RandomSelector(config) => {
+                let features = config.features.iter().map(|entry| {
+                    let chance = entry.chance;
+                    let feature = self.emit_placed_ref(&entry.feature);
+                    quote! { (#chance, #feature) }
+                });
+                let default = self.emit_placed_ref(&config.default);
+                quote! {
+                    ConfiguredFeature::RandomSelector {
+                        features: &[#(#features),*],
+                        default: #default,
+                    }
+                }
+            }
+            ConfiguredFeatureJson::SimpleRandomSelector(config) => {
+                let features = config.features.iter().map(|f| self.emit_placed_ref(f));
+                quote! { ConfiguredFeature::SimpleRandomSelector(&[#(#features),*]) }
+            }
+            ConfiguredFeatureJson::RandomBooleanSelector(config) => {
+                let feature_true = self.emit_placed_ref(&config.feature_true);
+                let feature_false = self.emit_placed_ref(&config.feature_false);
+                quote! {
+                    ConfiguredFeature::RandomBooleanSelector {
+                        feature_true: #feature_true,
+                        feature_false: #feature_false,
+                    }
+                }
+            }
+            ConfiguredFeatureJson::Unsupported(kind) => {
+                quote! { ConfiguredFeature::Unsupported(#kind) }
+            }
+        }
+    }
+}
+
+fn emit_modifier(modifier: &PlacementModifierJson) -> TokenStream {
+    match modifier {
+        PlacementModifierJson::Count { count } => {
+            let count = emit_int_provider(count);
+            quote! { PlacementModifier::Count(#count) }
+        }
+        PlacementModifierJson::CountOnEveryLayer { count } => {
+            let count = emit_int_provider(count);
+            quote! { PlacementModifier::CountOnEveryLayer(#count) }
+        }
+        PlacementModifierJson::RarityFilter { chance } => {
+            quote! { PlacementModifier::RarityFilter { chance: #chance } }
+        }
+        PlacementModifierJson::InSquare {} => quote! { PlacementModifier::InSquare },
+        PlacementModifierJson::Biome {} => quote! { PlacementModifier::Biome },
+        PlacementModifierJson::HeightRange { height } => {
+            let height = emit_height_provider(height);
+            quote! { PlacementModifier::HeightRange(#height) }
+        }
+        PlacementModifierJson::Heightmap { heightmap } => match emit_heightmap(heightmap) {
+            Some(heightmap) => quote! { PlacementModifier::Heightmap(#heightmap) },
+            None => quote! { PlacementModifier::Unsupported },
+        },
+        PlacementModifierJson::NoiseThresholdCount {
+            noise_level,
+            below_noise,
+            above_noise,
+        } => {
+            quote! {
+                PlacementModifier::NoiseThresholdCount {
+                    noise_level: #noise_level,
+                    below_noise: #below_noise,
+                    above_noise: #above_noise,
+                }
+            }
+        }
+        PlacementModifierJson::NoiseBasedCount {
+            noise_to_count_ratio,
+            noise_factor,
+            noise_offset,
+        } => {
+            quote! {
+                PlacementModifier::NoiseBasedCount {
+                    noise_to_count_ratio: #noise_to_count_ratio,
+                    noise_factor: #noise_factor,
+                    noise_offset: #noise_offset,
+                }
+            }
+        }
+        PlacementModifierJson::SurfaceWaterDepthFilter { max_water_depth } => {
+            quote! { PlacementModifier::SurfaceWaterDepthFilter { max_water_depth: #max_water_depth } }
+        }
+        PlacementModifierJson::SurfaceRelativeThresholdFilter {
+            heightmap,
+            min_inclusive,
+            max_inclusive,
+        } => match emit_heightmap(heightmap) {
+            Some(heightmap) => quote! {
+                PlacementModifier::SurfaceRelativeThresholdFilter {
+                    heightmap: #heightmap,
+                    min_inclusive: #min_inclusive,
+                    max_inclusive: #max_inclusive,
+                }
+            },
+            None => quote! { PlacementModifier::Unsupported },
+        },
+        PlacementModifierJson::BlockPredicateFilter { predicate } => {
+            let predicate = emit_predicate(predicate);
+            quote! { PlacementModifier::BlockPredicateFilter(#predicate) }
+        }
+        PlacementModifierJson::RandomOffset {
+            xz_spread,
+            y_spread,
+        } => {
+            let xz_spread = emit_int_provider(xz_spread);
+            let y_spread = emit_int_provider(y_spread);
+            quote! { PlacementModifier::RandomOffset { xz_spread: #xz_spread, y_spread: #y_spread } }
+        }
+        PlacementModifierJson::EnvironmentScan {
+            direction_of_search,
+            max_steps,
+            target_condition,
+            allowed_search_condition,
+        } => {
+            let direction = emit_direction(direction_of_search);
+            let target_condition = emit_predicate(target_condition);
+            let allowed = match allowed_search_condition {
+                Some(predicate) => {
+                    let predicate = emit_predicate(predicate);
+                    quote! { Some(#predicate) }
+                }
+                None => quote! { None },
+            };
+            quote! {
+                PlacementModifier::EnvironmentScan {
+                    direction_of_search: #direction,
+                    max_steps: #max_steps,
+                    target_condition: #target_condition,
+                    allowed_search_condition: #allowed,
+                }
+            }
+        }
+        PlacementModifierJson::FixedPlacement { positions } => {
+            let positions = positions.iter().map(|[x, y, z]| quote! { [#x, #y, #z] });
+            quote! { PlacementModifier::FixedPlacement(&[#(#positions),*]) }
+        }
+        PlacementModifierJson::Unsupported => quote! { PlacementModifier::Unsupported },
+    }
+}
+
+pub(super) fn emit_int_provider(provider: &IntProviderJson) -> TokenStream {
+    match provider {
+        IntProviderJson::Constant(value)
+        | IntProviderJson::Typed(TypedIntProvider::Constant { value }) => {
+            quote! { IntProvider::Constant(#value) }
+        }
+        IntProviderJson::Typed(TypedIntProvider::Uniform {
+            min_inclusive,
+            max_inclusive,
+        }) => {
+            quote! { IntProvider::Uniform { min_inclusive: #min_inclusive, max_inclusive: #max_inclusive } }
+        }
+        IntProviderJson::Typed(TypedIntProvider::BiasedToBottom {
+            min_inclusive,
+            max_inclusive,
+        }) => {
+            quote! { IntProvider::BiasedToBottom { min_inclusive: #min_inclusive, max_inclusive: #max_inclusive } }
+        }
+        IntProviderJson::Typed(TypedIntProvider::Clamped {
+            source,
+            min_inclusive,
+            max_inclusive,
+        }) => {
+            let source = emit_int_provider(source);
+            quote! {
+                IntProvider::Clamped {
+                    source: &#source,
+                    min_inclusive: #min_inclusive,
+                    max_inclusive: #max_inclusive,
+                }
+            }
+        }
+        IntProviderJson::Typed(TypedIntProvider::WeightedList { distribution }) => {
+            let entries = distribution.iter().map(|entry| {
+                let data = emit_int_provider(&entry.data);
+                let weight = entry.weight;
+                quote! { (#data, #weight) }
+            });
+            quote! { IntProvider::WeightedList(&[#(#entries),*]) }
+        }
+        IntProviderJson::Typed(TypedIntProvider::Unsupported) => {
+            quote! { IntProvider::Constant(0) }
+        }
+    }
+}
+
+pub(super) fn emit_height_provider(provider: &HeightProviderJson) -> TokenStream {
+    let typed = match provider {
+        HeightProviderJson::Anchor(anchor) => {
+            let anchor = emit_anchor(anchor);
+            return quote! { HeightProvider::Constant(#anchor) };
+        }
+        HeightProviderJson::Typed(typed) => typed,
+    };
+
+    match typed {
+        TypedHeightProvider::Constant { value } => {
+            let value = emit_anchor(value);
+            quote! { HeightProvider::Constant(#value) }
+        }
+        TypedHeightProvider::Uniform {
+            min_inclusive,
+            max_inclusive,
+        } => {
+            let min = emit_anchor(min_inclusive);
+            let max = emit_anchor(max_inclusive);
+            quote! { HeightProvider::Uniform { min_inclusive: #min, max_inclusive: #max } }
+        }
+        TypedHeightProvider::Trapezoid {
+            min_inclusive,
+            max_inclusive,
+            plateau,
+        } => {
+            let min = emit_anchor(min_inclusive);
+            let max = emit_anchor(max_inclusive);
+            quote! { HeightProvider::Trapezoid { min_inclusive: #min, max_inclusive: #max, plateau: #plateau } }
+        }
+        TypedHeightProvider::BiasedToBottom {
+            min_inclusive,
+            max_inclusive,
+            inner,
+        } => {
+            let min = emit_anchor(min_inclusive);
+            let max = emit_anchor(max_inclusive);
+            quote! { HeightProvider::BiasedToBottom { min_inclusive: #min, max_inclusive: #max, inner: #inner } }
+        }
+        TypedHeightProvider::VeryBiasedToBottom {
+            min_inclusive,
+            max_inclusive,
+            inner,
+        } => {
+            let min = emit_anchor(min_inclusive);
+            let max = emit_anchor(max_inclusive);
+            quote! { HeightProvider::VeryBiasedToBottom { min_inclusive: #min, max_inclusive: #max, inner: #inner } }
+        }
+        TypedHeightProvider::WeightedList { distribution } => {
+            let entries = distribution.iter().map(|entry| {
+                let data = emit_height_provider(&entry.data);
+                let weight = entry.weight;
+                quote! { (#data, #weight) }
+            });
+            quote! { HeightProvider::WeightedList(&[#(#entries),*]) }
+        }
+    }
+}
+
+pub(super) fn emit_anchor(anchor: &VerticalAnchor) -> TokenStream {
+    match anchor {
+        VerticalAnchor::Absolute { absolute } => quote! { VerticalAnchor::Absolute(#absolute) },
+        VerticalAnchor::AboveBottom { above_bottom } => {
+            quote! { VerticalAnchor::AboveBottom(#above_bottom) }
+        }
+        VerticalAnchor::BelowTop { below_top } => quote! { VerticalAnchor::BelowTop(#below_top) },
+    }
+}
+
+pub(super) fn emit_heightmap(heightmap: &str) -> Option<TokenStream> {
+    Some(match heightmap {
+        "WORLD_SURFACE_WG" => quote! { Heightmap::WorldSurfaceWg },
+        "WORLD_SURFACE" => quote! { Heightmap::WorldSurface },
+        "OCEAN_FLOOR_WG" => quote! { Heightmap::OceanFloorWg },
+        "OCEAN_FLOOR" => quote! { Heightmap::OceanFloor },
+        "MOTION_BLOCKING" => quote! { Heightmap::MotionBlocking },
+        "MOTION_BLOCKING_NO_LEAVES" => quote! { Heightmap::MotionBlockingNoLeaves },
+        _ => return None,
+    })
+}
+
+fn emit_direction(direction: &str) -> TokenStream {
+    match direction {
+        "up" => quote! { Direction::Up },
+        "north" => quote! { Direction::North },
+        "south" => quote! { Direction::South },
+        "west" => quote! { Direction::West },
+        "east" => quote! { Direction::East },
+        _ => quote! { Direction::Down },
+    }
+}
+
+fn emit_predicate(predicate: &BlockPredicateJson) -> TokenStream {
+    let offset = |[x, y, z]: &[i32; 3]| quote! { [#x, #y, #z] };
+    match predicate {
+        BlockPredicateJson::MatchingBlocks { offset: o, blocks } => {
+            let o = offset(o);
+            let blocks = blocks.clone().into_vec();
+            quote! { BlockPredicate::MatchingBlocks { offset: #o, blocks: &[#(#blocks),*] } }
+        }
+        BlockPredicateJson::MatchingBlockTag { offset: o, tag } => {
+            let o = offset(o);
+            quote! { BlockPredicate::MatchingBlockTag { offset: #o, tag: #tag } }
+        }
+        BlockPredicateJson::MatchingFluids { offset: o, fluids } => {
+            let o = offset(o);
+            let fluids = fluids.clone().into_vec();
+            quote! { BlockPredicate::MatchingFluids { offset: #o, fluids: &[#(#fluids),*] } }
+        }
+        BlockPredicateJson::Solid { offset: o } => {
+            let o = offset(o);
+            quote! { BlockPredicate::Solid { offset: #o } }
+        }
+        BlockPredicateJson::Replaceable { offset: o } => {
+            let o = offset(o);
+            quote! { BlockPredicate::Replaceable { offset: #o } }
+        }
+        BlockPredicateJson::WouldSurvive { offset: o, state } => {
+            let o = offset(o);
+            let state = emit_block_state(state);
+            quote! { BlockPredicate::WouldSurvive { offset: #o, state: #state } }
+        }
+        BlockPredicateJson::HasSturdyFace {
+            offset: o,
+            direction,
+        } => {
+            let o = offset(o);
+            let direction = emit_direction(direction);
+            quote! { BlockPredicate::HasSturdyFace { offset: #o, direction: #direction } }
+        }
+        BlockPredicateJson::InsideWorldBounds { offset: o } => {
+            let o = offset(o);
+            quote! { BlockPredicate::InsideWorldBounds { offset: #o } }
+        }
+        BlockPredicateJson::Not { predicate } => {
+            let predicate = emit_predicate(predicate);
+            quote! { BlockPredicate::Not(&#predicate) }
+        }
+        BlockPredicateJson::AllOf { predicates } => {
+            let predicates = predicates.iter().map(emit_predicate);
+            quote! { BlockPredicate::AllOf(&[#(#predicates),*]) }
+        }
+        BlockPredicateJson::AnyOf { predicates } => {
+            let predicates = predicates.iter().map(emit_predicate);
+            quote! { BlockPredicate::AnyOf(&[#(#predicates),*]) }
+        }
+        BlockPredicateJson::True {} => quote! { BlockPredicate::True },
+        BlockPredicateJson::Unsupported => quote! { BlockPredicate::Unsupported },
+    }
+}
+
+fn emit_state_provider(provider: &StateProviderJson) -> TokenStream {
+    match provider {
+        StateProviderJson::Simple { state } => {
+            let state = emit_block_state(state);
+            quote! { BlockStateProvider::Simple(#state) }
+        }
+        StateProviderJson::Rotated { state } => {
+            let state = emit_block_state(state);
+            quote! { BlockStateProvider::Rotated(#state) }
+        }
+        StateProviderJson::Weighted { entries } => {
+            let entries = entries.iter().map(|entry| {
+                let state = emit_block_state(&entry.data);
+                let weight = entry.weight;
+                quote! { (#state, #weight) }
+            });
+            quote! { BlockStateProvider::Weighted(&[#(#entries),*]) }
+        }
+        StateProviderJson::RandomizedInt { source } => {
+            let source = emit_state_provider(source);
+            quote! { BlockStateProvider::RandomizedInt(&#source) }
+        }
+        StateProviderJson::NoiseThreshold {
+            seed,
+            noise,
+            scale,
+            threshold,
+            high_chance,
+            default_state,
+            low_states,
+            high_states,
+        } => {
+            let noise = emit_noise_parameters(noise);
+            let default_state = emit_block_state(default_state);
+            let low_states = low_states.iter().map(emit_block_state);
+            let high_states = high_states.iter().map(emit_block_state);
+            quote! {
+                BlockStateProvider::NoiseThreshold {
+                    seed: #seed,
+                    noise: #noise,
+                    scale: #scale,
+                    threshold: #threshold,
+                    high_chance: #high_chance,
+                    default_state: #default_state,
+                    low_states: &[#(#low_states),*],
+                    high_states: &[#(#high_states),*],
+                }
+            }
+        }
+        StateProviderJson::Noise {
+            seed,
+            noise,
+            scale,
+            states,
+        } => {
+            let noise = emit_noise_parameters(noise);
+            let states = states.iter().map(emit_block_state);
+            quote! {
+                BlockStateProvider::Noise {
+                    seed: #seed,
+                    noise: #noise,
+                    scale: #scale,
+                    states: &[#(#states),*],
+                }
+            }
+        }
+        StateProviderJson::Unsupported => quote! { BlockStateProvider::Unsupported },
+    }
+}
+
+fn emit_noise_parameters(noise: &NoiseParams) -> TokenStream {
+    let first_octave = noise.first_octave;
+    let amplitudes = &noise.amplitudes;
+    quote! { NoiseParameters { first_octave: #first_octave, amplitudes: &[#(#amplitudes),*] } }
+}
+
+pub(super) fn emit_block_state(state: &BlockState) -> TokenStream {
+    let name = &state.name;
+    let mut properties: Vec<(&String, &String)> = state.properties.iter().flatten().collect();
+    properties.sort();
+    let properties = properties
+        .iter()
+        .map(|(key, value)| quote! { (#key, #value) });
+    quote! { BlockState { name: #name, properties: &[#(#properties),*] } }
+}
+
+pub(super) fn emit_rule_test(rule: &RuleTestJson) -> TokenStream {
+    match rule {
+        RuleTestJson::AlwaysTrue {} => quote! { RuleTest::AlwaysTrue },
+        RuleTestJson::TagMatch { tag } => quote! { RuleTest::TagMatch(#tag) },
+        RuleTestJson::BlockMatch { block } => quote! { RuleTest::BlockMatch(#block) },
+        RuleTestJson::RandomBlockMatch { block, probability } => {
+            quote! { RuleTest::RandomBlockMatch { block: #block, probability: #probability } }
+        }
+        // Blocks are placed in their default state, so states match by name
+        RuleTestJson::BlockstateMatch { block_state } => {
+            let block = &block_state.name;
+            quote! { RuleTest::BlockMatch(#block) }
+        }
+        RuleTestJson::Unsupported => quote! { RuleTest::Unsupported },
+    }
+}
+
+fn emit_trunk_placer(placer: &TrunkPlacerJson) -> TokenStream {
+    let kind = match strip_minecraft_prefix(&placer.kind) {
+        "forking_trunk_placer" => quote! { TrunkPlacerKind::Forking },
+        "giant_trunk_placer" => quote! { TrunkPlacerKind::Giant },
+        "mega_jungle_trunk_placer" => quote! { TrunkPlacerKind::MegaJungle },
+        "dark_oak_trunk_placer" => quote! { TrunkPlacerKind::DarkOak },
+        "fancy_trunk_placer" => quote! { TrunkPlacerKind::Fancy },
+        "bending_trunk_placer" => quote! { TrunkPlacerKind::Bending },
+        "upwards_branching_trunk_placer" => quote! { TrunkPlacerKind::UpwardsBranching },
+        "cherry_trunk_placer" => quote! { TrunkPlacerKind::Cherry },
+        _ => quote! { TrunkPlacerKind::Straight },
+    };
+    let base_height = placer.base_height;
+    let height_rand_a = placer.height_rand_a;
+    let height_rand_b = placer.height_rand_b;
+    quote! {
+        TrunkPlacer {
+            kind: #kind,
+            base_height: #base_height,
+            height_rand_a: #height_rand_a,
+            height_rand_b: #height_rand_b,
+        }
+    }
+}
+
+fn emit_foliage_placer(placer: &FoliagePlacerJson) -> TokenStream {
+    let kind = match strip_minecraft_prefix(&placer.kind) {
+        "bush_foliage_placer" => quote! { FoliagePlacerKind::Bush },
+        "fancy_foliage_placer" => quote! { FoliagePlacerKind::Fancy },
+        "jungle_foliage_placer" => quote! { FoliagePlacerKind::Jungle },
+        "spruce_foliage_placer" => quote! { FoliagePlacerKind::Spruce },
+        "pine_foliage_placer" => quote! { FoliagePlacerKind::Pine },
+        "mega_pine_foliage_placer" => quote! { FoliagePlacerKind::MegaPine },
+        "acacia_foliage_placer" => quote! { FoliagePlacerKind::Acacia },
+        "dark_oak_foliage_placer" => quote! { FoliagePlacerKind::DarkOak },
+        "random_spread_foliage_placer" => quote! { FoliagePlacerKind::RandomSpread },
+        "cherry_foliage_placer" => quote! { FoliagePlacerKind::Cherry },
+        _ => quote! { FoliagePlacerKind::Blob },
+    };
+    let radius = emit_int_provider(&placer.radius);
+    let offset = emit_int_provider(&placer.offset);
+    let height = placer
+        .height
+        .as_ref()
+        .or(placer.trunk_height.as_ref())
+        .or(placer.crown_height.as_ref())
+        .or(placer.foliage_height.as_ref())
+        .map_or_else(|| quote! { IntProvider::Constant(0) }, emit_int_provider);
+    quote! {
+        FoliagePlacer {
+            kind: #kind,
+            radius: #radius,
+            offset: #offset,
+            height: #height,
+        }
+    }
+}
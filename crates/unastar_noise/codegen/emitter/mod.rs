@@ -1,6 +1,9 @@
 pub mod biome_features;
+pub mod carver;
 pub mod emitter_quote;
+pub mod feature;
 pub mod noise;
+pub mod structure;
 pub mod surface_rule;
 
 use super::analyzer::DependencyGraph;
@@ -10,17 +13,37 @@ use std::path::Path;
 
 pub fn emit_all(
     output_dir: &Path,
-    noises: &HashMap<String, parser::noise::NoiseParams>,
-    density_functions: &HashMap<String, parser::density_function::DensityFunctionArg>,
-    noise_settings: &HashMap<String, parser::noise_settings::NoiseSettings>,
-    biomes: &HashMap<String, parser::biome::BiomeJson>,
+    worldgen: &parser::ParsedWorldgen,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let parser::ParsedWorldgen {
+        noises,
+        density_functions,
+        noise_settings,
+        biomes,
+        placed_features,
+        configured_features,
+        structures,
+        structure_sets,
+        template_pools,
+        processor_lists,
+        carvers,
+    } = worldgen;
+
     // Generate noise_params.rs (dynamic - from JSON)
     noise::emit_noise_params(output_dir, noises)?;
 
-    // Generate overworld_compiled.rs (AOT compiled - this is the only thing we need now)
-    if let Some(overworld) = noise_settings.get("minecraft:overworld") {
-        let router = &overworld.noise_router;
+    // Generate one AOT compiled module per dimension router. The overworld's
+    // items are re-exported at the crate root; the others live in their own
+    // module since the generated item names are the same.
+    for (settings, file) in [
+        ("minecraft:overworld", "overworld_compiled.rs"),
+        ("minecraft:nether", "nether_compiled.rs"),
+        ("minecraft:end", "end_compiled.rs"),
+    ] {
+        let Some(settings) = noise_settings.get(settings) else {
+            continue;
+        };
+        let router = &settings.noise_router;
         let router_fields: Vec<(&str, &parser::density_function::DensityFunctionArg)> = vec![
             ("barrier", &router.barrier),
             ("continents", &router.continents),
@@ -46,29 +69,69 @@ pub fn emit_all(
         let mut emitter = emitter_quote::AotEmitter::new(&graph);
         let compiled_code = emitter.emit_module();
 
-        std::fs::write(output_dir.join("overworld_compiled.rs"), compiled_code)?;
+        std::fs::write(output_dir.join(file), compiled_code)?;
     }
 
     // Generate biome_features.rs
     biome_features::emit_biome_features(output_dir, biomes)?;
 
-    // Generate surface_rules.rs from overworld surface_rule
-    if let Some(overworld) = noise_settings.get("minecraft:overworld") {
-        surface_rule::emit_surface_rules(output_dir, &overworld.surface_rule)?;
-    }
+    // Generate features.rs with the placed and configured features
+    feature::emit_features(output_dir, placed_features, configured_features)?;
+
+    // Generate structures.rs with the jigsaw structures and their pools
+    structure::emit_structures(
+        output_dir,
+        structures,
+        structure_sets,
+        template_pools,
+        processor_lists,
+    )?;
+
+    // Generate carvers.rs with the configured carvers
+    carver::emit_carvers(output_dir, carvers)?;
+
+    // Generate surface_rules.rs with one builder per dimension
+    let surface_rules: Vec<surface_rule::SurfaceRuleSet> = [
+        ("minecraft:overworld", "build_vanilla_surface_rule"),
+        ("minecraft:nether", "build_nether_surface_rule"),
+        ("minecraft:end", "build_end_surface_rule"),
+    ]
+    .into_iter()
+    .filter_map(|(settings, function)| {
+        let settings = noise_settings.get(settings)?;
+        Some(surface_rule::SurfaceRuleSet {
+            function,
+            rule: &settings.surface_rule,
+            min_y: settings.noise.min_y,
+            max_y: settings.noise.min_y + settings.noise.height - 1,
+        })
+    })
+    .collect();
+    surface_rule::emit_surface_rules(output_dir, &surface_rules)?;
 
     // Generate mod.rs - use regular comments instead of doc comments for include!() compatibility
     let mod_content = r#"// Generated worldgen code.
 // Do not edit manually - regenerated at build time from worldgen JSON.
 
 mod biome_features;
+mod carvers;
+mod features;
 mod noise_params;
 mod overworld_compiled;
+mod structures;
 mod surface_rules;
 
+// AOT compiled Nether and End routers, kept in their own modules because
+// their item names match the overworld's.
+pub mod nether_compiled;
+pub mod end_compiled;
+
 pub use biome_features::*;
+pub use carvers::*;
+pub use features::*;
 pub use noise_params::*;
 pub use overworld_compiled::*;
+pub use structures::*;
 pub use surface_rules::*;
 "#;
     std::fs::write(output_dir.join("mod.rs"), mod_content)?;
@@ -0,0 +1,484 @@
+//! Jigsaw structure, structure set, template pool and processor list emitter.
+//!
+//! Every named value becomes a private static and named references resolve
+//! to the statics, like the feature emitter. Names missing from the data
+//! (non-jigsaw structures, unknown processor lists) resolve to nothing, so a
+//! bad reference never fails the build.
+
+use super::feature::{
+    emit_block_state, emit_height_provider, emit_heightmap, emit_int_provider, emit_rule_test,
+};
+use crate::codegen::parser::biome::strip_minecraft_prefix;
+use crate::codegen::parser::structure::{
+    DimensionPaddingJson, JigsawStructureJson, PoolAliasJson, PoolElementJson, PosRuleTestJson,
+    ProcessorJson, ProcessorListJson, ProcessorsRef, StructureJson, StructurePlacementJson,
+    StructureSetJson, TemplatePoolJson,
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Emit structures.rs with the jigsaw structures, structure sets, template
+/// pools and processor lists.
+pub fn emit_structures(
+    output_dir: &Path,
+    structures: &HashMap<String, StructureJson>,
+    structure_sets: &HashMap<String, StructureSetJson>,
+    template_pools: &HashMap<String, TemplatePoolJson>,
+    processor_lists: &HashMap<String, ProcessorListJson>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let jigsaw: HashMap<&String, &JigsawStructureJson> = structures
+        .iter()
+        .filter_map(|(name, structure)| match structure {
+            StructureJson::Jigsaw(jigsaw) => Some((name, &**jigsaw)),
+            StructureJson::Unsupported => None,
+        })
+        .collect();
+    let emitter = StructureEmitter { processor_lists };
+
+    let mut structure_names: Vec<&String> = jigsaw.keys().copied().collect();
+    structure_names.sort();
+    let mut set_names: Vec<&String> = structure_sets.keys().collect();
+    set_names.sort();
+    let mut pool_names: Vec<&String> = template_pools.keys().collect();
+    pool_names.sort();
+    let mut processor_names: Vec<&String> = processor_lists.keys().collect();
+    processor_names.sort();
+
+    let structure_statics = structure_names.iter().map(|name| {
+        let ident = structure_ident(name);
+        let value = emit_jigsaw_structure(jigsaw[*name]);
+        quote! { static #ident: JigsawStructure = #value; }
+    });
+    let set_statics = set_names.iter().map(|name| {
+        let ident = set_ident(name);
+        let value = emit_structure_set(&structure_sets[*name]);
+        quote! { static #ident: StructureSet = #value; }
+    });
+    let pool_statics = pool_names.iter().map(|name| {
+        let ident = pool_ident(name);
+        let value = emitter.emit_pool(&template_pools[*name]);
+        quote! { static #ident: TemplatePool = #value; }
+    });
+    let processor_statics = processor_names.iter().map(|name| {
+        let ident = processor_ident(name);
+        let processors = &processor_lists[*name].processors;
+        let len = processors.len();
+        let values = processors.iter().map(emit_processor);
+        quote! { static #ident: [Processor; #len] = [#(#values),*]; }
+    });
+
+    let structure_arms = structure_names.iter().map(|name| {
+        let ident = structure_ident(name);
+        let name = name.as_str();
+        quote! { #name => Some(&#ident), }
+    });
+    let set_arms = set_names.iter().map(|name| {
+        let ident = set_ident(name);
+        let name = name.as_str();
+        quote! { #name => Some(&#ident), }
+    });
+    let pool_arms = pool_names.iter().map(|name| {
+        let ident = pool_ident(name);
+        let name = name.as_str();
+        quote! { #name => Some(&#ident), }
+    });
+    let processor_arms = processor_names.iter().map(|name| {
+        let ident = processor_ident(name);
+        let name = name.as_str();
+        quote! { #name => Some(&#ident), }
+    });
+    let structure_list = structure_names.iter().map(|name| name.as_str());
+    let set_list = set_names.iter().map(|name| name.as_str());
+
+    let code = quote! {
+        // Generated structures, template pools and processor lists - do not edit manually.
+
+        use crate::GenerationStep;
+        use crate::feature::*;
+        use crate::structure::*;
+        use crate::surface::VerticalAnchor;
+
+        #(#processor_statics)*
+
+        #(#pool_statics)*
+
+        #(#structure_statics)*
+
+        #(#set_statics)*
+
+        /// Names of all jigsaw structures, sorted.
+        pub const JIGSAW_STRUCTURES: &[&str] = &[#(#structure_list),*];
+
+        /// Names of all structure sets, sorted.
+        pub const STRUCTURE_SETS: &[&str] = &[#(#set_list),*];
+
+        /// Look up a jigsaw structure by name, with or without the `minecraft:` prefix.
+        pub fn jigsaw_structure(name: &str) -> Option<&'static JigsawStructure> {
+            match name.strip_prefix("minecraft:").unwrap_or(name) {
+                #(#structure_arms)*
+                _ => None,
+            }
+        }
+
+        /// Look up a structure set by name, with or without the `minecraft:` prefix.
+        pub fn structure_set(name: &str) -> Option<&'static StructureSet> {
+            match name.strip_prefix("minecraft:").unwrap_or(name) {
+                #(#set_arms)*
+                _ => None,
+            }
+        }
+
+        /// Look up a template pool by name, with or without the `minecraft:` prefix.
+        pub fn template_pool(name: &str) -> Option<&'static TemplatePool> {
+            match name.strip_prefix("minecraft:").unwrap_or(name) {
+                #(#pool_arms)*
+                _ => None,
+            }
+        }
+
+        /// Look up a processor list by name, with or without the `minecraft:` prefix.
+        pub fn processor_list(name: &str) -> Option<&'static [Processor]> {
+            match name.strip_prefix("minecraft:").unwrap_or(name) {
+                #(#processor_arms)*
+                _ => None,
+            }
+        }
+    };
+
+    std::fs::write(output_dir.join("structures.rs"), code.to_string())?;
+
+    Ok(())
+}
+
+fn static_name(name: &str) -> String {
+    name.replace('/', "__").to_uppercase()
+}
+
+fn structure_ident(name: &str) -> proc_macro2::Ident {
+    format_ident!("JIGSAW_{}", static_name(name))
+}
+
+fn set_ident(name: &str) -> proc_macro2::Ident {
+    format_ident!("STRUCTURE_SET_{}", static_name(name))
+}
+
+fn pool_ident(name: &str) -> proc_macro2::Ident {
+    format_ident!("POOL_{}", static_name(name))
+}
+
+fn processor_ident(name: &str) -> proc_macro2::Ident {
+    format_ident!("PROCESSORS_{}", static_name(name))
+}
+
+struct StructureEmitter<'a> {
+    processor_lists: &'a HashMap<String, ProcessorListJson>,
+}
+
+impl StructureEmitter<'_> {
+    /// Emit a `TemplatePool` value.
+    fn emit_pool(&self, pool: &TemplatePoolJson) -> TokenStream {
+        let fallback = &pool.fallback;
+        let elements = pool.elements.iter().map(|entry| {
+            let element = self.emit_element(&entry.element);
+            let weight = entry.weight;
+            quote! { (#element, #weight) }
+        });
+        quote! {
+            TemplatePool {
+                elements: &[#(#elements),*],
+                fallback: #fallback,
+            }
+        }
+    }
+
+    /// Emit a `PoolElement` value.
+    fn emit_element(&self, element: &PoolElementJson) -> TokenStream {
+        match element {
+            PoolElementJson::Single {
+                location,
+                processors,
+                projection,
+            }
+            | PoolElementJson::LegacySingle {
+                location,
+                processors,
+                projection,
+            } => {
+                let legacy = matches!(element, PoolElementJson::LegacySingle { .. });
+                let processors = self.emit_processors_ref(processors);
+                let projection = emit_projection(projection);
+                quote! {
+                    PoolElement::Single {
+                        location: #location,
+                        processors: #processors,
+                        projection: #projection,
+                        legacy: #legacy,
+                    }
+                }
+            }
+            PoolElementJson::List {
+                elements,
+                projection,
+            } => {
+                let elements = elements.iter().map(|element| self.emit_element(element));
+                let projection = emit_projection(projection);
+                quote! { PoolElement::List { elements: &[#(#elements),*], projection: #projection } }
+            }
+            PoolElementJson::Feature {
+                feature,
+                projection,
+            } => {
+                let projection = emit_projection(projection);
+                quote! { PoolElement::Feature { feature: #feature, projection: #projection } }
+            }
+            PoolElementJson::Empty {} => quote! { PoolElement::Empty },
+        }
+    }
+
+    /// Emit a `&'static [Processor]`.
+    fn emit_processors_ref(&self, processors: &ProcessorsRef) -> TokenStream {
+        match processors {
+            ProcessorsRef::Named(name) => {
+                let name = strip_minecraft_prefix(name);
+                if self.processor_lists.contains_key(name) {
+                    let ident = processor_ident(name);
+                    quote! { &#ident }
+                } else {
+                    quote! { &[] }
+                }
+            }
+            ProcessorsRef::Inline(list) => {
+                let processors = list.processors.iter().map(emit_processor);
+                quote! { &[#(#processors),*] }
+            }
+        }
+    }
+}
+
+fn emit_jigsaw_structure(structure: &JigsawStructureJson) -> TokenStream {
+    let biomes = structure.biomes.clone().into_vec();
+    let step = format_ident!("{}", snake_to_camel(&structure.step));
+    let start_pool = &structure.start_pool;
+    let start_jigsaw_name = match &structure.start_jigsaw_name {
+        Some(name) => quote! { Some(#name) },
+        None => quote! { None },
+    };
+    let start_height = emit_height_provider(&structure.start_height);
+    let project_start_to_heightmap = match structure
+        .project_start_to_heightmap
+        .as_deref()
+        .and_then(emit_heightmap)
+    {
+        Some(heightmap) => quote! { Some(#heightmap) },
+        None => quote! { None },
+    };
+    let size = structure.size;
+    let max_distance_from_center = structure.max_distance_from_center;
+    let use_expansion_hack = structure.use_expansion_hack;
+    let (bottom, top) = match structure.dimension_padding {
+        DimensionPaddingJson::Uniform(padding) => (padding, padding),
+        DimensionPaddingJson::Split { bottom, top } => (bottom, top),
+    };
+    let pool_aliases = structure.pool_aliases.iter().map(emit_pool_alias);
+
+    quote! {
+        JigsawStructure {
+            biomes: &[#(#biomes),*],
+            step: GenerationStep::#step,
+            start_pool: #start_pool,
+            start_jigsaw_name: #start_jigsaw_name,
+            start_height: #start_height,
+            project_start_to_heightmap: #project_start_to_heightmap,
+            size: #size,
+            max_distance_from_center: #max_distance_from_center,
+            use_expansion_hack: #use_expansion_hack,
+            dimension_padding: (#bottom, #top),
+            pool_aliases: &[#(#pool_aliases),*],
+        }
+    }
+}
+
+fn emit_pool_alias(alias: &PoolAliasJson) -> TokenStream {
+    match alias {
+        PoolAliasJson::Direct { alias, target } => {
+            quote! { PoolAlias::Direct { alias: #alias, target: #target } }
+        }
+        PoolAliasJson::Random { alias, targets } => {
+            let targets = targets.iter().map(|entry| {
+                let target = &entry.data;
+                let weight = entry.weight;
+                quote! { (#target, #weight) }
+            });
+            quote! { PoolAlias::Random { alias: #alias, targets: &[#(#targets),*] } }
+        }
+        PoolAliasJson::RandomGroup { groups } => {
+            let groups = groups.iter().map(|entry| {
+                let aliases = entry.data.iter().map(emit_pool_alias);
+                let weight = entry.weight;
+                quote! { (&[#(#aliases),*], #weight) }
+            });
+            quote! { PoolAlias::RandomGroup { groups: &[#(#groups),*] } }
+        }
+    }
+}
+
+fn emit_structure_set(set: &StructureSetJson) -> TokenStream {
+    let structures = set.structures.iter().map(|entry| {
+        let structure = &entry.structure;
+        let weight = entry.weight;
+        quote! { (#structure, #weight) }
+    });
+    let placement = match &set.placement {
+        StructurePlacementJson::RandomSpread {
+            salt,
+            spacing,
+            separation,
+            spread_type,
+            frequency,
+            frequency_reduction_method,
+            exclusion_zone,
+        } => {
+            let spread_type = match spread_type.as_str() {
+                "triangular" => quote! { SpreadType::Triangular },
+                _ => quote! { SpreadType::Linear },
+            };
+            let method = match frequency_reduction_method.as_str() {
+                "legacy_type_1" => quote! { FrequencyReductionMethod::LegacyType1 },
+                "legacy_type_2" => quote! { FrequencyReductionMethod::LegacyType2 },
+                "legacy_type_3" => quote! { FrequencyReductionMethod::LegacyType3 },
+                _ => quote! { FrequencyReductionMethod::Default },
+            };
+            let exclusion_zone = match exclusion_zone {
+                Some(zone) => {
+                    let other_set = &zone.other_set;
+                    let chunk_count = zone.chunk_count;
+                    quote! { Some((#other_set, #chunk_count)) }
+                }
+                None => quote! { None },
+            };
+            quote! {
+                StructurePlacement::RandomSpread {
+                    salt: #salt,
+                    spacing: #spacing,
+                    separation: #separation,
+                    spread_type: #spread_type,
+                    frequency: #frequency,
+                    frequency_reduction_method: #method,
+                    exclusion_zone: #exclusion_zone,
+                }
+            }
+        }
+        StructurePlacementJson::Unsupported => quote! { StructurePlacement::Unsupported },
+    };
+
+    quote! {
+        StructureSet {
+            structures: &[#(#structures),*],
+            placement: #placement,
+        }
+    }
+}
+
+fn emit_processor(processor: &ProcessorJson) -> TokenStream {
+    match processor {
+        ProcessorJson::BlockRot {
+            integrity,
+            rottable_blocks,
+        } => {
+            let rottable_blocks = match rottable_blocks {
+                Some(tag) => quote! { Some(#tag) },
+                None => quote! { None },
+            };
+            quote! { Processor::BlockRot { integrity: #integrity, rottable_blocks: #rottable_blocks } }
+        }
+        ProcessorJson::Rule { rules } => {
+            let rules = rules.iter().map(|rule| {
+                let input = emit_rule_test(&rule.input_predicate);
+                let location = emit_rule_test(&rule.location_predicate);
+                let position = emit_pos_rule_test(rule.position_predicate.as_ref());
+                let output = emit_block_state(&rule.output_state);
+                quote! {
+                    ProcessorRule {
+                        input_predicate: #input,
+                        location_predicate: #location,
+                        position_predicate: #position,
+                        output_state: #output,
+                    }
+                }
+            });
+            quote! { Processor::Rule(&[#(#rules),*]) }
+        }
+        ProcessorJson::ProtectedBlocks { value } => quote! { Processor::ProtectedBlocks(#value) },
+        ProcessorJson::Capped { delegate, limit } => {
+            let delegate = emit_processor(delegate);
+            let limit = emit_int_provider(limit);
+            quote! { Processor::Capped { delegate: &#delegate, limit: #limit } }
+        }
+        ProcessorJson::Unsupported => quote! { Processor::Unsupported },
+    }
+}
+
+fn emit_pos_rule_test(test: Option<&PosRuleTestJson>) -> TokenStream {
+    match test {
+        Some(PosRuleTestJson::LinearPos {
+            min_chance,
+            max_chance,
+            min_dist,
+            max_dist,
+        }) => quote! {
+            PosRuleTest::LinearPos {
+                min_chance: #min_chance,
+                max_chance: #max_chance,
+                min_dist: #min_dist,
+                max_dist: #max_dist,
+            }
+        },
+        Some(PosRuleTestJson::AxisAlignedLinearPos {
+            axis,
+            min_chance,
+            max_chance,
+            min_dist,
+            max_dist,
+        }) => {
+            let axis = match axis.as_str() {
+                "x" => 0usize,
+                "z" => 2,
+                _ => 1,
+            };
+            quote! {
+                PosRuleTest::AxisAlignedLinearPos {
+                    axis: #axis,
+                    min_chance: #min_chance,
+                    max_chance: #max_chance,
+                    min_dist: #min_dist,
+                    max_dist: #max_dist,
+                }
+            }
+        }
+        Some(PosRuleTestJson::AlwaysTrue {} | PosRuleTestJson::Unsupported) | None => {
+            quote! { PosRuleTest::AlwaysTrue }
+        }
+    }
+}
+
+fn emit_projection(projection: &str) -> TokenStream {
+    match projection {
+        "terrain_matching" => quote! { Projection::TerrainMatching },
+        _ => quote! { Projection::Rigid },
+    }
+}
+
+/// `surface_structures` -> `SurfaceStructures`
+fn snake_to_camel(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -9,16 +9,37 @@ use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use std::path::Path;
 
+/// A dimension's surface rule and the builder function to emit for it.
+pub struct SurfaceRuleSet<'a> {
+    pub function: &'a str,
+    pub rule: &'a RuleSource,
+    /// Lowest and highest block Y, used to resolve vertical anchors.
+    pub min_y: i32,
+    pub max_y: i32,
+}
+
 /// Emit surface rule code to the output directory.
 pub fn emit_surface_rules(
     output_dir: &Path,
-    surface_rule: &RuleSource,
+    rule_sets: &[SurfaceRuleSet],
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut emitter = SurfaceRuleEmitter::new();
-    let rule_code = emitter.emit_rule(surface_rule);
+    let builders: Vec<TokenStream> = rule_sets
+        .iter()
+        .map(|set| {
+            let mut emitter = SurfaceRuleEmitter::new(set.min_y, set.max_y);
+            let rule_code = emitter.emit_rule(set.rule);
+            let function = format_ident!("{}", set.function);
+            quote! {
+                /// Build a surface rule from the dimension's noise settings JSON.
+                pub fn #function(seed: i64) -> Box<dyn Rule> {
+                    #rule_code
+                }
+            }
+        })
+        .collect();
 
     let code = quote! {
-        // Generated overworld surface rules - do not edit manually.
+        // Generated surface rules - do not edit manually.
 
         use crate::surface::{
             AbovePreliminarySurface, BiomeCheck, BlockRule, BandlandsRule, CaveSurface, Hole,
@@ -29,10 +50,7 @@ pub fn emit_surface_rules(
         use crate::noise::DoublePerlinNoise;
         use crate::xoroshiro::Xoroshiro128;
 
-        /// Build the vanilla overworld surface rule from JSON.
-        pub fn build_vanilla_surface_rule(seed: i64) -> Box<dyn Rule> {
-            #rule_code
-        }
+        #(#builders)*
     };
 
     std::fs::write(output_dir.join("surface_rules.rs"), code.to_string())?;
@@ -44,11 +62,19 @@ pub fn emit_surface_rules(
 struct SurfaceRuleEmitter {
     /// Counter for generating unique noise variable names.
     noise_counter: u32,
+    /// Bottom of the dimension.
+    min_y: i32,
+    /// Highest block Y of the dimension (Java's `below_top: 0`).
+    max_y: i32,
 }
 
 impl SurfaceRuleEmitter {
-    fn new() -> Self {
-        Self { noise_counter: 0 }
+    fn new(min_y: i32, max_y: i32) -> Self {
+        Self {
+            noise_counter: 0,
+            min_y,
+            max_y,
+        }
     }
 
     /// Emit code for a rule.
@@ -253,15 +279,12 @@ impl SurfaceRuleEmitter {
         }
     }
 
-    /// Resolve an anchor to a concrete Y value (using default world bounds).
+    /// Resolve an anchor to a concrete Y value within the dimension's bounds.
     fn resolve_anchor_value(&self, anchor: &VerticalAnchor) -> i32 {
-        const MIN_Y: i32 = -64;
-        const MAX_Y: i32 = 320;
-
         match anchor {
             VerticalAnchor::Absolute { absolute } => *absolute,
-            VerticalAnchor::AboveBottom { above_bottom } => MIN_Y + above_bottom,
-            VerticalAnchor::BelowTop { below_top } => MAX_Y - below_top,
+            VerticalAnchor::AboveBottom { above_bottom } => self.min_y + above_bottom,
+            VerticalAnchor::BelowTop { below_top } => self.max_y - below_top,
         }
     }
 
@@ -310,7 +333,7 @@ mod tests {
 
     #[test]
     fn test_emit_block_rule() {
-        let mut emitter = SurfaceRuleEmitter::new();
+        let mut emitter = SurfaceRuleEmitter::new(-64, 320);
         let rule = RuleSource::Block {
             result_state: crate::codegen::parser::surface_rule::BlockState {
                 name: "minecraft:grass_block".to_string(),
@@ -326,7 +349,7 @@ mod tests {
 
     #[test]
     fn test_emit_biome_check() {
-        let mut emitter = SurfaceRuleEmitter::new();
+        let mut emitter = SurfaceRuleEmitter::new(-64, 320);
         let condition = ConditionSource::Biome {
             biome_is: vec!["minecraft:desert".to_string()],
         };
@@ -339,7 +362,7 @@ mod tests {
 
     #[test]
     fn test_resolve_anchor() {
-        let emitter = SurfaceRuleEmitter::new();
+        let emitter = SurfaceRuleEmitter::new(-64, 320);
 
         assert_eq!(
             emitter.resolve_anchor_value(&VerticalAnchor::Absolute { absolute: 63 }),
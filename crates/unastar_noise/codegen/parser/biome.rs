@@ -21,7 +21,6 @@ impl Default for StringOrArray {
 }
 
 impl StringOrArray {
-    #[allow(dead_code)]
     pub fn into_vec(self) -> Vec<String> {
         match self {
             StringOrArray::Single(s) => vec![s],
@@ -40,7 +39,6 @@ pub struct BiomeJson {
 
     /// Carver references (cave, canyon, etc.) - can be a string or array in JSON.
     #[serde(default)]
-    #[allow(dead_code)]
     pub carvers: StringOrArray,
 
     /// Temperature value for the biome (0.0 - 2.0 typical range)
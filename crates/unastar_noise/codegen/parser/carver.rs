@@ -0,0 +1,143 @@
+//! Configured carver parser.
+//!
+//! Parses `configured_carver/*.json`. Debug settings are ignored.
+
+use super::biome::StringOrArray;
+use super::feature::HeightProviderJson;
+use super::surface_rule::VerticalAnchor;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A configured carver, keyed by its `type`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", content = "config")]
+pub enum ConfiguredCarverJson {
+    #[serde(rename = "minecraft:cave")]
+    Cave(CaveCarverJson),
+    #[serde(rename = "minecraft:nether_cave")]
+    NetherCave(CaveCarverJson),
+    #[serde(rename = "minecraft:canyon")]
+    Canyon(CanyonCarverJson),
+}
+
+/// Settings shared by every carver type.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CarverJson {
+    pub probability: f32,
+    pub y: HeightProviderJson,
+    #[serde(rename = "yScale")]
+    pub y_scale: FloatProviderJson,
+    pub lava_level: VerticalAnchor,
+    pub replaceable: StringOrArray,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CaveCarverJson {
+    #[serde(flatten)]
+    pub carver: CarverJson,
+    pub horizontal_radius_multiplier: FloatProviderJson,
+    pub vertical_radius_multiplier: FloatProviderJson,
+    pub floor_level: FloatProviderJson,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CanyonCarverJson {
+    #[serde(flatten)]
+    pub carver: CarverJson,
+    pub vertical_rotation: FloatProviderJson,
+    pub shape: CanyonShapeJson,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CanyonShapeJson {
+    pub distance_factor: FloatProviderJson,
+    pub thickness: FloatProviderJson,
+    pub width_smoothness: i32,
+    pub horizontal_radius_factor: FloatProviderJson,
+    pub vertical_radius_default_factor: f32,
+    pub vertical_radius_center_factor: f32,
+}
+
+/// Float provider: a constant or a typed distribution.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum FloatProviderJson {
+    Constant(f32),
+    Typed(TypedFloatProvider),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum TypedFloatProvider {
+    #[serde(rename = "minecraft:constant")]
+    Constant { value: f32 },
+    #[serde(rename = "minecraft:uniform")]
+    Uniform {
+        min_inclusive: f32,
+        max_exclusive: f32,
+    },
+    #[serde(rename = "minecraft:trapezoid")]
+    Trapezoid { min: f32, max: f32, plateau: f32 },
+}
+
+/// Parse every configured carver, keyed by file name.
+pub fn parse_all(
+    dir: &Path,
+) -> Result<HashMap<String, ConfiguredCarverJson>, Box<dyn std::error::Error>> {
+    let mut carvers = HashMap::new();
+
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry?;
+        if entry.path().extension().is_some_and(|e| e == "json") {
+            let name = entry
+                .path()
+                .file_stem()
+                .unwrap()
+                .to_string_lossy()
+                .to_string();
+            let content = std::fs::read_to_string(entry.path())?;
+            let carver: ConfiguredCarverJson = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse configured carver {}: {}", name, e))?;
+            carvers.insert(name, carver);
+        }
+    }
+
+    Ok(carvers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nether_cave() {
+        let json = r##"{
+            "type": "minecraft:nether_cave",
+            "config": {
+                "floor_level": -0.7,
+                "horizontal_radius_multiplier": 1.0,
+                "lava_level": {"above_bottom": 10},
+                "probability": 0.2,
+                "replaceable": "#minecraft:nether_carver_replaceables",
+                "vertical_radius_multiplier": 1.0,
+                "y": {
+                    "type": "minecraft:uniform",
+                    "max_inclusive": {"below_top": 1},
+                    "min_inclusive": {"absolute": 0}
+                },
+                "yScale": 0.5
+            }
+        }"##;
+        let carver: ConfiguredCarverJson = serde_json::from_str(json).unwrap();
+        let ConfiguredCarverJson::NetherCave(cave) = carver else {
+            panic!("expected a nether cave");
+        };
+        assert_eq!(cave.carver.probability, 0.2);
+        assert!(matches!(cave.floor_level, FloatProviderJson::Constant(f) if f == -0.7));
+        assert!(matches!(
+            cave.carver.lava_level,
+            VerticalAnchor::AboveBottom { above_bottom: 10 }
+        ));
+    }
+}
@@ -0,0 +1,655 @@
+//! Placed and configured feature parser.
+//!
+//! Parses `placed_feature/*.json` and `configured_feature/*.json`. Feature
+//! configs are keyed by their `type`, so configured features are read as a
+//! raw `{type, config}` pair first and the config is then deserialized into
+//! the matching struct. Feature types and placement modifiers we don't
+//! generate are kept as `Unsupported` so the rest of a file still parses.
+
+use super::biome::StringOrArray;
+use super::noise::NoiseParams;
+use super::surface_rule::{BlockState, VerticalAnchor};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A placed feature: a configured feature plus its placement modifiers.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlacedFeatureJson {
+    pub feature: ConfiguredFeatureRef,
+    #[serde(default)]
+    pub placement: Vec<PlacementModifierJson>,
+}
+
+/// Reference to a configured feature, by name or inline.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ConfiguredFeatureRef {
+    Named(String),
+    Inline(Box<ConfiguredFeatureJson>),
+}
+
+/// Reference to a placed feature, by name or inline.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum PlacedFeatureRef {
+    Named(String),
+    Inline(Box<PlacedFeatureJson>),
+}
+
+/// An entry of a weighted list.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Weighted<T> {
+    pub data: T,
+    pub weight: i32,
+}
+
+/// Integer provider: a constant or a typed distribution.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum IntProviderJson {
+    Constant(i32),
+    Typed(TypedIntProvider),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum TypedIntProvider {
+    #[serde(rename = "minecraft:constant")]
+    Constant { value: i32 },
+    #[serde(rename = "minecraft:uniform")]
+    Uniform {
+        min_inclusive: i32,
+        max_inclusive: i32,
+    },
+    #[serde(rename = "minecraft:biased_to_bottom")]
+    BiasedToBottom {
+        min_inclusive: i32,
+        max_inclusive: i32,
+    },
+    #[serde(rename = "minecraft:clamped")]
+    Clamped {
+        source: Box<IntProviderJson>,
+        min_inclusive: i32,
+        max_inclusive: i32,
+    },
+    #[serde(rename = "minecraft:weighted_list")]
+    WeightedList {
+        distribution: Vec<Weighted<IntProviderJson>>,
+    },
+    #[serde(other)]
+    Unsupported,
+}
+
+/// Height provider: a typed distribution or a bare vertical anchor.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum HeightProviderJson {
+    Typed(TypedHeightProvider),
+    Anchor(VerticalAnchor),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum TypedHeightProvider {
+    #[serde(rename = "minecraft:constant")]
+    Constant { value: VerticalAnchor },
+    #[serde(rename = "minecraft:uniform")]
+    Uniform {
+        min_inclusive: VerticalAnchor,
+        max_inclusive: VerticalAnchor,
+    },
+    #[serde(rename = "minecraft:trapezoid")]
+    Trapezoid {
+        min_inclusive: VerticalAnchor,
+        max_inclusive: VerticalAnchor,
+        #[serde(default)]
+        plateau: i32,
+    },
+    #[serde(rename = "minecraft:biased_to_bottom")]
+    BiasedToBottom {
+        min_inclusive: VerticalAnchor,
+        max_inclusive: VerticalAnchor,
+        #[serde(default = "default_inner")]
+        inner: i32,
+    },
+    #[serde(rename = "minecraft:very_biased_to_bottom")]
+    VeryBiasedToBottom {
+        min_inclusive: VerticalAnchor,
+        max_inclusive: VerticalAnchor,
+        #[serde(default = "default_inner")]
+        inner: i32,
+    },
+    #[serde(rename = "minecraft:weighted_list")]
+    WeightedList {
+        distribution: Vec<Weighted<HeightProviderJson>>,
+    },
+}
+
+fn default_inner() -> i32 {
+    1
+}
+
+/// Placement modifier, applied in order to a stream of positions.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum PlacementModifierJson {
+    #[serde(rename = "minecraft:count")]
+    Count { count: IntProviderJson },
+    #[serde(rename = "minecraft:count_on_every_layer")]
+    CountOnEveryLayer { count: IntProviderJson },
+    #[serde(rename = "minecraft:rarity_filter")]
+    RarityFilter { chance: i32 },
+    #[serde(rename = "minecraft:in_square")]
+    InSquare {},
+    #[serde(rename = "minecraft:biome")]
+    Biome {},
+    #[serde(rename = "minecraft:height_range")]
+    HeightRange { height: HeightProviderJson },
+    #[serde(rename = "minecraft:heightmap")]
+    Heightmap { heightmap: String },
+    #[serde(rename = "minecraft:noise_threshold_count")]
+    NoiseThresholdCount {
+        noise_level: f64,
+        below_noise: i32,
+        above_noise: i32,
+    },
+    #[serde(rename = "minecraft:noise_based_count")]
+    NoiseBasedCount {
+        noise_to_count_ratio: i32,
+        noise_factor: f64,
+        #[serde(default)]
+        noise_offset: f64,
+    },
+    #[serde(rename = "minecraft:surface_water_depth_filter")]
+    SurfaceWaterDepthFilter { max_water_depth: i32 },
+    #[serde(rename = "minecraft:surface_relative_threshold_filter")]
+    SurfaceRelativeThresholdFilter {
+        heightmap: String,
+        #[serde(default = "min_int")]
+        min_inclusive: i32,
+        #[serde(default = "max_int")]
+        max_inclusive: i32,
+    },
+    #[serde(rename = "minecraft:block_predicate_filter")]
+    BlockPredicateFilter { predicate: BlockPredicateJson },
+    #[serde(rename = "minecraft:random_offset")]
+    RandomOffset {
+        xz_spread: IntProviderJson,
+        y_spread: IntProviderJson,
+    },
+    #[serde(rename = "minecraft:environment_scan")]
+    EnvironmentScan {
+        direction_of_search: String,
+        max_steps: i32,
+        target_condition: BlockPredicateJson,
+        #[serde(default)]
+        allowed_search_condition: Option<BlockPredicateJson>,
+    },
+    #[serde(rename = "minecraft:fixed_placement")]
+    FixedPlacement { positions: Vec<[i32; 3]> },
+    #[serde(other)]
+    Unsupported,
+}
+
+fn min_int() -> i32 {
+    i32::MIN
+}
+
+fn max_int() -> i32 {
+    i32::MAX
+}
+
+/// Block predicate, tested at a position plus an offset.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum BlockPredicateJson {
+    #[serde(rename = "minecraft:matching_blocks")]
+    MatchingBlocks {
+        #[serde(default)]
+        offset: [i32; 3],
+        blocks: StringOrArray,
+    },
+    #[serde(rename = "minecraft:matching_block_tag")]
+    MatchingBlockTag {
+        #[serde(default)]
+        offset: [i32; 3],
+        tag: String,
+    },
+    #[serde(rename = "minecraft:matching_fluids")]
+    MatchingFluids {
+        #[serde(default)]
+        offset: [i32; 3],
+        fluids: StringOrArray,
+    },
+    #[serde(rename = "minecraft:solid")]
+    Solid {
+        #[serde(default)]
+        offset: [i32; 3],
+    },
+    #[serde(rename = "minecraft:replaceable")]
+    Replaceable {
+        #[serde(default)]
+        offset: [i32; 3],
+    },
+    #[serde(rename = "minecraft:would_survive")]
+    WouldSurvive {
+        #[serde(default)]
+        offset: [i32; 3],
+        state: BlockState,
+    },
+    #[serde(rename = "minecraft:has_sturdy_face")]
+    HasSturdyFace {
+        #[serde(default)]
+        offset: [i32; 3],
+        direction: String,
+    },
+    #[serde(rename = "minecraft:inside_world_bounds")]
+    InsideWorldBounds {
+        #[serde(default)]
+        offset: [i32; 3],
+    },
+    #[serde(rename = "minecraft:not")]
+    Not { predicate: Box<BlockPredicateJson> },
+    #[serde(rename = "minecraft:all_of")]
+    AllOf { predicates: Vec<BlockPredicateJson> },
+    #[serde(rename = "minecraft:any_of")]
+    AnyOf { predicates: Vec<BlockPredicateJson> },
+    #[serde(rename = "minecraft:true")]
+    True {},
+    #[serde(other)]
+    Unsupported,
+}
+
+/// Block state provider.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum StateProviderJson {
+    #[serde(rename = "minecraft:simple_state_provider")]
+    Simple { state: BlockState },
+    #[serde(rename = "minecraft:rotated_block_provider")]
+    Rotated { state: BlockState },
+    #[serde(rename = "minecraft:weighted_state_provider")]
+    Weighted { entries: Vec<Weighted<BlockState>> },
+    /// Only the source is kept; the randomized property is not modelled.
+    #[serde(rename = "minecraft:randomized_int_state_provider")]
+    RandomizedInt { source: Box<StateProviderJson> },
+    #[serde(rename = "minecraft:noise_threshold_provider")]
+    NoiseThreshold {
+        seed: i64,
+        noise: NoiseParams,
+        scale: f32,
+        threshold: f32,
+        high_chance: f32,
+        default_state: BlockState,
+        low_states: Vec<BlockState>,
+        high_states: Vec<BlockState>,
+    },
+    /// `noise_provider`; `dual_noise_provider` is read as its primary noise.
+    #[serde(
+        rename = "minecraft:noise_provider",
+        alias = "minecraft:dual_noise_provider"
+    )]
+    Noise {
+        seed: i64,
+        noise: NoiseParams,
+        scale: f32,
+        states: Vec<BlockState>,
+    },
+    #[serde(other)]
+    Unsupported,
+}
+
+/// Rule-based state provider used by disks.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuleBasedStateProviderJson {
+    pub fallback: StateProviderJson,
+    #[serde(default)]
+    pub rules: Vec<StateRuleJson>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StateRuleJson {
+    pub if_true: BlockPredicateJson,
+    pub then: StateProviderJson,
+}
+
+/// Ore target rule test.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "predicate_type")]
+pub enum RuleTestJson {
+    #[serde(rename = "minecraft:always_true")]
+    AlwaysTrue {},
+    #[serde(rename = "minecraft:tag_match")]
+    TagMatch { tag: String },
+    #[serde(rename = "minecraft:block_match")]
+    BlockMatch { block: String },
+    #[serde(rename = "minecraft:random_block_match")]
+    RandomBlockMatch { block: String, probability: f32 },
+    #[serde(rename = "minecraft:blockstate_match")]
+    BlockstateMatch { block_state: BlockState },
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TreeConfigJson {
+    pub trunk_provider: StateProviderJson,
+    pub foliage_provider: StateProviderJson,
+    pub dirt_provider: StateProviderJson,
+    pub trunk_placer: TrunkPlacerJson,
+    pub foliage_placer: FoliagePlacerJson,
+    #[serde(default)]
+    pub force_dirt: bool,
+    #[serde(default)]
+    pub ignore_vines: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrunkPlacerJson {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub base_height: i32,
+    pub height_rand_a: i32,
+    pub height_rand_b: i32,
+}
+
+/// Foliage placer. The placer-specific height field (`height`,
+/// `trunk_height`, `crown_height` or `foliage_height`) is read into
+/// whichever of the optional fields is present.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FoliagePlacerJson {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub radius: IntProviderJson,
+    pub offset: IntProviderJson,
+    #[serde(default)]
+    pub height: Option<IntProviderJson>,
+    #[serde(default)]
+    pub trunk_height: Option<IntProviderJson>,
+    #[serde(default)]
+    pub crown_height: Option<IntProviderJson>,
+    #[serde(default)]
+    pub foliage_height: Option<IntProviderJson>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OreConfigJson {
+    pub size: i32,
+    pub discard_chance_on_air_exposure: f32,
+    pub targets: Vec<OreTargetJson>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OreTargetJson {
+    pub target: RuleTestJson,
+    pub state: BlockState,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RandomPatchConfigJson {
+    #[serde(default = "default_tries")]
+    pub tries: i32,
+    #[serde(default = "default_xz_spread")]
+    pub xz_spread: i32,
+    #[serde(default = "default_y_spread")]
+    pub y_spread: i32,
+    pub feature: PlacedFeatureRef,
+}
+
+fn default_tries() -> i32 {
+    128
+}
+
+fn default_xz_spread() -> i32 {
+    7
+}
+
+fn default_y_spread() -> i32 {
+    3
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiskConfigJson {
+    pub state_provider: RuleBasedStateProviderJson,
+    pub target: BlockPredicateJson,
+    pub radius: IntProviderJson,
+    pub half_height: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LakeConfigJson {
+    pub fluid: StateProviderJson,
+    pub barrier: StateProviderJson,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpringConfigJson {
+    pub state: BlockState,
+    #[serde(default = "default_true")]
+    pub requires_block_below: bool,
+    #[serde(default = "default_rock_count")]
+    pub rock_count: i32,
+    #[serde(default = "default_hole_count")]
+    pub hole_count: i32,
+    pub valid_blocks: StringOrArray,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_rock_count() -> i32 {
+    4
+}
+
+fn default_hole_count() -> i32 {
+    1
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SimpleBlockConfigJson {
+    pub to_place: StateProviderJson,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RandomSelectorConfigJson {
+    pub features: Vec<WeightedFeatureJson>,
+    pub default: PlacedFeatureRef,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WeightedFeatureJson {
+    pub chance: f32,
+    pub feature: PlacedFeatureRef,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SimpleRandomSelectorConfigJson {
+    pub features: Vec<PlacedFeatureRef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RandomBooleanSelectorConfigJson {
+    pub feature_true: PlacedFeatureRef,
+    pub feature_false: PlacedFeatureRef,
+}
+
+/// Configured feature, keyed by its `type`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "RawConfiguredFeature")]
+pub enum ConfiguredFeatureJson {
+    Tree(Box<TreeConfigJson>),
+    Ore(OreConfigJson),
+    ScatteredOre(OreConfigJson),
+    /// `random_patch`, `flower` and `no_bonemeal_flower`.
+    RandomPatch(RandomPatchConfigJson),
+    Disk(DiskConfigJson),
+    Lake(Box<LakeConfigJson>),
+    Spring(SpringConfigJson),
+    SimpleBlock(SimpleBlockConfigJson),
+    RandomSelector(RandomSelectorConfigJson),
+    SimpleRandomSelector(SimpleRandomSelectorConfigJson),
+    RandomBooleanSelector(RandomBooleanSelectorConfigJson),
+    /// Any other feature type, by name.
+    Unsupported(String),
+}
+
+#[derive(Deserialize)]
+struct RawConfiguredFeature {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    config: serde_json::Value,
+}
+
+impl TryFrom<RawConfiguredFeature> for ConfiguredFeatureJson {
+    type Error = serde_json::Error;
+
+    fn try_from(raw: RawConfiguredFeature) -> Result<Self, Self::Error> {
+        let config = raw.config;
+        Ok(match raw.kind.as_str() {
+            "minecraft:tree" => Self::Tree(serde_json::from_value(config)?),
+            "minecraft:ore" => Self::Ore(serde_json::from_value(config)?),
+            "minecraft:scattered_ore" => Self::ScatteredOre(serde_json::from_value(config)?),
+            "minecraft:random_patch" | "minecraft:flower" | "minecraft:no_bonemeal_flower" => {
+                Self::RandomPatch(serde_json::from_value(config)?)
+            }
+            "minecraft:disk" => Self::Disk(serde_json::from_value(config)?),
+            "minecraft:lake" => Self::Lake(serde_json::from_value(config)?),
+            "minecraft:spring_feature" => Self::Spring(serde_json::from_value(config)?),
+            "minecraft:simple_block" => Self::SimpleBlock(serde_json::from_value(config)?),
+            "minecraft:random_selector" => Self::RandomSelector(serde_json::from_value(config)?),
+            "minecraft:simple_random_selector" => {
+                Self::SimpleRandomSelector(serde_json::from_value(config)?)
+            }
+            "minecraft:random_boolean_selector" => {
+                Self::RandomBooleanSelector(serde_json::from_value(config)?)
+            }
+            _ => Self::Unsupported(raw.kind),
+        })
+    }
+}
+
+/// Parse every placed feature, keyed by file name.
+pub fn parse_placed(
+    dir: &Path,
+) -> Result<HashMap<String, PlacedFeatureJson>, Box<dyn std::error::Error>> {
+    parse_dir(dir, "placed feature")
+}
+
+/// Parse every configured feature, keyed by file name.
+pub fn parse_configured(
+    dir: &Path,
+) -> Result<HashMap<String, ConfiguredFeatureJson>, Box<dyn std::error::Error>> {
+    parse_dir(dir, "configured feature")
+}
+
+fn parse_dir<T: DeserializeOwned>(
+    dir: &Path,
+    what: &str,
+) -> Result<HashMap<String, T>, Box<dyn std::error::Error>> {
+    let mut features = HashMap::new();
+
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry?;
+        if entry.path().extension().is_some_and(|e| e == "json") {
+            let name = entry
+                .path()
+                .file_stem()
+                .unwrap()
+                .to_string_lossy()
+                .to_string();
+            let content = std::fs::read_to_string(entry.path())?;
+            let feature: T = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse {} {}: {}", what, name, e))?;
+            features.insert(name, feature);
+        }
+    }
+
+    Ok(features)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_placed_ore() {
+        let json = r#"{
+            "feature": "minecraft:ore_iron",
+            "placement": [
+                {"type": "minecraft:count", "count": 90},
+                {"type": "minecraft:in_square"},
+                {
+                    "type": "minecraft:height_range",
+                    "height": {
+                        "type": "minecraft:trapezoid",
+                        "max_inclusive": {"absolute": 384},
+                        "min_inclusive": {"absolute": 80}
+                    }
+                },
+                {"type": "minecraft:biome"}
+            ]
+        }"#;
+
+        let placed: PlacedFeatureJson = serde_json::from_str(json).unwrap();
+        assert!(
+            matches!(placed.feature, ConfiguredFeatureRef::Named(ref n) if n == "minecraft:ore_iron")
+        );
+        assert_eq!(placed.placement.len(), 4);
+        assert!(matches!(
+            placed.placement[2],
+            PlacementModifierJson::HeightRange {
+                height: HeightProviderJson::Typed(TypedHeightProvider::Trapezoid {
+                    plateau: 0,
+                    ..
+                })
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_inline_patch() {
+        let json = r#"{
+            "type": "minecraft:random_patch",
+            "config": {
+                "feature": {
+                    "feature": {
+                        "type": "minecraft:simple_block",
+                        "config": {
+                            "to_place": {
+                                "type": "minecraft:simple_state_provider",
+                                "state": {"Name": "minecraft:short_grass"}
+                            }
+                        }
+                    },
+                    "placement": [{
+                        "type": "minecraft:block_predicate_filter",
+                        "predicate": {"type": "minecraft:matching_blocks", "blocks": "minecraft:air"}
+                    }]
+                },
+                "tries": 32,
+                "xz_spread": 7,
+                "y_spread": 3
+            }
+        }"#;
+
+        let feature: ConfiguredFeatureJson = serde_json::from_str(json).unwrap();
+        let ConfiguredFeatureJson::RandomPatch(patch) = feature else {
+            panic!("Expected random_patch");
+        };
+        assert_eq!(patch.tries, 32);
+        assert!(matches!(patch.feature, PlacedFeatureRef::Inline(_)));
+    }
+
+    #[test]
+    fn test_parse_unsupported_type() {
+        let json = r#"{"type": "minecraft:kelp", "config": {}}"#;
+        let feature: ConfiguredFeatureJson = serde_json::from_str(json).unwrap();
+        assert!(
+            matches!(feature, ConfiguredFeatureJson::Unsupported(ref t) if t == "minecraft:kelp")
+        );
+    }
+}
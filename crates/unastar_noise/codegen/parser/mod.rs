@@ -1,5 +1,25 @@
 pub mod biome;
+pub mod carver;
 pub mod density_function;
+pub mod feature;
 pub mod noise;
 pub mod noise_settings;
+pub mod structure;
 pub mod surface_rule;
+
+use std::collections::HashMap;
+
+/// Everything parsed from the worldgen JSON, by resource name.
+pub struct ParsedWorldgen {
+    pub noises: HashMap<String, noise::NoiseParams>,
+    pub density_functions: HashMap<String, density_function::DensityFunctionArg>,
+    pub noise_settings: HashMap<String, noise_settings::NoiseSettings>,
+    pub biomes: HashMap<String, biome::BiomeJson>,
+    pub placed_features: HashMap<String, feature::PlacedFeatureJson>,
+    pub configured_features: HashMap<String, feature::ConfiguredFeatureJson>,
+    pub structures: HashMap<String, structure::StructureJson>,
+    pub structure_sets: HashMap<String, structure::StructureSetJson>,
+    pub template_pools: HashMap<String, structure::TemplatePoolJson>,
+    pub processor_lists: HashMap<String, structure::ProcessorListJson>,
+    pub carvers: HashMap<String, carver::ConfiguredCarverJson>,
+}
@@ -12,11 +12,24 @@ pub struct NoiseSettings {
     pub ore_veins_enabled: bool,
     #[allow(dead_code)]
     pub sea_level: i32,
+    /// Vertical range and cell size.
+    pub noise: NoiseShape,
     pub noise_router: NoiseRouter,
     /// Surface rules parsed from JSON.
     pub surface_rule: RuleSource,
 }
 
+/// Vertical range and cell size of a dimension's terrain.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NoiseShape {
+    pub min_y: i32,
+    pub height: i32,
+    #[allow(dead_code)]
+    pub size_horizontal: i32,
+    #[allow(dead_code)]
+    pub size_vertical: i32,
+}
+
 /// Noise router containing all density function fields.
 /// preliminary_surface_level is parsed as a find_top_surface density function.
 #[derive(Debug, Clone, Deserialize)]
@@ -0,0 +1,370 @@
+//! Jigsaw structure, structure set, template pool and processor list parser.
+//!
+//! Parses `structure/*.json`, `structure_set/*.json`, `template_pool/**/*.json`
+//! and `processor_list/*.json`. Only jigsaw structures are kept; the other
+//! structure types are read as `Unsupported`. Template pools are nested in
+//! directories, so they are keyed by their path (`village/plains/houses`).
+
+use super::biome::StringOrArray;
+use super::feature::{HeightProviderJson, IntProviderJson, RuleTestJson, Weighted};
+use super::surface_rule::BlockState;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A structure, of which only jigsaw structures are generated.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum StructureJson {
+    #[serde(rename = "minecraft:jigsaw")]
+    Jigsaw(Box<JigsawStructureJson>),
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JigsawStructureJson {
+    pub biomes: StringOrArray,
+    pub step: String,
+    pub start_pool: String,
+    #[serde(default)]
+    pub start_jigsaw_name: Option<String>,
+    pub start_height: HeightProviderJson,
+    #[serde(default)]
+    pub project_start_to_heightmap: Option<String>,
+    pub size: i32,
+    pub max_distance_from_center: i32,
+    #[serde(default)]
+    pub use_expansion_hack: bool,
+    #[serde(default)]
+    pub dimension_padding: DimensionPaddingJson,
+    #[serde(default)]
+    pub pool_aliases: Vec<PoolAliasJson>,
+}
+
+/// Blocks kept free at the bottom and top of the world.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum DimensionPaddingJson {
+    Uniform(i32),
+    Split {
+        #[serde(default)]
+        bottom: i32,
+        #[serde(default)]
+        top: i32,
+    },
+}
+
+impl Default for DimensionPaddingJson {
+    fn default() -> Self {
+        DimensionPaddingJson::Uniform(0)
+    }
+}
+
+/// Pool alias binding, resolved once per structure start.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum PoolAliasJson {
+    #[serde(rename = "minecraft:direct")]
+    Direct { alias: String, target: String },
+    #[serde(rename = "minecraft:random")]
+    Random {
+        alias: String,
+        targets: Vec<Weighted<String>>,
+    },
+    #[serde(rename = "minecraft:random_group")]
+    RandomGroup {
+        groups: Vec<Weighted<Vec<PoolAliasJson>>>,
+    },
+}
+
+/// A structure set: structures sharing one placement.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StructureSetJson {
+    pub structures: Vec<StructureSetEntryJson>,
+    pub placement: StructurePlacementJson,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StructureSetEntryJson {
+    pub structure: String,
+    pub weight: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum StructurePlacementJson {
+    #[serde(rename = "minecraft:random_spread")]
+    RandomSpread {
+        salt: i32,
+        spacing: i32,
+        separation: i32,
+        #[serde(default = "default_spread_type")]
+        spread_type: String,
+        #[serde(default = "default_frequency")]
+        frequency: f32,
+        #[serde(default = "default_frequency_reduction_method")]
+        frequency_reduction_method: String,
+        #[serde(default)]
+        exclusion_zone: Option<ExclusionZoneJson>,
+    },
+    #[serde(other)]
+    Unsupported,
+}
+
+fn default_spread_type() -> String {
+    "linear".to_string()
+}
+
+fn default_frequency() -> f32 {
+    1.0
+}
+
+fn default_frequency_reduction_method() -> String {
+    "default".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExclusionZoneJson {
+    pub other_set: String,
+    pub chunk_count: i32,
+}
+
+/// A template pool: weighted pool elements plus a fallback pool.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemplatePoolJson {
+    pub elements: Vec<PoolElementEntryJson>,
+    pub fallback: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoolElementEntryJson {
+    pub element: PoolElementJson,
+    pub weight: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "element_type")]
+pub enum PoolElementJson {
+    #[serde(rename = "minecraft:single_pool_element")]
+    Single {
+        location: String,
+        processors: ProcessorsRef,
+        projection: String,
+    },
+    #[serde(rename = "minecraft:legacy_single_pool_element")]
+    LegacySingle {
+        location: String,
+        processors: ProcessorsRef,
+        projection: String,
+    },
+    #[serde(rename = "minecraft:list_pool_element")]
+    List {
+        elements: Vec<PoolElementJson>,
+        projection: String,
+    },
+    #[serde(rename = "minecraft:feature_pool_element")]
+    Feature { feature: String, projection: String },
+    #[serde(rename = "minecraft:empty_pool_element")]
+    Empty {},
+}
+
+/// Reference to a processor list, by name or inline.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ProcessorsRef {
+    Named(String),
+    Inline(ProcessorListJson),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProcessorListJson {
+    pub processors: Vec<ProcessorJson>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "processor_type")]
+pub enum ProcessorJson {
+    #[serde(rename = "minecraft:block_rot")]
+    BlockRot {
+        integrity: f32,
+        #[serde(default)]
+        rottable_blocks: Option<String>,
+    },
+    #[serde(rename = "minecraft:rule")]
+    Rule { rules: Vec<ProcessorRuleJson> },
+    #[serde(rename = "minecraft:protected_blocks")]
+    ProtectedBlocks { value: String },
+    #[serde(rename = "minecraft:capped")]
+    Capped {
+        delegate: Box<ProcessorJson>,
+        limit: IntProviderJson,
+    },
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProcessorRuleJson {
+    pub input_predicate: RuleTestJson,
+    pub location_predicate: RuleTestJson,
+    #[serde(default)]
+    pub position_predicate: Option<PosRuleTestJson>,
+    pub output_state: BlockState,
+}
+
+/// Position rule test, comparing a block to the piece's position.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "predicate_type")]
+pub enum PosRuleTestJson {
+    #[serde(rename = "minecraft:always_true")]
+    AlwaysTrue {},
+    #[serde(rename = "minecraft:linear_pos")]
+    LinearPos {
+        #[serde(default)]
+        min_chance: f32,
+        #[serde(default)]
+        max_chance: f32,
+        #[serde(default)]
+        min_dist: i32,
+        #[serde(default)]
+        max_dist: i32,
+    },
+    #[serde(rename = "minecraft:axis_aligned_linear_pos")]
+    AxisAlignedLinearPos {
+        #[serde(default = "default_axis")]
+        axis: String,
+        #[serde(default)]
+        min_chance: f32,
+        #[serde(default)]
+        max_chance: f32,
+        #[serde(default)]
+        min_dist: i32,
+        #[serde(default)]
+        max_dist: i32,
+    },
+    #[serde(other)]
+    Unsupported,
+}
+
+fn default_axis() -> String {
+    "y".to_string()
+}
+
+/// Parse every structure, keyed by file name.
+pub fn parse_structures(
+    dir: &Path,
+) -> Result<HashMap<String, StructureJson>, Box<dyn std::error::Error>> {
+    parse_dir(dir, "structure")
+}
+
+/// Parse every structure set, keyed by file name.
+pub fn parse_structure_sets(
+    dir: &Path,
+) -> Result<HashMap<String, StructureSetJson>, Box<dyn std::error::Error>> {
+    parse_dir(dir, "structure set")
+}
+
+/// Parse every template pool, keyed by its path below `dir`.
+pub fn parse_template_pools(
+    dir: &Path,
+) -> Result<HashMap<String, TemplatePoolJson>, Box<dyn std::error::Error>> {
+    parse_dir(dir, "template pool")
+}
+
+/// Parse every processor list, keyed by file name.
+pub fn parse_processor_lists(
+    dir: &Path,
+) -> Result<HashMap<String, ProcessorListJson>, Box<dyn std::error::Error>> {
+    parse_dir(dir, "processor list")
+}
+
+/// Parse the JSON files below `dir`, keyed by their path without extension.
+fn parse_dir<T: DeserializeOwned>(
+    dir: &Path,
+    what: &str,
+) -> Result<HashMap<String, T>, Box<dyn std::error::Error>> {
+    let mut values = HashMap::new();
+
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry?;
+        if entry.path().extension().is_some_and(|e| e == "json") {
+            let name = entry
+                .path()
+                .strip_prefix(dir)?
+                .with_extension("")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let content = std::fs::read_to_string(entry.path())?;
+            let value: T = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse {} {}: {}", what, name, e))?;
+            values.insert(name, value);
+        }
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_non_jigsaw_structure() {
+        let json = r##"{"type": "minecraft:igloo", "biomes": "#minecraft:has_structure/igloo"}"##;
+        let structure: StructureJson = serde_json::from_str(json).unwrap();
+        assert!(matches!(structure, StructureJson::Unsupported));
+    }
+
+    #[test]
+    fn test_parse_pool_elements() {
+        let json = r#"{
+            "elements": [
+                {
+                    "element": {
+                        "element_type": "minecraft:legacy_single_pool_element",
+                        "location": "minecraft:village/plains/streets/corner_01",
+                        "processors": "minecraft:street_plains",
+                        "projection": "terrain_matching"
+                    },
+                    "weight": 2
+                },
+                {
+                    "element": {
+                        "element_type": "minecraft:single_pool_element",
+                        "location": "minecraft:pillager_outpost/watchtower",
+                        "processors": {"processors": []},
+                        "projection": "rigid"
+                    },
+                    "weight": 1
+                },
+                {"element": {"element_type": "minecraft:empty_pool_element"}, "weight": 6}
+            ],
+            "fallback": "minecraft:empty"
+        }"#;
+
+        let pool: TemplatePoolJson = serde_json::from_str(json).unwrap();
+        assert_eq!(pool.elements.len(), 3);
+        assert!(matches!(
+            pool.elements[0].element,
+            PoolElementJson::LegacySingle {
+                processors: ProcessorsRef::Named(_),
+                ..
+            }
+        ));
+        assert!(matches!(
+            pool.elements[1].element,
+            PoolElementJson::Single {
+                processors: ProcessorsRef::Inline(_),
+                ..
+            }
+        ));
+        assert!(matches!(
+            pool.elements[2].element,
+            PoolElementJson::Empty {}
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+//! Configured carvers.
+//!
+//! These are the runtime forms of the `configured_carver` worldgen JSON,
+//! looked up with [`configured_carver`](crate::configured_carver). The
+//! carvers of a biome are listed by
+//! [`BiomeFeatures::carvers`](crate::BiomeFeatures::carvers). Carving needs
+//! the chunk and its aquifer, so that part lives with the world generator.
+
+use crate::feature::HeightProvider;
+use crate::surface::VerticalAnchor;
+use crate::xoroshiro::JavaRandom;
+
+/// Float provider.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FloatProvider {
+    Constant(f32),
+    Uniform {
+        min_inclusive: f32,
+        max_exclusive: f32,
+    },
+    Trapezoid {
+        min: f32,
+        max: f32,
+        plateau: f32,
+    },
+}
+
+impl FloatProvider {
+    /// Sample a value.
+    pub fn sample(&self, random: &mut JavaRandom) -> f32 {
+        match *self {
+            FloatProvider::Constant(value) => value,
+            FloatProvider::Uniform {
+                min_inclusive,
+                max_exclusive,
+            } => random.next_float() * (max_exclusive - min_inclusive) + min_inclusive,
+            FloatProvider::Trapezoid { min, max, plateau } => {
+                let range = max - min;
+                let slope = (range - plateau) / 2.0;
+                let top = range - slope;
+                min + random.next_float() * top + random.next_float() * slope
+            }
+        }
+    }
+}
+
+/// A configured carver.
+#[derive(Debug, Clone, Copy)]
+pub struct ConfiguredCarver {
+    /// Chance of a chunk starting the carver.
+    pub probability: f32,
+    /// Height the carving starts at.
+    pub y: HeightProvider,
+    pub y_scale: FloatProvider,
+    /// Carved blocks at or below this level become lava.
+    pub lava_level: VerticalAnchor,
+    /// Blocks and block tags (`#`-prefixed) the carver may remove.
+    pub replaceable: &'static [&'static str],
+    pub kind: CarverKind,
+}
+
+/// Carver type and its type-specific settings.
+#[derive(Debug, Clone, Copy)]
+pub enum CarverKind {
+    Cave(CaveSettings),
+    /// Nether caves: wider tunnels, carved to cave air and lava only.
+    NetherCave(CaveSettings),
+    Canyon {
+        vertical_rotation: FloatProvider,
+        shape: CanyonShape,
+    },
+}
+
+/// Settings of cave and nether cave carvers.
+#[derive(Debug, Clone, Copy)]
+pub struct CaveSettings {
+    pub horizontal_radius_multiplier: FloatProvider,
+    pub vertical_radius_multiplier: FloatProvider,
+    /// Relative height below which a tunnel keeps its floor.
+    pub floor_level: FloatProvider,
+}
+
+/// Shape of a canyon.
+#[derive(Debug, Clone, Copy)]
+pub struct CanyonShape {
+    /// Length of the canyon, as a factor of the carving range.
+    pub distance_factor: FloatProvider,
+    pub thickness: FloatProvider,
+    /// Chance (one in this) of the width changing at each layer.
+    pub width_smoothness: i32,
+    pub horizontal_radius_factor: FloatProvider,
+    pub vertical_radius_default_factor: f32,
+    pub vertical_radius_center_factor: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_float_provider_ranges() {
+        let mut random = JavaRandom::from_seed(7);
+        let uniform = FloatProvider::Uniform {
+            min_inclusive: 0.75,
+            max_exclusive: 1.0,
+        };
+        let trapezoid = FloatProvider::Trapezoid {
+            min: 0.0,
+            max: 6.0,
+            plateau: 2.0,
+        };
+        for _ in 0..100 {
+            assert!((0.75..1.0).contains(&uniform.sample(&mut random)));
+            assert!((0.0..6.0).contains(&trapezoid.sample(&mut random)));
+        }
+        assert_eq!(FloatProvider::Constant(3.0).sample(&mut random), 3.0);
+    }
+
+    #[test]
+    fn test_generated_carvers() {
+        let cave = crate::configured_carver("minecraft:cave").unwrap();
+        assert_eq!(cave.probability, 0.15);
+        assert_eq!(cave.lava_level, VerticalAnchor::AboveBottom(8));
+        assert_eq!(
+            cave.replaceable,
+            &["#minecraft:overworld_carver_replaceables"]
+        );
+        assert!(matches!(cave.kind, CarverKind::Cave(_)));
+
+        let canyon = crate::configured_carver("canyon").unwrap();
+        assert!(matches!(
+            canyon.kind,
+            CarverKind::Canyon {
+                shape: CanyonShape {
+                    width_smoothness: 3,
+                    ..
+                },
+                ..
+            }
+        ));
+        assert_eq!(canyon.y_scale, FloatProvider::Constant(3.0));
+
+        let nether = crate::configured_carver("nether_cave").unwrap();
+        assert!(matches!(
+            nether.kind,
+            CarverKind::NetherCave(CaveSettings {
+                floor_level: FloatProvider::Constant(-0.7),
+                ..
+            })
+        ));
+
+        assert_eq!(
+            crate::BiomeFeatures::Plains.carvers(),
+            &["cave", "cave_extra_underground", "canyon"]
+        );
+        assert_eq!(
+            crate::BiomeFeatures::NetherWastes.carvers(),
+            &["nether_cave"]
+        );
+        assert!(crate::configured_carver("missing").is_none());
+    }
+}
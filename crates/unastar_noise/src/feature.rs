@@ -0,0 +1,594 @@
+//! Placed and configured features.
+//!
+//! These are the runtime forms of the `placed_feature` and
+//! `configured_feature` worldgen JSON. The build script emits every feature
+//! as a static, looked up with [`placed_feature`](crate::placed_feature) and
+//! [`configured_feature`](crate::configured_feature). Placing a feature needs
+//! block access, so that part lives with the world generator.
+//!
+//! Feature types and placement modifiers that aren't generated are kept as
+//! `Unsupported` and place nothing.
+
+use crate::surface::VerticalAnchor;
+use crate::xoroshiro::JavaRandom;
+
+/// Java's `Mth.randomBetweenInclusive`.
+#[inline]
+pub fn random_between_inclusive(random: &mut JavaRandom, min: i32, max: i32) -> i32 {
+    random.next_int((max - min + 1) as u32) + min
+}
+
+/// Pick an entry of a weighted list (Java's `WeightedRandom.getRandomItem`).
+pub fn pick_weighted<'a, T>(random: &mut JavaRandom, entries: &'a [(T, i32)]) -> Option<&'a T> {
+    let total: i32 = entries.iter().map(|(_, weight)| weight).sum();
+    if total <= 0 {
+        return None;
+    }
+
+    let mut index = random.next_int(total as u32);
+    for (entry, weight) in entries {
+        index -= weight;
+        if index < 0 {
+            return Some(entry);
+        }
+    }
+    None
+}
+
+/// A block state from the JSON: block name plus properties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockState {
+    /// Block name, e.g. `minecraft:oak_log`.
+    pub name: &'static str,
+    /// Block state properties, sorted by key.
+    pub properties: &'static [(&'static str, &'static str)],
+}
+
+/// Integer provider.
+#[derive(Debug, Clone, Copy)]
+pub enum IntProvider {
+    Constant(i32),
+    Uniform {
+        min_inclusive: i32,
+        max_inclusive: i32,
+    },
+    BiasedToBottom {
+        min_inclusive: i32,
+        max_inclusive: i32,
+    },
+    Clamped {
+        source: &'static IntProvider,
+        min_inclusive: i32,
+        max_inclusive: i32,
+    },
+    WeightedList(&'static [(IntProvider, i32)]),
+}
+
+impl IntProvider {
+    /// Sample a value.
+    pub fn sample(&self, random: &mut JavaRandom) -> i32 {
+        match *self {
+            IntProvider::Constant(value) => value,
+            IntProvider::Uniform {
+                min_inclusive,
+                max_inclusive,
+            } => random_between_inclusive(random, min_inclusive, max_inclusive),
+            IntProvider::BiasedToBottom {
+                min_inclusive,
+                max_inclusive,
+            } => {
+                let range = random.next_int((max_inclusive - min_inclusive + 1) as u32);
+                min_inclusive + random.next_int((range + 1) as u32)
+            }
+            IntProvider::Clamped {
+                source,
+                min_inclusive,
+                max_inclusive,
+            } => source.sample(random).clamp(min_inclusive, max_inclusive),
+            IntProvider::WeightedList(entries) => {
+                pick_weighted(random, entries).map_or(0, |provider| provider.sample(random))
+            }
+        }
+    }
+}
+
+/// Height provider, sampling a Y coordinate between two anchors.
+#[derive(Debug, Clone, Copy)]
+pub enum HeightProvider {
+    Constant(VerticalAnchor),
+    Uniform {
+        min_inclusive: VerticalAnchor,
+        max_inclusive: VerticalAnchor,
+    },
+    Trapezoid {
+        min_inclusive: VerticalAnchor,
+        max_inclusive: VerticalAnchor,
+        plateau: i32,
+    },
+    BiasedToBottom {
+        min_inclusive: VerticalAnchor,
+        max_inclusive: VerticalAnchor,
+        inner: i32,
+    },
+    VeryBiasedToBottom {
+        min_inclusive: VerticalAnchor,
+        max_inclusive: VerticalAnchor,
+        inner: i32,
+    },
+    WeightedList(&'static [(HeightProvider, i32)]),
+}
+
+impl HeightProvider {
+    /// Sample a Y coordinate in a dimension spanning `min_y..=max_y`.
+    pub fn sample(&self, random: &mut JavaRandom, min_y: i32, max_y: i32) -> i32 {
+        match *self {
+            HeightProvider::Constant(anchor) => anchor.resolve(min_y, max_y),
+            HeightProvider::Uniform {
+                min_inclusive,
+                max_inclusive,
+            } => {
+                let (min, max) = (
+                    min_inclusive.resolve(min_y, max_y),
+                    max_inclusive.resolve(min_y, max_y),
+                );
+                if min > max {
+                    return min;
+                }
+                random_between_inclusive(random, min, max)
+            }
+            HeightProvider::Trapezoid {
+                min_inclusive,
+                max_inclusive,
+                plateau,
+            } => {
+                let (min, max) = (
+                    min_inclusive.resolve(min_y, max_y),
+                    max_inclusive.resolve(min_y, max_y),
+                );
+                if min > max {
+                    return min;
+                }
+                let range = max - min;
+                if plateau >= range {
+                    return random_between_inclusive(random, min, max);
+                }
+                let slope = (range - plateau) / 2;
+                let top = range - slope;
+                min + random_between_inclusive(random, 0, top)
+                    + random_between_inclusive(random, 0, slope)
+            }
+            HeightProvider::BiasedToBottom {
+                min_inclusive,
+                max_inclusive,
+                inner,
+            } => {
+                let (min, max) = (
+                    min_inclusive.resolve(min_y, max_y),
+                    max_inclusive.resolve(min_y, max_y),
+                );
+                if max - min - inner + 1 <= 0 {
+                    return min;
+                }
+                let upper = random.next_int((max - min - inner + 1) as u32);
+                random.next_int((upper + inner) as u32) + min
+            }
+            HeightProvider::VeryBiasedToBottom {
+                min_inclusive,
+                max_inclusive,
+                inner,
+            } => {
+                let (min, max) = (
+                    min_inclusive.resolve(min_y, max_y),
+                    max_inclusive.resolve(min_y, max_y),
+                );
+                if max - min - inner + 1 <= 0 {
+                    return min;
+                }
+                let upper = random_between_inclusive(random, min + inner, max);
+                let middle = random_between_inclusive(random, min, upper - 1);
+                random_between_inclusive(random, min, middle - 1 + inner)
+            }
+            HeightProvider::WeightedList(entries) => pick_weighted(random, entries)
+                .map_or(min_y, |provider| provider.sample(random, min_y, max_y)),
+        }
+    }
+}
+
+/// Heightmap types used by placement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heightmap {
+    WorldSurfaceWg,
+    WorldSurface,
+    OceanFloorWg,
+    OceanFloor,
+    MotionBlocking,
+    MotionBlockingNoLeaves,
+}
+
+/// A block face or search direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Down,
+    Up,
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Direction {
+    /// Unit offset of the direction.
+    pub fn offset(self) -> [i32; 3] {
+        match self {
+            Direction::Down => [0, -1, 0],
+            Direction::Up => [0, 1, 0],
+            Direction::North => [0, 0, -1],
+            Direction::South => [0, 0, 1],
+            Direction::West => [-1, 0, 0],
+            Direction::East => [1, 0, 0],
+        }
+    }
+}
+
+/// Block predicate, tested at a position plus `offset`.
+///
+/// Block lists may contain tags, written with a leading `#`.
+#[derive(Debug, Clone, Copy)]
+pub enum BlockPredicate {
+    True,
+    Not(&'static BlockPredicate),
+    AllOf(&'static [BlockPredicate]),
+    AnyOf(&'static [BlockPredicate]),
+    MatchingBlocks {
+        offset: [i32; 3],
+        blocks: &'static [&'static str],
+    },
+    MatchingBlockTag {
+        offset: [i32; 3],
+        tag: &'static str,
+    },
+    MatchingFluids {
+        offset: [i32; 3],
+        fluids: &'static [&'static str],
+    },
+    Solid {
+        offset: [i32; 3],
+    },
+    Replaceable {
+        offset: [i32; 3],
+    },
+    WouldSurvive {
+        offset: [i32; 3],
+        state: BlockState,
+    },
+    HasSturdyFace {
+        offset: [i32; 3],
+        direction: Direction,
+    },
+    InsideWorldBounds {
+        offset: [i32; 3],
+    },
+    Unsupported,
+}
+
+/// Placement modifier, applied in order to a stream of positions.
+#[derive(Debug, Clone, Copy)]
+pub enum PlacementModifier {
+    /// Repeat each position `count` times.
+    Count(IntProvider),
+    /// Java's `count_on_every_layer`: one position per floor, per layer down.
+    CountOnEveryLayer(IntProvider),
+    /// Keep a position with probability `1 / chance`.
+    RarityFilter {
+        chance: i32,
+    },
+    /// Spread the position over the chunk.
+    InSquare,
+    /// Keep positions whose biome lists this feature.
+    Biome,
+    HeightRange(HeightProvider),
+    Heightmap(Heightmap),
+    NoiseThresholdCount {
+        noise_level: f64,
+        below_noise: i32,
+        above_noise: i32,
+    },
+    NoiseBasedCount {
+        noise_to_count_ratio: i32,
+        noise_factor: f64,
+        noise_offset: f64,
+    },
+    SurfaceWaterDepthFilter {
+        max_water_depth: i32,
+    },
+    SurfaceRelativeThresholdFilter {
+        heightmap: Heightmap,
+        min_inclusive: i32,
+        max_inclusive: i32,
+    },
+    BlockPredicateFilter(BlockPredicate),
+    RandomOffset {
+        xz_spread: IntProvider,
+        y_spread: IntProvider,
+    },
+    EnvironmentScan {
+        direction_of_search: Direction,
+        max_steps: i32,
+        target_condition: BlockPredicate,
+        allowed_search_condition: Option<BlockPredicate>,
+    },
+    FixedPlacement(&'static [[i32; 3]]),
+    Unsupported,
+}
+
+/// Noise parameters of a noise-based state provider.
+#[derive(Debug, Clone, Copy)]
+pub struct NoiseParameters {
+    pub first_octave: i32,
+    pub amplitudes: &'static [f64],
+}
+
+/// Block state provider.
+#[derive(Debug, Clone, Copy)]
+pub enum BlockStateProvider {
+    Simple(BlockState),
+    Rotated(BlockState),
+    Weighted(&'static [(BlockState, i32)]),
+    /// Randomized integer property; only the source state is used.
+    RandomizedInt(&'static BlockStateProvider),
+    NoiseThreshold {
+        seed: i64,
+        noise: NoiseParameters,
+        scale: f32,
+        threshold: f32,
+        high_chance: f32,
+        default_state: BlockState,
+        low_states: &'static [BlockState],
+        high_states: &'static [BlockState],
+    },
+    Noise {
+        seed: i64,
+        noise: NoiseParameters,
+        scale: f32,
+        states: &'static [BlockState],
+    },
+    Unsupported,
+}
+
+/// Rule-based state provider used by disks.
+#[derive(Debug, Clone, Copy)]
+pub struct RuleBasedStateProvider {
+    pub fallback: BlockStateProvider,
+    pub rules: &'static [(BlockPredicate, BlockStateProvider)],
+}
+
+/// Rule test deciding which blocks an ore replaces.
+#[derive(Debug, Clone, Copy)]
+pub enum RuleTest {
+    AlwaysTrue,
+    TagMatch(&'static str),
+    BlockMatch(&'static str),
+    RandomBlockMatch {
+        block: &'static str,
+        probability: f32,
+    },
+    Unsupported,
+}
+
+/// Trunk placer types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrunkPlacerKind {
+    Straight,
+    Forking,
+    Giant,
+    MegaJungle,
+    DarkOak,
+    Fancy,
+    Bending,
+    UpwardsBranching,
+    Cherry,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TrunkPlacer {
+    pub kind: TrunkPlacerKind,
+    pub base_height: i32,
+    pub height_rand_a: i32,
+    pub height_rand_b: i32,
+}
+
+impl TrunkPlacer {
+    /// Java's `TrunkPlacer.getTreeHeight`.
+    pub fn tree_height(&self, random: &mut JavaRandom) -> i32 {
+        self.base_height
+            + random.next_int((self.height_rand_a + 1) as u32)
+            + random.next_int((self.height_rand_b + 1) as u32)
+    }
+}
+
+/// Foliage placer types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoliagePlacerKind {
+    Blob,
+    Bush,
+    Fancy,
+    Jungle,
+    Spruce,
+    Pine,
+    MegaPine,
+    Acacia,
+    DarkOak,
+    RandomSpread,
+    Cherry,
+}
+
+/// Foliage placer. `height` holds whichever placer-specific height the
+/// JSON gives (`height`, `trunk_height`, `crown_height`, `foliage_height`).
+#[derive(Debug, Clone, Copy)]
+pub struct FoliagePlacer {
+    pub kind: FoliagePlacerKind,
+    pub radius: IntProvider,
+    pub offset: IntProvider,
+    pub height: IntProvider,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TreeConfig {
+    pub trunk_provider: BlockStateProvider,
+    pub foliage_provider: BlockStateProvider,
+    pub dirt_provider: BlockStateProvider,
+    pub trunk_placer: TrunkPlacer,
+    pub foliage_placer: FoliagePlacer,
+    pub force_dirt: bool,
+    pub ignore_vines: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OreTarget {
+    pub target: RuleTest,
+    pub state: BlockState,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OreConfig {
+    pub size: i32,
+    pub discard_chance_on_air_exposure: f32,
+    pub targets: &'static [OreTarget],
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RandomPatchConfig {
+    pub tries: i32,
+    pub xz_spread: i32,
+    pub y_spread: i32,
+    pub feature: &'static PlacedFeature,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DiskConfig {
+    pub state_provider: RuleBasedStateProvider,
+    pub target: BlockPredicate,
+    pub radius: IntProvider,
+    pub half_height: i32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LakeConfig {
+    pub fluid: BlockStateProvider,
+    pub barrier: BlockStateProvider,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpringConfig {
+    pub state: BlockState,
+    pub requires_block_below: bool,
+    pub rock_count: i32,
+    pub hole_count: i32,
+    pub valid_blocks: &'static [&'static str],
+}
+
+/// A configured feature: a feature type and its config.
+#[derive(Debug, Clone, Copy)]
+pub enum ConfiguredFeature {
+    Tree(&'static TreeConfig),
+    Ore(OreConfig),
+    ScatteredOre(OreConfig),
+    /// `random_patch`, `flower` and `no_bonemeal_flower`.
+    RandomPatch(RandomPatchConfig),
+    Disk(DiskConfig),
+    Lake(&'static LakeConfig),
+    Spring(SpringConfig),
+    SimpleBlock {
+        to_place: BlockStateProvider,
+    },
+    RandomSelector {
+        features: &'static [(f32, &'static PlacedFeature)],
+        default: &'static PlacedFeature,
+    },
+    SimpleRandomSelector(&'static [&'static PlacedFeature]),
+    RandomBooleanSelector {
+        feature_true: &'static PlacedFeature,
+        feature_false: &'static PlacedFeature,
+    },
+    /// A feature type that isn't generated, by name.
+    Unsupported(&'static str),
+}
+
+/// A placed feature: a configured feature and where to place it.
+#[derive(Debug, Clone, Copy)]
+pub struct PlacedFeature {
+    pub feature: &'static ConfiguredFeature,
+    pub placement: &'static [PlacementModifier],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_int_provider_ranges() {
+        let mut random = JavaRandom::from_seed(42);
+        let uniform = IntProvider::Uniform {
+            min_inclusive: 2,
+            max_inclusive: 6,
+        };
+        let biased = IntProvider::BiasedToBottom {
+            min_inclusive: 1,
+            max_inclusive: 3,
+        };
+        for _ in 0..1000 {
+            assert!((2..=6).contains(&uniform.sample(&mut random)));
+            assert!((1..=3).contains(&biased.sample(&mut random)));
+        }
+
+        let weighted = IntProvider::WeightedList(&[
+            (IntProvider::Constant(0), 19),
+            (IntProvider::Constant(1), 1),
+        ]);
+        let ones = (0..2000)
+            .filter(|_| weighted.sample(&mut random) == 1)
+            .count();
+        assert!(ones > 50 && ones < 150, "{ones}");
+    }
+
+    #[test]
+    fn test_height_provider_ranges() {
+        let mut random = JavaRandom::from_seed(7);
+        let trapezoid = HeightProvider::Trapezoid {
+            min_inclusive: VerticalAnchor::Absolute(-24),
+            max_inclusive: VerticalAnchor::Absolute(56),
+            plateau: 0,
+        };
+        let uniform = HeightProvider::Uniform {
+            min_inclusive: VerticalAnchor::AboveBottom(0),
+            max_inclusive: VerticalAnchor::BelowTop(0),
+        };
+        for _ in 0..1000 {
+            assert!((-24..=56).contains(&trapezoid.sample(&mut random, -64, 319)));
+            assert!((-64..=319).contains(&uniform.sample(&mut random, -64, 319)));
+        }
+    }
+
+    #[test]
+    fn test_generated_features() {
+        let ore = crate::placed_feature("ore_iron_upper").unwrap();
+        assert!(matches!(ore.feature, ConfiguredFeature::Ore(config) if config.size == 9));
+        assert!(matches!(
+            ore.placement[0],
+            PlacementModifier::Count(IntProvider::Constant(90))
+        ));
+
+        let trees = crate::placed_feature("minecraft:trees_plains").unwrap();
+        assert!(matches!(
+            trees.feature,
+            ConfiguredFeature::RandomSelector { .. }
+        ));
+        assert!(crate::configured_feature("oak").is_some());
+        assert!(crate::placed_feature("not_a_feature").is_none());
+
+        // Every feature a biome references resolves
+        for name in crate::ALL_PLACED_FEATURES {
+            assert!(crate::placed_feature(name).is_some(), "{name}");
+        }
+    }
+}
@@ -33,7 +33,9 @@ pub use types::*;
 pub mod noise;
 pub mod xoroshiro;
 
-pub use noise::{BlendedNoise, DoublePerlinNoise, OctaveNoise, PerlinNoise, SimplexNoise};
+pub use noise::{
+    BlendedNoise, DoublePerlinNoise, EndIslandNoise, OctaveNoise, PerlinNoise, SimplexNoise,
+};
 pub use xoroshiro::{get_seed, JavaRandom, PositionalRandomFactory, Xoroshiro128};
 
 // Biome enum
@@ -55,5 +57,17 @@ pub use surface::{
     BandlandsRule, BlockIdRule, BlockRule, Rule, SequenceRule, TestRule,
 };
 
+// Placed and configured features
+pub mod feature;
+pub use feature::{ConfiguredFeature, PlacedFeature, PlacementModifier};
+
+// Jigsaw structures, structure sets, template pools and processor lists
+pub mod structure;
+pub use structure::{JigsawStructure, PoolElement, StructureSet, TemplatePool};
+
+// Configured carvers
+pub mod carver;
+pub use carver::{CarverKind, ConfiguredCarver, FloatProvider};
+
 // Include generated code from OUT_DIR
 include!(concat!(env!("OUT_DIR"), "/mod.rs"));
@@ -1394,6 +1394,65 @@ impl SimplexNoise {
     }
 }
 
+// =============================================================================
+// End Islands
+// =============================================================================
+
+/// The End's island height field, matching Java's `EndIslandDensityFunction`.
+///
+/// Produces the central island around the origin and, beyond 1024 blocks,
+/// the scattered outer islands placed where the simplex noise dips below -0.9.
+#[derive(Debug, Clone)]
+pub struct EndIslandNoise {
+    noise: SimplexNoise,
+}
+
+impl EndIslandNoise {
+    /// Create the island noise for a world seed.
+    pub fn new(seed: i64) -> Self {
+        // Java seeds a legacy random and skips 17292 values; the salt keeps
+        // this noise independent of the registry noises.
+        let mut rng = Xoroshiro128::from_seed(seed.wrapping_add(17292));
+        Self {
+            noise: SimplexNoise::new(&mut rng),
+        }
+    }
+
+    /// Sample the density at a block column, in the range [-0.84375, 0.5625].
+    pub fn sample(&self, block_x: i32, block_z: i32) -> f64 {
+        (self.height_value(block_x / 8, block_z / 8) as f64 - 8.0) / 128.0
+    }
+
+    /// Java's `getHeightValue`: island height at a 8-block cell coordinate.
+    fn height_value(&self, x: i32, z: i32) -> f32 {
+        let i = x / 2;
+        let j = z / 2;
+        let k = x % 2;
+        let l = z % 2;
+
+        let dist = ((x as i64 * x as i64 + z as i64 * z as i64) as f32).sqrt();
+        let mut height = (100.0 - dist * 8.0).clamp(-100.0, 80.0);
+
+        for m in -12..=12 {
+            for n in -12..=12 {
+                let o = (i + m) as i64;
+                let p = (j + n) as i64;
+                if o * o + p * p > 4096
+                    && self.noise.get_value_2d(o as f64, p as f64) < -0.9_f32 as f64
+                {
+                    let size = ((o as f32).abs() * 3439.0 + (p as f32).abs() * 147.0) % 13.0 + 9.0;
+                    let h = (k - m * 2) as f32;
+                    let q = (l - n * 2) as f32;
+                    let island = (100.0 - (h * h + q * q).sqrt() * size).clamp(-100.0, 80.0);
+                    height = height.max(island);
+                }
+            }
+        }
+
+        height
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -1449,6 +1508,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_end_island_noise() {
+        let islands = EndIslandNoise::new(12345);
+
+        // The central island is solid at the origin and gone well before the void ring
+        assert!(islands.sample(0, 0) > 0.0);
+        assert!(islands.sample(600, 0) < 0.0);
+
+        for x in (-2000..2000).step_by(97) {
+            let value = islands.sample(x, x / 2);
+            assert!(
+                (-0.84375..=0.5625).contains(&value),
+                "End island value {} out of range",
+                value
+            );
+        }
+    }
+
     #[test]
     fn test_perlin_noise_deterministic() {
         // Test that noise is deterministic with same seed
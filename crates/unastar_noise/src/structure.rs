@@ -0,0 +1,260 @@
+//! Jigsaw structures, structure sets, template pools and processor lists.
+//!
+//! These are the runtime forms of the `structure`, `structure_set`,
+//! `template_pool` and `processor_list` worldgen JSON, looked up with
+//! [`jigsaw_structure`](crate::jigsaw_structure),
+//! [`structure_set`](crate::structure_set),
+//! [`template_pool`](crate::template_pool) and
+//! [`processor_list`](crate::processor_list). Loading templates and
+//! assembling pieces needs the world, so that part lives with the world
+//! generator.
+//!
+//! Only jigsaw structures are generated. Structure sets keep every
+//! structure name, so [`jigsaw_structure`](crate::jigsaw_structure) returns
+//! `None` for the other structure types.
+
+use crate::GenerationStep;
+use crate::feature::{BlockState, HeightProvider, Heightmap, IntProvider, RuleTest};
+use crate::xoroshiro::JavaRandom;
+
+/// A jigsaw structure (`"type": "minecraft:jigsaw"`).
+#[derive(Debug, Clone, Copy)]
+pub struct JigsawStructure {
+    /// Biomes and biome tags (`#`-prefixed) the structure starts in.
+    pub biomes: &'static [&'static str],
+    /// Generation step the pieces are placed in.
+    pub step: GenerationStep,
+    /// Pool of the start piece.
+    pub start_pool: &'static str,
+    /// Jigsaw of the start piece placed at the start position, if any.
+    pub start_jigsaw_name: Option<&'static str>,
+    pub start_height: HeightProvider,
+    /// Heightmap the start height is relative to, if any.
+    pub project_start_to_heightmap: Option<Heightmap>,
+    /// Maximum jigsaw depth.
+    pub size: i32,
+    /// Maximum distance of any piece from the start, in blocks.
+    pub max_distance_from_center: i32,
+    /// Reserve room above small pieces for what their jigsaws lead to.
+    pub use_expansion_hack: bool,
+    /// Blocks kept free at the bottom and top of the world.
+    pub dimension_padding: (i32, i32),
+    pub pool_aliases: &'static [PoolAlias],
+}
+
+/// Pool alias binding, resolved once per structure start.
+#[derive(Debug, Clone, Copy)]
+pub enum PoolAlias {
+    Direct {
+        alias: &'static str,
+        target: &'static str,
+    },
+    Random {
+        alias: &'static str,
+        targets: &'static [(&'static str, i32)],
+    },
+    RandomGroup {
+        groups: &'static [(&'static [PoolAlias], i32)],
+    },
+}
+
+/// A structure set: structures sharing one placement.
+#[derive(Debug, Clone, Copy)]
+pub struct StructureSet {
+    pub structures: &'static [(&'static str, i32)],
+    pub placement: StructurePlacement,
+}
+
+/// Where a structure set's starts go.
+#[derive(Debug, Clone, Copy)]
+pub enum StructurePlacement {
+    /// One start per `spacing`-chunk square region.
+    RandomSpread {
+        salt: i32,
+        spacing: i32,
+        separation: i32,
+        spread_type: SpreadType,
+        frequency: f32,
+        frequency_reduction_method: FrequencyReductionMethod,
+        /// Another set whose starts must be at least `chunk_count` chunks away.
+        exclusion_zone: Option<(&'static str, i32)>,
+    },
+    Unsupported,
+}
+
+/// Distribution of a start inside its region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpreadType {
+    Linear,
+    Triangular,
+}
+
+impl SpreadType {
+    /// Sample an offset in `0..range` (Java's `RandomSpreadType.evaluate`).
+    pub fn evaluate(self, random: &mut JavaRandom, range: i32) -> i32 {
+        match self {
+            SpreadType::Linear => random.next_int(range as u32),
+            SpreadType::Triangular => {
+                (random.next_int(range as u32) + random.next_int(range as u32)) / 2
+            }
+        }
+    }
+}
+
+/// How a placement's `frequency` skips starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrequencyReductionMethod {
+    Default,
+    LegacyType1,
+    LegacyType2,
+    LegacyType3,
+}
+
+/// A template pool: weighted elements plus the pool used past the
+/// maximum depth.
+#[derive(Debug, Clone, Copy)]
+pub struct TemplatePool {
+    pub elements: &'static [(PoolElement, i32)],
+    pub fallback: &'static str,
+}
+
+/// A piece a pool can place.
+#[derive(Debug, Clone, Copy)]
+pub enum PoolElement {
+    /// A structure template. Legacy elements don't place air.
+    Single {
+        location: &'static str,
+        processors: &'static [Processor],
+        projection: Projection,
+        legacy: bool,
+    },
+    /// Several elements placed at the same position.
+    List {
+        elements: &'static [PoolElement],
+        projection: Projection,
+    },
+    /// A placed feature.
+    Feature {
+        feature: &'static str,
+        projection: Projection,
+    },
+    Empty,
+}
+
+impl PoolElement {
+    pub fn projection(&self) -> Projection {
+        match *self {
+            PoolElement::Single { projection, .. }
+            | PoolElement::List { projection, .. }
+            | PoolElement::Feature { projection, .. } => projection,
+            PoolElement::Empty => Projection::Rigid,
+        }
+    }
+}
+
+/// How a piece follows the terrain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    /// Placed as built.
+    Rigid,
+    /// Every column moved to the surface.
+    TerrainMatching,
+}
+
+/// A structure processor, changing or dropping template blocks.
+#[derive(Debug, Clone, Copy)]
+pub enum Processor {
+    /// Keep a block with probability `integrity`, limited to a tag if given.
+    BlockRot {
+        integrity: f32,
+        rottable_blocks: Option<&'static str>,
+    },
+    /// Replace blocks matching the first matching rule.
+    Rule(&'static [ProcessorRule]),
+    /// Don't replace blocks in a tag.
+    ProtectedBlocks(&'static str),
+    /// Apply `delegate` to at most `limit` blocks of a piece.
+    Capped {
+        delegate: &'static Processor,
+        limit: IntProvider,
+    },
+    Unsupported,
+}
+
+/// A rule of a rule processor.
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessorRule {
+    /// Test of the template block.
+    pub input_predicate: RuleTest,
+    /// Test of the block already in the world.
+    pub location_predicate: RuleTest,
+    /// Test of the block's position relative to the piece.
+    pub position_predicate: PosRuleTest,
+    pub output_state: BlockState,
+}
+
+/// Chance of a rule passing, by distance from the piece's position.
+#[derive(Debug, Clone, Copy)]
+pub enum PosRuleTest {
+    AlwaysTrue,
+    /// Manhattan distance.
+    LinearPos {
+        min_chance: f32,
+        max_chance: f32,
+        min_dist: i32,
+        max_dist: i32,
+    },
+    /// Distance along one axis (0 = x, 1 = y, 2 = z).
+    AxisAlignedLinearPos {
+        axis: usize,
+        min_chance: f32,
+        max_chance: f32,
+        min_dist: i32,
+        max_dist: i32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_structures() {
+        let village = crate::jigsaw_structure("village_plains").unwrap();
+        assert_eq!(village.start_pool, "minecraft:village/plains/town_centers");
+        assert_eq!(village.size, 6);
+        assert_eq!(village.step, GenerationStep::SurfaceStructures);
+        assert!(crate::jigsaw_structure("minecraft:igloo").is_none());
+
+        let outposts = crate::structure_set("pillager_outposts").unwrap();
+        assert!(matches!(
+            outposts.placement,
+            StructurePlacement::RandomSpread {
+                spacing: 32,
+                frequency_reduction_method: FrequencyReductionMethod::LegacyType1,
+                exclusion_zone: Some(("minecraft:villages", 10)),
+                ..
+            }
+        ));
+
+        let plates = crate::template_pool("minecraft:pillager_outpost/base_plates").unwrap();
+        assert!(matches!(
+            plates.elements[0].0,
+            PoolElement::Single { legacy: true, .. }
+        ));
+        assert!(crate::template_pool("empty").unwrap().elements.is_empty());
+
+        let rot = crate::processor_list("outpost_rot").unwrap();
+        assert!(matches!(rot[0], Processor::BlockRot { .. }));
+    }
+
+    #[test]
+    fn test_pools_resolve() {
+        // Every start pool and fallback names a generated pool
+        for name in crate::JIGSAW_STRUCTURES {
+            let structure = crate::jigsaw_structure(name).unwrap();
+            let pool = crate::template_pool(structure.start_pool).unwrap();
+            assert!(crate::template_pool(pool.fallback).is_some(), "{name}");
+        }
+    }
+}
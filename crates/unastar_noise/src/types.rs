@@ -59,6 +59,12 @@ pub trait NoiseSource {
 
     /// Sample old blended noise (SIMD version for 4 Y positions).
     fn sample_blended_noise_4(&self, x: f64, y: f64x4, z: f64, xz_scale: f64, y_scale: f64, xz_factor: f64, y_factor: f64, smear_scale_multiplier: f64) -> f64x4;
+
+    /// Sample the End island density (`end_islands`) at a block position.
+    ///
+    /// The central island fills the area around the origin; outer islands
+    /// start about 1000 blocks out.
+    fn sample_end_islands(&self, x: i32, z: i32) -> f64;
 }
 
 /// Find the Y level where density becomes positive (first solid block from top).